@@ -17,10 +17,17 @@ fn main() -> Result<()> {
     // Compile all cTrader Open API protobuf files
     prost_build::compile_protos(proto_files, &["proto/"])?;
     
+    // gRPC control interface (server and client)
+    tonic_build::configure()
+        .build_server(true)
+        .build_client(true)
+        .compile(&["proto/control.proto"], &["proto/"])?;
+    
     // Re-run build if any proto file changes
     for file in proto_files {
         println!("cargo:rerun-if-changed={}", file);
     }
+    println!("cargo:rerun-if-changed=proto/control.proto");
     
     Ok(())
 }
@@ -0,0 +1,34 @@
+//! Arbitrary bytes through the reader task's frame and payload decoding
+//!
+//! The input is treated as a stretch of the cTrader stream: frames are split
+//! off the front until one is incomplete or rejected, and each envelope goes
+//! through the same payload decoding as in the reader. Any panic is a bug.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use palm_oil_bot::modules::trading::frame::{self, FrameError, MAX_FRAME_LEN};
+
+fuzz_target!(|data: &[u8]| {
+    let mut rest = data;
+    loop {
+        match frame::split_frame(rest) {
+            Ok(Some((message, used))) => {
+                assert!(used >= 4 && used <= rest.len());
+                let _ = frame::decode_inbound(message);
+                rest = &rest[used..];
+            }
+            Ok(None) => break,
+            Err(FrameError::TooLarge(len)) => {
+                assert!(len > MAX_FRAME_LEN);
+                break;
+            }
+            Err(FrameError::Decode(_)) => break,
+        }
+    }
+
+    // The whole input as one frame body, as read after a valid prefix
+    if let Ok(message) = frame::decode_frame(data) {
+        let _ = frame::decode_inbound(message);
+    }
+});
@@ -3,6 +3,9 @@
 //! Simulates trading strategy on historical or synthetic price data.
 //! Calculates performance metrics: win rate, total P&L, max drawdown, Sharpe ratio.
 //!
+//! Every run goes through the library `BacktestEngine`, the same strategy,
+//! risk checks, TP/SL and sizing code as the live bot; only fills are simulated.
+//!
 //! ## Usage
 //! ```bash
 //! # Normal mode (1.5% volatility)
@@ -10,31 +13,75 @@
 //!
 //! # Stress mode (5% volatility with losing streaks)
 //! cargo run --bin backtest -- --stress
+//!
+//! # Historical candles (timestamp,open,high,low,close) with recorded sentiment
+//! cargo run --bin backtest -- --candles fcpo_h1.csv --sentiment-csv sentiment.csv
+//! cargo run --bin backtest -- --candles fcpo_h1.csv --sentiment-trade-log data/trade_log.csv
+//! cargo run --bin backtest -- --candles fcpo_h1.csv --sentiment-db data/positions.db
+//! ```
+//!
+//! Without a sentiment source, sentiment is simulated from RSI (neutral for
+//! ticks). With one, each candle uses the latest recorded reading (neutral
+//! when none applies). `--sentiment-db` replays the bot's `sentiment_history`
+//! table, or its market briefs when no history was recorded. Candle files are
+//! read as `--timeframe` bars (default 1h).
+//!
+//! ## Tick resolution
+//! Replay ticks downloaded with `download-ticks`: exits trigger on every tick
+//! at its own bid/ask and signals use candles of `--timeframe` (default 5m, 1h
+//! for trend following) built from the ticks.
+//! ```bash
+//! cargo run --bin backtest -- --ticks data/ticks --symbol FCPO --from 2024-03-01 --to 2024-03-08
+//! ```
+//!
+//! `--strategy vwap_reversion` runs the session-VWAP reversion strategy and
+//! `--strategy trend_following` the EMA cross + ADX trend strategy instead of
+//! RSI + sentiment, as baselines to compare against:
+//! ```bash
+//! cargo run --bin backtest -- --ticks data/ticks --from 2024-03-01 --to 2024-03-08 --strategy vwap_reversion
+//! ```
+//!
+//! ## Fill model
+//! Fills default to the candle close. Candle files with a `spread` column (or
+//! `bid` and `ask` columns) are filled at the ask on entry and the bid on exit
+//! using each candle's own spread, and stops/targets trigger on that side of
+//! the quote. `--spread` applies a fixed spread to candles without one.
+//! For conservative results:
+//! ```bash
+//! cargo run --bin backtest -- --spread 2.0 --slippage-pct 0.02 --commission 3.5
+//! # or the built-in conservative preset
+//! cargo run --bin backtest -- --conservative
+//! ```
+//!
+//! ## Baselines
+//! Save a named run and compare later runs against it; the comparison exits
+//! non-zero when P&L or drawdown regress by more than the tolerance (percent of
+//! initial balance, default 1%). Use `--seed` so synthetic runs are reproducible.
+//! ```bash
+//! cargo run --bin backtest -- --seed 42 --save-baseline main
+//! cargo run --bin backtest -- --seed 42 --compare main --tolerance 0.5
 //! ```
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::backtest::integrity::CandleFile;
+use palm_oil_bot::modules::backtest::{BacktestEngine, BacktestReport, FillModel, TickStore};
+use palm_oil_bot::modules::scraper::SentimentSeries;
 use palm_oil_bot::modules::trading::{
-    circuit_breakers::{CircuitBreakerConfig, CircuitBreakers},
-    indicators::RsiCalculator,
-    orders::OrderSide,
-    strategy::TradingStrategy,
+    strategy::{Strategy, StrategyKind, TradingStrategy},
+    Candle, OppositeSignalPolicy, PositionDatabase, RsiCalculator, StructureStopConfig, Tick,
+    TimeFrame, TrendFollowing, TrendFollowingConfig, VwapReversion, VwapReversionConfig,
 };
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use std::env;
-use tracing::{info, warn};
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
 
 const INITIAL_BALANCE: f64 = 10000.0;
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-struct Candle {
-    timestamp: DateTime<Utc>,
-    open: f64,
-    high: f64,
-    low: f64,
-    close: f64,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum BacktestMode {
     Normal,
@@ -57,94 +104,207 @@ impl BacktestMode {
     }
 }
 
-#[derive(Debug)]
-struct BacktestResult {
-    mode: BacktestMode,
+/// Pessimistic costs for a scalping strategy on FCPO
+fn conservative_fill_model() -> FillModel {
+    FillModel {
+        spread: 2.0,
+        slippage_percent: 0.02,
+        commission_per_unit: 3.5,
+    }
+}
+
+/// Fill model from `--conservative` and per-field overrides. `--commission`
+/// is per lot; the engine charges per unit of volume, and one unit stands in
+/// for one lot here.
+fn fill_model_from_args(args: &[String]) -> anyhow::Result<FillModel> {
+    let mut model = if args.iter().any(|a| a == "--conservative") {
+        conservative_fill_model()
+    } else {
+        FillModel::default()
+    };
+
+    if let Some(v) = arg_value(args, "--spread") {
+        model.spread = v.parse()?;
+    }
+    if let Some(v) = arg_value(args, "--slippage-pct") {
+        model.slippage_percent = v.parse()?;
+    }
+    if let Some(v) = arg_value(args, "--commission") {
+        model.commission_per_unit = v.parse()?;
+    }
+    anyhow::ensure!(
+        arg_value(args, "--partial-fill-prob").is_none(),
+        "--partial-fill-prob is not supported: the backtest engine fills entries in full"
+    );
+    Ok(model)
+}
+
+/// Key metrics of a saved backtest run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Baseline {
+    name: String,
+    created_at: DateTime<Utc>,
+    mode: String,
+    seed: Option<u64>,
     total_trades: u32,
-    winning_trades: u32,
-    losing_trades: u32,
+    win_rate: f64,
     total_pnl: f64,
     max_drawdown: f64,
-    win_rate: f64,
-    avg_win: f64,
-    avg_loss: f64,
     final_balance: f64,
-    circuit_breaker_triggers: CircuitBreakerTriggers,
 }
 
-#[derive(Debug, Default)]
-struct CircuitBreakerTriggers {
-    daily_loss_triggers: u32,
-    consecutive_loss_triggers: u32,
-    volatility_triggers: u32,
-    trades_blocked: u32,
-}
+impl Baseline {
+    fn from_report(
+        name: &str,
+        report: &BacktestReport,
+        mode: BacktestMode,
+        seed: Option<u64>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            created_at: Utc::now(),
+            mode: mode.name().to_string(),
+            seed,
+            total_trades: report.total_trades as u32,
+            win_rate: report.win_rate,
+            total_pnl: report.net_pnl,
+            max_drawdown: report.max_drawdown,
+            final_balance: report.final_balance,
+        }
+    }
 
-impl BacktestResult {
-    fn print_report(&self) {
-        let mode_emoji = if self.mode == BacktestMode::Stress { "🔥" } else { "📊" };
-        
-        println!("\n╔══════════════════════════════════════════════════════════╗");
-        println!("║   {} BACKTEST RESULTS - {} MODE {}                    ║", 
-            mode_emoji, self.mode.name(), mode_emoji);
-        println!("╠══════════════════════════════════════════════════════════╣");
-        println!("║ PERFORMANCE METRICS                                      ║");
-        println!("╠══════════════════════════════════════════════════════════╣");
-        println!("║ Initial Balance    : ${:.2}", INITIAL_BALANCE);
-        println!("║ Final Balance      : ${:.2}", self.final_balance);
-        println!("║ Total P&L          : ${:.2} ({:.2}%)", 
-            self.total_pnl,
-            (self.total_pnl / INITIAL_BALANCE) * 100.0
-        );
-        println!("║ Max Drawdown       : ${:.2} ({:.2}%)", 
-            self.max_drawdown,
-            (self.max_drawdown / INITIAL_BALANCE) * 100.0
-        );
-        println!("╠══════════════════════════════════════════════════════════╣");
-        println!("║ TRADE STATISTICS                                         ║");
-        println!("╠══════════════════════════════════════════════════════════╣");
-        println!("║ Total Trades       : {}", self.total_trades);
-        println!("║ Winning Trades     : {} ({:.1}%)", 
-            self.winning_trades,
-            self.win_rate
-        );
-        println!("║ Losing Trades      : {} ({:.1}%)", 
-            self.losing_trades,
-            100.0 - self.win_rate
-        );
-        println!("║ Average Win        : ${:.2}", self.avg_win);
-        println!("║ Average Loss       : ${:.2}", self.avg_loss);
-        
-        if self.avg_loss != 0.0 {
-            let profit_factor = self.avg_win / self.avg_loss.abs();
-            println!("║ Profit Factor      : {:.2}", profit_factor);
+    fn path(name: &str) -> PathBuf {
+        let dir = env::var("BACKTEST_BASELINE_DIR").unwrap_or_else(|_| "data/backtest_baselines".to_string());
+        PathBuf::from(dir).join(format!("{}.json", name))
+    }
+
+    fn save(&self) -> anyhow::Result<PathBuf> {
+        let path = Self::path(&self.name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    fn load(name: &str) -> anyhow::Result<Self> {
+        let path = Self::path(name);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Cannot read baseline {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// Print deltas against a baseline; returns true when the run regressed
+fn compare_to_baseline(
+    baseline: &Baseline,
+    report: &BacktestReport,
+    mode: BacktestMode,
+    tolerance_percent: f64,
+) -> bool {
+    let tolerance = INITIAL_BALANCE * tolerance_percent / 100.0;
+    let pnl_delta = report.net_pnl - baseline.total_pnl;
+    let drawdown_delta = report.max_drawdown - baseline.max_drawdown;
+    let trade_delta = report.total_trades as i64 - baseline.total_trades as i64;
+
+    let pnl_regressed = pnl_delta < -tolerance;
+    let drawdown_regressed = drawdown_delta > tolerance;
+
+    println!("\n📐 COMPARISON WITH BASELINE '{}' ({})", baseline.name, baseline.created_at.format("%Y-%m-%d %H:%M"));
+    if baseline.mode != mode.name() {
+        println!("   ⚠️ Baseline mode {} differs from current mode {}", baseline.mode, mode.name());
+    }
+    println!("   Tolerance      : ${:.2} ({:.2}% of initial balance)", tolerance, tolerance_percent);
+    println!(
+        "   P&L            : ${:.2} -> ${:.2} (Δ {:+.2}){}",
+        baseline.total_pnl, report.net_pnl, pnl_delta,
+        if pnl_regressed { "  ❌ REGRESSION" } else { "" }
+    );
+    println!(
+        "   Max Drawdown   : ${:.2} -> ${:.2} (Δ {:+.2}){}",
+        baseline.max_drawdown, report.max_drawdown, drawdown_delta,
+        if drawdown_regressed { "  ❌ REGRESSION" } else { "" }
+    );
+    println!(
+        "   Trades         : {} -> {} (Δ {:+})",
+        baseline.total_trades, report.total_trades, trade_delta
+    );
+    println!(
+        "   Win Rate       : {:.1}% -> {:.1}% (Δ {:+.1})",
+        baseline.win_rate, report.win_rate, report.win_rate - baseline.win_rate
+    );
+
+    pnl_regressed || drawdown_regressed
+}
+
+fn print_report(report: &BacktestReport, mode: BacktestMode) {
+    let mode_emoji = if mode == BacktestMode::Stress { "🔥" } else { "📊" };
+    let average = |total: f64, count: usize| if count > 0 { total / count as f64 } else { 0.0 };
+
+    println!("\n╔══════════════════════════════════════════════════════════╗");
+    println!("║   {} BACKTEST RESULTS - {} MODE {}                    ║",
+        mode_emoji, mode.name(), mode_emoji);
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║ PERFORMANCE METRICS                                      ║");
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║ Strategy           : {}", report.strategy);
+    if report.ticks > 0 {
+        println!("║ Data               : {} ticks, {} candles", report.ticks, report.candles);
+    } else {
+        println!("║ Data               : {} candles", report.candles);
+    }
+    println!("║ Initial Balance    : ${:.2}", report.initial_balance);
+    println!("║ Final Balance      : ${:.2}", report.final_balance);
+    println!("║ Total P&L          : ${:.2} ({:.2}%)",
+        report.net_pnl,
+        (report.net_pnl / report.initial_balance) * 100.0
+    );
+    println!("║ Max Drawdown       : ${:.2} ({:.2}%)",
+        report.max_drawdown,
+        report.max_drawdown_percent
+    );
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║ TRADE STATISTICS                                         ║");
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║ Total Trades       : {}", report.total_trades);
+    println!("║ Winning Trades     : {} ({:.1}%)",
+        report.winning_trades,
+        report.win_rate
+    );
+    println!("║ Losing Trades      : {} ({:.1}%)",
+        report.losing_trades,
+        100.0 - report.win_rate
+    );
+    println!("║ Average Win        : ${:.2}", average(report.gross_profit, report.winning_trades));
+    println!("║ Average Loss       : ${:.2}", average(report.gross_loss, report.losing_trades));
+    if let Some(profit_factor) = report.profit_factor {
+        println!("║ Profit Factor      : {:.2}", profit_factor);
+    }
 
+    if report.fill_model != FillModel::default() {
         println!("╠══════════════════════════════════════════════════════════╣");
-        println!("║ CIRCUIT BREAKER REPORT                                   ║");
+        println!("║ EXECUTION COSTS                                          ║");
         println!("╠══════════════════════════════════════════════════════════╣");
-        println!("║ Daily Loss Triggers    : {}", self.circuit_breaker_triggers.daily_loss_triggers);
-        println!("║ Consecutive Loss Triggers : {}", self.circuit_breaker_triggers.consecutive_loss_triggers);
-        println!("║ Volatility Triggers    : {}", self.circuit_breaker_triggers.volatility_triggers);
-        println!("║ Trades Blocked         : {}", self.circuit_breaker_triggers.trades_blocked);
-        
-        let total_triggers = self.circuit_breaker_triggers.daily_loss_triggers
-            + self.circuit_breaker_triggers.consecutive_loss_triggers
-            + self.circuit_breaker_triggers.volatility_triggers;
-        
-        if total_triggers > 0 {
-            println!("║ 🛡️ Circuit breakers protected capital {} time(s)", total_triggers);
-        } else {
-            println!("║ ✅ No circuit breakers triggered");
-        }
-        
-        println!("╚══════════════════════════════════════════════════════════╝\n");
+        println!("║ Spread / Slippage  : {:.2} / {:.3}%", report.fill_model.spread, report.fill_model.slippage_percent);
+        println!("║ Commission / Lot   : ${:.2}", report.fill_model.commission_per_unit);
+        println!("║ Total Commissions  : ${:.2}", report.commissions);
+        println!("║ Spread+Slippage    : ${:.2}", report.execution_costs);
+    }
+
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║ RISK CHECKS                                              ║");
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║ Signals Blocked    : {}", report.signals_blocked);
+    if report.signals_blocked > 0 {
+        println!("║ 🛡️ Circuit breakers and risk limits refused {} signal(s)", report.signals_blocked);
+    } else {
+        println!("║ ✅ No signal blocked by the risk checks");
     }
+
+    println!("╚══════════════════════════════════════════════════════════╝\n");
 }
 
-fn generate_price_data(num_candles: usize, start_price: f64, volatility: f64) -> Vec<Candle> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
+fn generate_price_data(rng: &mut ChaCha8Rng, num_candles: usize, start_price: f64, volatility: f64) -> Vec<Candle> {
     let mut candles = Vec::with_capacity(num_candles);
     let mut current_price = start_price;
     let mut timestamp = Utc::now() - chrono::Duration::hours(num_candles as i64);
@@ -152,18 +312,12 @@ fn generate_price_data(num_candles: usize, start_price: f64, volatility: f64) ->
     for _ in 0..num_candles {
         let change = rng.gen_range(-volatility..volatility);
         let new_price = current_price * (1.0 + change / 100.0);
-        
+
         let high = current_price.max(new_price) * (1.0 + rng.gen_range(0.0..0.3) / 100.0);
         let low = current_price.min(new_price) * (1.0 - rng.gen_range(0.0..0.3) / 100.0);
-        
-        candles.push(Candle {
-            timestamp,
-            open: current_price,
-            high,
-            low,
-            close: new_price,
-        });
-        
+
+        candles.push(synthetic_candle(timestamp, current_price, high, low, new_price));
+
         current_price = new_price;
         timestamp += chrono::Duration::hours(1);
     }
@@ -171,10 +325,29 @@ fn generate_price_data(num_candles: usize, start_price: f64, volatility: f64) ->
     candles
 }
 
-fn simulate_sentiment(rsi: f64, volatility_factor: f64) -> i32 {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    
+/// Hourly synthetic bar without a recorded spread
+fn synthetic_candle(
+    timestamp: DateTime<Utc>,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+) -> Candle {
+    Candle {
+        timestamp,
+        timeframe: TimeFrame::H1,
+        open,
+        high,
+        low,
+        close,
+        volume: 1,
+        avg_spread: None,
+        close_spread: None,
+    }
+}
+
+fn simulate_sentiment(rng: &mut ChaCha8Rng, rsi: f64, volatility_factor: f64) -> i32 {
+
     let base_sentiment = if rsi < 30.0 {
         rng.gen_range(20..60)
     } else if rsi > 70.0 {
@@ -182,218 +355,43 @@ fn simulate_sentiment(rsi: f64, volatility_factor: f64) -> i32 {
     } else {
         rng.gen_range(-40..40)
     };
-    
+
     let noise = rng.gen_range(-20..20);
     let sentiment = base_sentiment + (noise as f64 * volatility_factor) as i32;
-    
+
     sentiment.clamp(-100, 100)
 }
 
-fn run_backtest(candles: &[Candle], mode: BacktestMode) -> BacktestResult {
-    let config = Config::default();
-    let trading_config = config.trading.clone();
-    let mut strategy = TradingStrategy::new(
-        config.strategy,
-        trading_config.clone(),
-        INITIAL_BALANCE,
-    );
-    
-    let cb_config = CircuitBreakerConfig::default();
-    let mut circuit_breakers = CircuitBreakers::new(cb_config);
-    let mut cb_triggers = CircuitBreakerTriggers::default();
-    
-    let mut rsi_calc = RsiCalculator::new(14);
-    let mut balance = INITIAL_BALANCE;
-    let mut peak_balance = INITIAL_BALANCE;
-    let mut max_drawdown = 0.0;
-    
-    let mut total_trades = 0;
-    let mut winning_trades = 0;
-    let mut losing_trades = 0;
-    let mut total_wins = 0.0;
-    let mut total_losses = 0.0;
-    
-    let mut atr_values: Vec<f64> = Vec::new();
-    
-    let mut current_position: Option<(String, OrderSide, f64, f64)> = None;
-    
-    info!("Starting backtest in {} mode with {} candles", mode.name(), candles.len());
-    
-    for (idx, candle) in candles.iter().enumerate() {
-        let price = candle.close;
-        
-        // Calculate ATR-like value for volatility check
-        let atr = candle.high - candle.low;
-        atr_values.push(atr);
-        let avg_atr = if atr_values.len() >= 14 {
-            atr_values.iter().rev().take(14).sum::<f64>() / 14.0
-        } else {
-            atr
-        };
-        
-        // Check volatility circuit breaker
-        if circuit_breakers.check_volatility(atr, avg_atr) {
-            cb_triggers.volatility_triggers += 1;
-        }
-        
-        // Check daily loss circuit breaker
-        let daily_pnl_pct = (balance - INITIAL_BALANCE) / INITIAL_BALANCE;
-        if circuit_breakers.check_daily_loss(daily_pnl_pct) {
-            cb_triggers.daily_loss_triggers += 1;
-        }
-        
-        let rsi_opt = rsi_calc.add_price(price);
-        
-        if let Some(rsi) = rsi_opt {
-            let sentiment = simulate_sentiment(rsi, if mode == BacktestMode::Stress { 1.5 } else { 0.5 });
-            
-            if let Some((_pos_id, side, entry_price, volume)) = current_position.take() {
-                let pnl = match side {
-                    OrderSide::Buy => (price - entry_price) * volume,
-                    OrderSide::Sell => (entry_price - price) * volume,
-                };
-                
-                let pnl_percent = (pnl / entry_price) * 100.0;
-                
-                let should_close = if pnl_percent >= trading_config.take_profit_percent {
-                    info!("Take profit hit at {:.2}% on candle {}", pnl_percent, idx);
-                    true
-                } else if pnl_percent <= -trading_config.stop_loss_percent {
-                    warn!("Stop loss hit at {:.2}% on candle {}", pnl_percent, idx);
-                    true
-                } else {
-                    false
-                };
-                
-                if should_close {
-                    balance += pnl;
-                    total_trades += 1;
-                    
-                    if pnl > 0.0 {
-                        winning_trades += 1;
-                        total_wins += pnl;
-                        circuit_breakers.record_trade_result(true);
-                    } else {
-                        losing_trades += 1;
-                        total_losses += pnl.abs();
-                        circuit_breakers.record_trade_result(false);
-                        
-                        // Check if consecutive loss breaker was triggered
-                        if circuit_breakers.get_consecutive_losses() >= 3 && !circuit_breakers.is_trading_allowed() {
-                            cb_triggers.consecutive_loss_triggers += 1;
-                        }
-                    }
-                    
-                    info!(
-                        "Closed {} position: Entry={:.2}, Exit={:.2}, P&L={:.2}, Balance={:.2}",
-                        side, entry_price, price, pnl, balance
-                    );
-                    
-                    if balance > peak_balance {
-                        peak_balance = balance;
-                    }
-                    
-                    let drawdown = peak_balance - balance;
-                    if drawdown > max_drawdown {
-                        max_drawdown = drawdown;
-                    }
-                } else {
-                    current_position = Some((_pos_id, side, entry_price, volume));
-                }
-            }
-            
-            if current_position.is_none() {
-                // Check if circuit breakers allow trading
-                if !circuit_breakers.is_trading_allowed() {
-                    cb_triggers.trades_blocked += 1;
-                    continue;
-                }
-                
-                let signal = strategy.generate_signal(rsi, sentiment);
-                
-                if signal != palm_oil_bot::modules::trading::strategy::Signal::Hold {
-                    if let Ok(can_open) = strategy.can_open_position() {
-                        if can_open {
-                            let side = match signal {
-                                palm_oil_bot::modules::trading::strategy::Signal::Buy => OrderSide::Buy,
-                                palm_oil_bot::modules::trading::strategy::Signal::Sell => OrderSide::Sell,
-                                _ => continue,
-                            };
-                            
-                            let volume = 1.0;
-                            let pos_id = format!("backtest_{}", idx);
-                            
-                            info!(
-                                "Opened {} position at {:.2} (RSI={:.2}, Sentiment={})",
-                                side, price, rsi, sentiment
-                            );
-                            
-                            current_position = Some((pos_id, side, price, volume));
-                        }
-                    }
-                }
-            }
-        }
+/// Value following a `--flag` argument
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|idx| args.get(idx + 1))
+        .cloned()
+}
+
+/// Load the historical sentiment series requested on the command line, if any
+fn load_sentiment_series(args: &[String]) -> anyhow::Result<Option<SentimentSeries>> {
+    if let Some(path) = arg_value(args, "--sentiment-csv") {
+        return Ok(Some(SentimentSeries::from_csv(&path)?));
     }
-    
-    if let Some((_, side, entry_price, volume)) = current_position {
-        let final_price = candles.last().unwrap().close;
-        let final_pnl = match side {
-            OrderSide::Buy => (final_price - entry_price) * volume,
-            OrderSide::Sell => (entry_price - final_price) * volume,
-        };
-        
-        balance += final_pnl;
-        total_trades += 1;
-        
-        if final_pnl > 0.0 {
-            winning_trades += 1;
-            total_wins += final_pnl;
-        } else {
-            losing_trades += 1;
-            total_losses += final_pnl.abs();
-        }
-        
-        warn!("Position still open at end - force closed with P&L: {:.2}", final_pnl);
+    if let Some(path) = arg_value(args, "--sentiment-trade-log") {
+        return Ok(Some(SentimentSeries::from_trade_log(&path)?));
     }
-    
-    let total_pnl = balance - INITIAL_BALANCE;
-    let win_rate = if total_trades > 0 {
-        (winning_trades as f64 / total_trades as f64) * 100.0
-    } else {
-        0.0
-    };
-    
-    let avg_win = if winning_trades > 0 {
-        total_wins / winning_trades as f64
-    } else {
-        0.0
-    };
-    
-    let avg_loss = if losing_trades > 0 {
-        total_losses / losing_trades as f64
-    } else {
-        0.0
-    };
-    
-    BacktestResult {
-        mode,
-        total_trades,
-        winning_trades,
-        losing_trades,
-        total_pnl,
-        max_drawdown,
-        win_rate,
-        avg_win,
-        avg_loss,
-        final_balance: balance,
-        circuit_breaker_triggers: cb_triggers,
+    if let Some(path) = arg_value(args, "--sentiment-db") {
+        let symbol = env::var("SYMBOL").unwrap_or_else(|_| "FCPO".to_string());
+        let db = PositionDatabase::new(&path)?;
+        // Recorded readings when the bot kept a history, daily briefs otherwise
+        let history = db.get_sentiment_history(&symbol, None, None)?;
+        if !history.is_empty() {
+            return Ok(Some(SentimentSeries::from_sentiment_history(&history)));
+        }
+        return Ok(Some(SentimentSeries::from_market_briefs(&db.get_market_briefs(&symbol)?)));
     }
+    Ok(None)
 }
 
-fn generate_stress_price_data(num_candles: usize, start_price: f64) -> Vec<Candle> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
+fn generate_stress_price_data(rng: &mut ChaCha8Rng, num_candles: usize, start_price: f64) -> Vec<Candle> {
     let mut candles = Vec::with_capacity(num_candles);
     let mut current_price = start_price;
     let mut timestamp = Utc::now() - chrono::Duration::hours(num_candles as i64);
@@ -412,18 +410,12 @@ fn generate_stress_price_data(num_candles: usize, start_price: f64) -> Vec<Candl
 
         let change = volatility;
         let new_price = current_price * (1.0 + change / 100.0);
-        
+
         let high = current_price.max(new_price) * (1.0 + rng.gen_range(0.0..1.0) / 100.0);
         let low = current_price.min(new_price) * (1.0 - rng.gen_range(0.0..1.0) / 100.0);
-        
-        candles.push(Candle {
-            timestamp,
-            open: current_price,
-            high,
-            low,
-            close: new_price,
-        });
-        
+
+        candles.push(synthetic_candle(timestamp, current_price, high, low, new_price));
+
         current_price = new_price;
         timestamp += chrono::Duration::hours(1);
     }
@@ -431,7 +423,103 @@ fn generate_stress_price_data(num_candles: usize, start_price: f64) -> Vec<Candl
     candles
 }
 
-fn main() {
+fn parse_day(args: &[String], flag: &str) -> anyhow::Result<NaiveDate> {
+    let value = arg_value(args, flag)
+        .ok_or_else(|| anyhow::anyhow!("{} YYYY-MM-DD is required with --ticks", flag))?;
+    Ok(NaiveDate::parse_from_str(&value, "%Y-%m-%d")?)
+}
+
+fn parse_timeframe(value: &str) -> anyhow::Result<TimeFrame> {
+    Ok(match value.to_lowercase().as_str() {
+        "1m" | "m1" => TimeFrame::M1,
+        "5m" | "m5" => TimeFrame::M5,
+        "15m" | "m15" => TimeFrame::M15,
+        "30m" | "m30" => TimeFrame::M30,
+        "1h" | "h1" => TimeFrame::H1,
+        "4h" | "h4" => TimeFrame::H4,
+        "1d" | "d1" => TimeFrame::D1,
+        other => anyhow::bail!("Unknown timeframe {:?}", other),
+    })
+}
+
+/// What the engine replays
+enum Replay<'a> {
+    Candles(&'a [Candle]),
+    Ticks(&'a [Tick], TimeFrame),
+}
+
+/// Historical ticks for `--ticks <dir>` between `--from` and `--to`
+fn load_ticks(args: &[String], dir: &str, config: &Config) -> anyhow::Result<Vec<Tick>> {
+    let symbol = arg_value(args, "--symbol").unwrap_or_else(|| config.trading.symbol.clone());
+    let from = parse_day(args, "--from")?;
+    let to = parse_day(args, "--to")?;
+
+    let start = from.and_hms_opt(0, 0, 0).expect("midnight").and_utc();
+    let end = to.and_hms_opt(0, 0, 0).expect("midnight").and_utc() + Duration::days(1);
+    let ticks = TickStore::new(dir).load_range(&symbol, start, end)?;
+    anyhow::ensure!(!ticks.is_empty(), "No ticks for {} in {} between {} and {}", symbol, dir, from, to);
+    println!("Ticks: {} for {}\n", ticks.len(), symbol);
+    Ok(ticks)
+}
+
+/// Run the `kind` strategy over `replay`
+fn run_strategy<F>(
+    config: &Config,
+    kind: StrategyKind,
+    fill_model: FillModel,
+    replay: Replay<'_>,
+    sentiment: F,
+) -> anyhow::Result<BacktestReport>
+where
+    F: FnMut(&Candle) -> i32,
+{
+    println!("Strategy: {}\n", kind);
+    match kind {
+        StrategyKind::RsiSentiment => {
+            let strategy = TradingStrategy::new(
+                config.strategy.clone(),
+                config.trading.clone(),
+                config.trading.initial_balance,
+            );
+            run_engine(config, strategy, fill_model, replay, sentiment)
+        }
+        StrategyKind::VwapReversion => {
+            let strategy = VwapReversion::from_config(config)?;
+            run_engine(config, strategy, fill_model, replay, sentiment)
+        }
+        StrategyKind::TrendFollowing => {
+            let strategy = TrendFollowing::from_config(config)?;
+            run_engine(config, strategy, fill_model, replay, sentiment)
+        }
+    }
+}
+
+/// Run `strategy` through the engine with `config`'s structure stop and the
+/// opposite-signal policy from the environment
+fn run_engine<S: Strategy, F>(
+    config: &Config,
+    mut strategy: S,
+    fill_model: FillModel,
+    replay: Replay<'_>,
+    sentiment: F,
+) -> anyhow::Result<BacktestReport>
+where
+    F: FnMut(&Candle) -> i32,
+{
+    strategy
+        .core_mut()
+        .set_opposite_signal_policy(OppositeSignalPolicy::from_env()?);
+    if let Some(structure_stop) = config.decision.structure_stop {
+        strategy.core_mut().set_structure_stop(Some(structure_stop));
+    }
+    let engine = BacktestEngine::with_strategy(config, strategy, fill_model);
+    Ok(match replay {
+        Replay::Candles(candles) => engine.run(candles, sentiment),
+        Replay::Ticks(ticks, timeframe) => engine.run_ticks(ticks, timeframe, sentiment),
+    })
+}
+
+fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter("backtest=info,palm_oil_bot=warn")
         .init();
@@ -445,39 +533,116 @@ fn main() {
 
     println!("\n🌴 Palm Oil Trading Bot - Backtesting Engine 🌴\n");
     println!("Mode: {} (volatility: {}%)\n", mode.name(), mode.volatility());
-    
-    info!("Generating synthetic price data...");
-    let candles = match mode {
-        BacktestMode::Normal => generate_price_data(1000, 4850.0, mode.volatility()),
-        BacktestMode::Stress => generate_stress_price_data(1000, 4850.0),
+
+    let seed = arg_value(&args, "--seed").map(|v| v.parse::<u64>()).transpose()?;
+    let mut rng = match seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
     };
-    
-    info!("Running backtest simulation...");
-    let result = run_backtest(&candles, mode);
-    
-    result.print_report();
-    
-    if result.win_rate >= 50.0 && result.total_pnl > 0.0 {
+
+    let mut config = Config::default();
+    config.trading.initial_balance = INITIAL_BALANCE;
+    let kind: StrategyKind = arg_value(&args, "--strategy").unwrap_or_default().parse()?;
+    config.strategy.kind = kind;
+    match kind {
+        StrategyKind::RsiSentiment => {}
+        StrategyKind::VwapReversion => config.strategy.vwap = VwapReversionConfig::from_env()?,
+        StrategyKind::TrendFollowing => config.strategy.trend = TrendFollowingConfig::from_env()?,
+    }
+    config.decision.structure_stop = StructureStopConfig::from_env()?;
+    let ticks = arg_value(&args, "--ticks");
+    // Synthetic bars are hourly; ticks default to the strategy's timeframe
+    let timeframe = parse_timeframe(&arg_value(&args, "--timeframe").unwrap_or_else(|| {
+        match ticks {
+            Some(_) => kind.default_timeframe().into(),
+            None => "1h".into(),
+        }
+    }))?;
+
+    let sentiment_series = load_sentiment_series(&args)?;
+    let fill_model = fill_model_from_args(&args)?;
+    match (&sentiment_series, &ticks) {
+        (Some(series), _) => println!("Sentiment: {} historical readings\n", series.len()),
+        (None, Some(_)) => println!("Sentiment: neutral\n"),
+        (None, None) => println!("Sentiment: simulated from RSI\n"),
+    }
+
+    let report = if let Some(dir) = &ticks {
+        let ticks = load_ticks(&args, dir, &config)?;
+        run_strategy(&config, kind, fill_model, Replay::Ticks(&ticks, timeframe), |candle| {
+            sentiment_series.as_ref().map(|s| s.score_at(candle.end_time())).unwrap_or(0)
+        })?
+    } else {
+        let candles = match arg_value(&args, "--candles") {
+            Some(path) => {
+                info!("Loading historical candles from {}...", path);
+                let mut candles = CandleFile::load(&path, timeframe)?.candles;
+                candles.sort_by_key(|c| c.timestamp);
+                candles
+            }
+            None => {
+                info!("Generating synthetic price data...");
+                match mode {
+                    BacktestMode::Normal => generate_price_data(&mut rng, 1000, 4850.0, mode.volatility()),
+                    BacktestMode::Stress => generate_stress_price_data(&mut rng, 1000, 4850.0),
+                }
+            }
+        };
+
+        info!("Running backtest simulation...");
+        let volatility_factor = if mode == BacktestMode::Stress { 1.5 } else { 0.5 };
+        let mut rsi_calculator = RsiCalculator::new(config.strategy.rsi_period);
+        run_strategy(&config, kind, fill_model, Replay::Candles(&candles), |candle| {
+            let rsi = rsi_calculator.add_price(candle.close);
+            match (&sentiment_series, rsi) {
+                (Some(series), _) => series.score_at(candle.end_time()),
+                (None, Some(rsi)) => simulate_sentiment(&mut rng, rsi, volatility_factor),
+                (None, None) => 0,
+            }
+        })?
+    };
+
+    print_report(&report, mode);
+
+    if report.win_rate >= 50.0 && report.net_pnl > 0.0 {
         println!("✅ Strategy shows positive results!");
     } else {
         println!("⚠️  Strategy needs optimization.");
     }
-    
+
     // Summary for stress mode
     if mode == BacktestMode::Stress {
-        let total_cb = result.circuit_breaker_triggers.daily_loss_triggers
-            + result.circuit_breaker_triggers.consecutive_loss_triggers
-            + result.circuit_breaker_triggers.volatility_triggers;
-        
-        if total_cb > 0 {
+        if report.signals_blocked > 0 {
             println!("\n🛡️ STRESS TEST VALIDATION:");
-            println!("   Circuit breakers successfully triggered {} time(s)", total_cb);
-            println!("   {} trades were blocked, protecting capital", result.circuit_breaker_triggers.trades_blocked);
+            println!("   {} signals were blocked by the risk checks, protecting capital", report.signals_blocked);
             println!("   ✅ Capital protection mechanisms working correctly\n");
         } else {
             println!("\n⚠️ STRESS TEST WARNING:");
-            println!("   No circuit breakers triggered during stress test");
+            println!("   No signal blocked by the risk checks during stress test");
             println!("   Consider if thresholds need adjustment\n");
         }
     }
+
+    if let Some(name) = arg_value(&args, "--save-baseline") {
+        let path = Baseline::from_report(&name, &report, mode, seed).save()?;
+        println!("💾 Baseline '{}' saved to {}", name, path.display());
+    }
+
+    if let Some(name) = arg_value(&args, "--compare") {
+        let baseline = Baseline::load(&name)?;
+        let tolerance = arg_value(&args, "--tolerance")
+            .map(|v| v.parse::<f64>())
+            .transpose()?
+            .unwrap_or(1.0);
+        if seed.is_none() && arg_value(&args, "--candles").is_none() && ticks.is_none() {
+            println!("   ⚠️ Synthetic data without --seed: results are not reproducible");
+        }
+        if compare_to_baseline(&baseline, &report, mode, tolerance) {
+            println!("\n❌ Performance regressed beyond tolerance");
+            std::process::exit(1);
+        }
+        println!("\n✅ No regression against baseline '{}'", name);
+    }
+
+    Ok(())
 }
@@ -6,9 +6,10 @@ use chrono::{DateTime, Utc};
 use palm_oil_bot::modules::trading::{
     indicators::RsiCalculator,
     orders::{OrderSide, Position},
-    strategy::{Signal, TradingStrategy},
+    strategy::{OppositeSignalPolicy, Signal, StrategyKind, TradingStrategy},
+    TrendFollowingConfig, VwapReversionConfig,
 };
-use palm_oil_bot::config::{StrategyConfig, TradingConfig};
+use palm_oil_bot::config::{RsiExitMode, SizingPolicy, StrategyConfig, TradingConfig};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use std::fs::File;
@@ -146,6 +147,12 @@ fn run_simulation(candles: &[Candle], params: Params) -> Metrics {
         max_positions: 1,
         max_daily_loss_percent: 5.0,
         initial_balance: 10000.0,
+        trailing_activation_percent: None,
+        atr_exits: None,
+        break_even: None,
+        scale_in: None,
+        entry_cooldown_candles: 0,
+        sizing: SizingPolicy::FixedRisk,
     };
 
     let strategy_config = StrategyConfig {
@@ -154,6 +161,13 @@ fn run_simulation(candles: &[Candle], params: Params) -> Metrics {
         rsi_overbought: params.rsi_sell,
         rsi_timeframe: "5m".to_string(),
         sentiment_threshold: SENTIMENT_THRESHOLD,
+        rsi_exit: RsiExitMode::Off,
+        macd_confirmation: false,
+        higher_timeframe: None,
+        opposite_signal_policy: OppositeSignalPolicy::Ignore,
+        kind: StrategyKind::RsiSentiment,
+        vwap: VwapReversionConfig::default(),
+        trend: TrendFollowingConfig::default(),
     };
 
     let mut strategy = TradingStrategy::new(strategy_config, trading_config, INITIAL_BALANCE);
@@ -0,0 +1,117 @@
+//! Create, verify, list and restore persistence database backups.
+//!
+//! Usage:
+//!   cargo run --bin backup -- create
+//!   cargo run --bin backup -- list
+//!   cargo run --bin backup -- verify data/backups/positions-20240304T080000Z.db.gz.enc
+//!   cargo run --bin backup -- restore data/backups/positions-20240304T080000Z.db.gz.enc --output restored.db
+//!
+//! Configuration comes from `.env`: `PERSISTENCE_DB_PATH`, `BACKUP_DIR`,
+//! `BACKUP_KEEP` and `BACKUP_PASSPHRASE` (required to read encrypted backups).
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use dotenvy::dotenv;
+use palm_oil_bot::modules::storage::backup::{
+    backup_time, create_backup, list_backups, restore_backup, verify_backup,
+};
+use palm_oil_bot::modules::storage::BackupConfig;
+use palm_oil_bot::modules::trading::PositionDatabase;
+use std::env;
+use std::path::PathBuf;
+
+/// CLI arguments for the backup utility
+#[derive(Parser, Debug)]
+#[command(name = "backup")]
+#[command(about = "Manage compressed, optionally encrypted SQLite backups")]
+struct Args {
+    #[command(subcommand)]
+    command: BackupCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum BackupCommand {
+    /// Snapshot the database, verify the backup and rotate old ones
+    Create {
+        /// Database to back up (defaults to PERSISTENCE_DB_PATH)
+        #[arg(long)]
+        db: Option<PathBuf>,
+        /// Output directory (defaults to BACKUP_DIR)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Number of newest backups to keep (defaults to BACKUP_KEEP)
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+    /// List backups, oldest first
+    List {
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// Check checksum, decryption and SQLite integrity of a backup
+    Verify { file: PathBuf },
+    /// Decode a backup into a plain SQLite file
+    Restore {
+        file: PathBuf,
+        /// Destination database (must not exist)
+        #[arg(long)]
+        output: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    dotenv().ok();
+    let args = Args::parse();
+    let mut config = BackupConfig::from_env();
+
+    match args.command {
+        BackupCommand::Create { db, dir, keep } => {
+            let db_path = db.unwrap_or_else(|| {
+                env::var("PERSISTENCE_DB_PATH")
+                    .unwrap_or_else(|_| "data/positions.db".to_string())
+                    .into()
+            });
+            if !db_path.is_file() {
+                anyhow::bail!("Database {} does not exist", db_path.display());
+            }
+            if let Some(dir) = dir {
+                config.dir = dir;
+            }
+            if let Some(keep) = keep {
+                config.keep = keep;
+            }
+
+            let db = PositionDatabase::new(&db_path)?;
+            let backup = create_backup(&db, &config)?;
+            println!(
+                "Backup written to {} ({} bytes, {})",
+                backup.path.display(),
+                backup.bytes,
+                if backup.encrypted { "encrypted" } else { "unencrypted" }
+            );
+            for path in backup.rotated {
+                println!("Rotated out {}", path.display());
+            }
+        }
+        BackupCommand::List { dir } => {
+            let dir = dir.unwrap_or(config.dir);
+            for path in list_backups(&dir)? {
+                let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                let taken = backup_time(&path)
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default();
+                println!("{:<26} {:>10}  {}", taken, size, path.display());
+            }
+        }
+        BackupCommand::Verify { file } => {
+            verify_backup(&file, config.passphrase.as_deref())?;
+            println!("{}: OK", file.display());
+        }
+        BackupCommand::Restore { file, output } => {
+            restore_backup(&file, config.passphrase.as_deref(), &output)?;
+            println!("Restored {} to {}", file.display(), output.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! Drive a running bot over its gRPC control interface.
+//!
+//! Usage:
+//!   cargo run --bin botctl -- status
+//!   cargo run --bin botctl -- positions
+//!   cargo run --bin botctl -- orders
+//!   cargo run --bin botctl -- pause | resume
+//!   cargo run --bin botctl -- close <position_id>
+//!   cargo run --bin botctl -- cancel-all [--flatten]
+//!   cargo run --bin botctl -- sentiment
+//!   cargo run --bin botctl -- arm [--minutes 30] | disarm
+//!
+//! The bot must run with `GRPC_API_ENABLED=true`; the endpoint and token
+//! default to `GRPC_API_ADDR` and `GRPC_API_TOKEN` from `.env`.
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use dotenvy::dotenv;
+use palm_oil_bot::modules::monitoring::connect_control_client;
+use palm_oil_bot::modules::monitoring::grpc_api::proto::{
+    CancelAllRequest, ClosePositionRequest, ListOrdersRequest, ListPositionsRequest,
+    RefreshSentimentRequest, SetArmedRequest, SetPausedRequest, StatusRequest,
+};
+
+/// CLI arguments for the gRPC control client
+#[derive(Parser, Debug)]
+#[command(name = "botctl")]
+#[command(about = "Control a running bot over gRPC")]
+struct Args {
+    /// gRPC endpoint (default: http://$GRPC_API_ADDR)
+    #[arg(long)]
+    endpoint: Option<String>,
+
+    /// Bearer token (default: $GRPC_API_TOKEN)
+    #[arg(long)]
+    token: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show uptime, pause state, day P&L and last signal
+    Status,
+    /// List open positions
+    Positions,
+    /// List pending limit/stop orders
+    Orders,
+    /// Stop opening new positions
+    Pause,
+    /// Allow new positions again
+    Resume,
+    /// Close one position at market
+    Close { position_id: String },
+    /// Cancel all pending orders
+    CancelAll {
+        /// Also close every open position
+        #[arg(long)]
+        flatten: bool,
+    },
+    /// Force a fresh sentiment reading
+    Sentiment,
+    /// Allow live orders for a limited time
+    Arm {
+        /// Arming window in minutes (default: LIVE_ARMING_TTL_MINUTES)
+        #[arg(long)]
+        minutes: Option<i64>,
+    },
+    /// Stop sending live orders now
+    Disarm,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    let args = Args::parse();
+
+    let endpoint = args.endpoint.unwrap_or_else(|| {
+        let addr = std::env::var("GRPC_API_ADDR").unwrap_or_else(|_| "127.0.0.1:50051".to_string());
+        format!("http://{}", addr)
+    });
+    let token = args
+        .token
+        .or_else(|| std::env::var("GRPC_API_TOKEN").ok())
+        .ok_or_else(|| anyhow::anyhow!("No token: pass --token or set GRPC_API_TOKEN"))?;
+
+    let mut client = connect_control_client(&endpoint, &token).await?;
+
+    match args.command {
+        Command::Status => {
+            let status = client.get_status(StatusRequest {}).await?.into_inner();
+            println!("{}", status.summary);
+        }
+        Command::Positions => {
+            let positions = client
+                .list_positions(ListPositionsRequest {})
+                .await?
+                .into_inner()
+                .positions;
+            if positions.is_empty() {
+                println!("No open positions");
+            }
+            for p in positions {
+                println!(
+                    "{:<12} {:<4} {:>6} @ {:<10.2} P&L {:+.2}  SL {}  TP {}",
+                    p.id,
+                    p.side,
+                    p.volume,
+                    p.entry_price,
+                    p.current_pnl,
+                    p.stop_loss.map_or("-".to_string(), |v| format!("{:.2}", v)),
+                    p.take_profit
+                        .map_or("-".to_string(), |v| format!("{:.2}", v)),
+                );
+            }
+        }
+        Command::Orders => {
+            let orders = client
+                .list_orders(ListOrdersRequest {})
+                .await?
+                .into_inner()
+                .orders;
+            if orders.is_empty() {
+                println!("No pending orders");
+            }
+            for o in orders {
+                let price = o.limit_price.or(o.stop_price).unwrap_or_default();
+                println!(
+                    "{:<12} {:<4} {:>8} @ {:<10.2} placed {}",
+                    o.order_id, o.side, o.volume, price, o.placed_at
+                );
+            }
+        }
+        command @ (Command::Pause | Command::Resume) => {
+            let paused = matches!(command, Command::Pause);
+            client.set_paused(SetPausedRequest { paused }).await?;
+            println!("{}", if paused { "Paused" } else { "Resumed" });
+        }
+        Command::Close { position_id } => {
+            let closed = client
+                .close_position(ClosePositionRequest { position_id })
+                .await?
+                .into_inner();
+            println!(
+                "Closed {} {} {} @ {:.2}",
+                closed.id, closed.side, closed.volume, closed.entry_price
+            );
+        }
+        Command::CancelAll { flatten } => {
+            let report = client
+                .cancel_all(CancelAllRequest { flatten })
+                .await?
+                .into_inner();
+            println!("Cancelled orders: {:?}", report.cancelled_orders);
+            if flatten {
+                println!("Closed positions: {:?}", report.closed_positions);
+            }
+            for failure in &report.failures {
+                eprintln!("Failed: {}", failure);
+            }
+            if !report.failures.is_empty() {
+                anyhow::bail!("{} cancel/close requests failed", report.failures.len());
+            }
+        }
+        Command::Sentiment => {
+            let sentiment = client
+                .refresh_sentiment(RefreshSentimentRequest {})
+                .await?
+                .into_inner();
+            println!(
+                "Sentiment {} (confidence {:.2}, source {})",
+                sentiment.score, sentiment.confidence, sentiment.source
+            );
+        }
+        Command::Arm { minutes } => {
+            let status = client
+                .set_armed(SetArmedRequest {
+                    armed: true,
+                    ttl_minutes: minutes,
+                })
+                .await?
+                .into_inner();
+            match status.armed_until {
+                Some(until) => println!("Armed until {}", until),
+                None => println!("Arming not required; live orders are allowed"),
+            }
+        }
+        Command::Disarm => {
+            client
+                .set_armed(SetArmedRequest {
+                    armed: false,
+                    ttl_minutes: None,
+                })
+                .await?;
+            println!("Disarmed");
+        }
+    }
+
+    Ok(())
+}
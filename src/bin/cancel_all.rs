@@ -0,0 +1,61 @@
+//! Emergency stop: cancel every pending order on the account, optionally
+//! closing every open position as well.
+//!
+//! Usage:
+//!   cargo run --bin cancel-all
+//!   cargo run --bin cancel-all -- --flatten
+//!
+//! Connects with the credentials from `.env`, independently of a running bot.
+//! To go through a running bot instead, use `POST /control/cancel_all` (see
+//! `CONTROL_API_ENABLED`) or the `X` / `F` keys of the dashboard.
+
+use anyhow::Result;
+use clap::Parser;
+use dotenvy::dotenv;
+use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::security::RedactingMakeWriter;
+use palm_oil_bot::modules::trading::CTraderClient;
+use tracing::{error, info};
+
+/// CLI arguments for the cancel-all utility
+#[derive(Parser, Debug)]
+#[command(name = "cancel-all")]
+#[command(about = "Cancel all pending orders (and optionally close all positions)")]
+struct Args {
+    /// Also close every open position at market
+    #[arg(long)]
+    flatten: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_writer(RedactingMakeWriter::stdout())
+        .with_env_filter("palm_oil_bot=info,cancel_all=info")
+        .init();
+    let args = Args::parse();
+
+    let config = Config::from_env()?;
+    config.validate()?;
+
+    let client = CTraderClient::new(config.ctrader.clone());
+    client.verify_credentials()?;
+    client.connect().await?;
+    client.authenticate().await?;
+
+    let report = client.cancel_all(args.flatten).await?;
+    info!("Cancelled orders: {:?}", report.cancelled_orders);
+    if args.flatten {
+        info!("Closed positions: {:?}", report.closed_positions);
+    }
+    for failure in &report.failures {
+        error!("Failed: {}", failure);
+    }
+
+    client.disconnect().await?;
+    if !report.is_complete() {
+        anyhow::bail!("{} cancel/close requests failed", report.failures.len());
+    }
+    Ok(())
+}
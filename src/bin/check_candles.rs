@@ -0,0 +1,175 @@
+//! Validate stored candle files before backtesting them.
+//!
+//! Usage:
+//!   cargo run --bin check-candles -- fcpo_m5.csv --timeframe 5m
+//!   cargo run --bin check-candles -- fcpo_m5.csv --timeframe 5m --repair
+//!   cargo run --bin check-candles -- fcpo_m5.csv --repair --output fcpo_m5_fixed.csv
+//!
+//! Reports out-of-order rows, duplicate timestamps, gaps (weekends excepted),
+//! zero-volume bars and inconsistent OHLC, and exits non-zero when any are
+//! found. With `--repair`, rows are sorted and de-duplicated, and missing or
+//! bad bars are replaced with the broker's trendbars (bid prices) for the
+//! affected range. Without `--output`, the original file is kept as
+//! `<file>.bak` and replaced.
+
+use anyhow::Result;
+use clap::Parser;
+use dotenvy::dotenv;
+use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::backtest::integrity::{
+    check_candles, repair, repair_window, CandleFile,
+};
+use palm_oil_bot::modules::trading::{CTraderClient, TimeFrame};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// CLI arguments for the candle checker
+#[derive(Parser, Debug)]
+#[command(name = "check-candles")]
+#[command(about = "Check stored candles for gaps, duplicates and bad bars")]
+struct Args {
+    /// Candle CSV (timestamp,open,high,low,close[,volume][,spread])
+    file: PathBuf,
+
+    /// Candle timeframe (1m, 5m, 15m, 30m, 1h, 4h, 1d)
+    #[arg(long, default_value = "5m")]
+    timeframe: String,
+
+    /// Fix the file using broker trendbars
+    #[arg(long)]
+    repair: bool,
+
+    /// Symbol to fetch trendbars for (defaults to SYMBOL from the environment)
+    #[arg(long)]
+    symbol: Option<String>,
+
+    /// Write the repaired candles here instead of replacing the file
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// List every problem instead of the first few of each kind
+    #[arg(long)]
+    verbose: bool,
+}
+
+fn parse_timeframe(value: &str) -> Result<TimeFrame> {
+    Ok(match value.to_lowercase().as_str() {
+        "1m" | "m1" => TimeFrame::M1,
+        "5m" | "m5" => TimeFrame::M5,
+        "15m" | "m15" => TimeFrame::M15,
+        "30m" | "m30" => TimeFrame::M30,
+        "1h" | "h1" => TimeFrame::H1,
+        "4h" | "h4" => TimeFrame::H4,
+        "1d" | "d1" => TimeFrame::D1,
+        other => anyhow::bail!("Unknown timeframe {:?}", other),
+    })
+}
+
+fn print_problems<T: std::fmt::Debug>(kind: &str, items: &[T], verbose: bool) {
+    if items.is_empty() {
+        return;
+    }
+    let shown = if verbose {
+        items.len()
+    } else {
+        items.len().min(5)
+    };
+    println!("{} ({}):", kind, items.len());
+    for item in &items[..shown] {
+        println!("  {:?}", item);
+    }
+    if shown < items.len() {
+        println!("  ... {} more (--verbose to list all)", items.len() - shown);
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter("palm_oil_bot=info,check_candles=info")
+        .init();
+    let args = Args::parse();
+    let timeframe = parse_timeframe(&args.timeframe)?;
+
+    let mut file = CandleFile::load(&args.file, timeframe)?;
+    let report = check_candles(&file, timeframe);
+    println!("{}: {}", args.file.display(), report.summary());
+    print_problems(
+        "Out of order (row, timestamp)",
+        &report.out_of_order,
+        args.verbose,
+    );
+    print_problems("Duplicate timestamps", &report.duplicates, args.verbose);
+    print_problems("Gaps", &report.gaps, args.verbose);
+    print_problems("Zero-volume bars", &report.zero_volume, args.verbose);
+    print_problems("Inconsistent OHLC", &report.invalid_ohlc, args.verbose);
+    if !file.has_volume {
+        warn!("No volume column: zero-volume check skipped");
+    }
+
+    if report.is_clean() {
+        println!("✅ No problems found");
+        return Ok(());
+    }
+    if !args.repair {
+        anyhow::bail!("Candle data has problems; rerun with --repair to fix them");
+    }
+
+    let reference = match repair_window(&report, timeframe) {
+        Some((from, to)) => {
+            let config = Config::from_env()?;
+            config.validate()?;
+            let symbol = args
+                .symbol
+                .clone()
+                .unwrap_or_else(|| config.trading.symbol.clone());
+
+            let client = CTraderClient::new(config.ctrader.clone());
+            client.verify_credentials()?;
+            client.connect().await?;
+            client.authenticate().await?;
+            let symbol_id = client.get_symbol_id(&symbol).await?;
+            info!(
+                "Fetching {} {} trendbars from {} to {}",
+                symbol, timeframe, from, to
+            );
+            let bars = client
+                .get_trendbars_between(
+                    symbol_id,
+                    timeframe,
+                    from.timestamp_millis(),
+                    to.timestamp_millis(),
+                )
+                .await?;
+            client.disconnect().await?;
+            bars
+        }
+        // Only ordering/duplicate problems: no broker data needed
+        None => Vec::new(),
+    };
+
+    let summary = repair(&mut file, &reference);
+    println!(
+        "Repaired: {} duplicates removed, {} bars filled, {} bars replaced",
+        summary.duplicates_removed, summary.bars_filled, summary.bars_replaced
+    );
+
+    let output = match &args.output {
+        Some(path) => path.clone(),
+        None => {
+            let backup = args.file.with_extension("csv.bak");
+            std::fs::copy(&args.file, &backup)?;
+            println!("Original kept as {}", backup.display());
+            args.file.clone()
+        }
+    };
+    file.save(&output)?;
+
+    let remaining = check_candles(&file, timeframe);
+    println!("{}: {}", output.display(), remaining.summary());
+    if !remaining.is_clean() {
+        anyhow::bail!("Remaining problems are missing from broker data too (session breaks?)");
+    }
+    Ok(())
+}
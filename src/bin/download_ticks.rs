@@ -0,0 +1,115 @@
+//! Download historical cTrader ticks into the local tick store for
+//! tick-resolution backtests.
+//!
+//! Usage:
+//!   cargo run --bin download-ticks -- --from 2024-03-01 --to 2024-03-08
+//!   cargo run --bin download-ticks -- --symbol FCPO --from 2024-03-01 --dir data/ticks --force
+//!
+//! Bid and ask ticks are fetched day by day (UTC) and merged into one quote
+//! file per day under `<dir>/<SYMBOL>/`. Days already in the store are skipped
+//! unless `--force` is given, so an interrupted download can be resumed. The
+//! result is replayed with `cargo run --bin backtest -- --ticks <dir> ...`.
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use clap::Parser;
+use dotenvy::dotenv;
+use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::backtest::{merge_quotes, TickStore};
+use palm_oil_bot::modules::security::RedactingMakeWriter;
+use palm_oil_bot::modules::trading::protobuf::ProtoOaQuoteType;
+use palm_oil_bot::modules::trading::CTraderClient;
+use tracing::{info, warn};
+
+/// CLI arguments for the tick downloader
+#[derive(Parser, Debug)]
+#[command(name = "download-ticks")]
+#[command(about = "Download historical bid/ask ticks from cTrader into a local store")]
+struct Args {
+    /// Symbol name (defaults to SYMBOL from the environment)
+    #[arg(long)]
+    symbol: Option<String>,
+
+    /// First day to download (YYYY-MM-DD, UTC)
+    #[arg(long)]
+    from: NaiveDate,
+
+    /// Last day to download, inclusive (defaults to yesterday)
+    #[arg(long)]
+    to: Option<NaiveDate>,
+
+    /// Tick store directory
+    #[arg(long, default_value = "data/ticks")]
+    dir: String,
+
+    /// Download days that are already in the store again
+    #[arg(long)]
+    force: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_writer(RedactingMakeWriter::stdout())
+        .with_env_filter("palm_oil_bot=info,download_ticks=info")
+        .init();
+    let args = Args::parse();
+
+    let config = Config::from_env()?;
+    config.validate()?;
+    let symbol = args.symbol.unwrap_or_else(|| config.trading.symbol.clone());
+    let today = Utc::now().date_naive();
+    let last_day = args.to.unwrap_or(today - Duration::days(1)).min(today);
+    anyhow::ensure!(
+        args.from <= last_day,
+        "--from is after the last day to download"
+    );
+
+    let client = CTraderClient::new(config.ctrader.clone());
+    client.verify_credentials()?;
+    client.connect().await?;
+    client.authenticate().await?;
+    let symbol_id = client.get_symbol_id(&symbol).await?;
+
+    let store = TickStore::new(&args.dir);
+    let mut day = args.from;
+    while day <= last_day {
+        if store.has_day(&symbol, day) && !args.force {
+            info!("{} {}: already downloaded", symbol, day);
+            day += Duration::days(1);
+            continue;
+        }
+
+        let from_ms = day
+            .and_hms_opt(0, 0, 0)
+            .context("invalid day")?
+            .and_utc()
+            .timestamp_millis();
+        let to_ms =
+            (from_ms + Duration::days(1).num_milliseconds() - 1).min(Utc::now().timestamp_millis());
+        let bids = client
+            .get_tick_data(symbol_id, ProtoOaQuoteType::Bid, from_ms, to_ms)
+            .await?;
+        let asks = client
+            .get_tick_data(symbol_id, ProtoOaQuoteType::Ask, from_ms, to_ms)
+            .await?;
+        let quotes = merge_quotes(&bids, &asks);
+        if quotes.is_empty() {
+            warn!("{} {}: no ticks (market closed?)", symbol, day);
+        }
+
+        let path = store.write_day(&symbol, day, &quotes)?;
+        info!(
+            "{} {}: {} quotes -> {}",
+            symbol,
+            day,
+            quotes.len(),
+            path.display()
+        );
+        day += Duration::days(1);
+    }
+
+    client.disconnect().await?;
+    Ok(())
+}
@@ -1,9 +1,13 @@
-//! Export closed trades and daily stats from SQLite persistence.
+//! Export closed trades, daily stats and sentiment history from SQLite persistence.
 //!
 //! Usage:
 //!   cargo run --bin export-trades -- --format csv --output closed_trades.csv
 //!   cargo run --bin export-trades -- --format json --output closed_trades.json
 //!   cargo run --bin export-trades -- --daily-stats --output daily_stats.csv
+//!   cargo run --bin export-trades -- --by-version
+//!   cargo run --bin export-trades -- --sentiment --symbol FCPO --output sentiment.csv
+//!
+//! The sentiment CSV can be fed back to `backtest --sentiment-csv`.
 
 use palm_oil_bot::modules::trading::PositionDatabase;
 use std::env;
@@ -15,6 +19,9 @@ fn main() -> anyhow::Result<()> {
     let mut format = "csv".to_string();
     let mut output = None;
     let mut daily_stats = false;
+    let mut by_version = false;
+    let mut sentiment = false;
+    let mut symbol = env::var("SYMBOL").unwrap_or_else(|_| "FCPO".to_string());
     let mut db_path = env::var("PERSISTENCE_DB_PATH").unwrap_or_else(|_| "data/positions.db".to_string());
 
     let mut idx = 1;
@@ -35,6 +42,18 @@ fn main() -> anyhow::Result<()> {
             "--daily-stats" => {
                 daily_stats = true;
             }
+            "--by-version" => {
+                by_version = true;
+            }
+            "--sentiment" => {
+                sentiment = true;
+            }
+            "--symbol" => {
+                if let Some(val) = args.get(idx + 1) {
+                    symbol = val.clone();
+                    idx += 1;
+                }
+            }
             "--db" => {
                 if let Some(val) = args.get(idx + 1) {
                     db_path = val.clone();
@@ -49,6 +68,8 @@ fn main() -> anyhow::Result<()> {
     let output_path = output.unwrap_or_else(|| {
         if daily_stats {
             "daily_stats.csv".to_string()
+        } else if sentiment {
+            format!("sentiment_history.{}", if format == "json" { "json" } else { "csv" })
         } else if format == "json" {
             "closed_trades.json".to_string()
         } else {
@@ -57,6 +78,26 @@ fn main() -> anyhow::Result<()> {
     });
 
     let db = PositionDatabase::new(&db_path)?;
+
+    if by_version {
+        println!(
+            "{:<24} {:>6} {:>7} {:>12}  {:<25} {:<25}",
+            "strategy_version", "trades", "win%", "pnl", "first_close", "last_close"
+        );
+        for stats in db.get_strategy_version_stats()? {
+            println!(
+                "{:<24} {:>6} {:>6.1}% {:>12.2}  {:<25} {:<25}",
+                stats.strategy_version.as_deref().unwrap_or("(unversioned)"),
+                stats.total_trades,
+                stats.win_rate(),
+                stats.total_pnl,
+                stats.first_closed_at,
+                stats.last_closed_at,
+            );
+        }
+        return Ok(());
+    }
+
     let path = PathBuf::from(output_path);
 
     if daily_stats {
@@ -65,6 +106,17 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    if sentiment {
+        if format == "json" {
+            let records = db.get_sentiment_history(&symbol, None, None)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&records)?)?;
+        } else {
+            db.export_sentiment_history_csv(&symbol, &path)?;
+        }
+        println!("Exported {} sentiment history to {}", symbol, path.display());
+        return Ok(());
+    }
+
     if format == "json" {
         db.export_closed_trades_json(&path)?;
         println!("Exported closed trades JSON to {}", path.display());
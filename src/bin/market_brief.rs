@@ -0,0 +1,74 @@
+//! Generate (or show) the long-form market brief on demand.
+//!
+//! Queries Perplexity for a structured brief (supply/demand, MPOB expectations,
+//! technical levels), stores it in SQLite and pushes it to Telegram when
+//! `TELEGRAM_BOT_TOKEN` / `TELEGRAM_CHAT_ID` are set. Telegram is the only
+//! delivery channel; there is no email delivery.
+//!
+//! Usage:
+//!   cargo run --bin market-brief
+//!   cargo run --bin market-brief -- --no-send
+//!   cargo run --bin market-brief -- --latest
+
+use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::notifications::TelegramNotifier;
+use palm_oil_bot::modules::scraper::PerplexityClient;
+use palm_oil_bot::modules::security::{ApiRateLimiter, RedactingMakeWriter};
+use palm_oil_bot::modules::trading::PositionDatabase;
+use std::env;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(RedactingMakeWriter::stdout())
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("palm_oil_bot=info".parse()?)
+                .add_directive("reqwest=warn".parse()?),
+        )
+        .init();
+
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+    let latest_only = args.iter().any(|a| a == "--latest");
+    let no_send = args.iter().any(|a| a == "--no-send");
+    let db_path =
+        env::var("PERSISTENCE_DB_PATH").unwrap_or_else(|_| "data/positions.db".to_string());
+
+    let config = Config::from_env()?;
+    let symbol = config.trading.symbol.clone();
+    let db = PositionDatabase::new(&db_path)?;
+
+    let brief = if latest_only {
+        match db.get_latest_market_brief(&symbol)? {
+            Some(brief) => brief,
+            None => {
+                println!("No stored market brief for {}", symbol);
+                return Ok(());
+            }
+        }
+    } else {
+        let rate_limiter = Arc::new(ApiRateLimiter::for_perplexity());
+        let client = PerplexityClient::with_symbol(config.perplexity.clone(), rate_limiter, &symbol);
+        let brief = client.get_market_brief().await?;
+        db.save_market_brief(&brief)?;
+        brief
+    };
+
+    let message = brief.to_message();
+    println!("{}", message);
+
+    if !no_send {
+        match TelegramNotifier::from_env() {
+            Some(notifier) => {
+                notifier.send_message(&message).await?;
+                println!("Brief sent to Telegram");
+            }
+            None => println!("Telegram not configured; brief not sent"),
+        }
+    }
+
+    Ok(())
+}
@@ -2,12 +2,34 @@
 //!
 //! Performs grid search on strategy parameters to find optimal configuration.
 //! Tests 192 combinations (4×4×4×3) and ranks by profit factor.
+//!
+//! The most recent `--holdout` percent of candles (default 30%) is never shown
+//! to the optimizer. The top parameter sets are re-run on that holdout and
+//! reported side by side with their in-sample metrics; sets whose
+//! out-of-sample results collapse (P&L flips negative or profit factor drops
+//! below `--collapse-ratio` of in-sample) are flagged as likely overfit.
+//!
+//! With `--genetic`, runs a genetic algorithm over the continuous parameter
+//! space instead, using the backtest as the objective:
+//!
+//! ```bash
+//! cargo run --bin optimize -- --genetic --generations 40 --population 30 \
+//!     --max-drawdown 15 --min-trades 10 --state data/optimizer_state.json
+//! ```
+//!
+//! Candidates breaching the drawdown / trade-count constraints are ranked below
+//! every feasible one. State is written after each generation, so an
+//! interrupted run picks up where it left off (`--fresh` starts over).
 
 use chrono::{DateTime, Utc};
 use palm_oil_bot::modules::trading::{indicators::RsiCalculator, orders::OrderSide};
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 const INITIAL_BALANCE: f64 = 10000.0;
 const NUM_CANDLES: usize = 1000;
@@ -28,7 +50,7 @@ struct Candle {
     close: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct StrategyParams {
     rsi_oversold: f64,
     rsi_overbought: f64,
@@ -61,7 +83,6 @@ struct BacktestResult {
 }
 
 fn generate_price_data(rng: &mut ChaCha8Rng, num_candles: usize, start_price: f64, volatility: f64) -> Vec<Candle> {
-    use rand::Rng;
     let mut candles = Vec::with_capacity(num_candles);
     let mut current_price = start_price;
     let mut timestamp = Utc::now() - chrono::Duration::hours(num_candles as i64);
@@ -89,8 +110,6 @@ fn generate_price_data(rng: &mut ChaCha8Rng, num_candles: usize, start_price: f6
 }
 
 fn simulate_sentiment(rng: &mut ChaCha8Rng, rsi: f64) -> i32 {
-    use rand::Rng;
-
     let base_sentiment = if rsi < 30.0 {
         rng.gen_range(20..60)
     } else if rsi > 70.0 {
@@ -231,7 +250,414 @@ fn run_backtest(candles: &[Candle], params: &StrategyParams, seed: u64) -> Backt
     }
 }
 
-fn main() {
+// ---------------------------------------------------------------------------
+// Out-of-sample validation
+// ---------------------------------------------------------------------------
+
+/// Share of the most recent candles held out from optimization by default
+const DEFAULT_HOLDOUT_PERCENT: f64 = 30.0;
+/// OOS profit factor below this fraction of the IS one counts as a collapse
+const DEFAULT_COLLAPSE_RATIO: f64 = 0.5;
+
+/// Chronological in-sample / out-of-sample split
+#[derive(Debug, Clone, Copy)]
+struct Holdout {
+    percent: f64,
+    collapse_ratio: f64,
+}
+
+impl Holdout {
+    fn from_args(args: &[String]) -> anyhow::Result<Self> {
+        Ok(Self {
+            percent: parse_arg(args, "--holdout", DEFAULT_HOLDOUT_PERCENT)?.clamp(0.0, 90.0),
+            collapse_ratio: parse_arg(args, "--collapse-ratio", DEFAULT_COLLAPSE_RATIO)?,
+        })
+    }
+
+    /// Split candles into (in-sample, out-of-sample); the holdout is always the
+    /// most recent data so the optimizer never sees it
+    fn split<'a>(&self, candles: &'a [Candle]) -> (&'a [Candle], &'a [Candle]) {
+        let oos_len = (candles.len() as f64 * self.percent / 100.0).round() as usize;
+        candles.split_at(candles.len() - oos_len.min(candles.len()))
+    }
+
+    /// Whether OOS performance falls apart relative to IS
+    fn collapsed(&self, in_sample: &BacktestResult, out_of_sample: &BacktestResult) -> bool {
+        if out_of_sample.total_trades == 0 {
+            return in_sample.total_trades > 0;
+        }
+        let is_pf = in_sample.profit_factor.min(MAX_FITNESS_PF);
+        let oos_pf = out_of_sample.profit_factor.min(MAX_FITNESS_PF);
+        let pnl_flipped = in_sample.pnl_percent > 0.0 && out_of_sample.pnl_percent < 0.0;
+        pnl_flipped || oos_pf < is_pf * self.collapse_ratio
+    }
+}
+
+/// Side-by-side IS/OOS metrics for one parameter set
+struct Validation {
+    in_sample: BacktestResult,
+    out_of_sample: BacktestResult,
+    collapsed: bool,
+}
+
+fn validate(holdout: &Holdout, in_sample: &BacktestResult, oos_candles: &[Candle], seed: u64) -> Validation {
+    let out_of_sample = run_backtest(oos_candles, &in_sample.params, seed);
+    Validation {
+        collapsed: holdout.collapsed(in_sample, &out_of_sample),
+        in_sample: in_sample.clone(),
+        out_of_sample,
+    }
+}
+
+fn format_pf(pf: f64) -> String {
+    if pf.is_infinite() {
+        "  INF".to_string()
+    } else {
+        format!("{:5.2}", pf)
+    }
+}
+
+fn print_validation_table(holdout: &Holdout, validations: &[Validation]) {
+    println!("\n╔═══════════════════════════════════════════════════════════════════════════════════╗");
+    println!("║              IN-SAMPLE vs OUT-OF-SAMPLE (last {:>4.1}% held out)                      ║", holdout.percent);
+    println!("╠═══════════════════════════════════════════════════════════════════════════════════╣");
+    println!("║ Parameters                   │ IS PF  IS P&L  Trd │ OOS PF OOS P&L Trd │ Status  ║");
+    println!("╠═══════════════════════════════════════════════════════════════════════════════════╣");
+    for v in validations {
+        println!(
+            "║ {:28} │ {} {:+6.1}% {:3} │ {} {:+6.1}% {:3} │ {:7} ║",
+            v.in_sample.params.to_string(),
+            format_pf(v.in_sample.profit_factor),
+            v.in_sample.pnl_percent,
+            v.in_sample.total_trades,
+            format_pf(v.out_of_sample.profit_factor),
+            v.out_of_sample.pnl_percent,
+            v.out_of_sample.total_trades,
+            if v.collapsed { "⚠️ OOS" } else { "ok" },
+        );
+    }
+    println!("╚═══════════════════════════════════════════════════════════════════════════════════╝");
+
+    let collapsed = validations.iter().filter(|v| v.collapsed).count();
+    if collapsed > 0 {
+        println!(
+            "⚠️  {} of {} parameter sets collapse out-of-sample (likely overfit).",
+            collapsed,
+            validations.len()
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Genetic optimizer
+// ---------------------------------------------------------------------------
+
+/// Search bounds per parameter (min, max). RSI ranges don't overlap so every
+/// candidate keeps oversold < overbought.
+const RSI_OVERSOLD_BOUNDS: (f64, f64) = (15.0, 45.0);
+const RSI_OVERBOUGHT_BOUNDS: (f64, f64) = (55.0, 85.0);
+const TAKE_PROFIT_BOUNDS: (f64, f64) = (0.5, 5.0);
+const STOP_LOSS_BOUNDS: (f64, f64) = (0.5, 3.0);
+
+/// Profit factor cap so a lucky run with no losers doesn't dominate
+const MAX_FITNESS_PF: f64 = 10.0;
+const DEFAULT_STATE_PATH: &str = "data/optimizer_state.json";
+
+/// Hard limits a parameter set must satisfy to be considered
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Constraints {
+    max_drawdown_percent: f64,
+    min_trades: u32,
+}
+
+impl Constraints {
+    /// Penalty for breaching the constraints (0.0 when feasible)
+    fn violation(&self, result: &BacktestResult) -> f64 {
+        let drawdown_excess = (result.max_drawdown_percent - self.max_drawdown_percent).max(0.0);
+        let missing_trades = self.min_trades.saturating_sub(result.total_trades) as f64;
+        drawdown_excess + missing_trades
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GeneticConfig {
+    population: usize,
+    generations: usize,
+    mutation_rate: f64,
+    elite: usize,
+    constraints: Constraints,
+    seed: u64,
+}
+
+impl GeneticConfig {
+    fn from_args(args: &[String]) -> anyhow::Result<Self> {
+        let population = parse_arg(args, "--population", 24usize)?.max(4);
+        Ok(Self {
+            population,
+            generations: parse_arg(args, "--generations", 30usize)?,
+            mutation_rate: parse_arg(args, "--mutation-rate", 0.2f64)?.clamp(0.0, 1.0),
+            elite: (population / 6).max(1),
+            constraints: Constraints {
+                max_drawdown_percent: parse_arg(args, "--max-drawdown", 15.0f64)?,
+                min_trades: parse_arg(args, "--min-trades", 10u32)?,
+            },
+            seed: parse_arg(args, "--seed", SEED)?,
+        })
+    }
+}
+
+/// Resumable optimizer state, persisted after every generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OptimizerState {
+    seed: u64,
+    constraints: Constraints,
+    /// Holdout the population was evolved against
+    #[serde(default)]
+    holdout_percent: f64,
+    /// Number of generations already evaluated
+    generation: usize,
+    /// Population to evaluate next
+    population: Vec<StrategyParams>,
+    /// Best feasible-or-not candidate seen so far
+    best: Option<ScoredParams>,
+    /// Best fitness per completed generation
+    history: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScoredParams {
+    params: StrategyParams,
+    fitness: f64,
+}
+
+impl OptimizerState {
+    fn new(config: &GeneticConfig, holdout: &Holdout) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+        Self {
+            seed: config.seed,
+            constraints: config.constraints,
+            holdout_percent: holdout.percent,
+            generation: 0,
+            population: (0..config.population).map(|_| random_params(&mut rng)).collect(),
+            best: None,
+            history: Vec::new(),
+        }
+    }
+
+    fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename so an interrupted save never corrupts the state
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> anyhow::Result<T> {
+    match args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)) {
+        Some(raw) => raw
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid value for {}: {}", flag, raw)),
+        None => Ok(default),
+    }
+}
+
+fn random_params(rng: &mut ChaCha8Rng) -> StrategyParams {
+    StrategyParams {
+        rsi_oversold: rng.gen_range(RSI_OVERSOLD_BOUNDS.0..=RSI_OVERSOLD_BOUNDS.1),
+        rsi_overbought: rng.gen_range(RSI_OVERBOUGHT_BOUNDS.0..=RSI_OVERBOUGHT_BOUNDS.1),
+        take_profit: rng.gen_range(TAKE_PROFIT_BOUNDS.0..=TAKE_PROFIT_BOUNDS.1),
+        stop_loss: rng.gen_range(STOP_LOSS_BOUNDS.0..=STOP_LOSS_BOUNDS.1),
+    }
+}
+
+/// Objective: capped profit factor for feasible candidates, negative penalty otherwise
+fn fitness(result: &BacktestResult, constraints: &Constraints) -> f64 {
+    let violation = constraints.violation(result);
+    if violation > 0.0 {
+        return -violation;
+    }
+    if result.profit_factor.is_finite() {
+        result.profit_factor.min(MAX_FITNESS_PF)
+    } else {
+        MAX_FITNESS_PF
+    }
+}
+
+fn tournament<'a>(rng: &mut ChaCha8Rng, scored: &'a [ScoredParams]) -> &'a StrategyParams {
+    let mut winner = &scored[rng.gen_range(0..scored.len())];
+    for _ in 0..2 {
+        let challenger = &scored[rng.gen_range(0..scored.len())];
+        if challenger.fitness > winner.fitness {
+            winner = challenger;
+        }
+    }
+    &winner.params
+}
+
+/// Blend crossover: each gene is a random mix of the two parents
+fn crossover(rng: &mut ChaCha8Rng, a: &StrategyParams, b: &StrategyParams) -> StrategyParams {
+    let mut mix = |x: f64, y: f64| {
+        let t: f64 = rng.gen_range(0.0..=1.0);
+        x + (y - x) * t
+    };
+    StrategyParams {
+        rsi_oversold: mix(a.rsi_oversold, b.rsi_oversold),
+        rsi_overbought: mix(a.rsi_overbought, b.rsi_overbought),
+        take_profit: mix(a.take_profit, b.take_profit),
+        stop_loss: mix(a.stop_loss, b.stop_loss),
+    }
+}
+
+/// Perturb each gene with probability `rate` by up to 10% of its range
+fn mutate(rng: &mut ChaCha8Rng, params: &mut StrategyParams, rate: f64) {
+    let mut perturb = |value: &mut f64, (min, max): (f64, f64)| {
+        if rng.gen_bool(rate) {
+            let step = (max - min) * 0.1;
+            *value = (*value + rng.gen_range(-step..=step)).clamp(min, max);
+        }
+    };
+    perturb(&mut params.rsi_oversold, RSI_OVERSOLD_BOUNDS);
+    perturb(&mut params.rsi_overbought, RSI_OVERBOUGHT_BOUNDS);
+    perturb(&mut params.take_profit, TAKE_PROFIT_BOUNDS);
+    perturb(&mut params.stop_loss, STOP_LOSS_BOUNDS);
+}
+
+/// Build the next population from the scored one (sorted best first)
+fn next_generation(rng: &mut ChaCha8Rng, scored: &[ScoredParams], config: &GeneticConfig) -> Vec<StrategyParams> {
+    let mut next: Vec<StrategyParams> = scored.iter().take(config.elite).map(|s| s.params.clone()).collect();
+    while next.len() < config.population {
+        let a = tournament(rng, scored);
+        let b = tournament(rng, scored);
+        let mut child = crossover(rng, a, b);
+        mutate(rng, &mut child, config.mutation_rate);
+        next.push(child);
+    }
+    next
+}
+
+fn run_genetic(args: &[String]) -> anyhow::Result<()> {
+    let config = GeneticConfig::from_args(args)?;
+    let holdout = Holdout::from_args(args)?;
+    let state_path = parse_arg(args, "--state", PathBuf::from(DEFAULT_STATE_PATH))?;
+    let fresh = args.iter().any(|a| a == "--fresh");
+
+    let mut state = match OptimizerState::load(&state_path)? {
+        Some(saved) if !fresh => {
+            if saved.seed != config.seed
+                || saved.constraints != config.constraints
+                || saved.holdout_percent != holdout.percent
+            {
+                anyhow::bail!(
+                    "State in {} was created with different seed/constraints/holdout; pass --fresh to start over",
+                    state_path.display()
+                );
+            }
+            println!("Resuming from {} (generation {})", state_path.display(), saved.generation);
+            saved
+        }
+        _ => OptimizerState::new(&config, &holdout),
+    };
+
+    println!("\n🧬 Genetic optimization");
+    println!(
+        "   Population {} | Generations {} | Max DD {:.1}% | Min trades {}",
+        config.population, config.generations, config.constraints.max_drawdown_percent, config.constraints.min_trades
+    );
+
+    let mut data_rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let all_candles = generate_price_data(&mut data_rng, NUM_CANDLES, START_PRICE, VOLATILITY);
+    let (candles, oos_candles) = holdout.split(&all_candles);
+    println!(
+        "   In-sample {} candles | Out-of-sample {} candles",
+        candles.len(),
+        oos_candles.len()
+    );
+
+    while state.generation < config.generations {
+        let mut scored: Vec<ScoredParams> = state
+            .population
+            .iter()
+            .map(|params| {
+                let result = run_backtest(candles, params, config.seed);
+                ScoredParams {
+                    params: params.clone(),
+                    fitness: fitness(&result, &config.constraints),
+                }
+            })
+            .collect();
+        scored.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap_or(Ordering::Equal));
+
+        let leader = scored[0].clone();
+        if state.best.as_ref().is_none_or(|best| leader.fitness > best.fitness) {
+            state.best = Some(leader.clone());
+        }
+        state.history.push(leader.fitness);
+
+        println!(
+            "   Gen {:3}: best fitness {:6.2} ({})",
+            state.generation + 1,
+            leader.fitness,
+            leader.params
+        );
+
+        // Seed per generation so a resumed run evolves exactly like an uninterrupted one
+        let mut rng = ChaCha8Rng::seed_from_u64(config.seed.wrapping_add(state.generation as u64 + 1));
+        state.population = next_generation(&mut rng, &scored, &config);
+        state.generation += 1;
+        state.save(&state_path)?;
+    }
+
+    let Some(best) = state.best else {
+        println!("No generations evaluated.");
+        return Ok(());
+    };
+
+    let result = run_backtest(candles, &best.params, config.seed);
+    let feasible = config.constraints.violation(&result) == 0.0;
+
+    println!("\nBest parameters: {}", best.params);
+    println!("   Profit Factor : {}", format_pf(result.profit_factor).trim());
+    println!("   Total P&L     : {:+.2}%", result.pnl_percent);
+    println!("   Win Rate      : {:.1}%", result.win_rate);
+    println!("   Max Drawdown  : {:.2}%", result.max_drawdown_percent);
+    println!("   Total Trades  : {}", result.total_trades);
+    println!("   State saved   : {}", state_path.display());
+
+    let validation = (!oos_candles.is_empty()).then(|| validate(&holdout, &result, oos_candles, config.seed));
+    if let Some(validation) = &validation {
+        print_validation_table(&holdout, std::slice::from_ref(validation));
+    }
+
+    if !feasible {
+        println!("\n❌ No candidate satisfied the constraints. Loosen them or expand the search.");
+    } else if validation.is_some_and(|v| v.collapsed) {
+        println!("\n⚠️  Best candidate meets the constraints in-sample but collapses out-of-sample.");
+    } else {
+        println!("\n✅ Best candidate satisfies all constraints.");
+    }
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--genetic") {
+        return run_genetic(&args);
+    }
+    let holdout = Holdout::from_args(&args)?;
+
     println!("\n╔══════════════════════════════════════════════════════════════════╗");
     println!("║     🌴 PALM OIL BOT - PARAMETER OPTIMIZATION 🌴                  ║");
     println!("╠══════════════════════════════════════════════════════════════════╣");
@@ -256,8 +682,10 @@ fn main() {
     println!("║ Generating synthetic price data ({} candles)...                ║", NUM_CANDLES);
 
     let mut rng = ChaCha8Rng::seed_from_u64(SEED);
-    let candles = generate_price_data(&mut rng, NUM_CANDLES, START_PRICE, VOLATILITY);
+    let all_candles = generate_price_data(&mut rng, NUM_CANDLES, START_PRICE, VOLATILITY);
+    let (candles, oos_candles) = holdout.split(&all_candles);
 
+    println!("║ Holding out last {:.0}% ({} candles) for out-of-sample check      ║", holdout.percent, oos_candles.len());
     println!("║ Running {} backtests...                                        ║", total_combinations);
     println!("╚══════════════════════════════════════════════════════════════════╝\n");
 
@@ -275,7 +703,7 @@ fn main() {
                         stop_loss,
                     };
 
-                    let result = run_backtest(&candles, &params, SEED);
+                    let result = run_backtest(candles, &params, SEED);
                     results.push(result);
 
                     count += 1;
@@ -322,6 +750,15 @@ fn main() {
 
     println!("╚══════════════════════════════════════════════════════════════════╝");
 
+    let validations: Vec<Validation> = if oos_candles.is_empty() {
+        Vec::new()
+    } else {
+        results.iter().take(10).map(|r| validate(&holdout, r, oos_candles, SEED)).collect()
+    };
+    if !validations.is_empty() {
+        print_validation_table(&holdout, &validations);
+    }
+
     let above_target: Vec<_> = results.iter().filter(|r| r.profit_factor >= 1.5 && r.profit_factor.is_finite()).collect();
 
     println!("\n╔══════════════════════════════════════════════════════════════════╗");
@@ -353,6 +790,10 @@ fn main() {
 
     println!("╚══════════════════════════════════════════════════════════════════╝\n");
 
+    if validations.first().is_some_and(|v| v.collapsed) {
+        println!("⚠️  Best in-sample parameters collapse out-of-sample; prefer a set marked ok.");
+    }
+
     if above_target.len() >= 5 {
         println!("✅ Found {} parameter combinations exceeding target PF of 1.5!", above_target.len());
     } else if !above_target.is_empty() {
@@ -360,4 +801,6 @@ fn main() {
     } else {
         println!("❌ No combinations reached target PF of 1.5. Strategy needs refinement.");
     }
+
+    Ok(())
 }
@@ -5,14 +5,19 @@
 //! Usage: cargo run --bin test-connection
 
 use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::security::RedactingMakeWriter;
 use palm_oil_bot::modules::trading::{CTraderClient, OrderTicket};
-use palm_oil_bot::modules::trading::protobuf::ProtoOATradeSide;
+use palm_oil_bot::modules::trading::protobuf::{ProtoOAOrderType, ProtoOATradeSide};
 use tracing::{error, info};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Load .env before logging so the redactor knows the credentials
+    dotenvy::dotenv().ok();
+
     // Initialize logging
     tracing_subscriber::fmt()
+        .with_writer(RedactingMakeWriter::stdout())
         .with_env_filter("palm_oil_bot=debug,test_connection=debug")
         .init();
 
@@ -49,6 +54,14 @@ async fn main() -> anyhow::Result<()> {
             return Err(e.into());
         }
     }
+    match client.verify_trading_scope().await {
+        Ok(scope) => info!(
+            "✓ Token can trade account {} ({})",
+            scope.account_id,
+            scope.broker.as_deref().unwrap_or("unknown broker")
+        ),
+        Err(e) => error!("✗ Trading scope check failed: {}", e),
+    }
 
     // Test 3: Subscribe to FCPO symbol
     // Note: You'll need to get the actual symbol ID from cTrader
@@ -88,11 +101,17 @@ async fn main() -> anyhow::Result<()> {
         symbol_id,
         side: ProtoOATradeSide::Buy,
         volume: 10, // 0.1 lot = 10 in cTrader units
+        order_type: ProtoOAOrderType::Market,
+        limit_price: None,
+        stop_price: None,
+        expiration: None,
         stop_loss: Some(4800.0),
         take_profit: Some(4950.0),
         relative_stop_loss: None,
         relative_take_profit: None,
+        trailing_stop_loss: false,
         label: Some("Palm Oil Bot Test".to_string()),
+        comment: None,
     };
     info!("Order ticket created: {:?}", order_ticket);
     info!("✓ Order structure validated");
@@ -0,0 +1,196 @@
+//! Walk-forward optimization of the strategy parameters on stored candles.
+//!
+//! Usage:
+//!   cargo run --release --bin walk-forward -- fcpo_h1.csv --timeframe 1h
+//!   cargo run --release --bin walk-forward -- fcpo_h1.csv --train 1000 --test 250 \
+//!       --sentiment-csv sentiment.csv --objective net-pnl --json walk_forward.json
+//!   cargo run --release --bin walk-forward -- fcpo_h1.csv --rsi-periods 14 \
+//!       --oversold 25,30 --overbought 70,75 --take-profit 2,3 --stop-loss 1,1.5
+//!
+//! Every window searches the grid on `--train` candles and trades the winner
+//! on the next `--test` candles. Prints the best parameters per window with
+//! in-sample and out-of-sample results, then the stitched out-of-sample
+//! record. Without a sentiment source the score is `--sentiment` (default 0).
+
+use anyhow::Result;
+use clap::Parser;
+use palm_oil_bot::config::Config;
+use palm_oil_bot::modules::backtest::integrity::CandleFile;
+use palm_oil_bot::modules::backtest::{
+    FillModel, Objective, ParameterGrid, WalkForwardConfig, WalkForwardOptimizer,
+};
+use palm_oil_bot::modules::scraper::SentimentSeries;
+use palm_oil_bot::modules::trading::TimeFrame;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// CLI arguments for the walk-forward optimizer
+#[derive(Parser, Debug)]
+#[command(name = "walk-forward")]
+#[command(about = "Walk-forward grid search over RSI, TP/SL and sentiment parameters")]
+struct Args {
+    /// Candle CSV (timestamp,open,high,low,close[,volume][,spread])
+    file: PathBuf,
+
+    /// Candle timeframe (1m, 5m, 15m, 30m, 1h, 4h, 1d)
+    #[arg(long, default_value = "1h")]
+    timeframe: String,
+
+    /// Candles in each training (in-sample) window
+    #[arg(long, default_value_t = 1000)]
+    train: usize,
+
+    /// Candles in each test (out-of-sample) window
+    #[arg(long, default_value_t = 250)]
+    test: usize,
+
+    /// Candles to advance between windows (default: --test)
+    #[arg(long)]
+    step: Option<usize>,
+
+    /// Training trades a parameter set needs before it can win a window
+    #[arg(long, default_value_t = 5)]
+    min_trades: usize,
+
+    /// Ranking: profit-factor, net-pnl or return-over-drawdown
+    #[arg(long, default_value = "profit-factor")]
+    objective: String,
+
+    /// RSI periods to try (comma-separated)
+    #[arg(long, default_value = "9,14,21")]
+    rsi_periods: String,
+
+    /// RSI oversold thresholds to try
+    #[arg(long, default_value = "25,30,35")]
+    oversold: String,
+
+    /// RSI overbought thresholds to try
+    #[arg(long, default_value = "65,70,75")]
+    overbought: String,
+
+    /// Take-profit percentages to try
+    #[arg(long, default_value = "1.5,2,3")]
+    take_profit: String,
+
+    /// Stop-loss percentages to try
+    #[arg(long, default_value = "1,1.5,2")]
+    stop_loss: String,
+
+    /// Sentiment thresholds to try
+    #[arg(long, default_value = "20,30,40")]
+    sentiment_thresholds: String,
+
+    /// Historical sentiment (timestamp,score[,confidence])
+    #[arg(long)]
+    sentiment_csv: Option<PathBuf>,
+
+    /// Historical sentiment from the bot's trade log
+    #[arg(long)]
+    sentiment_trade_log: Option<PathBuf>,
+
+    /// Constant sentiment score when no history is given
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    sentiment: i32,
+
+    /// Spread in price units for candles without a recorded one
+    #[arg(long, default_value_t = 0.0)]
+    spread: f64,
+
+    /// Adverse slippage per fill, in percent of price
+    #[arg(long, default_value_t = 0.0)]
+    slippage: f64,
+
+    /// Commission per unit of volume, per fill
+    #[arg(long, default_value_t = 0.0)]
+    commission: f64,
+
+    /// Turn the strategy's trend filter off
+    #[arg(long)]
+    no_trend_filter: bool,
+
+    /// Also write the full report as JSON here
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+fn parse_timeframe(value: &str) -> Result<TimeFrame> {
+    Ok(match value.to_lowercase().as_str() {
+        "1m" | "m1" => TimeFrame::M1,
+        "5m" | "m5" => TimeFrame::M5,
+        "15m" | "m15" => TimeFrame::M15,
+        "30m" | "m30" => TimeFrame::M30,
+        "1h" | "h1" => TimeFrame::H1,
+        "4h" | "h4" => TimeFrame::H4,
+        "1d" | "d1" => TimeFrame::D1,
+        other => anyhow::bail!("Unknown timeframe {:?}", other),
+    })
+}
+
+/// Comma-separated values for one grid axis
+fn parse_list<T: FromStr>(flag: &str, value: &str) -> Result<Vec<T>> {
+    let values = value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            v.parse()
+                .map_err(|_| anyhow::anyhow!("--{}: invalid value {:?}", flag, v))
+        })
+        .collect::<Result<Vec<T>>>()?;
+    anyhow::ensure!(!values.is_empty(), "--{} needs at least one value", flag);
+    Ok(values)
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let timeframe = parse_timeframe(&args.timeframe)?;
+    let objective: Objective = args.objective.parse().map_err(anyhow::Error::msg)?;
+
+    let grid = ParameterGrid {
+        rsi_periods: parse_list("rsi-periods", &args.rsi_periods)?,
+        rsi_oversold: parse_list("oversold", &args.oversold)?,
+        rsi_overbought: parse_list("overbought", &args.overbought)?,
+        take_profit_percent: parse_list("take-profit", &args.take_profit)?,
+        stop_loss_percent: parse_list("stop-loss", &args.stop_loss)?,
+        sentiment_thresholds: parse_list("sentiment-thresholds", &args.sentiment_thresholds)?,
+    };
+    let mut config = WalkForwardConfig::new(args.train, args.test);
+    config.step_candles = args.step.unwrap_or(args.test);
+    config.min_trades = args.min_trades;
+    config.objective = objective;
+
+    let candles = CandleFile::load(&args.file, timeframe)?.candles;
+    let sentiment = match (&args.sentiment_csv, &args.sentiment_trade_log) {
+        (Some(path), _) => Some(SentimentSeries::from_csv(path)?),
+        (None, Some(path)) => Some(SentimentSeries::from_trade_log(path)?),
+        (None, None) => None,
+    };
+
+    println!(
+        "{} candles from {}, {} parameter sets per window, objective {:?}\n",
+        candles.len(),
+        args.file.display(),
+        grid.combinations().len(),
+        objective
+    );
+
+    let fill_model = FillModel {
+        spread: args.spread,
+        slippage_percent: args.slippage,
+        commission_per_unit: args.commission,
+    };
+    let optimizer = WalkForwardOptimizer::new(Config::default(), fill_model, grid, config)
+        .with_trend_filter(!args.no_trend_filter);
+    let report = optimizer.run(&candles, |candle| match &sentiment {
+        Some(series) => series.score_at(candle.end_time()),
+        None => args.sentiment,
+    })?;
+
+    print!("{}", report.summary());
+
+    if let Some(path) = &args.json {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("\nReport written to {}", path.display());
+    }
+    Ok(())
+}
@@ -2,85 +2,184 @@
 //!
 //! Aggregates ticks into candles, computes RSI, combines sentiment,
 //! and executes trades while respecting circuit breakers.
+//!
+//! Candles and signals are driven by the cycle (`CYCLE_INTERVAL_SECS`), while
+//! every spot event also goes through a lightweight exit check so SL/TP and
+//! trailing activation react at tick speed between cycles.
 
-use crate::config::Config;
+use crate::config::{Config, SizingPolicy};
 use crate::error::{BotError, CTraderError, Result};
-use crate::modules::monitoring::{metrics_enabled, start_metrics_server};
-use crate::modules::monitoring::{MetricsHandle, Trade};
-use crate::modules::scraper::{PerplexityClient, SentimentResult, TwitterScraper};
+use crate::modules::backtest::ReplaySpeed;
+use crate::modules::monitoring::{
+    admin_socket_path, export_api_enabled, metrics_enabled, start_admin_socket, start_grpc_server,
+    start_metrics_server, ControlApi, EventStream, ExportSources, GrafanaApi, GrpcApiConfig,
+    WebDashboard,
+};
+use crate::modules::monitoring::{
+    AlertMetric, AlertRules, BreakerSnapshot, CrashReporter, CrashState, MetricsHandle, Trade,
+    TriggeredAlert,
+};
+use crate::modules::notifications::{
+    start_telegram_bot, telegram_commands_enabled, HeartbeatPosition, HeartbeatSchedule,
+    HeartbeatSummary, TelegramNotifier,
+};
+use crate::modules::scraper::{
+    MarketBriefSchedule, PerplexityClient, RedditConfig, RedditScraper, RssConfig, RssNewsClient,
+    SentimentAggregator, SentimentBreakdown, SentimentResult, TwitterScraper, FundamentalsClient,
+    SentimentProvider, TwitterApiClient, TwitterApiConfig,
+};
 use crate::modules::security::ApiRateLimiter;
-use crate::modules::trading::protobuf::{ProtoOATradeSide, ProtoOaTradingMode};
+use crate::modules::storage::{
+    start_archive_scheduler, start_backup_scheduler, start_tick_recorder, ArchiveConfig, Archiver,
+    BackupConfig, TickRecorder, TickRecorderConfig,
+};
+use crate::modules::trading::protobuf::{ProtoOAOrderType, ProtoOATradeSide, ProtoOaTradingMode};
+use crate::modules::trading::normalize;
 use crate::modules::trading::{
     Candle, CandleBuilder, CTraderClient, EventChannelHandle, MarketEvent, OrderSide, OrderTicket,
-    RsiCalculator, Signal, Tick, TimeFrame, TradingStrategy, PositionDatabase, CloseReason,
-    Position, SymbolMeta,
+    RsiCalculator, Signal, SignalContext, Strategy, Tick, TimeFrame, TradingStrategy,
+    PositionDatabase, CloseReason,
+    Position, SymbolClassification, SymbolMeta, BrokerPosition, ReconciliationEngine,
+    ReconciliationResult,
+    LeaderElectionConfig, LeaderElector, LeaderTransition, BrokerOrder, PendingOrderBook,
+    UnknownOrderPolicy, reconcile_orders, emergency_channel, CancelAllReport, EmergencyCommand,
+    EmergencyHandle, LabelContext, OrderLabeler, Price, IndicatorSample, SentimentRecord,
+    StatusReport, SignalDecision,
+    Exposure, OrderExposure, RiskManager, ArmingConfig, ArmingStatus, ArmingSwitch,
+    OrderSizeGuard, BalanceMonitor, EntryKind,
+    VolatilityRegime, SpreadGuard, PriceFreshness, StalePriceGuard, SimulatedBroker,
+    OppositeSignalPolicy, StrategyFeatures, ReconnectTrip, ConnectionChange,
+    ConnectionState, CrossMarketFilter, CrossCheck, SecondaryFeed,
+    SecondaryFeedConfig, BreakerScenario, ScenarioOutcome, ScenarioVerdict,
 };
+use crate::modules::trading::blackout::FEED_REFRESH_INTERVAL;
+use crate::modules::trading::breaker_scenarios::{BREAKER_BLOCKER, PROBE_BLOCKER};
 use crate::modules::utils::{retry_with_backoff, RetryConfig};
 
-use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
 use std::io::Write as IoWrite;
 use std::sync::Arc;
 use std::{env, fs, path::Path};
 use std::time::Instant;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio::time::{interval, sleep, Duration};
 use tracing::{debug, error, info, warn};
 
 /// Sentiment cache TTL in minutes
 const SENTIMENT_CACHE_TTL_MINUTES: i64 = 5;
 
+/// Trendbars fetched at startup to seed RSI/EMA (covers the 50-period trend EMA)
+const DEFAULT_WARMUP_BARS: u32 = 100;
+
 /// Neutral sentiment value used as fallback
 const NEUTRAL_SENTIMENT: i32 = 0;
 
+/// Clock skew versus broker spot time above which a warning is logged
+const CLOCK_SKEW_WARN_MS: i64 = 2_000;
+
+/// Wait after the first failed exit close; doubles per failure up to
+/// `CLOSE_RETRY_MAX_SECS`
+const CLOSE_RETRY_BASE_SECS: u64 = 5;
+
+/// Longest wait between two attempts to close the same position
+const CLOSE_RETRY_MAX_SECS: u64 = 300;
+
 /// Cached sentiment data with TTL
+///
+/// The TTL decides when the providers are asked again; the score itself
+/// decays exponentially toward neutral with its age (`half_life`), so a
+/// reading weighs less the older it gets instead of counting in full until
+/// it expires. When every provider fails, the last reading keeps decaying
+/// rather than dropping straight to neutral.
 #[derive(Debug, Clone)]
 pub struct SentimentCache {
-    /// Cached sentiment score (-100 to +100)
+    /// Cached sentiment score (-100 to +100), as read
     pub value: i32,
     /// Full sentiment result
     pub result: Option<SentimentResult>,
-    /// When the cache was last updated
+    /// When the cached reading was taken
     pub timestamp: DateTime<Utc>,
+    /// When the providers were last asked (a failed fetch keeps the reading)
+    pub checked_at: DateTime<Utc>,
     /// Cache time-to-live
     pub ttl: ChronoDuration,
+    /// Age at which the score has decayed to half (None = no decay)
+    pub half_life: Option<ChronoDuration>,
 }
 
 impl SentimentCache {
-    /// Create a new empty cache with specified TTL
+    /// Create a new empty cache with specified TTL and no decay
     pub fn new(ttl_minutes: i64) -> Self {
         Self {
             value: NEUTRAL_SENTIMENT,
             result: None,
             timestamp: DateTime::<Utc>::MIN_UTC,
+            checked_at: DateTime::<Utc>::MIN_UTC,
             ttl: ChronoDuration::minutes(ttl_minutes),
+            half_life: None,
         }
     }
 
+    /// Decay the score with the given half-life (0 = no decay)
+    pub fn with_half_life(mut self, minutes: i64) -> Self {
+        self.half_life = (minutes > 0).then(|| ChronoDuration::minutes(minutes));
+        self
+    }
+
     /// Check if the cache is still valid
     pub fn is_valid(&self) -> bool {
-        let age = Utc::now() - self.timestamp;
+        let age = Utc::now() - self.checked_at;
         age < self.ttl
     }
 
-    /// Get cached value if valid, otherwise None
+    /// Get the decayed cached value if valid, otherwise None
     pub fn get(&self) -> Option<i32> {
         if self.is_valid() {
-            Some(self.value)
+            Some(self.decayed_value(Utc::now()))
         } else {
             None
         }
     }
 
+    /// Cached score weighted by its age: halved every `half_life`
+    pub fn decayed_value(&self, now: DateTime<Utc>) -> i32 {
+        let Some(half_life) = self.half_life else {
+            return self.value;
+        };
+        let age_ms = (now - self.timestamp).num_milliseconds().max(0) as f64;
+        let factor = 0.5f64.powf(age_ms / half_life.num_milliseconds() as f64);
+        (self.value as f64 * factor).round() as i32
+    }
+
+    /// Cached result with the decayed score
+    pub fn decayed_result(&self, now: DateTime<Utc>) -> Option<SentimentResult> {
+        let score = self.decayed_value(now);
+        self.result.clone().map(|result| result.with_score(score))
+    }
+
     /// Update the cache with a new value
     pub fn update(&mut self, value: i32, result: Option<SentimentResult>) {
         self.value = value;
         self.result = result;
         self.timestamp = Utc::now();
+        self.checked_at = self.timestamp;
+    }
+
+    /// Every provider failed: with decay on, keep the last reading (it goes
+    /// on decaying) until the next TTL; None without decay or a reading
+    pub fn fallback_on_failure(&mut self, now: DateTime<Utc>) -> Option<SentimentResult> {
+        if self.half_life.is_none() {
+            return None;
+        }
+        let result = self.decayed_result(now)?;
+        self.checked_at = now;
+        Some(result)
     }
 
     /// Get time until cache expires (or zero if expired)
     pub fn time_until_expiry(&self) -> ChronoDuration {
-        let age = Utc::now() - self.timestamp;
+        let age = Utc::now() - self.checked_at;
         let remaining = self.ttl - age;
         if remaining.num_seconds() > 0 {
             remaining
@@ -91,7 +190,7 @@ impl SentimentCache {
 
     /// Force invalidate the cache
     pub fn invalidate(&mut self) {
-        self.timestamp = DateTime::<Utc>::MIN_UTC;
+        self.checked_at = DateTime::<Utc>::MIN_UTC;
     }
 }
 
@@ -114,41 +213,118 @@ impl TradeLogger {
                 let _ = fs::create_dir_all(parent);
             }
             if let Ok(mut f) = fs::File::create(path) {
-                let _ = writeln!(f, "timestamp,event,side,symbol,entry_price,sl,tp,volume,rsi,sentiment_score,sentiment_confidence,signal,position_id,close_price,pnl,close_reason");
+                let _ = writeln!(f, "timestamp,event,side,symbol,entry_price,sl,tp,volume,rsi,sentiment_score,sentiment_confidence,signal,position_id,close_price,pnl,close_reason,cross_market");
             }
         }
         Self { path: path.to_string() }
     }
 
-    fn log_open(&self, timestamp: &str, side: &str, symbol: &str, entry: f64, sl: f64, tp: f64, volume: f64, rsi: f64, sentiment_score: i32, sentiment_confidence: f64, signal: &str, position_id: &str) {
+    #[allow(clippy::too_many_arguments)]
+    fn log_open(&self, timestamp: &str, side: &str, symbol: &str, entry: f64, sl: f64, tp: f64, volume: f64, rsi: f64, sentiment_score: i32, sentiment_confidence: f64, signal: &str, position_id: &str, cross_market: &str) {
         if let Ok(mut f) = fs::OpenOptions::new().append(true).open(&self.path) {
-            let _ = writeln!(f, "{},{},{},{},{:.5},{:.5},{:.5},{:.4},{:.2},{},{:.2},{},{},,,",
-                timestamp, "OPEN", side, symbol, entry, sl, tp, volume, rsi, sentiment_score, sentiment_confidence, signal, position_id);
+            let _ = writeln!(f, "{},OPEN,{},{},{:.5},{:.5},{:.5},{:.4},{:.2},{},{:.2},{},{},,,,{}",
+                timestamp, side, symbol, entry, sl, tp, volume, rsi, sentiment_score, sentiment_confidence, signal, position_id, cross_market);
         }
     }
 
     fn log_close(&self, timestamp: &str, position_id: &str, close_price: f64, pnl: f64, reason: &str) {
         if let Ok(mut f) = fs::OpenOptions::new().append(true).open(&self.path) {
-            let _ = writeln!(f, "{},{},,,,,,,,,,,{},{:.5},{:.2},{}",
-                timestamp, "CLOSE", position_id, close_price, pnl, reason);
+            let _ = writeln!(f, "{},CLOSE,,,,,,,,,,,{},{:.5},{:.2},{},",
+                timestamp, position_id, close_price, pnl, reason);
+        }
+    }
+}
+
+/// Second aggregation of the tick stream, for higher-timeframe RSI
+/// confirmation (`HIGHER_TIMEFRAME`)
+struct HigherTimeframe {
+    builder: CandleBuilder,
+    rsi_calculator: RsiCalculator,
+    /// Latest RSI, None until enough candles have closed
+    rsi: Option<f64>,
+}
+
+impl HigherTimeframe {
+    fn new(timeframe: TimeFrame, rsi_period: usize) -> Self {
+        Self {
+            builder: CandleBuilder::from_env(timeframe),
+            rsi_calculator: RsiCalculator::new(rsi_period),
+            rsi: None,
+        }
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        if let Some(rsi) = self.rsi_calculator.add_price(candle.close) {
+            self.rsi = Some(rsi);
         }
     }
 }
 
-pub struct TradingBot {
-    strategy: TradingStrategy,
+/// Failed exit closes per position. Exits are checked on every spot event,
+/// so a close the broker keeps refusing is retried on a backoff rather than
+/// at tick rate.
+#[derive(Debug, Default)]
+struct CloseRetries {
+    /// Position id -> (consecutive failures, earliest next attempt)
+    failures: HashMap<String, (u32, Instant)>,
+}
+
+impl CloseRetries {
+    /// Whether a close of `position_id` may be attempted at `now`
+    fn ready(&self, position_id: &str, now: Instant) -> bool {
+        self.failures
+            .get(position_id)
+            .map_or(true, |(_, next)| now >= *next)
+    }
+
+    /// Record a failed close; returns the wait before the next attempt
+    fn failed(&mut self, position_id: &str, now: Instant) -> Duration {
+        let entry = self
+            .failures
+            .entry(position_id.to_string())
+            .or_insert((0, now));
+        entry.0 += 1;
+        let wait = CLOSE_RETRY_BASE_SECS
+            .saturating_mul(2u64.saturating_pow(entry.0 - 1))
+            .min(CLOSE_RETRY_MAX_SECS);
+        let wait = Duration::from_secs(wait);
+        entry.1 = now + wait;
+        wait
+    }
+
+    /// Forget positions that closed or are no longer open
+    fn retain_open(&mut self, open: &[Position]) {
+        self.failures
+            .retain(|id, _| open.iter().any(|position| &position.id == id));
+    }
+}
+
+/// Live trading loop, generic over the entry/exit logic (`Strategy`)
+pub struct TradingBot<S: Strategy = TradingStrategy> {
+    strategy: S,
     ctrader: CTraderClient,
     candle_builder: CandleBuilder,
     rsi_calculator: RsiCalculator,
+    /// Optional confirmation timeframe built from the same ticks
+    higher_timeframe: Option<HigherTimeframe>,
     event_channel: EventChannelHandle,
     config: Config,
-    perplexity: PerplexityClient,
-    twitter: TwitterScraper,
+    /// Also a sentiment provider; kept for the market briefs
+    perplexity: Arc<PerplexityClient>,
+    /// Perplexity, Twitter, then Reddit and the news feeds when enabled
+    /// (SENTIMENT_PROVIDER_WEIGHTS)
+    sentiment_providers: SentimentAggregator,
     position_db: Option<PositionDatabase>,
     metrics: MetricsHandle,
     symbol_id: i64,
     last_price: Option<f64>,
+    /// Last bid/ask quote, when the feed provides both sides
+    last_quote: Option<(f64, f64)>,
+    /// Whether the current clock-skew excursion has already been logged
+    clock_skew_warned: bool,
     symbol_meta: Option<SymbolMeta>,
+    /// Category / asset class of the traded symbol
+    symbol_class: Option<SymbolClassification>,
     /// Sentiment cache to avoid excessive API calls
     sentiment_cache: Arc<RwLock<SentimentCache>>,
     /// CSV trade logger for backtesting
@@ -157,58 +333,487 @@ pub struct TradingBot {
     last_rsi: f64,
     /// Last known sentiment for trade logging
     last_sentiment: SentimentResult,
+    /// Last strategy signal for trade logging
+    last_signal: Signal,
+    /// Daily market brief schedule (MARKET_BRIEF_HOUR_UTC)
+    market_brief_schedule: Option<MarketBriefSchedule>,
+    /// Date of the last scheduled market brief
+    last_market_brief: Option<NaiveDate>,
+    /// UTC date whose closed trades are still accumulating (rolled up once it ends)
+    last_stats_rollup: Option<NaiveDate>,
+    /// Periodic alive summary (HEARTBEAT_INTERVAL_HOURS)
+    heartbeat_schedule: Option<HeartbeatSchedule>,
+    /// When the last heartbeat summary went out
+    last_heartbeat: Option<DateTime<Utc>>,
+    /// When the bot was created, for uptime
+    started_at: DateTime<Utc>,
+    /// Local time the last price tick or spot event was received
+    last_tick_at: Option<DateTime<Utc>>,
+    /// Server time of the last tick fed to the candle builders, for gap
+    /// detection after reconnects
+    last_tick_time: Option<DateTime<Utc>>,
+    /// Telegram delivery for reports (TELEGRAM_BOT_TOKEN / TELEGRAM_CHAT_ID)
+    telegram: Option<TelegramNotifier>,
+    /// Query every sentiment provider together and blend them
+    /// (SENTIMENT_MULTI_SOURCE)
+    multi_source_sentiment: bool,
+    /// Weather/production series blended into sentiment (FUNDAMENTALS_ENABLED)
+    fundamentals: Option<FundamentalsClient>,
+    /// Soybean/crude correlation filter on signals (CROSS_MARKET_SYMBOLS)
+    cross_market: Option<CrossMarketFilter>,
+    /// Broker symbol IDs of the cross markets that resolved and subscribed
+    cross_market_ids: Vec<(String, i64)>,
+    /// Cross-market readings at the last closed candle, for trade logging
+    last_cross_market: String,
+    /// Second price source for cross-checks and stale-feed monitoring
+    /// (SECONDARY_FEED_URL)
+    secondary_feed: Option<SecondaryFeed>,
+    /// Strategy version/config fingerprint stamped on every position
+    strategy_version: String,
+    /// Another instance owns the account: dry-run only, nothing shared is written
+    observer_only: bool,
+    /// Hot-standby lease (LEADER_ELECTION_ENABLED); only the leader trades
+    leader: Option<LeaderElector>,
+    /// State snapshot source for crash bundles
+    crash_reporter: Option<CrashReporter>,
+    /// Limit/stop orders the bot expects to be working at the broker
+    pending_orders: PendingOrderBook,
+    /// Handling of unknown bot-labelled broker orders (PENDING_ORDER_POLICY)
+    pending_order_policy: UnknownOrderPolicy,
+    /// Label/comment templates for new orders (ORDER_LABEL_TEMPLATE, ORDER_COMMENT_TEMPLATE)
+    order_labeler: OrderLabeler,
+    /// Orders sent this session, rendered as `{signal_id}`
+    orders_sent: u64,
+    /// Portfolio exposure and margin limits for new orders (RISK_MANAGER_ENABLED)
+    risk_manager: Option<RiskManager>,
+    /// Hard per-order caps (MAX_ORDER_NOTIONAL / MAX_ORDER_LOTS)
+    size_guard: OrderSizeGuard,
+    /// Entry filter on the bid/ask spread (MAX_SPREAD / MAX_SPREAD_PERCENT)
+    spread_guard: SpreadGuard,
+    /// Halts the cycle on prices older than MAX_PRICE_AGE_SECS
+    stale_price: StalePriceGuard,
+    /// Dry-run fills, commission and swap (DRY_RUN_* costs)
+    simulated_broker: SimulatedBroker,
+    /// Unexplained balance change detection (BALANCE_MONITOR_ENABLED)
+    balance_monitor: Option<BalanceMonitor>,
+    /// When the news blackout ICS feed was last read (NEWS_BLACKOUT_ICS)
+    last_blackout_refresh: Option<DateTime<Utc>>,
+    /// Start of the last news blackout window announced
+    blackout_started: Option<DateTime<Utc>>,
+    /// Operator arming needed before live orders are sent (LIVE_ARMING_REQUIRED)
+    arming: ArmingSwitch,
+    /// Positions whose stop was handed to the broker as a trailing stop
+    trailing_stops: HashSet<String>,
+    /// Positions whose stop was moved to break-even (BREAK_EVEN_ACTIVATION_PERCENT)
+    break_even_stops: HashSet<String>,
+    /// Backoff for exit closes the broker refused
+    close_retries: CloseRetries,
+    /// User-defined market alerts (ALERT_RULES)
+    alert_rules: AlertRules,
+    /// New entries paused by the operator (Telegram `/pause`); exits keep running
+    paused: bool,
+    /// Whether the daily-loss circuit breaker was tripped at the last candle
+    breaker_tripped: bool,
+    /// Set while a QUICK_TEST breaker scenario runs: a signal that gets past
+    /// every filter is withheld instead of reaching `execute_trade`
+    breaker_probe: bool,
+    /// Last reconciliation mismatch alert, so a lasting mismatch is reported once
+    last_reconcile_alert: Option<String>,
+    /// Sender for operator panic commands (dashboard, control API, Telegram)
+    emergency: EmergencyHandle,
+    /// Panic commands, run by the trading loop between ticks
+    emergency_rx: mpsc::Receiver<EmergencyCommand>,
 }
 
-impl TradingBot {
+impl TradingBot<TradingStrategy> {
+    /// Bot running the built-in RSI + sentiment strategy
     pub fn new(config: Config) -> Result<Self> {
-        let timeframe = parse_timeframe(&config.strategy.rsi_timeframe);
-        let strategy = TradingStrategy::new(
+        let mut strategy = TradingStrategy::new(
             config.strategy.clone(),
             config.trading.clone(),
             config.trading.initial_balance,
         );
+        if let Some(margin) = config.decision.disagreement_rsi_margin {
+            strategy.set_disagreement_rsi_margin(margin);
+        }
+        Self::with_strategy(config, strategy)
+    }
+}
+
+impl<S: Strategy> TradingBot<S> {
+    /// Bot running a custom strategy; risk limits still come from `config`
+    pub fn with_strategy(config: Config, mut strategy: S) -> Result<Self> {
+        let timeframe = parse_timeframe(&config.strategy.rsi_timeframe);
+        let decision = &config.decision;
+        strategy
+            .core_mut()
+            .set_max_positions_per_asset_class(decision.max_positions_per_asset_class);
+        strategy
+            .core_mut()
+            .set_market_calendar(decision.market_calendar.clone());
+        strategy.core_mut().set_blackout(decision.blackout.clone());
+        strategy.core_mut().set_volatility(decision.volatility);
+        // Strategies may bring their own trailing stop; the env setting wins
+        if let Some(structure_stop) = decision.structure_stop {
+            strategy.core_mut().set_structure_stop(Some(structure_stop));
+        }
+        strategy
+            .core_mut()
+            .set_opposite_signal_policy(config.strategy.opposite_signal_policy);
+        if config.strategy.macd_confirmation {
+            strategy.core_mut().set_macd_confirmation(true);
+        }
+        strategy.core_mut().set_features(StrategyFeatures::from_env()?);
+        let multi_source_sentiment = env::var("SENTIMENT_MULTI_SOURCE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
         let ctrader = CTraderClient::new(config.ctrader.clone());
-        let candle_builder = CandleBuilder::new(timeframe);
+        let candle_builder = CandleBuilder::from_env(timeframe);
         let rsi_calculator = RsiCalculator::new(config.strategy.rsi_period);
+        let higher_timeframe = config.strategy.higher_timeframe.as_ref().and_then(|settings| {
+            let higher = confirmation_timeframe(&settings.timeframe, timeframe)?;
+            strategy
+                .core_mut()
+                .set_higher_rsi_filter(settings.rsi_buy_below, settings.rsi_sell_above);
+            info!("Confirming {} signals with {} RSI", timeframe, higher);
+            Some(HigherTimeframe::new(higher, config.strategy.rsi_period))
+        });
         let event_channel = EventChannelHandle::default();
         
         // Create rate limiters for API clients
         let perplexity_rate_limiter = Arc::new(ApiRateLimiter::for_perplexity());
         let twitter_rate_limiter = Arc::new(ApiRateLimiter::for_twitter());
         
-        let perplexity = PerplexityClient::with_symbol(config.perplexity.clone(), perplexity_rate_limiter, &config.trading.symbol);
-        let twitter = TwitterScraper::new(config.kols.clone(), twitter_rate_limiter);
+        let perplexity = Arc::new(PerplexityClient::with_symbol(
+            config.perplexity.clone(),
+            perplexity_rate_limiter,
+            &config.trading.symbol,
+        ));
+        info!(
+            "Sentiment LLM: {} (model {})",
+            config.perplexity.backend, config.perplexity.model
+        );
+        let twitter: Arc<dyn SentimentProvider> = match TwitterApiConfig::from_env()? {
+            Some(api_config) => {
+                info!("Twitter sentiment through the v2 search API");
+                Arc::new(TwitterApiClient::new(
+                    api_config,
+                    config.kols.clone(),
+                    twitter_rate_limiter,
+                ))
+            }
+            None => Arc::new(TwitterScraper::new(config.kols.clone(), twitter_rate_limiter)),
+        };
+        let mut sentiment_providers =
+            SentimentAggregator::new(decision.sentiment_weights.clone())
+                .with(perplexity.clone())
+                .with(twitter);
+        if let Some(reddit_config) = RedditConfig::from_env()? {
+            info!("Reddit sentiment enabled for r/{}", reddit_config.subreddits.join(", r/"));
+            let rate_limiter = Arc::new(ApiRateLimiter::for_reddit());
+            sentiment_providers.add(Arc::new(RedditScraper::new(reddit_config, rate_limiter)));
+        }
+        if let Some(rss_config) = RssConfig::from_env()? {
+            info!("News feed sentiment enabled for {} feed(s)", rss_config.feeds.len());
+            sentiment_providers.add(Arc::new(RssNewsClient::new(rss_config)));
+        }
+        let fundamentals = decision.fundamentals.clone().map(|fundamentals_config| {
+            info!(
+                "Fundamental inputs enabled: {} series, {:.0}% of sentiment",
+                fundamentals_config.series.len(),
+                fundamentals_config.weight * 100.0
+            );
+            strategy
+                .core_mut()
+                .set_fundamentals_weight(fundamentals_config.weight);
+            FundamentalsClient::new(fundamentals_config)
+        });
+        let cross_market = decision.cross_market.clone().map(|cross_market_config| {
+            info!(
+                "Cross-market filter on {} over {} candles (veto {})",
+                cross_market_config.symbols.join(", "),
+                cross_market_config.window,
+                cross_market_config.veto
+            );
+            CrossMarketFilter::new(cross_market_config)
+        });
+        let secondary_feed = SecondaryFeedConfig::from_env()?.map(|feed_config| {
+            info!(
+                "Secondary price feed enabled every {}s (alert above {:.2}% apart)",
+                feed_config.poll_interval.as_secs(),
+                feed_config.max_divergence_percent
+            );
+            SecondaryFeed::new(feed_config)
+        });
+        info!(
+            "Sentiment providers: {}",
+            sentiment_providers.provider_names().join(", ")
+        );
         let position_db = init_position_db();
+        let leader = match (LeaderElectionConfig::from_env(), &position_db) {
+            (Some(election), Some(db)) => {
+                info!("Leader election enabled as node {}", election.node_id);
+                Some(LeaderElector::new(
+                    db.clone(),
+                    config.ctrader.active_account_id(),
+                    election,
+                ))
+            }
+            (Some(_), None) => {
+                return Err(BotError::Config(
+                    "LEADER_ELECTION_ENABLED requires SQLite persistence (PERSISTENCE_DB_PATH)"
+                        .to_string(),
+                ))
+            }
+            (None, _) => None,
+        };
         let metrics = MetricsHandle::new(config.trading.initial_balance);
 
         let trade_log_path = env::var("TRADE_LOG_PATH").unwrap_or_else(|_| "data/trade_log.csv".to_string());
         let trade_logger = TradeLogger::new(&trade_log_path);
         info!("Trade logger enabled at {}", trade_log_path);
 
+        // Custom strategies get their own version so per-version stats stay separate
+        let strategy_version = match strategy.name() {
+            "rsi_sentiment" => config.strategy_fingerprint(),
+            name => format!("{}-{}", name, config.strategy_fingerprint()),
+        };
+        info!("Strategy version: {}", strategy_version);
+
+        let market_brief_schedule = MarketBriefSchedule::from_env();
+        if let Some(schedule) = &market_brief_schedule {
+            info!("Daily market brief scheduled at {:02}:00 UTC", schedule.hour_utc);
+        }
+
+        let (emergency, emergency_rx) = emergency_channel();
+
+        let live = config.ctrader.environment.is_live()
+            || config.ctrader.server.starts_with("live.");
+        let arming = ArmingSwitch::new(ArmingConfig::from_env(live));
+        if arming.is_required() && !config.bot.dry_run {
+            warn!("Live trading is disarmed: no orders are sent until an operator arms the bot");
+        }
+
+        let size_guard = OrderSizeGuard::from_env()?;
+        if !size_guard.is_enabled() && live && !config.bot.dry_run {
+            warn!("No MAX_ORDER_NOTIONAL / MAX_ORDER_LOTS set: order size is bounded by RISK_PER_TRADE only");
+        }
+        let spread_guard = config.decision.spread_guard;
+        let sentiment_half_life = config.decision.sentiment_half_life_minutes;
+        let stale_price = StalePriceGuard::from_env()?;
+        let simulated_broker = SimulatedBroker::from_env()?;
+
         Ok(Self {
             strategy,
             ctrader,
             candle_builder,
             rsi_calculator,
+            higher_timeframe,
             event_channel,
             config,
             perplexity,
-            twitter,
+            sentiment_providers,
             position_db,
             metrics,
             symbol_id: 0,
             last_price: None,
+            last_quote: None,
+            clock_skew_warned: false,
             symbol_meta: None,
-            sentiment_cache: Arc::new(RwLock::new(SentimentCache::default())),
+            symbol_class: None,
+            sentiment_cache: Arc::new(RwLock::new(
+                SentimentCache::default().with_half_life(sentiment_half_life),
+            )),
             trade_logger,
             last_rsi: 50.0,
             last_sentiment: SentimentResult::new(0, "init"),
+            last_signal: Signal::Hold,
+            market_brief_schedule,
+            last_market_brief: None,
+            last_stats_rollup: None,
+            heartbeat_schedule: HeartbeatSchedule::from_env(),
+            last_heartbeat: None,
+            started_at: Utc::now(),
+            last_tick_at: None,
+            last_tick_time: None,
+            telegram: TelegramNotifier::from_env(),
+            multi_source_sentiment,
+            fundamentals,
+            cross_market,
+            cross_market_ids: Vec::new(),
+            last_cross_market: String::new(),
+            secondary_feed,
+            strategy_version,
+            observer_only: false,
+            leader,
+            crash_reporter: None,
+            pending_orders: PendingOrderBook::new(),
+            pending_order_policy: UnknownOrderPolicy::from_env(),
+            order_labeler: OrderLabeler::from_env(),
+            orders_sent: 0,
+            risk_manager: RiskManager::from_env(),
+            size_guard,
+            spread_guard,
+            stale_price,
+            simulated_broker,
+            balance_monitor: BalanceMonitor::from_env(),
+            last_blackout_refresh: None,
+            blackout_started: None,
+            arming,
+            trailing_stops: HashSet::new(),
+            break_even_stops: HashSet::new(),
+            close_retries: CloseRetries::default(),
+            alert_rules: AlertRules::from_env(),
+            paused: false,
+            breaker_tripped: false,
+            breaker_probe: false,
+            last_reconcile_alert: None,
+            emergency,
+            emergency_rx,
         })
     }
 
+    /// Run alongside the instance that owns the account without interfering:
+    /// force dry-run, skip SQLite persistence, backups, archives and scheduled
+    /// briefs, and log hypothetical trades to a separate CSV
+    pub fn set_observer_only(&mut self) {
+        self.observer_only = true;
+        self.config.bot.dry_run = true;
+        self.position_db = None;
+        self.market_brief_schedule = None;
+        let observer_log = Path::new(&self.trade_logger.path).with_extension("observer.csv");
+        self.trade_logger = TradeLogger::new(&observer_log.to_string_lossy());
+        warn!(
+            "Observer-only mode: dry-run, trades logged to {}",
+            observer_log.display()
+        );
+    }
+
+    /// Match tracked limit/stop orders against the broker's working orders
+    /// (cached by the preceding reconcile) and the order history
+    async fn reconcile_pending_orders(&mut self) {
+        let working: Vec<BrokerOrder> = self
+            .ctrader
+            .cached_pending_orders()
+            .await
+            .into_iter()
+            .filter(|o| o.symbol_id == self.symbol_id)
+            .collect();
+
+        // History is only needed for tracked orders that stopped working
+        let needs_history = self
+            .pending_orders
+            .orders()
+            .any(|t| !working.iter().any(|w| w.order_id == t.order_id));
+        let history = match self.pending_orders.oldest_placed_at() {
+            Some(oldest) if needs_history => {
+                let from = (oldest - ChronoDuration::minutes(1)).timestamp_millis();
+                match self.ctrader.list_orders(from, Utc::now().timestamp_millis()).await {
+                    Ok(history) => history,
+                    Err(err) => {
+                        warn!("Order history unavailable, pending orders left as tracked: {}", err);
+                        return;
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
+
+        let outcome = reconcile_orders(
+            &self.pending_orders,
+            &working,
+            &history,
+            self.pending_order_policy,
+            self.order_labeler.owner_prefix(),
+        );
+        if !outcome.has_changes() {
+            return;
+        }
+
+        for (order, position_id) in &outcome.filled {
+            info!(
+                "Pending order {} filled while disconnected (position {:?})",
+                order.order_id, position_id
+            );
+        }
+        for (order, status) in &outcome.finished {
+            info!("Pending order {} ended while disconnected: {:?}", order.order_id, status);
+        }
+        for order in &outcome.missing {
+            warn!(
+                "Pending order {} is no longer working and has no history; dropping it",
+                order.order_id
+            );
+        }
+        for order in &outcome.adopt {
+            warn!("Adopting unknown pending order {} from the broker", order.order_id);
+        }
+        for order in &outcome.cancel {
+            warn!("Cancelling unknown pending order {} (PENDING_ORDER_POLICY=cancel)", order.order_id);
+            if let Err(err) = self.ctrader.cancel_order(order.order_id).await {
+                warn!("Failed to cancel pending order {}: {}", order.order_id, err);
+            }
+        }
+        self.pending_orders.apply(&outcome);
+    }
+
+    /// Keep the crash reporter's state snapshot current on every tick
+    pub fn set_crash_reporter(&mut self, reporter: CrashReporter) {
+        self.crash_reporter = Some(reporter);
+    }
+
+    fn refresh_crash_state(&self) {
+        let Some(reporter) = &self.crash_reporter else {
+            return;
+        };
+        let risk = self.strategy.core().risk_state();
+        reporter.update_state(CrashState {
+            symbol: self.config.trading.symbol.clone(),
+            strategy_version: self.strategy_version.clone(),
+            dry_run: self.config.bot.dry_run,
+            last_price: self.last_price,
+            open_positions: self.strategy.core().get_open_positions().to_vec(),
+            breakers: BreakerSnapshot {
+                circuit_breaker: risk.circuit_breaker,
+                daily_pnl: risk.daily_pnl,
+                daily_trades: risk.daily_trades,
+                consecutive_losses: risk.consecutive_losses,
+            },
+            updated_at: Some(Utc::now()),
+        });
+    }
+
+    /// Check the access token may trade the configured account before
+    /// anything is started; dry-run only needs read access, so it just warns
+    async fn verify_trading_scope(&self) -> Result<()> {
+        match self.ctrader.verify_trading_scope().await {
+            Ok(scope) => {
+                info!(
+                    "Trading scope verified: account {} (login {}, {}, {})",
+                    scope.account_id,
+                    scope
+                        .trader_login
+                        .map_or_else(|| "?".to_string(), |login| login.to_string()),
+                    scope.broker.as_deref().unwrap_or("unknown broker"),
+                    if scope.is_live { "LIVE" } else { "DEMO" }
+                );
+                Ok(())
+            }
+            Err(err) if self.config.bot.dry_run => {
+                warn!("Access token cannot trade ({}); fine for dry_run", err);
+                Ok(())
+            }
+            Err(err) => {
+                error!("Access token cannot trade the configured account: {}", err);
+                Err(err)
+            }
+        }
+    }
+
     /// Main trading loop.
     pub async fn run(&mut self) -> Result<()> {
         info!("Trading bot starting...");
+        self.backfill_daily_stats();
 
         if self.config.bot.dry_run && self.config.ctrader.access_token.is_none() {
             return self.run_offline_dry_run().await;
@@ -217,10 +822,13 @@ impl TradingBot {
         self.ctrader.verify_credentials()?;
         connect_with_retry(&self.ctrader).await?;
         authenticate_with_retry(&self.ctrader).await?;
+        self.verify_trading_scope().await?;
 
-        if metrics_enabled() {
-            start_metrics_server(self.metrics.clone());
-        }
+        self.start_http_server();
+        self.start_grpc_api();
+        self.start_admin_console();
+        self.start_storage_schedulers();
+        self.start_telegram();
 
         match self.ctrader.get_trader().await {
             Ok(trader) => {
@@ -230,7 +838,10 @@ impl TradingBot {
                 "Account balance: {:.2} (money_digits={})",
                 balance, money_digits
             );
-            self.strategy.update_balance(balance);
+            self.strategy.core_mut().update_balance(balance);
+            if let Some(monitor) = &mut self.balance_monitor {
+                monitor.observe(balance, 0.0, Utc::now());
+            }
             }
             Err(err) => {
                 warn!(
@@ -292,26 +903,88 @@ impl TradingBot {
             }
         }
 
+        match self.ctrader.get_symbol_classification(symbol_id).await {
+            Ok(class) => {
+                info!(
+                    "Symbol classification: category={:?} asset_class={:?}",
+                    class.category, class.asset_class
+                );
+                self.symbol_class = Some(class);
+            }
+            Err(err) => warn!(
+                "Failed to fetch symbol category/asset class for {}: {}",
+                symbol_name, err
+            ),
+        }
+
+        if self.size_guard.max_lots.is_some()
+            && self.symbol_meta.as_ref().and_then(|m| m.lot_size).is_none()
+        {
+            warn!("Broker gave no lot size for {}: MAX_ORDER_LOTS is not enforced", symbol_name);
+        }
+        // Lot size is in volume units (base units × 100)
+        let units_per_lot = self
+            .symbol_meta
+            .as_ref()
+            .and_then(|m| m.lot_size)
+            .map(|lot_size| lot_size as f64 / 100.0);
+        if matches!(self.config.trading.sizing, SizingPolicy::FixedLots { .. })
+            && units_per_lot.is_none()
+        {
+            warn!(
+                "Broker gave no lot size for {}: fixed-lot sizing cannot open positions",
+                symbol_name
+            );
+        }
+        self.strategy.core_mut().set_units_per_lot(units_per_lot);
+
         info!("🌴 Trading {} with symbol ID: {}", symbol_name, symbol_id);
 
-        if !self.config.bot.dry_run {
-            self.reconcile_positions().await?;
+        if self.leader.is_some() {
+            // Startup reconciliation runs when this node wins the lease
+            self.update_leadership().await;
+        } else if !self.config.bot.dry_run {
+            self.reconcile_positions(true).await?;
         } else {
             info!("Skipping broker reconciliation in dry_run mode");
         }
+        self.warm_up_indicators().await;
+        if let Some(config) = TickRecorderConfig::from_env()? {
+            // Subscribed first so the recording starts with the first spot
+            start_tick_recorder(
+                TickRecorder::new(config, self.config.trading.symbol.clone()),
+                self.symbol_id,
+                self.ctrader.subscribe_spots(),
+            );
+        }
         self.ctrader.subscribe_to_symbol(self.symbol_id).await?;
         self.wait_for_initial_price(30).await?;
+        self.subscribe_cross_markets().await;
 
         // QUICK_TEST mode: force a BUY and SELL trade, report results, then exit
         if env::var("QUICK_TEST").ok().map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false) {
+            if !self.arming.allows_orders(Utc::now()) {
+                return Err(BotError::Config(
+                    "QUICK_TEST places orders right away; set LIVE_ARMING_REQUIRED=false to run it"
+                        .to_string(),
+                ));
+            }
             return self.run_quick_test().await;
         }
 
-        self.run_immediate_test_trades().await?;
+        if !self.is_standby() {
+            self.run_immediate_test_trades().await?;
+        }
 
+        // Subscribed before the first status so no change falls in between
+        let mut connection_changes = self.ctrader.subscribe_connection_changes();
+        let state = self.ctrader.connection_state();
         self.event_channel
             .publish(MarketEvent::ConnectionStatus {
-                connected: true,
+                connected: state == ConnectionState::Connected,
+                state,
+                attempt: None,
+                duration_secs: None,
                 message: "Connected to cTrader".to_string(),
                 timestamp: Utc::now(),
             })
@@ -320,6 +993,21 @@ impl TradingBot {
         let mut ticker = interval(Duration::from_secs(self.config.bot.cycle_interval_secs));
         let mut reconcile_interval = interval(Duration::from_secs(300));
         reconcile_interval.tick().await;
+        let mut leader_interval = interval(
+            self.leader
+                .as_ref()
+                .map(|l| l.heartbeat_interval())
+                .unwrap_or(Duration::from_secs(3600)),
+        );
+        leader_interval.tick().await;
+        let mut secondary_interval = interval(
+            self.secondary_feed
+                .as_ref()
+                .map(|feed| feed.config().poll_interval)
+                .unwrap_or(Duration::from_secs(3600)),
+        );
+        let mut spots = self.ctrader.subscribe_spots();
+        let mut reconnect_trips = self.ctrader.subscribe_reconnect_trips();
 
         let shutdown = tokio::signal::ctrl_c();
         tokio::pin!(shutdown);
@@ -330,33 +1018,74 @@ impl TradingBot {
                     info!("Received shutdown signal");
                     break;
                 }
+                _ = leader_interval.tick(), if self.leader.is_some() => {
+                    self.update_leadership().await;
+                }
+                _ = secondary_interval.tick(), if self.secondary_feed.is_some() => {
+                    self.check_secondary_feed().await;
+                }
+                Some(command) = self.emergency_rx.recv() => {
+                    self.handle_emergency(command).await;
+                }
+                spot = spots.recv() => match spot {
+                    Ok(price) => self.on_spot(price).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Spot stream lagged; skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {}
+                },
+                Ok(trip) = reconnect_trips.recv() => {
+                    self.on_reconnect_trip(trip).await;
+                }
+                Ok(change) = connection_changes.recv() => {
+                    self.on_connection_change(change).await;
+                }
                 _ = reconcile_interval.tick() => {
-                    if !self.config.bot.dry_run {
-                        if let Err(err) = self.reconcile_positions().await {
+                    if !self.config.bot.dry_run && !self.is_standby() {
+                        if let Err(err) = self.reconcile_positions(false).await {
                             warn!("Reconciliation error: {}", err);
                         }
+                        self.check_balance().await;
                     }
                 }
                 _ = ticker.tick() => {
+                    // A lost acknowledgement holds further commands on its
+                    // position until the broker state is read again
+                    if self.ctrader.take_reconcile_request() && !self.is_standby() {
+                        if let Err(err) = self.reconcile_positions(false).await {
+                            warn!("Reconciliation after a lost acknowledgement failed: {}", err);
+                        }
+                    }
+                    self.expire_arming().await;
+                    self.maybe_refresh_blackout_feed().await;
+                    if !self.is_standby() {
+                        self.maybe_send_market_brief().await;
+                        self.maybe_roll_up_daily_stats();
+                        self.check_blackout().await;
+                    }
+                    // Before the price fetch so a dead feed is still reported
+                    self.maybe_send_heartbeat().await;
+
                     let price = match self.ctrader.get_price(self.symbol_id).await {
                         Ok(price) => price,
                         Err(err) => {
                             warn!("Failed to fetch price: {}", err);
+                            // The client's reader task owns reconnection
                             if should_retry_ctrader(&err) {
-                                warn!("Attempting reconnect after price error");
-                                let _ = self.ctrader.disconnect().await;
-                                if connect_with_retry(&self.ctrader).await.is_ok()
-                                    && authenticate_with_retry(&self.ctrader).await.is_ok()
-                                {
-                                    continue;
-                                }
+                                self.ctrader
+                                    .request_reconnect(&format!("price error: {}", err));
                             }
                             continue;
                         }
                     };
 
-                    let mid_price = (price.bid + price.ask) / 2.0;
-                    let tick = Tick::new(price.timestamp, mid_price);
+                    if !self.price_is_fresh(&price).await {
+                        continue;
+                    }
+                    if let Some(skew_ms) = price.clock_skew_ms {
+                        self.record_clock_skew(skew_ms);
+                    }
+                    let tick = Tick::from_quote(price.timestamp, price.bid, price.ask);
                     self.process_tick(tick).await?;
                 }
             }
@@ -366,6 +1095,58 @@ impl TradingBot {
         Ok(())
     }
 
+    /// Whether the cycle may act on `price`. The feed going stale and
+    /// coming back each raise one alert.
+    async fn price_is_fresh(&mut self, price: &Price) -> bool {
+        let (level, message, fresh) = match self.stale_price.check(price.age()) {
+            PriceFreshness::Fresh => return true,
+            PriceFreshness::Stale { first: false, .. } => return false,
+            PriceFreshness::Stale { age, first: true } => (
+                crate::modules::trading::AlertLevel::Warning,
+                format!(
+                    "Price feed stale: last tick {}s ago; trading halted until ticks resume",
+                    age.as_secs()
+                ),
+                false,
+            ),
+            PriceFreshness::Recovered => (
+                crate::modules::trading::AlertLevel::Info,
+                "Price feed resumed; trading continues".to_string(),
+                true,
+            ),
+        };
+
+        if fresh {
+            info!("{}", message);
+        } else {
+            warn!("{}", message);
+        }
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level,
+                message,
+                timestamp: Utc::now(),
+            })
+            .await;
+        fresh
+    }
+
+    /// Publish local-vs-broker clock skew, warning once per excursion
+    fn record_clock_skew(&mut self, skew_ms: i64) {
+        self.metrics.with_metrics_mut(|m| m.update_clock_skew(skew_ms));
+
+        let excessive = skew_ms.abs() > CLOCK_SKEW_WARN_MS;
+        if excessive && !self.clock_skew_warned {
+            warn!(
+                "Local clock differs from broker spot time by {}ms; candles use server time",
+                skew_ms
+            );
+        } else if !excessive && self.clock_skew_warned {
+            info!("Clock skew back within {}ms ({}ms)", CLOCK_SKEW_WARN_MS, skew_ms);
+        }
+        self.clock_skew_warned = excessive;
+    }
+
     async fn wait_for_initial_price(&self, timeout_secs: u64) -> Result<()> {
         let timeout_duration = Duration::from_secs(timeout_secs);
         let start = Instant::now();
@@ -417,7 +1198,7 @@ impl TradingBot {
                 }
             };
             let entry_price = (price.bid + price.ask) / 2.0;
-            if let Err(err) = self.execute_trade(side, entry_price).await {
+            if let Err(err) = self.execute_trade(side, entry_price, EntryKind::New).await {
                 warn!("Test trade {:?} failed: {}", side, err);
             }
             sleep(Duration::from_secs(delay_secs)).await;
@@ -454,6 +1235,9 @@ impl TradingBot {
         info!("  Will place BUY + SELL trades and exit");
         info!("========================================");
 
+        // Parsed first so a typo stops the test before any order is placed
+        let scenarios = BreakerScenario::from_env()?;
+
         let hold_secs: u64 = env::var("QUICK_TEST_HOLD_SECS")
             .ok()
             .and_then(|v| v.parse().ok())
@@ -526,18 +1310,34 @@ impl TradingBot {
                 symbol_id: self.symbol_id,
                 side: trade_side,
                 volume,
+                order_type: ProtoOAOrderType::Market,
+                limit_price: None,
+                stop_price: None,
+                expiration: None,
                 stop_loss: Some(sl),
                 take_profit: Some(tp),
                 relative_stop_loss: self.relative_distance(entry, sl),
                 relative_take_profit: self.relative_distance(entry, tp),
+                trailing_stop_loss: false,
                 label: Some("QuickTest".to_string()),
+                comment: None,
             };
 
+            // Same gate as strategy entries (cTrader volume is base units × 100)
+            if !self.order_allowed(*side, entry, volume as f64 / 100.0, volume).await {
+                error!("[QUICK TEST] {:?} order refused by the size caps or risk limits", side);
+                continue;
+            }
+
             info!("[QUICK TEST] Placing {:?} at {:.5} SL={:.5} TP={:.5} vol={}", side, entry, sl, tp, volume);
 
             match self.ctrader.place_order(ticket).await {
-                Ok((order_id, position_id)) => {
-                    info!("[QUICK TEST] Order filled: order_id={} position_id={}", order_id, position_id);
+                Ok(placement) => {
+                    let position_id = placement.position_id;
+                    info!(
+                        "[QUICK TEST] Order {}: order_id={} position_id={}",
+                        placement.status, placement.order_id, position_id
+                    );
 
                     // Wait for the specified hold time
                     info!("[QUICK TEST] Holding for {}s...", hold_secs);
@@ -597,15 +1397,87 @@ impl TradingBot {
         }
         info!("========================================");
 
+        let outcomes = self.run_breaker_scenarios(&scenarios).await;
+
         // Close any remaining positions
         if let Err(err) = self.close_all_positions("quick_test_cleanup").await {
             warn!("[QUICK TEST] Cleanup failed: {}", err);
         }
 
         self.shutdown().await?;
+        let failed: Vec<String> = outcomes
+            .iter()
+            .filter(|outcome| outcome.failed())
+            .map(|outcome| outcome.scenario.to_string())
+            .collect();
+        if !failed.is_empty() {
+            return Err(BotError::Trading(format!(
+                "breaker scenario(s) did not block orders: {}",
+                failed.join(", ")
+            )));
+        }
         Ok(())
     }
 
+    /// Trip each breaker with synthetic trade results (QUICK_TEST_SCENARIOS)
+    /// and push a buy signal through the normal entry path: it must be
+    /// blocked by the circuit breakers. The probe flag skips closes on the
+    /// signal and the breaker alert, and withholds a signal that gets past
+    /// the filters, so no order is built even if a breaker is broken. The
+    /// risk state is put back after each scenario.
+    async fn run_breaker_scenarios(
+        &mut self,
+        scenarios: &[BreakerScenario],
+    ) -> Vec<ScenarioOutcome> {
+        let mut outcomes = Vec::new();
+        for &scenario in scenarios {
+            info!("[QUICK TEST] Breaker scenario: {}", scenario);
+            let price = match self.ctrader.get_price(self.symbol_id).await {
+                Ok(p) => (p.bid + p.ask) / 2.0,
+                Err(err) => {
+                    warn!("[QUICK TEST] Failed to get price: {}; using the last one", err);
+                    self.last_price.unwrap_or_default()
+                }
+            };
+            let candle = Candle {
+                timestamp: Utc::now(),
+                timeframe: self.candle_builder.timeframe(),
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: 0,
+                avg_spread: None,
+                close_spread: None,
+            };
+
+            let saved = self.strategy.core().risk_state().clone();
+            scenario.inject(self.strategy.core_mut());
+            let orders_before = self.orders_sent;
+            let mut decision = SignalDecision::new(Signal::Buy).with_side(OrderSide::Buy);
+            self.breaker_probe = true;
+            let result = self.act_on_signal(&candle, &mut decision).await;
+            self.breaker_probe = false;
+            self.strategy.core_mut().restore_risk_state(saved);
+
+            let orders_sent = self.orders_sent - orders_before;
+            let outcome = match result {
+                Ok(()) => ScenarioOutcome::judge(scenario, orders_sent, &decision.blockers),
+                Err(err) => ScenarioOutcome {
+                    scenario,
+                    verdict: ScenarioVerdict::Failed(format!("entry path failed: {}", err)),
+                },
+            };
+            if outcome.failed() {
+                error!("[QUICK TEST] {}", outcome);
+            } else {
+                info!("[QUICK TEST] {}", outcome);
+            }
+            outcomes.push(outcome);
+        }
+        outcomes
+    }
+
     /// Offline dry-run mode: runs without cTrader connection using synthetic prices.
     /// Useful for testing the full trading pipeline without OAuth credentials.
     async fn run_offline_dry_run(&mut self) -> Result<()> {
@@ -617,9 +1489,11 @@ impl TradingBot {
         info!("  Using synthetic price data");
         info!("========================================");
 
-        if metrics_enabled() {
-            start_metrics_server(self.metrics.clone());
-        }
+        self.start_http_server();
+        self.start_grpc_api();
+        self.start_admin_console();
+        self.start_storage_schedulers();
+        self.start_telegram();
 
         self.symbol_id = 1; // synthetic symbol ID
         let base_price: f64 = 4200.0; // typical FCPO price in MYR
@@ -637,7 +1511,18 @@ impl TradingBot {
                     info!("Received shutdown signal");
                     break;
                 }
+                Some(command) = self.emergency_rx.recv() => match command {
+                    EmergencyCommand::CancelAll { reply, .. } => {
+                        warn!("Cancel-all ignored: no broker connection in offline dry-run");
+                        if let Some(reply) = reply {
+                            let _ = reply.send(Err("no broker connection in offline dry-run".to_string()));
+                        }
+                    }
+                    command => self.handle_emergency(command).await,
+                },
                 _ = ticker.tick() => {
+                    self.maybe_roll_up_daily_stats();
+                    self.maybe_send_heartbeat().await;
                     cycle += 1;
                     // Random walk: ±0.5% per tick
                     let change = ((cycle as f64 * 1.618).sin() * 0.005) * base_price;
@@ -658,102 +1543,1331 @@ impl TradingBot {
         Ok(())
     }
 
-    /// Process a single tick.
-    async fn process_tick(&mut self, tick: Tick) -> Result<()> {
-        self.last_price = Some(tick.price);
+    /// Replay recorded ticks through `process_tick` in place of the broker
+    /// feed, paced by `speed`; stops after the last tick. Dry-run only:
+    /// entries and exits go through the simulated broker.
+    pub async fn run_replay(&mut self, ticks: Vec<Tick>, speed: ReplaySpeed) -> Result<()> {
+        if !self.config.bot.dry_run {
+            return Err(BotError::Config("replay only runs in dry-run mode".to_string()));
+        }
+        if self.leader.is_some() {
+            return Err(BotError::Config(
+                "replay does not run with LEADER_ELECTION_ENABLED".to_string(),
+            ));
+        }
+        let (Some(first), Some(last)) = (ticks.first(), ticks.last()) else {
+            return Err(BotError::Config("replay file holds no ticks".to_string()));
+        };
+        info!("========================================");
+        info!("  REPLAY MODE ({} ticks at {})", ticks.len(), speed);
+        info!("  {} to {}", first.timestamp, last.timestamp);
+        info!("========================================");
 
-        self.event_channel
-            .publish(MarketEvent::PriceTick {
-                symbol_id: self.symbol_id,
-                symbol: self.config.trading.symbol.clone(),
-                bid: tick.price,
-                ask: tick.price,
-                spread: 0.0,
-                timestamp: tick.timestamp,
-            })
-            .await;
+        self.start_http_server();
+        self.symbol_id = 1; // synthetic symbol ID, as in offline dry-run
 
-        self.strategy.update_price(tick.price);
-        self.check_exits().await?;
+        let total = ticks.len();
+        let mut replayed = 0;
+        let mut previous: Option<DateTime<Utc>> = None;
+        let shutdown = tokio::signal::ctrl_c();
+        tokio::pin!(shutdown);
 
-        if let Some(candle) = self.candle_builder.add_tick(tick) {
-            self.event_channel
-                .publish(MarketEvent::BarClosed {
-                    symbol_id: self.symbol_id,
-                    symbol: self.config.trading.symbol.clone(),
-                    timeframe: candle.timeframe.to_string(),
-                    open: candle.open,
-                    high: candle.high,
-                    low: candle.low,
-                    close: candle.close,
-                    volume: candle.volume as f64,
-                    timestamp: candle.timestamp,
-                })
-                .await;
+        for tick in ticks {
+            let pause = previous.and_then(|at| speed.wait_between(at, tick.timestamp));
+            tokio::select! {
+                biased;
+                _ = &mut shutdown => {
+                    info!("Received shutdown signal");
+                    break;
+                }
+                _ = async {
+                    if let Some(pause) = pause {
+                        sleep(pause).await;
+                    }
+                } => {}
+            }
+            previous = Some(tick.timestamp);
 
-            self.process_signal(&candle).await?;
+            if let Err(err) = self.process_tick(tick).await {
+                warn!("Tick processing error: {}", err);
+            }
+            replayed += 1;
+            if replayed % 10_000 == 0 {
+                info!("Replayed {}/{} ticks", replayed, total);
+            }
         }
 
+        let (pnl, win_rate) = self
+            .metrics
+            .with_metrics(|m| (m.total_pnl(), m.win_rate()));
+        info!(
+            "Replay finished: {}/{} ticks, P&L {:.2}, win rate {:.1}%, {} position(s) still open",
+            replayed,
+            total,
+            pnl,
+            win_rate,
+            self.strategy.core().get_open_positions().len()
+        );
         Ok(())
     }
 
-    /// Check for position exits.
-    async fn check_exits(&mut self) -> Result<()> {
-        let price = match self.last_price {
-            Some(price) => price,
-            None => return Ok(()),
-        };
-
-        let positions: Vec<_> = self.strategy.get_open_positions().to_vec();
-        for position in positions {
-            if let Some(reason) = self.strategy.check_position_exit(&position, price) {
-                info!("Closing position {} due to {:?}", position.id, reason);
-
-                if !self.config.bot.dry_run {
-                    let position_id = match position.id.parse::<i64>() {
-                        Ok(id) => id,
+    /// Start the metrics / export / control / Grafana / web dashboard / event
+    /// stream HTTP server when any is enabled
+    fn start_http_server(&self) {
+        let exports = export_api_enabled()
+            .then(|| ExportSources::from_env(self.position_db.clone()))
+            .flatten();
+        let control = ControlApi::from_env(self.emergency.clone());
+        let grafana = GrafanaApi::from_env(
+            self.position_db.clone(),
+            &self.config.trading.symbol,
+            self.config.trading.initial_balance,
+        );
+        let web = WebDashboard::from_env(
+            self.metrics.clone(),
+            self.emergency.clone(),
+            &self.config.trading.symbol,
+        );
+        let stream = EventStream::from_env(self.event_channel.clone());
+        if !metrics_enabled()
+            && exports.is_none()
+            && control.is_none()
+            && grafana.is_none()
+            && web.is_none()
+            && stream.is_none()
+        {
+            return;
+        }
+        if let Some(web) = &web {
+            web.start_alert_feed(self.event_channel.clone());
+        }
+        start_metrics_server(
+            self.metrics.clone(),
+            exports,
+            control,
+            grafana,
+            web,
+            stream,
+        );
+    }
+
+    /// Start the gRPC control server when GRPC_API_ENABLED is set
+    fn start_grpc_api(&self) {
+        if let Some(config) = GrpcApiConfig::from_env() {
+            start_grpc_server(config, self.emergency.clone());
+        }
+    }
+
+    /// Start the local admin console when ADMIN_SOCKET_PATH is set
+    fn start_admin_console(&self) {
+        if let Some(path) = admin_socket_path() {
+            start_admin_socket(path, self.emergency.clone());
+        }
+    }
+
+    /// Push fills and critical alerts to Telegram and, with
+    /// TELEGRAM_COMMANDS_ENABLED, accept commands from the chat
+    fn start_telegram(&self) {
+        let Some(telegram) = &self.telegram else {
+            return;
+        };
+        let control = telegram_commands_enabled().then(|| self.emergency.clone());
+        start_telegram_bot(telegram.clone(), self.event_channel.clone(), control);
+    }
+
+    /// Start scheduled local backups (`BACKUP_INTERVAL_HOURS`) and off-host
+    /// archives (`ARCHIVE_BACKEND`) when configured
+    fn start_storage_schedulers(&self) {
+        if self.observer_only {
+            return;
+        }
+        if let Some(db) = &self.position_db {
+            start_backup_scheduler(db.clone(), BackupConfig::from_env());
+        }
+        if let Some(config) = ArchiveConfig::from_env() {
+            start_archive_scheduler(Archiver::new(config, self.position_db.clone()));
+        }
+    }
+
+    /// Fill `daily_stats` for past days that have closed trades but no row yet.
+    fn backfill_daily_stats(&mut self) {
+        let today = Utc::now().date_naive();
+        self.last_stats_rollup = Some(today);
+
+        let Some(db) = &self.position_db else {
+            return;
+        };
+        match db.backfill_daily_stats(&today.to_string()) {
+            Ok(days) if !days.is_empty() => {
+                info!("Backfilled daily stats for {} day(s): {}", days.len(), days.join(", "))
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Daily stats backfill failed: {}", err),
+        }
+    }
+
+    /// Roll the finished day's closed trades into `daily_stats` once the UTC date changes.
+    fn maybe_roll_up_daily_stats(&mut self) {
+        let today = Utc::now().date_naive();
+        let Some(last) = self.last_stats_rollup.replace(today) else {
+            return;
+        };
+        if last == today {
+            return;
+        }
+
+        let Some(db) = &self.position_db else {
+            return;
+        };
+        match db.rollup_daily_stats(&last.to_string()) {
+            Ok(Some(stats)) => info!(
+                "Daily stats {}: {} trades, P&L {:.2}, win rate {:.1}%",
+                stats.date,
+                stats.total_trades,
+                stats.total_pnl,
+                stats.win_rate()
+            ),
+            Ok(None) => debug!("No closed trades on {}; no daily stats row", last),
+            Err(err) => warn!("Daily stats roll-up for {} failed: {}", last, err),
+        }
+        // Catch any days skipped while the bot was idle
+        if let Err(err) = db.backfill_daily_stats(&today.to_string()) {
+            warn!("Daily stats backfill failed: {}", err);
+        }
+    }
+
+    /// Generate, store and deliver the daily market brief once its hour is reached.
+    async fn maybe_send_market_brief(&mut self) {
+        let Some(schedule) = self.market_brief_schedule else {
+            return;
+        };
+        let now = Utc::now();
+        if !schedule.is_due(now, self.last_market_brief) {
+            return;
+        }
+        // Mark as done up front so a failing API is not retried every cycle
+        self.last_market_brief = Some(now.date_naive());
+
+        // Off the loop: a slow Perplexity call must not stall trading
+        let perplexity = Arc::clone(&self.perplexity);
+        let position_db = self.position_db.clone();
+        let telegram = self.telegram.clone();
+        tokio::spawn(async move {
+            let result =
+                send_market_brief(&perplexity, position_db.as_ref(), telegram.as_ref()).await;
+            if let Err(err) = result {
+                warn!("Daily market brief failed: {}", err);
+            }
+        });
+    }
+
+    /// Post the periodic alive summary to Telegram once its interval elapsed.
+    async fn maybe_send_heartbeat(&mut self) {
+        let Some(schedule) = self.heartbeat_schedule else {
+            return;
+        };
+        let now = Utc::now();
+        if !schedule.is_due(now, self.started_at, self.last_heartbeat) {
+            return;
+        }
+        self.last_heartbeat = Some(now);
+
+        let message = self.heartbeat_summary(now).to_message();
+        match &self.telegram {
+            Some(telegram) => {
+                if let Err(err) = telegram.send_message(&message).await {
+                    warn!("Failed to send heartbeat: {}", err);
+                }
+            }
+            None => info!("{}", message),
+        }
+    }
+
+    /// Current state for the heartbeat message
+    fn heartbeat_summary(&self, now: DateTime<Utc>) -> HeartbeatSummary {
+        let core = self.strategy.core();
+        let fallback_price = self.fallback_price(now);
+        let positions = core
+            .get_open_positions()
+            .iter()
+            .map(|position| HeartbeatPosition {
+                side: position.side.to_string(),
+                volume: position.volume,
+                entry_price: position.entry_price,
+                unrealized_pnl: fallback_price
+                    .or(self.last_price)
+                    .map(|mid| position.calculate_pnl(self.exit_quote(position.side, mid))),
+            })
+            .collect();
+
+        HeartbeatSummary {
+            symbol: self.config.trading.symbol.clone(),
+            uptime: now - self.started_at,
+            dry_run: self.config.bot.dry_run,
+            standby: self.is_standby(),
+            paused: self.paused,
+            positions,
+            daily_pnl: core.risk_state().daily_pnl,
+            daily_trades: core.risk_state().daily_trades,
+            last_signal: self.last_signal.to_string(),
+            last_price: self.last_price,
+            feed_age: self.last_tick_at.map(|at| now - at),
+            fallback_price,
+        }
+    }
+
+    /// Secondary feed price standing in for monitoring while the cTrader
+    /// feed is stale; never used for trading
+    fn fallback_price(&self, now: DateTime<Utc>) -> Option<f64> {
+        if !self.stale_price.is_stale() {
+            return None;
+        }
+        self.secondary_feed
+            .as_ref()
+            .and_then(|feed| feed.fallback_price(now))
+    }
+
+    /// Poll the secondary price source: alert when it and the cTrader price
+    /// drift apart (and when they agree again), or show its price on the
+    /// dashboard while the cTrader feed is stale
+    async fn check_secondary_feed(&mut self) {
+        let Some(feed) = &mut self.secondary_feed else {
+            return;
+        };
+        if let Err(err) = feed.poll().await {
+            warn!("Secondary price feed unavailable: {}", err);
+            return;
+        }
+        let now = Utc::now();
+        if self.stale_price.is_stale() {
+            // A stale cTrader price would only report the outage again
+            if let Some(price) = feed.fallback_price(now) {
+                debug!("cTrader feed stale; dashboard shows the secondary price {:.2}", price);
+                self.metrics.with_metrics_mut(|m| m.current_price = Some(price));
+            }
+            return;
+        }
+        let Some(primary) = self.last_price else {
+            return;
+        };
+        let (level, message) = match feed.cross_check(primary, now) {
+            Some(CrossCheck::Diverged { divergence, first: true }) => (
+                crate::modules::trading::AlertLevel::Warning,
+                format!("⚠️ Price feeds disagree: {}", divergence),
+            ),
+            Some(CrossCheck::Converged { percent }) => (
+                crate::modules::trading::AlertLevel::Info,
+                format!("Price feeds agree again ({:.2}% apart)", percent),
+            ),
+            _ => return,
+        };
+        if matches!(level, crate::modules::trading::AlertLevel::Warning) {
+            warn!("{}", message);
+        } else {
+            info!("{}", message);
+        }
+        if let Some(telegram) = &self.telegram {
+            if let Err(err) = telegram.send_message(&message).await {
+                warn!("Failed to send feed divergence alert: {}", err);
+            }
+        }
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level,
+                message,
+                timestamp: now,
+            })
+            .await;
+    }
+
+    /// Status for operator commands, built from the heartbeat summary
+    fn status_report(&self, now: DateTime<Utc>) -> StatusReport {
+        let summary = self.heartbeat_summary(now);
+        StatusReport {
+            symbol: summary.symbol.clone(),
+            dry_run: summary.dry_run,
+            standby: summary.standby,
+            paused: summary.paused,
+            uptime_secs: summary.uptime.num_seconds(),
+            open_positions: summary.positions.len(),
+            daily_pnl: summary.daily_pnl,
+            daily_trades: summary.daily_trades,
+            last_signal: summary.last_signal.clone(),
+            last_price: summary.last_price,
+            feed_age_secs: summary.feed_age.map(|age| age.num_seconds()),
+            arming: self.arming.status(now),
+            summary: summary.to_message(),
+        }
+    }
+
+    /// Exit check for a spot event between cycles. Only the quote is updated;
+    /// indicators and candles keep following the cycle.
+    async fn on_spot(&mut self, price: Price) {
+        if price.symbol_id != self.symbol_id || price.bid <= 0.0 || price.ask <= 0.0 {
+            return;
+        }
+        self.last_price = Some((price.bid + price.ask) / 2.0);
+        self.last_quote = Some((price.bid, price.ask));
+        self.last_tick_at = Some(Utc::now());
+
+        if self.is_standby() || self.strategy.core().get_open_positions().is_empty() {
+            return;
+        }
+        if let Err(err) = self.check_exits().await {
+            warn!("Tick exit check failed: {}", err);
+        }
+    }
+
+    /// Process a single tick.
+    async fn process_tick(&mut self, tick: Tick) -> Result<()> {
+        self.last_price = Some(tick.price);
+        self.last_quote = tick.bid.zip(tick.ask);
+        self.last_tick_at = Some(Utc::now());
+
+        self.event_channel
+            .publish(MarketEvent::PriceTick {
+                symbol_id: self.symbol_id,
+                symbol: self.config.trading.symbol.clone(),
+                bid: tick.bid.unwrap_or(tick.price),
+                ask: tick.ask.unwrap_or(tick.price),
+                spread: tick.spread().unwrap_or(0.0),
+                timestamp: tick.timestamp,
+            })
+            .await;
+
+        if !self.alert_rules.is_empty() {
+            let mut alerts = self
+                .alert_rules
+                .observe(AlertMetric::Price, tick.price, tick.timestamp);
+            if let Some(spread) = tick.spread() {
+                alerts.extend(
+                    self.alert_rules
+                        .observe(AlertMetric::Spread, spread, tick.timestamp),
+                );
+            }
+            self.raise_alerts(alerts).await;
+        }
+
+        self.strategy.on_price(tick.price);
+        self.refresh_crash_state();
+        if !self.is_standby() {
+            self.check_exits().await?;
+        }
+
+        let timeframe = self.candle_builder.timeframe();
+        match self.last_tick_time.replace(tick.timestamp) {
+            Some(previous) if is_feed_gap(previous, tick.timestamp, timeframe) => {
+                self.resync_after_gap(previous, tick.timestamp).await;
+            }
+            _ => {}
+        }
+
+        // The higher timeframe goes first so a candle closing on both sees
+        // the updated higher-timeframe RSI
+        if let Some(higher) = self.higher_timeframe.as_mut() {
+            if let Some(candle) = higher.builder.add_tick(tick) {
+                higher.update(&candle);
+                debug!(
+                    "{} candle close={:.5} RSI={:?}",
+                    candle.timeframe, candle.close, higher.rsi
+                );
+                self.persist_candle(&candle);
+            }
+        }
+
+        if let Some(candle) = self.candle_builder.add_tick(tick) {
+            self.persist_candle(&candle);
+            self.event_channel
+                .publish(MarketEvent::BarClosed {
+                    symbol_id: self.symbol_id,
+                    symbol: self.config.trading.symbol.clone(),
+                    timeframe: candle.timeframe.to_string(),
+                    open: candle.open,
+                    high: candle.high,
+                    low: candle.low,
+                    close: candle.close,
+                    volume: candle.volume as f64,
+                    timestamp: candle.timestamp,
+                })
+                .await;
+
+            self.process_signal(&candle).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Publish triggered alert rules and forward them to Telegram
+    async fn raise_alerts(&self, alerts: Vec<TriggeredAlert>) {
+        for alert in alerts {
+            info!("{}", alert.message);
+            self.event_channel
+                .publish(MarketEvent::Alert {
+                    level: alert.level,
+                    message: alert.message.clone(),
+                    timestamp: Utc::now(),
+                })
+                .await;
+            if let Some(telegram) = &self.telegram {
+                if let Err(err) = telegram.send_message(&alert.message).await {
+                    warn!("Failed to send alert: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Check for position exits.
+    async fn check_exits(&mut self) -> Result<()> {
+        let price = match self.last_price {
+            Some(price) => price,
+            None => return Ok(()),
+        };
+
+        let positions: Vec<_> = self.strategy.core().get_open_positions().to_vec();
+        self.close_retries.retain_open(&positions);
+        for position in positions {
+            let price = self.exit_quote(position.side, price);
+            if let Some(reason) = self.strategy.check_exit(&position, price) {
+                if !self.config.bot.dry_run {
+                    let position_id = match position.id.parse::<i64>() {
+                        Ok(id) => id,
                         Err(_) => {
                             warn!("Skipping close: invalid position id {}", position.id);
                             continue;
                         }
                     };
+                    // An unacknowledged command is still in flight: the
+                    // reconcile settles it before anything else is sent
+                    if self.ctrader.position_command_held(position_id) {
+                        debug!("Close of position {} waits for reconciliation", position.id);
+                        continue;
+                    }
+                    let now = Instant::now();
+                    if !self.close_retries.ready(&position.id, now) {
+                        continue;
+                    }
+                    info!("Closing position {} due to {:?}", position.id, reason);
                     let volume = (position.volume * 100.0) as i64;
-                    self.ctrader.close_position(position_id, volume).await?;
+                    if let Err(err) = self.ctrader.close_position(position_id, volume).await {
+                        let wait = self.close_retries.failed(&position.id, now);
+                        warn!(
+                            "Failed to close position {}: {}; next attempt in {}s",
+                            position.id,
+                            err,
+                            wait.as_secs()
+                        );
+                        continue;
+                    }
                     // Reconcile immediately after close
-                    if let Err(err) = self.reconcile_positions().await {
+                    if let Err(err) = self.reconcile_positions(false).await {
                         warn!("Post-close reconciliation failed: {}", err);
                     }
+                } else {
+                    info!("Closing position {} due to {:?}", position.id, reason);
+                }
+
+                self.record_local_close(&position, price, reason).await;
+            } else {
+                self.manage_open_position(&position, price).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stop management for a position that stays open: break-even first,
+    /// then the trailing stop on a later tick (it keeps the moved stop)
+    async fn manage_open_position(&mut self, position: &Position, price: f64) {
+        if self.trailing_stops.contains(&position.id) {
+            return;
+        }
+        if !self.break_even_stops.contains(&position.id) {
+            if let Some(stop_loss) = self.strategy.core().break_even_stop(position, price) {
+                self.move_stop_to_break_even(position, stop_loss).await;
+                return;
+            }
+        }
+        if self
+            .strategy
+            .core()
+            .trailing_activation_reached(position, price)
+        {
+            self.activate_trailing_stop(position).await;
+        }
+    }
+
+    /// Move the stop of a profitable position to break-even
+    /// (BREAK_EVEN_ACTIVATION_PERCENT). Attempted once per position.
+    async fn move_stop_to_break_even(&mut self, position: &Position, stop_loss: f64) {
+        self.break_even_stops.insert(position.id.clone());
+        if let Some(stop_loss) = self.amend_stop_loss(position, stop_loss, "break-even").await {
+            info!(
+                "Position {} in profit: stop moved to break-even {:.5}",
+                position.id, stop_loss
+            );
+        }
+    }
+
+    /// Tighten the stops of open positions to the structure level of the
+    /// candle just closed (STRUCTURE_STOP_MODE). Positions handed to a
+    /// broker-side trailing stop are left alone.
+    async fn update_structure_stops(&mut self, candle: &Candle) {
+        let updates: Vec<(Position, f64)> = self
+            .strategy
+            .core()
+            .get_open_positions()
+            .iter()
+            .filter(|p| !self.trailing_stops.contains(&p.id))
+            .filter_map(|p| {
+                // The quote the position would close on
+                let price = match p.side {
+                    OrderSide::Buy => candle.bid_close(),
+                    OrderSide::Sell => candle.ask_close(),
+                };
+                let stop_loss = self.strategy.core().structure_stop_update(p, price)?;
+                Some((p.clone(), stop_loss))
+            })
+            .collect();
+        for (position, stop_loss) in updates {
+            let previous = position.stop_loss.unwrap_or(f64::NAN);
+            if let Some(stop_loss) = self.amend_stop_loss(&position, stop_loss, "structure").await {
+                info!(
+                    "Position {} stop tightened to structure level {:.5} (was {:.5})",
+                    position.id, stop_loss, previous
+                );
+            }
+        }
+    }
+
+    /// Move the stop of `position` to `stop_loss` (normalized), keeping its
+    /// take profit: amended at the broker outside dry-run, then recorded
+    /// locally. The stop it moved to, `None` when the amend failed.
+    async fn amend_stop_loss(
+        &mut self,
+        position: &Position,
+        stop_loss: f64,
+        label: &str,
+    ) -> Option<f64> {
+        let stop_loss = self.normalize_price(stop_loss);
+        if self.config.bot.dry_run {
+            info!(
+                "[DRY RUN] Would move stop of position {} to {} {:.5}",
+                position.id, label, stop_loss
+            );
+        } else {
+            let Ok(position_id) = position.id.parse::<i64>() else {
+                warn!("Cannot move stop of position {}: no broker id", position.id);
+                return None;
+            };
+            let take_profit = position.take_profit.map(|tp| self.normalize_price(tp));
+            if let Err(err) = self
+                .ctrader
+                .amend_position_sltp(position_id, Some(stop_loss), take_profit, false)
+                .await
+            {
+                warn!(
+                    "Failed to move stop of position {} to {}: {}",
+                    position.id, label, err
+                );
+                return None;
+            }
+        }
+
+        self.strategy
+            .core_mut()
+            .position_manager_mut()
+            .set_stop_loss(&position.id, stop_loss);
+        Some(stop_loss)
+    }
+
+    /// Hand the stop of a profitable position to the broker as a trailing
+    /// stop (TRAILING_ACTIVATION_PERCENT). Attempted once per position.
+    async fn activate_trailing_stop(&mut self, position: &Position) {
+        self.trailing_stops.insert(position.id.clone());
+        if self.config.bot.dry_run {
+            info!(
+                "[DRY RUN] Would switch position {} to a trailing stop",
+                position.id
+            );
+            return;
+        }
+
+        let (Ok(position_id), Some(stop_loss)) = (position.id.parse::<i64>(), position.stop_loss)
+        else {
+            warn!(
+                "Cannot trail position {}: no broker id or stop loss",
+                position.id
+            );
+            return;
+        };
+        let stop_loss = self.normalize_price(stop_loss);
+        let take_profit = position.take_profit.map(|tp| self.normalize_price(tp));
+        match self
+            .ctrader
+            .amend_position_sltp(position_id, Some(stop_loss), take_profit, true)
+            .await
+        {
+            Ok(()) => info!(
+                "Position {} in profit: stop {:.5} now trails the price",
+                position.id, stop_loss
+            ),
+            Err(err) => warn!(
+                "Failed to switch position {} to a trailing stop: {}",
+                position.id, err
+            ),
+        }
+    }
+
+    /// Book a closed position locally: strategy, database, trade log, metrics, events
+    /// In dry-run the exit is filled by the simulated broker instead, which
+    /// also charges commission and swap
+    async fn record_local_close(&mut self, position: &Position, price: f64, reason: CloseReason) {
+        self.trailing_stops.remove(&position.id);
+        self.break_even_stops.remove(&position.id);
+        let (price, costs) = if self.config.bot.dry_run {
+            let mid = self.last_price.unwrap_or(price);
+            let close = self
+                .simulated_broker
+                .close(position, mid, self.quote_spread(), Utc::now());
+            info!(
+                "[DRY RUN] Position {} closed at {:.5}: commission {:.2}, swap {:.2}",
+                position.id, close.price, close.commission, close.swap
+            );
+            (self.normalize_price(close.price), close.costs())
+        } else {
+            (price, 0.0)
+        };
+        let closed = self
+            .strategy
+            .core_mut()
+            .close_position_with_costs(&position.id, price, reason, costs);
+        if let Some(pnl) = closed {
+            self.persist_close_position(&position.id, price, reason);
+            self.trade_logger.log_close(
+                &Utc::now().to_rfc3339(),
+                &position.id,
+                price,
+                pnl,
+                &format!("{:?}", reason),
+            );
+            let kelly = self.strategy.core().kelly_estimate();
+            self.metrics.with_metrics_mut(|m| {
+                let _ = m.close_trade_with_costs(&position.id, price, costs);
+                m.update_kelly(kelly);
+            });
+            self.event_channel
+                .publish(MarketEvent::PositionClosed {
+                    position_id: position.id.parse().unwrap_or_default(),
+                    symbol_id: self.symbol_id,
+                    realized_pnl: pnl,
+                    close_reason: reason.to_string(),
+                    timestamp: Utc::now(),
+                })
+                .await;
+        }
+    }
+
+    /// Run an operator panic command queued on the emergency handle
+    async fn handle_emergency(&mut self, command: EmergencyCommand) {
+        match command {
+            EmergencyCommand::CancelAll { flatten, reply } => {
+                let outcome = self.cancel_all(flatten).await;
+                if let Err(err) = &outcome {
+                    error!("Cancel-all failed: {}", err);
+                }
+                if let Some(reply) = reply {
+                    let _ = reply.send(outcome.map_err(|e| e.to_string()));
+                }
+            }
+            EmergencyCommand::SetPaused(paused) => {
+                if paused != self.paused {
+                    warn!(
+                        "New entries {} by operator",
+                        if paused { "paused" } else { "resumed" }
+                    );
+                }
+                self.paused = paused;
+            }
+            EmergencyCommand::Status { reply } => {
+                let _ = reply.send(self.status_report(Utc::now()));
+            }
+            EmergencyCommand::Positions { reply } => {
+                let _ = reply.send(self.strategy.core().get_open_positions().to_vec());
+            }
+            EmergencyCommand::Orders { reply } => {
+                let _ = reply.send(self.pending_orders.orders().cloned().collect());
+            }
+            EmergencyCommand::ClosePosition { position_id, reply } => {
+                let outcome = self.close_position_by_operator(&position_id).await;
+                if let Err(err) = &outcome {
+                    warn!("Operator close of position {} failed: {}", position_id, err);
+                }
+                let _ = reply.send(outcome.map_err(|e| e.to_string()));
+            }
+            EmergencyCommand::RefreshSentiment { reply } => {
+                self.sentiment_cache.write().await.invalidate();
+                let sentiment = self.fetch_current_sentiment().await;
+                info!(
+                    "Sentiment refreshed by operator: {} ({})",
+                    sentiment.score, sentiment.source
+                );
+                self.last_sentiment = sentiment.clone();
+                let _ = reply.send(sentiment);
+            }
+            EmergencyCommand::Reload { reply } => {
+                let _ = reply.send(self.reload_settings());
+            }
+            EmergencyCommand::Arm { ttl_minutes, reply } => {
+                let _ = reply.send(self.arm(ttl_minutes));
+            }
+            EmergencyCommand::Disarm { reply } => {
+                let now = Utc::now();
+                if self.arming.disarm() {
+                    warn!("Live trading disarmed by operator");
+                }
+                let _ = reply.send(self.arming.status(now));
+            }
+            EmergencyCommand::Features { reply } => {
+                let _ = reply.send(self.strategy.core().features().clone());
+            }
+            EmergencyCommand::SetFeature {
+                feature,
+                enabled,
+                reply,
+            } => {
+                if self.strategy.core_mut().set_feature(feature, enabled) {
+                    warn!(
+                        "Strategy feature {} {} by operator",
+                        feature,
+                        if enabled { "enabled" } else { "disabled" }
+                    );
+                }
+                let _ = reply.send(self.strategy.core().features().clone());
+            }
+        }
+    }
+
+    /// Arm live orders for `ttl_minutes` (the configured default when `None`)
+    fn arm(&mut self, ttl_minutes: Option<i64>) -> std::result::Result<ArmingStatus, String> {
+        let now = Utc::now();
+        if !self.arming.is_required() {
+            return Err("arming is not required (LIVE_ARMING_REQUIRED=false)".to_string());
+        }
+        let until = self
+            .arming
+            .arm(now, ttl_minutes.map(ChronoDuration::minutes))
+            .map_err(|err| {
+                warn!("Arm request refused: {}", err);
+                err
+            })?;
+        warn!("Live trading ARMED by operator until {}", until.format("%Y-%m-%d %H:%M UTC"));
+        Ok(self.arming.status(now))
+    }
+
+    /// Re-read the broker balance and compare the change with the deals
+    /// executed since the last reading (BALANCE_MONITOR_ENABLED); an
+    /// unexplained jump raises a critical alert and pauses new entries
+    async fn check_balance(&mut self) {
+        let Some(previous) = self.balance_monitor.as_ref().map(|m| m.last().copied()) else {
+            return;
+        };
+        let now = Utc::now();
+        let balance = match self.ctrader.get_balance().await {
+            Ok(balance) => balance,
+            Err(err) => {
+                warn!("Balance check: balance unavailable: {}", err);
+                return;
+            }
+        };
+        let explained = match previous {
+            Some(previous) => match self
+                .ctrader
+                .deal_balance_changes(previous.at.timestamp_millis(), now.timestamp_millis())
+                .await
+            {
+                Ok(explained) => explained,
+                Err(err) => {
+                    // Keep the old baseline; the next pass covers this window too
+                    warn!("Balance check: deal history unavailable: {}", err);
+                    return;
+                }
+            },
+            None => 0.0,
+        };
+
+        let Some(monitor) = self.balance_monitor.as_mut() else {
+            return;
+        };
+        let anomaly = monitor.observe(balance, explained, now);
+        let pause = monitor.config().pause_on_anomaly;
+        // Sizing and the daily-loss limit follow the broker balance
+        self.strategy.core_mut().update_balance(balance);
+
+        let Some(anomaly) = anomaly else {
+            debug!("Balance check: {:.2} ({:+.2} from deals)", balance, explained);
+            return;
+        };
+        let mut message = format!("Unexplained balance change: {}", anomaly);
+        if pause && !self.paused {
+            self.paused = true;
+            message.push_str("; new entries paused until an operator resumes the bot");
+        }
+        error!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Critical,
+                message: message.clone(),
+                timestamp: now,
+            })
+            .await;
+        if let Some(telegram) = &self.telegram {
+            if let Err(err) = telegram.send_message(&message).await {
+                warn!("Failed to send balance alert: {}", err);
+            }
+        }
+    }
+
+    /// Publish a connection state change or reconnect attempt, for the
+    /// dashboard and notifiers
+    async fn on_connection_change(&mut self, change: ConnectionChange) {
+        self.event_channel
+            .publish(MarketEvent::ConnectionStatus {
+                connected: change.to == ConnectionState::Connected,
+                state: change.to,
+                attempt: change.attempt,
+                duration_secs: Some(change.duration.as_secs_f64()),
+                message: change.to_string(),
+                timestamp: change.at,
+            })
+            .await;
+    }
+
+    /// The reconnect breaker opened: the broker keeps refusing connections
+    async fn on_reconnect_trip(&mut self, trip: ReconnectTrip) {
+        let message = format!("⛔ cTrader unreachable: {}", trip);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Critical,
+                message: message.clone(),
+                timestamp: Utc::now(),
+            })
+            .await;
+        if let Some(telegram) = &self.telegram {
+            if let Err(err) = telegram.send_message(&message).await {
+                warn!("Failed to send reconnect alert: {}", err);
+            }
+        }
+    }
+
+    /// Disarm once the arming window has ended and tell the operators
+    async fn expire_arming(&mut self) {
+        let Some(until) = self.arming.expire(Utc::now()) else {
+            return;
+        };
+        let message = format!(
+            "Live trading disarmed: arming expired at {}; signals are logged only until re-armed",
+            until.format("%H:%M UTC")
+        );
+        warn!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Warning,
+                message: message.clone(),
+                timestamp: Utc::now(),
+            })
+            .await;
+        if let Some(telegram) = &self.telegram {
+            if let Err(err) = telegram.send_message(&message).await {
+                warn!("Failed to send disarm alert: {}", err);
+            }
+        }
+    }
+
+    /// Re-read the news blackout ICS feed every `FEED_REFRESH_INTERVAL`; a
+    /// failed read keeps the previous windows and is retried next cycle
+    async fn maybe_refresh_blackout_feed(&mut self) {
+        let now = Utc::now();
+        let due = self
+            .last_blackout_refresh
+            .is_none_or(|last| now - last >= FEED_REFRESH_INTERVAL);
+        let Some(schedule) = self.strategy.core_mut().blackout_mut() else {
+            return;
+        };
+        if schedule.ics_source.is_none() || !due {
+            return;
+        }
+        match schedule.refresh_feed().await {
+            Ok(count) => {
+                info!("News blackout feed: {} events", count);
+                self.last_blackout_refresh = Some(now);
+            }
+            Err(err) => warn!("Failed to read news blackout feed: {}", err),
+        }
+    }
+
+    /// Announce a news blackout once when it starts and, with
+    /// NEWS_BLACKOUT_FLATTEN, close open positions ahead of the release
+    async fn check_blackout(&mut self) {
+        let core = self.strategy.core();
+        let Some(window) = core.active_blackout().cloned() else {
+            return;
+        };
+        if self.blackout_started == Some(window.start) {
+            return;
+        }
+        self.blackout_started = Some(window.start);
+
+        let open: Vec<String> = core
+            .get_open_positions()
+            .iter()
+            .map(|p| p.id.clone())
+            .collect();
+        let flatten = core.blackout().is_some_and(|s| s.flatten) && !open.is_empty();
+        let message = format!(
+            "📰 News blackout {}: no new entries{}",
+            window,
+            if flatten { ", closing open positions" } else { "" }
+        );
+        warn!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Warning,
+                message,
+                timestamp: Utc::now(),
+            })
+            .await;
+
+        if flatten {
+            for position_id in open {
+                let closed = self
+                    .close_position_now(&position_id, CloseReason::RiskLimit)
+                    .await;
+                if let Err(err) = closed {
+                    error!("News blackout close of position {} failed: {}", position_id, err);
+                }
+            }
+        }
+    }
+
+    /// Re-read `.env` and swap in the settings that are safe to change while
+    /// running: alert rules, heartbeat interval and market brief hour. Strategy
+    /// and risk parameters still need a restart.
+    fn reload_settings(&mut self) -> String {
+        let source = match dotenvy::dotenv_override() {
+            Ok(path) => path.display().to_string(),
+            Err(err) => {
+                warn!("Reload: .env not read ({}); using the process environment", err);
+                "process environment".to_string()
+            }
+        };
+        self.alert_rules = AlertRules::from_env();
+        self.heartbeat_schedule = HeartbeatSchedule::from_env();
+        if !self.observer_only {
+            self.market_brief_schedule = MarketBriefSchedule::from_env();
+        }
+        match StrategyFeatures::from_env() {
+            Ok(features) => self.strategy.core_mut().set_features(features),
+            Err(err) => warn!("Reload: strategy features kept: {}", err),
+        }
+
+        let message = format!(
+            "Reloaded from {}: {} alert rules, heartbeat {}, market brief {}, features {}",
+            source,
+            self.alert_rules.len(),
+            self.heartbeat_schedule
+                .map(|s| format!("every {}m", s.interval.num_minutes()))
+                .unwrap_or_else(|| "off".to_string()),
+            self.market_brief_schedule
+                .map(|s| format!("at {:02}:00 UTC", s.hour_utc))
+                .unwrap_or_else(|| "off".to_string()),
+            self.strategy.core().features().tag(),
+        );
+        info!("{}", message);
+        message
+    }
+
+    /// Close one position at market on operator request
+    async fn close_position_by_operator(&mut self, position_id: &str) -> Result<Position> {
+        warn!("Closing position {} on operator request", position_id);
+        self.close_position_now(position_id, CloseReason::Manual).await
+    }
+
+    /// Close one tracked position at market and record it locally
+    async fn close_position_now(
+        &mut self,
+        position_id: &str,
+        reason: CloseReason,
+    ) -> Result<Position> {
+        if self.observer_only || self.is_standby() {
+            return Err(BotError::Config(
+                "close refused: this instance does not own the account".to_string(),
+            ));
+        }
+        let position = self
+            .strategy
+            .core()
+            .get_open_positions()
+            .iter()
+            .find(|p| p.id == position_id)
+            .cloned()
+            .ok_or_else(|| BotError::Trading(format!("Position {} not found", position_id)))?;
+
+        if !self.config.bot.dry_run {
+            let broker_id = position.id.parse::<i64>().map_err(|_| {
+                BotError::Trading(format!("Invalid position id {}", position.id))
+            })?;
+            let volume = (position.volume * 100.0) as i64;
+            self.ctrader.close_position(broker_id, volume).await?;
+            if let Err(err) = self.reconcile_positions(false).await {
+                warn!("Post-close reconciliation failed: {}", err);
+            }
+        }
+
+        let price = self
+            .last_price
+            .map(|mid| self.exit_quote(position.side, mid))
+            .unwrap_or(position.entry_price);
+        self.record_local_close(&position, price, reason).await;
+        Ok(position)
+    }
+
+    /// Cancel every pending order at the broker and, with `flatten`, close
+    /// every open position; local books follow the broker's answers
+    async fn cancel_all(&mut self, flatten: bool) -> Result<CancelAllReport> {
+        if self.observer_only || self.is_standby() {
+            return Err(BotError::Config(
+                "cancel-all refused: this instance does not own the account".to_string(),
+            ));
+        }
+
+        let report = self.ctrader.cancel_all(flatten).await?;
+        for order_id in &report.cancelled_orders {
+            self.pending_orders.remove(*order_id);
+        }
+
+        let closed: Vec<Position> = self
+            .strategy
+            .core()
+            .get_open_positions()
+            .iter()
+            .filter(|p| {
+                p.id.parse::<i64>()
+                    .is_ok_and(|id| report.closed_positions.contains(&id))
+            })
+            .cloned()
+            .collect();
+        for position in closed {
+            let price = self
+                .last_price
+                .map(|mid| self.exit_quote(position.side, mid))
+                .unwrap_or(position.entry_price);
+            self.record_local_close(&position, price, CloseReason::Manual).await;
+        }
+
+        let message = format!(
+            "🛑 Cancel-all{}: {} orders cancelled, {} positions closed, {} failures",
+            if flatten { " + flatten" } else { "" },
+            report.cancelled_orders.len(),
+            report.closed_positions.len(),
+            report.failures.len()
+        );
+        warn!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Critical,
+                message,
+                timestamp: Utc::now(),
+            })
+            .await;
+
+        Ok(report)
+    }
+
+    /// Seed RSI and the strategy's EMA from recent trendbars so signals are
+    /// available from the first live candle (INDICATOR_WARMUP_BARS, 0 = off)
+    async fn warm_up_indicators(&mut self) {
+        let bars = env::var("INDICATOR_WARMUP_BARS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_WARMUP_BARS);
+        if bars == 0 {
+            return;
+        }
+
+        let timeframe = self.candle_builder.timeframe();
+        let Some((candles, source)) = self.warm_up_candles(timeframe, bars).await else {
+            warn!("Indicator warm-up skipped, waiting for live candles");
+            return;
+        };
+
+        for candle in &candles {
+            self.strategy.on_price(candle.close);
+            self.strategy.on_candle(candle);
+            if let Some(rsi) = self.rsi_calculator.add_price(candle.close) {
+                self.last_rsi = rsi;
+            }
+        }
+        info!(
+            "Warmed up indicators from {} {} {} (RSI ready: {}, EMA ready: {})",
+            candles.len(),
+            timeframe,
+            source,
+            self.rsi_calculator.is_ready(),
+            self.strategy.core().current_ema().is_some()
+        );
+
+        let Some(timeframe) = self.higher_timeframe.as_ref().map(|h| h.builder.timeframe()) else {
+            return;
+        };
+        let warmed = self.warm_up_candles(timeframe, bars).await;
+        let Some(higher) = self.higher_timeframe.as_mut() else {
+            return;
+        };
+        match warmed {
+            Some((candles, source)) => {
+                for candle in &candles {
+                    higher.update(candle);
+                }
+                info!(
+                    "Warmed up {} RSI from {} {} (ready: {})",
+                    timeframe,
+                    candles.len(),
+                    source,
+                    higher.rsi.is_some()
+                );
+            }
+            None => warn!("{} RSI warm-up skipped", timeframe),
+        }
+    }
+
+    /// Rebuild candles and indicators after the feed went quiet for more than
+    /// a candle (reconnect, outage): the partial candles built before the gap
+    /// are dropped and the candles closed since then are replayed from the
+    /// broker's trendbars, without trading on them
+    async fn resync_after_gap(&mut self, last_tick: DateTime<Utc>, now: DateTime<Utc>) {
+        let timeframe = self.candle_builder.timeframe();
+        let gap_minutes = (now - last_tick).num_minutes();
+        self.candle_builder = CandleBuilder::from_env(timeframe);
+        let replayed = match self.gap_candles(timeframe, last_tick, now).await {
+            Some(candles) => {
+                for candle in &candles {
+                    self.strategy.on_price(candle.close);
+                    self.strategy.on_candle(candle);
+                    if let Some(rsi) = self.rsi_calculator.add_price(candle.close) {
+                        self.last_rsi = rsi;
+                    }
                 }
+                candles.len()
+            }
+            None => 0,
+        };
 
-                if let Some(pnl) = self.strategy.close_position(&position.id, price, reason) {
-                    self.persist_close_position(&position.id, price, reason);
-                    self.trade_logger.log_close(
-                        &Utc::now().to_rfc3339(),
-                        &position.id,
-                        price,
-                        pnl,
-                        &format!("{:?}", reason),
-                    );
-                    self.metrics.with_metrics_mut(|m| {
-                        let _ = m.close_trade(&position.id, price);
-                    });
-                    self.event_channel
-                        .publish(MarketEvent::PositionClosed {
-                            position_id: position.id.parse().unwrap_or_default(),
-                            symbol_id: self.symbol_id,
-                            realized_pnl: pnl,
-                            close_reason: reason.to_string(),
-                            timestamp: Utc::now(),
-                        })
-                        .await;
+        let higher_timeframe = self.higher_timeframe.as_ref().map(|h| h.builder.timeframe());
+        if let Some(higher_timeframe) = higher_timeframe {
+            let candles = self.gap_candles(higher_timeframe, last_tick, now).await;
+            if let Some(higher) = self.higher_timeframe.as_mut() {
+                higher.builder = CandleBuilder::from_env(higher_timeframe);
+                for candle in candles.iter().flatten() {
+                    higher.update(candle);
                 }
             }
         }
 
-        Ok(())
+        let message = format!(
+            "Price feed gap of {} min; replayed {} {} trendbar(s) before resuming signals",
+            gap_minutes, replayed, timeframe
+        );
+        info!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Info,
+                message,
+                timestamp: Utc::now(),
+            })
+            .await;
+    }
+
+    /// Closed `timeframe` candles from the one that was forming at
+    /// `last_tick` up to `now`, stored for later warm-ups
+    async fn gap_candles(
+        &self,
+        timeframe: TimeFrame,
+        last_tick: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<Vec<Candle>> {
+        let from_ms = timeframe.candle_start(last_tick).timestamp_millis();
+        match self
+            .ctrader
+            .get_trendbars_between(self.symbol_id, timeframe, from_ms, now.timestamp_millis())
+            .await
+        {
+            Ok(candles) => {
+                for candle in &candles {
+                    self.persist_candle(candle);
+                }
+                Some(candles)
+            }
+            Err(err) => {
+                warn!(
+                    "{} trendbars unavailable after feed gap ({}); indicators skip the gap",
+                    timeframe, err
+                );
+                None
+            }
+        }
+    }
+
+    /// Closed `timeframe` candles to warm up from: the stored candles when
+    /// they run up to the last closed candle (no gap since the previous run),
+    /// the broker's trendbars otherwise, stored candles with a gap when the
+    /// broker has none to give
+    async fn warm_up_candles(
+        &self,
+        timeframe: TimeFrame,
+        bars: u32,
+    ) -> Option<(Vec<Candle>, &'static str)> {
+        let stored = match &self.position_db {
+            Some(db) => db
+                .get_recent_candles(&self.config.trading.symbol, timeframe, bars as usize)
+                .unwrap_or_else(|err| {
+                    warn!("Failed to load stored {} candles: {}", timeframe, err);
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+        let current = timeframe.candle_start(Utc::now());
+        if stored.last().is_some_and(|last| last.end_time() >= current) {
+            return Some((stored, "stored candles"));
+        }
+
+        match self.ctrader.get_trendbars(self.symbol_id, timeframe, bars).await {
+            Ok(candles) if !candles.is_empty() => {
+                for candle in &candles {
+                    self.persist_candle(candle);
+                }
+                Some((candles, "trendbars"))
+            }
+            Ok(_) if !stored.is_empty() => Some((stored, "stored candles (with a gap)")),
+            Err(err) if !stored.is_empty() => {
+                warn!("{} trendbars unavailable ({}); using stored candles", timeframe, err);
+                Some((stored, "stored candles (with a gap)"))
+            }
+            Ok(_) => None,
+            Err(err) => {
+                warn!("{} trendbars unavailable: {}", timeframe, err);
+                None
+            }
+        }
+    }
+
+    /// Handle for queueing emergency commands (dashboard, control API)
+    pub fn emergency_handle(&self) -> EmergencyHandle {
+        self.emergency.clone()
     }
 
     async fn process_signal(&mut self, candle: &Candle) -> Result<()> {
+        self.strategy.on_candle(candle);
+        let previous_rsi = self.rsi_calculator.current();
         let rsi = match self.rsi_calculator.add_price(candle.close) {
             Some(value) => value,
             None => {
@@ -761,8 +2875,23 @@ impl TradingBot {
                 return Ok(());
             }
         };
+        let alerts = self.alert_rules.observe(AlertMetric::Rsi, rsi, Utc::now());
+        self.raise_alerts(alerts).await;
+
+        if self.is_standby() {
+            // Keep indicators warm for a takeover, but leave trading to the leader
+            self.last_rsi = rsi;
+            debug!("Standby: candle close={:.5} RSI={:.1}", candle.close, rsi);
+            return Ok(());
+        }
+        self.close_on_rsi(previous_rsi, rsi).await;
+        self.update_structure_stops(candle).await;
 
         let sentiment = self.fetch_current_sentiment().await;
+        let alerts = self
+            .alert_rules
+            .observe_sentiment(sentiment.score, Utc::now());
+        self.raise_alerts(alerts).await;
         // Store for trade logging
         self.last_rsi = rsi;
         self.last_sentiment = sentiment.clone();
@@ -770,14 +2899,128 @@ impl TradingBot {
         self.metrics.with_metrics_mut(|m| {
             m.update_market_data(candle.close, rsi, sentiment.score);
         });
-        let signal = self.strategy.generate_signal(rsi, sentiment.score);
+        self.record_indicator_sample(candle, rsi, sentiment.score);
+        let fundamental_bias = match &mut self.fundamentals {
+            Some(fundamentals) => fundamentals.bias(Utc::now()).await,
+            None => None,
+        };
+        let mut decision = self.strategy.generate_signal(&SignalContext {
+            candle,
+            rsi,
+            sentiment: sentiment.score,
+            sentiment_confidence: sentiment.confidence,
+            sentiment_dispersion: sentiment.dispersion.unwrap_or(0.0),
+            higher_rsi: self.higher_timeframe.as_ref().and_then(|h| h.rsi),
+            fundamental_bias,
+        });
+        self.last_signal = decision.signal;
+        self.annotate_cross_markets(candle, &mut decision).await;
 
+        let result = self.act_on_signal(candle, &mut decision).await;
         info!(
-            "Candle close={:.5} RSI={:.1} Sentiment={} Signal={:?}",
-            candle.close, rsi, sentiment.score, signal
+            "Candle close={:.5} RSI={:.1} Sentiment={} Decision: {}",
+            candle.close, rsi, sentiment.score, decision
         );
+        self.publish_decision(candle, decision).await;
+        result
+    }
+
+    /// Close reversed positions and enter on the decision's signal unless a
+    /// filter stands in the way; each filter that blocks a buy or sell is
+    /// recorded on the decision
+    async fn act_on_signal(
+        &mut self,
+        candle: &Candle,
+        decision: &mut SignalDecision,
+    ) -> Result<()> {
+        // A breaker scenario only checks the entry filters: it must not
+        // close real positions or announce its synthetic breaker trip
+        let probe = self.breaker_probe;
+        let signal = decision.signal;
+        let entry_quote = match signal {
+            Signal::Buy => Some((OrderSide::Buy, candle.ask_close())),
+            Signal::Sell => Some((OrderSide::Sell, candle.bid_close())),
+            Signal::Hold => None,
+        };
+        if !probe && !self.close_on_signal(signal).await {
+            decision.block(format!(
+                "closed opposite position(s) under policy {}",
+                self.strategy.core().opposite_signal_policy()
+            ));
+            return Ok(());
+        }
+
+        let entry = match entry_quote {
+            Some((side, price)) => self.strategy.core_mut().entry_for(side, price)?,
+            None => self
+                .strategy
+                .core_mut()
+                .can_open_position()?
+                .then_some(EntryKind::New),
+        };
+        let can_open = entry.is_some();
+        let tripped = self.strategy.core().risk_state().circuit_breaker;
+        if tripped && !self.breaker_tripped && !probe {
+            let message = format!(
+                "⛔ Circuit breaker tripped: daily P&L {:.2}, trading halted for the day",
+                self.strategy.core().risk_state().daily_pnl
+            );
+            error!("{}", message);
+            self.event_channel
+                .publish(MarketEvent::Alert {
+                    level: crate::modules::trading::AlertLevel::Critical,
+                    message,
+                    timestamp: Utc::now(),
+                })
+                .await;
+        }
+        if !probe {
+            self.breaker_tripped = tripped;
+        }
+
+        let cooldown = entry_quote
+            .and_then(|(side, _)| self.strategy.core().entry_cooldown_remaining(side));
+        if let (Some(remaining), Some((side, _))) = (cooldown, entry_quote) {
+            info!(
+                "Entry cooldown: {} more candle(s) before another {} entry; skipping {:?} signal",
+                remaining, side, signal
+            );
+            decision.block(format!("entry cooldown, {} candle(s) left", remaining));
+            return Ok(());
+        }
+
+        if let (false, Some(window)) = (can_open, self.strategy.core().active_blackout()) {
+            if signal != Signal::Hold {
+                info!("News blackout {}; skipping {:?} signal", window, signal);
+                decision.block(format!("news blackout {}", window));
+            }
+            return Ok(());
+        }
+
+        if !can_open && self.strategy.core().volatility_regime() == VolatilityRegime::Extreme {
+            if signal != Signal::Hold {
+                info!("Extreme volatility; standing aside on {:?} signal", signal);
+                decision.block("extreme volatility");
+            }
+            return Ok(());
+        }
+
+        if !can_open && !self.strategy.core().market_open() {
+            if signal != Signal::Hold {
+                let next_open = self
+                    .strategy
+                    .core()
+                    .market_calendar()
+                    .and_then(|calendar| calendar.next_open(Utc::now()))
+                    .map(|at| format!(", next session {}", at.format("%a %H:%M UTC")))
+                    .unwrap_or_default();
+                info!("Market closed{}; skipping {:?} signal", next_open, signal);
+                decision.block(format!("market closed{}", next_open));
+            }
+            return Ok(());
+        }
 
-        if !self.strategy.can_open_position()? {
+        if !can_open {
             self.event_channel
                 .publish(MarketEvent::Alert {
                     level: crate::modules::trading::AlertLevel::Warning,
@@ -785,19 +3028,351 @@ impl TradingBot {
                     timestamp: Utc::now(),
                 })
                 .await;
+            if signal != Signal::Hold {
+                decision.block(BREAKER_BLOCKER);
+            }
+            return Ok(());
+        }
+
+        if signal != Signal::Hold && self.paused {
+            info!("Paused by operator; skipping {:?} signal", signal);
+            decision.block("paused by operator");
+            return Ok(());
+        }
+
+        if let Some(veto) = self.cross_market.as_ref().and_then(|c| c.veto(signal)) {
+            info!("Cross-market veto: {}; skipping {:?} signal", veto, signal);
+            decision.block(veto);
+            return Ok(());
+        }
+
+        // Scale-in legs belong to a position already counted in its class
+        if entry == Some(EntryKind::New)
+            && signal != Signal::Hold
+            && self.strategy.core().asset_class_limit_reached(self.asset_class())
+        {
+            info!(
+                "Asset class limit reached for {:?}; skipping {:?} signal",
+                self.asset_class(),
+                signal
+            );
+            decision.block(format!("asset class limit reached for {:?}", self.asset_class()));
             return Ok(());
         }
 
-        match signal {
-            Signal::Buy => self.execute_trade(OrderSide::Buy, candle.close).await?,
-            Signal::Sell => self.execute_trade(OrderSide::Sell, candle.close).await?,
-            Signal::Hold => {}
+        if let (Some((side, price)), Some(entry)) = (entry_quote, entry) {
+            if self.breaker_probe {
+                warn!(
+                    "Breaker scenario: {:?} signal got past every filter; order withheld",
+                    signal
+                );
+                decision.block(PROBE_BLOCKER);
+                return Ok(());
+            }
+            self.execute_trade(side, price, entry).await?;
         }
 
         Ok(())
     }
 
-    async fn execute_trade(&mut self, side: OrderSide, entry_price: f64) -> Result<()> {
+    /// Resolve and subscribe the cross markets (CROSS_MARKET_SYMBOLS) on the
+    /// same feed; one the broker does not offer is left out with a warning
+    async fn subscribe_cross_markets(&mut self) {
+        let symbols = match &self.cross_market {
+            Some(cross_market) => cross_market.config().symbols.clone(),
+            None => return,
+        };
+        for symbol in symbols {
+            let subscribed = match self.ctrader.get_symbol_id(&symbol).await {
+                Ok(symbol_id) => self
+                    .ctrader
+                    .subscribe_to_symbol(symbol_id)
+                    .await
+                    .map(|_| symbol_id),
+                Err(err) => Err(err),
+            };
+            match subscribed {
+                Ok(symbol_id) => {
+                    info!("Cross market {} subscribed (symbol ID {})", symbol, symbol_id);
+                    self.cross_market_ids.push((symbol, symbol_id));
+                }
+                Err(err) => warn!("Cross market {} left out: {}", symbol, err),
+            }
+        }
+    }
+
+    /// Sample the cross markets at this candle, add their factors to the
+    /// decision and surface the readings on the dashboard
+    async fn annotate_cross_markets(&mut self, candle: &Candle, decision: &mut SignalDecision) {
+        let Some(cross_market) = &mut self.cross_market else {
+            return;
+        };
+        for (symbol, symbol_id) in &self.cross_market_ids {
+            match self.ctrader.get_price(*symbol_id).await {
+                Ok(price) if price.bid > 0.0 && price.ask > 0.0 => {
+                    cross_market.record(symbol, candle.close, (price.bid + price.ask) / 2.0)
+                }
+                Ok(_) => debug!("One-sided {} quote; cross market not sampled", symbol),
+                Err(err) => debug!("No {} price for the cross-market filter: {}", symbol, err),
+            }
+        }
+        cross_market.annotate(decision);
+        let readings = cross_market.readings();
+        self.last_cross_market = readings
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        self.metrics.with_metrics_mut(|m| m.update_cross_markets(readings));
+    }
+
+    /// Publish the candle's decision on the event channel and store it in
+    /// the `signal_decisions` table
+    async fn publish_decision(&self, candle: &Candle, decision: SignalDecision) {
+        if let Some(db) = &self.position_db {
+            let symbol = &self.config.trading.symbol;
+            if let Err(err) = db.save_signal_decision(symbol, candle.end_time(), &decision) {
+                warn!("Failed to persist signal decision: {}", err);
+            }
+        }
+        self.event_channel
+            .publish(MarketEvent::SignalDecision {
+                symbol_id: self.symbol_id,
+                symbol: self.config.trading.symbol.clone(),
+                decision,
+                timestamp: candle.end_time(),
+            })
+            .await;
+    }
+
+    /// Close the positions `signal` reverses (OPPOSITE_SIGNAL_POLICY).
+    /// Returns whether the signal may go on to enter: always when nothing
+    /// was closed, only under `Reverse` (and if every close went through)
+    /// otherwise. Exits are not subject to the entry filters.
+    async fn close_on_signal(&mut self, signal: Signal) -> bool {
+        let reversed: Vec<(Position, CloseReason)> = self
+            .strategy
+            .core()
+            .get_open_positions()
+            .iter()
+            .filter_map(|p| Some((p.clone(), self.strategy.check_signal_exit(p, signal)?)))
+            .collect();
+        if reversed.is_empty() {
+            return true;
+        }
+        let policy = self.strategy.core().opposite_signal_policy();
+        info!(
+            "{} signal against {} open position(s); policy {}",
+            signal,
+            reversed.len(),
+            policy
+        );
+        for (position, reason) in &reversed {
+            if let Err(err) = self.close_position_now(&position.id, *reason).await {
+                warn!(
+                    "Failed to close position {} on opposite signal: {}",
+                    position.id, err
+                );
+                return false;
+            }
+        }
+        policy == OppositeSignalPolicy::Reverse
+    }
+
+    /// Close open positions whose RSI exit level was crossed on this candle
+    async fn close_on_rsi(&mut self, previous_rsi: Option<f64>, rsi: f64) {
+        let exits: Vec<(Position, CloseReason)> = self
+            .strategy
+            .core()
+            .get_open_positions()
+            .iter()
+            .filter_map(|p| Some((p.clone(), self.strategy.check_rsi_exit(p, previous_rsi, rsi)?)))
+            .collect();
+        for (position, reason) in exits {
+            info!(
+                "RSI {:.1} crossed the exit level; closing {:?} position {}",
+                rsi, position.side, position.id
+            );
+            if let Err(err) = self.close_position_now(&position.id, reason).await {
+                warn!("Failed to close position {} on RSI exit: {}", position.id, err);
+            }
+        }
+    }
+
+    /// Order gate for every new order, strategy entries and QUICK_TEST
+    /// alike: the leader lease, the hard size caps, then the portfolio risk limits
+    async fn order_allowed(
+        &self,
+        side: OrderSide,
+        entry_price: f64,
+        volume: f64,
+        volume_units: i64,
+    ) -> bool {
+        if self.is_standby() {
+            warn!("{:?} order refused: the leader lease is not held", side);
+            return false;
+        }
+        self.size_allows(side, entry_price, volume, volume_units).await
+            && self.risk_allows(side, entry_price, volume, volume_units).await
+    }
+
+    /// Whether the order stays under the hard size caps, whatever
+    /// RISK_PER_TRADE and the balance produced
+    async fn size_allows(
+        &self,
+        side: OrderSide,
+        entry_price: f64,
+        volume: f64,
+        volume_units: i64,
+    ) -> bool {
+        let lots = self
+            .symbol_meta
+            .as_ref()
+            .and_then(|meta| meta.lot_size)
+            .filter(|lot_size| *lot_size > 0)
+            .map(|lot_size| volume_units as f64 / lot_size as f64);
+        let Err(violation) = self.size_guard.check(volume, entry_price, lots) else {
+            return true;
+        };
+
+        let message = format!(
+            "Order size cap: {} order skipped, {} (check RISK_PER_TRADE and the balance)",
+            side, violation
+        );
+        error!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Critical,
+                message: message.clone(),
+                timestamp: Utc::now(),
+            })
+            .await;
+        if let Some(telegram) = &self.telegram {
+            if let Err(err) = telegram.send_message(&message).await {
+                warn!("Failed to send size cap alert: {}", err);
+            }
+        }
+        false
+    }
+
+    /// Whether the latest quote's spread allows an entry; a refusal raises a
+    /// warning alert. Without a two-sided quote the spread is unknown and the
+    /// entry goes ahead.
+    async fn spread_allows(&self, side: OrderSide) -> bool {
+        let Some((bid, ask)) = self.last_quote else {
+            if self.spread_guard.is_enabled() {
+                debug!("No bid/ask quote yet; spread guard not applied");
+            }
+            return true;
+        };
+        let Err(violation) = self.spread_guard.check(bid, ask) else {
+            return true;
+        };
+
+        let message = format!("Spread guard: {} entry skipped, {}", side, violation);
+        warn!("{}", message);
+        self.event_channel
+            .publish(MarketEvent::Alert {
+                level: crate::modules::trading::AlertLevel::Warning,
+                message,
+                timestamp: Utc::now(),
+            })
+            .await;
+        false
+    }
+
+    /// Whether the portfolio risk limits allow a new order: broker positions
+    /// and expected margin when live, the strategy's own positions in dry-run
+    async fn risk_allows(
+        &self,
+        side: OrderSide,
+        entry_price: f64,
+        volume: f64,
+        volume_units: i64,
+    ) -> bool {
+        let Some(risk_manager) = &self.risk_manager else {
+            return true;
+        };
+
+        let (positions, expected_margin) = if self.config.bot.dry_run {
+            let positions: Vec<Exposure> = self
+                .strategy
+                .core()
+                .get_open_positions()
+                .iter()
+                .map(|p| Exposure {
+                    symbol_id: self.symbol_id,
+                    notional: p.volume * p.entry_price,
+                    used_margin: 0.0,
+                })
+                .collect();
+            (positions, None)
+        } else {
+            let positions = match self.ctrader.get_positions().await {
+                Ok(positions) => positions,
+                Err(err) => {
+                    warn!("Risk check: broker positions unavailable ({}); skipping trade", err);
+                    return false;
+                }
+            };
+            let positions: Vec<Exposure> = positions
+                .iter()
+                .map(|p| Exposure {
+                    symbol_id: p.symbol_id,
+                    notional: p.volume as f64 / 100.0 * p.entry_price,
+                    used_margin: p.used_margin,
+                })
+                .collect();
+            let trade_side = match side {
+                OrderSide::Buy => ProtoOATradeSide::Buy,
+                OrderSide::Sell => ProtoOATradeSide::Sell,
+            };
+            let expected_margin = match self
+                .ctrader
+                .expected_margin(self.symbol_id, volume_units, trade_side)
+                .await
+            {
+                Ok(margin) => Some(margin),
+                Err(err) => {
+                    warn!("Expected margin unavailable ({}); checking exposure only", err);
+                    None
+                }
+            };
+            (positions, expected_margin)
+        };
+
+        let order = OrderExposure {
+            symbol_id: self.symbol_id,
+            notional: volume * entry_price,
+            expected_margin,
+        };
+        let balance = self.strategy.core().account_balance();
+        match risk_manager.check(balance, &positions, &order) {
+            Ok(report) => {
+                info!("Risk check passed: {}", report);
+                true
+            }
+            Err(rejection) => {
+                let message = format!("Risk limit: {} order skipped, {}", side, rejection);
+                warn!("{}", message);
+                self.event_channel
+                    .publish(MarketEvent::Alert {
+                        level: crate::modules::trading::AlertLevel::Warning,
+                        message,
+                        timestamp: Utc::now(),
+                    })
+                    .await;
+                false
+            }
+        }
+    }
+
+    async fn execute_trade(
+        &mut self,
+        side: OrderSide,
+        entry_price: f64,
+        entry: EntryKind,
+    ) -> Result<()> {
         if let Some(meta) = &self.symbol_meta {
             if let Some(mode) = meta.trading_mode {
                 if mode != ProtoOaTradingMode::Enabled {
@@ -807,10 +3382,15 @@ impl TradingBot {
             }
         }
 
+        if !self.spread_allows(side).await {
+            return Ok(());
+        }
+
         let entry_price = self.normalize_price(entry_price);
-        let take_profit_raw = self.strategy.calculate_take_profit(entry_price, side);
-        let stop_loss_raw = self.strategy.calculate_stop_loss(entry_price, side);
-        let volume_raw = self.strategy.calculate_position_size(entry_price, stop_loss_raw);
+        let take_profit_raw = self.strategy.take_profit(entry_price, side);
+        let stop_loss_raw = self.strategy.stop_loss(entry_price, side);
+        let volume_raw =
+            self.strategy.position_size(entry_price, stop_loss_raw) * entry.size_factor();
 
         let (take_profit, stop_loss) =
             self.normalize_tp_sl(side, entry_price, take_profit_raw, stop_loss_raw);
@@ -828,23 +3408,54 @@ impl TradingBot {
             "Signal: {:?} entry={:.2} tp={:.2} sl={:.2} vol={:.2}",
             side, entry_price, take_profit, stop_loss, volume
         );
+        if let EntryKind::ScaleIn { leg, size_factor } = entry {
+            let average = self
+                .strategy
+                .core()
+                .position_manager()
+                .average_entry(&self.config.trading.symbol, side);
+            info!(
+                "Scale-in leg {} at {:.0}% size (average entry {:.2})",
+                leg,
+                size_factor * 100.0,
+                average.unwrap_or(entry_price)
+            );
+        }
+
+        if !self.config.bot.dry_run && !self.arming.allows_orders(Utc::now()) {
+            info!("Live trading not armed: {:?} signal not sent", side);
+            return Ok(());
+        }
+
+        if !self.order_allowed(side, entry_price, volume, volume_units).await {
+            return Ok(());
+        }
 
         if self.config.bot.dry_run {
             let position_id = format!("dry_run_{}", Utc::now().timestamp_millis());
+            let fill = self.simulated_broker.open(side, entry_price, self.quote_spread());
+            let fill_price = self.normalize_price(fill.price);
+            info!(
+                "[DRY RUN] {:?} filled at {:.5} (mid {:.5}, execution cost {:.5})",
+                side, fill_price, entry_price, fill.execution_cost
+            );
             let position = crate::modules::trading::Position::new(
                 position_id.clone(),
                 self.config.trading.symbol.clone(),
                 side,
-                entry_price,
+                fill_price,
                 volume,
             )
             .with_take_profit(take_profit)
-            .with_stop_loss(stop_loss);
+            .with_stop_loss(stop_loss)
+            .with_strategy_version(self.strategy_version.clone())
+            .with_features(self.strategy.core().features().tag());
+            let position = self.tag_asset_class(position);
             self.persist_open_position(&position);
             self.metrics.with_metrics_mut(|m| {
-                m.add_trade(Trade::new(position_id.clone(), format!("{:?}", side), volume, entry_price));
+                m.add_trade(Trade::new(position_id.clone(), format!("{:?}", side), volume, fill_price));
             });
-            self.strategy.add_position(position);
+            self.strategy.core_mut().add_position(position);
             return Ok(());
         }
 
@@ -853,19 +3464,59 @@ impl TradingBot {
             OrderSide::Sell => ProtoOATradeSide::Sell,
         };
 
+        self.orders_sent += 1;
+        let label_context = LabelContext {
+            strategy: self.strategy.name(),
+            strategy_version: &self.strategy_version,
+            signal: match side {
+                OrderSide::Buy => "BUY",
+                OrderSide::Sell => "SELL",
+            },
+            signal_id: self.orders_sent,
+            symbol: &self.config.trading.symbol,
+        };
+        let label = self.order_labeler.label(&label_context);
+        let comment = self.order_labeler.comment(&label_context);
+
         let ticket = OrderTicket {
             symbol_id: self.symbol_id,
             side: trade_side,
             volume: volume_units,
+            order_type: ProtoOAOrderType::Market,
+            limit_price: None,
+            stop_price: None,
+            expiration: None,
             stop_loss: Some(stop_loss),
             take_profit: Some(take_profit),
             relative_stop_loss: self.relative_distance(entry_price, stop_loss),
             relative_take_profit: self.relative_distance(entry_price, take_profit),
-            label: Some("PalmOilBot".to_string()),
+            trailing_stop_loss: false,
+            label: Some(label),
+            comment,
         };
 
         match self.ctrader.place_order(ticket).await {
-            Ok((order_id, position_id)) => {
+            Ok(placement) => {
+                let (order_id, position_id) = (placement.order_id, placement.position_id);
+                for status in &placement.lifecycle {
+                    self.event_channel
+                        .publish(MarketEvent::OrderStatusChanged {
+                            order_id,
+                            position_id,
+                            symbol_id: self.symbol_id,
+                            status: *status,
+                            timestamp: Utc::now(),
+                        })
+                        .await;
+                }
+                // A market order cancelled after a partial fill keeps what filled
+                let partial = placement.filled_volume > 0 && placement.filled_volume < volume_units;
+                let volume = if partial {
+                    volume * placement.filled_volume as f64 / volume_units as f64
+                } else {
+                    volume
+                };
+                let entry_price = placement.fill_price.unwrap_or(entry_price);
                 self.event_channel
                     .publish(MarketEvent::OrderFilled {
                         order_id,
@@ -890,7 +3541,10 @@ impl TradingBot {
                     volume,
                 )
                 .with_take_profit(take_profit)
-                .with_stop_loss(stop_loss);
+                .with_stop_loss(stop_loss)
+                .with_strategy_version(self.strategy_version.clone())
+                .with_features(self.strategy.core().features().tag());
+                let position = self.tag_asset_class(position);
 
                 self.persist_open_position(&position);
                 self.trade_logger.log_open(
@@ -903,17 +3557,18 @@ impl TradingBot {
                     volume,
                     self.last_rsi,
                     self.last_sentiment.score,
-                    self.last_sentiment.confidence as f64,
-                    &format!("{:?}", self.strategy.generate_signal(self.last_rsi, self.last_sentiment.score)),
+                    self.last_sentiment.confidence,
+                    &format!("{:?}", self.last_signal),
                     &position_id.to_string(),
+                    &self.last_cross_market,
                 );
                 self.metrics.with_metrics_mut(|m| {
                     m.add_trade(Trade::new(position_id.to_string(), format!("{:?}", side), volume, entry_price));
                 });
-                self.strategy.add_position(position);
+                self.strategy.core_mut().add_position(position);
 
                 // Reconcile immediately after order fill
-                if let Err(err) = self.reconcile_positions().await {
+                if let Err(err) = self.reconcile_positions(false).await {
                     warn!("Post-fill reconciliation failed: {}", err);
                 }
             }
@@ -932,44 +3587,8 @@ impl TradingBot {
         Ok(())
     }
 
-    fn price_factor(&self) -> f64 {
-        // Default to 5 digits (10^5 = 100000) if symbol_meta is unavailable
-        const DEFAULT_DIGITS: i32 = 5;
-
-        let digits = self.symbol_meta
-            .as_ref()
-            .map(|m| m.digits)
-            .filter(|&d| d >= 0)
-            .unwrap_or(DEFAULT_DIGITS);
-
-        10_f64.powi(digits)
-    }
-
-    fn round_price_up(&self, price: f64) -> f64 {
-        let factor = self.price_factor();
-        self.normalize_price((price * factor).ceil() / factor)
-    }
-
-    fn round_price_down(&self, price: f64) -> f64 {
-        let factor = self.price_factor();
-        self.normalize_price((price * factor).floor() / factor)
-    }
-
     fn normalize_price(&self, price: f64) -> f64 {
-        // Default to 5 digits if symbol_meta is unavailable (safe for most commodities/forex)
-        const DEFAULT_DIGITS: usize = 5;
-
-        let prec = match &self.symbol_meta {
-            Some(meta) if meta.digits >= 0 => meta.digits as usize,
-            _ => {
-                // Log warning only once per missing meta scenario
-                debug!("Using default precision ({} digits) - symbol_meta unavailable", DEFAULT_DIGITS);
-                DEFAULT_DIGITS
-            }
-        };
-
-        let formatted = format!("{:.prec$}", price, prec = prec);
-        formatted.parse::<f64>().unwrap_or(price)
+        normalize::normalize_price(self.symbol_meta.as_ref(), price)
     }
 
     fn relative_distance(&self, entry: f64, target: f64) -> Option<i64> {
@@ -984,216 +3603,89 @@ impl TradingBot {
     }
 
     fn normalize_tp_sl(&self, side: OrderSide, entry: f64, take_profit: f64, stop_loss: f64) -> (f64, f64) {
-        let mut tp = take_profit;
-        let mut sl = stop_loss;
-
-        if let Some(meta) = &self.symbol_meta {
-            if let Some(min_tp) = meta.min_distance_price(entry, meta.tp_distance) {
-                match side {
-                    OrderSide::Buy => {
-                        let target = entry + min_tp;
-                        if tp < target {
-                            tp = target;
-                        }
-                    }
-                    OrderSide::Sell => {
-                        let target = entry - min_tp;
-                        if tp > target {
-                            tp = target;
-                        }
-                    }
-                }
-            }
-
-            if let Some(min_sl) = meta.min_distance_price(entry, meta.sl_distance) {
-                match side {
-                    OrderSide::Buy => {
-                        let target = entry - min_sl;
-                        if sl > target {
-                            sl = target;
-                        }
-                    }
-                    OrderSide::Sell => {
-                        let target = entry + min_sl;
-                        if sl < target {
-                            sl = target;
-                        }
-                    }
-                }
-            }
-        }
-
-        match side {
-            OrderSide::Buy => {
-                tp = self.round_price_up(tp);
-                sl = self.round_price_down(sl);
-            }
-            OrderSide::Sell => {
-                tp = self.round_price_down(tp);
-                sl = self.round_price_up(sl);
-            }
-        }
-
-        if let Some(meta) = &self.symbol_meta {
-            if let Some(point) = meta.point_size() {
-                match side {
-                    OrderSide::Buy => {
-                        if tp <= entry {
-                            tp = self.round_price_up(entry + point);
-                        }
-                        if sl >= entry {
-                            sl = self.round_price_down(entry - point);
-                        }
-                    }
-                    OrderSide::Sell => {
-                        if tp >= entry {
-                            tp = self.round_price_down(entry - point);
-                        }
-                        if sl <= entry {
-                            sl = self.round_price_up(entry + point);
-                        }
-                    }
-                }
-
-                if let Some(min_tp) = meta.min_distance_price(entry, meta.tp_distance) {
-                    match side {
-                        OrderSide::Buy => {
-                            let min_target = entry + min_tp;
-                            if tp < min_target {
-                                tp = self.round_price_up(min_target + point);
-                            }
-                        }
-                        OrderSide::Sell => {
-                            let min_target = entry - min_tp;
-                            if tp > min_target {
-                                tp = self.round_price_down(min_target - point);
-                            }
-                        }
-                    }
-                }
-
-                if let Some(min_sl) = meta.min_distance_price(entry, meta.sl_distance) {
-                    match side {
-                        OrderSide::Buy => {
-                            let min_target = entry - min_sl;
-                            if sl > min_target {
-                                sl = self.round_price_down(min_target - point);
-                            }
-                        }
-                        OrderSide::Sell => {
-                            let min_target = entry + min_sl;
-                            if sl < min_target {
-                                sl = self.round_price_up(min_target + point);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        (tp, sl)
+        normalize::normalize_tp_sl(self.symbol_meta.as_ref(), side, entry, take_profit, stop_loss)
     }
 
-    /// Convert base currency units to cTrader volume units, aligned to broker constraints.
-    ///
-    /// Input: base_currency_units (e.g. 33,898 EUR for a 2% risk trade on EURUSD)
-    /// Output: (base_units_display, ctrader_volume_units)
-    /// cTrader volume = base_currency_units × 100 (centigranular convention)
+    /// Convert base currency units to cTrader volume units, aligned to broker
+    /// constraints (see `normalize::normalize_volume`)
     fn normalize_volume(&self, base_units: f64) -> Option<(f64, i64)> {
-        // cTrader uses centigranular volume: 1 base unit = 100 volume units
-        let mut units = (base_units * 100.0).round() as i64;
-        if units <= 0 {
-            return None;
-        }
-
-        // Safety cap when symbol_meta is missing: limit to 5,000,000 (≈0.5 lots forex)
-        const DEFAULT_MAX_VOLUME: i64 = 5_000_000;
-
-        if let Some(meta) = &self.symbol_meta {
-            if let Some(step) = meta.step_volume {
-                if step > 0 {
-                    units = (units / step) * step;
-                }
-            }
-            if let Some(min) = meta.min_volume {
-                if units < min {
-                    units = min;
-                }
-            }
-            if let Some(max) = meta.max_volume {
-                if units > max {
-                    units = max;
-                }
-            }
-        } else {
-            // No symbol meta: apply safety cap
-            if units > DEFAULT_MAX_VOLUME {
-                warn!(
-                    "No symbol meta; capping volume {} → {} (safety limit)",
-                    units, DEFAULT_MAX_VOLUME
-                );
-                units = DEFAULT_MAX_VOLUME;
-            }
-        }
-
-        if units <= 0 {
-            return None;
-        }
-
-        Some((units as f64 / 100.0, units))
+        normalize::normalize_volume(self.symbol_meta.as_ref(), base_units)
     }
 
     /// Fetch current sentiment with caching (TTL 5 minutes)
     ///
-    /// Returns cached value if valid, otherwise fetches from Perplexity API.
-    /// Falls back to Twitter sentiment, then neutral (0) if all APIs fail.
+    /// Returns cached value if valid, otherwise asks the sentiment providers:
+    /// all at once with SENTIMENT_MULTI_SOURCE, else Perplexity first and
+    /// then Twitter, Reddit and the news feeds in turn. Cached scores decay
+    /// toward neutral with SENTIMENT_HALF_LIFE_MINUTES. If every provider
+    /// fails, the last reading (decayed), else neutral (0).
     pub async fn fetch_current_sentiment(&self) -> SentimentResult {
         // Check cache first
         {
             let cache = self.sentiment_cache.read().await;
-            if let Some(result) = &cache.result {
-                if cache.is_valid() {
+            if cache.is_valid() {
+                if let Some(result) = cache.decayed_result(Utc::now()) {
                     debug!(
-                        "Using cached sentiment: {} (expires in {}s)",
+                        "Using cached sentiment: {} (read as {}, expires in {}s)",
+                        result.score,
                         cache.value,
                         cache.time_until_expiry().num_seconds()
                     );
-                    return result.clone();
+                    return result;
                 }
             }
         }
 
-        // Cache miss - fetch from Perplexity API
-        info!("Sentiment cache expired, fetching from Perplexity API...");
-
-        let result = match self.perplexity.get_market_sentiment().await {
-            Ok(sentiment) => {
+        let breakdown = if self.multi_source_sentiment {
+            info!("Sentiment cache expired, fetching from all sentiment providers...");
+            self.sentiment_providers.aggregate().await
+        } else {
+            info!("Sentiment cache expired, fetching from the sentiment providers in turn...");
+            self.sentiment_providers.first_available().await
+        };
+        let (result, summary) = match breakdown {
+            Some(SentimentBreakdown { result, providers }) => {
+                let summary = providers
+                    .iter()
+                    .map(|p| match p.score {
+                        Some(score) => {
+                            format!("{}={} ({:.0}%)", p.provider, score, p.share * 100.0)
+                        }
+                        None => format!("{}=failed", p.provider),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
                 info!(
-                    "Perplexity sentiment: {} ({:?}, confidence: {:.2})",
-                    sentiment.score, sentiment.sentiment_type, sentiment.confidence
+                    "Sentiment: {} (confidence: {:.2}, dispersion: {:.2}) from {}",
+                    result.score,
+                    result.confidence,
+                    result.dispersion.unwrap_or(0.0),
+                    summary
                 );
-                sentiment
-            }
-            Err(crate::error::BotError::Perplexity(crate::error::PerplexityError::RateLimited)) => {
-                warn!("Perplexity rate limited (HTTP 429), falling back to Twitter");
-                match self.twitter.get_sentiment().await {
-                    Ok(sentiment) => {
-                        info!("Twitter sentiment fallback: {}", sentiment.score);
-                        sentiment
-                    }
-                    Err(err) => {
-                        warn!("Twitter sentiment also failed: {}", err);
-                        warn!("Using neutral sentiment ({}) as fallback", NEUTRAL_SENTIMENT);
-                        SentimentResult::new(NEUTRAL_SENTIMENT, "fallback").with_confidence(0.1)
-                    }
-                }
+                self.metrics
+                    .with_metrics_mut(|m| m.update_sentiment_providers(providers));
+                (result, Some(summary))
             }
-            Err(err) => {
-                warn!("Perplexity API failed: {}", err);
+            None => {
+                let previous = self
+                    .sentiment_cache
+                    .write()
+                    .await
+                    .fallback_on_failure(Utc::now());
+                if let Some(previous) = previous {
+                    warn!(
+                        "Every sentiment provider failed; last reading decayed to {}",
+                        previous.score
+                    );
+                    return previous;
+                }
                 warn!("Using neutral sentiment ({}) as fallback", NEUTRAL_SENTIMENT);
-                SentimentResult::new(NEUTRAL_SENTIMENT, "fallback").with_confidence(0.1)
+                let fallback =
+                    SentimentResult::new(NEUTRAL_SENTIMENT, "fallback").with_confidence(0.1);
+                (fallback, None)
             }
         };
+        self.record_sentiment(&result, summary);
 
         // Update cache
         {
@@ -1204,11 +3696,11 @@ impl TradingBot {
         result
     }
 
-    /// Get cached sentiment without fetching (returns None if expired)
+    /// Get cached sentiment (decayed) without fetching (returns None if expired)
     pub async fn get_cached_sentiment(&self) -> Option<SentimentResult> {
         let cache = self.sentiment_cache.read().await;
         if cache.is_valid() {
-            cache.result.clone()
+            cache.decayed_result(Utc::now())
         } else {
             None
         }
@@ -1225,59 +3717,237 @@ impl TradingBot {
         self.fetch_current_sentiment().await
     }
 
-    /// Reconcile open positions with broker state (basic)
-    async fn reconcile_positions(&mut self) -> Result<()> {
+    /// Reconcile open positions with broker state.
+    ///
+    /// Positions found in the database are re-adopted with their original SL/TP
+    /// and entry metadata; unknown broker positions get default levels. On
+    /// startup, stored positions the broker no longer holds are marked missing.
+    async fn reconcile_positions(&mut self, on_startup: bool) -> Result<()> {
         let broker_positions = match self.ctrader.reconcile_positions().await {
             Ok(positions) => positions,
             Err(err) => {
                 warn!("Reconciliation failed: {}", err);
-                Vec::new()
+                return Ok(());
             }
         };
+        self.reconcile_pending_orders().await;
 
-        if broker_positions.is_empty() {
+        if broker_positions.is_empty() && !on_startup {
             info!("No broker positions found during reconciliation");
             return Ok(());
         }
 
+        // Each broker position paired with its current price; skipping one
+        // never shifts the others
+        let paired: Vec<(BrokerPosition, f64)> = broker_positions
+            .iter()
+            .filter_map(|pos| {
+                let side = match pos.side.as_str() {
+                    "BUY" => OrderSide::Buy,
+                    "SELL" => OrderSide::Sell,
+                    _ => {
+                        warn!("Skipping position {} with unknown side: {}", pos.position_id, pos.side);
+                        return None;
+                    }
+                };
+                let bp = BrokerPosition {
+                    position_id: pos.position_id,
+                    symbol_id: pos.symbol_id,
+                    symbol: self.config.trading.symbol.clone(),
+                    side,
+                    entry_price: pos.entry_price,
+                    volume: (pos.volume as f64) / 100.0,
+                    current_pnl: pos.profit,
+                };
+                Some((bp, pos.current_price))
+            })
+            .collect();
+        let broker: Vec<BrokerPosition> = paired.iter().map(|(bp, _)| bp.clone()).collect();
+
+        // Bot-managed positions persisted before the restart / last cycle
+        let stored: HashMap<String, Position> = self
+            .load_stored_positions()
+            .into_iter()
+            .map(|p| (p.id.clone(), p))
+            .collect();
+
+        let engine = ReconciliationEngine::new();
+        let result = engine.reconcile(&stored, &broker);
+
         let mut reconciled = Vec::new();
-        for pos in broker_positions {
-            let side = match pos.side.as_str() {
-                "BUY" => OrderSide::Buy,
-                "SELL" => OrderSide::Sell,
-                _ => {
-                    warn!("Skipping position {} with unknown side: {}", pos.position_id, pos.side);
-                    continue;
+        for (bp, current_price) in &paired {
+            let id = bp.position_id.to_string();
+            let mut position = match stored.get(&id) {
+                Some(stored_pos) => engine.adopt_stored_position(stored_pos, bp),
+                None => {
+                    warn!(
+                        "Broker position {} not found in database; adopting with default SL/TP",
+                        id
+                    );
+                    let position = Position::new(id, bp.symbol.clone(), bp.side, bp.entry_price, bp.volume)
+                        .with_take_profit(self.strategy.take_profit(bp.entry_price, bp.side))
+                        .with_stop_loss(self.strategy.stop_loss(bp.entry_price, bp.side));
+                    self.persist_open_position(&position);
+                    position
                 }
             };
+            position.current_price = *current_price;
+            let position = self.tag_asset_class(position);
 
-            let volume = (pos.volume as f64) / 100.0;
-            let mut position = crate::modules::trading::Position::new(
-                pos.position_id.to_string(),
-                self.config.trading.symbol.clone(),
-                side,
-                pos.entry_price,
-                volume,
-            )
-            .with_take_profit(self.strategy.calculate_take_profit(pos.entry_price, side))
-            .with_stop_loss(self.strategy.calculate_stop_loss(pos.entry_price, side));
-            position.current_price = pos.current_price;
-            position.current_pnl = pos.profit;
+            match position.strategy_version.as_deref() {
+                Some(version) if version != self.strategy_version => warn!(
+                    "Position {} was opened under strategy {} (current: {})",
+                    position.id, version, self.strategy_version
+                ),
+                None => warn!(
+                    "Position {} has no recorded strategy version (opened outside the bot or before versioning)",
+                    position.id
+                ),
+                _ => {}
+            }
 
             reconciled.push(position);
         }
 
-        self.strategy.reconcile_positions(reconciled);
-        info!("Reconciled broker positions into strategy state");
+        // Persisted positions the broker no longer has were closed while we were offline
+        if on_startup {
+            if let Some(db) = &self.position_db {
+                for id in &result.orphaned_local {
+                    warn!("Stored position {} is no longer open at the broker; marking missing", id);
+                    if let Err(err) = db.mark_position_missing(id) {
+                        warn!("Failed to mark position {} missing: {}", id, err);
+                    }
+                }
+            }
+        }
+
+        let reconcile_alert = reconciliation_alert(&result);
+        if reconcile_alert != self.last_reconcile_alert {
+            if let Some(message) = &reconcile_alert {
+                warn!("{}", message);
+                self.event_channel
+                    .publish(MarketEvent::Alert {
+                        level: crate::modules::trading::AlertLevel::Critical,
+                        message: message.clone(),
+                        timestamp: Utc::now(),
+                    })
+                    .await;
+            }
+            self.last_reconcile_alert = reconcile_alert;
+        }
+
+        info!(
+            "Reconciled {} broker positions ({} recovered from database, {} external)",
+            reconciled.len(),
+            result.synced.len() + result.mismatched.len(),
+            result.missing_local.len()
+        );
+        self.strategy.core_mut().reconcile_positions(reconciled);
         Ok(())
     }
 
+    /// Open positions persisted for the traded symbol under broker IDs
+    /// (dry-run positions are never on the broker, so they are left alone)
+    fn load_stored_positions(&self) -> Vec<Position> {
+        let Some(db) = &self.position_db else {
+            return Vec::new();
+        };
+        match db.get_open_positions() {
+            Ok(positions) => positions
+                .into_iter()
+                .filter(|p| p.symbol == self.config.trading.symbol && p.id.parse::<i64>().is_ok())
+                .collect(),
+            Err(err) => {
+                warn!("Failed to load stored positions: {}", err);
+                Vec::new()
+            }
+        }
+    }
+
     /// Shutdown bot and disconnect
     pub async fn shutdown(&mut self) -> Result<()> {
+        if let Some(leader) = &mut self.leader {
+            if let Err(err) = leader.release() {
+                warn!("Failed to release leader lease: {}", err);
+            }
+        }
         self.ctrader.disconnect().await?;
         Ok(())
     }
 
+    /// Whether another node holds the hot-standby lease, or our own renewal
+    /// is too old to trade on
+    fn is_standby(&self) -> bool {
+        self.leader.as_ref().map(|l| !l.holds_lease()).unwrap_or(false)
+    }
+
+    /// Renew or contest the leader lease and react to role changes
+    async fn update_leadership(&mut self) {
+        let Some(leader) = self.leader.as_mut() else {
+            return;
+        };
+        let transition = match leader.heartbeat() {
+            Ok(transition) => transition,
+            Err(err) => {
+                warn!("Leader lease heartbeat failed: {}", err);
+                return;
+            }
+        };
+        let node_id = leader.node_id().to_string();
+
+        let message = match transition {
+            LeaderTransition::Promoted => {
+                warn!("Node {} acquired the leader lease; taking over trading", node_id);
+                if !self.config.bot.dry_run {
+                    if let Err(err) = self.reconcile_positions(true).await {
+                        warn!("Reconciliation after promotion failed: {}", err);
+                    }
+                }
+                format!("🟢 {} is now the active trading node", node_id)
+            }
+            LeaderTransition::Demoted => {
+                error!("Node {} lost the leader lease; standing by", node_id);
+                // The new leader manages these positions from now on
+                self.strategy.core_mut().reconcile_positions(Vec::new());
+                format!("🟡 {} lost the leader lease and is standing by", node_id)
+            }
+            LeaderTransition::Unchanged(_) => return,
+        };
+
+        if let Some(telegram) = &self.telegram {
+            if let Err(err) = telegram.send_message(&message).await {
+                warn!("Failed to send leadership alert: {}", err);
+            }
+        }
+    }
+
+    /// Price a position would close at: longs sell at the bid, shorts buy at the ask
+    fn exit_quote(&self, side: OrderSide, mid: f64) -> f64 {
+        match (self.last_quote, side) {
+            (Some((bid, _)), OrderSide::Buy) => bid,
+            (Some((_, ask)), OrderSide::Sell) => ask,
+            (None, _) if self.config.bot.dry_run => self.simulated_broker.exit_quote(side, mid),
+            (None, _) => mid,
+        }
+    }
+
+    /// Spread of the latest two-sided quote
+    fn quote_spread(&self) -> Option<f64> {
+        self.last_quote.map(|(bid, ask)| ask - bid)
+    }
+
+    /// Asset class of the traded symbol, when the broker provided one
+    fn asset_class(&self) -> Option<&str> {
+        self.symbol_class.as_ref().and_then(|c| c.asset_class.as_deref())
+    }
+
+    fn tag_asset_class(&self, position: Position) -> Position {
+        match self.asset_class() {
+            Some(class) => position.with_asset_class(class),
+            None => position,
+        }
+    }
+
     fn persist_open_position(&self, position: &Position) {
         let Some(db) = &self.position_db else {
             return;
@@ -1290,6 +3960,51 @@ impl TradingBot {
         }
     }
 
+    /// Store a freshly resolved sentiment reading for analysis and backtests
+    fn record_sentiment(&self, result: &SentimentResult, breakdown: Option<String>) {
+        let Some(db) = &self.position_db else {
+            return;
+        };
+        let record = SentimentRecord {
+            symbol: self.config.trading.symbol.clone(),
+            recorded_at: result.timestamp,
+            source: result.source.clone(),
+            score: result.score,
+            confidence: result.confidence,
+            summary: result.raw_text.clone().or(breakdown),
+        };
+        if let Err(err) = db.save_sentiment(&record) {
+            warn!("Failed to persist sentiment reading: {}", err);
+        }
+    }
+
+    /// Store the candle's indicators for the Grafana datasource
+    fn record_indicator_sample(&self, candle: &Candle, rsi: f64, sentiment: i32) {
+        let Some(db) = &self.position_db else {
+            return;
+        };
+        let sample = IndicatorSample {
+            symbol: self.config.trading.symbol.clone(),
+            recorded_at: candle.end_time(),
+            close: candle.close,
+            rsi,
+            sentiment,
+        };
+        if let Err(err) = db.save_indicator_sample(&sample) {
+            warn!("Failed to persist indicator sample: {}", err);
+        }
+    }
+
+    /// Store a closed candle for warm-up after a restart
+    fn persist_candle(&self, candle: &Candle) {
+        let Some(db) = &self.position_db else {
+            return;
+        };
+        if let Err(err) = db.save_candle(&self.config.trading.symbol, candle) {
+            warn!("Failed to persist candle: {}", err);
+        }
+    }
+
     fn persist_close_position(&self, position_id: &str, exit_price: f64, reason: CloseReason) {
         let Some(db) = &self.position_db else {
             return;
@@ -1324,6 +4039,32 @@ fn init_position_db() -> Option<PositionDatabase> {
     }
 }
 
+/// Fetch a market brief from Perplexity, persist it and push it to Telegram.
+async fn send_market_brief(
+    perplexity: &PerplexityClient,
+    position_db: Option<&PositionDatabase>,
+    telegram: Option<&TelegramNotifier>,
+) -> Result<()> {
+    let brief = perplexity.get_market_brief().await?;
+    info!(
+        "Market brief generated for {} (score: {}, confidence: {:.2})",
+        brief.symbol, brief.score, brief.confidence
+    );
+
+    if let Some(db) = position_db {
+        if let Err(err) = db.save_market_brief(&brief) {
+            warn!("Failed to persist market brief: {}", err);
+        }
+    }
+
+    match telegram {
+        Some(telegram) => telegram.send_message(&brief.to_message()).await?,
+        None => info!("Telegram not configured; market brief stored only"),
+    }
+
+    Ok(())
+}
+
 fn is_auth_api_error(message: &str) -> bool {
     let msg = message.to_ascii_uppercase();
     msg.contains("CH_CLIENT_NOT_AUTHENTICATED")
@@ -1334,6 +4075,13 @@ fn is_auth_api_error(message: &str) -> bool {
 
 fn should_retry_ctrader(err: &BotError) -> bool {
     match err {
+        // Refused by the reconnect breaker: retrying before its cool-down
+        // ends is pointless
+        BotError::CTrader(CTraderError::ConnectionFailed(message))
+            if message.starts_with("reconnect breaker open") =>
+        {
+            false
+        }
         BotError::CTrader(CTraderError::ConnectionFailed(_))
         | BotError::CTrader(CTraderError::Timeout)
         | BotError::CTrader(CTraderError::Disconnected)
@@ -1343,6 +4091,7 @@ fn should_retry_ctrader(err: &BotError) -> bool {
     }
 }
 
+/// Startup connect; later reconnects are the client's reader task's job
 async fn connect_with_retry(client: &CTraderClient) -> Result<()> {
     let config = RetryConfig::default()
         .with_max_delay(30000)
@@ -1362,6 +4111,45 @@ async fn authenticate_with_retry(client: &CTraderClient) -> Result<()> {
     .await
 }
 
+/// Operator alert for reconciliation problems worth acting on, if any
+fn reconciliation_alert(result: &ReconciliationResult) -> Option<String> {
+    if result.mismatched.is_empty() && result.missing_local.is_empty() {
+        return None;
+    }
+    let mut lines = vec![format!(
+        "⚠️ Reconciliation: {} mismatched, {} unknown broker positions",
+        result.mismatched.len(),
+        result.missing_local.len()
+    )];
+    lines.extend(
+        result
+            .mismatched
+            .iter()
+            .map(|(id, reason)| format!("  {}: {}", id, reason)),
+    );
+    lines.extend(
+        result
+            .missing_local
+            .iter()
+            .map(|id| format!("  {}: not in the database, adopted", id)),
+    );
+    Some(lines.join("\n"))
+}
+
+/// Confirmation timeframe from `HIGHER_TIMEFRAME`; must be longer than the
+/// strategy timeframe
+fn confirmation_timeframe(value: &str, primary: TimeFrame) -> Option<TimeFrame> {
+    let higher = parse_timeframe(value.trim());
+    if higher.duration_secs() <= primary.duration_secs() {
+        warn!(
+            "HIGHER_TIMEFRAME={} is not longer than the {} strategy timeframe; ignored",
+            value, primary
+        );
+        return None;
+    }
+    Some(higher)
+}
+
 fn parse_timeframe(timeframe: &str) -> TimeFrame {
     match timeframe.to_lowercase().as_str() {
         "1m" | "m1" => TimeFrame::M1,
@@ -1375,6 +4163,12 @@ fn parse_timeframe(timeframe: &str) -> TimeFrame {
     }
 }
 
+/// Whether ticks `previous` and `current` are more than one candle apart, so
+/// the candles built around the gap are incomplete
+fn is_feed_gap(previous: DateTime<Utc>, current: DateTime<Utc>, timeframe: TimeFrame) -> bool {
+    current - previous > timeframe.to_duration()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1425,6 +4219,36 @@ mod tests {
         assert!(remaining.num_minutes() >= 4);
     }
 
+    #[test]
+    fn test_sentiment_cache_decay() {
+        use crate::modules::scraper::SentimentType;
+
+        let mut cache = SentimentCache::new(5).with_half_life(30);
+        cache.update(80, Some(SentimentResult::new(80, "test")));
+        let read_at = cache.timestamp;
+
+        assert_eq!(cache.decayed_value(read_at), 80);
+        assert_eq!(cache.decayed_value(read_at + ChronoDuration::minutes(30)), 40);
+        let later = cache
+            .decayed_result(read_at + ChronoDuration::minutes(60))
+            .unwrap();
+        assert_eq!(later.score, 20);
+        assert_eq!(later.sentiment_type, SentimentType::Neutral);
+
+        // A failed refresh keeps the reading, which goes on decaying
+        cache.invalidate();
+        let fallback = cache
+            .fallback_on_failure(read_at + ChronoDuration::minutes(90))
+            .unwrap();
+        assert_eq!(fallback.score, 10);
+        assert_eq!(cache.value, 80);
+
+        let mut flat = SentimentCache::new(5).with_half_life(0);
+        flat.update(80, Some(SentimentResult::new(80, "test")));
+        assert_eq!(flat.decayed_value(Utc::now() + ChronoDuration::minutes(60)), 80);
+        assert!(flat.fallback_on_failure(Utc::now()).is_none());
+    }
+
     #[test]
     fn test_sentiment_cache_default() {
         let cache = SentimentCache::default();
@@ -1432,6 +4256,32 @@ mod tests {
         assert_eq!(cache.value, NEUTRAL_SENTIMENT);
     }
 
+    #[test]
+    fn test_reconciliation_alert() {
+        let mut result = ReconciliationResult::new();
+        result.synced.push("1".to_string());
+        assert_eq!(reconciliation_alert(&result), None);
+
+        result
+            .mismatched
+            .push(("2".to_string(), "volume 1 vs 2".to_string()));
+        result.missing_local.push(3);
+        let alert = reconciliation_alert(&result).unwrap();
+        assert!(alert.starts_with("⚠️ Reconciliation: 1 mismatched, 1 unknown broker positions"));
+        assert!(alert.contains("2: volume 1 vs 2"));
+        assert!(alert.contains("3: not in the database, adopted"));
+    }
+
+    #[test]
+    fn test_feed_gap_is_longer_than_one_candle() {
+        use chrono::TimeZone;
+
+        let at = |minute: u32| Utc.with_ymd_and_hms(2024, 3, 4, 8, minute, 0).unwrap();
+        assert!(!is_feed_gap(at(0), at(5), TimeFrame::M5));
+        assert!(is_feed_gap(at(0), at(6), TimeFrame::M5));
+        assert!(!is_feed_gap(at(0), at(45), TimeFrame::H1));
+    }
+
     #[test]
     fn test_parse_timeframe() {
         assert_eq!(parse_timeframe("1m"), TimeFrame::M1);
@@ -1549,4 +4399,80 @@ mod tests {
             digits_5
         );
     }
+
+    #[tokio::test]
+    async fn test_breaker_scenarios_close_nothing_and_raise_no_alert() {
+        let mut config = Config::default();
+        config.bot.dry_run = true;
+        config.strategy.opposite_signal_policy = OppositeSignalPolicy::Close;
+        let mut bot = TradingBot::new(config).unwrap();
+        bot.position_db = None;
+        bot.last_price = Some(4850.0);
+        let symbol = bot.config.trading.symbol.clone();
+        let short = Position::new("7", symbol, OrderSide::Sell, 4850.0, 1.0);
+        bot.strategy.core_mut().add_position(short);
+        let (_, mut alerts) = bot
+            .event_channel
+            .subscribe(crate::modules::trading::EventFilter::event_types(vec![
+                crate::modules::trading::EventType::Alert,
+            ]))
+            .await;
+
+        let outcomes = bot.run_breaker_scenarios(&BreakerScenario::ALL).await;
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|outcome| !outcome.failed()));
+        // The synthetic buy would close the short under the close policy
+        assert_eq!(bot.strategy.core().get_open_positions().len(), 1);
+        assert!(alerts.try_recv().is_err());
+        assert!(!bot.breaker_tripped);
+        assert_eq!(bot.orders_sent, 0);
+    }
+
+    #[test]
+    fn test_close_retries_back_off() {
+        let mut retries = CloseRetries::default();
+        let now = Instant::now();
+        assert!(retries.ready("7", now));
+
+        assert_eq!(retries.failed("7", now), Duration::from_secs(5));
+        assert!(!retries.ready("7", now));
+        assert!(retries.ready("7", now + Duration::from_secs(5)));
+        assert!(retries.ready("8", now));
+
+        assert_eq!(retries.failed("7", now), Duration::from_secs(10));
+        for _ in 0..10 {
+            retries.failed("7", now);
+        }
+        assert_eq!(retries.failed("7", now), Duration::from_secs(300));
+
+        retries.retain_open(&[]);
+        assert!(retries.ready("7", now));
+    }
+
+    #[tokio::test]
+    async fn test_failed_exit_close_backs_off_and_checks_the_rest() {
+        let mut config = Config::default();
+        config.bot.dry_run = false;
+        let mut bot = TradingBot::new(config).unwrap();
+        bot.position_db = None;
+        bot.last_price = Some(4850.0);
+        for id in ["7", "8"] {
+            let symbol = bot.config.trading.symbol.clone();
+            // 2% down, past the 1.5% stop
+            let long = Position::new(id, symbol, OrderSide::Buy, 4950.0, 1.0);
+            bot.strategy.core_mut().add_position(long);
+        }
+
+        // Not connected: every close fails, none may abort the others
+        bot.check_exits().await.unwrap();
+        assert_eq!(bot.strategy.core().get_open_positions().len(), 2);
+        assert_eq!(bot.close_retries.failures["7"].0, 1);
+        assert_eq!(bot.close_retries.failures["8"].0, 1);
+
+        // The next tick waits out the backoff instead of trying again
+        bot.check_exits().await.unwrap();
+        assert_eq!(bot.close_retries.failures["7"].0, 1);
+        assert_eq!(bot.close_retries.failures["8"].0, 1);
+    }
 }
@@ -3,9 +3,19 @@
 //! Loads configuration from environment variables and .env file.
 
 use crate::error::{BotError, Result};
+use crate::modules::scraper::{FundamentalsConfig, SentimentAggregator};
+use crate::modules::trading::{
+    BlackoutSchedule, CrossMarketConfig, OppositeSignalPolicy, SpreadGuard, StrategyKind,
+    StructureStopConfig, TrendFollowingConfig, VolatilityConfig, VwapReversionConfig,
+};
+use crate::modules::utils::MarketCalendar;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
 
+/// Age at which a cached sentiment score counts half (SENTIMENT_HALF_LIFE_MINUTES)
+const DEFAULT_SENTIMENT_HALF_LIFE_MINUTES: i64 = 30;
+
 /// Main configuration structure
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -15,6 +25,9 @@ pub struct Config {
     pub strategy: StrategyConfig,
     pub kols: Vec<String>,
     pub bot: BotConfig,
+    /// Filters, stops and sentiment inputs configured by their own modules
+    #[serde(skip)]
+    pub decision: DecisionConfig,
 }
 
 /// Trading environment (DEMO or LIVE)
@@ -138,12 +151,139 @@ impl CTraderConfig {
     }
 }
 
-/// Perplexity API configuration
+/// Service answering the sentiment prompts (`LLM_BACKEND`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmBackend {
+    /// Perplexity Sonar, with web search and citations
+    #[default]
+    Perplexity,
+    /// Any OpenAI-compatible chat completions API (OpenAI, Groq, vLLM, ...)
+    OpenAi,
+    /// Anthropic Messages API
+    Anthropic,
+    /// Local Ollama server; needs no API key
+    Ollama,
+}
+
+impl LlmBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Perplexity => "perplexity",
+            Self::OpenAi => "openai",
+            Self::Anthropic => "anthropic",
+            Self::Ollama => "ollama",
+        }
+    }
+
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            Self::Perplexity => "https://api.perplexity.ai/chat/completions",
+            Self::OpenAi => "https://api.openai.com/v1/chat/completions",
+            Self::Anthropic => "https://api.anthropic.com/v1/messages",
+            Self::Ollama => "http://localhost:11434/api/chat",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            Self::Perplexity => "sonar",
+            Self::OpenAi => "gpt-4o-mini",
+            Self::Anthropic => "claude-3-5-haiku-latest",
+            Self::Ollama => "llama3.1",
+        }
+    }
+
+    pub fn needs_api_key(&self) -> bool {
+        !matches!(self, Self::Ollama)
+    }
+}
+
+impl std::str::FromStr for LlmBackend {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "perplexity" => Ok(Self::Perplexity),
+            "openai" | "openai_compatible" => Ok(Self::OpenAi),
+            "anthropic" => Ok(Self::Anthropic),
+            "ollama" => Ok(Self::Ollama),
+            other => Err(BotError::Config(format!(
+                "invalid LLM_BACKEND: {:?} (expected perplexity, openai, anthropic or ollama)",
+                other
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for LlmBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// LLM sentiment configuration (Perplexity unless `LLM_BACKEND` says otherwise)
 #[derive(Debug, Clone, Deserialize)]
 pub struct PerplexityConfig {
+    /// Service the prompts go to
+    #[serde(default)]
+    pub backend: LlmBackend,
+    /// Empty for backends that need none
     pub api_key: String,
     pub endpoint: String,
     pub model: String,
+    /// Sampling temperature, 0.0-2.0 (lower = more repeatable scores)
+    #[serde(default = "default_perplexity_temperature")]
+    pub temperature: f32,
+    /// Token budget of a sentiment query (market briefs use their own)
+    #[serde(default = "default_perplexity_max_tokens")]
+    pub max_tokens: u32,
+    /// Prompt overrides; the built-in prompts are used where unset
+    #[serde(default)]
+    pub prompts: PromptTemplates,
+}
+
+fn default_perplexity_temperature() -> f32 {
+    0.3
+}
+
+fn default_perplexity_max_tokens() -> u32 {
+    500
+}
+
+/// Perplexity prompt templates; `{symbol}` is replaced with the traded symbol
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PromptTemplates {
+    /// System message setting the analyst's role
+    pub system: Option<String>,
+    /// Short-term sentiment query; should ask for `SENTIMENT_SCORE:` and
+    /// `CONFIDENCE:` lines, or the score falls back to keyword analysis
+    pub sentiment: Option<String>,
+    /// Long-form market brief query
+    pub market_brief: Option<String>,
+}
+
+impl PromptTemplates {
+    /// Read `PERPLEXITY_SYSTEM_PROMPT`, `PERPLEXITY_SENTIMENT_PROMPT` and
+    /// `PERPLEXITY_BRIEF_PROMPT`, each given inline or as a file path through
+    /// the same name with a `_FILE` suffix
+    fn from_env() -> Result<Self> {
+        let template = |key: &str| -> Result<Option<String>> {
+            if let Ok(path) = env::var(format!("{}_FILE", key)) {
+                if !path.trim().is_empty() {
+                    return std::fs::read_to_string(path.trim()).map(Some).map_err(|e| {
+                        BotError::Config(format!("cannot read {}_FILE {:?}: {}", key, path, e))
+                    });
+                }
+            }
+            Ok(env::var(key).ok().filter(|t| !t.trim().is_empty()))
+        };
+        Ok(Self {
+            system: template("PERPLEXITY_SYSTEM_PROMPT")?,
+            sentiment: template("PERPLEXITY_SENTIMENT_PROMPT")?,
+            market_brief: template("PERPLEXITY_BRIEF_PROMPT")?,
+        })
+    }
 }
 
 /// Trading parameters
@@ -156,6 +296,184 @@ pub struct TradingConfig {
     pub max_positions: usize,
     pub max_daily_loss_percent: f64,
     pub initial_balance: f64,
+    /// Profit (%) at which an open position's stop becomes a broker-side
+    /// trailing stop; `None` keeps fixed stops
+    #[serde(default)]
+    pub trailing_activation_percent: Option<f64>,
+    /// ATR-based SL/TP distances; `None` uses the fixed percentages
+    #[serde(default)]
+    pub atr_exits: Option<AtrExitConfig>,
+    /// Move the stop to entry once a position is far enough in profit;
+    /// `None` leaves stops where they were placed
+    #[serde(default)]
+    pub break_even: Option<BreakEvenConfig>,
+    /// Add smaller legs to a winning position when the signal re-fires;
+    /// `None` opens one position per entry
+    #[serde(default)]
+    pub scale_in: Option<ScaleInConfig>,
+    /// Closed candles that must pass after an entry before another entry in
+    /// the same direction; 0 disables the cooldown
+    #[serde(default)]
+    pub entry_cooldown_candles: usize,
+    /// How entry size is derived; fixed risk per trade by default
+    #[serde(default)]
+    pub sizing: SizingPolicy,
+}
+
+/// Stop-loss / take-profit at `N × ATR` from the entry (`SL_TP_MODE=atr`)
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct AtrExitConfig {
+    /// ATR period, in strategy-timeframe candles
+    pub period: usize,
+    /// Stop-loss distance in ATRs
+    pub stop_loss_multiplier: f64,
+    /// Take-profit distance in ATRs
+    pub take_profit_multiplier: f64,
+}
+
+impl AtrExitConfig {
+    /// Read `ATR_PERIOD`, `ATR_STOP_MULTIPLIER` and `ATR_TAKE_PROFIT_MULTIPLIER`
+    /// when `SL_TP_MODE=atr`
+    fn from_env() -> Option<Self> {
+        if !get_env_or("SL_TP_MODE", "percent").eq_ignore_ascii_case("atr") {
+            return None;
+        }
+        Some(Self {
+            period: get_env_or("ATR_PERIOD", "14").parse().unwrap_or(14),
+            stop_loss_multiplier: get_env_or("ATR_STOP_MULTIPLIER", "1.5")
+                .parse()
+                .unwrap_or(1.5),
+            take_profit_multiplier: get_env_or("ATR_TAKE_PROFIT_MULTIPLIER", "2.0")
+                .parse()
+                .unwrap_or(2.0),
+        })
+    }
+}
+
+/// Break-even stop management (`BREAK_EVEN_ACTIVATION_PERCENT`)
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct BreakEvenConfig {
+    /// Profit (%) at which the stop moves to entry
+    pub activation_percent: f64,
+    /// Distance (%) beyond the entry, in the position's favour, at which the
+    /// stop is placed (covers spread and commission)
+    pub offset_percent: f64,
+}
+
+impl BreakEvenConfig {
+    /// Read `BREAK_EVEN_ACTIVATION_PERCENT` and `BREAK_EVEN_OFFSET_PERCENT`
+    /// (default 0); disabled while the activation is unset
+    fn from_env() -> Option<Self> {
+        let activation_percent = env::var("BREAK_EVEN_ACTIVATION_PERCENT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())?;
+        Some(Self {
+            activation_percent,
+            offset_percent: get_env_or("BREAK_EVEN_OFFSET_PERCENT", "0")
+                .parse()
+                .unwrap_or(0.0),
+        })
+    }
+}
+
+/// Scale-in / pyramiding (`SCALE_IN_MAX_ADDS`)
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ScaleInConfig {
+    /// Legs that may be added to one position
+    pub max_adds: usize,
+    /// Each leg's size relative to the previous one (0.5 = half)
+    pub size_factor: f64,
+    /// Profit (%) over the average entry the position needs before an add
+    pub min_profit_percent: f64,
+}
+
+impl ScaleInConfig {
+    /// Read `SCALE_IN_MAX_ADDS`, `SCALE_IN_SIZE_FACTOR` (default 0.5) and
+    /// `SCALE_IN_MIN_PROFIT_PERCENT` (default 0.5); disabled while the
+    /// number of adds is unset or 0
+    fn from_env() -> Option<Self> {
+        let max_adds = env::var("SCALE_IN_MAX_ADDS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| *v > 0)?;
+        Some(Self {
+            max_adds,
+            size_factor: get_env_or("SCALE_IN_SIZE_FACTOR", "0.5")
+                .parse()
+                .unwrap_or(0.5),
+            min_profit_percent: get_env_or("SCALE_IN_MIN_PROFIT_PERCENT", "0.5")
+                .parse()
+                .unwrap_or(0.5),
+        })
+    }
+}
+
+/// Position sizing policy (`POSITION_SIZING`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SizingPolicy {
+    /// Lose `RISK_PER_TRADE` % of the balance if the stop is hit
+    #[default]
+    FixedRisk,
+    /// The same size every entry, in broker lots
+    FixedLots { lots: f64 },
+    /// Risk `fraction` of the Kelly criterion estimated from the last
+    /// `lookback` closed trades, at most `max_risk_percent` of the balance.
+    /// Fixed risk applies until `min_trades` trades are recorded.
+    KellyFraction {
+        fraction: f64,
+        max_risk_percent: f64,
+        lookback: usize,
+        min_trades: usize,
+    },
+    /// Size so that a one-ATR move is `target_percent` of the balance
+    VolatilityTarget { target_percent: f64 },
+}
+
+impl SizingPolicy {
+    /// Read `POSITION_SIZING` (`fixed_risk`, `fixed_lots`, `kelly` or
+    /// `volatility_target`) and the parameters of the chosen policy
+    fn from_env() -> Result<Self> {
+        let number = |key: &str, default: &str| -> f64 {
+            get_env_or(key, default).trim().parse().unwrap_or(f64::NAN)
+        };
+        let count = |key: &str, default: &str| -> usize {
+            get_env_or(key, default).trim().parse().unwrap_or(0)
+        };
+        let mode = get_env_or("POSITION_SIZING", "fixed_risk").to_ascii_lowercase();
+        match mode.trim() {
+            "fixed_risk" => Ok(Self::FixedRisk),
+            "fixed_lots" => Ok(Self::FixedLots {
+                lots: number("FIXED_LOTS", "NaN"),
+            }),
+            "kelly" | "kelly_fraction" => Ok(Self::KellyFraction {
+                fraction: number("KELLY_FRACTION", "0.5"),
+                max_risk_percent: number("KELLY_MAX_RISK_PERCENT", "2.0"),
+                lookback: count("KELLY_LOOKBACK_TRADES", "50"),
+                min_trades: count("KELLY_MIN_TRADES", "20"),
+            }),
+            "volatility_target" => Ok(Self::VolatilityTarget {
+                target_percent: number("VOL_TARGET_PERCENT", "NaN"),
+            }),
+            other => Err(BotError::Config(format!(
+                "POSITION_SIZING must be fixed_risk, fixed_lots, kelly or volatility_target, got {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Most of the balance one entry can lose at its stop, in percent, when
+    /// the policy bounds it
+    pub fn max_risk_percent(&self, risk_per_trade: f64) -> Option<f64> {
+        match self {
+            Self::FixedRisk => Some(risk_per_trade),
+            // Fixed risk until enough trades are recorded
+            Self::KellyFraction {
+                max_risk_percent, ..
+            } => Some(max_risk_percent.max(risk_per_trade)),
+            Self::FixedLots { .. } | Self::VolatilityTarget { .. } => None,
+        }
+    }
 }
 
 /// Strategy parameters
@@ -166,6 +484,246 @@ pub struct StrategyConfig {
     pub rsi_overbought: f64,
     pub rsi_timeframe: String,
     pub sentiment_threshold: i32,
+    /// How open positions exit on RSI, besides TP/SL
+    #[serde(default)]
+    pub rsi_exit: RsiExitMode,
+    /// Require the MACD histogram to turn with the RSI signal (`MACD_CONFIRMATION`)
+    #[serde(default)]
+    pub macd_confirmation: bool,
+    /// RSI bounds on a longer timeframe; `None` leaves entries unconfirmed
+    #[serde(default)]
+    pub higher_timeframe: Option<HigherTimeframeConfig>,
+    /// What a signal against open positions does
+    #[serde(default)]
+    pub opposite_signal_policy: OppositeSignalPolicy,
+    /// Built-in strategy traded (`STRATEGY`)
+    #[serde(skip)]
+    pub kind: StrategyKind,
+    /// `VWAP_*` parameters, read when `kind` is VWAP reversion
+    #[serde(skip)]
+    pub vwap: VwapReversionConfig,
+    /// `TREND_*` parameters, read when `kind` is trend following
+    #[serde(skip)]
+    pub trend: TrendFollowingConfig,
+}
+
+/// Higher-timeframe RSI confirmation, built from the same ticks (`HIGHER_TIMEFRAME`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HigherTimeframeConfig {
+    /// Confirmation timeframe (`1h`, `4h`, ...); ignored unless longer than `rsi_timeframe`
+    pub timeframe: String,
+    /// Buys only while the higher-timeframe RSI is below this
+    pub rsi_buy_below: f64,
+    /// Sells only while the higher-timeframe RSI is above this
+    pub rsi_sell_above: f64,
+}
+
+impl HigherTimeframeConfig {
+    /// Read `HIGHER_TIMEFRAME` and the `HIGHER_TF_RSI_BUY_BELOW` /
+    /// `HIGHER_TF_RSI_SELL_ABOVE` bounds (default 50); `None` when unset
+    fn from_env() -> Option<Self> {
+        let timeframe = get_env_or("HIGHER_TIMEFRAME", "").trim().to_string();
+        if timeframe.is_empty() {
+            return None;
+        }
+        let bound = |key: &str| get_env_or(key, "50").trim().parse().unwrap_or(50.0);
+        Some(Self {
+            timeframe,
+            rsi_buy_below: bound("HIGHER_TF_RSI_BUY_BELOW"),
+            rsi_sell_above: bound("HIGHER_TF_RSI_SELL_ABOVE"),
+        })
+    }
+}
+
+/// RSI-based exit for open positions (`RSI_EXIT_MODE`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum RsiExitMode {
+    /// Positions only exit on TP/SL and the other risk rules
+    #[default]
+    Off,
+    /// Close longs when RSI crosses back above `long_exit` and shorts when
+    /// it crosses back below `short_exit`, instead of waiting for the TP
+    MeanReversion { long_exit: f64, short_exit: f64 },
+}
+
+impl RsiExitMode {
+    /// Read `RSI_EXIT_MODE` (`off` or `mean_reversion`) and, for mean
+    /// reversion, the `RSI_EXIT_LONG` / `RSI_EXIT_SHORT` levels
+    fn from_env() -> Result<Self> {
+        let level = |key: &str| -> f64 {
+            get_env_or(key, "50").trim().parse().unwrap_or(f64::NAN)
+        };
+        let mode = get_env_or("RSI_EXIT_MODE", "off").to_ascii_lowercase();
+        match mode.trim() {
+            "off" | "none" => Ok(Self::Off),
+            "mean_reversion" => Ok(Self::MeanReversion {
+                long_exit: level("RSI_EXIT_LONG"),
+                short_exit: level("RSI_EXIT_SHORT"),
+            }),
+            other => Err(BotError::Config(format!(
+                "RSI_EXIT_MODE must be off or mean_reversion, got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Decision settings owned by the trading and sentiment modules, whose
+/// `from_env` documents the variables. Loaded with the rest of the config so
+/// the strategy fingerprint covers them.
+#[derive(Debug, Clone)]
+pub struct DecisionConfig {
+    /// Exchange sessions outside which no entry is taken (`MARKET_HOURS_ENABLED`)
+    pub market_calendar: Option<MarketCalendar>,
+    /// No entries around scheduled releases (`NEWS_BLACKOUT_ENABLED`)
+    pub blackout: Option<BlackoutSchedule>,
+    /// Volatility-regime sizing and halt (`VOL_REGIME_ENABLED`)
+    pub volatility: Option<VolatilityConfig>,
+    /// Swing or chandelier stops; wins over a strategy's own (`STRUCTURE_STOP_MODE`)
+    pub structure_stop: Option<StructureStopConfig>,
+    /// Spread limits for new entries (`MAX_SPREAD`, `MAX_SPREAD_PERCENT`)
+    pub spread_guard: SpreadGuard,
+    /// Soybean oil / crude correlation filter (`CROSS_MARKET_SYMBOLS`)
+    pub cross_market: Option<CrossMarketConfig>,
+    /// External series blended into sentiment (`FUNDAMENTALS_ENABLED`)
+    pub fundamentals: Option<FundamentalsConfig>,
+    /// Sentiment provider weights by name, missing = 1.0
+    /// (`SENTIMENT_PROVIDER_WEIGHTS`)
+    pub sentiment_weights: HashMap<String, f64>,
+    /// Age at which a cached sentiment score counts half, 0 = no decay
+    /// (`SENTIMENT_HALF_LIFE_MINUTES`)
+    pub sentiment_half_life_minutes: i64,
+    /// Extra RSI distance required when sentiment sources fully disagree;
+    /// `None` keeps the strategy default (`SENTIMENT_DISAGREEMENT_RSI_MARGIN`)
+    pub disagreement_rsi_margin: Option<f64>,
+    /// Open positions allowed per asset class (`MAX_POSITIONS_PER_ASSET_CLASS`)
+    pub max_positions_per_asset_class: Option<usize>,
+}
+
+impl DecisionConfig {
+    /// Read every module's settings; the session calendar is `symbol`'s
+    fn from_env(symbol: &str) -> Result<Self> {
+        let var = |key: &str| env::var(key).ok().map(|v| v.trim().to_string());
+        Ok(Self {
+            market_calendar: MarketCalendar::from_env(symbol)?,
+            blackout: BlackoutSchedule::from_env()?,
+            volatility: VolatilityConfig::from_env()?,
+            structure_stop: StructureStopConfig::from_env()?,
+            spread_guard: SpreadGuard::from_env()?,
+            cross_market: CrossMarketConfig::from_env()?,
+            fundamentals: FundamentalsConfig::from_env()?,
+            sentiment_weights: SentimentAggregator::weights_from_env()?,
+            sentiment_half_life_minutes: var("SENTIMENT_HALF_LIFE_MINUTES")
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|minutes| *minutes >= 0)
+                .unwrap_or(DEFAULT_SENTIMENT_HALF_LIFE_MINUTES),
+            disagreement_rsi_margin: var("SENTIMENT_DISAGREEMENT_RSI_MARGIN")
+                .and_then(|v| v.parse::<f64>().ok()),
+            max_positions_per_asset_class: var("MAX_POSITIONS_PER_ASSET_CLASS")
+                .and_then(|v| v.parse::<usize>().ok()),
+        })
+    }
+
+    /// Add the settings that are on to the strategy hash input; all off
+    /// adds nothing, so hashes from before these were covered still match
+    fn append_canonical(&self, canonical: &mut String) {
+        if let Some(calendar) = &self.market_calendar {
+            canonical.push_str(&format!("|hours:{}", calendar.canonical()));
+        }
+        if let Some(blackout) = &self.blackout {
+            canonical.push_str(&format!("|blackout:{}", blackout.canonical()));
+        }
+        if let Some(vol) = &self.volatility {
+            canonical.push_str(&format!(
+                "|vol:{}/{}<{}x{}<{}",
+                vol.window, vol.baseline, vol.reduce_ratio, vol.reduce_factor, vol.halt_ratio
+            ));
+        }
+        if let Some(stop) = &self.structure_stop {
+            canonical.push_str(&format!(
+                "|structure:{}/{}x{}",
+                stop.mode, stop.lookback, stop.atr_multiplier
+            ));
+        }
+        if self.spread_guard.is_enabled() {
+            canonical.push_str(&format!(
+                "|spread:{:?}/{:?}",
+                self.spread_guard.max_spread, self.spread_guard.max_spread_percent
+            ));
+        }
+        if let Some(cross) = &self.cross_market {
+            canonical.push_str(&format!(
+                "|cross:{}/{}>{}/{}+{}{}",
+                cross.symbols.join(","),
+                cross.window,
+                cross.min_correlation,
+                cross.min_move_percent,
+                cross.boost,
+                if cross.veto { "/veto" } else { "" }
+            ));
+        }
+        if let Some(fundamentals) = &self.fundamentals {
+            let series: Vec<String> = fundamentals
+                .series
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{}={} {:?}~{}/{}x{}",
+                        entry.name,
+                        entry.url,
+                        entry.field,
+                        entry.neutral,
+                        entry.scale,
+                        entry.weight
+                    )
+                })
+                .collect();
+            canonical.push_str(&format!(
+                "|fundamentals:{}@{}",
+                series.join(";"),
+                fundamentals.weight
+            ));
+        }
+        if !self.sentiment_weights.is_empty() {
+            // Sorted: the map has no order of its own
+            let mut weights: Vec<String> = self
+                .sentiment_weights
+                .iter()
+                .map(|(name, weight)| format!("{}:{}", name, weight))
+                .collect();
+            weights.sort();
+            canonical.push_str(&format!("|weights:{}", weights.join(",")));
+        }
+        if self.sentiment_half_life_minutes != DEFAULT_SENTIMENT_HALF_LIFE_MINUTES {
+            canonical.push_str(&format!("|halflife:{}", self.sentiment_half_life_minutes));
+        }
+        if let Some(margin) = self.disagreement_rsi_margin {
+            canonical.push_str(&format!("|disagree:{}", margin));
+        }
+        if let Some(limit) = self.max_positions_per_asset_class {
+            canonical.push_str(&format!("|perclass:{}", limit));
+        }
+    }
+}
+
+/// Every filter off, as when none of the variables is set
+impl Default for DecisionConfig {
+    fn default() -> Self {
+        Self {
+            market_calendar: None,
+            blackout: None,
+            volatility: None,
+            structure_stop: None,
+            spread_guard: SpreadGuard::default(),
+            cross_market: None,
+            fundamentals: None,
+            sentiment_weights: HashMap::new(),
+            sentiment_half_life_minutes: DEFAULT_SENTIMENT_HALF_LIFE_MINUTES,
+            disagreement_rsi_margin: None,
+            max_positions_per_asset_class: None,
+        }
+    }
 }
 
 /// Bot runtime settings
@@ -182,6 +740,9 @@ impl Config {
         // Load .env file if present
         dotenvy::dotenv().ok();
 
+        let llm_backend: LlmBackend = get_env_or("LLM_BACKEND", "perplexity").parse()?;
+        let symbol = get_env_or("SYMBOL", "FCPO");
+        let kind = StrategyKind::from_env()?;
         let config = Config {
             ctrader: CTraderConfig {
                 environment: get_env_or("CTRADER_ENVIRONMENT", "demo")
@@ -198,15 +759,24 @@ impl Config {
                 account_id_live: env::var("CTRADER_ACCOUNT_ID_LIVE").ok(),
             },
             perplexity: PerplexityConfig {
-                api_key: get_env("PERPLEXITY_API_KEY")?,
-                endpoint: get_env_or(
-                    "PERPLEXITY_ENDPOINT",
-                    "https://api.perplexity.ai/chat/completions",
-                ),
-                model: get_env_or("PERPLEXITY_MODEL", "sonar"),
+                backend: llm_backend,
+                api_key: llm_env("API_KEY", llm_backend).unwrap_or_default(),
+                endpoint: llm_env("ENDPOINT", llm_backend)
+                    .unwrap_or_else(|| llm_backend.default_endpoint().to_string()),
+                model: llm_env("MODEL", llm_backend)
+                    .unwrap_or_else(|| llm_backend.default_model().to_string()),
+                temperature: get_env_or("PERPLEXITY_TEMPERATURE", "0.3")
+                    .trim()
+                    .parse()
+                    .unwrap_or(f32::NAN),
+                max_tokens: get_env_or("PERPLEXITY_MAX_TOKENS", "500")
+                    .trim()
+                    .parse()
+                    .unwrap_or(0),
+                prompts: PromptTemplates::from_env()?,
             },
             trading: TradingConfig {
-                symbol: get_env_or("SYMBOL", "FCPO"),
+                symbol: symbol.clone(),
                 risk_per_trade: get_env_or("RISK_PER_TRADE", "1.0").parse().unwrap_or(1.0),
                 take_profit_percent: get_env_or("TAKE_PROFIT_PERCENT", "2.0")
                     .parse()
@@ -221,15 +791,45 @@ impl Config {
                 initial_balance: get_env_or("INITIAL_BALANCE", "10000.0")
                     .parse()
                     .unwrap_or(10000.0),
+                trailing_activation_percent: env::var("TRAILING_ACTIVATION_PERCENT")
+                    .ok()
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .filter(|v| *v > 0.0),
+                atr_exits: AtrExitConfig::from_env(),
+                break_even: BreakEvenConfig::from_env(),
+                scale_in: ScaleInConfig::from_env(),
+                entry_cooldown_candles: get_env_or("ENTRY_COOLDOWN_CANDLES", "0")
+                    .parse()
+                    .unwrap_or(0),
+                sizing: SizingPolicy::from_env()?,
             },
             strategy: StrategyConfig {
                 rsi_period: get_env_or("RSI_PERIOD", "14").parse().unwrap_or(14),
                 rsi_oversold: get_env_or("RSI_OVERSOLD", "30").parse().unwrap_or(30.0),
                 rsi_overbought: get_env_or("RSI_OVERBOUGHT", "70").parse().unwrap_or(70.0),
-                rsi_timeframe: get_env_or("RSI_TIMEFRAME", "5m"),
+                rsi_timeframe: get_env_or("RSI_TIMEFRAME", kind.default_timeframe()),
                 sentiment_threshold: get_env_or("SENTIMENT_THRESHOLD", "30")
                     .parse()
                     .unwrap_or(30),
+                rsi_exit: RsiExitMode::from_env()?,
+                macd_confirmation: matches!(
+                    get_env_or("MACD_CONFIRMATION", "false")
+                        .trim()
+                        .to_ascii_lowercase()
+                        .as_str(),
+                    "1" | "true"
+                ),
+                higher_timeframe: HigherTimeframeConfig::from_env(),
+                opposite_signal_policy: OppositeSignalPolicy::from_env()?,
+                kind,
+                vwap: match kind {
+                    StrategyKind::VwapReversion => VwapReversionConfig::from_env()?,
+                    _ => VwapReversionConfig::default(),
+                },
+                trend: match kind {
+                    StrategyKind::TrendFollowing => TrendFollowingConfig::from_env()?,
+                    _ => TrendFollowingConfig::default(),
+                },
             },
             kols: vec![
                 get_env_or("KOL_1", "PalmOilTrader"),
@@ -245,6 +845,7 @@ impl Config {
                 dry_run: get_env_or("DRY_RUN", "true").parse().unwrap_or(true),
                 log_level: get_env_or("RUST_LOG", "info"),
             },
+            decision: DecisionConfig::from_env(&symbol)?,
         };
 
         Ok(config)
@@ -281,8 +882,27 @@ impl Config {
                 return Err(BotError::Config("CTRADER_ACCOUNT_ID_LIVE is required for LIVE trading".into()));
             }
         }
-        if self.perplexity.api_key.is_empty() {
-            return Err(BotError::Config("PERPLEXITY_API_KEY is required".into()));
+        if self.perplexity.api_key.is_empty() && self.perplexity.backend.needs_api_key() {
+            return Err(BotError::Config(match self.perplexity.backend {
+                LlmBackend::Perplexity => "PERPLEXITY_API_KEY is required".into(),
+                backend => format!("LLM_API_KEY is required for LLM_BACKEND={}", backend),
+            }));
+        }
+        if !(0.0..=2.0).contains(&self.perplexity.temperature) {
+            return Err(BotError::Config(
+                "PERPLEXITY_TEMPERATURE must be between 0.0 and 2.0".into(),
+            ));
+        }
+        if self.perplexity.max_tokens == 0 {
+            return Err(BotError::Config("PERPLEXITY_MAX_TOKENS must be positive".into()));
+        }
+        if let Some(template) = &self.perplexity.prompts.sentiment {
+            if !template.contains("SENTIMENT_SCORE") {
+                tracing::warn!(
+                    "PERPLEXITY_SENTIMENT_PROMPT does not ask for SENTIMENT_SCORE; \
+                     scores will come from keyword analysis"
+                );
+            }
         }
         if self.trading.take_profit_percent <= 0.0 {
             return Err(BotError::Config(
@@ -292,20 +912,237 @@ impl Config {
         if self.trading.stop_loss_percent <= 0.0 {
             return Err(BotError::Config("STOP_LOSS_PERCENT must be positive".into()));
         }
+        if let Some(atr) = &self.trading.atr_exits {
+            if atr.period == 0 {
+                return Err(BotError::Config("ATR_PERIOD must be positive".into()));
+            }
+            if atr.stop_loss_multiplier <= 0.0 || atr.take_profit_multiplier <= 0.0 {
+                return Err(BotError::Config(
+                    "ATR_STOP_MULTIPLIER and ATR_TAKE_PROFIT_MULTIPLIER must be positive".into(),
+                ));
+            }
+        }
+        if let Some(break_even) = &self.trading.break_even {
+            if break_even.activation_percent <= 0.0 || break_even.offset_percent < 0.0 {
+                return Err(BotError::Config(
+                    "BREAK_EVEN_ACTIVATION_PERCENT must be positive and BREAK_EVEN_OFFSET_PERCENT not negative"
+                        .into(),
+                ));
+            }
+            if break_even.offset_percent >= break_even.activation_percent {
+                return Err(BotError::Config(
+                    "BREAK_EVEN_OFFSET_PERCENT must be below BREAK_EVEN_ACTIVATION_PERCENT".into(),
+                ));
+            }
+        }
+        if let Some(scale_in) = &self.trading.scale_in {
+            if !(scale_in.size_factor > 0.0 && scale_in.size_factor <= 1.0) {
+                return Err(BotError::Config(
+                    "SCALE_IN_SIZE_FACTOR must be above 0 and at most 1".into(),
+                ));
+            }
+            if scale_in.min_profit_percent < 0.0 {
+                return Err(BotError::Config(
+                    "SCALE_IN_MIN_PROFIT_PERCENT must not be negative".into(),
+                ));
+            }
+        }
+        match self.trading.sizing {
+            SizingPolicy::FixedRisk => {}
+            SizingPolicy::FixedLots { lots } => {
+                if !(lots > 0.0) {
+                    return Err(BotError::Config(
+                        "FIXED_LOTS must be positive with POSITION_SIZING=fixed_lots".into(),
+                    ));
+                }
+            }
+            SizingPolicy::KellyFraction {
+                fraction,
+                max_risk_percent,
+                lookback,
+                min_trades,
+            } => {
+                if !(fraction > 0.0 && fraction <= 1.0) || !(max_risk_percent > 0.0) {
+                    return Err(BotError::Config(
+                        "KELLY_FRACTION must be in (0, 1] and KELLY_MAX_RISK_PERCENT positive"
+                            .into(),
+                    ));
+                }
+                if min_trades == 0 || lookback < min_trades {
+                    return Err(BotError::Config(
+                        "KELLY_MIN_TRADES must be positive and at most KELLY_LOOKBACK_TRADES".into(),
+                    ));
+                }
+            }
+            SizingPolicy::VolatilityTarget { target_percent } => {
+                if !(target_percent > 0.0) {
+                    return Err(BotError::Config(
+                        "VOL_TARGET_PERCENT must be positive with POSITION_SIZING=volatility_target"
+                            .into(),
+                    ));
+                }
+            }
+        }
+        if let RsiExitMode::MeanReversion {
+            long_exit,
+            short_exit,
+        } = self.strategy.rsi_exit
+        {
+            let in_range = |level: f64| level > 0.0 && level < 100.0;
+            if !in_range(long_exit) || !in_range(short_exit) {
+                return Err(BotError::Config(
+                    "RSI_EXIT_LONG and RSI_EXIT_SHORT must be between 0 and 100".into(),
+                ));
+            }
+        }
         // Verify position sizing stays within daily loss limit
-        let max_concurrent_risk = self.trading.max_positions as f64 * self.trading.risk_per_trade;
+        let risk_per_trade = self
+            .trading
+            .sizing
+            .max_risk_percent(self.trading.risk_per_trade)
+            .unwrap_or(self.trading.risk_per_trade);
+        let max_concurrent_risk = self.trading.max_positions as f64 * risk_per_trade;
         if max_concurrent_risk >= self.trading.max_daily_loss_percent {
             return Err(BotError::Config(format!(
                 "Position sizing unsafe: {} positions × {:.1}% risk = {:.1}% >= {:.1}% daily loss limit. \
-                 Reduce MAX_POSITIONS or RISK_PER_TRADE.",
+                 Reduce MAX_POSITIONS or RISK_PER_TRADE (KELLY_MAX_RISK_PERCENT with Kelly sizing).",
                 self.trading.max_positions,
-                self.trading.risk_per_trade,
+                risk_per_trade,
                 max_concurrent_risk,
                 self.trading.max_daily_loss_percent,
             )));
         }
         Ok(())
     }
+
+    /// Fingerprint of the parameters that drive trading decisions.
+    ///
+    /// Formatted as `<version>-<hash>`, where the version comes from
+    /// `STRATEGY_VERSION` (defaults to the crate version) and the hash covers
+    /// symbol, risk, TP/SL (including ATR mode, break-even, scale-in and the
+    /// trailing-stop activation), RSI/sentiment thresholds, MACD and
+    /// higher-timeframe confirmation, the opposite-signal policy, the VWAP or
+    /// trend parameters of the selected strategy and every [`DecisionConfig`]
+    /// setting (sessions, blackouts, volatility, structure stops, spread and
+    /// cross-market filters, fundamentals and sentiment weighting). Stamped on
+    /// every position so performance can be split across parameter changes.
+    pub fn strategy_fingerprint(&self) -> String {
+        let version = get_env_or("STRATEGY_VERSION", env!("CARGO_PKG_VERSION"));
+        format!("{}-{}", version, self.strategy_hash())
+    }
+
+    /// Stable 8-hex-digit hash of the strategy parameters
+    pub fn strategy_hash(&self) -> String {
+        let mut canonical = format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            self.trading.symbol,
+            self.trading.risk_per_trade,
+            self.trading.take_profit_percent,
+            self.trading.stop_loss_percent,
+            self.trading.max_positions,
+            self.trading.max_daily_loss_percent,
+            self.strategy.rsi_period,
+            self.strategy.rsi_oversold,
+            self.strategy.rsi_overbought,
+            self.strategy.rsi_timeframe,
+            self.strategy.sentiment_threshold,
+        );
+        // Only appended when set, so percent-mode hashes are unchanged
+        if let Some(atr) = &self.trading.atr_exits {
+            canonical.push_str(&format!(
+                "|atr:{}x{}x{}",
+                atr.period, atr.stop_loss_multiplier, atr.take_profit_multiplier
+            ));
+        }
+        if let Some(break_even) = &self.trading.break_even {
+            canonical.push_str(&format!(
+                "|be:{}+{}",
+                break_even.activation_percent, break_even.offset_percent
+            ));
+        }
+        if let Some(scale_in) = &self.trading.scale_in {
+            canonical.push_str(&format!(
+                "|scale:{}x{}@{}",
+                scale_in.max_adds, scale_in.size_factor, scale_in.min_profit_percent
+            ));
+        }
+        if self.trading.entry_cooldown_candles > 0 {
+            canonical.push_str(&format!("|cooldown:{}", self.trading.entry_cooldown_candles));
+        }
+        match self.trading.sizing {
+            SizingPolicy::FixedRisk => {}
+            SizingPolicy::FixedLots { lots } => canonical.push_str(&format!("|lots:{}", lots)),
+            SizingPolicy::KellyFraction {
+                fraction,
+                max_risk_percent,
+                lookback,
+                min_trades,
+            } => canonical.push_str(&format!(
+                "|kelly:{}<{}@{}/{}",
+                fraction, max_risk_percent, lookback, min_trades
+            )),
+            SizingPolicy::VolatilityTarget { target_percent } => {
+                canonical.push_str(&format!("|voltarget:{}", target_percent))
+            }
+        }
+        if let RsiExitMode::MeanReversion {
+            long_exit,
+            short_exit,
+        } = self.strategy.rsi_exit
+        {
+            canonical.push_str(&format!("|rsiexit:{}/{}", long_exit, short_exit));
+        }
+        if let Some(activation) = self.trading.trailing_activation_percent {
+            canonical.push_str(&format!("|trail:{}", activation));
+        }
+        if self.strategy.macd_confirmation {
+            canonical.push_str("|macd");
+        }
+        if let Some(higher) = &self.strategy.higher_timeframe {
+            canonical.push_str(&format!(
+                "|htf:{}<{}>{}",
+                higher.timeframe, higher.rsi_buy_below, higher.rsi_sell_above
+            ));
+        }
+        if self.strategy.opposite_signal_policy != OppositeSignalPolicy::Ignore {
+            canonical.push_str(&format!(
+                "|opposite:{}",
+                self.strategy.opposite_signal_policy
+            ));
+        }
+        match self.strategy.kind {
+            StrategyKind::RsiSentiment => {}
+            StrategyKind::VwapReversion => {
+                let vwap = &self.strategy.vwap;
+                canonical.push_str(&format!(
+                    "|vwap:{}/{}/{}@{}",
+                    vwap.band_width,
+                    vwap.sentiment_gate,
+                    vwap.min_session_candles,
+                    vwap.session_start_hour
+                ));
+            }
+            StrategyKind::TrendFollowing => {
+                let trend = &self.strategy.trend;
+                canonical.push_str(&format!(
+                    "|trend:{}/{}/{}>{}/{}x{}/{}",
+                    trend.fast_period,
+                    trend.slow_period,
+                    trend.adx_period,
+                    trend.adx_threshold,
+                    trend.trailing_atr_multiplier,
+                    trend.trailing_lookback,
+                    trend.take_profit_atr
+                ));
+            }
+        }
+        self.decision.append_canonical(&mut canonical);
+        // FNV-1a: unlike DefaultHasher, stable across Rust releases
+        let hash = canonical.bytes().fold(0xcbf29ce484222325u64, |acc, b| {
+            (acc ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+        format!("{:08x}", (hash >> 32) as u32 ^ hash as u32)
+    }
 }
 
 /// Default configuration for backtesting (no API keys required)
@@ -325,9 +1162,13 @@ impl Default for Config {
                 account_id_live: None,
             },
             perplexity: PerplexityConfig {
+                backend: LlmBackend::Perplexity,
                 api_key: String::new(),
                 endpoint: "https://api.perplexity.ai/chat/completions".to_string(),
                 model: "sonar".to_string(),
+                temperature: 0.3,
+                max_tokens: 500,
+                prompts: PromptTemplates::default(),
             },
             trading: TradingConfig {
                 symbol: "FCPO".to_string(),
@@ -337,6 +1178,12 @@ impl Default for Config {
                 max_positions: 1,
                 max_daily_loss_percent: 5.0,
                 initial_balance: 10000.0,
+                trailing_activation_percent: None,
+                atr_exits: None,
+                break_even: None,
+                scale_in: None,
+                entry_cooldown_candles: 0,
+                sizing: SizingPolicy::FixedRisk,
             },
             strategy: StrategyConfig {
                 rsi_period: 14,
@@ -344,6 +1191,13 @@ impl Default for Config {
                 rsi_overbought: 70.0,
                 rsi_timeframe: "5m".to_string(),
                 sentiment_threshold: 30,
+                rsi_exit: RsiExitMode::Off,
+                macd_confirmation: false,
+                higher_timeframe: None,
+                opposite_signal_policy: OppositeSignalPolicy::Ignore,
+                kind: StrategyKind::RsiSentiment,
+                vwap: VwapReversionConfig::default(),
+                trend: TrendFollowingConfig::default(),
             },
             kols: vec![
                 "PalmOilTrader".to_string(),
@@ -354,6 +1208,7 @@ impl Default for Config {
                 dry_run: true,
                 log_level: "info".to_string(),
             },
+            decision: DecisionConfig::default(),
         }
     }
 }
@@ -363,6 +1218,15 @@ fn get_env(key: &str) -> Result<String> {
     env::var(key).map_err(|_| BotError::Config(format!("Missing environment variable: {}", key)))
 }
 
+/// `LLM_<suffix>`, or `PERPLEXITY_<suffix>` when the backend is Perplexity
+fn llm_env(suffix: &str, backend: LlmBackend) -> Option<String> {
+    let value = |key: String| env::var(key).ok().filter(|v| !v.trim().is_empty());
+    value(format!("LLM_{}", suffix)).or_else(|| match backend {
+        LlmBackend::Perplexity => value(format!("PERPLEXITY_{}", suffix)),
+        _ => None,
+    })
+}
+
 /// Get environment variable with default value
 fn get_env_or(key: &str, default: &str) -> String {
     env::var(key).unwrap_or_else(|_| default.to_string())
@@ -392,9 +1256,13 @@ mod tests {
                 account_id_live: None,
             },
             perplexity: PerplexityConfig {
+                backend: LlmBackend::Perplexity,
                 api_key: "test-key".into(),
                 endpoint: "https://api.perplexity.ai".into(),
                 model: "sonar".into(),
+                temperature: 0.3,
+                max_tokens: 500,
+                prompts: PromptTemplates::default(),
             },
             trading: TradingConfig {
                 symbol: "FCPO".into(),
@@ -404,6 +1272,12 @@ mod tests {
                 max_positions: 1,
                 max_daily_loss_percent: 5.0,
                 initial_balance: 10000.0,
+                trailing_activation_percent: None,
+                atr_exits: None,
+                break_even: None,
+                scale_in: None,
+                entry_cooldown_candles: 0,
+                sizing: SizingPolicy::FixedRisk,
             },
             strategy: StrategyConfig {
                 rsi_period: 14,
@@ -411,6 +1285,13 @@ mod tests {
                 rsi_overbought: 70.0,
                 rsi_timeframe: "5m".into(),
                 sentiment_threshold: 30,
+                rsi_exit: RsiExitMode::Off,
+                macd_confirmation: false,
+                higher_timeframe: None,
+                opposite_signal_policy: OppositeSignalPolicy::Ignore,
+                kind: StrategyKind::RsiSentiment,
+                vwap: VwapReversionConfig::default(),
+                trend: TrendFollowingConfig::default(),
             },
             kols: vec!["test".into()],
             bot: BotConfig {
@@ -418,6 +1299,7 @@ mod tests {
                 dry_run: true,
                 log_level: "info".into(),
             },
+            decision: DecisionConfig::default(),
         };
 
         assert!(config.validate().is_ok());
@@ -440,6 +1322,176 @@ mod tests {
         env::remove_var("CTRADER_ACCESS_TOKEN");
     }
 
+    #[test]
+    fn test_strategy_hash_tracks_parameters() {
+        let config = Config::default();
+        assert_eq!(config.strategy_hash(), Config::default().strategy_hash());
+        assert_eq!(config.strategy_hash().len(), 8);
+
+        let mut changed = Config::default();
+        changed.strategy.rsi_oversold = 25.0;
+        assert_ne!(config.strategy_hash(), changed.strategy_hash());
+
+        let mut atr = Config::default();
+        atr.trading.atr_exits = Some(AtrExitConfig {
+            period: 14,
+            stop_loss_multiplier: 1.5,
+            take_profit_multiplier: 2.0,
+        });
+        assert_ne!(config.strategy_hash(), atr.strategy_hash());
+
+        let mut break_even = Config::default();
+        break_even.trading.break_even = Some(BreakEvenConfig {
+            activation_percent: 1.0,
+            offset_percent: 0.1,
+        });
+        assert_ne!(config.strategy_hash(), break_even.strategy_hash());
+
+        let mut cooldown = Config::default();
+        cooldown.trading.entry_cooldown_candles = 3;
+        assert_ne!(config.strategy_hash(), cooldown.strategy_hash());
+
+        let mut kelly = Config::default();
+        kelly.trading.sizing = SizingPolicy::KellyFraction {
+            fraction: 0.5,
+            max_risk_percent: 2.0,
+            lookback: 50,
+            min_trades: 20,
+        };
+        assert_ne!(config.strategy_hash(), kelly.strategy_hash());
+
+        let mut rsi_exit = Config::default();
+        rsi_exit.strategy.rsi_exit = RsiExitMode::MeanReversion {
+            long_exit: 50.0,
+            short_exit: 50.0,
+        };
+        assert_ne!(config.strategy_hash(), rsi_exit.strategy_hash());
+
+        let mut trailing = Config::default();
+        trailing.trading.trailing_activation_percent = Some(1.5);
+        assert_ne!(config.strategy_hash(), trailing.strategy_hash());
+
+        let mut macd = Config::default();
+        macd.strategy.macd_confirmation = true;
+        assert_ne!(config.strategy_hash(), macd.strategy_hash());
+
+        let mut higher = Config::default();
+        higher.strategy.higher_timeframe = Some(HigherTimeframeConfig {
+            timeframe: "1h".to_string(),
+            rsi_buy_below: 50.0,
+            rsi_sell_above: 50.0,
+        });
+        assert_ne!(config.strategy_hash(), higher.strategy_hash());
+
+        let mut opposite = Config::default();
+        opposite.strategy.opposite_signal_policy = OppositeSignalPolicy::Reverse;
+        assert_ne!(config.strategy_hash(), opposite.strategy_hash());
+
+        // Runtime-only settings don't change the fingerprint
+        let mut runtime = Config::default();
+        runtime.bot.cycle_interval_secs = 5;
+        runtime.trading.initial_balance = 50_000.0;
+        assert_eq!(config.strategy_hash(), runtime.strategy_hash());
+    }
+
+    #[test]
+    fn test_strategy_hash_tracks_decision_settings() {
+        use crate::modules::scraper::{FundamentalSeries, SeriesField};
+        use crate::modules::trading::StructureStopMode;
+        use chrono::{Duration, FixedOffset};
+
+        let config = Config::default();
+
+        let mut hours = Config::default();
+        hours.decision.market_calendar = Some(MarketCalendar::bursa_fcpo());
+        assert_ne!(config.strategy_hash(), hours.strategy_hash());
+
+        let mut blackout = Config::default();
+        blackout.decision.blackout = Some(BlackoutSchedule::new(
+            Duration::minutes(15),
+            Duration::minutes(30),
+            FixedOffset::east_opt(0).unwrap(),
+        ));
+        assert_ne!(config.strategy_hash(), blackout.strategy_hash());
+
+        let mut volatility = Config::default();
+        volatility.decision.volatility = Some(VolatilityConfig::default());
+        assert_ne!(config.strategy_hash(), volatility.strategy_hash());
+
+        let mut structure = Config::default();
+        structure.decision.structure_stop =
+            Some(StructureStopConfig::new(StructureStopMode::Swing));
+        assert_ne!(config.strategy_hash(), structure.strategy_hash());
+
+        let mut spread = Config::default();
+        spread.decision.spread_guard.max_spread = Some(2.0);
+        assert_ne!(config.strategy_hash(), spread.strategy_hash());
+
+        let mut cross = Config::default();
+        cross.decision.cross_market = Some(CrossMarketConfig {
+            symbols: vec!["SOYOIL".into()],
+            window: 48,
+            min_correlation: 0.5,
+            min_move_percent: 0.5,
+            boost: 0.2,
+            veto: true,
+        });
+        assert_ne!(config.strategy_hash(), cross.strategy_hash());
+
+        let mut fundamentals = Config::default();
+        fundamentals.decision.fundamentals = Some(FundamentalsConfig {
+            series: vec![FundamentalSeries {
+                name: "stocks".into(),
+                url: "https://example.com/stocks.json".into(),
+                field: SeriesField::Json("/value".into()),
+                neutral: 2.0,
+                scale: -0.5,
+                weight: 1.0,
+            }],
+            refresh: Duration::hours(24),
+            weight: 0.2,
+        });
+        assert_ne!(config.strategy_hash(), fundamentals.strategy_hash());
+
+        let mut weights = Config::default();
+        weights
+            .decision
+            .sentiment_weights
+            .insert("twitter".into(), 0.5);
+        assert_ne!(config.strategy_hash(), weights.strategy_hash());
+
+        let mut half_life = Config::default();
+        half_life.decision.sentiment_half_life_minutes = 0;
+        assert_ne!(config.strategy_hash(), half_life.strategy_hash());
+
+        let mut disagreement = Config::default();
+        disagreement.decision.disagreement_rsi_margin = Some(10.0);
+        assert_ne!(config.strategy_hash(), disagreement.strategy_hash());
+
+        let mut per_class = Config::default();
+        per_class.decision.max_positions_per_asset_class = Some(1);
+        assert_ne!(config.strategy_hash(), per_class.strategy_hash());
+
+        let mut vwap = Config::default();
+        vwap.strategy.kind = StrategyKind::VwapReversion;
+        assert_ne!(config.strategy_hash(), vwap.strategy_hash());
+        let mut vwap_band = vwap.clone();
+        vwap_band.strategy.vwap.band_width = 2.5;
+        assert_ne!(vwap.strategy_hash(), vwap_band.strategy_hash());
+
+        let mut trend = Config::default();
+        trend.strategy.kind = StrategyKind::TrendFollowing;
+        assert_ne!(config.strategy_hash(), trend.strategy_hash());
+        let mut trend_adx = trend.clone();
+        trend_adx.strategy.trend.adx_threshold = 30.0;
+        assert_ne!(trend.strategy_hash(), trend_adx.strategy_hash());
+
+        // Another strategy's parameters don't move this one's hash
+        let mut unused = Config::default();
+        unused.strategy.vwap.band_width = 2.5;
+        assert_eq!(config.strategy_hash(), unused.strategy_hash());
+    }
+
     #[test]
     fn test_ctrader_access_token_default_is_none() {
         let config = Config::default();
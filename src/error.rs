@@ -23,6 +23,14 @@ pub enum BotError {
     #[error("Twitter scraping error: {0}")]
     Twitter(String),
 
+    /// Reddit scraping errors
+    #[error("Reddit scraping error: {0}")]
+    Reddit(String),
+
+    /// RSS/Atom news feed errors
+    #[error("News feed error: {0}")]
+    Feed(String),
+
     /// Strategy errors
     #[error("Strategy error: {0}")]
     Strategy(String),
@@ -75,6 +83,9 @@ pub enum CTraderError {
 
     #[error("API error: {0}")]
     ApiError(String),
+
+    #[error("Insufficient permissions: {0}")]
+    InsufficientPermissions(String),
 }
 
 /// Perplexity API specific errors
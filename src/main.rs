@@ -1,37 +1,108 @@
 //! Palm Oil Trading Bot - Main Entry Point
 //!
 //! Uses TradingBot runtime from bot.rs
+//!
+//! ```bash
+//! # Replay a recorded session offline (dry-run, trades go to data/replay.db)
+//! cargo run -- --replay data/ticks/FCPO/2024-03-04.csv --replay-speed 10x
+//!
+//! # Trade the session-VWAP reversion strategy instead of RSI + sentiment
+//! STRATEGY=vwap_reversion cargo run
+//! ```
 
+use clap::Parser;
 use palm_oil_bot::bot::TradingBot;
 use palm_oil_bot::config::Config;
-use palm_oil_bot::modules::security::SecretValidator;
-use tracing::{error, info};
+use palm_oil_bot::modules::backtest::{load_replay_ticks, ReplaySpeed};
+use palm_oil_bot::modules::monitoring::{start_log_shipper, CrashReporter, LogShipperConfig};
+use palm_oil_bot::modules::security::{
+    redacting_fmt_layer, InstanceLock, LockConflictMode, LockOutcome, SecretValidator,
+};
+use palm_oil_bot::modules::trading::{
+    LeaderElectionConfig, Strategy, StrategyKind, Tick, TrendFollowing, VwapReversion,
+};
+use std::path::PathBuf;
+use tracing::{error, info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Parser, Debug)]
+#[command(name = "palm-oil-bot")]
+#[command(about = "Automated FCPO trading bot (RSI + sentiment)")]
+struct Args {
+    /// Replay recorded ticks (.csv or .parquet) instead of the broker feed, in dry-run
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Replay pace: 1x keeps the recorded gaps, 10x is ten times faster, max does not wait
+    #[arg(long, default_value = "1x", requires = "replay")]
+    replay_speed: ReplaySpeed,
+
+    /// SQLite file for replayed trades, kept apart from the live database
+    #[arg(long, default_value = "data/replay.db", requires = "replay")]
+    replay_db: String,
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    let args = Args::parse();
+
+    // Load .env early so log shipping settings are visible
+    dotenvy::dotenv().ok();
+
+    // Initialize logging (console, plus Loki/Elasticsearch when LOG_SHIP_BACKEND is set);
+    // every sink redacts secrets, so .env must be loaded first
+    let log_shipper = LogShipperConfig::from_env().map(start_log_shipper);
+    let crash_reporter = CrashReporter::from_env();
+    tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive("palm_oil_bot=info".parse()?)
                 .add_directive("reqwest=warn".parse()?)
         )
+        .with(redacting_fmt_layer())
+        .with(log_shipper)
+        .with(crash_reporter.layer())
         .init();
+    crash_reporter.install_panic_hook();
 
     info!("========================================");
     info!("  Palm Oil Trading Bot v0.1.0");
     info!("  Symbol: FCPO (Palm Oil CFD)");
-    info!("  Strategy: RSI + Sentiment Analysis");
     info!("========================================");
 
-    // Load .env before validation
-    dotenvy::dotenv().ok();
-
     // Validate secrets before loading config
     SecretValidator::validate_required_secrets();
 
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
     config.validate()?;
+    let strategy = config.strategy.kind;
+    info!("  Strategy: {} on {} candles", strategy, config.strategy.rsi_timeframe);
+
+    if let Some(path) = &args.replay {
+        // Replays never reach the broker and never touch live persistence
+        config.bot.dry_run = true;
+        std::env::set_var("PERSISTENCE_DB_PATH", &args.replay_db);
+        let ticks = load_replay_ticks(path)?;
+        info!("Loaded {} ticks from {}", ticks.len(), path.display());
+
+        match strategy {
+            StrategyKind::RsiSentiment => {
+                replay(TradingBot::new(config)?, crash_reporter, ticks, args.replay_speed).await?
+            }
+            StrategyKind::VwapReversion => {
+                let vwap = VwapReversion::from_config(&config)?;
+                let bot = TradingBot::with_strategy(config, vwap)?;
+                replay(bot, crash_reporter, ticks, args.replay_speed).await?
+            }
+            StrategyKind::TrendFollowing => {
+                let trend = TrendFollowing::from_config(&config)?;
+                let bot = TradingBot::with_strategy(config, trend)?;
+                replay(bot, crash_reporter, ticks, args.replay_speed).await?
+            }
+        }
+        return Ok(());
+    }
 
     info!("Configuration loaded:");
     info!("  Server: {}:{}", config.ctrader.server, config.ctrader.port);
@@ -39,10 +110,86 @@ async fn main() -> anyhow::Result<()> {
     info!("  Dry Run: {}", config.bot.dry_run);
     info!("  Cycle Interval: {}s", config.bot.cycle_interval_secs);
 
-    let mut bot = TradingBot::new(config.clone())?;
+    // Refuse (or observe) when another instance already trades this account;
+    // the lock is held until main returns
+    let account_id = config.ctrader.active_account_id().to_string();
+    let (_instance_lock, observer_only) = if LeaderElectionConfig::enabled() {
+        // Hot-standby nodes share the account on purpose; the DB lease decides who trades
+        info!("Leader election enabled; skipping the single-instance lock");
+        (None, false)
+    } else {
+        match InstanceLock::acquire(&account_id)? {
+            LockOutcome::Acquired(lock) => {
+                info!("Instance lock acquired at {}", lock.path().display());
+                (Some(lock), false)
+            }
+            LockOutcome::HeldBy(owner) => {
+                let owner = owner
+                    .map(|o| o.to_string())
+                    .unwrap_or_else(|| "another process".to_string());
+                match LockConflictMode::from_env() {
+                    LockConflictMode::Refuse => {
+                        error!("Account {} is already traded by {}", account_id, owner);
+                        anyhow::bail!(
+                            "Another bot instance owns account {} ({}); set INSTANCE_LOCK_MODE=observe to run observer-only",
+                            account_id,
+                            owner
+                        );
+                    }
+                    LockConflictMode::Observe => {
+                        warn!("Account {} is already traded by {}; running observer-only", account_id, owner);
+                        (None, true)
+                    }
+                }
+            }
+        }
+    };
+
+    match strategy {
+        StrategyKind::RsiSentiment => {
+            run_live(TradingBot::new(config)?, observer_only, crash_reporter).await
+        }
+        StrategyKind::VwapReversion => {
+            let vwap = VwapReversion::from_config(&config)?;
+            let bot = TradingBot::with_strategy(config, vwap)?;
+            run_live(bot, observer_only, crash_reporter).await
+        }
+        StrategyKind::TrendFollowing => {
+            let trend = TrendFollowing::from_config(&config)?;
+            let bot = TradingBot::with_strategy(config, trend)?;
+            run_live(bot, observer_only, crash_reporter).await
+        }
+    }
+}
+
+/// Replay recorded ticks through `bot`
+async fn replay<S: Strategy>(
+    mut bot: TradingBot<S>,
+    crash_reporter: CrashReporter,
+    ticks: Vec<Tick>,
+    speed: ReplaySpeed,
+) -> anyhow::Result<()> {
+    bot.set_crash_reporter(crash_reporter);
+    bot.run_replay(ticks, speed).await?;
+    Ok(())
+}
+
+/// Run `bot` against the broker until it stops
+async fn run_live<S: Strategy>(
+    mut bot: TradingBot<S>,
+    observer_only: bool,
+    crash_reporter: CrashReporter,
+) -> anyhow::Result<()> {
+    if observer_only {
+        bot.set_observer_only();
+    }
+    bot.set_crash_reporter(crash_reporter.clone());
 
     if let Err(err) = bot.run().await {
         error!("Bot stopped with error: {}", err);
+        if let Some(path) = crash_reporter.report_fatal(&format!("fatal: {}", err)).await {
+            error!("Crash bundle written to {}", path.display());
+        }
         return Err(err.into());
     }
 
@@ -0,0 +1,622 @@
+//! Candle and tick replay through the production strategy code
+//!
+//! Each candle is processed in the same order as the live bot processes a
+//! closed candle: the strategy sees the close price (`on_price`), open
+//! positions are checked for exits at the quote they would close on, then the
+//! strategy sees the closed candle (`on_candle`, MACD), RSI from
+//! `RsiCalculator` and the sentiment reading feed `generate_signal`, and a
+//! signal only opens a position when `entry_for` (circuit breakers, daily
+//! loss, position limits, scale-in legs) allows it. A signal against open
+//! positions first closes them through `check_signal_exit` when the strategy's
+//! opposite-signal policy says so, and only enters under `Reverse`, as in the
+//! bot. Positions whose RSI exit level the candle crossed close first through
+//! `check_rsi_exit`, and structure stops are tightened to the candle's level.
+//! TP/SL levels and position size come
+//! from the strategy hooks, and closes go through `TradingStrategy::close_position`
+//! so the risk state sees every trade. Daily resets follow candle time.
+//!
+//! `run` evaluates exits on candle closes only, like the bot's polling loop;
+//! intrabar highs and lows are not used. `run_ticks` replays historical ticks
+//! instead: exits are checked on every tick at its own bid/ask, candles are
+//! built with the bot's `CandleBuilder`, and entries fill at the tick that
+//! closed the signal candle.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use super::fill::FillModel;
+use super::report::{BacktestReport, BacktestTrade};
+use crate::config::Config;
+use crate::modules::trading::{
+    Candle, CandleBuilder, CloseReason, EntryKind, OppositeSignalPolicy, OrderSide, Position,
+    RsiCalculator, Signal, SignalContext, Strategy, Tick, TimeFrame, TradingStrategy,
+};
+
+/// Price point fills and marks are taken from: a candle close or a tick
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    time: DateTime<Utc>,
+    mid: f64,
+    spread: Option<f64>,
+}
+
+impl Mark {
+    fn candle(candle: &Candle) -> Self {
+        Self {
+            time: candle.end_time(),
+            mid: candle.close,
+            spread: candle.close_spread,
+        }
+    }
+
+    fn tick(tick: &Tick) -> Self {
+        Self {
+            time: tick.timestamp,
+            mid: tick.price,
+            spread: tick.spread(),
+        }
+    }
+}
+
+/// Replays candles or ticks through a `Strategy` with simulated fills
+pub struct BacktestEngine<S: Strategy = TradingStrategy> {
+    strategy: S,
+    rsi_calculator: RsiCalculator,
+    fill_model: FillModel,
+    symbol: String,
+    initial_balance: f64,
+    balance: f64,
+    /// Entry commission per open position, charged when it closes
+    entry_commissions: HashMap<String, f64>,
+    next_position: u64,
+    peak_equity: f64,
+}
+
+impl BacktestEngine<TradingStrategy> {
+    /// Engine running the built-in RSI + sentiment strategy from `config`
+    pub fn from_config(config: &Config, fill_model: FillModel) -> Self {
+        let strategy = TradingStrategy::new(
+            config.strategy.clone(),
+            config.trading.clone(),
+            config.trading.initial_balance,
+        );
+        Self::with_strategy(config, strategy, fill_model)
+    }
+}
+
+impl<S: Strategy> BacktestEngine<S> {
+    /// Engine running a custom strategy; RSI period, symbol and starting
+    /// balance come from `config`
+    pub fn with_strategy(config: &Config, mut strategy: S, fill_model: FillModel) -> Self {
+        let initial_balance = config.trading.initial_balance;
+        strategy.core_mut().update_balance(initial_balance);
+        Self {
+            strategy,
+            rsi_calculator: RsiCalculator::new(config.strategy.rsi_period),
+            fill_model,
+            symbol: config.trading.symbol.clone(),
+            initial_balance,
+            balance: initial_balance,
+            entry_commissions: HashMap::new(),
+            next_position: 0,
+            peak_equity: initial_balance,
+        }
+    }
+
+    /// Replay `candles` (oldest first); `sentiment` supplies the sentiment
+    /// score in force at each candle
+    pub fn run<F>(mut self, candles: &[Candle], mut sentiment: F) -> BacktestReport
+    where
+        F: FnMut(&Candle) -> i32,
+    {
+        let mut report = self.new_report(candles.len(), 0);
+
+        for candle in candles {
+            let mark = Mark::candle(candle);
+            self.on_mark(mark, &mut report);
+            let score = sentiment(candle);
+            self.on_candle_close(candle, mark, score, &mut report);
+        }
+
+        if let Some(last) = candles.last() {
+            self.finish(Mark::candle(last), &mut report);
+        }
+        report.finish();
+        report
+    }
+
+    /// Replay historical `ticks` (oldest first), building `timeframe` candles
+    /// for signals; `sentiment` is asked at each candle close
+    pub fn run_ticks<F>(
+        mut self,
+        ticks: &[Tick],
+        timeframe: TimeFrame,
+        mut sentiment: F,
+    ) -> BacktestReport
+    where
+        F: FnMut(&Candle) -> i32,
+    {
+        let mut report = self.new_report(0, ticks.len());
+        let mut candle_builder = CandleBuilder::new(timeframe);
+
+        for tick in ticks {
+            let mark = Mark::tick(tick);
+            self.on_mark(mark, &mut report);
+            if let Some(candle) = candle_builder.add_tick(*tick) {
+                report.candles += 1;
+                let score = sentiment(&candle);
+                self.on_candle_close(&candle, mark, score, &mut report);
+            }
+        }
+
+        if let Some(last) = ticks.last() {
+            self.finish(Mark::tick(last), &mut report);
+        }
+        report.finish();
+        report
+    }
+
+    fn new_report(&self, candles: usize, ticks: usize) -> BacktestReport {
+        let mut report = BacktestReport::new(
+            self.strategy.name(),
+            candles,
+            self.initial_balance,
+            self.fill_model,
+        );
+        report.ticks = ticks;
+        report
+    }
+
+    /// New price: advance the clock, feed the strategy, check exits
+    fn on_mark(&mut self, mark: Mark, report: &mut BacktestReport) {
+        self.strategy.core_mut().set_simulated_time(mark.time);
+        self.strategy.on_price(mark.mid);
+        self.check_exits(mark, report);
+        self.track_drawdown(mark, report);
+    }
+
+    /// Signal evaluation for a closed candle; entries fill at `entry`
+    fn on_candle_close(
+        &mut self,
+        candle: &Candle,
+        entry: Mark,
+        sentiment: i32,
+        report: &mut BacktestReport,
+    ) {
+        self.strategy.on_candle(candle);
+        let previous_rsi = self.rsi_calculator.current();
+        let Some(rsi) = self.rsi_calculator.add_price(candle.close) else {
+            return;
+        };
+        self.close_on_rsi(previous_rsi, rsi, entry, report);
+        self.tighten_structure_stops(entry);
+        let signal = self
+            .strategy
+            .generate_signal(&SignalContext {
+                candle,
+                rsi,
+                sentiment,
+                sentiment_confidence: 1.0,
+                sentiment_dispersion: 0.0,
+                higher_rsi: None,
+                fundamental_bias: None,
+            })
+            .signal;
+
+        if self.close_reversed(signal, entry, report)
+            && self.strategy.core().opposite_signal_policy() != OppositeSignalPolicy::Reverse
+        {
+            return;
+        }
+
+        let side = signal.side();
+        // Risk checks run every candle, as in the bot, so their state advances the same way
+        let core = self.strategy.core_mut();
+        let allowed = match side {
+            Some(side) => {
+                let price = self.fill_model.fill_price_at(entry.mid, entry.spread, side);
+                core.entry_for(side, price).unwrap_or(None)
+            }
+            None => core
+                .can_open_position()
+                .unwrap_or(false)
+                .then_some(EntryKind::New),
+        };
+        let Some(side) = side else {
+            return;
+        };
+        let Some(kind) = allowed else {
+            report.signals_blocked += 1;
+            return;
+        };
+        self.open_position(entry, side, kind, report);
+    }
+
+    /// Close the positions `signal` reverses at `mark`; whether any closed
+    fn close_reversed(&mut self, signal: Signal, mark: Mark, report: &mut BacktestReport) -> bool {
+        let positions: Vec<Position> = self.strategy.core().get_open_positions().to_vec();
+        let mut closed = false;
+        for position in positions {
+            if let Some(reason) = self.strategy.check_signal_exit(&position, signal) {
+                self.close_position(&position, mark, reason, report);
+                closed = true;
+            }
+        }
+        closed
+    }
+
+    /// Move open positions' stops to the structure level at `mark` when that
+    /// tightens them, as the bot amends them on a candle close
+    fn tighten_structure_stops(&mut self, mark: Mark) {
+        let positions: Vec<Position> = self.strategy.core().get_open_positions().to_vec();
+        for position in positions {
+            let quote = self
+                .fill_model
+                .quote_at(mark.mid, mark.spread, position.side.opposite());
+            if let Some(stop_loss) = self.strategy.core().structure_stop_update(&position, quote) {
+                self.strategy
+                    .core_mut()
+                    .position_manager_mut()
+                    .set_stop_loss(&position.id, stop_loss);
+            }
+        }
+    }
+
+    /// Close the positions whose RSI exit level was crossed at `mark`
+    fn close_on_rsi(
+        &mut self,
+        previous_rsi: Option<f64>,
+        rsi: f64,
+        mark: Mark,
+        report: &mut BacktestReport,
+    ) {
+        let positions: Vec<Position> = self.strategy.core().get_open_positions().to_vec();
+        for position in positions {
+            if let Some(reason) = self.strategy.check_rsi_exit(&position, previous_rsi, rsi) {
+                self.close_position(&position, mark, reason, report);
+            }
+        }
+    }
+
+    /// Close whatever is still open at the last price
+    fn finish(&mut self, last: Mark, report: &mut BacktestReport) {
+        let remaining: Vec<Position> = self.strategy.core().get_open_positions().to_vec();
+        for position in remaining {
+            self.close_position(&position, last, CloseReason::Manual, report);
+        }
+        self.track_drawdown(last, report);
+    }
+
+    fn open_position(
+        &mut self,
+        mark: Mark,
+        side: OrderSide,
+        kind: EntryKind,
+        report: &mut BacktestReport,
+    ) {
+        let entry_price = self.fill_model.fill_price_at(mark.mid, mark.spread, side);
+        let take_profit = self.strategy.take_profit(entry_price, side);
+        let stop_loss = self.strategy.stop_loss(entry_price, side);
+        let volume = self.strategy.position_size(entry_price, stop_loss) * kind.size_factor();
+        if !volume.is_finite() || volume <= 0.0 {
+            debug!("Backtest: skipping {} signal with volume {}", side, volume);
+            return;
+        }
+
+        self.next_position += 1;
+        let id = format!("backtest_{}", self.next_position);
+        let mut position =
+            Position::new(id.clone(), self.symbol.clone(), side, entry_price, volume)
+                .with_take_profit(take_profit)
+                .with_stop_loss(stop_loss);
+        position.opened_at = mark.time;
+
+        report.execution_costs += (entry_price - mark.mid).abs() * volume;
+        self.entry_commissions
+            .insert(id, self.fill_model.commission(volume));
+        self.strategy.core_mut().add_position(position);
+    }
+
+    fn check_exits(&mut self, mark: Mark, report: &mut BacktestReport) {
+        let positions: Vec<Position> = self.strategy.core().get_open_positions().to_vec();
+        for position in positions {
+            let quote = self
+                .fill_model
+                .quote_at(mark.mid, mark.spread, position.side.opposite());
+            if let Some(reason) = self.strategy.check_exit(&position, quote) {
+                self.close_position(&position, mark, reason, report);
+            }
+        }
+    }
+
+    fn close_position(
+        &mut self,
+        position: &Position,
+        mark: Mark,
+        reason: CloseReason,
+        report: &mut BacktestReport,
+    ) {
+        let exit_price =
+            self.fill_model
+                .fill_price_at(mark.mid, mark.spread, position.side.opposite());
+        let Some(gross_pnl) =
+            self.strategy
+                .core_mut()
+                .close_position(&position.id, exit_price, reason)
+        else {
+            return;
+        };
+
+        let commission = self.entry_commissions.remove(&position.id).unwrap_or(0.0)
+            + self.fill_model.commission(position.volume);
+        let net_pnl = gross_pnl - commission;
+        self.balance += net_pnl;
+        self.strategy.core_mut().update_balance(self.balance);
+
+        report.commissions += commission;
+        report.execution_costs += (mark.mid - exit_price).abs() * position.volume;
+        report.trades.push(BacktestTrade {
+            position_id: position.id.clone(),
+            side: position.side,
+            volume: position.volume,
+            entry_time: position.opened_at,
+            entry_price: position.entry_price,
+            exit_time: mark.time,
+            exit_price,
+            close_reason: reason,
+            gross_pnl,
+            commission,
+            net_pnl,
+        });
+    }
+
+    /// Mark open positions to the quote they would close at
+    fn equity(&self, mark: Mark) -> f64 {
+        let unrealized: f64 = self
+            .strategy
+            .core()
+            .get_open_positions()
+            .iter()
+            .map(|p| {
+                let quote = self
+                    .fill_model
+                    .quote_at(mark.mid, mark.spread, p.side.opposite());
+                match p.side {
+                    OrderSide::Buy => (quote - p.entry_price) * p.volume,
+                    OrderSide::Sell => (p.entry_price - quote) * p.volume,
+                }
+            })
+            .sum();
+        self.balance + unrealized
+    }
+
+    fn track_drawdown(&mut self, mark: Mark, report: &mut BacktestReport) {
+        let equity = self.equity(mark);
+        self.peak_equity = self.peak_equity.max(equity);
+        let drawdown = self.peak_equity - equity;
+        if drawdown > report.max_drawdown {
+            report.max_drawdown = drawdown;
+            report.max_drawdown_percent = drawdown / self.peak_equity * 100.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RsiExitMode;
+    use chrono::Duration;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        let start = DateTime::parse_from_rfc3339("2024-03-04T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Candle {
+                timestamp: start + Duration::hours(i as i64),
+                timeframe: TimeFrame::H1,
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                volume: 1,
+                avg_spread: None,
+                close_spread: None,
+            })
+            .collect()
+    }
+
+    fn engine(fill_model: FillModel) -> BacktestEngine {
+        let config = Config::default();
+        let mut strategy = TradingStrategy::new(
+            config.strategy.clone(),
+            config.trading.clone(),
+            config.trading.initial_balance,
+        );
+        strategy.set_trend_filter(false);
+        BacktestEngine::with_strategy(&config, strategy, fill_model)
+    }
+
+    /// 15 falling closes make RSI 0 on the first reading, then price recovers
+    fn dip_and_rebound() -> Vec<f64> {
+        let mut closes: Vec<f64> = (0..15).map(|i| 1000.0 - i as f64 * 2.0).collect();
+        closes.extend([975.0, 1000.0, 1010.0]);
+        closes
+    }
+
+    #[test]
+    fn test_bullish_dip_hits_take_profit() {
+        let report = engine(FillModel::default()).run(&candles(&dip_and_rebound()), |_| 50);
+
+        let first = &report.trades[0];
+        assert_eq!(first.side, OrderSide::Buy);
+        assert_eq!(first.entry_price, 972.0);
+        assert_eq!(first.close_reason, CloseReason::TakeProfit);
+        assert!(first.net_pnl > 0.0);
+        assert_eq!(report.win_rate, 100.0);
+        // Still oversold on the next candle, but the position limit applies
+        assert!(report.signals_blocked > 0);
+        assert_eq!(
+            report.final_balance,
+            report.initial_balance + report.trades.iter().map(|t| t.net_pnl).sum::<f64>()
+        );
+    }
+
+    #[test]
+    fn test_costs_reduce_pnl() {
+        let closes = dip_and_rebound();
+        let free = engine(FillModel::default()).run(&candles(&closes), |_| 50);
+        let costly = engine(FillModel {
+            spread: 1.0,
+            slippage_percent: 0.05,
+            commission_per_unit: 0.1,
+        })
+        .run(&candles(&closes), |_| 50);
+
+        assert_eq!(free.trades[0].entry_price, 972.0);
+        assert!(costly.trades[0].entry_price > 972.0);
+        assert!(costly.commissions > 0.0);
+        assert!(costly.execution_costs > 0.0);
+        assert!(costly.trades[0].net_pnl < free.trades[0].net_pnl);
+    }
+
+    #[test]
+    fn test_bearish_sentiment_blocks_buy() {
+        let report = engine(FillModel::default()).run(&candles(&dip_and_rebound()), |_| -50);
+        assert_eq!(report.total_trades, 0);
+        assert_eq!(report.profit_factor, None);
+    }
+
+    /// Long from the dip, then 14 small rises to overbought under bearish
+    /// sentiment: a sell signal well before the take profit
+    fn reversal_run(policy: OppositeSignalPolicy) -> BacktestReport {
+        let mut closes: Vec<f64> = (0..15).map(|i| 1000.0 - i as f64 * 2.0).collect();
+        closes.extend((1..=14).map(|i| 972.0 + i as f64 * 0.5));
+        let candles = candles(&closes);
+        let turn = candles[15].timestamp;
+
+        let config = Config::default();
+        let mut strategy = TradingStrategy::new(
+            config.strategy.clone(),
+            config.trading.clone(),
+            config.trading.initial_balance,
+        );
+        strategy.set_trend_filter(false);
+        strategy.set_opposite_signal_policy(policy);
+        BacktestEngine::with_strategy(&config, strategy, FillModel::default()).run(
+            &candles,
+            |candle| if candle.timestamp < turn { 50 } else { -50 },
+        )
+    }
+
+    #[test]
+    fn test_opposite_signal_closes_before_take_profit() {
+        let held = reversal_run(OppositeSignalPolicy::Ignore);
+        assert_eq!(held.trades.len(), 1);
+        assert_eq!(held.trades[0].close_reason, CloseReason::Manual);
+
+        let closed = reversal_run(OppositeSignalPolicy::Close);
+        assert_eq!(closed.trades.len(), 1);
+        let exit = &closed.trades[0];
+        assert_eq!(exit.side, OrderSide::Buy);
+        assert_eq!(exit.close_reason, CloseReason::SignalReversal);
+        assert!(exit.exit_price > exit.entry_price && exit.exit_price < 990.0);
+
+        let reversed = reversal_run(OppositeSignalPolicy::Reverse);
+        assert_eq!(reversed.trades.len(), 2);
+        assert_eq!(reversed.trades[0].close_reason, CloseReason::SignalReversal);
+        assert_eq!(reversed.trades[1].side, OrderSide::Sell);
+        assert_eq!(reversed.trades[1].entry_time, reversed.trades[0].exit_time);
+    }
+
+    #[test]
+    fn test_rsi_mean_reversion_exit_before_take_profit() {
+        // Buy at the bottom of a dip, then a slow recovery that never reaches the TP
+        let mut closes: Vec<f64> = (0..15).map(|i| 1000.0 - i as f64).collect();
+        closes.extend((1..=14).map(|i| 986.0 + i as f64));
+        let run = |rsi_exit| {
+            let mut config = Config::default();
+            config.strategy.rsi_exit = rsi_exit;
+            let mut strategy = TradingStrategy::new(
+                config.strategy.clone(),
+                config.trading.clone(),
+                config.trading.initial_balance,
+            );
+            strategy.set_trend_filter(false);
+            BacktestEngine::with_strategy(&config, strategy, FillModel::default())
+                .run(&candles(&closes), |_| 50)
+        };
+
+        let held = run(RsiExitMode::Off);
+        assert_eq!(held.trades.len(), 1);
+        assert_eq!(held.trades[0].close_reason, CloseReason::Manual);
+
+        let reverted = run(RsiExitMode::MeanReversion {
+            long_exit: 50.0,
+            short_exit: 50.0,
+        });
+        assert_eq!(reverted.trades.len(), 1);
+        let exit = &reverted.trades[0];
+        assert_eq!(exit.close_reason, CloseReason::Signal);
+        assert!(exit.exit_price > exit.entry_price);
+        assert!(exit.exit_time < held.trades[0].exit_time);
+    }
+
+    #[test]
+    fn test_consecutive_losses_stop_new_entries() {
+        // Repeated dips that keep falling through the stop loss
+        let mut closes = Vec::new();
+        let mut price = 1000.0;
+        for _ in 0..8 {
+            for _ in 0..16 {
+                closes.push(price);
+                price -= 2.0;
+            }
+            price *= 0.97;
+        }
+        let report = engine(FillModel::default()).run(&candles(&closes), |_| 50);
+
+        let stops = report
+            .trades
+            .iter()
+            .filter(|t| t.close_reason == CloseReason::StopLoss)
+            .count();
+        assert_eq!(stops, 3);
+        assert!(report.signals_blocked > 0);
+        assert!(report.max_drawdown > 0.0);
+        assert_eq!(report.profit_factor, Some(0.0));
+    }
+
+    #[test]
+    fn test_ticks_trigger_intrabar_stop() {
+        // Hourly candles only ever close at 972 and above, but a tick inside
+        // the candle after entry trades through the stop loss
+        let start = DateTime::parse_from_rfc3339("2024-03-04T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut ticks: Vec<Tick> = dip_and_rebound()
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Tick::from_quote(start + Duration::hours(i as i64), *close, *close))
+            .collect();
+        // Entry happens on the first tick of hour 15, when the hour-14 candle closes
+        let entry_time = ticks[15].timestamp;
+        ticks.insert(
+            16,
+            Tick::from_quote(entry_time + Duration::minutes(30), 940.0, 940.0),
+        );
+
+        let report = engine(FillModel::default()).run_ticks(&ticks, TimeFrame::H1, |_| 50);
+
+        let first = &report.trades[0];
+        assert_eq!(first.entry_time, entry_time);
+        assert_eq!(first.close_reason, CloseReason::StopLoss);
+        assert_eq!(first.exit_price, 940.0);
+        assert_eq!(report.ticks, ticks.len());
+        assert!(report.candles > 0);
+    }
+}
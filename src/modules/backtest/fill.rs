@@ -0,0 +1,106 @@
+//! Simulated order fills
+//!
+//! Entries buy at the ask / sell at the bid and exits trade the opposite side,
+//! like the live bot's `exit_quote`. A recorded spread (a candle's closing
+//! spread, or a historical tick's bid/ask) takes precedence over the model's
+//! fixed spread; slippage is always adverse.
+
+use serde::Serialize;
+
+use crate::modules::trading::{Candle, OrderSide};
+
+/// Execution cost model applied to every simulated fill
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct FillModel {
+    /// Full bid/ask spread in price units, used when the candle has none
+    pub spread: f64,
+    /// Adverse slippage as a percentage of price, per fill
+    pub slippage_percent: f64,
+    /// Commission per unit of volume, charged on entry and on exit
+    pub commission_per_unit: f64,
+}
+
+impl FillModel {
+    /// Quoted price a `side` order trades against at the candle close, before
+    /// slippage (ask for buys, bid for sells)
+    pub fn quote(&self, candle: &Candle, side: OrderSide) -> f64 {
+        self.quote_at(candle.close, candle.close_spread, side)
+    }
+
+    /// Price actually paid (buy) or received (sell) at the candle close
+    pub fn fill_price(&self, candle: &Candle, side: OrderSide) -> f64 {
+        self.fill_price_at(candle.close, candle.close_spread, side)
+    }
+
+    /// Quote for a `side` order around `mid`; a recorded `spread` takes
+    /// precedence over the model's
+    pub fn quote_at(&self, mid: f64, spread: Option<f64>, side: OrderSide) -> f64 {
+        let half_spread = spread.unwrap_or(self.spread) / 2.0;
+        match side {
+            OrderSide::Buy => mid + half_spread,
+            OrderSide::Sell => mid - half_spread,
+        }
+    }
+
+    /// Fill price for a `side` order around `mid`, including slippage
+    pub fn fill_price_at(&self, mid: f64, spread: Option<f64>, side: OrderSide) -> f64 {
+        let slippage = mid * self.slippage_percent / 100.0;
+        match side {
+            OrderSide::Buy => self.quote_at(mid, spread, side) + slippage,
+            OrderSide::Sell => self.quote_at(mid, spread, side) - slippage,
+        }
+    }
+
+    /// Commission for one side of a trade
+    pub fn commission(&self, volume: f64) -> f64 {
+        self.commission_per_unit * volume
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::TimeFrame;
+    use chrono::Utc;
+
+    fn candle(close: f64, close_spread: Option<f64>) -> Candle {
+        Candle {
+            timestamp: Utc::now(),
+            timeframe: TimeFrame::H1,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1,
+            avg_spread: close_spread,
+            close_spread,
+        }
+    }
+
+    #[test]
+    fn test_fills_cross_the_spread_and_slip() {
+        let model = FillModel {
+            spread: 2.0,
+            slippage_percent: 0.1,
+            commission_per_unit: 0.5,
+        };
+        let bar = candle(1000.0, None);
+
+        assert_eq!(model.quote(&bar, OrderSide::Buy), 1001.0);
+        assert_eq!(model.quote(&bar, OrderSide::Sell), 999.0);
+        assert_eq!(model.fill_price(&bar, OrderSide::Buy), 1002.0);
+        assert_eq!(model.fill_price(&bar, OrderSide::Sell), 998.0);
+        assert_eq!(model.commission(4.0), 2.0);
+    }
+
+    #[test]
+    fn test_recorded_spread_overrides_model() {
+        let model = FillModel {
+            spread: 2.0,
+            ..Default::default()
+        };
+        let bar = candle(1000.0, Some(6.0));
+        assert_eq!(model.quote(&bar, OrderSide::Buy), 1003.0);
+        assert_eq!(model.fill_price(&bar, OrderSide::Sell), 997.0);
+    }
+}
@@ -0,0 +1,425 @@
+//! Integrity checks for stored candle files
+//!
+//! Candle CSVs fed to the backtester (`timestamp,open,high,low,close` plus
+//! optional `volume`, `spread` or `bid`/`ask` columns) are scanned for:
+//! - out-of-order timestamps (a row older than the one before it)
+//! - duplicate timestamps
+//! - gaps: missing bars between consecutive timestamps, except spans that
+//!   fall entirely on a weekend
+//! - zero-volume bars (only when the file has a `volume` column)
+//! - inconsistent OHLC (high below open/close, low above them, non-positive
+//!   prices)
+//!
+//! `repair` sorts and de-duplicates the rows and patches gaps, zero-volume and
+//! inconsistent bars from a reference series (trendbars downloaded from the
+//! broker by `check-candles --repair`).
+
+use chrono::{DateTime, Datelike, Duration, Utc, Weekday};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{BotError, Result};
+use crate::modules::trading::{Candle, TimeFrame};
+
+/// Bars missing between two consecutive candles
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandleGap {
+    /// Start of the first missing bar
+    pub from: DateTime<Utc>,
+    /// Start of the next bar present
+    pub to: DateTime<Utc>,
+    pub missing_bars: i64,
+}
+
+/// Problems found in a candle series
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    pub candles: usize,
+    /// Row index (0-based, header excluded) and timestamp of each row older
+    /// than its predecessor
+    pub out_of_order: Vec<(usize, DateTime<Utc>)>,
+    pub duplicates: Vec<DateTime<Utc>>,
+    pub gaps: Vec<CandleGap>,
+    pub zero_volume: Vec<DateTime<Utc>>,
+    pub invalid_ohlc: Vec<DateTime<Utc>>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.out_of_order.is_empty()
+            && self.duplicates.is_empty()
+            && self.gaps.is_empty()
+            && self.zero_volume.is_empty()
+            && self.invalid_ohlc.is_empty()
+    }
+
+    pub fn missing_bars(&self) -> i64 {
+        self.gaps.iter().map(|g| g.missing_bars).sum()
+    }
+
+    /// One-line summary for logs and CLIs
+    pub fn summary(&self) -> String {
+        format!(
+            "{} candles: {} out of order, {} duplicates, {} gaps ({} missing bars), {} zero-volume, {} invalid OHLC",
+            self.candles,
+            self.out_of_order.len(),
+            self.duplicates.len(),
+            self.gaps.len(),
+            self.missing_bars(),
+            self.zero_volume.len(),
+            self.invalid_ohlc.len()
+        )
+    }
+}
+
+/// Candles read from a CSV file, in file order
+#[derive(Debug, Clone)]
+pub struct CandleFile {
+    pub candles: Vec<Candle>,
+    /// Whether the file has a `volume` column; zero-volume checks need one
+    pub has_volume: bool,
+}
+
+impl CandleFile {
+    /// Read a candle CSV without sorting, so ordering problems stay visible
+    pub fn load(path: impl AsRef<Path>, timeframe: TimeFrame) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let header: Vec<String> = content
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .map(|c| c.trim().to_ascii_lowercase())
+            .collect();
+        let column = |name: &str| header.iter().position(|c| c == name);
+        let (volume_idx, spread_idx, bid_idx, ask_idx) = (
+            column("volume"),
+            column("spread"),
+            column("bid"),
+            column("ask"),
+        );
+
+        let mut candles = Vec::new();
+        for (line_no, line) in content.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = || {
+                BotError::Other(format!(
+                    "{}:{}: invalid candle row {:?}",
+                    path.display(),
+                    line_no + 1,
+                    line
+                ))
+            };
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 5 {
+                return Err(invalid());
+            }
+            let number = |idx: usize| fields.get(idx).and_then(|v| v.parse::<f64>().ok());
+            let timestamp = parse_timestamp(fields[0]).ok_or_else(invalid)?;
+            let spread = spread_idx
+                .and_then(number)
+                .or_else(|| Some(number(ask_idx?)? - number(bid_idx?)?))
+                .map(|spread| spread.max(0.0));
+
+            candles.push(Candle {
+                timestamp,
+                timeframe,
+                open: number(1).ok_or_else(invalid)?,
+                high: number(2).ok_or_else(invalid)?,
+                low: number(3).ok_or_else(invalid)?,
+                close: number(4).ok_or_else(invalid)?,
+                volume: volume_idx.and_then(number).unwrap_or(0.0).max(0.0) as u64,
+                avg_spread: spread,
+                close_spread: spread,
+            });
+        }
+
+        Ok(Self {
+            candles,
+            has_volume: volume_idx.is_some(),
+        })
+    }
+
+    /// Write candles as `timestamp,open,high,low,close,volume[,spread]`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let with_spread = self.candles.iter().any(|c| c.close_spread.is_some());
+        let mut file = fs::File::create(path)?;
+        writeln!(
+            file,
+            "timestamp,open,high,low,close,volume{}",
+            if with_spread { ",spread" } else { "" }
+        )?;
+        for c in &self.candles {
+            write!(
+                file,
+                "{},{},{},{},{},{}",
+                c.timestamp.to_rfc3339(),
+                c.open,
+                c.high,
+                c.low,
+                c.close,
+                c.volume
+            )?;
+            if with_spread {
+                write!(
+                    file,
+                    ",{}",
+                    c.close_spread.map(|s| s.to_string()).unwrap_or_default()
+                )?;
+            }
+            writeln!(file)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            value
+                .parse::<i64>()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        })
+}
+
+fn is_weekend(time: DateTime<Utc>) -> bool {
+    matches!(time.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn ohlc_consistent(c: &Candle) -> bool {
+    c.low > 0.0 && c.low <= c.open.min(c.close) && c.high >= c.open.max(c.close)
+}
+
+/// Scan `file` for ordering, duplicate, gap, volume and OHLC problems
+pub fn check_candles(file: &CandleFile, timeframe: TimeFrame) -> IntegrityReport {
+    let candles = &file.candles;
+    let mut report = IntegrityReport {
+        candles: candles.len(),
+        ..Default::default()
+    };
+
+    for (idx, pair) in candles.windows(2).enumerate() {
+        if pair[1].timestamp < pair[0].timestamp {
+            report.out_of_order.push((idx + 1, pair[1].timestamp));
+        }
+    }
+
+    let mut sorted: Vec<&Candle> = candles.iter().collect();
+    sorted.sort_by_key(|c| c.timestamp);
+    let step = timeframe.to_duration();
+    for pair in sorted.windows(2) {
+        let (prev, next) = (pair[0].timestamp, pair[1].timestamp);
+        if next == prev {
+            if report.duplicates.last() != Some(&next) {
+                report.duplicates.push(next);
+            }
+            continue;
+        }
+        let mut missing = 0;
+        let mut bar = prev + step;
+        while bar < next {
+            if !is_weekend(bar) {
+                missing += 1;
+            }
+            bar += step;
+        }
+        if missing > 0 {
+            report.gaps.push(CandleGap {
+                from: prev + step,
+                to: next,
+                missing_bars: missing,
+            });
+        }
+    }
+
+    for c in candles {
+        if file.has_volume && c.volume == 0 {
+            report.zero_volume.push(c.timestamp);
+        }
+        if !ohlc_consistent(c) {
+            report.invalid_ohlc.push(c.timestamp);
+        }
+    }
+    report
+}
+
+/// Counts of what `repair` changed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RepairSummary {
+    pub duplicates_removed: usize,
+    pub bars_filled: usize,
+    pub bars_replaced: usize,
+}
+
+/// Sort and de-duplicate `file` (the last row for a timestamp wins), fill
+/// missing bars from `reference` and replace zero-volume or inconsistent bars
+/// that `reference` has. Bars the reference does not have are left as they are.
+pub fn repair(file: &mut CandleFile, reference: &[Candle]) -> RepairSummary {
+    let mut summary = RepairSummary::default();
+    let mut by_time: BTreeMap<DateTime<Utc>, Candle> = BTreeMap::new();
+    for candle in file.candles.drain(..) {
+        if by_time.insert(candle.timestamp, candle).is_some() {
+            summary.duplicates_removed += 1;
+        }
+    }
+
+    let (first, last) = match (by_time.keys().next(), by_time.keys().next_back()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return summary,
+    };
+    for bar in reference {
+        if bar.timestamp < first || bar.timestamp > last {
+            continue;
+        }
+        match by_time.get(&bar.timestamp) {
+            None => {
+                by_time.insert(bar.timestamp, bar.clone());
+                summary.bars_filled += 1;
+            }
+            Some(existing)
+                if (file.has_volume && existing.volume == 0) || !ohlc_consistent(existing) =>
+            {
+                by_time.insert(bar.timestamp, bar.clone());
+                summary.bars_replaced += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    file.candles = by_time.into_values().collect();
+    summary
+}
+
+/// Time range `[from, to)` the reference data must cover to repair `report`
+pub fn repair_window(
+    report: &IntegrityReport,
+    timeframe: TimeFrame,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let times = report
+        .gaps
+        .iter()
+        .flat_map(|g| [g.from, g.to])
+        .chain(report.zero_volume.iter().copied())
+        .chain(report.invalid_ohlc.iter().copied());
+    let (min, max) = times.fold(None, |acc: Option<(DateTime<Utc>, DateTime<Utc>)>, t| {
+        Some(match acc {
+            Some((lo, hi)) => (lo.min(t), hi.max(t)),
+            None => (t, t),
+        })
+    })?;
+    Some((min, max + timeframe.to_duration() + Duration::seconds(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CSV: &str = "timestamp,open,high,low,close,volume
+2024-03-04T00:00:00Z,100,101,99,100.5,10
+2024-03-04T02:00:00Z,100.5,102,100,101,12
+2024-03-04T01:00:00Z,100,101,99,100,0
+2024-03-04T02:00:00Z,100.5,102,100,101,12
+2024-03-04T05:00:00Z,101,100,99,101.5,8
+";
+
+    fn load(dir: &TempDir) -> CandleFile {
+        let path = dir.path().join("candles.csv");
+        fs::write(&path, CSV).unwrap();
+        CandleFile::load(&path, TimeFrame::H1).unwrap()
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2024-03-04T{:02}:00:00Z", hour))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn bar(hour: u32, volume: u64) -> Candle {
+        Candle {
+            timestamp: at(hour),
+            timeframe: TimeFrame::H1,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.0,
+            volume,
+            avg_spread: None,
+            close_spread: None,
+        }
+    }
+
+    #[test]
+    fn test_check_finds_every_problem() {
+        let dir = TempDir::new().unwrap();
+        let report = check_candles(&load(&dir), TimeFrame::H1);
+
+        assert_eq!(report.candles, 5);
+        assert_eq!(report.out_of_order, vec![(2, at(1))]);
+        assert_eq!(report.duplicates, vec![at(2)]);
+        assert_eq!(
+            report.gaps,
+            vec![CandleGap {
+                from: at(3),
+                to: at(5),
+                missing_bars: 2
+            }]
+        );
+        assert_eq!(report.zero_volume, vec![at(1)]);
+        // high 100 is below the close of 101.5
+        assert_eq!(report.invalid_ohlc, vec![at(5)]);
+        assert!(!report.is_clean());
+        assert_eq!(
+            repair_window(&report, TimeFrame::H1),
+            Some((at(1), at(6) + Duration::seconds(1)))
+        );
+    }
+
+    #[test]
+    fn test_weekend_gap_is_not_reported() {
+        let friday = DateTime::parse_from_rfc3339("2024-03-08T23:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut monday = bar(0, 1);
+        monday.timestamp = friday + Duration::hours(49);
+        let mut last_friday_bar = bar(0, 1);
+        last_friday_bar.timestamp = friday;
+        let file = CandleFile {
+            candles: vec![last_friday_bar, monday],
+            has_volume: true,
+        };
+        assert!(check_candles(&file, TimeFrame::H1).is_clean());
+    }
+
+    #[test]
+    fn test_repair_and_save_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let mut file = load(&dir);
+        let reference = vec![bar(1, 7), bar(3, 5), bar(4, 6), bar(5, 9), bar(6, 4)];
+
+        let summary = repair(&mut file, &reference);
+        assert_eq!(
+            summary,
+            RepairSummary {
+                duplicates_removed: 1,
+                bars_filled: 2,
+                bars_replaced: 2,
+            }
+        );
+
+        let path = dir.path().join("repaired.csv");
+        file.save(&path).unwrap();
+        let reloaded = CandleFile::load(&path, TimeFrame::H1).unwrap();
+        assert!(check_candles(&reloaded, TimeFrame::H1).is_clean());
+        assert_eq!(reloaded.candles.len(), 6);
+    }
+}
@@ -0,0 +1,36 @@
+//! Backtesting on historical candles and ticks
+//!
+//! Replays `Candle`s (or historical `Tick`s) through the same code the live
+//! bot runs: `RsiCalculator`, a `Strategy` (by default `TradingStrategy`) with
+//! its circuit breakers and risk limits, and the strategy's TP/SL and sizing
+//! hooks. Only the broker is simulated.
+//!
+//! - `engine`: `BacktestEngine`, the candle and tick replay loops
+//! - `fill`: Spread / slippage / commission model for simulated fills
+//! - `integrity`: Gap / duplicate / bad-bar checks and repair for candle files
+//! - `replay`: Recorded tick files and pacing for the bot's `--replay` mode
+//! - `report`: Win rate, profit factor, drawdown and per-trade results
+//! - `tick_store`: Per-day CSV store of downloaded cTrader ticks
+//! - `walk_forward`: Grid search over strategy parameters on rolling
+//!   train/test windows, with out-of-sample statistics
+
+pub mod engine;
+pub mod fill;
+pub mod integrity;
+pub mod replay;
+pub mod report;
+pub mod tick_store;
+pub mod walk_forward;
+
+pub use engine::BacktestEngine;
+pub use fill::FillModel;
+pub use integrity::{check_candles, CandleFile, IntegrityReport};
+pub use replay::{load_replay_ticks, ReplaySpeed};
+pub use report::{BacktestReport, BacktestTrade};
+pub use tick_store::{
+    merge_quotes, read_tick_csv, read_tick_parquet, write_tick_parquet, TickStore,
+};
+pub use walk_forward::{
+    Objective, ParameterGrid, ParameterSet, WalkForwardConfig, WalkForwardOptimizer,
+    WalkForwardReport,
+};
@@ -0,0 +1,155 @@
+//! Recorded tick replay
+//!
+//! `palm-oil-bot --replay <file>` feeds a recorded tick file through the live
+//! bot's tick handling instead of the broker feed, so a session can be
+//! reproduced offline with the same candles, exits and signals. Files are
+//! read by extension:
+//! - `.csv`: the tick store format, `timestamp_ms,bid,ask` with a header
+//! - `.parquet`: `timestamp_ms` (or `timestamp`, int64 or timestamp-millis),
+//!   `bid` and `ask` columns; needs the `parquet` cargo feature
+//!
+//! `--replay-speed` paces the ticks by their recorded timestamps: `1x` keeps
+//! the original gaps, `10x` divides them by ten, `max` does not wait at all.
+//! Gaps are capped at `MAX_REPLAY_WAIT` so a weekend in the file does not
+//! stall the replay.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::tick_store::{read_tick_csv, read_tick_parquet};
+use crate::error::{BotError, Result};
+use crate::modules::trading::Tick;
+
+/// Longest wall-clock wait between two replayed ticks
+pub const MAX_REPLAY_WAIT: Duration = Duration::from_secs(60);
+
+/// How fast recorded time passes during a replay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Recorded gaps divided by this factor
+    Multiplier(f64),
+    /// No waiting between ticks
+    Max,
+}
+
+impl Default for ReplaySpeed {
+    fn default() -> Self {
+        ReplaySpeed::Multiplier(1.0)
+    }
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let raw = s.trim().to_ascii_lowercase();
+        if raw == "max" {
+            return Ok(ReplaySpeed::Max);
+        }
+        match raw.trim_end_matches('x').parse::<f64>() {
+            Ok(factor) if factor.is_finite() && factor > 0.0 => Ok(ReplaySpeed::Multiplier(factor)),
+            _ => Err(BotError::Config(format!(
+                "invalid replay speed {:?} (expected e.g. 1x, 10x or max)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ReplaySpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplaySpeed::Multiplier(factor) => write!(f, "{}x", factor),
+            ReplaySpeed::Max => write!(f, "max speed"),
+        }
+    }
+}
+
+impl ReplaySpeed {
+    /// Wall-clock wait between ticks recorded at `from` and `to`, if any
+    pub fn wait_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Option<Duration> {
+        let ReplaySpeed::Multiplier(factor) = self else {
+            return None;
+        };
+        let gap = (to - from).to_std().ok()?;
+        Some(gap.div_f64(*factor).min(MAX_REPLAY_WAIT)).filter(|wait| !wait.is_zero())
+    }
+}
+
+/// Read a recorded tick file, oldest tick first
+pub fn load_replay_ticks(path: &Path) -> Result<Vec<Tick>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let mut ticks = match extension.as_deref() {
+        Some("csv") => read_tick_csv(path)?,
+        Some("parquet") => read_tick_parquet(path)?,
+        _ => {
+            return Err(BotError::Config(format!(
+                "{}: replay files must be .csv or .parquet",
+                path.display()
+            )))
+        }
+    };
+    ticks.sort_by_key(|tick| tick.timestamp);
+    Ok(ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_replay_speed_paces_recorded_gaps() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 2, 0, 0).unwrap();
+        let later = |secs| start + chrono::Duration::seconds(secs);
+
+        let realtime: ReplaySpeed = "1x".parse().unwrap();
+        assert_eq!(realtime, ReplaySpeed::default());
+        assert_eq!(
+            realtime.wait_between(start, later(5)),
+            Some(Duration::from_secs(5))
+        );
+        let fast: ReplaySpeed = "10".parse().unwrap();
+        assert_eq!(
+            fast.wait_between(start, later(5)),
+            Some(Duration::from_millis(500))
+        );
+        // Long gaps are capped, ticks at the same time do not wait
+        assert_eq!(
+            realtime.wait_between(start, later(3_600)),
+            Some(MAX_REPLAY_WAIT)
+        );
+        assert_eq!(realtime.wait_between(start, start), None);
+
+        let max: ReplaySpeed = "MAX".parse().unwrap();
+        assert_eq!(max.wait_between(start, later(5)), None);
+        assert!("0x".parse::<ReplaySpeed>().is_err());
+        assert!("fast".parse::<ReplaySpeed>().is_err());
+    }
+
+    #[test]
+    fn test_load_replay_ticks_from_csv() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.csv");
+        std::fs::write(
+            &path,
+            "timestamp_ms,bid,ask\n1709517602000,4801,4803\n1709517601000,4800,4802\n",
+        )
+        .unwrap();
+
+        let ticks = load_replay_ticks(&path).unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0].bid, Some(4800.0));
+        assert_eq!(ticks[1].price, 4802.0);
+
+        assert!(load_replay_ticks(&dir.path().join("session.json")).is_err());
+    }
+}
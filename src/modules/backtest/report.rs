@@ -0,0 +1,137 @@
+//! Backtest results
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::fill::FillModel;
+use crate::modules::trading::{CloseReason, OrderSide};
+
+/// One simulated round trip
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestTrade {
+    pub position_id: String,
+    pub side: OrderSide,
+    pub volume: f64,
+    pub entry_time: DateTime<Utc>,
+    pub entry_price: f64,
+    pub exit_time: DateTime<Utc>,
+    pub exit_price: f64,
+    pub close_reason: CloseReason,
+    /// P&L at the fill prices, as booked by the strategy
+    pub gross_pnl: f64,
+    /// Entry + exit commission
+    pub commission: f64,
+    pub net_pnl: f64,
+}
+
+/// Summary of a backtest run
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub strategy: String,
+    pub candles: usize,
+    /// Ticks replayed; 0 for candle-only runs
+    pub ticks: usize,
+    pub initial_balance: f64,
+    pub final_balance: f64,
+    pub net_pnl: f64,
+    pub total_trades: usize,
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+    /// Winning trades / total trades, in percent
+    pub win_rate: f64,
+    /// Sum of winning net P&L
+    pub gross_profit: f64,
+    /// Sum of losing net P&L, as a positive number
+    pub gross_loss: f64,
+    /// `gross_profit / gross_loss`; `None` without losing trades
+    pub profit_factor: Option<f64>,
+    /// Largest peak-to-trough fall of mark-to-market equity
+    pub max_drawdown: f64,
+    /// `max_drawdown` relative to the peak it fell from, in percent
+    pub max_drawdown_percent: f64,
+    pub commissions: f64,
+    /// Spread + slippage paid versus the mid price
+    pub execution_costs: f64,
+    /// Signals refused by the risk checks (circuit breakers, position limits)
+    pub signals_blocked: u32,
+    pub fill_model: FillModel,
+    pub trades: Vec<BacktestTrade>,
+}
+
+impl BacktestReport {
+    pub(crate) fn new(
+        strategy: &str,
+        candles: usize,
+        initial_balance: f64,
+        fill_model: FillModel,
+    ) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            candles,
+            ticks: 0,
+            initial_balance,
+            final_balance: initial_balance,
+            net_pnl: 0.0,
+            total_trades: 0,
+            winning_trades: 0,
+            losing_trades: 0,
+            win_rate: 0.0,
+            gross_profit: 0.0,
+            gross_loss: 0.0,
+            profit_factor: None,
+            max_drawdown: 0.0,
+            max_drawdown_percent: 0.0,
+            commissions: 0.0,
+            execution_costs: 0.0,
+            signals_blocked: 0,
+            fill_model,
+            trades: Vec::new(),
+        }
+    }
+
+    /// Fill in the trade statistics from `trades`
+    pub(crate) fn finish(&mut self) {
+        self.total_trades = self.trades.len();
+        self.winning_trades = self.trades.iter().filter(|t| t.net_pnl > 0.0).count();
+        self.losing_trades = self.total_trades - self.winning_trades;
+        self.net_pnl = self.trades.iter().map(|t| t.net_pnl).sum();
+        self.final_balance = self.initial_balance + self.net_pnl;
+        self.win_rate = if self.total_trades > 0 {
+            self.winning_trades as f64 / self.total_trades as f64 * 100.0
+        } else {
+            0.0
+        };
+        self.gross_profit = self
+            .trades
+            .iter()
+            .filter(|t| t.net_pnl > 0.0)
+            .map(|t| t.net_pnl)
+            .sum();
+        self.gross_loss = self
+            .trades
+            .iter()
+            .filter(|t| t.net_pnl <= 0.0)
+            .map(|t| -t.net_pnl)
+            .sum();
+        self.profit_factor = (self.gross_loss > 0.0).then(|| self.gross_profit / self.gross_loss);
+    }
+
+    /// Plain-text summary for logs and CLIs
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} trades over {} candles, win rate {:.1}%, profit factor {}, net P&L {:.2} ({:.2}%), max drawdown {:.2} ({:.2}%), costs {:.2}",
+            self.strategy,
+            self.total_trades,
+            self.candles,
+            self.win_rate,
+            self.profit_factor
+                .map(|pf| format!("{:.2}", pf))
+                .unwrap_or_else(|| "n/a".to_string()),
+            self.net_pnl,
+            self.net_pnl / self.initial_balance * 100.0,
+            self.max_drawdown,
+            self.max_drawdown_percent,
+            self.commissions + self.execution_costs
+        )
+    }
+}
@@ -0,0 +1,357 @@
+//! Local store of historical ticks
+//!
+//! Ticks downloaded from cTrader (`download-ticks`) are kept as one CSV file
+//! per symbol and UTC day:
+//!
+//! ```text
+//! <dir>/<SYMBOL>/<YYYY-MM-DD>.csv
+//! timestamp_ms,bid,ask
+//! ```
+//!
+//! cTrader serves bid and ask ticks as separate series; `merge_quotes`
+//! combines them into quotes carrying the latest known bid and ask, which is
+//! what the live bot sees from spot events.
+//!
+//! Days can also be kept as Parquet with the same columns
+//! (`read_tick_parquet` / `write_tick_parquet`, `parquet` cargo feature).
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{BotError, Result};
+use crate::modules::trading::Tick;
+
+const HEADER: &str = "timestamp_ms,bid,ask";
+
+/// Directory of per-day tick files
+#[derive(Debug, Clone)]
+pub struct TickStore {
+    dir: PathBuf,
+}
+
+impl TickStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// File holding `symbol`'s ticks for `day`
+    pub fn day_path(&self, symbol: &str, day: NaiveDate) -> PathBuf {
+        self.dir
+            .join(symbol)
+            .join(format!("{}.csv", day.format("%Y-%m-%d")))
+    }
+
+    /// Whether `day` has already been downloaded
+    pub fn has_day(&self, symbol: &str, day: NaiveDate) -> bool {
+        self.day_path(symbol, day).exists()
+    }
+
+    /// Write one day of quotes, replacing any previous file. Written to a
+    /// temporary file first so an interrupted download leaves no partial day.
+    pub fn write_day(&self, symbol: &str, day: NaiveDate, ticks: &[Tick]) -> Result<PathBuf> {
+        let path = self.day_path(symbol, day);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("csv.tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        writeln!(file, "{}", HEADER)?;
+        for tick in ticks {
+            let (Some(bid), Some(ask)) = (tick.bid, tick.ask) else {
+                continue;
+            };
+            writeln!(
+                file,
+                "{},{},{}",
+                tick.timestamp.timestamp_millis(),
+                bid,
+                ask
+            )?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(path)
+    }
+
+    /// Ticks for `symbol` with `from <= timestamp < to`, oldest first. Days
+    /// that were never downloaded are skipped.
+    pub fn load_range(
+        &self,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Tick>> {
+        let mut ticks = Vec::new();
+        let mut day = from.date_naive();
+        while day <= to.date_naive() {
+            let path = self.day_path(symbol, day);
+            if path.exists() {
+                ticks.extend(
+                    read_tick_csv(&path)?
+                        .into_iter()
+                        .filter(|t| t.timestamp >= from && t.timestamp < to),
+                );
+            }
+            day += Duration::days(1);
+        }
+        Ok(ticks)
+    }
+}
+
+/// Read a `timestamp_ms,bid,ask` tick file, in file order
+pub fn read_tick_csv(path: &Path) -> Result<Vec<Tick>> {
+    let content = fs::read_to_string(path)?;
+    let mut ticks = Vec::new();
+    for (line_no, line) in content.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || {
+            BotError::Other(format!(
+                "{}:{}: invalid tick line {:?}",
+                path.display(),
+                line_no + 1,
+                line
+            ))
+        };
+        let mut fields = line.split(',');
+        let timestamp = fields
+            .next()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .and_then(DateTime::from_timestamp_millis)
+            .ok_or_else(invalid)?;
+        let bid = fields
+            .next()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .ok_or_else(invalid)?;
+        let ask = fields
+            .next()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .ok_or_else(invalid)?;
+        ticks.push(Tick::from_quote(timestamp, bid, ask));
+    }
+    Ok(ticks)
+}
+
+/// Read a Parquet tick file with `timestamp_ms` (or `timestamp`), `bid` and
+/// `ask` columns, in file order
+#[cfg(feature = "parquet")]
+pub fn read_tick_parquet(path: &Path) -> Result<Vec<Tick>> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    fn number(field: &Field) -> Option<f64> {
+        match field {
+            Field::Double(v) => Some(*v),
+            Field::Float(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    let parquet_error = |err: parquet::errors::ParquetError| {
+        BotError::Other(format!("{}: {}", path.display(), err))
+    };
+    let reader = SerializedFileReader::new(std::fs::File::open(path)?).map_err(parquet_error)?;
+    let mut ticks = Vec::new();
+    for (row_no, row) in reader
+        .get_row_iter(None)
+        .map_err(parquet_error)?
+        .enumerate()
+    {
+        let row = row.map_err(parquet_error)?;
+        let (mut timestamp, mut bid, mut ask) = (None, None, None);
+        for (name, field) in row.get_column_iter() {
+            match (name.as_str(), field) {
+                ("timestamp_ms" | "timestamp", Field::Long(ms))
+                | ("timestamp_ms" | "timestamp", Field::TimestampMillis(ms)) => {
+                    timestamp = DateTime::from_timestamp_millis(*ms)
+                }
+                ("bid", field) => bid = number(field),
+                ("ask", field) => ask = number(field),
+                _ => {}
+            }
+        }
+        match (timestamp, bid, ask) {
+            (Some(timestamp), Some(bid), Some(ask)) => {
+                ticks.push(Tick::from_quote(timestamp, bid, ask))
+            }
+            _ => {
+                return Err(BotError::Other(format!(
+                    "{}: row {} lacks timestamp_ms, bid or ask",
+                    path.display(),
+                    row_no
+                )))
+            }
+        }
+    }
+    Ok(ticks)
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn read_tick_parquet(path: &Path) -> Result<Vec<Tick>> {
+    Err(BotError::Config(format!(
+        "{}: Parquet replay needs a build with `--features parquet`",
+        path.display()
+    )))
+}
+
+/// Write ticks with both sides quoted as a Parquet file (`timestamp_ms`
+/// int64, `bid` and `ask` doubles), the columns `read_tick_parquet` reads
+#[cfg(feature = "parquet")]
+pub fn write_tick_parquet(path: &Path, ticks: &[Tick]) -> Result<()> {
+    use parquet::data_type::{DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    let parquet_error = |err: parquet::errors::ParquetError| {
+        BotError::Other(format!("{}: {}", path.display(), err))
+    };
+    let quotes: Vec<(i64, f64, f64)> = ticks
+        .iter()
+        .filter_map(|t| Some((t.timestamp.timestamp_millis(), t.bid?, t.ask?)))
+        .collect();
+    let timestamps: Vec<i64> = quotes.iter().map(|q| q.0).collect();
+    let bids: Vec<f64> = quotes.iter().map(|q| q.1).collect();
+    let asks: Vec<f64> = quotes.iter().map(|q| q.2).collect();
+
+    let schema = parse_message_type(
+        "message tick { REQUIRED INT64 timestamp_ms; REQUIRED DOUBLE bid; REQUIRED DOUBLE ask; }",
+    )
+    .map_err(parquet_error)?;
+    let properties = WriterProperties::builder().build();
+    let mut writer = SerializedFileWriter::new(
+        fs::File::create(path)?,
+        Arc::new(schema),
+        Arc::new(properties),
+    )
+    .map_err(parquet_error)?;
+    let mut row_group = writer.next_row_group().map_err(parquet_error)?;
+    if let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+        column
+            .typed::<Int64Type>()
+            .write_batch(&timestamps, None, None)
+            .map_err(parquet_error)?;
+        column.close().map_err(parquet_error)?;
+    }
+    for values in [&bids, &asks] {
+        if let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+            column
+                .typed::<DoubleType>()
+                .write_batch(values, None, None)
+                .map_err(parquet_error)?;
+            column.close().map_err(parquet_error)?;
+        }
+    }
+    row_group.close().map_err(parquet_error)?;
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn write_tick_parquet(path: &Path, _ticks: &[Tick]) -> Result<()> {
+    Err(BotError::Config(format!(
+        "{}: writing Parquet needs a build with `--features parquet`",
+        path.display()
+    )))
+}
+
+/// Combine separate bid and ask series (`(timestamp_ms, price)`, oldest first)
+/// into quotes. A quote is emitted at every timestamp once both sides are
+/// known, carrying the latest bid and ask.
+pub fn merge_quotes(bids: &[(i64, f64)], asks: &[(i64, f64)]) -> Vec<Tick> {
+    let mut ticks = Vec::with_capacity(bids.len().max(asks.len()));
+    let (mut b, mut a) = (0, 0);
+    let (mut bid, mut ask): (Option<f64>, Option<f64>) = (None, None);
+
+    while b < bids.len() || a < asks.len() {
+        let next_bid = bids.get(b).map(|(t, _)| *t).unwrap_or(i64::MAX);
+        let next_ask = asks.get(a).map(|(t, _)| *t).unwrap_or(i64::MAX);
+        let timestamp = next_bid.min(next_ask);
+        while b < bids.len() && bids[b].0 == timestamp {
+            bid = Some(bids[b].1);
+            b += 1;
+        }
+        while a < asks.len() && asks[a].0 == timestamp {
+            ask = Some(asks[a].1);
+            a += 1;
+        }
+        if let (Some(bid), Some(ask), Some(time)) =
+            (bid, ask, DateTime::from_timestamp_millis(timestamp))
+        {
+            ticks.push(Tick::from_quote(time, bid, ask));
+        }
+    }
+    ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge_quotes_carries_latest_side() {
+        let bids = [(1_000, 4800.0), (3_000, 4801.0)];
+        let asks = [(2_000, 4802.0), (3_000, 4803.0), (4_000, 4804.0)];
+        let ticks = merge_quotes(&bids, &asks);
+
+        let quotes: Vec<(i64, f64, f64)> = ticks
+            .iter()
+            .map(|t| {
+                (
+                    t.timestamp.timestamp_millis(),
+                    t.bid.unwrap(),
+                    t.ask.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            quotes,
+            vec![
+                (2_000, 4800.0, 4802.0),
+                (3_000, 4801.0, 4803.0),
+                (4_000, 4801.0, 4804.0),
+            ]
+        );
+        assert_eq!(ticks[0].price, 4801.0);
+    }
+
+    #[test]
+    fn test_store_round_trip_and_range() {
+        let dir = TempDir::new().unwrap();
+        let store = TickStore::new(dir.path());
+        let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let start = day.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let ticks: Vec<Tick> = (0..4)
+            .map(|i| {
+                let price = 4800.0 + i as f64;
+                Tick::from_quote(start + Duration::hours(i), price, price + 1.0)
+            })
+            .collect();
+
+        assert!(!store.has_day("FCPO", day));
+        store.write_day("FCPO", day, &ticks).unwrap();
+        assert!(store.has_day("FCPO", day));
+
+        let loaded = store
+            .load_range(
+                "FCPO",
+                start + Duration::hours(1),
+                start + Duration::hours(3),
+            )
+            .unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].bid, Some(4801.0));
+        assert_eq!(loaded[1].ask, Some(4803.0));
+
+        // Days that were never downloaded are skipped
+        let wide = store
+            .load_range("FCPO", start - Duration::days(2), start + Duration::days(2))
+            .unwrap();
+        assert_eq!(wide.len(), 4);
+    }
+}
@@ -0,0 +1,693 @@
+//! Walk-forward parameter optimization
+//!
+//! Splits the candle history into rolling windows of `train_candles`
+//! followed by `test_candles`. In every window each `ParameterSet` of the
+//! grid (RSI period and thresholds, TP/SL percentages, sentiment threshold)
+//! is backtested on the training slice with `BacktestEngine`; the best one by
+//! the chosen `Objective` is then run on the following test slice, which the
+//! search never saw. The out-of-sample results of all windows, stitched
+//! together, are what the parameters would have earned if re-optimized on
+//! that schedule.
+//!
+//! Test runs start `warmup` candles early so RSI and the trend filter are
+//! primed; only trades entered inside the test slice count.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use super::engine::BacktestEngine;
+use super::fill::FillModel;
+use super::report::{BacktestReport, BacktestTrade};
+use crate::config::Config;
+use crate::error::{BotError, Result};
+use crate::modules::trading::{Candle, TradingStrategy};
+
+/// One point of the parameter grid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ParameterSet {
+    pub rsi_period: usize,
+    pub rsi_oversold: f64,
+    pub rsi_overbought: f64,
+    pub take_profit_percent: f64,
+    pub stop_loss_percent: f64,
+    pub sentiment_threshold: i32,
+}
+
+impl ParameterSet {
+    /// The parameters currently in `config`
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            rsi_period: config.strategy.rsi_period,
+            rsi_oversold: config.strategy.rsi_oversold,
+            rsi_overbought: config.strategy.rsi_overbought,
+            take_profit_percent: config.trading.take_profit_percent,
+            stop_loss_percent: config.trading.stop_loss_percent,
+            sentiment_threshold: config.strategy.sentiment_threshold,
+        }
+    }
+
+    /// Copy of `base` running these parameters
+    pub fn apply(&self, base: &Config) -> Config {
+        let mut config = base.clone();
+        config.strategy.rsi_period = self.rsi_period;
+        config.strategy.rsi_oversold = self.rsi_oversold;
+        config.strategy.rsi_overbought = self.rsi_overbought;
+        config.strategy.sentiment_threshold = self.sentiment_threshold;
+        config.trading.take_profit_percent = self.take_profit_percent;
+        config.trading.stop_loss_percent = self.stop_loss_percent;
+        config
+    }
+
+    fn is_valid(&self) -> bool {
+        self.rsi_period > 1
+            && self.rsi_oversold < self.rsi_overbought
+            && self.take_profit_percent > 0.0
+            && self.stop_loss_percent > 0.0
+    }
+}
+
+impl fmt::Display for ParameterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RSI({}) {}/{}, TP {}%, SL {}%, sentiment ±{}",
+            self.rsi_period,
+            self.rsi_oversold,
+            self.rsi_overbought,
+            self.take_profit_percent,
+            self.stop_loss_percent,
+            self.sentiment_threshold
+        )
+    }
+}
+
+/// Values swept for each parameter; the grid is their cartesian product
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParameterGrid {
+    pub rsi_periods: Vec<usize>,
+    pub rsi_oversold: Vec<f64>,
+    pub rsi_overbought: Vec<f64>,
+    pub take_profit_percent: Vec<f64>,
+    pub stop_loss_percent: Vec<f64>,
+    pub sentiment_thresholds: Vec<i32>,
+}
+
+impl Default for ParameterGrid {
+    fn default() -> Self {
+        Self {
+            rsi_periods: vec![9, 14, 21],
+            rsi_oversold: vec![25.0, 30.0, 35.0],
+            rsi_overbought: vec![65.0, 70.0, 75.0],
+            take_profit_percent: vec![1.5, 2.0, 3.0],
+            stop_loss_percent: vec![1.0, 1.5, 2.0],
+            sentiment_thresholds: vec![20, 30, 40],
+        }
+    }
+}
+
+impl ParameterGrid {
+    /// Every valid combination (oversold below overbought, positive TP/SL)
+    pub fn combinations(&self) -> Vec<ParameterSet> {
+        let mut sets = Vec::new();
+        for &rsi_period in &self.rsi_periods {
+            for &rsi_oversold in &self.rsi_oversold {
+                for &rsi_overbought in &self.rsi_overbought {
+                    for &take_profit_percent in &self.take_profit_percent {
+                        for &stop_loss_percent in &self.stop_loss_percent {
+                            for &sentiment_threshold in &self.sentiment_thresholds {
+                                let set = ParameterSet {
+                                    rsi_period,
+                                    rsi_oversold,
+                                    rsi_overbought,
+                                    take_profit_percent,
+                                    stop_loss_percent,
+                                    sentiment_threshold,
+                                };
+                                if set.is_valid() {
+                                    sets.push(set);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        sets
+    }
+
+    /// Longest RSI period in the grid
+    pub fn max_rsi_period(&self) -> usize {
+        self.rsi_periods.iter().copied().max().unwrap_or_default()
+    }
+}
+
+/// What "best" means when ranking parameter sets on a training window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Objective {
+    /// Gross profit / gross loss
+    #[default]
+    ProfitFactor,
+    /// Net P&L after costs
+    NetPnl,
+    /// Net P&L divided by max drawdown
+    ReturnOverDrawdown,
+}
+
+impl Objective {
+    fn score(self, stats: &WindowStats) -> f64 {
+        match self {
+            Objective::ProfitFactor => match stats.profit_factor {
+                Some(pf) => pf,
+                // No losing trade: better than any finite factor if it made money
+                None if stats.net_pnl > 0.0 => f64::INFINITY,
+                None => 0.0,
+            },
+            Objective::NetPnl => stats.net_pnl,
+            Objective::ReturnOverDrawdown if stats.max_drawdown > 0.0 => {
+                stats.net_pnl / stats.max_drawdown
+            }
+            Objective::ReturnOverDrawdown if stats.net_pnl > 0.0 => f64::INFINITY,
+            Objective::ReturnOverDrawdown => stats.net_pnl,
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['-', '_'], "").as_str() {
+            "profitfactor" | "pf" => Ok(Objective::ProfitFactor),
+            "netpnl" | "pnl" => Ok(Objective::NetPnl),
+            "returnoverdrawdown" | "calmar" => Ok(Objective::ReturnOverDrawdown),
+            other => Err(format!(
+                "unknown objective '{}' (profit-factor, net-pnl, return-over-drawdown)",
+                other
+            )),
+        }
+    }
+}
+
+/// Window sizes (in candles) and ranking rules
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardConfig {
+    pub train_candles: usize,
+    pub test_candles: usize,
+    /// Candles the windows advance by; defaults to `test_candles` so test
+    /// slices tile the history without overlap
+    pub step_candles: usize,
+    /// Candidates with fewer training trades rank below all others
+    pub min_trades: usize,
+    pub objective: Objective,
+}
+
+impl WalkForwardConfig {
+    pub fn new(train_candles: usize, test_candles: usize) -> Self {
+        Self {
+            train_candles,
+            test_candles,
+            step_candles: test_candles,
+            min_trades: 5,
+            objective: Objective::default(),
+        }
+    }
+}
+
+/// Trade statistics of one backtest slice
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WindowStats {
+    pub trades: usize,
+    pub net_pnl: f64,
+    pub win_rate: f64,
+    pub profit_factor: Option<f64>,
+    /// Peak-to-trough drop of closed-trade equity
+    pub max_drawdown: f64,
+}
+
+impl WindowStats {
+    /// Statistics of `trades` (in exit order)
+    pub fn from_trades<'a>(trades: impl IntoIterator<Item = &'a BacktestTrade>) -> Self {
+        let mut stats = Self::default();
+        let (mut wins, mut gross_profit, mut gross_loss) = (0usize, 0.0, 0.0);
+        let (mut equity, mut peak) = (0.0f64, 0.0f64);
+        for trade in trades {
+            stats.trades += 1;
+            stats.net_pnl += trade.net_pnl;
+            if trade.net_pnl > 0.0 {
+                wins += 1;
+                gross_profit += trade.net_pnl;
+            } else {
+                gross_loss -= trade.net_pnl;
+            }
+            equity += trade.net_pnl;
+            peak = peak.max(equity);
+            stats.max_drawdown = stats.max_drawdown.max(peak - equity);
+        }
+        if stats.trades > 0 {
+            stats.win_rate = wins as f64 / stats.trades as f64 * 100.0;
+        }
+        stats.profit_factor = (gross_loss > 0.0).then(|| gross_profit / gross_loss);
+        stats
+    }
+
+    fn from_report(report: &BacktestReport) -> Self {
+        Self::from_trades(&report.trades)
+    }
+}
+
+/// Best parameters of one window and how they did on either side of the split
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowResult {
+    pub index: usize,
+    pub train_start: DateTime<Utc>,
+    pub train_end: DateTime<Utc>,
+    pub test_start: DateTime<Utc>,
+    pub test_end: DateTime<Utc>,
+    pub best: ParameterSet,
+    pub in_sample: WindowStats,
+    pub out_of_sample: WindowStats,
+    pub candidates: usize,
+}
+
+/// Per-window winners plus the stitched out-of-sample record
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardReport {
+    pub config: WalkForwardConfig,
+    pub windows: Vec<WindowResult>,
+    /// All out-of-sample trades of all windows together
+    pub out_of_sample: WindowStats,
+    /// Windows whose out-of-sample P&L was positive
+    pub profitable_windows: usize,
+    /// Out-of-sample P&L per candle as a share of in-sample P&L per candle;
+    /// near 1 means the optimized edge held up, near 0 or below that it did not
+    pub efficiency: Option<f64>,
+    /// Parameter set chosen most often across windows
+    pub most_selected: Option<ParameterSet>,
+}
+
+impl WalkForwardReport {
+    /// Plain-text table for the CLI
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "{:>3}  {:<10} {:<10}  {:<48} {:>6} {:>10} {:>6} {:>10} {:>6}\n",
+            "#",
+            "train",
+            "test",
+            "best parameters",
+            "IS tr",
+            "IS P&L",
+            "OOS tr",
+            "OOS P&L",
+            "OOS PF"
+        );
+        for w in &self.windows {
+            out.push_str(&format!(
+                "{:>3}  {:<10} {:<10}  {:<48} {:>6} {:>10.2} {:>6} {:>10.2} {:>6}\n",
+                w.index,
+                w.train_start.format("%Y-%m-%d"),
+                w.test_start.format("%Y-%m-%d"),
+                w.best.to_string(),
+                w.in_sample.trades,
+                w.in_sample.net_pnl,
+                w.out_of_sample.trades,
+                w.out_of_sample.net_pnl,
+                format_pf(w.out_of_sample.profit_factor)
+            ));
+        }
+        out.push_str(&format!(
+            "\nOut-of-sample: {} trades, net P&L {:.2}, win rate {:.1}%, profit factor {}, max drawdown {:.2}\n",
+            self.out_of_sample.trades,
+            self.out_of_sample.net_pnl,
+            self.out_of_sample.win_rate,
+            format_pf(self.out_of_sample.profit_factor),
+            self.out_of_sample.max_drawdown
+        ));
+        out.push_str(&format!(
+            "Profitable windows: {}/{}, walk-forward efficiency {}\n",
+            self.profitable_windows,
+            self.windows.len(),
+            self.efficiency
+                .map(|e| format!("{:.2}", e))
+                .unwrap_or_else(|| "n/a".to_string())
+        ));
+        if let Some(set) = &self.most_selected {
+            out.push_str(&format!("Most selected: {}\n", set));
+        }
+        out
+    }
+}
+
+fn format_pf(profit_factor: Option<f64>) -> String {
+    profit_factor
+        .map(|pf| format!("{:.2}", pf))
+        .unwrap_or_else(|| "n/a".to_string())
+}
+
+/// Runs the grid search window by window
+pub struct WalkForwardOptimizer {
+    base: Config,
+    fill_model: FillModel,
+    grid: ParameterGrid,
+    config: WalkForwardConfig,
+    trend_filter: bool,
+}
+
+impl WalkForwardOptimizer {
+    /// Optimizer over `grid`; every other setting comes from `base`
+    pub fn new(
+        base: Config,
+        fill_model: FillModel,
+        grid: ParameterGrid,
+        config: WalkForwardConfig,
+    ) -> Self {
+        Self {
+            base,
+            fill_model,
+            grid,
+            config,
+            trend_filter: true,
+        }
+    }
+
+    /// Enable or disable the strategy's trend filter in every run
+    pub fn with_trend_filter(mut self, enabled: bool) -> Self {
+        self.trend_filter = enabled;
+        self
+    }
+
+    /// Candles run before each test slice to prime the indicators
+    pub fn warmup(&self) -> usize {
+        self.grid.max_rsi_period() * 3
+    }
+
+    /// Optimize every window of `candles` (oldest first); `sentiment` gives
+    /// the score in force at each candle
+    pub fn run<F>(&self, candles: &[Candle], sentiment: F) -> Result<WalkForwardReport>
+    where
+        F: Fn(&Candle) -> i32,
+    {
+        let WalkForwardConfig {
+            train_candles,
+            test_candles,
+            step_candles,
+            ..
+        } = self.config;
+        if train_candles == 0 || test_candles == 0 || step_candles == 0 {
+            return Err(BotError::Config(
+                "Walk-forward train, test and step sizes must be positive".to_string(),
+            ));
+        }
+        if candles.len() < train_candles + test_candles {
+            return Err(BotError::Config(format!(
+                "Walk-forward needs at least {} candles ({} train + {} test), got {}",
+                train_candles + test_candles,
+                train_candles,
+                test_candles,
+                candles.len()
+            )));
+        }
+        let candidates = self.grid.combinations();
+        if candidates.is_empty() {
+            return Err(BotError::Config(
+                "Parameter grid has no valid combination".to_string(),
+            ));
+        }
+
+        let mut windows = Vec::new();
+        let mut out_of_sample_trades = Vec::new();
+        let mut start = 0;
+        while start + train_candles + test_candles <= candles.len() {
+            let train = &candles[start..start + train_candles];
+            let test_from = start + train_candles;
+            let test = &candles[test_from..test_from + test_candles];
+
+            let (best, in_sample) = self.best_on(train, &candidates, &sentiment);
+            let trades = self.out_of_sample(candles, test_from, test, &best, &sentiment);
+            let out_of_sample = WindowStats::from_trades(&trades);
+            out_of_sample_trades.extend(trades);
+
+            windows.push(WindowResult {
+                index: windows.len() + 1,
+                train_start: train[0].timestamp,
+                train_end: train[train.len() - 1].timestamp,
+                test_start: test[0].timestamp,
+                test_end: test[test.len() - 1].timestamp,
+                best,
+                in_sample,
+                out_of_sample,
+                candidates: candidates.len(),
+            });
+            start += step_candles;
+        }
+
+        Ok(self.report(windows, &out_of_sample_trades))
+    }
+
+    fn backtest<F>(
+        &self,
+        params: &ParameterSet,
+        candles: &[Candle],
+        sentiment: &F,
+    ) -> BacktestReport
+    where
+        F: Fn(&Candle) -> i32,
+    {
+        let config = params.apply(&self.base);
+        let mut strategy = TradingStrategy::new(
+            config.strategy.clone(),
+            config.trading.clone(),
+            config.trading.initial_balance,
+        );
+        strategy.set_trend_filter(self.trend_filter);
+        BacktestEngine::with_strategy(&config, strategy, self.fill_model).run(candles, sentiment)
+    }
+
+    /// Best candidate on `train` and its in-sample statistics
+    fn best_on<F>(
+        &self,
+        train: &[Candle],
+        candidates: &[ParameterSet],
+        sentiment: &F,
+    ) -> (ParameterSet, WindowStats)
+    where
+        F: Fn(&Candle) -> i32,
+    {
+        let objective = self.config.objective;
+        let min_trades = self.config.min_trades;
+        candidates
+            .iter()
+            .map(|params| {
+                let stats = WindowStats::from_report(&self.backtest(params, train, sentiment));
+                (*params, stats)
+            })
+            .max_by(|(_, a), (_, b)| {
+                (a.trades >= min_trades)
+                    .cmp(&(b.trades >= min_trades))
+                    .then_with(|| objective.score(a).total_cmp(&objective.score(b)))
+                    .then_with(|| a.net_pnl.total_cmp(&b.net_pnl))
+                    // Earlier grid points win ties
+                    .then(Ordering::Greater)
+            })
+            .expect("grid is not empty")
+    }
+
+    /// Run `best` from `warmup` candles before the test slice and keep the
+    /// trades entered inside the slice
+    fn out_of_sample<F>(
+        &self,
+        candles: &[Candle],
+        test_from: usize,
+        test: &[Candle],
+        best: &ParameterSet,
+        sentiment: &F,
+    ) -> Vec<BacktestTrade>
+    where
+        F: Fn(&Candle) -> i32,
+    {
+        let run_from = test_from.saturating_sub(self.warmup());
+        let report = self.backtest(best, &candles[run_from..test_from + test.len()], sentiment);
+        let test_start = test[0].timestamp;
+        report
+            .trades
+            .into_iter()
+            .filter(|t| t.entry_time >= test_start)
+            .collect()
+    }
+
+    fn report(
+        &self,
+        windows: Vec<WindowResult>,
+        out_of_sample_trades: &[BacktestTrade],
+    ) -> WalkForwardReport {
+        let out_of_sample = WindowStats::from_trades(out_of_sample_trades);
+
+        let in_sample_per_candle: f64 = windows.iter().map(|w| w.in_sample.net_pnl).sum::<f64>()
+            / (windows.len() * self.config.train_candles).max(1) as f64;
+        let out_of_sample_per_candle =
+            out_of_sample.net_pnl / (windows.len() * self.config.test_candles).max(1) as f64;
+        let efficiency =
+            (in_sample_per_candle > 0.0).then(|| out_of_sample_per_candle / in_sample_per_candle);
+
+        let mut counts: Vec<(ParameterSet, usize)> = Vec::new();
+        for w in &windows {
+            match counts.iter_mut().find(|(set, _)| *set == w.best) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((w.best, 1)),
+            }
+        }
+        let most_selected = counts
+            .iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(Ordering::Greater))
+            .map(|(set, _)| *set);
+
+        WalkForwardReport {
+            config: self.config.clone(),
+            profitable_windows: windows
+                .iter()
+                .filter(|w| w.out_of_sample.net_pnl > 0.0)
+                .count(),
+            windows,
+            out_of_sample,
+            efficiency,
+            most_selected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::{CloseReason, OrderSide, TimeFrame};
+    use chrono::Duration;
+
+    fn candles(n: usize) -> Vec<Candle> {
+        let start = DateTime::parse_from_rfc3339("2024-03-04T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        (0..n)
+            .map(|i| {
+                // Slow sine wave: regular dips and rallies for RSI to trade
+                let close = 1000.0 + 30.0 * (i as f64 / 12.0).sin();
+                Candle {
+                    timestamp: start + Duration::hours(i as i64),
+                    timeframe: TimeFrame::H1,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 1,
+                    avg_spread: None,
+                    close_spread: None,
+                }
+            })
+            .collect()
+    }
+
+    fn trade(net_pnl: f64) -> BacktestTrade {
+        let time = Utc::now();
+        BacktestTrade {
+            position_id: "1".to_string(),
+            side: OrderSide::Buy,
+            volume: 1.0,
+            entry_time: time,
+            entry_price: 100.0,
+            exit_time: time,
+            exit_price: 100.0 + net_pnl,
+            close_reason: CloseReason::TakeProfit,
+            gross_pnl: net_pnl,
+            commission: 0.0,
+            net_pnl,
+        }
+    }
+
+    #[test]
+    fn test_grid_skips_invalid_combinations() {
+        let grid = ParameterGrid {
+            rsi_periods: vec![14],
+            rsi_oversold: vec![30.0, 70.0],
+            rsi_overbought: vec![70.0],
+            take_profit_percent: vec![2.0],
+            stop_loss_percent: vec![1.0, 0.0],
+            sentiment_thresholds: vec![30],
+        };
+        let sets = grid.combinations();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].rsi_oversold, 30.0);
+        assert_eq!(ParameterGrid::default().combinations().len(), 729);
+    }
+
+    #[test]
+    fn test_window_stats() {
+        let stats =
+            WindowStats::from_trades(&[trade(30.0), trade(-10.0), trade(-20.0), trade(5.0)]);
+        assert_eq!(stats.trades, 4);
+        assert_eq!(stats.net_pnl, 5.0);
+        assert_eq!(stats.win_rate, 50.0);
+        assert_eq!(stats.profit_factor, Some(35.0 / 30.0));
+        assert_eq!(stats.max_drawdown, 30.0);
+    }
+
+    #[test]
+    fn test_objective_parse_and_rank() {
+        assert_eq!("net-pnl".parse::<Objective>(), Ok(Objective::NetPnl));
+        assert_eq!(
+            "Profit_Factor".parse::<Objective>(),
+            Ok(Objective::ProfitFactor)
+        );
+        assert!("sharpe".parse::<Objective>().is_err());
+
+        let flawless = WindowStats::from_trades(&[trade(10.0)]);
+        let mixed = WindowStats::from_trades(&[trade(50.0), trade(-10.0)]);
+        assert!(Objective::ProfitFactor.score(&flawless) > Objective::ProfitFactor.score(&mixed));
+        assert!(Objective::NetPnl.score(&mixed) > Objective::NetPnl.score(&flawless));
+    }
+
+    #[test]
+    fn test_windows_tile_history() {
+        let grid = ParameterGrid {
+            rsi_periods: vec![14],
+            rsi_oversold: vec![30.0, 35.0],
+            rsi_overbought: vec![70.0],
+            take_profit_percent: vec![1.0],
+            stop_loss_percent: vec![1.0],
+            sentiment_thresholds: vec![30],
+        };
+        let mut config = WalkForwardConfig::new(200, 100);
+        config.min_trades = 1;
+        let optimizer =
+            WalkForwardOptimizer::new(Config::default(), FillModel::default(), grid, config)
+                .with_trend_filter(false);
+
+        let history = candles(520);
+        let report = optimizer.run(&history, |_| 50).unwrap();
+
+        // 200 + 100, then +100 twice more; a fourth window would need 600 candles
+        assert_eq!(report.windows.len(), 3);
+        assert_eq!(report.windows[0].test_start, history[200].timestamp);
+        assert_eq!(report.windows[2].test_end, history[499].timestamp);
+        assert!(report.windows.iter().all(|w| w.candidates == 2));
+        assert_eq!(
+            report.out_of_sample.trades,
+            report
+                .windows
+                .iter()
+                .map(|w| w.out_of_sample.trades)
+                .sum::<usize>()
+        );
+        assert!(report.summary().contains("Out-of-sample"));
+    }
+
+    #[test]
+    fn test_too_little_history_is_an_error() {
+        let optimizer = WalkForwardOptimizer::new(
+            Config::default(),
+            FillModel::default(),
+            ParameterGrid::default(),
+            WalkForwardConfig::new(200, 100),
+        );
+        assert!(optimizer.run(&candles(250), |_| 0).is_err());
+    }
+}
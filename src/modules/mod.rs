@@ -1,14 +1,20 @@
 //! Bot modules
 //!
 //! This module contains all the core functionality:
+//! - `backtest`: Candle replay through the production strategy code
 //! - `scraper`: Sentiment analysis from Perplexity API and Twitter
 //! - `trading`: cTrader API client and trading logic
 //! - `monitoring`: Dashboard and metrics
-//! - `security`: Secrets validation and rate limiting
+//! - `notifications`: Telegram delivery for reports and alerts
+//! - `security`: Secrets validation, rate limiting and API access roles
+//! - `storage`: Scheduled off-host archives of bot data
 //! - `utils`: Helper functions
 
+pub mod backtest;
 pub mod monitoring;
+pub mod notifications;
 pub mod scraper;
 pub mod security;
+pub mod storage;
 pub mod trading;
 pub mod utils;
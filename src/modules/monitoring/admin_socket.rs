@@ -0,0 +1,382 @@
+//! Local admin console on a Unix domain socket.
+//!
+//! For operators on the same host who don't want to expose an HTTP port:
+//! with `ADMIN_SOCKET_PATH` set, the bot listens on that socket (mode 0600,
+//! so only its own user can connect) and answers one command per line:
+//!
+//! ```text
+//! status          uptime, pause state, day P&L, last signal
+//! positions       open positions
+//! pause / resume  stop or allow new entries (exits keep running)
+//! close <id>      close one position at market
+//! reload          re-read .env (alert rules, heartbeat, market brief hour, features)
+//! arm [minutes]   allow live orders for a while (LIVE_ARMING_REQUIRED)
+//! disarm          stop sending live orders
+//! features        strategy feature flags
+//! feature <name> on|off   switch a strategy component (e.g. trend_filter)
+//! help / quit
+//! ```
+//!
+//! Every reply ends with an empty line so scripts can read it back, e.g.
+//! `echo status | socat - UNIX-CONNECT:/run/palm-oil-bot.sock`.
+
+use std::path::PathBuf;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::modules::trading::{EmergencyHandle, StrategyFeature};
+
+const HELP_TEXT: &str = "Commands:
+  status          uptime, pause state, day P&L, last signal
+  positions       open positions
+  pause           stop opening new positions (exits keep running)
+  resume          allow new positions again
+  close <id>      close one position at market
+  reload          re-read .env (alert rules, heartbeat, market brief hour, features)
+  arm [minutes]   allow live orders for a while (default window if omitted)
+  disarm          stop sending live orders
+  features        strategy feature flags
+  feature <name> on|off
+                  switch a strategy component (trend_filter, sentiment_gate,
+                  macd_confirmation, higher_timeframe, session_filter,
+                  news_blackout)
+  quit            close this session";
+
+const FEATURE_USAGE: &str = "usage: feature <name> on|off";
+
+/// Commands understood by the console
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCommand {
+    Status,
+    Positions,
+    Pause,
+    Resume,
+    Close(String),
+    Reload,
+    /// Arm for this many minutes, or the configured default
+    Arm(Option<i64>),
+    Disarm,
+    Features,
+    /// Switch a strategy feature on (`true`) or off
+    SetFeature(StrategyFeature, bool),
+    Help,
+    Quit,
+}
+
+impl AdminCommand {
+    /// Parse one input line; `Err` carries the message sent back
+    pub fn parse(line: &str) -> std::result::Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default().to_lowercase();
+        let arg = words.next();
+        match (command.as_str(), arg) {
+            ("status", None) => Ok(Self::Status),
+            ("positions", None) => Ok(Self::Positions),
+            ("pause", None) => Ok(Self::Pause),
+            ("resume", None) => Ok(Self::Resume),
+            ("close", Some(id)) if words.next().is_none() => Ok(Self::Close(id.to_string())),
+            ("close", _) => Err("usage: close <position_id>".to_string()),
+            ("reload", None) => Ok(Self::Reload),
+            ("arm", None) => Ok(Self::Arm(None)),
+            ("arm", Some(minutes)) if words.next().is_none() => minutes
+                .parse::<i64>()
+                .ok()
+                .filter(|m| *m > 0)
+                .map(|m| Self::Arm(Some(m)))
+                .ok_or_else(|| "usage: arm [minutes]".to_string()),
+            ("arm", _) => Err("usage: arm [minutes]".to_string()),
+            ("disarm", None) => Ok(Self::Disarm),
+            ("features", None) => Ok(Self::Features),
+            ("feature", Some(name)) => {
+                let enabled = match (words.next(), words.next()) {
+                    (Some(state), None) if state.eq_ignore_ascii_case("on") => true,
+                    (Some(state), None) if state.eq_ignore_ascii_case("off") => false,
+                    _ => return Err(FEATURE_USAGE.to_string()),
+                };
+                let feature = name
+                    .parse::<StrategyFeature>()
+                    .map_err(|e| e.to_string())?;
+                Ok(Self::SetFeature(feature, enabled))
+            }
+            ("feature", None) => Err(FEATURE_USAGE.to_string()),
+            ("help", None) | ("?", None) => Ok(Self::Help),
+            ("quit", None) | ("exit", None) => Ok(Self::Quit),
+            ("", _) => Err("empty command; try 'help'".to_string()),
+            _ => Err(format!("unknown command '{}'; try 'help'", line.trim())),
+        }
+    }
+}
+
+/// Socket path from `ADMIN_SOCKET_PATH` (console disabled when unset)
+pub fn admin_socket_path() -> Option<PathBuf> {
+    std::env::var("ADMIN_SOCKET_PATH")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Listen on `path` and serve console sessions until the bot exits
+#[cfg(unix)]
+pub fn start_admin_socket(path: PathBuf, control: EmergencyHandle) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let listener = match bind_private(&path) {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!("Admin socket {} not started: {}", path.display(), err);
+                return;
+            }
+        };
+        info!("Admin console listening on {}", path.display());
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_session(stream, control.clone()));
+                }
+                Err(err) => {
+                    warn!("Admin socket accept failed: {}", err);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                }
+            }
+        }
+    })
+}
+
+/// Bind inside a fresh 0700 directory next to `path`, restrict the socket to
+/// 0600 and only then move it into place (replacing a socket left behind by a
+/// previous run), so it is never reachable with looser permissions
+#[cfg(unix)]
+fn bind_private(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::path::Path;
+
+    let parent = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let staging = parent.join(format!(".admin-socket-{}", std::process::id()));
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let staged = staging.join("admin.sock");
+
+    let bound = tokio::net::UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&staging);
+    bound
+}
+
+/// Unix domain sockets are not available: log and do nothing
+#[cfg(not(unix))]
+pub fn start_admin_socket(path: PathBuf, _control: EmergencyHandle) -> JoinHandle<()> {
+    warn!(
+        "ADMIN_SOCKET_PATH={} ignored: Unix sockets are not supported on this platform",
+        path.display()
+    );
+    tokio::spawn(async {})
+}
+
+#[cfg(unix)]
+async fn serve_session(stream: tokio::net::UnixStream, control: EmergencyHandle) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    debug!("Admin console session opened");
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply = match AdminCommand::parse(&line) {
+            Ok(AdminCommand::Quit) => break,
+            Ok(command) => run_command(command, &control).await,
+            Err(message) => message,
+        };
+        if writer
+            .write_all(format!("{}\n\n", reply.trim_end()).as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+    debug!("Admin console session closed");
+}
+
+async fn run_command(command: AdminCommand, control: &EmergencyHandle) -> String {
+    match command {
+        AdminCommand::Status => control
+            .status()
+            .await
+            .unwrap_or_else(|err| format!("error: {}", err)),
+        AdminCommand::Positions => match control.positions().await {
+            Ok(positions) if positions.is_empty() => "No open positions".to_string(),
+            Ok(positions) => positions
+                .iter()
+                .map(|p| {
+                    format!(
+                        "{} {} {} @ {:.2} (P&L {:+.2})",
+                        p.id, p.side, p.volume, p.entry_price, p.current_pnl
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(err) => format!("error: {}", err),
+        },
+        AdminCommand::Pause | AdminCommand::Resume => {
+            let paused = command == AdminCommand::Pause;
+            warn!(
+                "Admin console: {} requested",
+                if paused { "pause" } else { "resume" }
+            );
+            if control.request_pause(paused) {
+                if paused { "paused" } else { "resumed" }.to_string()
+            } else {
+                "error: trading loop busy or stopped".to_string()
+            }
+        }
+        AdminCommand::Close(position_id) => {
+            warn!("Admin console: close of position {} requested", position_id);
+            match control.close_position(&position_id).await {
+                Ok(position) => format!(
+                    "closed {} {} {} @ {:.2}",
+                    position.id, position.side, position.volume, position.entry_price
+                ),
+                Err(err) => format!("error: {}", err),
+            }
+        }
+        AdminCommand::Reload => control
+            .reload()
+            .await
+            .unwrap_or_else(|err| format!("error: {}", err)),
+        AdminCommand::Arm(ttl_minutes) => {
+            warn!(
+                "Admin console: arm requested (ttl_minutes={:?})",
+                ttl_minutes
+            );
+            match control.arm(ttl_minutes).await {
+                Ok(status) => match status.armed_until {
+                    Some(until) => format!("armed until {}", until.format("%Y-%m-%d %H:%M UTC")),
+                    None => "armed".to_string(),
+                },
+                Err(err) => format!("error: {}", err),
+            }
+        }
+        AdminCommand::Disarm => {
+            warn!("Admin console: disarm requested");
+            match control.disarm().await {
+                Ok(_) => "disarmed".to_string(),
+                Err(err) => format!("error: {}", err),
+            }
+        }
+        AdminCommand::Features => control
+            .features()
+            .await
+            .map(|features| features.to_string())
+            .unwrap_or_else(|err| format!("error: {}", err)),
+        AdminCommand::SetFeature(feature, enabled) => {
+            warn!(
+                "Admin console: feature {} {} requested",
+                feature,
+                if enabled { "on" } else { "off" }
+            );
+            control
+                .set_feature(feature, enabled)
+                .await
+                .map(|features| features.to_string())
+                .unwrap_or_else(|err| format!("error: {}", err))
+        }
+        AdminCommand::Help => HELP_TEXT.to_string(),
+        AdminCommand::Quit => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(AdminCommand::parse("status"), Ok(AdminCommand::Status));
+        assert_eq!(AdminCommand::parse("  PAUSE \r"), Ok(AdminCommand::Pause));
+        assert_eq!(
+            AdminCommand::parse("close 123456"),
+            Ok(AdminCommand::Close("123456".to_string()))
+        );
+        assert_eq!(AdminCommand::parse("reload"), Ok(AdminCommand::Reload));
+        assert_eq!(AdminCommand::parse("exit"), Ok(AdminCommand::Quit));
+        assert_eq!(AdminCommand::parse("arm"), Ok(AdminCommand::Arm(None)));
+        assert_eq!(
+            AdminCommand::parse("arm 30"),
+            Ok(AdminCommand::Arm(Some(30)))
+        );
+        assert_eq!(AdminCommand::parse("disarm"), Ok(AdminCommand::Disarm));
+        assert!(AdminCommand::parse("arm soon").is_err());
+        assert!(AdminCommand::parse("arm 0").is_err());
+        assert_eq!(
+            AdminCommand::parse("feature trend_filter OFF"),
+            Ok(AdminCommand::SetFeature(StrategyFeature::TrendFilter, false))
+        );
+        assert_eq!(AdminCommand::parse("features"), Ok(AdminCommand::Features));
+        assert!(AdminCommand::parse("feature trend_filter").is_err());
+        assert!(AdminCommand::parse("feature divergence on").is_err());
+
+        assert!(AdminCommand::parse("close").is_err());
+        assert!(AdminCommand::parse("close 1 2").is_err());
+        assert!(AdminCommand::parse("status now").is_err());
+        assert!(AdminCommand::parse("").is_err());
+        assert!(AdminCommand::parse("flatten")
+            .unwrap_err()
+            .contains("unknown command"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_session_round_trip() {
+        use crate::modules::trading::{emergency_channel, EmergencyCommand};
+        use tokio::net::UnixStream;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("admin.sock");
+        let (control, mut rx) = emergency_channel();
+        start_admin_socket(path.clone(), control);
+
+        let bot = tokio::spawn(async move {
+            if let Some(EmergencyCommand::Reload { reply }) = rx.recv().await {
+                let _ = reply.send("Reloaded from .env: 2 alert rules".to_string());
+            }
+        });
+
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(s) = UnixStream::connect(&path).await {
+                stream = Some(s);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let (reader, mut writer) = stream.expect("socket listening").into_split();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            // Only the socket is left: the staging directory is gone
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+        let mut lines = BufReader::new(reader).lines();
+
+        writer.write_all(b"bogus\nreload\n").await.unwrap();
+        assert!(lines
+            .next_line()
+            .await
+            .unwrap()
+            .unwrap()
+            .contains("unknown command"));
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "");
+        assert_eq!(
+            lines.next_line().await.unwrap().unwrap(),
+            "Reloaded from .env: 2 alert rules"
+        );
+        bot.await.unwrap();
+    }
+}
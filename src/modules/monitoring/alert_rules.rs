@@ -0,0 +1,385 @@
+//! User-defined market alert rules
+//!
+//! Rules come from `ALERT_RULES`, separated by `;`:
+//! - `price>4900` / `price<4700`: mid price crosses the level
+//! - `rsi>70` / `rsi<30`: candle RSI crosses the value
+//! - `spread>5`: bid/ask spread rises above the value
+//! - `sentiment_flip`: blended sentiment changes sign
+//!
+//! A crossing rule fires when the value moves from one side of the level to
+//! the other, so it stays quiet while the value sits past the level and
+//! re-arms once it comes back. Each rule is then silenced for
+//! `ALERT_COOLDOWN_SECS` (default 300) so a value hovering around a level
+//! does not flood notifications. Alerts are raised whether or not the bot
+//! trades.
+
+use chrono::{DateTime, Duration, Utc};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use tracing::warn;
+
+use crate::modules::trading::AlertLevel;
+
+const DEFAULT_COOLDOWN_SECS: i64 = 300;
+
+/// Value an alert rule watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMetric {
+    Price,
+    Rsi,
+    Spread,
+}
+
+impl fmt::Display for AlertMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertMetric::Price => write!(f, "price"),
+            AlertMetric::Rsi => write!(f, "RSI"),
+            AlertMetric::Spread => write!(f, "spread"),
+        }
+    }
+}
+
+/// One alert condition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertRule {
+    /// `metric` crosses `level` upwards (`above`) or downwards
+    Cross {
+        metric: AlertMetric,
+        above: bool,
+        level: f64,
+    },
+    /// Sentiment score changes sign
+    SentimentFlip,
+}
+
+impl AlertRule {
+    fn level(&self) -> AlertLevel {
+        match self {
+            AlertRule::Cross {
+                metric: AlertMetric::Spread,
+                ..
+            } => AlertLevel::Warning,
+            _ => AlertLevel::Info,
+        }
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertRule::Cross {
+                metric,
+                above,
+                level,
+            } => write!(f, "{}{}{}", metric, if *above { ">" } else { "<" }, level),
+            AlertRule::SentimentFlip => write!(f, "sentiment_flip"),
+        }
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim().to_lowercase();
+        if rule == "sentiment_flip" {
+            return Ok(AlertRule::SentimentFlip);
+        }
+
+        let (name, above, level) = if let Some((name, level)) = rule.split_once('>') {
+            (name, true, level)
+        } else if let Some((name, level)) = rule.split_once('<') {
+            (name, false, level)
+        } else {
+            return Err(format!("alert rule {:?} has no > or <", s));
+        };
+        let metric = match name.trim() {
+            "price" => AlertMetric::Price,
+            "rsi" => AlertMetric::Rsi,
+            "spread" => AlertMetric::Spread,
+            other => return Err(format!("unknown alert metric {:?}", other)),
+        };
+        if metric == AlertMetric::Spread && !above {
+            return Err("spread rules only support >".to_string());
+        }
+        let level = level
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("invalid level in alert rule {:?}", s))?;
+
+        Ok(AlertRule::Cross {
+            metric,
+            above,
+            level,
+        })
+    }
+}
+
+/// An alert that fired
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggeredAlert {
+    pub level: AlertLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+struct RuleState {
+    rule: AlertRule,
+    last_value: Option<f64>,
+    last_fired: Option<DateTime<Utc>>,
+}
+
+impl RuleState {
+    fn cooled_down(&self, now: DateTime<Utc>, cooldown: Duration) -> bool {
+        match self.last_fired {
+            Some(fired) => now - fired >= cooldown,
+            None => true,
+        }
+    }
+}
+
+/// Configured rules and the last value each one saw
+#[derive(Debug, Clone)]
+pub struct AlertRules {
+    rules: Vec<RuleState>,
+    cooldown: Duration,
+}
+
+impl AlertRules {
+    pub fn new(rules: Vec<AlertRule>, cooldown: Duration) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| RuleState {
+                    rule,
+                    last_value: None,
+                    last_fired: None,
+                })
+                .collect(),
+            cooldown,
+        }
+    }
+
+    /// Load `ALERT_RULES` and `ALERT_COOLDOWN_SECS`. Invalid rules are
+    /// skipped with a warning.
+    pub fn from_env() -> Self {
+        let rules = env::var("ALERT_RULES")
+            .unwrap_or_default()
+            .split(';')
+            .filter(|r| !r.trim().is_empty())
+            .filter_map(|r| match r.parse::<AlertRule>() {
+                Ok(rule) => Some(rule),
+                Err(err) => {
+                    warn!("ALERT_RULES: {}; rule ignored", err);
+                    None
+                }
+            })
+            .collect();
+        let cooldown_secs = env::var("ALERT_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_COOLDOWN_SECS);
+        Self::new(rules, Duration::seconds(cooldown_secs.max(0)))
+    }
+
+    /// Whether any rule is configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Number of configured rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Feed a new reading of `metric`; returns the alerts it triggers
+    pub fn observe(
+        &mut self,
+        metric: AlertMetric,
+        value: f64,
+        now: DateTime<Utc>,
+    ) -> Vec<TriggeredAlert> {
+        let cooldown = self.cooldown;
+        let mut triggered = Vec::new();
+        for state in &mut self.rules {
+            let AlertRule::Cross {
+                metric: rule_metric,
+                above,
+                level,
+            } = state.rule
+            else {
+                continue;
+            };
+            if rule_metric != metric {
+                continue;
+            }
+
+            let crossed = match state.last_value {
+                // Spread alerts also fire on the first reading past the level
+                None => metric == AlertMetric::Spread && value > level,
+                Some(prev) if above => prev <= level && value > level,
+                Some(prev) => prev >= level && value < level,
+            };
+            state.last_value = Some(value);
+            if crossed && state.cooled_down(now, cooldown) {
+                state.last_fired = Some(now);
+                triggered.push(TriggeredAlert {
+                    level: state.rule.level(),
+                    message: format!(
+                        "🔔 Alert {}: {} {} {} ({})",
+                        state.rule,
+                        metric,
+                        if above { "rose above" } else { "fell below" },
+                        level,
+                        format_value(value)
+                    ),
+                });
+            }
+        }
+        triggered
+    }
+
+    /// Feed a new sentiment score; returns the alerts it triggers
+    pub fn observe_sentiment(&mut self, score: i32, now: DateTime<Utc>) -> Vec<TriggeredAlert> {
+        let cooldown = self.cooldown;
+        let mut triggered = Vec::new();
+        for state in &mut self.rules {
+            if state.rule != AlertRule::SentimentFlip || score == 0 {
+                continue;
+            }
+            // Neutral readings keep the last non-zero sign
+            let flipped = state
+                .last_value
+                .is_some_and(|prev| prev.signum() != (score as f64).signum());
+            state.last_value = Some(score as f64);
+            if flipped && state.cooled_down(now, cooldown) {
+                state.last_fired = Some(now);
+                triggered.push(TriggeredAlert {
+                    level: state.rule.level(),
+                    message: format!(
+                        "🔔 Alert sentiment_flip: sentiment turned {} ({:+})",
+                        if score > 0 { "bullish" } else { "bearish" },
+                        score
+                    ),
+                });
+            }
+        }
+        triggered
+    }
+}
+
+fn format_value(value: f64) -> String {
+    let text = format!("{:.5}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn t0() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap()
+    }
+
+    fn rules(spec: &[&str], cooldown_secs: i64) -> AlertRules {
+        AlertRules::new(
+            spec.iter().map(|r| r.parse().unwrap()).collect(),
+            Duration::seconds(cooldown_secs),
+        )
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(
+            "price>4900".parse(),
+            Ok(AlertRule::Cross {
+                metric: AlertMetric::Price,
+                above: true,
+                level: 4900.0
+            })
+        );
+        assert_eq!(
+            " RSI < 30 ".parse(),
+            Ok(AlertRule::Cross {
+                metric: AlertMetric::Rsi,
+                above: false,
+                level: 30.0
+            })
+        );
+        assert_eq!("sentiment_flip".parse(), Ok(AlertRule::SentimentFlip));
+        assert!("volume>10".parse::<AlertRule>().is_err());
+        assert!("price=4900".parse::<AlertRule>().is_err());
+        assert!("spread<1".parse::<AlertRule>().is_err());
+        assert!("price>abc".parse::<AlertRule>().is_err());
+    }
+
+    #[test]
+    fn test_price_cross_fires_once_and_rearms() {
+        let mut alerts = rules(&["price>4900"], 0);
+        let now = t0();
+
+        // First reading only sets the baseline
+        assert!(alerts.observe(AlertMetric::Price, 4950.0, now).is_empty());
+        assert!(alerts.observe(AlertMetric::Price, 4890.0, now).is_empty());
+
+        let fired = alerts.observe(AlertMetric::Price, 4901.0, now);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].level, AlertLevel::Info);
+        assert!(fired[0].message.contains("rose above 4900"));
+
+        // Staying above does not repeat; coming back re-arms
+        assert!(alerts.observe(AlertMetric::Price, 4920.0, now).is_empty());
+        assert!(alerts.observe(AlertMetric::Price, 4900.0, now).is_empty());
+        assert_eq!(alerts.observe(AlertMetric::Price, 4905.0, now).len(), 1);
+    }
+
+    #[test]
+    fn test_cooldown_silences_repeats() {
+        let mut alerts = rules(&["rsi<30"], 300);
+        let now = t0();
+        alerts.observe(AlertMetric::Rsi, 35.0, now);
+        assert_eq!(alerts.observe(AlertMetric::Rsi, 28.0, now).len(), 1);
+
+        alerts.observe(AlertMetric::Rsi, 31.0, now + Duration::seconds(60));
+        assert!(alerts
+            .observe(AlertMetric::Rsi, 29.0, now + Duration::seconds(120))
+            .is_empty());
+
+        alerts.observe(AlertMetric::Rsi, 31.0, now + Duration::seconds(400));
+        assert_eq!(
+            alerts
+                .observe(AlertMetric::Rsi, 29.0, now + Duration::seconds(500))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_spread_fires_on_first_wide_reading() {
+        let mut alerts = rules(&["spread>5", "price<4700"], 0);
+        let fired = alerts.observe(AlertMetric::Spread, 6.5, t0());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].level, AlertLevel::Warning);
+        assert!(fired[0].message.contains("(6.5)"));
+
+        // Other metrics are untouched
+        assert!(alerts.observe(AlertMetric::Price, 4600.0, t0()).is_empty());
+    }
+
+    #[test]
+    fn test_sentiment_flip() {
+        let mut alerts = rules(&["sentiment_flip"], 0);
+        let now = t0();
+        assert!(alerts.observe_sentiment(40, now).is_empty());
+        assert!(alerts.observe_sentiment(0, now).is_empty());
+        assert!(alerts.observe_sentiment(10, now).is_empty());
+
+        let fired = alerts.observe_sentiment(-25, now);
+        assert_eq!(fired.len(), 1);
+        assert!(fired[0].message.contains("bearish (-25)"));
+        assert!(alerts.observe_sentiment(-50, now).is_empty());
+    }
+}
@@ -0,0 +1,442 @@
+//! HTTP control API for operating the running bot from scripts.
+//!
+//! Served alongside `/metrics` when `CONTROL_API_ENABLED` is set:
+//! - `GET /control/status` — uptime, pause/standby state, day P&L, last signal
+//! - `GET /control/positions` — open positions
+//! - `POST /control/pause` / `POST /control/resume` — stop or allow new entries
+//!   (open positions are still managed)
+//! - `POST /control/close/{position_id}` — close one position at market
+//! - `POST /control/sentiment/refresh` — drop the cached sentiment and fetch
+//!   a fresh reading
+//! - `POST /control/cancel_all` — cancel every pending order at the broker
+//! - `POST /control/cancel_all?flatten=true` — also close every open position
+//! - `POST /control/arm?ttl_minutes=30` — allow live orders for a while (the
+//!   configured default without `ttl_minutes`); `POST /control/disarm` stops them
+//! - `GET /control/features` — strategy feature flags
+//! - `POST /control/features/{name}?enabled=false` — switch a strategy
+//!   component (e.g. `trend_filter`) off, or back on with `enabled=true`
+//!
+//! The endpoints can move money, so they are only mounted when a token is
+//! configured (`CONTROL_API_TOKEN`, admin role, or role tokens in
+//! `ACCESS_TOKENS`); requests must send `Authorization: Bearer <token>`.
+//! Viewers may read status and positions, operators may also pause, close,
+//! cancel and disarm, and only admins may flatten, arm or switch features. Missing tokens get
+//! 401, insufficient roles 403. Commands run on the trading loop between ticks
+//! and answer with JSON once done.
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::modules::security::{AccessPolicy, Action, Role};
+use crate::modules::trading::{EmergencyHandle, StrategyFeature};
+
+/// Name the access checks are audited under
+const API: &str = "control_api";
+
+/// How long a caller waits for the trading loop to finish a command
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// State behind the control endpoints
+#[derive(Clone)]
+pub struct ControlApi {
+    pub emergency: EmergencyHandle,
+    /// Accepted bearer tokens and their roles
+    pub access: AccessPolicy,
+}
+
+impl ControlApi {
+    /// Build from `CONTROL_API_ENABLED`, `CONTROL_API_TOKEN` and
+    /// `ACCESS_TOKENS`; `None` when disabled or when no token is configured
+    pub fn from_env(emergency: EmergencyHandle) -> Option<Self> {
+        let enabled = matches!(
+            std::env::var("CONTROL_API_ENABLED").as_deref(),
+            Ok("true") | Ok("1") | Ok("yes")
+        );
+        if !enabled {
+            return None;
+        }
+        let access = AccessPolicy::from_env()
+            .with_token(std::env::var("CONTROL_API_TOKEN").ok(), Role::Admin);
+        if access.is_empty() {
+            warn!("CONTROL_API_ENABLED is set but neither CONTROL_API_TOKEN nor ACCESS_TOKENS is set; control API disabled");
+            return None;
+        }
+        Some(Self { emergency, access })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CancelAllParams {
+    #[serde(default)]
+    flatten: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ArmParams {
+    ttl_minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeatureParams {
+    enabled: bool,
+}
+
+/// Router serving `/control/*`
+pub fn control_router(control: ControlApi) -> Router {
+    Router::new()
+        .route("/control/status", get(status_handler))
+        .route("/control/positions", get(positions_handler))
+        .route("/control/pause", post(pause_handler))
+        .route("/control/resume", post(resume_handler))
+        .route("/control/close/:position_id", post(close_handler))
+        .route("/control/sentiment/refresh", post(sentiment_handler))
+        .route("/control/cancel_all", post(cancel_all_handler))
+        .route("/control/arm", post(arm_handler))
+        .route("/control/disarm", post(disarm_handler))
+        .route("/control/features", get(features_handler))
+        .route("/control/features/:name", post(set_feature_handler))
+        .with_state(control)
+}
+
+/// Wait for the trading loop's answer and render it as JSON
+async fn run_command<T: Serialize>(
+    command: impl Future<Output = std::result::Result<T, String>>,
+    failure: StatusCode,
+) -> Response {
+    match tokio::time::timeout(COMMAND_TIMEOUT, command).await {
+        Ok(Ok(value)) => Json(value).into_response(),
+        Ok(Err(err)) => (failure, err).into_response(),
+        Err(_) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            "Command still running; check the bot logs",
+        )
+            .into_response(),
+    }
+}
+
+async fn status_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    run_command(
+        control.emergency.status_report(),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+    .await
+}
+
+async fn positions_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    run_command(
+        control.emergency.positions(),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+    .await
+}
+
+async fn pause_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    set_paused(&control, &headers, true)
+}
+
+async fn resume_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    set_paused(&control, &headers, false)
+}
+
+fn set_paused(control: &ControlApi, headers: &HeaderMap, paused: bool) -> Response {
+    let action = if paused {
+        Action::Pause
+    } else {
+        Action::Resume
+    };
+    if let Err(denied) = control.access.authorize_headers(API, headers, action) {
+        return denied.into_response();
+    }
+    warn!(
+        "Control API: {} requested",
+        if paused { "pause" } else { "resume" }
+    );
+    if control.emergency.request_pause(paused) {
+        (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({ "paused": paused })),
+        )
+            .into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Command not queued: trading loop busy or stopped",
+        )
+            .into_response()
+    }
+}
+
+async fn close_handler(
+    State(control): State<ControlApi>,
+    Path(position_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::ClosePosition)
+    {
+        return denied.into_response();
+    }
+
+    match control.emergency.positions().await {
+        Ok(positions) if !positions.iter().any(|p| p.id == position_id) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("Position {} not found", position_id),
+            )
+                .into_response();
+        }
+        Ok(_) => {}
+        Err(err) => return (StatusCode::SERVICE_UNAVAILABLE, err).into_response(),
+    }
+
+    warn!("Control API: close of position {} requested", position_id);
+    run_command(
+        control.emergency.close_position(&position_id),
+        StatusCode::CONFLICT,
+    )
+    .await
+}
+
+async fn sentiment_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::RefreshSentiment)
+    {
+        return denied.into_response();
+    }
+    run_command(
+        control.emergency.refresh_sentiment(),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+    .await
+}
+
+async fn arm_handler(
+    State(control): State<ControlApi>,
+    Query(params): Query<ArmParams>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(denied) = control.access.authorize_headers(API, &headers, Action::Arm) {
+        return denied.into_response();
+    }
+    warn!(
+        "Control API: arm requested (ttl_minutes={:?})",
+        params.ttl_minutes
+    );
+    run_command(
+        control.emergency.arm(params.ttl_minutes),
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
+
+async fn disarm_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::Disarm)
+    {
+        return denied.into_response();
+    }
+    warn!("Control API: disarm requested");
+    run_command(control.emergency.disarm(), StatusCode::SERVICE_UNAVAILABLE).await
+}
+
+async fn features_handler(State(control): State<ControlApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    run_command(control.emergency.features(), StatusCode::SERVICE_UNAVAILABLE).await
+}
+
+async fn set_feature_handler(
+    State(control): State<ControlApi>,
+    Path(name): Path<String>,
+    Query(params): Query<FeatureParams>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(denied) = control
+        .access
+        .authorize_headers(API, &headers, Action::SetFeature)
+    {
+        return denied.into_response();
+    }
+    let feature = match name.parse::<StrategyFeature>() {
+        Ok(feature) => feature,
+        Err(err) => return (StatusCode::NOT_FOUND, err.to_string()).into_response(),
+    };
+    warn!(
+        "Control API: feature {} {} requested",
+        feature,
+        if params.enabled { "on" } else { "off" }
+    );
+    run_command(
+        control.emergency.set_feature(feature, params.enabled),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+    .await
+}
+
+async fn cancel_all_handler(
+    State(control): State<ControlApi>,
+    Query(params): Query<CancelAllParams>,
+    headers: HeaderMap,
+) -> Response {
+    let action = if params.flatten {
+        Action::Flatten
+    } else {
+        Action::CancelOrders
+    };
+    if let Err(denied) = control.access.authorize_headers(API, &headers, action) {
+        return denied.into_response();
+    }
+
+    warn!(
+        "Control API: cancel-all requested (flatten={})",
+        params.flatten
+    );
+    let outcome = tokio::time::timeout(
+        COMMAND_TIMEOUT,
+        control.emergency.cancel_all(params.flatten),
+    )
+    .await;
+    match outcome {
+        Ok(Ok(report)) => {
+            let status = if report.is_complete() {
+                StatusCode::OK
+            } else {
+                StatusCode::MULTI_STATUS
+            };
+            (status, Json(report)).into_response()
+        }
+        Ok(Err(err)) => {
+            error!("Control API: cancel-all failed: {}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, err).into_response()
+        }
+        Err(_) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            "Cancel-all still running; check the bot logs",
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::security::AccessDenied;
+    use crate::modules::trading::emergency_channel;
+    use axum::http::header;
+
+    #[test]
+    fn test_bearer_token_required() {
+        let (emergency, _rx) = emergency_channel();
+        let control = ControlApi {
+            emergency,
+            access: AccessPolicy::default().with_token(Some("secret".to_string()), Role::Admin),
+        };
+
+        let mut headers = HeaderMap::new();
+        let status = |r: Result<Role, AccessDenied>| r.unwrap_err().into_response().status();
+        assert_eq!(
+            status(
+                control
+                    .access
+                    .authorize_headers(API, &headers, Action::ReadState)
+            ),
+            StatusCode::UNAUTHORIZED
+        );
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        assert_eq!(
+            status(
+                control
+                    .access
+                    .authorize_headers(API, &headers, Action::ReadState)
+            ),
+            StatusCode::UNAUTHORIZED
+        );
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert_eq!(
+            control
+                .access
+                .authorize_headers(API, &headers, Action::Flatten)
+                .unwrap(),
+            Role::Admin
+        );
+    }
+
+    #[test]
+    fn test_roles_limit_commands() {
+        let (emergency, _rx) = emergency_channel();
+        let control = ControlApi {
+            emergency,
+            access: AccessPolicy::parse("viewer:dash,operator:ops"),
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer dash".parse().unwrap());
+        assert!(control
+            .access
+            .authorize_headers(API, &headers, Action::ReadState)
+            .is_ok());
+        assert_eq!(
+            control
+                .access
+                .authorize_headers(API, &headers, Action::Pause)
+                .unwrap_err()
+                .into_response()
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+
+        headers.insert(header::AUTHORIZATION, "Bearer ops".parse().unwrap());
+        assert!(control
+            .access
+            .authorize_headers(API, &headers, Action::CancelOrders)
+            .is_ok());
+        assert_eq!(
+            control
+                .access
+                .authorize_headers(API, &headers, Action::Flatten)
+                .unwrap_err()
+                .into_response()
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_outcome_status_codes() {
+        let ok = run_command(async { Ok(vec![1, 2]) }, StatusCode::CONFLICT).await;
+        assert_eq!(ok.status(), StatusCode::OK);
+
+        let failed = run_command(
+            async { Err::<(), _>("Position 7 not found".to_string()) },
+            StatusCode::CONFLICT,
+        )
+        .await;
+        assert_eq!(failed.status(), StatusCode::CONFLICT);
+    }
+}
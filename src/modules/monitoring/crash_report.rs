@@ -0,0 +1,358 @@
+//! Crash bundles for post-mortems of unattended crashes.
+//!
+//! `CrashReporter` keeps the last `CRASH_EVENT_BUFFER` log events (through a
+//! `tracing` layer) and a snapshot of the bot state that the trading loop
+//! refreshes every tick. On a panic (via the panic hook) or a fatal error
+//! returned from the main loop it writes `CRASH_DIR/crash-<timestamp>.json`
+//! containing the reason, backtrace, open positions, breaker state, config
+//! fingerprint and recent events, then optionally alerts on Telegram
+//! (`CRASH_TELEGRAM_NOTIFY`, on by default when Telegram is configured).
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, TryLockError};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+use crate::error::Result;
+use crate::modules::notifications::TelegramNotifier;
+use crate::modules::security::redact_secrets;
+use crate::modules::trading::Position;
+
+/// Breaker / risk counters at the time of the crash
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BreakerSnapshot {
+    pub circuit_breaker: bool,
+    pub daily_pnl: f64,
+    pub daily_trades: u32,
+    pub consecutive_losses: u32,
+}
+
+/// Bot state refreshed by the trading loop
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrashState {
+    pub symbol: String,
+    /// Strategy version / config fingerprint
+    pub strategy_version: String,
+    pub dry_run: bool,
+    pub last_price: Option<f64>,
+    pub open_positions: Vec<Position>,
+    pub breakers: BreakerSnapshot,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Everything written to a crash file
+#[derive(Debug, Serialize)]
+pub struct CrashBundle {
+    pub reason: String,
+    pub occurred_at: DateTime<Utc>,
+    pub pid: u32,
+    pub backtrace: String,
+    /// `None` when the state lock was held by the crashing thread
+    pub state: Option<CrashState>,
+    pub recent_events: Vec<String>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    capacity: usize,
+    notify_telegram: bool,
+    events: Mutex<VecDeque<String>>,
+    state: Mutex<CrashState>,
+}
+
+/// Shared crash context (clones share the same buffers)
+#[derive(Clone)]
+pub struct CrashReporter {
+    inner: Arc<Inner>,
+}
+
+impl CrashReporter {
+    /// Create a reporter writing to `dir` and keeping `capacity` recent events
+    pub fn new(dir: impl Into<PathBuf>, capacity: usize, notify_telegram: bool) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                dir: dir.into(),
+                capacity: capacity.max(1),
+                notify_telegram,
+                events: Mutex::new(VecDeque::with_capacity(capacity.max(1))),
+                state: Mutex::new(CrashState::default()),
+            }),
+        }
+    }
+
+    /// Load from `CRASH_DIR`, `CRASH_EVENT_BUFFER` and `CRASH_TELEGRAM_NOTIFY`
+    pub fn from_env() -> Self {
+        Self::new(
+            env::var("CRASH_DIR").unwrap_or_else(|_| "data/crashes".to_string()),
+            env::var("CRASH_EVENT_BUFFER")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(200),
+            env::var("CRASH_TELEGRAM_NOTIFY")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(true),
+        )
+    }
+
+    /// `tracing` layer feeding the recent-events buffer
+    pub fn layer(&self) -> RecentEventsLayer {
+        RecentEventsLayer {
+            reporter: self.clone(),
+        }
+    }
+
+    /// Replace the state snapshot
+    pub fn update_state(&self, state: CrashState) {
+        let mut guard = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = state;
+    }
+
+    fn push_event(&self, line: String) {
+        let mut events = self.inner.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() == self.inner.capacity {
+            events.pop_front();
+        }
+        events.push_back(line);
+    }
+
+    /// Assemble a bundle without blocking on locks the crashing thread may hold
+    pub fn bundle(&self, reason: &str, backtrace: String) -> CrashBundle {
+        let state = match self.inner.state.try_lock() {
+            Ok(state) => Some(state.clone()),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner().clone()),
+            Err(TryLockError::WouldBlock) => None,
+        };
+        let recent_events = match self.inner.events.try_lock() {
+            Ok(events) => events.iter().cloned().collect(),
+            Err(TryLockError::Poisoned(poisoned)) => {
+                poisoned.into_inner().iter().cloned().collect()
+            }
+            Err(TryLockError::WouldBlock) => Vec::new(),
+        };
+
+        CrashBundle {
+            reason: reason.to_string(),
+            occurred_at: Utc::now(),
+            pid: std::process::id(),
+            backtrace,
+            state,
+            recent_events,
+        }
+    }
+
+    /// Write a bundle to `CRASH_DIR` and return its path
+    pub fn write_bundle(&self, reason: &str, backtrace: String) -> Result<PathBuf> {
+        let bundle = self.bundle(reason, backtrace);
+        fs::create_dir_all(&self.inner.dir)?;
+        let path = self.inner.dir.join(format!(
+            "crash-{}.json",
+            bundle.occurred_at.format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+        Ok(path)
+    }
+
+    /// Record a fatal error returned from the main loop and alert on Telegram
+    pub async fn report_fatal(&self, reason: &str) -> Option<PathBuf> {
+        let path = match self.write_bundle(reason, Backtrace::force_capture().to_string()) {
+            Ok(path) => path,
+            Err(err) => {
+                eprintln!("Failed to write crash bundle: {}", err);
+                return None;
+            }
+        };
+        self.notify(reason, &path).await;
+        Some(path)
+    }
+
+    /// Write a bundle for every panic (and alert), then run the previous hook
+    pub fn install_panic_hook(&self) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let reason = format!("panic: {}", info);
+            match reporter.write_bundle(&reason, Backtrace::force_capture().to_string()) {
+                Ok(path) => {
+                    eprintln!("Crash bundle written to {}", path.display());
+                    reporter.notify_blocking(&reason, &path);
+                }
+                Err(err) => eprintln!("Failed to write crash bundle: {}", err),
+            }
+            previous(info);
+        }));
+    }
+
+    async fn notify(&self, reason: &str, path: &Path) {
+        if !self.inner.notify_telegram {
+            return;
+        }
+        let Some(telegram) = TelegramNotifier::from_env() else {
+            return;
+        };
+        let open_positions = self
+            .inner
+            .state
+            .try_lock()
+            .map(|s| s.open_positions.len().to_string())
+            .unwrap_or_else(|_| "unknown".to_string());
+        let message = format!(
+            "💥 Bot crashed\n{}\nOpen positions: {}\nBundle: {}",
+            truncate(reason, 1000),
+            open_positions,
+            path.display()
+        );
+        if let Err(err) = telegram.send_message(&message).await {
+            eprintln!("Failed to send crash alert: {}", err);
+        }
+    }
+
+    /// Panic hooks may run inside the Tokio runtime, so the alert is sent from
+    /// a dedicated thread with its own runtime (bounded wait)
+    fn notify_blocking(&self, reason: &str, path: &Path) {
+        if !self.inner.notify_telegram {
+            return;
+        }
+        let reporter = self.clone();
+        let (reason, path) = (reason.to_string(), path.to_path_buf());
+        let sender = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build();
+            if let Ok(runtime) = runtime {
+                runtime.block_on(async {
+                    let _ = tokio::time::timeout(
+                        std::time::Duration::from_secs(15),
+                        reporter.notify(&reason, &path),
+                    )
+                    .await;
+                });
+            }
+        });
+        let _ = sender.join();
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars).collect();
+    out.push('…');
+    out
+}
+
+/// Formats events as `timestamp LEVEL target: message key=value ...`
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// `tracing` layer keeping the most recent events for crash bundles
+pub struct RecentEventsLayer {
+    reporter: CrashReporter,
+}
+
+impl<S: Subscriber> Layer<S> for RecentEventsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        let meta = event.metadata();
+        let line = format!(
+            "{} {} {}: {}{}",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            meta.level(),
+            meta.target(),
+            visitor.message,
+            visitor.fields
+        );
+        self.reporter.push_event(redact_secrets(&line).into_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::OrderSide;
+    use tempfile::TempDir;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_bundle_contains_state_and_recent_events() {
+        let dir = TempDir::new().unwrap();
+        let reporter = CrashReporter::new(dir.path(), 2, false);
+        reporter.update_state(CrashState {
+            symbol: "FCPO".to_string(),
+            strategy_version: "0.1.0-abcd1234".to_string(),
+            open_positions: vec![Position::new("42", "FCPO", OrderSide::Buy, 4850.0, 1.0)],
+            breakers: BreakerSnapshot {
+                circuit_breaker: true,
+                consecutive_losses: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let subscriber = tracing_subscriber::registry().with(reporter.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::warn!(position_id = 42, "second");
+            tracing::error!("third");
+        });
+
+        let path = reporter
+            .write_bundle("fatal: test", "bt".to_string())
+            .unwrap();
+        let bundle: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(bundle["reason"], "fatal: test");
+        assert_eq!(bundle["state"]["strategy_version"], "0.1.0-abcd1234");
+        assert_eq!(bundle["state"]["open_positions"][0]["id"], "42");
+        assert_eq!(bundle["state"]["breakers"]["consecutive_losses"], 3);
+
+        let events = bundle["recent_events"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].as_str().unwrap().contains("WARN"));
+        assert!(events[0]
+            .as_str()
+            .unwrap()
+            .contains("second position_id=42"));
+        assert!(events[1].as_str().unwrap().ends_with("third"));
+    }
+
+    #[test]
+    fn test_bundle_skips_state_held_by_crashing_thread() {
+        let dir = TempDir::new().unwrap();
+        let reporter = CrashReporter::new(dir.path(), 10, false);
+        let _guard = reporter.inner.state.lock().unwrap();
+
+        let bundle = reporter.bundle("panic", String::new());
+        assert!(bundle.state.is_none());
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("abcdef", 3), "abc…");
+    }
+}
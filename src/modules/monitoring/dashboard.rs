@@ -4,13 +4,15 @@
 //!
 //! ## Features
 //! - Live metrics display (balance, P&L, win rate)
-//! - Market data (FCPO price, RSI, sentiment)
+//! - Market data (FCPO price, RSI, sentiment and its providers)
 //! - Open positions overview
 //! - Trade history
 //! - Auto-refresh every second
+//! - Emergency cancel-all (`X`) / cancel-all + flatten (`F`) when wired to the bot
 //! - Graceful exit on Ctrl+C
 
 use crate::modules::monitoring::metrics::MetricsHandle;
+use crate::modules::trading::EmergencyHandle;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
@@ -32,6 +34,10 @@ pub struct Dashboard {
     metrics: MetricsHandle,
     terminal: Terminal<CrosstermBackend<Stdout>>,
     should_quit: bool,
+    /// Panic commands to the running bot (None: keys are disabled)
+    emergency: Option<EmergencyHandle>,
+    /// Result of the last emergency keystroke, shown in the footer
+    notice: Option<String>,
 }
 
 impl Dashboard {
@@ -48,15 +54,24 @@ impl Dashboard {
             metrics,
             terminal,
             should_quit: false,
+            emergency: None,
+            notice: None,
         })
     }
 
+    /// Enable the emergency cancel-all keys for an in-process bot
+    pub fn with_emergency(mut self, emergency: EmergencyHandle) -> Self {
+        self.emergency = Some(emergency);
+        self
+    }
+
     /// Run the dashboard (blocking)
     pub fn run(&mut self) -> io::Result<()> {
         loop {
             // Draw UI - extract metrics snapshot before draw to avoid borrow conflict
             let metrics_snapshot = self.metrics.snapshot();
-            self.terminal.draw(|f| render_ui(f, &metrics_snapshot))?;
+            let notice = self.notice.as_deref();
+            self.terminal.draw(|f| render_ui(f, &metrics_snapshot, notice))?;
 
             // Handle events with timeout
             if event::poll(Duration::from_millis(1000))? {
@@ -69,6 +84,8 @@ impl Dashboard {
                             KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                                 self.should_quit = true;
                             }
+                            KeyCode::Char('x') | KeyCode::Char('X') => self.request_cancel_all(false),
+                            KeyCode::Char('f') | KeyCode::Char('F') => self.request_cancel_all(true),
                             _ => {}
                         }
                     }
@@ -83,11 +100,27 @@ impl Dashboard {
         Ok(())
     }
 
+    fn request_cancel_all(&mut self, flatten: bool) {
+        let what = if flatten { "Cancel-all + flatten" } else { "Cancel-all" };
+        self.notice = Some(match &self.emergency {
+            Some(emergency) if emergency.request_cancel_all(flatten) => {
+                format!("{} sent to the bot", what)
+            }
+            Some(_) => format!("{} not sent: bot is busy or stopped", what),
+            None => format!("{} unavailable: dashboard not attached to a bot", what),
+        });
+    }
 }
 
 /// Render the UI (standalone function to avoid borrow conflicts)
-fn render_ui(frame: &mut ratatui::Frame, metrics: &crate::modules::monitoring::metrics::BotMetrics) {
+fn render_ui(
+    frame: &mut ratatui::Frame,
+    metrics: &crate::modules::monitoring::metrics::BotMetrics,
+    notice: Option<&str>,
+) {
     let size = frame.size();
+    // Market data grows by one line per sentiment provider
+    let market_height = 5 + metrics.sentiment_providers.len() as u16;
 
     // Create main layout
     let chunks = Layout::default()
@@ -95,7 +128,7 @@ fn render_ui(frame: &mut ratatui::Frame, metrics: &crate::modules::monitoring::m
         .constraints([
             Constraint::Length(3),  // Header
             Constraint::Length(5),  // Account info
-            Constraint::Length(5),  // Market data
+            Constraint::Length(market_height), // Market data
             Constraint::Min(6),     // Positions
             Constraint::Length(4),  // Stats
             Constraint::Length(1),  // Footer
@@ -108,7 +141,7 @@ fn render_ui(frame: &mut ratatui::Frame, metrics: &crate::modules::monitoring::m
     render_market(frame, chunks[2], metrics);
     render_positions(frame, chunks[3], metrics);
     render_stats(frame, chunks[4], metrics);
-    render_footer(frame, chunks[5]);
+    render_footer(frame, chunks[5], notice);
 }
 
 /// Render header
@@ -162,7 +195,7 @@ fn render_account(frame: &mut ratatui::Frame, area: Rect, metrics: &crate::modul
         Line::from(vec![
             Span::styled("Balance:    ", Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("${:.2}", metrics.current_balance),
+                format!("${:.2}", metrics.current_balance.to_f64()),
                 Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
             ),
             Span::raw(" "),
@@ -238,7 +271,7 @@ fn render_market(frame: &mut ratatui::Frame, area: Rect, metrics: &crate::module
         Color::Gray
     };
 
-    let text = vec![
+    let mut text = vec![
         Line::from(vec![
             Span::styled("FCPO Price:  ", Style::default().fg(Color::Gray)),
             Span::styled(
@@ -261,9 +294,34 @@ fn render_market(frame: &mut ratatui::Frame, area: Rect, metrics: &crate::module
                     .fg(sentiment_color)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled(" (Perplexity)", Style::default().fg(Color::DarkGray)),
         ]),
     ];
+    // One line per sentiment provider: score and share of the blend
+    for reading in &metrics.sentiment_providers {
+        let detail = match reading.score {
+            Some(score) => format!(
+                "{:+} ({:.0}%, conf {:.2})",
+                score,
+                reading.share * 100.0,
+                reading.confidence
+            ),
+            None => "failed".to_string(),
+        };
+        let color = if reading.score.is_none() {
+            Color::Red
+        } else if reading.share > 0.0 {
+            Color::White
+        } else {
+            Color::DarkGray
+        };
+        text.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<11}", reading.provider),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(detail, Style::default().fg(color)),
+        ]));
+    }
 
     let market = Paragraph::new(text)
         .block(
@@ -397,15 +455,25 @@ fn render_stats(frame: &mut ratatui::Frame, area: Rect, metrics: &crate::modules
 }
 
 /// Render footer with controls
-fn render_footer(frame: &mut ratatui::Frame, area: Rect) {
-    let footer = Paragraph::new(Line::from(vec![
-        Span::styled("Press ", Style::default().fg(Color::Gray)),
-        Span::styled("Q", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-        Span::styled(" or ", Style::default().fg(Color::Gray)),
-        Span::styled("Ctrl+C", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-        Span::styled(" to quit", Style::default().fg(Color::Gray)),
-    ]))
-    .alignment(Alignment::Center);
+fn render_footer(frame: &mut ratatui::Frame, area: Rect, notice: Option<&str>) {
+    let line = match notice {
+        Some(notice) => Line::from(Span::styled(
+            notice.to_string(),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        None => Line::from(vec![
+            Span::styled("Press ", Style::default().fg(Color::Gray)),
+            Span::styled("Q", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(" or ", Style::default().fg(Color::Gray)),
+            Span::styled("Ctrl+C", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(" to quit, ", Style::default().fg(Color::Gray)),
+            Span::styled("X", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::styled(" cancel all orders, ", Style::default().fg(Color::Gray)),
+            Span::styled("F", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::styled(" cancel all + flatten", Style::default().fg(Color::Gray)),
+        ]),
+    };
+    let footer = Paragraph::new(line).alignment(Alignment::Center);
 
     frame.render_widget(footer, area);
 }
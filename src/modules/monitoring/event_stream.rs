@@ -0,0 +1,187 @@
+//! WebSocket stream of `MarketEvent`s for external UIs.
+//!
+//! Served alongside `/metrics` when `EVENT_STREAM_ENABLED` is set:
+//! - `GET /events/ws` — upgrade to a WebSocket; every event is sent as one
+//!   JSON text frame tagged with its type, e.g.
+//!   `{"type":"PriceTick","symbol_id":1,"symbol":"FCPO","bid":...}`
+//! - `?events=PriceTick,Alert` picks the event types (default: `PriceTick`,
+//!   `BarClosed`, `OrderFilled`, `Alert`; `all` for everything)
+//!
+//! The stream needs `EVENT_STREAM_TOKEN` or `ACCESS_TOKENS` and stays off
+//! without them. Clients send one of those tokens (any role, viewer is
+//! enough) as `Authorization: Bearer <token>` or, since browsers cannot set
+//! headers on a WebSocket, `?token=<token>`. A client that falls behind loses
+//! events rather than slowing down the trading loop.
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::modules::security::{bearer_token, AccessPolicy, Action, Role};
+use crate::modules::trading::{EventChannelHandle, EventFilter, EventType};
+
+/// Event types streamed when the client does not ask for specific ones
+pub const DEFAULT_STREAM_EVENTS: [EventType; 4] = [
+    EventType::PriceTick,
+    EventType::BarClosed,
+    EventType::OrderFilled,
+    EventType::Alert,
+];
+
+/// State behind the WebSocket endpoint
+#[derive(Clone)]
+pub struct EventStream {
+    pub events: EventChannelHandle,
+    /// Accepted tokens (header or `?token=`)
+    pub access: AccessPolicy,
+}
+
+impl EventStream {
+    /// Build from `EVENT_STREAM_ENABLED`, `EVENT_STREAM_TOKEN` and
+    /// `ACCESS_TOKENS`; `None` when disabled or when no token is configured
+    pub fn from_env(events: EventChannelHandle) -> Option<Self> {
+        let enabled = matches!(
+            std::env::var("EVENT_STREAM_ENABLED").as_deref(),
+            Ok("true") | Ok("1") | Ok("yes")
+        );
+        if !enabled {
+            return None;
+        }
+        let access = AccessPolicy::from_env()
+            .with_token(std::env::var("EVENT_STREAM_TOKEN").ok(), Role::Admin);
+        if access.is_empty() {
+            warn!("EVENT_STREAM_ENABLED is set but neither EVENT_STREAM_TOKEN nor ACCESS_TOKENS is set; event stream disabled");
+            return None;
+        }
+        Some(Self { events, access })
+    }
+
+    fn authorized(&self, headers: &HeaderMap, query_token: Option<&str>) -> bool {
+        let from_header = bearer_token(
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok()),
+        );
+        self.access
+            .authorize(
+                "event_stream",
+                from_header.or(query_token),
+                Action::ReadState,
+            )
+            .is_ok()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamParams {
+    events: Option<String>,
+    token: Option<String>,
+}
+
+/// Event types from `?events=`; an empty list means all types
+fn parse_event_types(spec: Option<&str>) -> std::result::Result<Vec<EventType>, String> {
+    match spec.map(str::trim) {
+        None | Some("") => Ok(DEFAULT_STREAM_EVENTS.to_vec()),
+        Some(s) if s.eq_ignore_ascii_case("all") => Ok(Vec::new()),
+        Some(s) => s.split(',').map(str::parse).collect(),
+    }
+}
+
+/// Router serving `/events/ws`
+pub fn event_stream_router(stream: EventStream) -> Router {
+    Router::new()
+        .route("/events/ws", get(ws_handler))
+        .with_state(stream)
+}
+
+async fn ws_handler(
+    State(stream): State<EventStream>,
+    Query(params): Query<StreamParams>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !stream.authorized(&headers, params.token.as_deref()) {
+        return (StatusCode::UNAUTHORIZED, "Missing or invalid token").into_response();
+    }
+    let event_types = match parse_event_types(params.events.as_deref()) {
+        Ok(types) => types,
+        Err(err) => return (StatusCode::BAD_REQUEST, err).into_response(),
+    };
+    ws.on_upgrade(move |socket| forward_events(socket, stream.events, event_types))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    events: EventChannelHandle,
+    event_types: Vec<EventType>,
+) {
+    let (id, mut rx) = events
+        .subscribe(EventFilter::event_types(event_types))
+        .await;
+    debug!("Event stream client {} connected", id);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let Some(event) = event else { break };
+                let text = match serde_json::to_string(&event) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        warn!("Failed to serialize event for the stream: {}", err);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                // Client messages are ignored; pings are answered by the socket
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    events.unsubscribe(id).await;
+    debug!("Event stream client {} disconnected", id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_types() {
+        assert_eq!(
+            parse_event_types(None).unwrap(),
+            DEFAULT_STREAM_EVENTS.to_vec()
+        );
+        assert!(parse_event_types(Some("all")).unwrap().is_empty());
+        assert_eq!(
+            parse_event_types(Some("Alert, order_filled")).unwrap(),
+            vec![EventType::Alert, EventType::OrderFilled]
+        );
+        assert!(parse_event_types(Some("Alert,Trades")).is_err());
+    }
+
+    #[test]
+    fn test_token_from_header_or_query() {
+        let stream = EventStream {
+            events: EventChannelHandle::new(10),
+            access: AccessPolicy::parse("viewer:secret"),
+        };
+        let mut headers = HeaderMap::new();
+        assert!(!stream.authorized(&headers, None));
+        assert!(!stream.authorized(&headers, Some("wrong")));
+        assert!(stream.authorized(&headers, Some("secret")));
+
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(stream.authorized(&headers, None));
+    }
+}
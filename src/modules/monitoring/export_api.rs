@@ -0,0 +1,315 @@
+//! HTTP export endpoints for trades, daily stats and the trade audit log.
+//!
+//! Served alongside `/metrics` when `EXPORT_API_ENABLED` is set:
+//! - `GET /export/trades.csv` / `/export/trades.json` — closed trades
+//! - `GET /export/daily_stats.csv` / `/export/daily_stats.json`
+//! - `GET /export/audit.csv` / `/export/audit.json` — CSV trade log (OPEN/CLOSE events)
+//!
+//! Requests must send `Authorization: Bearer <token>` with `EXPORT_API_TOKEN`
+//! or any `ACCESS_TOKENS` role (viewer and up); with neither set the endpoints
+//! stay off.
+
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::error::{BotError, Result};
+use crate::modules::security::{AccessPolicy, Action, Role};
+use crate::modules::trading::PositionDatabase;
+
+/// Name the access checks are audited under
+const API: &str = "export_api";
+
+/// Data sources behind the export endpoints
+#[derive(Clone)]
+pub struct ExportSources {
+    /// Trade database; trades/stats endpoints return 503 without it
+    pub db: Option<PositionDatabase>,
+    /// CSV trade log written by the bot (`TRADE_LOG_PATH`)
+    pub trade_log_path: PathBuf,
+    /// Accepted bearer tokens and their roles
+    pub access: AccessPolicy,
+}
+
+impl ExportSources {
+    /// Build from `TRADE_LOG_PATH`, `EXPORT_API_TOKEN` and `ACCESS_TOKENS`;
+    /// `None` when no token is configured
+    pub fn from_env(db: Option<PositionDatabase>) -> Option<Self> {
+        let access = AccessPolicy::from_env()
+            .with_token(std::env::var("EXPORT_API_TOKEN").ok(), Role::Admin);
+        if access.is_empty() {
+            warn!("EXPORT_API_ENABLED is set but neither EXPORT_API_TOKEN nor ACCESS_TOKENS is set; export API disabled");
+            return None;
+        }
+        Some(Self {
+            db,
+            trade_log_path: std::env::var("TRADE_LOG_PATH")
+                .unwrap_or_else(|_| "data/trade_log.csv".to_string())
+                .into(),
+            access,
+        })
+    }
+
+    fn db(&self) -> Result<&PositionDatabase> {
+        self.db
+            .as_ref()
+            .ok_or_else(|| BotError::Config("Persistence database is not available".to_string()))
+    }
+
+    /// Render a dataset in the requested format
+    pub fn render(&self, dataset: Dataset, format: ExportFormat) -> Result<String> {
+        match (dataset, format) {
+            (Dataset::Trades, ExportFormat::Csv) => {
+                let mut buf = Vec::new();
+                self.db()?.write_closed_trades_csv(&mut buf)?;
+                Ok(String::from_utf8_lossy(&buf).into_owned())
+            }
+            (Dataset::Trades, ExportFormat::Json) => self.db()?.closed_trades_json(),
+            (Dataset::DailyStats, ExportFormat::Csv) => {
+                let mut buf = Vec::new();
+                self.db()?.write_daily_stats_csv(&mut buf)?;
+                Ok(String::from_utf8_lossy(&buf).into_owned())
+            }
+            (Dataset::DailyStats, ExportFormat::Json) => to_json(&self.db()?.get_all_daily_stats()?),
+            (Dataset::Audit, ExportFormat::Csv) => self.read_trade_log(),
+            (Dataset::Audit, ExportFormat::Json) => {
+                to_json(&csv_to_records(&self.read_trade_log()?)?)
+            }
+        }
+    }
+
+    fn read_trade_log(&self) -> Result<String> {
+        std::fs::read_to_string(&self.trade_log_path).map_err(|e| {
+            BotError::Config(format!(
+                "Failed to read trade log {}: {}",
+                self.trade_log_path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Exportable datasets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dataset {
+    Trades,
+    DailyStats,
+    Audit,
+}
+
+/// Export encodings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Parse an export file name such as `trades.csv` or `daily_stats.json`
+pub fn parse_export_name(name: &str) -> Option<(Dataset, ExportFormat)> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    let dataset = match stem {
+        "trades" => Dataset::Trades,
+        "daily_stats" => Dataset::DailyStats,
+        "audit" => Dataset::Audit,
+        _ => return None,
+    };
+    let format = match ext {
+        "csv" => ExportFormat::Csv,
+        "json" => ExportFormat::Json,
+        _ => return None,
+    };
+    Some((dataset, format))
+}
+
+/// Whether the export endpoints should be served (`EXPORT_API_ENABLED`)
+pub fn export_api_enabled() -> bool {
+    matches!(
+        std::env::var("EXPORT_API_ENABLED").as_deref(),
+        Ok("true") | Ok("1") | Ok("yes")
+    )
+}
+
+/// Router serving `/export/:file`
+pub fn export_router(sources: ExportSources) -> Router {
+    Router::new()
+        .route("/export/:file", get(export_handler))
+        .with_state(sources)
+}
+
+async fn export_handler(
+    State(sources): State<ExportSources>,
+    UrlPath(file): UrlPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(denied) = sources
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    let Some((dataset, format)) = parse_export_name(&file) else {
+        return (StatusCode::NOT_FOUND, "Unknown export").into_response();
+    };
+
+    let rendered = tokio::task::spawn_blocking(move || sources.render(dataset, format)).await;
+    match rendered {
+        Ok(Ok(body)) => (
+            [
+                (header::CONTENT_TYPE, format.content_type().to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", file),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        Ok(Err(err)) => {
+            warn!("Export {} failed: {}", file, err);
+            (StatusCode::SERVICE_UNAVAILABLE, err.to_string()).into_response()
+        }
+        Err(err) => {
+            warn!("Export {} task failed: {}", file, err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| BotError::Config(format!("Failed to serialize JSON: {}", e)))
+}
+
+/// Turn a headered CSV into one JSON object per row (all values as strings)
+fn csv_to_records(csv: &str) -> Result<Vec<serde_json::Map<String, serde_json::Value>>> {
+    let parse_err = |e: csv::Error| BotError::Config(format!("Failed to parse trade log: {}", e));
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(csv.as_bytes());
+    let columns = reader.headers().map_err(parse_err)?.clone();
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(parse_err)?;
+            Ok(columns
+                .iter()
+                .zip(record.iter())
+                .map(|(col, value)| (col.to_string(), serde_json::Value::String(value.to_string())))
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::{CloseReason, OrderSide, Position};
+    use tempfile::TempDir;
+
+    fn sources(dir: &TempDir) -> ExportSources {
+        let db = PositionDatabase::new(dir.path().join("test.db")).unwrap();
+        db.upsert_position(&Position::new("42", "FCPO", OrderSide::Buy, 4850.0, 1.0))
+            .unwrap();
+        db.close_position("42", 4870.0, CloseReason::TakeProfit).unwrap();
+
+        let trade_log_path = dir.path().join("trade_log.csv");
+        std::fs::write(
+            &trade_log_path,
+            "timestamp,event,position_id,note\n\
+             2024-03-04T08:00:00+00:00,OPEN,42,\"RSI 28, sentiment +40\"\n",
+        )
+        .unwrap();
+
+        ExportSources {
+            db: Some(db),
+            trade_log_path,
+            access: AccessPolicy::parse("viewer:secret"),
+        }
+    }
+
+    #[test]
+    fn test_parse_export_name() {
+        assert_eq!(
+            parse_export_name("trades.csv"),
+            Some((Dataset::Trades, ExportFormat::Csv))
+        );
+        assert_eq!(
+            parse_export_name("daily_stats.json"),
+            Some((Dataset::DailyStats, ExportFormat::Json))
+        );
+        assert_eq!(parse_export_name("audit.xml"), None);
+        assert_eq!(parse_export_name("positions.csv"), None);
+    }
+
+    #[test]
+    fn test_render_datasets() {
+        let dir = TempDir::new().unwrap();
+        let sources = sources(&dir);
+
+        let trades = sources.render(Dataset::Trades, ExportFormat::Csv).unwrap();
+        assert!(trades.starts_with("position_id,broker_id"));
+        assert!(trades.contains("42,42,FCPO"));
+
+        let trades_json = sources.render(Dataset::Trades, ExportFormat::Json).unwrap();
+        assert!(trades_json.contains("\"position_id\": \"42\""));
+
+        let audit: serde_json::Value =
+            serde_json::from_str(&sources.render(Dataset::Audit, ExportFormat::Json).unwrap())
+                .unwrap();
+        assert_eq!(audit[0]["event"], "OPEN");
+        assert_eq!(audit[0]["position_id"], "42");
+        // Quoted commas stay inside their field
+        assert_eq!(audit[0]["note"], "RSI 28, sentiment +40");
+    }
+
+    #[test]
+    fn test_render_without_db_fails() {
+        let dir = TempDir::new().unwrap();
+        let sources = ExportSources {
+            db: None,
+            ..sources(&dir)
+        };
+        assert!(sources.render(Dataset::DailyStats, ExportFormat::Csv).is_err());
+        assert!(sources.render(Dataset::Audit, ExportFormat::Csv).is_ok());
+    }
+
+    #[test]
+    fn test_bearer_token_required() {
+        let dir = TempDir::new().unwrap();
+        let sources = sources(&dir);
+
+        let mut headers = HeaderMap::new();
+        assert!(sources
+            .access
+            .authorize_headers(API, &headers, Action::ReadState)
+            .is_err());
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        assert!(sources
+            .access
+            .authorize_headers(API, &headers, Action::ReadState)
+            .is_err());
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert_eq!(
+            sources
+                .access
+                .authorize_headers(API, &headers, Action::ReadState)
+                .ok(),
+            Some(Role::Viewer)
+        );
+    }
+}
@@ -0,0 +1,479 @@
+//! Grafana JSON datasource endpoints
+//!
+//! Served alongside `/metrics` when `GRAFANA_API_ENABLED` is set, for the
+//! Grafana "JSON" datasource plugin (URL `http://<host>:<METRICS_PORT>/grafana`):
+//! - `GET /grafana` — connection test
+//! - `POST /grafana/search` / `POST /grafana/metrics` — available targets
+//! - `POST /grafana/query` — time series / tables for the requested range
+//!
+//! Targets come from the SQLite database: `equity` (initial balance plus
+//! cumulative realized P&L), `realized_pnl` and `daily_pnl` from closed
+//! trades, `close` / `rsi` / `sentiment` from the per-candle indicator
+//! samples, and `trades` as a table. Requests must send
+//! `Authorization: Bearer <token>` (a custom header in the datasource settings)
+//! with `GRAFANA_API_TOKEN` or any `ACCESS_TOKENS` role; with neither set the
+//! endpoints stay off.
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::error::Result;
+use crate::modules::security::{AccessPolicy, Action, Role};
+use crate::modules::trading::{ClosedTradeRecord, PositionDatabase};
+
+/// Name the access checks are audited under
+const API: &str = "grafana_api";
+
+/// Targets offered to Grafana
+pub const TARGETS: &[&str] = &[
+    "equity",
+    "realized_pnl",
+    "daily_pnl",
+    "close",
+    "rsi",
+    "sentiment",
+    "trades",
+];
+
+/// State behind the Grafana endpoints
+#[derive(Clone)]
+pub struct GrafanaApi {
+    pub db: PositionDatabase,
+    /// Symbol whose indicator samples are served
+    pub symbol: String,
+    /// Starting point of the equity curve
+    pub initial_balance: f64,
+    /// Accepted bearer tokens and their roles
+    pub access: AccessPolicy,
+}
+
+impl GrafanaApi {
+    /// Build from `GRAFANA_API_ENABLED`, `GRAFANA_API_TOKEN` and
+    /// `ACCESS_TOKENS`; `None` when disabled, without a token or without a
+    /// database
+    pub fn from_env(
+        db: Option<PositionDatabase>,
+        symbol: &str,
+        initial_balance: f64,
+    ) -> Option<Self> {
+        let enabled = matches!(
+            std::env::var("GRAFANA_API_ENABLED").as_deref(),
+            Ok("true") | Ok("1") | Ok("yes")
+        );
+        if !enabled {
+            return None;
+        }
+        let access = AccessPolicy::from_env()
+            .with_token(std::env::var("GRAFANA_API_TOKEN").ok(), Role::Admin);
+        if access.is_empty() {
+            warn!("GRAFANA_API_ENABLED is set but neither GRAFANA_API_TOKEN nor ACCESS_TOKENS is set; Grafana API disabled");
+            return None;
+        }
+        let Some(db) = db else {
+            warn!("GRAFANA_API_ENABLED is set but the persistence database is unavailable; Grafana API disabled");
+            return None;
+        };
+        Some(Self {
+            db,
+            symbol: symbol.to_string(),
+            initial_balance,
+            access,
+        })
+    }
+
+    /// Answer a `/query` request: one entry per known, visible target
+    pub fn query(&self, request: &QueryRequest) -> Result<Vec<Value>> {
+        let (from, to) = (request.range.from, request.range.to);
+        let max_points = request.max_data_points.unwrap_or(0);
+        let needs_trades = request
+            .targets
+            .iter()
+            .any(|t| matches!(t.target.as_str(), "equity" | "realized_pnl" | "trades"));
+        let trades = if needs_trades {
+            self.db.get_closed_trades()?
+        } else {
+            Vec::new()
+        };
+
+        let mut results = Vec::new();
+        for target in request.targets.iter().filter(|t| !t.hide) {
+            let name = target.target.as_str();
+            let datapoints = match name {
+                "equity" => equity_points(&trades, self.initial_balance, from, to),
+                "realized_pnl" => trade_points(&trades, from, to, |t| t.realized_pnl),
+                "daily_pnl" => self
+                    .db
+                    .get_all_daily_stats()?
+                    .into_iter()
+                    .filter_map(|day| {
+                        let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?;
+                        let time = date.and_hms_opt(0, 0, 0)?.and_utc();
+                        (time >= from && time <= to).then_some((day.total_pnl, time))
+                    })
+                    .collect(),
+                "close" | "rsi" | "sentiment" => self
+                    .db
+                    .get_indicator_samples(&self.symbol, from, to)?
+                    .into_iter()
+                    .map(|s| {
+                        let value = match name {
+                            "close" => s.close,
+                            "rsi" => s.rsi,
+                            _ => s.sentiment as f64,
+                        };
+                        (value, s.recorded_at)
+                    })
+                    .collect(),
+                "trades" => {
+                    results.push(trades_table(&trades, from, to));
+                    continue;
+                }
+                other => {
+                    warn!("Grafana query for unknown target {:?}", other);
+                    continue;
+                }
+            };
+            results.push(json!({
+                "target": name,
+                "datapoints": downsample(datapoints, max_points)
+                    .into_iter()
+                    .map(|(value, time)| json!([value, time.timestamp_millis()]))
+                    .collect::<Vec<_>>(),
+            }));
+        }
+        Ok(results)
+    }
+}
+
+/// Body of a Grafana `/query` request (fields the bot does not use are ignored)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    pub range: QueryRange,
+    #[serde(default)]
+    pub targets: Vec<QueryTarget>,
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryTarget {
+    #[serde(default)]
+    pub target: String,
+    #[serde(default)]
+    pub hide: bool,
+}
+
+/// Body of a Grafana `/search` request
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchRequest {
+    #[serde(default)]
+    pub target: String,
+}
+
+fn closed_at(trade: &ClosedTradeRecord) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&trade.closed_at)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Equity after each trade closed in the range; trades before `from` still
+/// count towards the balance
+fn equity_points(
+    trades: &[ClosedTradeRecord],
+    initial_balance: f64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<(f64, DateTime<Utc>)> {
+    let mut equity = initial_balance;
+    let mut points = Vec::new();
+    for trade in trades {
+        let Some(time) = closed_at(trade) else {
+            continue;
+        };
+        if time > to {
+            break;
+        }
+        equity += trade.realized_pnl;
+        if time >= from {
+            points.push((equity, time));
+        }
+    }
+    points
+}
+
+fn trade_points(
+    trades: &[ClosedTradeRecord],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    value: impl Fn(&ClosedTradeRecord) -> f64,
+) -> Vec<(f64, DateTime<Utc>)> {
+    trades
+        .iter()
+        .filter_map(|t| closed_at(t).map(|time| (value(t), time)))
+        .filter(|(_, time)| *time >= from && *time <= to)
+        .collect()
+}
+
+fn trades_table(trades: &[ClosedTradeRecord], from: DateTime<Utc>, to: DateTime<Utc>) -> Value {
+    let rows: Vec<Value> = trades
+        .iter()
+        .filter_map(|t| closed_at(t).map(|time| (t, time)))
+        .filter(|(_, time)| *time >= from && *time <= to)
+        .map(|(t, time)| {
+            json!([
+                time.timestamp_millis(),
+                t.side,
+                t.entry_price,
+                t.exit_price,
+                t.volume,
+                t.realized_pnl,
+                t.close_reason,
+            ])
+        })
+        .collect();
+    json!({
+        "type": "table",
+        "columns": [
+            {"text": "Closed", "type": "time"},
+            {"text": "Side", "type": "string"},
+            {"text": "Entry", "type": "number"},
+            {"text": "Exit", "type": "number"},
+            {"text": "Volume", "type": "number"},
+            {"text": "P&L", "type": "number"},
+            {"text": "Reason", "type": "string"},
+        ],
+        "rows": rows,
+    })
+}
+
+/// Keep at most `max_points` evenly spaced points (always including the
+/// last one); 0 keeps everything
+fn downsample<T>(points: Vec<T>, max_points: usize) -> Vec<T> {
+    if max_points == 0 || points.len() <= max_points {
+        return points;
+    }
+    let step = points.len().div_ceil(max_points);
+    let last = points.len() - 1;
+    points
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| (last - i) % step == 0)
+        .map(|(_, p)| p)
+        .collect()
+}
+
+/// Router serving `/grafana`, `/grafana/search`, `/grafana/metrics` and `/grafana/query`
+pub fn grafana_router(api: GrafanaApi) -> Router {
+    Router::new()
+        .route("/grafana", get(health_handler))
+        .route("/grafana/", get(health_handler))
+        .route("/grafana/search", post(search_handler))
+        .route("/grafana/metrics", post(metrics_handler))
+        .route("/grafana/query", post(query_handler))
+        .with_state(api)
+}
+
+async fn health_handler(State(api): State<GrafanaApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = api
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    (StatusCode::OK, "OK").into_response()
+}
+
+async fn search_handler(
+    State(api): State<GrafanaApi>,
+    headers: HeaderMap,
+    body: Option<Json<SearchRequest>>,
+) -> Response {
+    if let Err(denied) = api
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    let prefix = body.map(|Json(b)| b.target).unwrap_or_default();
+    let names: Vec<&str> = TARGETS
+        .iter()
+        .copied()
+        .filter(|name| name.starts_with(prefix.trim()))
+        .collect();
+    Json(names).into_response()
+}
+
+async fn metrics_handler(State(api): State<GrafanaApi>, headers: HeaderMap) -> Response {
+    if let Err(denied) = api
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    let metrics: Vec<Value> = TARGETS
+        .iter()
+        .map(|name| json!({ "label": name, "value": name }))
+        .collect();
+    Json(metrics).into_response()
+}
+
+async fn query_handler(
+    State(api): State<GrafanaApi>,
+    headers: HeaderMap,
+    Json(request): Json<QueryRequest>,
+) -> Response {
+    if let Err(denied) = api
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    match tokio::task::spawn_blocking(move || api.query(&request)).await {
+        Ok(Ok(results)) => Json(results).into_response(),
+        Ok(Err(err)) => {
+            warn!("Grafana query failed: {}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, err.to_string()).into_response()
+        }
+        Err(err) => {
+            warn!("Grafana query task failed: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::{CloseReason, IndicatorSample, OrderSide, Position};
+    use axum::http::header;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn api(dir: &TempDir) -> GrafanaApi {
+        let db = PositionDatabase::new(dir.path().join("test.db")).unwrap();
+        db.upsert_position(&Position::new("1", "FCPO", OrderSide::Buy, 4850.0, 1.0))
+            .unwrap();
+        db.close_position("1", 4870.0, CloseReason::TakeProfit)
+            .unwrap();
+        db.upsert_position(&Position::new("2", "FCPO", OrderSide::Sell, 4870.0, 1.0))
+            .unwrap();
+        db.close_position("2", 4880.0, CloseReason::StopLoss)
+            .unwrap();
+        db.save_indicator_sample(&IndicatorSample {
+            symbol: "FCPO".to_string(),
+            recorded_at: Utc::now() - Duration::minutes(5),
+            close: 4860.0,
+            rsi: 28.5,
+            sentiment: -15,
+        })
+        .unwrap();
+
+        GrafanaApi {
+            db,
+            symbol: "FCPO".to_string(),
+            initial_balance: 10_000.0,
+            access: AccessPolicy::parse("viewer:secret"),
+        }
+    }
+
+    fn request(targets: &[&str]) -> QueryRequest {
+        serde_json::from_value(json!({
+            "range": {
+                "from": (Utc::now() - Duration::hours(1)).to_rfc3339(),
+                "to": (Utc::now() + Duration::hours(1)).to_rfc3339(),
+            },
+            "targets": targets
+                .iter()
+                .map(|t| json!({ "target": t, "refId": "A" }))
+                .collect::<Vec<_>>(),
+            "maxDataPoints": 500,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_equity_is_cumulative() {
+        let dir = TempDir::new().unwrap();
+        let api = api(&dir);
+        let trades = api.db.get_closed_trades().unwrap();
+        let pnl: Vec<f64> = trades.iter().map(|t| t.realized_pnl).collect();
+
+        let results = api.query(&request(&["equity"])).unwrap();
+        let points = results[0]["datapoints"].as_array().unwrap();
+        assert_eq!(results[0]["target"], "equity");
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0][0], 10_000.0 + pnl[0]);
+        assert_eq!(points[1][0], 10_000.0 + pnl[0] + pnl[1]);
+    }
+
+    #[test]
+    fn test_indicator_and_table_targets() {
+        let dir = TempDir::new().unwrap();
+        let api = api(&dir);
+
+        let results = api
+            .query(&request(&["rsi", "sentiment", "trades", "unknown"]))
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["datapoints"][0][0], 28.5);
+        assert_eq!(results[1]["datapoints"][0][0], -15.0);
+        assert_eq!(results[2]["type"], "table");
+        assert_eq!(results[2]["rows"].as_array().unwrap().len(), 2);
+        assert_eq!(results[2]["rows"][0][1], "Buy");
+    }
+
+    #[test]
+    fn test_range_excludes_older_points() {
+        let dir = TempDir::new().unwrap();
+        let api = api(&dir);
+        let mut request = request(&["close", "equity"]);
+        request.range.from = Utc::now() + Duration::minutes(30);
+
+        let results = api.query(&request).unwrap();
+        assert!(results[0]["datapoints"].as_array().unwrap().is_empty());
+        assert!(results[1]["datapoints"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_viewer_token_required() {
+        let dir = TempDir::new().unwrap();
+        let api = api(&dir);
+
+        let mut headers = HeaderMap::new();
+        assert!(api
+            .access
+            .authorize_headers(API, &headers, Action::ReadState)
+            .is_err());
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert_eq!(
+            api.access
+                .authorize_headers(API, &headers, Action::ReadState)
+                .ok(),
+            Some(Role::Viewer)
+        );
+    }
+
+    #[test]
+    fn test_downsample_keeps_last_point() {
+        let points: Vec<u32> = (0..10).collect();
+        assert_eq!(downsample(points.clone(), 0).len(), 10);
+        assert_eq!(downsample(points.clone(), 20).len(), 10);
+        let sampled = downsample(points, 4);
+        assert!(sampled.len() <= 4);
+        assert_eq!(sampled.last(), Some(&9));
+    }
+}
@@ -0,0 +1,503 @@
+//! gRPC control interface for programmatic integrations.
+//!
+//! The `BotControl` service (`proto/control.proto`) exposes the same
+//! operator commands as the HTTP control API with typed messages: status,
+//! open positions, pending orders, pause / resume, closing one position,
+//! cancel-all, a sentiment refresh and arming live trading. It listens on its own port when
+//! `GRPC_API_ENABLED` is set:
+//! - `GRPC_API_ADDR`: bind address (default `127.0.0.1:50051`)
+//! - `GRPC_API_TOKEN`: admin token; every call must carry the metadata
+//!   `authorization: Bearer <token>`. Role tokens from `ACCESS_TOKENS` are
+//!   accepted too: viewers may only read, operators may not flatten. Calls
+//!   without a known token fail with `UNAUTHENTICATED`, calls above the
+//!   caller's role with `PERMISSION_DENIED`.
+//!
+//! Other Rust tools drive the bot through [`ControlClient`], which adds the
+//! token to every call (see the `botctl` binary).
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tonic::codegen::InterceptedService;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint, Server};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::SentimentResult;
+use crate::modules::security::{
+    bearer_token, check_role, AccessDenied, AccessPolicy, Action, Role,
+};
+use crate::modules::trading::{
+    ArmingStatus, CancelAllReport, EmergencyHandle, Position, StatusReport, TrackedOrder,
+};
+
+/// Types and stubs generated from `proto/control.proto`
+pub mod proto {
+    tonic::include_proto!("palm_oil_bot.control");
+}
+
+use proto::bot_control_client::BotControlClient;
+use proto::bot_control_server::{BotControl, BotControlServer};
+use proto::{
+    ArmingReply, CancelAllReply, CancelAllRequest, ClosePositionRequest, ListOrdersReply,
+    ListOrdersRequest, ListPositionsReply, ListPositionsRequest, OrderInfo, PositionInfo,
+    RefreshSentimentRequest, SentimentReply, SetArmedRequest, SetPausedReply, SetPausedRequest,
+    StatusReply, StatusRequest,
+};
+
+/// Default bind address (localhost only)
+const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:50051";
+
+/// How long a call waits for the trading loop to answer
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// gRPC server settings
+#[derive(Debug, Clone)]
+pub struct GrpcApiConfig {
+    pub addr: SocketAddr,
+    /// Accepted bearer tokens and their roles
+    pub access: AccessPolicy,
+}
+
+impl GrpcApiConfig {
+    /// Load from `GRPC_API_ENABLED`, `GRPC_API_ADDR`, `GRPC_API_TOKEN` and
+    /// `ACCESS_TOKENS`; `None` when disabled or when no token is configured
+    pub fn from_env() -> Option<Self> {
+        let enabled = matches!(
+            std::env::var("GRPC_API_ENABLED").as_deref(),
+            Ok("true") | Ok("1") | Ok("yes")
+        );
+        if !enabled {
+            return None;
+        }
+        let access =
+            AccessPolicy::from_env().with_token(std::env::var("GRPC_API_TOKEN").ok(), Role::Admin);
+        if access.is_empty() {
+            warn!("GRPC_API_ENABLED is set but neither GRPC_API_TOKEN nor ACCESS_TOKENS is set; gRPC API disabled");
+            return None;
+        }
+        let addr = std::env::var("GRPC_API_ADDR").unwrap_or_else(|_| DEFAULT_GRPC_ADDR.to_string());
+        match addr.parse() {
+            Ok(addr) => Some(Self { addr, access }),
+            Err(_) => {
+                warn!("Invalid GRPC_API_ADDR {}; gRPC API disabled", addr);
+                None
+            }
+        }
+    }
+}
+
+/// `BotControl` implementation backed by the bot's command queue
+#[derive(Clone)]
+pub struct GrpcControl {
+    emergency: EmergencyHandle,
+}
+
+impl GrpcControl {
+    pub fn new(emergency: EmergencyHandle) -> Self {
+        Self { emergency }
+    }
+}
+
+/// Serve `BotControl` on `config.addr`, rejecting calls without a known token
+pub fn start_grpc_server(config: GrpcApiConfig, emergency: EmergencyHandle) -> JoinHandle<()> {
+    info!("Starting gRPC control server on {}", config.addr);
+    let access = config.access;
+    let service = BotControlServer::with_interceptor(GrpcControl::new(emergency), move |request| {
+        authenticate(&access, request)
+    });
+
+    tokio::spawn(async move {
+        if let Err(err) = Server::builder()
+            .add_service(service)
+            .serve(config.addr)
+            .await
+        {
+            warn!("gRPC control server stopped: {}", err);
+        }
+    })
+}
+
+/// Resolve the caller's role and attach it to the request for [`permit`]
+fn authenticate(
+    access: &AccessPolicy,
+    mut request: Request<()>,
+) -> std::result::Result<Request<()>, Status> {
+    let provided = bearer_token(
+        request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok()),
+    );
+    match provided.and_then(|token| access.role_of(token)) {
+        Some(role) => {
+            request.extensions_mut().insert(role);
+            Ok(request)
+        }
+        None => {
+            warn!(target: "audit", "[AUDIT] grpc_api call denied: unauthenticated");
+            Err(Status::unauthenticated(
+                AccessDenied::Unauthenticated.to_string(),
+            ))
+        }
+    }
+}
+
+/// Check the role attached by [`authenticate`] against `action`
+fn permit<T>(request: &Request<T>, action: Action) -> std::result::Result<(), Status> {
+    let Some(role) = request.extensions().get::<Role>().copied() else {
+        return Err(Status::unauthenticated(
+            AccessDenied::Unauthenticated.to_string(),
+        ));
+    };
+    check_role("grpc_api", role, action)
+        .map_err(|denied| Status::permission_denied(denied.to_string()))
+}
+
+/// Wait for the trading loop's answer
+async fn await_bot<T>(
+    command: impl std::future::Future<Output = std::result::Result<T, String>>,
+) -> std::result::Result<T, Status> {
+    match tokio::time::timeout(COMMAND_TIMEOUT, command).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => Err(Status::unavailable(err)),
+        Err(_) => Err(Status::deadline_exceeded(
+            "Command still running; check the bot logs",
+        )),
+    }
+}
+
+#[tonic::async_trait]
+impl BotControl for GrpcControl {
+    async fn get_status(
+        &self,
+        request: Request<StatusRequest>,
+    ) -> std::result::Result<Response<StatusReply>, Status> {
+        permit(&request, Action::ReadState)?;
+        let report = await_bot(self.emergency.status_report()).await?;
+        Ok(Response::new(report.into()))
+    }
+
+    async fn list_positions(
+        &self,
+        request: Request<ListPositionsRequest>,
+    ) -> std::result::Result<Response<ListPositionsReply>, Status> {
+        permit(&request, Action::ReadState)?;
+        let positions = await_bot(self.emergency.positions()).await?;
+        Ok(Response::new(ListPositionsReply {
+            positions: positions.iter().map(PositionInfo::from).collect(),
+        }))
+    }
+
+    async fn list_orders(
+        &self,
+        request: Request<ListOrdersRequest>,
+    ) -> std::result::Result<Response<ListOrdersReply>, Status> {
+        permit(&request, Action::ReadState)?;
+        let orders = await_bot(self.emergency.orders()).await?;
+        Ok(Response::new(ListOrdersReply {
+            orders: orders.iter().map(OrderInfo::from).collect(),
+        }))
+    }
+
+    async fn set_paused(
+        &self,
+        request: Request<SetPausedRequest>,
+    ) -> std::result::Result<Response<SetPausedReply>, Status> {
+        let paused = request.get_ref().paused;
+        permit(
+            &request,
+            if paused {
+                Action::Pause
+            } else {
+                Action::Resume
+            },
+        )?;
+        warn!(
+            "gRPC API: {} requested",
+            if paused { "pause" } else { "resume" }
+        );
+        if !self.emergency.request_pause(paused) {
+            return Err(Status::unavailable(
+                "Command not queued: trading loop busy or stopped",
+            ));
+        }
+        Ok(Response::new(SetPausedReply { paused }))
+    }
+
+    async fn close_position(
+        &self,
+        request: Request<ClosePositionRequest>,
+    ) -> std::result::Result<Response<PositionInfo>, Status> {
+        permit(&request, Action::ClosePosition)?;
+        let position_id = request.into_inner().position_id;
+        let positions = await_bot(self.emergency.positions()).await?;
+        if !positions.iter().any(|p| p.id == position_id) {
+            return Err(Status::not_found(format!(
+                "Position {} not found",
+                position_id
+            )));
+        }
+
+        warn!("gRPC API: close of position {} requested", position_id);
+        match tokio::time::timeout(COMMAND_TIMEOUT, self.emergency.close_position(&position_id))
+            .await
+        {
+            Ok(Ok(position)) => Ok(Response::new(PositionInfo::from(&position))),
+            Ok(Err(err)) => Err(Status::failed_precondition(err)),
+            Err(_) => Err(Status::deadline_exceeded(
+                "Close still running; check the bot logs",
+            )),
+        }
+    }
+
+    async fn cancel_all(
+        &self,
+        request: Request<CancelAllRequest>,
+    ) -> std::result::Result<Response<CancelAllReply>, Status> {
+        let flatten = request.get_ref().flatten;
+        let action = if flatten {
+            Action::Flatten
+        } else {
+            Action::CancelOrders
+        };
+        permit(&request, action)?;
+        warn!("gRPC API: cancel-all requested (flatten={})", flatten);
+        let report = await_bot(self.emergency.cancel_all(flatten)).await?;
+        Ok(Response::new(report.into()))
+    }
+
+    async fn refresh_sentiment(
+        &self,
+        request: Request<RefreshSentimentRequest>,
+    ) -> std::result::Result<Response<SentimentReply>, Status> {
+        permit(&request, Action::RefreshSentiment)?;
+        let sentiment = await_bot(self.emergency.refresh_sentiment()).await?;
+        Ok(Response::new(sentiment.into()))
+    }
+
+    async fn set_armed(
+        &self,
+        request: Request<SetArmedRequest>,
+    ) -> std::result::Result<Response<ArmingReply>, Status> {
+        let SetArmedRequest { armed, ttl_minutes } = request.get_ref().clone();
+        if armed {
+            permit(&request, Action::Arm)?;
+            warn!("gRPC API: arm requested (ttl={:?}m)", ttl_minutes);
+            match tokio::time::timeout(COMMAND_TIMEOUT, self.emergency.arm(ttl_minutes)).await {
+                Ok(Ok(status)) => Ok(Response::new(status.into())),
+                Ok(Err(err)) => Err(Status::failed_precondition(err)),
+                Err(_) => Err(Status::deadline_exceeded(
+                    "Command still running; check the bot logs",
+                )),
+            }
+        } else {
+            permit(&request, Action::Disarm)?;
+            warn!("gRPC API: disarm requested");
+            let status = await_bot(self.emergency.disarm()).await?;
+            Ok(Response::new(status.into()))
+        }
+    }
+}
+
+impl From<StatusReport> for StatusReply {
+    fn from(report: StatusReport) -> Self {
+        Self {
+            symbol: report.symbol,
+            dry_run: report.dry_run,
+            standby: report.standby,
+            paused: report.paused,
+            uptime_secs: report.uptime_secs,
+            open_positions: report.open_positions as u32,
+            daily_pnl: report.daily_pnl,
+            daily_trades: report.daily_trades,
+            last_signal: report.last_signal,
+            last_price: report.last_price,
+            feed_age_secs: report.feed_age_secs,
+            summary: report.summary,
+            arming_required: report.arming.required,
+            armed_until: report.arming.armed_until.map(|until| until.to_rfc3339()),
+        }
+    }
+}
+
+impl From<ArmingStatus> for ArmingReply {
+    fn from(status: ArmingStatus) -> Self {
+        Self {
+            required: status.required,
+            armed: status.armed,
+            armed_until: status.armed_until.map(|until| until.to_rfc3339()),
+        }
+    }
+}
+
+impl From<&Position> for PositionInfo {
+    fn from(position: &Position) -> Self {
+        Self {
+            id: position.id.clone(),
+            symbol: position.symbol.clone(),
+            side: position.side.to_string(),
+            entry_price: position.entry_price,
+            volume: position.volume,
+            current_pnl: position.current_pnl,
+            stop_loss: position.stop_loss,
+            take_profit: position.take_profit,
+            opened_at: position.opened_at.to_rfc3339(),
+            strategy_version: position.strategy_version.clone(),
+        }
+    }
+}
+
+impl From<&TrackedOrder> for OrderInfo {
+    fn from(order: &TrackedOrder) -> Self {
+        Self {
+            order_id: order.order_id,
+            symbol_id: order.symbol_id,
+            side: order.side.clone(),
+            volume: order.volume,
+            limit_price: order.limit_price,
+            stop_price: order.stop_price,
+            placed_at: order.placed_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<CancelAllReport> for CancelAllReply {
+    fn from(report: CancelAllReport) -> Self {
+        Self {
+            cancelled_orders: report.cancelled_orders,
+            closed_positions: report.closed_positions,
+            failures: report.failures,
+        }
+    }
+}
+
+impl From<SentimentResult> for SentimentReply {
+    fn from(sentiment: SentimentResult) -> Self {
+        Self {
+            score: sentiment.score,
+            confidence: sentiment.confidence,
+            source: sentiment.source,
+            timestamp: sentiment.timestamp.to_rfc3339(),
+        }
+    }
+}
+
+/// Adds `authorization: Bearer <token>` to every outgoing call
+#[derive(Clone)]
+pub struct BearerToken {
+    header: MetadataValue<Ascii>,
+}
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.header.clone());
+        Ok(request)
+    }
+}
+
+/// Typed client for a running bot
+pub type ControlClient = BotControlClient<InterceptedService<Channel, BearerToken>>;
+
+/// Connect to the bot's gRPC control server, e.g. `http://127.0.0.1:50051`
+pub async fn connect_control_client(endpoint: &str, token: &str) -> Result<ControlClient> {
+    let header = format!("Bearer {}", token)
+        .parse()
+        .map_err(|_| BotError::Config("gRPC token must be printable ASCII".to_string()))?;
+    let channel = Endpoint::from_shared(endpoint.to_string())
+        .map_err(|e| BotError::Config(format!("Invalid gRPC endpoint {}: {}", endpoint, e)))?
+        .connect()
+        .await
+        .map_err(|e| BotError::Other(format!("gRPC connect to {} failed: {}", endpoint, e)))?;
+    Ok(BotControlClient::with_interceptor(
+        channel,
+        BearerToken { header },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::{emergency_channel, EmergencyCommand, OrderSide};
+
+    #[test]
+    fn test_bearer_token_required() {
+        let access = AccessPolicy::default().with_token(Some("secret".to_string()), Role::Admin);
+        let request = Request::new(());
+        assert_eq!(
+            authenticate(&access, request).unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer wrong".parse().unwrap());
+        assert!(authenticate(&access, request).is_err());
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer secret".parse().unwrap());
+        let request = authenticate(&access, request).unwrap();
+        assert_eq!(request.extensions().get::<Role>(), Some(&Role::Admin));
+    }
+
+    #[tokio::test]
+    async fn test_viewer_cannot_pause() {
+        let (emergency, _rx) = emergency_channel();
+        let mut request = Request::new(SetPausedRequest { paused: true });
+        request.extensions_mut().insert(Role::Viewer);
+
+        let status = GrpcControl::new(emergency)
+            .set_paused(request)
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[test]
+    fn test_position_conversion() {
+        let mut position = Position::new("123", "FCPO", OrderSide::Sell, 4850.0, 1.0);
+        position.stop_loss = Some(4900.0);
+
+        let info = PositionInfo::from(&position);
+        assert_eq!(info.id, "123");
+        assert_eq!(info.side, "SELL");
+        assert_eq!(info.stop_loss, Some(4900.0));
+        assert_eq!(info.take_profit, None);
+    }
+
+    #[tokio::test]
+    async fn test_close_unknown_position_is_not_found() {
+        let (emergency, mut rx) = emergency_channel();
+        let worker = tokio::spawn(async move {
+            if let Some(EmergencyCommand::Positions { reply }) = rx.recv().await {
+                let _ = reply.send(vec![Position::new(
+                    "1",
+                    "FCPO",
+                    OrderSide::Buy,
+                    4800.0,
+                    1.0,
+                )]);
+            }
+        });
+
+        let mut request = Request::new(ClosePositionRequest {
+            position_id: "2".to_string(),
+        });
+        request.extensions_mut().insert(Role::Operator);
+        let status = GrpcControl::new(emergency)
+            .close_position(request)
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        worker.await.unwrap();
+    }
+}
@@ -0,0 +1,508 @@
+//! Ship structured logs to Loki or Elasticsearch over HTTP.
+//!
+//! `ShippingLayer` is a `tracing` layer that turns every event into a JSON
+//! record and hands it to a bounded queue without blocking the caller. A
+//! background task drains the queue in batches (`LOG_SHIP_BATCH_SIZE` records
+//! or every `LOG_SHIP_FLUSH_SECS`) and pushes them with a few retries. When the
+//! queue is full (endpoint down or slow) new records are dropped and counted
+//! rather than stalling the trading loop. Messages and string fields pass
+//! through the secret redactor before they are queued.
+//!
+//! Configuration:
+//! - `LOG_SHIP_BACKEND`: `loki` or `elasticsearch` (unset disables shipping)
+//! - `LOG_SHIP_URL`: base URL, e.g. `http://loki:3100` or `https://es:9200`
+//! - `LOG_SHIP_TOKEN` (bearer) or `LOG_SHIP_USERNAME` / `LOG_SHIP_PASSWORD` (basic)
+//! - `LOG_SHIP_LEVEL`: most verbose level shipped (default `info`)
+//! - `LOG_SHIP_LABELS`: extra Loki labels / ES fields, `key=value,...`
+//! - `LOG_SHIP_INDEX`: Elasticsearch index prefix (default `palm-oil-bot`)
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{interval, sleep, Duration, MissedTickBehavior};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+use crate::modules::security::redact_secrets;
+
+/// Targets never shipped: the HTTP stack used for shipping would otherwise log about itself
+const IGNORED_TARGET_PREFIXES: &[&str] = &["reqwest", "hyper", "h2", "rustls", "tokio_util"];
+
+/// Push attempts per batch before it is dropped
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Log aggregation backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogBackend {
+    Loki,
+    Elasticsearch,
+}
+
+/// Endpoint, batching and filtering settings
+#[derive(Debug, Clone)]
+pub struct LogShipperConfig {
+    pub backend: LogBackend,
+    pub url: String,
+    pub bearer_token: Option<String>,
+    pub basic_auth: Option<(String, String)>,
+    pub max_level: Level,
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+    /// Records buffered before new ones are dropped
+    pub queue_capacity: usize,
+    /// Static labels (Loki stream labels / extra ES document fields)
+    pub labels: BTreeMap<String, String>,
+    pub index_prefix: String,
+}
+
+impl LogShipperConfig {
+    /// Load from environment; `None` when `LOG_SHIP_BACKEND` or `LOG_SHIP_URL` is unset.
+    /// Runs before logging is initialised, so problems are reported on stderr.
+    pub fn from_env() -> Option<Self> {
+        let backend = match env::var("LOG_SHIP_BACKEND")
+            .ok()?
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "loki" => LogBackend::Loki,
+            "elasticsearch" | "elastic" | "es" => LogBackend::Elasticsearch,
+            "" | "none" | "off" => return None,
+            other => {
+                eprintln!(
+                    "Unknown LOG_SHIP_BACKEND '{}'; log shipping disabled",
+                    other
+                );
+                return None;
+            }
+        };
+        let Some(url) = non_empty("LOG_SHIP_URL") else {
+            eprintln!("LOG_SHIP_BACKEND is set but LOG_SHIP_URL is missing; log shipping disabled");
+            return None;
+        };
+
+        let mut labels = BTreeMap::from([
+            ("app".to_string(), "palm-oil-bot".to_string()),
+            ("host".to_string(), hostname()),
+        ]);
+        labels.extend(parse_labels(
+            &env::var("LOG_SHIP_LABELS").unwrap_or_default(),
+        ));
+
+        Some(Self {
+            backend,
+            url: url.trim_end_matches('/').to_string(),
+            bearer_token: non_empty("LOG_SHIP_TOKEN"),
+            basic_auth: non_empty("LOG_SHIP_USERNAME")
+                .map(|user| (user, env::var("LOG_SHIP_PASSWORD").unwrap_or_default())),
+            max_level: env::var("LOG_SHIP_LEVEL")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(Level::INFO),
+            batch_size: parse_or("LOG_SHIP_BATCH_SIZE", 100).max(1),
+            flush_interval: Duration::from_secs(parse_or("LOG_SHIP_FLUSH_SECS", 5).max(1) as u64),
+            queue_capacity: parse_or("LOG_SHIP_QUEUE", 10_000).max(1),
+            labels,
+            index_prefix: non_empty("LOG_SHIP_INDEX").unwrap_or_else(|| "palm-oil-bot".to_string()),
+        })
+    }
+}
+
+fn non_empty(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn parse_or(key: &str, default: usize) -> usize {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+fn hostname() -> String {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Parse `key=value,key2=value2`
+pub fn parse_labels(raw: &str) -> BTreeMap<String, String> {
+    raw.split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .filter(|(k, v)| !k.is_empty() && !v.is_empty())
+        .collect()
+}
+
+/// One structured log event
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    pub fields: Map<String, Value>,
+}
+
+impl LogRecord {
+    fn to_json(&self) -> Value {
+        let mut doc = self.fields.clone();
+        doc.insert("@timestamp".to_string(), json!(self.timestamp.to_rfc3339()));
+        doc.insert("level".to_string(), json!(self.level.as_str()));
+        doc.insert("target".to_string(), json!(self.target));
+        doc.insert("message".to_string(), json!(self.message));
+        Value::Object(doc)
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, json!(redact_secrets(value)));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, json!(redact_secrets(&format!("{:?}", value))));
+    }
+}
+
+/// `tracing` layer feeding the shipping queue
+pub struct ShippingLayer {
+    tx: mpsc::Sender<LogRecord>,
+    max_level: Level,
+    dropped: Arc<AtomicU64>,
+}
+
+impl ShippingLayer {
+    /// Records dropped because the queue was full
+    pub fn dropped(&self) -> Arc<AtomicU64> {
+        self.dropped.clone()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ShippingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        if *meta.level() > self.max_level
+            || IGNORED_TARGET_PREFIXES
+                .iter()
+                .any(|prefix| meta.target().starts_with(prefix))
+        {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            timestamp: Utc::now(),
+            level: *meta.level(),
+            target: meta.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+
+        if self.tx.try_send(record).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Create the layer and spawn its sender task (requires a running Tokio runtime)
+pub fn start_log_shipper(config: LogShipperConfig) -> ShippingLayer {
+    let (tx, rx) = mpsc::channel(config.queue_capacity);
+    let dropped = Arc::new(AtomicU64::new(0));
+    let layer = ShippingLayer {
+        tx,
+        max_level: config.max_level,
+        dropped: dropped.clone(),
+    };
+    tokio::spawn(run_sender(config, rx, dropped));
+    layer
+}
+
+async fn run_sender(
+    config: LogShipperConfig,
+    mut rx: mpsc::Receiver<LogRecord>,
+    dropped: Arc<AtomicU64>,
+) {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let mut flush = interval(config.flush_interval);
+    flush.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut batch: Vec<LogRecord> = Vec::with_capacity(config.batch_size);
+    let mut reported_drops = 0;
+
+    loop {
+        let closed = tokio::select! {
+            record = rx.recv() => match record {
+                Some(record) => {
+                    batch.push(record);
+                    if batch.len() < config.batch_size {
+                        continue;
+                    }
+                    false
+                }
+                None => true,
+            },
+            _ = flush.tick() => false,
+        };
+
+        // Dropped records are reported in-band so the gap is visible in the log store
+        let total_dropped = dropped.load(Ordering::Relaxed);
+        if total_dropped > reported_drops {
+            batch.push(LogRecord {
+                timestamp: Utc::now(),
+                level: Level::WARN,
+                target: module_path!().to_string(),
+                message: format!(
+                    "Log shipping queue full: {} records dropped",
+                    total_dropped - reported_drops
+                ),
+                fields: Map::new(),
+            });
+            reported_drops = total_dropped;
+        }
+
+        if !batch.is_empty() {
+            let records = std::mem::take(&mut batch);
+            if let Err(err) = push_with_retry(&client, &config, &records).await {
+                // Logging here would feed the failure back into the queue
+                eprintln!(
+                    "Log shipping failed, dropping {} records: {}",
+                    records.len(),
+                    err
+                );
+            }
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+async fn push_with_retry(
+    client: &reqwest::Client,
+    config: &LogShipperConfig,
+    records: &[LogRecord],
+) -> std::result::Result<(), String> {
+    let (url, content_type, body) = match config.backend {
+        LogBackend::Loki => (
+            format!("{}/loki/api/v1/push", config.url),
+            "application/json",
+            loki_payload(records, &config.labels).to_string(),
+        ),
+        LogBackend::Elasticsearch => (
+            format!("{}/_bulk", config.url),
+            "application/x-ndjson",
+            elasticsearch_payload(records, &config.index_prefix, &config.labels),
+        ),
+    };
+
+    let mut last_error = String::new();
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            sleep(Duration::from_millis(500 * 2u64.pow(attempt))).await;
+        }
+        let mut request = client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body.clone());
+        if let Some(token) = &config.bearer_token {
+            request = request.bearer_auth(token);
+        } else if let Some((user, password)) = &config.basic_auth {
+            request = request.basic_auth(user, Some(password));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => last_error = format!("HTTP {}", response.status()),
+            Err(err) => last_error = err.to_string(),
+        }
+    }
+    Err(last_error)
+}
+
+/// Loki push API body: one stream per level, values as `[ns, json line]`
+pub fn loki_payload(records: &[LogRecord], labels: &BTreeMap<String, String>) -> Value {
+    let mut streams: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for record in records {
+        let nanos = record
+            .timestamp
+            .timestamp_nanos_opt()
+            .unwrap_or_else(|| record.timestamp.timestamp_millis() * 1_000_000);
+        streams
+            .entry(record.level.as_str())
+            .or_default()
+            .push(json!([nanos.to_string(), record.to_json().to_string()]));
+    }
+
+    let streams: Vec<Value> = streams
+        .into_iter()
+        .map(|(level, values)| {
+            let mut stream: Map<String, Value> =
+                labels.iter().map(|(k, v)| (k.clone(), json!(v))).collect();
+            stream.insert("level".to_string(), json!(level.to_ascii_lowercase()));
+            json!({ "stream": stream, "values": values })
+        })
+        .collect();
+    json!({ "streams": streams })
+}
+
+/// Elasticsearch `_bulk` NDJSON body into daily indices `<prefix>-YYYY.MM.DD`
+pub fn elasticsearch_payload(
+    records: &[LogRecord],
+    index_prefix: &str,
+    labels: &BTreeMap<String, String>,
+) -> String {
+    let mut body = String::new();
+    for record in records {
+        let index = format!("{}-{}", index_prefix, record.timestamp.format("%Y.%m.%d"));
+        let mut doc = record.to_json();
+        if let Value::Object(map) = &mut doc {
+            for (k, v) in labels {
+                map.entry(k.clone()).or_insert_with(|| json!(v));
+            }
+        }
+        body.push_str(&json!({ "index": { "_index": index } }).to_string());
+        body.push('\n');
+        body.push_str(&doc.to_string());
+        body.push('\n');
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn record(level: Level, message: &str) -> LogRecord {
+        let mut fields = Map::new();
+        fields.insert("position_id".to_string(), json!("42"));
+        LogRecord {
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap(),
+            level,
+            target: "palm_oil_bot::bot".to_string(),
+            message: message.to_string(),
+            fields,
+        }
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        let labels = parse_labels("env=prod, region = sg ,broken,=x");
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels["env"], "prod");
+        assert_eq!(labels["region"], "sg");
+    }
+
+    #[test]
+    fn test_loki_payload_groups_by_level() {
+        let labels = parse_labels("app=bot");
+        let payload = loki_payload(
+            &[
+                record(Level::INFO, "opened"),
+                record(Level::WARN, "slow"),
+                record(Level::INFO, "closed"),
+            ],
+            &labels,
+        );
+
+        let streams = payload["streams"].as_array().unwrap();
+        assert_eq!(streams.len(), 2);
+        let info = streams
+            .iter()
+            .find(|s| s["stream"]["level"] == "info")
+            .unwrap();
+        assert_eq!(info["stream"]["app"], "bot");
+        assert_eq!(info["values"].as_array().unwrap().len(), 2);
+        assert_eq!(info["values"][0][0], "1709539200000000000");
+        let line: Value = serde_json::from_str(info["values"][0][1].as_str().unwrap()).unwrap();
+        assert_eq!(line["message"], "opened");
+        assert_eq!(line["position_id"], "42");
+    }
+
+    #[test]
+    fn test_elasticsearch_payload_is_bulk_ndjson() {
+        let labels = parse_labels("host=vps-1");
+        let body = elasticsearch_payload(&[record(Level::ERROR, "boom")], "bot", &labels);
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"index":{"_index":"bot-2024.03.04"}}"#);
+        let doc: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(doc["level"], "ERROR");
+        assert_eq!(doc["host"], "vps-1");
+    }
+
+    #[test]
+    fn test_layer_drops_when_queue_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let layer = ShippingLayer {
+            tx,
+            max_level: Level::INFO,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        let dropped = layer.dropped();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(position_id = 42, "first");
+            tracing::info!("second");
+            tracing::debug!("too verbose");
+            tracing::warn!(target: "reqwest::connect", "ignored");
+        });
+
+        let shipped = rx.try_recv().unwrap();
+        assert_eq!(shipped.message, "first");
+        assert_eq!(shipped.fields["position_id"], 42);
+        assert!(rx.try_recv().is_err());
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+}
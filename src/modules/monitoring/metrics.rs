@@ -3,13 +3,20 @@
 //! Tracks bot performance metrics including:
 //! - Trade history with entry/exit prices
 //! - Win rate calculation
-//! - P&L tracking (daily and total)
+//! - P&L tracking (daily and total), in account-currency minor units so
+//!   the running balance does not drift
 //! - Position monitoring
+//! - Per-provider sentiment readings
+//! - Correlated markets (soybean oil, crude) against palm oil
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
+use crate::modules::scraper::ProviderReading;
+use crate::modules::trading::{CrossMarketReading, KellyEstimate};
+use crate::modules::utils::money::{round_money, Money};
+
 /// Result of a completed trade
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TradeResult {
@@ -38,7 +45,7 @@ pub struct Trade {
     pub entry_time: DateTime<Utc>,
     /// Exit timestamp (None if still open)
     pub exit_time: Option<DateTime<Utc>>,
-    /// Profit/Loss in account currency
+    /// Profit/Loss in account currency, rounded to its decimals
     pub pnl: f64,
     /// Trade result
     pub result: TradeResult,
@@ -64,8 +71,8 @@ impl Trade {
     pub fn close(&mut self, exit_price: f64, pnl: f64) {
         self.exit_price = Some(exit_price);
         self.exit_time = Some(Utc::now());
-        self.pnl = pnl;
-        self.result = if pnl > 0.0 {
+        self.pnl = round_money(pnl);
+        self.result = if self.pnl > 0.0 {
             TradeResult::Win
         } else {
             TradeResult::Loss
@@ -90,19 +97,28 @@ impl Trade {
 #[derive(Debug, Clone)]
 pub struct BotMetrics {
     /// Starting balance
-    pub starting_balance: f64,
+    pub starting_balance: Money,
     /// Current balance
-    pub current_balance: f64,
+    pub current_balance: Money,
     /// Balance at start of today
-    pub daily_starting_balance: f64,
+    pub daily_starting_balance: Money,
     /// All trades (historical + open)
     pub trades: Vec<Trade>,
     /// Current RSI value
     pub current_rsi: Option<f64>,
     /// Current sentiment score
     pub current_sentiment: Option<i32>,
+    /// Per-provider readings behind the current sentiment
+    pub sentiment_providers: Vec<ProviderReading>,
+    /// Correlation and relative strength of each cross market
+    /// (CROSS_MARKET_SYMBOLS)
+    pub cross_markets: Vec<CrossMarketReading>,
     /// Current FCPO price
     pub current_price: Option<f64>,
+    /// Local clock minus broker spot time, in milliseconds (latest quote)
+    pub clock_skew_ms: Option<i64>,
+    /// Kelly criterion over recent trades (Kelly sizing only, once estimable)
+    pub kelly: Option<KellyEstimate>,
     /// Bot start time
     pub start_time: DateTime<Utc>,
 }
@@ -110,6 +126,7 @@ pub struct BotMetrics {
 impl BotMetrics {
     /// Create new metrics tracker
     pub fn new(starting_balance: f64) -> Self {
+        let starting_balance = Money::from_f64(starting_balance);
         Self {
             starting_balance,
             current_balance: starting_balance,
@@ -117,7 +134,11 @@ impl BotMetrics {
             trades: Vec::new(),
             current_rsi: None,
             current_sentiment: None,
+            sentiment_providers: Vec::new(),
+            cross_markets: Vec::new(),
             current_price: None,
+            clock_skew_ms: None,
+            kelly: None,
             start_time: Utc::now(),
         }
     }
@@ -129,22 +150,33 @@ impl BotMetrics {
 
     /// Close a trade by ID and update balance
     pub fn close_trade(&mut self, trade_id: &str, exit_price: f64) -> Option<f64> {
+        self.close_trade_with_costs(trade_id, exit_price, 0.0)
+    }
+
+    /// Close a trade by ID, net of `costs` (commission, swap), and update balance
+    pub fn close_trade_with_costs(
+        &mut self,
+        trade_id: &str,
+        exit_price: f64,
+        costs: f64,
+    ) -> Option<f64> {
         let trade = self
             .trades
             .iter_mut()
             .find(|t| t.id == trade_id && t.is_open())?;
 
-        let pnl = if trade.direction.eq_ignore_ascii_case("BUY") {
+        let gross = if trade.direction.eq_ignore_ascii_case("BUY") {
             (exit_price - trade.entry_price) * trade.volume
         } else if trade.direction.eq_ignore_ascii_case("SELL") {
             (trade.entry_price - exit_price) * trade.volume
         } else {
             0.0
         };
+        let pnl = gross - costs;
 
         trade.close(exit_price, pnl);
-        self.current_balance += pnl;
-        Some(pnl)
+        self.current_balance += Money::from_f64(trade.pnl);
+        Some(trade.pnl)
     }
 
     /// Update market data
@@ -154,9 +186,29 @@ impl BotMetrics {
         self.current_sentiment = Some(sentiment);
     }
 
+    /// Record the provider readings behind the latest sentiment
+    pub fn update_sentiment_providers(&mut self, providers: Vec<ProviderReading>) {
+        self.sentiment_providers = providers;
+    }
+
+    /// Record the latest cross-market readings
+    pub fn update_cross_markets(&mut self, readings: Vec<CrossMarketReading>) {
+        self.cross_markets = readings;
+    }
+
+    /// Record the skew between the local clock and the broker's spot time
+    pub fn update_clock_skew(&mut self, skew_ms: i64) {
+        self.clock_skew_ms = Some(skew_ms);
+    }
+
+    /// Record the Kelly estimate behind the next entry's size
+    pub fn update_kelly(&mut self, kelly: Option<KellyEstimate>) {
+        self.kelly = kelly;
+    }
+
     /// Update account balance
     pub fn update_balance(&mut self, balance: f64) {
-        self.current_balance = balance;
+        self.current_balance = Money::from_f64(balance);
     }
 
     /// Get total number of trades
@@ -201,20 +253,20 @@ impl BotMetrics {
 
     /// Get total P&L
     pub fn total_pnl(&self) -> f64 {
-        self.current_balance - self.starting_balance
+        (self.current_balance - self.starting_balance).to_f64()
     }
 
     /// Get today's P&L
     pub fn daily_pnl(&self) -> f64 {
-        self.current_balance - self.daily_starting_balance
+        (self.current_balance - self.daily_starting_balance).to_f64()
     }
 
     /// Get today's P&L as percentage
     pub fn daily_pnl_percent(&self) -> f64 {
-        if self.daily_starting_balance == 0.0 {
+        if self.daily_starting_balance == Money::ZERO {
             return 0.0;
         }
-        (self.daily_pnl() / self.daily_starting_balance) * 100.0
+        (self.daily_pnl() / self.daily_starting_balance.to_f64()) * 100.0
     }
 
     /// Get all open positions
@@ -263,7 +315,7 @@ impl BotMetrics {
 
     /// Record realized P&L from closed position
     pub fn record_realized_pnl(&mut self, pnl: f64) {
-        self.current_balance += pnl;
+        self.current_balance += Money::from_f64(pnl);
     }
 
     /// Add an open position to tracking
@@ -436,8 +488,22 @@ mod tests {
 
         let pnl = metrics.close_trade("t1", 102.0).expect("trade should close");
         assert!((pnl - 2.0).abs() < f64::EPSILON);
-        assert!((metrics.current_balance - 10002.0).abs() < f64::EPSILON);
+        assert_eq!(metrics.current_balance, Money::from_f64(10002.0));
         assert_eq!(metrics.get_total_trades(), 1);
         assert_eq!(metrics.get_open_positions().len(), 0);
     }
+
+    #[test]
+    fn test_realized_pnl_does_not_drift() {
+        let mut metrics = BotMetrics::new(10000.0);
+        for _ in 0..1_000 {
+            metrics.record_realized_pnl(0.1);
+            metrics.record_realized_pnl(0.2);
+        }
+        for _ in 0..1_000 {
+            metrics.record_realized_pnl(-0.3);
+        }
+        assert_eq!(metrics.current_balance, Money::from_f64(10000.0));
+        assert_eq!(metrics.daily_pnl(), 0.0);
+    }
 }
@@ -7,15 +7,45 @@
 //! - `dashboard`: Terminal UI with live data visualization
 //! - `risk_metrics`: Advanced risk calculations (Sharpe, VaR, Drawdown)
 //! - `circuit_breaker_status`: Real-time circuit breaker monitoring
+//! - `export_api`: HTTP download of trades, daily stats and the trade log
+//! - `grafana_api`: Grafana JSON datasource for equity, trades and indicators
+//! - `control_api`: Authenticated HTTP control API (status, positions, pause, close, cancel-all)
+//! - `grpc_api`: gRPC control service (tonic) and typed client
+//! - `event_stream`: WebSocket broadcast of `MarketEvent`s as JSON
+//! - `admin_socket`: Local admin console on a Unix domain socket (`ADMIN_SOCKET_PATH`)
+//! - `web_dashboard`: Browser dashboard (prices, positions, equity, alerts, controls)
+//! - `log_shipper`: Batched shipping of structured logs to Loki / Elasticsearch
+//! - `crash_report`: Crash bundles (state dump + backtrace) on panic or fatal error
+//! - `alert_rules`: User-defined price / RSI / spread / sentiment alerts (`ALERT_RULES`)
 
+pub mod admin_socket;
+pub mod alert_rules;
 pub mod circuit_breaker_status;
+pub mod control_api;
+pub mod crash_report;
 pub mod dashboard;
+pub mod event_stream;
+pub mod export_api;
+pub mod grafana_api;
+pub mod grpc_api;
+pub mod log_shipper;
 pub mod metrics;
 pub mod risk_metrics;
 pub mod prometheus;
+pub mod web_dashboard;
 
+pub use admin_socket::{admin_socket_path, start_admin_socket, AdminCommand};
+pub use alert_rules::{AlertMetric, AlertRule, AlertRules, TriggeredAlert};
 pub use circuit_breaker_status::{BreakerInfo, BreakerState, CircuitBreakerStatus};
+pub use control_api::ControlApi;
+pub use crash_report::{BreakerSnapshot, CrashReporter, CrashState, RecentEventsLayer};
 pub use dashboard::Dashboard;
+pub use event_stream::EventStream;
+pub use export_api::{export_api_enabled, ExportSources};
+pub use grafana_api::GrafanaApi;
+pub use grpc_api::{connect_control_client, start_grpc_server, ControlClient, GrpcApiConfig};
+pub use log_shipper::{start_log_shipper, LogShipperConfig, ShippingLayer};
 pub use metrics::{BotMetrics, MetricsHandle, Trade, TradeResult};
 pub use risk_metrics::RiskMetrics;
 pub use prometheus::{start_metrics_server, metrics_enabled};
+pub use web_dashboard::WebDashboard;
@@ -2,13 +2,21 @@
 
 use axum::{routing::get, Router};
 use chrono::Utc;
-use prometheus::{Encoder, Gauge, Registry, TextEncoder};
+use prometheus::{Encoder, Gauge, GaugeVec, Opts, Registry, TextEncoder};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
+use crate::modules::monitoring::control_api::{control_router, ControlApi};
+use crate::modules::monitoring::event_stream::{event_stream_router, EventStream};
+use crate::modules::monitoring::export_api::{export_router, ExportSources};
+use crate::modules::monitoring::grafana_api::{grafana_router, GrafanaApi};
+use crate::modules::monitoring::web_dashboard::{web_dashboard_router, WebDashboard};
 use crate::modules::monitoring::MetricsHandle;
+use crate::modules::scraper::ProviderReading;
+use crate::modules::security::rate_limiter_snapshots;
+use crate::modules::trading::frame::frame_counters;
 
 #[derive(Clone)]
 struct PrometheusExporter {
@@ -24,6 +32,151 @@ struct PrometheusExporter {
     bot_current_rsi: Gauge,
     bot_current_sentiment: Gauge,
     bot_runtime_seconds: Gauge,
+    bot_clock_skew_ms: Gauge,
+    bot_kelly_fraction: Gauge,
+    bot_kelly_win_rate: Gauge,
+    bot_kelly_payoff: Gauge,
+    ctrader_corrupt_frames: Option<GaugeVec>,
+    ctrader_stream_resyncs: Gauge,
+    rate_limiter: Option<RateLimiterGauges>,
+    sentiment_providers: Option<SentimentProviderGauges>,
+}
+
+/// Latest reading of each sentiment provider, labelled by `provider`
+#[derive(Clone)]
+struct SentimentProviderGauges {
+    score: GaugeVec,
+    confidence: GaugeVec,
+    share: GaugeVec,
+    up: GaugeVec,
+}
+
+impl SentimentProviderGauges {
+    fn new(registry: &Registry) -> Option<Self> {
+        let vec = |name: &str, help: &str| -> Option<GaugeVec> {
+            let gauge = GaugeVec::new(Opts::new(name, help), &["provider"])
+                .map_err(|err| warn!("Failed to create gauge {}: {}", name, err))
+                .ok()?;
+            if let Err(err) = registry.register(Box::new(gauge.clone())) {
+                warn!("Failed to register Prometheus gauge {}: {}", name, err);
+            }
+            Some(gauge)
+        };
+        Some(Self {
+            score: vec("sentiment_provider_score", "Latest score of the provider (-100..100)")?,
+            confidence: vec(
+                "sentiment_provider_confidence",
+                "Confidence of the provider's latest reading (0-1)",
+            )?,
+            share: vec(
+                "sentiment_provider_share",
+                "Fraction of the current sentiment taken from the provider",
+            )?,
+            up: vec(
+                "sentiment_provider_up",
+                "Whether the provider answered the latest query (1) or failed (0)",
+            )?,
+        })
+    }
+
+    fn update(&self, providers: &[ProviderReading]) {
+        for reading in providers {
+            let provider = [reading.provider.as_str()];
+            if let Some(score) = reading.score {
+                self.score.with_label_values(&provider).set(score as f64);
+            }
+            self.confidence
+                .with_label_values(&provider)
+                .set(reading.confidence);
+            self.share.with_label_values(&provider).set(reading.share);
+            self.up
+                .with_label_values(&provider)
+                .set(if reading.score.is_some() { 1.0 } else { 0.0 });
+        }
+    }
+}
+
+/// Per-API rate limiter pressure, labelled by `api`
+#[derive(Clone)]
+struct RateLimiterGauges {
+    tokens_remaining: GaugeVec,
+    queued: GaugeVec,
+    acquired_total: GaugeVec,
+    throttled_total: GaugeVec,
+    rejections_total: GaugeVec,
+    wait_seconds_total: GaugeVec,
+    last_wait_seconds: GaugeVec,
+    max_wait_seconds: GaugeVec,
+}
+
+impl RateLimiterGauges {
+    fn new(registry: &Registry) -> Option<Self> {
+        let vec = |name: &str, help: &str| -> Option<GaugeVec> {
+            let gauge = GaugeVec::new(Opts::new(name, help), &["api"])
+                .map_err(|err| warn!("Failed to create gauge {}: {}", name, err))
+                .ok()?;
+            if let Err(err) = registry.register(Box::new(gauge.clone())) {
+                warn!("Failed to register Prometheus gauge {}: {}", name, err);
+            }
+            Some(gauge)
+        };
+        Some(Self {
+            tokens_remaining: vec(
+                "rate_limiter_tokens_remaining",
+                "Requests still allowed in the current window",
+            )?,
+            queued: vec("rate_limiter_queued", "Callers waiting for a slot")?,
+            acquired_total: vec("rate_limiter_acquired_total", "Requests let through")?,
+            throttled_total: vec(
+                "rate_limiter_throttled_total",
+                "Requests that had to queue for a slot",
+            )?,
+            rejections_total: vec(
+                "rate_limiter_rejections_total",
+                "Requests refused after the max wait",
+            )?,
+            wait_seconds_total: vec(
+                "rate_limiter_wait_seconds_total",
+                "Total time spent queued",
+            )?,
+            last_wait_seconds: vec(
+                "rate_limiter_last_wait_seconds",
+                "Queue time of the latest request",
+            )?,
+            max_wait_seconds: vec(
+                "rate_limiter_max_wait_seconds",
+                "Longest queue time observed",
+            )?,
+        })
+    }
+
+    fn update(&self) {
+        for snapshot in rate_limiter_snapshots() {
+            let api = [snapshot.api.as_str()];
+            self.tokens_remaining
+                .with_label_values(&api)
+                .set(snapshot.tokens_remaining as f64);
+            self.queued.with_label_values(&api).set(snapshot.queued as f64);
+            self.acquired_total
+                .with_label_values(&api)
+                .set(snapshot.acquired_total as f64);
+            self.throttled_total
+                .with_label_values(&api)
+                .set(snapshot.throttled_total as f64);
+            self.rejections_total
+                .with_label_values(&api)
+                .set(snapshot.rejections_total as f64);
+            self.wait_seconds_total
+                .with_label_values(&api)
+                .set(snapshot.wait_seconds_total);
+            self.last_wait_seconds
+                .with_label_values(&api)
+                .set(snapshot.last_wait_seconds);
+            self.max_wait_seconds
+                .with_label_values(&api)
+                .set(snapshot.max_wait_seconds);
+        }
+    }
 }
 
 impl PrometheusExporter {
@@ -40,6 +193,22 @@ impl PrometheusExporter {
         let bot_current_rsi = create_gauge("bot_current_rsi", "Current RSI");
         let bot_current_sentiment = create_gauge("bot_current_sentiment", "Current sentiment");
         let bot_runtime_seconds = create_gauge("bot_runtime_seconds", "Runtime in seconds");
+        let bot_clock_skew_ms = create_gauge(
+            "bot_clock_skew_ms",
+            "Local clock minus broker spot timestamp (ms)",
+        );
+        let bot_kelly_fraction = create_gauge(
+            "bot_kelly_fraction",
+            "Full Kelly fraction over recent trades (Kelly sizing)",
+        );
+        let bot_kelly_win_rate =
+            create_gauge("bot_kelly_win_rate", "Win rate behind the Kelly estimate (0-1)");
+        let bot_kelly_payoff =
+            create_gauge("bot_kelly_payoff", "Average win over average loss (Kelly sizing)");
+        let ctrader_stream_resyncs = create_gauge(
+            "ctrader_stream_resyncs_total",
+            "cTrader reconnects forced by corrupt frames",
+        );
 
         for gauge in [
             bot_balance.clone(),
@@ -52,12 +221,34 @@ impl PrometheusExporter {
             bot_current_rsi.clone(),
             bot_current_sentiment.clone(),
             bot_runtime_seconds.clone(),
+            bot_clock_skew_ms.clone(),
+            bot_kelly_fraction.clone(),
+            bot_kelly_win_rate.clone(),
+            bot_kelly_payoff.clone(),
+            ctrader_stream_resyncs.clone(),
         ] {
             if let Err(err) = registry.register(Box::new(gauge)) {
                 warn!("Failed to register Prometheus gauge: {}", err);
             }
         }
 
+        let ctrader_corrupt_frames = GaugeVec::new(
+            Opts::new(
+                "ctrader_corrupt_frames_total",
+                "Corrupt cTrader frames received, by kind",
+            ),
+            &["kind"],
+        )
+        .map_err(|err| warn!("Failed to create gauge ctrader_corrupt_frames_total: {}", err))
+        .ok();
+        if let Some(gauge) = &ctrader_corrupt_frames {
+            if let Err(err) = registry.register(Box::new(gauge.clone())) {
+                warn!("Failed to register Prometheus gauge: {}", err);
+            }
+        }
+        let rate_limiter = RateLimiterGauges::new(&registry);
+        let sentiment_providers = SentimentProviderGauges::new(&registry);
+
         Self {
             registry,
             metrics,
@@ -71,12 +262,20 @@ impl PrometheusExporter {
             bot_current_rsi,
             bot_current_sentiment,
             bot_runtime_seconds,
+            bot_clock_skew_ms,
+            bot_kelly_fraction,
+            bot_kelly_win_rate,
+            bot_kelly_payoff,
+            ctrader_corrupt_frames,
+            ctrader_stream_resyncs,
+            rate_limiter,
+            sentiment_providers,
         }
     }
 
     fn update_from_snapshot(&self) {
         let snapshot = self.metrics.snapshot();
-        self.bot_balance.set(snapshot.current_balance);
+        self.bot_balance.set(snapshot.current_balance.to_f64());
         self.bot_total_pnl.set(snapshot.total_pnl());
         self.bot_daily_pnl.set(snapshot.daily_pnl());
         self.bot_win_rate.set(snapshot.win_rate());
@@ -92,6 +291,29 @@ impl PrometheusExporter {
             .set(snapshot.current_sentiment.unwrap_or(0) as f64);
         let runtime = (Utc::now() - snapshot.start_time).num_seconds();
         self.bot_runtime_seconds.set(runtime as f64);
+        self.bot_clock_skew_ms
+            .set(snapshot.clock_skew_ms.unwrap_or(0) as f64);
+        if let Some(kelly) = snapshot.kelly {
+            self.bot_kelly_fraction.set(kelly.fraction);
+            self.bot_kelly_win_rate.set(kelly.win_rate);
+            self.bot_kelly_payoff.set(kelly.payoff);
+        }
+        let frames = frame_counters();
+        if let Some(corrupt) = &self.ctrader_corrupt_frames {
+            corrupt
+                .with_label_values(&["oversized"])
+                .set(frames.oversized as f64);
+            corrupt
+                .with_label_values(&["undecodable"])
+                .set(frames.undecodable as f64);
+        }
+        self.ctrader_stream_resyncs.set(frames.resyncs as f64);
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.update();
+        }
+        if let Some(gauges) = &self.sentiment_providers {
+            gauges.update(&snapshot.sentiment_providers);
+        }
     }
 
     fn render(&self) -> String {
@@ -141,12 +363,41 @@ async fn metrics_handler(exporter: Arc<PrometheusExporter>) -> String {
     exporter.render()
 }
 
-pub fn start_metrics_server(metrics: MetricsHandle) -> JoinHandle<()> {
+/// Serve `/metrics`, plus the export, control, Grafana, web dashboard and
+/// event stream routes when given
+pub fn start_metrics_server(
+    metrics: MetricsHandle,
+    exports: Option<ExportSources>,
+    control: Option<ControlApi>,
+    grafana: Option<GrafanaApi>,
+    web: Option<WebDashboard>,
+    stream: Option<EventStream>,
+) -> JoinHandle<()> {
     let exporter = Arc::new(PrometheusExporter::new(metrics));
-    let app = Router::new().route("/metrics", get({
+    let mut app = Router::new().route("/metrics", get({
         let exporter = exporter.clone();
         move || metrics_handler(exporter.clone())
     }));
+    if let Some(sources) = exports {
+        info!("Export API enabled at /export/{{trades,daily_stats,audit}}.{{csv,json}}");
+        app = app.merge(export_router(sources));
+    }
+    if let Some(control) = control {
+        info!("Control API enabled at /control/{{status,positions,pause,resume,close,sentiment,cancel_all}}");
+        app = app.merge(control_router(control));
+    }
+    if let Some(grafana) = grafana {
+        info!("Grafana JSON datasource enabled at /grafana");
+        app = app.merge(grafana_router(grafana));
+    }
+    if let Some(web) = web {
+        info!("Web dashboard enabled at /dashboard");
+        app = app.merge(web_dashboard_router(web));
+    }
+    if let Some(stream) = stream {
+        info!("Event stream enabled at /events/ws");
+        app = app.merge(event_stream_router(stream));
+    }
 
     let addr = metrics_bind_addr();
     info!("Starting metrics server on {}", addr);
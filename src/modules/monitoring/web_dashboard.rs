@@ -0,0 +1,508 @@
+//! Browser dashboard for running the bot headless.
+//!
+//! Mirrors the ratatui dashboard as a single page served alongside `/metrics`
+//! when `WEB_DASHBOARD_ENABLED` is set, so a bot on a VPS can be watched from
+//! a phone:
+//! - `GET /dashboard` — the page (static HTML/JS embedded in the binary)
+//! - `GET /dashboard/api/state` — price, RSI, sentiment (with its per-provider
+//!   breakdown), cross markets, account, positions, equity curve, recent
+//!   alerts and the broker connection state as JSON (polled by the page)
+//! - `GET /dashboard/api/status` — the bot's status summary
+//! - `POST /dashboard/api/pause` / `POST /dashboard/api/resume` — stop or
+//!   allow new entries
+//! - `POST /dashboard/api/cancel_all[?flatten=true]` — emergency cancel-all
+//!
+//! The page shows positions and can move money, so the dashboard is only
+//! mounted when a token is configured (`WEB_DASHBOARD_TOKEN`, admin role, or
+//! role tokens in `ACCESS_TOKENS`). The API requires
+//! `Authorization: Bearer <token>`; open the page as
+//! `/dashboard#token=<token>` and it sends the header itself (the fragment
+//! never reaches the server or its logs). A viewer token can watch the page
+//! but its buttons are refused with 403.
+
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+use crate::modules::monitoring::metrics::{BotMetrics, MetricsHandle};
+use crate::modules::scraper::ProviderReading;
+use crate::modules::security::{AccessPolicy, Action, Role};
+use crate::modules::trading::{
+    AlertLevel, ConnectionState, CrossMarketReading, EmergencyHandle, EventChannelHandle,
+    EventFilter, EventType, MarketEvent,
+};
+use crate::modules::utils::Money;
+
+/// Name the access checks are audited under
+const API: &str = "web_dashboard";
+
+/// The page, with its script and styles inline
+const DASHBOARD_HTML: &str = include_str!("web/dashboard.html");
+
+/// Alerts kept for the page
+const MAX_ALERTS: usize = 50;
+
+/// How long a caller waits for the trading loop to answer a command
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One alert as shown on the page
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardAlert {
+    pub level: String,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest broker connection status as shown on the page
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardConnection {
+    pub state: ConnectionState,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// State behind the web dashboard
+#[derive(Clone)]
+pub struct WebDashboard {
+    pub metrics: MetricsHandle,
+    pub emergency: EmergencyHandle,
+    pub symbol: String,
+    /// Accepted bearer tokens and their roles
+    pub access: AccessPolicy,
+    alerts: Arc<Mutex<VecDeque<DashboardAlert>>>,
+    connection: Arc<Mutex<Option<DashboardConnection>>>,
+}
+
+impl WebDashboard {
+    pub fn new(
+        metrics: MetricsHandle,
+        emergency: EmergencyHandle,
+        symbol: &str,
+        access: AccessPolicy,
+    ) -> Self {
+        Self {
+            metrics,
+            emergency,
+            symbol: symbol.to_string(),
+            access,
+            alerts: Arc::new(Mutex::new(VecDeque::new())),
+            connection: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Build from `WEB_DASHBOARD_ENABLED`, `WEB_DASHBOARD_TOKEN` and
+    /// `ACCESS_TOKENS`; `None` when disabled or when no token is configured
+    pub fn from_env(
+        metrics: MetricsHandle,
+        emergency: EmergencyHandle,
+        symbol: &str,
+    ) -> Option<Self> {
+        let enabled = matches!(
+            std::env::var("WEB_DASHBOARD_ENABLED").as_deref(),
+            Ok("true") | Ok("1") | Ok("yes")
+        );
+        if !enabled {
+            return None;
+        }
+        let access = AccessPolicy::from_env()
+            .with_token(std::env::var("WEB_DASHBOARD_TOKEN").ok(), Role::Admin);
+        if access.is_empty() {
+            warn!("WEB_DASHBOARD_ENABLED is set but neither WEB_DASHBOARD_TOKEN nor ACCESS_TOKENS is set; web dashboard disabled");
+            return None;
+        }
+        Some(Self::new(metrics, emergency, symbol, access))
+    }
+
+    /// Keep the most recent alerts and the connection status from the event
+    /// channel for the page
+    pub fn start_alert_feed(&self, events: EventChannelHandle) -> JoinHandle<()> {
+        let dashboard = self.clone();
+        tokio::spawn(async move {
+            let (_id, mut rx) = events
+                .subscribe(EventFilter::event_types(vec![
+                    EventType::Alert,
+                    EventType::ConnectionStatus,
+                ]))
+                .await;
+            while let Some(event) = rx.recv().await {
+                match event {
+                    MarketEvent::Alert {
+                        level,
+                        message,
+                        timestamp,
+                    } => dashboard.push_alert(level, message, timestamp),
+                    MarketEvent::ConnectionStatus {
+                        state,
+                        message,
+                        timestamp,
+                        ..
+                    } => {
+                        *dashboard.connection.lock().unwrap() = Some(DashboardConnection {
+                            state,
+                            message,
+                            timestamp,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    fn push_alert(&self, level: AlertLevel, message: String, timestamp: DateTime<Utc>) {
+        let mut alerts = self.alerts.lock().unwrap();
+        if alerts.len() == MAX_ALERTS {
+            alerts.pop_front();
+        }
+        alerts.push_back(DashboardAlert {
+            level: format!("{:?}", level),
+            message,
+            timestamp,
+        });
+    }
+
+    fn state(&self) -> DashboardState {
+        let metrics = self.metrics.snapshot();
+        // Newest first, like the alert list on the page
+        let alerts = self.alerts.lock().unwrap().iter().rev().cloned().collect();
+        let mut state = DashboardState::from_metrics(&self.symbol, &metrics, alerts);
+        state.connection = self.connection.lock().unwrap().clone();
+        state
+    }
+}
+
+/// An open position row
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardPosition {
+    pub id: String,
+    pub direction: String,
+    pub volume: f64,
+    pub entry_price: f64,
+    pub duration_secs: i64,
+    pub pnl: f64,
+}
+
+/// Everything the page renders, in one poll
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardState {
+    pub symbol: String,
+    pub price: Option<f64>,
+    pub rsi: Option<f64>,
+    pub sentiment: Option<i32>,
+    /// Readings behind `sentiment`, one per provider
+    pub sentiment_providers: Vec<ProviderReading>,
+    /// Correlated markets against palm oil (CROSS_MARKET_SYMBOLS)
+    pub cross_markets: Vec<CrossMarketReading>,
+    pub balance: f64,
+    pub starting_balance: f64,
+    pub daily_pnl: f64,
+    pub daily_pnl_percent: f64,
+    pub total_pnl: f64,
+    pub total_trades: usize,
+    pub win_rate: f64,
+    pub runtime: String,
+    pub positions: Vec<DashboardPosition>,
+    /// `[unix_ms, balance]` after each closed trade, starting at bot start
+    pub equity: Vec<(i64, f64)>,
+    pub alerts: Vec<DashboardAlert>,
+    /// Latest broker connection status (None before the first one)
+    pub connection: Option<DashboardConnection>,
+}
+
+impl DashboardState {
+    fn from_metrics(symbol: &str, metrics: &BotMetrics, alerts: Vec<DashboardAlert>) -> Self {
+        let positions = metrics
+            .open_positions()
+            .into_iter()
+            .map(|t| DashboardPosition {
+                id: t.id.clone(),
+                direction: t.direction.clone(),
+                volume: t.volume,
+                entry_price: t.entry_price,
+                duration_secs: t.duration_secs(),
+                pnl: t.pnl,
+            })
+            .collect();
+
+        Self {
+            symbol: symbol.to_string(),
+            price: metrics.current_price,
+            rsi: metrics.current_rsi,
+            sentiment: metrics.current_sentiment,
+            sentiment_providers: metrics.sentiment_providers.clone(),
+            cross_markets: metrics.cross_markets.clone(),
+            balance: metrics.current_balance.to_f64(),
+            starting_balance: metrics.starting_balance.to_f64(),
+            daily_pnl: metrics.daily_pnl(),
+            daily_pnl_percent: metrics.daily_pnl_percent(),
+            total_pnl: metrics.total_pnl(),
+            total_trades: metrics.total_trades(),
+            win_rate: metrics.win_rate(),
+            runtime: metrics.runtime_formatted(),
+            positions,
+            equity: equity_curve(metrics),
+            alerts,
+            connection: None,
+        }
+    }
+}
+
+/// Balance after each closed trade, in exit order
+fn equity_curve(metrics: &BotMetrics) -> Vec<(i64, f64)> {
+    let mut closed: Vec<_> = metrics
+        .trades
+        .iter()
+        .filter_map(|t| t.exit_time.map(|exit| (exit, Money::from_f64(t.pnl))))
+        .collect();
+    closed.sort_by_key(|(exit, _)| *exit);
+
+    let mut balance = metrics.starting_balance;
+    let mut points = vec![(metrics.start_time.timestamp_millis(), balance.to_f64())];
+    for (exit, pnl) in closed {
+        balance += pnl;
+        points.push((exit.timestamp_millis(), balance.to_f64()));
+    }
+    points
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CancelAllParams {
+    #[serde(default)]
+    flatten: bool,
+}
+
+/// Router serving `/dashboard` and `/dashboard/api/*`
+pub fn web_dashboard_router(dashboard: WebDashboard) -> Router {
+    Router::new()
+        .route("/dashboard", get(page_handler))
+        .route("/dashboard/api/state", get(state_handler))
+        .route("/dashboard/api/status", get(status_handler))
+        .route("/dashboard/api/pause", post(pause_handler))
+        .route("/dashboard/api/resume", post(resume_handler))
+        .route("/dashboard/api/cancel_all", post(cancel_all_handler))
+        .with_state(dashboard)
+}
+
+async fn page_handler() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
+
+async fn state_handler(State(dashboard): State<WebDashboard>, headers: HeaderMap) -> Response {
+    if let Err(denied) = dashboard
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    Json(dashboard.state()).into_response()
+}
+
+async fn status_handler(State(dashboard): State<WebDashboard>, headers: HeaderMap) -> Response {
+    if let Err(denied) = dashboard
+        .access
+        .authorize_headers(API, &headers, Action::ReadState)
+    {
+        return denied.into_response();
+    }
+    match tokio::time::timeout(COMMAND_TIMEOUT, dashboard.emergency.status()).await {
+        Ok(Ok(status)) => status.into_response(),
+        Ok(Err(err)) => (StatusCode::SERVICE_UNAVAILABLE, err).into_response(),
+        Err(_) => (StatusCode::GATEWAY_TIMEOUT, "Bot did not answer in time").into_response(),
+    }
+}
+
+async fn pause_handler(State(dashboard): State<WebDashboard>, headers: HeaderMap) -> Response {
+    set_paused(&dashboard, &headers, true)
+}
+
+async fn resume_handler(State(dashboard): State<WebDashboard>, headers: HeaderMap) -> Response {
+    set_paused(&dashboard, &headers, false)
+}
+
+fn set_paused(dashboard: &WebDashboard, headers: &HeaderMap, paused: bool) -> Response {
+    let action = if paused {
+        Action::Pause
+    } else {
+        Action::Resume
+    };
+    if let Err(denied) = dashboard.access.authorize_headers(API, headers, action) {
+        return denied.into_response();
+    }
+    let what = if paused { "Pause" } else { "Resume" };
+    warn!("Web dashboard: {} requested", what.to_lowercase());
+    if dashboard.emergency.request_pause(paused) {
+        (StatusCode::ACCEPTED, format!("{} sent to the bot", what)).into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("{} not sent: bot is busy or stopped", what),
+        )
+            .into_response()
+    }
+}
+
+async fn cancel_all_handler(
+    State(dashboard): State<WebDashboard>,
+    Query(params): Query<CancelAllParams>,
+    headers: HeaderMap,
+) -> Response {
+    let action = if params.flatten {
+        Action::Flatten
+    } else {
+        Action::CancelOrders
+    };
+    if let Err(denied) = dashboard.access.authorize_headers(API, &headers, action) {
+        return denied.into_response();
+    }
+
+    warn!(
+        "Web dashboard: cancel-all requested (flatten={})",
+        params.flatten
+    );
+    let outcome = tokio::time::timeout(
+        COMMAND_TIMEOUT,
+        dashboard.emergency.cancel_all(params.flatten),
+    )
+    .await;
+    match outcome {
+        Ok(Ok(report)) => {
+            let status = if report.is_complete() {
+                StatusCode::OK
+            } else {
+                StatusCode::MULTI_STATUS
+            };
+            (status, Json(report)).into_response()
+        }
+        Ok(Err(err)) => {
+            error!("Web dashboard: cancel-all failed: {}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, err).into_response()
+        }
+        Err(_) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            "Cancel-all still running; check the bot logs",
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::monitoring::metrics::Trade;
+    use crate::modules::trading::emergency_channel;
+    use axum::http::header;
+
+    fn dashboard() -> WebDashboard {
+        let (emergency, _rx) = emergency_channel();
+        WebDashboard::new(
+            MetricsHandle::new(10_000.0),
+            emergency,
+            "FCPO",
+            AccessPolicy::parse("viewer:watch").with_token(Some("secret".to_string()), Role::Admin),
+        )
+    }
+
+    #[test]
+    fn test_bearer_token_required() {
+        let dashboard = dashboard();
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            dashboard
+                .access
+                .authorize_headers(API, &headers, Action::ReadState)
+                .unwrap_err()
+                .into_response()
+                .status(),
+            StatusCode::UNAUTHORIZED
+        );
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        assert!(dashboard
+            .access
+            .authorize_headers(API, &headers, Action::ReadState)
+            .is_err());
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(dashboard
+            .access
+            .authorize_headers(API, &headers, Action::Flatten)
+            .is_ok());
+
+        headers.insert(header::AUTHORIZATION, "Bearer watch".parse().unwrap());
+        assert!(dashboard
+            .access
+            .authorize_headers(API, &headers, Action::ReadState)
+            .is_ok());
+        assert_eq!(
+            dashboard
+                .access
+                .authorize_headers(API, &headers, Action::Pause)
+                .unwrap_err()
+                .into_response()
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn test_state_positions_and_equity() {
+        let dashboard = dashboard();
+        dashboard.metrics.with_metrics_mut(|m| {
+            m.update_market_data(4850.0, 28.5, 40);
+            m.update_sentiment_providers(vec![ProviderReading {
+                provider: "perplexity".to_string(),
+                weight: 1.0,
+                score: Some(40),
+                confidence: 0.8,
+                share: 1.0,
+                error: None,
+            }]);
+            m.add_trade(Trade::new("1".to_string(), "BUY".to_string(), 1.0, 4800.0));
+            m.add_trade(Trade::new("2".to_string(), "SELL".to_string(), 1.0, 4900.0));
+            m.close_position("1", 4840.0, 40.0);
+        });
+
+        let state = dashboard.state();
+        assert_eq!(state.symbol, "FCPO");
+        assert_eq!(state.price, Some(4850.0));
+        assert_eq!(state.rsi, Some(28.5));
+        assert_eq!(state.sentiment, Some(40));
+        assert_eq!(state.sentiment_providers[0].provider, "perplexity");
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions[0].id, "2");
+        assert_eq!(state.positions[0].direction, "SELL");
+
+        assert_eq!(state.equity.len(), 2);
+        assert_eq!(state.equity[0].1, 10_000.0);
+        assert_eq!(state.equity[1].1, 10_040.0);
+    }
+
+    #[test]
+    fn test_alerts_capped_newest_first() {
+        let dashboard = dashboard();
+        for i in 0..(MAX_ALERTS + 5) {
+            dashboard.push_alert(AlertLevel::Warning, format!("alert {}", i), Utc::now());
+        }
+        dashboard.push_alert(AlertLevel::Critical, "breaker".to_string(), Utc::now());
+
+        let state = dashboard.state();
+        assert_eq!(state.alerts.len(), MAX_ALERTS);
+        assert_eq!(state.alerts[0].level, "Critical");
+        assert_eq!(state.alerts[0].message, "breaker");
+        assert_eq!(state.alerts[MAX_ALERTS - 1].message, "alert 6");
+    }
+
+    #[test]
+    fn test_page_embedded() {
+        assert!(DASHBOARD_HTML.contains("/dashboard/api/state"));
+        assert!(DASHBOARD_HTML.contains("Authorization"));
+    }
+}
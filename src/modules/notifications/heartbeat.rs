@@ -0,0 +1,219 @@
+//! Periodic "bot is alive" summaries
+//!
+//! Every `HEARTBEAT_INTERVAL_HOURS` (unset = off; fractions allowed) the bot
+//! posts uptime, open positions, today's P&L, the last signal and how fresh
+//! the price feed is, so quiet markets can be told apart from a stuck bot.
+//! While the feed is stale, the secondary price source (if any) stands in for
+//! the unrealized P&L.
+
+use chrono::{DateTime, Duration, Utc};
+use std::env;
+
+/// Feed older than this is flagged as stale in the summary
+const STALE_FEED_SECS: i64 = 300;
+
+/// Fixed-interval heartbeat schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatSchedule {
+    pub interval: Duration,
+}
+
+impl HeartbeatSchedule {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval: interval.max(Duration::minutes(1)),
+        }
+    }
+
+    /// Load from `HEARTBEAT_INTERVAL_HOURS` (disabled when unset, zero or invalid)
+    pub fn from_env() -> Option<Self> {
+        env::var("HEARTBEAT_INTERVAL_HOURS")
+            .ok()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|h| *h > 0.0)
+            .map(|h| Self::new(Duration::seconds((h * 3600.0) as i64)))
+    }
+
+    /// Whether a summary is due, given when the last one was sent. The first
+    /// one goes out one interval after `started_at`.
+    pub fn is_due(
+        &self,
+        now: DateTime<Utc>,
+        started_at: DateTime<Utc>,
+        last_sent: Option<DateTime<Utc>>,
+    ) -> bool {
+        now - last_sent.unwrap_or(started_at) >= self.interval
+    }
+}
+
+/// One open position as shown in the summary
+#[derive(Debug, Clone)]
+pub struct HeartbeatPosition {
+    pub side: String,
+    pub volume: f64,
+    pub entry_price: f64,
+    /// Unrealized P&L at the last price (or the secondary feed's while the
+    /// feed is stale), when known
+    pub unrealized_pnl: Option<f64>,
+}
+
+/// Snapshot of the bot state for one heartbeat
+#[derive(Debug, Clone)]
+pub struct HeartbeatSummary {
+    pub symbol: String,
+    pub uptime: Duration,
+    pub dry_run: bool,
+    pub standby: bool,
+    /// New entries paused by the operator (`/pause`)
+    pub paused: bool,
+    pub positions: Vec<HeartbeatPosition>,
+    pub daily_pnl: f64,
+    pub daily_trades: u32,
+    pub last_signal: String,
+    pub last_price: Option<f64>,
+    /// Time since the last price tick, None when no tick was received yet
+    pub feed_age: Option<Duration>,
+    /// Secondary feed price standing in while the feed is stale
+    pub fallback_price: Option<f64>,
+}
+
+impl HeartbeatSummary {
+    /// Telegram message text
+    pub fn to_message(&self) -> String {
+        let mut lines = vec![format!(
+            "💓 {} bot alive — up {}{}{}{}",
+            self.symbol,
+            format_duration(self.uptime),
+            if self.dry_run { " (dry run)" } else { "" },
+            if self.standby { " [standby]" } else { "" },
+            if self.paused { " [paused]" } else { "" },
+        )];
+
+        lines.push(match (self.feed_age, self.last_price) {
+            (Some(age), Some(price)) if age.num_seconds() >= STALE_FEED_SECS => format!(
+                "⚠️ Feed stale: last price {:.2}, {} ago",
+                price,
+                format_duration(age)
+            ),
+            (Some(age), Some(price)) => {
+                format!(
+                    "Feed: last price {:.2}, {} ago",
+                    price,
+                    format_duration(age)
+                )
+            }
+            _ => "⚠️ Feed: no price received yet".to_string(),
+        });
+        if let Some(price) = self.fallback_price {
+            lines.push(format!("Secondary feed: {:.2} (used for P&L)", price));
+        }
+
+        lines.push(format!(
+            "Day P&L: {:+.2} ({} trades) | Last signal: {}",
+            self.daily_pnl, self.daily_trades, self.last_signal
+        ));
+
+        if self.positions.is_empty() {
+            lines.push("Open positions: none".to_string());
+        } else {
+            lines.push(format!("Open positions: {}", self.positions.len()));
+            for position in &self.positions {
+                let pnl = position
+                    .unrealized_pnl
+                    .map(|pnl| format!(" ({:+.2})", pnl))
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "  {} {} @ {:.2}{}",
+                    position.side, position.volume, position.entry_price, pnl
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// `3d 4h`, `5h 12m`, `7m 5s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (days, hours, mins) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else {
+        format!("{}m {}s", mins, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn summary() -> HeartbeatSummary {
+        HeartbeatSummary {
+            symbol: "FCPO".to_string(),
+            uptime: Duration::hours(28),
+            dry_run: false,
+            standby: false,
+            paused: false,
+            positions: Vec::new(),
+            daily_pnl: -12.5,
+            daily_trades: 2,
+            last_signal: "Hold".to_string(),
+            last_price: Some(4850.0),
+            feed_age: Some(Duration::seconds(4)),
+            fallback_price: None,
+        }
+    }
+
+    #[test]
+    fn test_schedule_is_due() {
+        let schedule = HeartbeatSchedule::new(Duration::hours(4));
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+
+        assert!(!schedule.is_due(start + Duration::hours(3), start, None));
+        assert!(schedule.is_due(start + Duration::hours(4), start, None));
+
+        let sent = start + Duration::hours(4);
+        assert!(!schedule.is_due(start + Duration::hours(7), start, Some(sent)));
+        assert!(schedule.is_due(start + Duration::hours(8), start, Some(sent)));
+    }
+
+    #[test]
+    fn test_message_contents() {
+        let mut s = summary();
+        s.positions.push(HeartbeatPosition {
+            side: "Buy".to_string(),
+            volume: 1.0,
+            entry_price: 4800.0,
+            unrealized_pnl: Some(50.0),
+        });
+        let message = s.to_message();
+
+        assert!(message.contains("FCPO bot alive — up 1d 4h"));
+        assert!(message.contains("Feed: last price 4850.00, 0m 4s ago"));
+        assert!(message.contains("Day P&L: -12.50 (2 trades) | Last signal: Hold"));
+        assert!(message.contains("Open positions: 1"));
+        assert!(message.contains("Buy 1 @ 4800.00 (+50.00)"));
+    }
+
+    #[test]
+    fn test_stale_and_missing_feed_flagged() {
+        let mut s = summary();
+        s.feed_age = Some(Duration::minutes(12));
+        assert!(s.to_message().contains("⚠️ Feed stale"));
+        assert!(!s.to_message().contains("Secondary feed"));
+        s.fallback_price = Some(4862.5);
+        assert!(s.to_message().contains("Secondary feed: 4862.50 (used for P&L)"));
+
+        s.paused = true;
+        assert!(s.to_message().contains("up 1d 4h [paused]"));
+
+        s.feed_age = None;
+        s.last_price = None;
+        assert!(s.to_message().contains("no price received yet"));
+        assert!(s.to_message().contains("Open positions: none"));
+    }
+}
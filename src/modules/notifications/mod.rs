@@ -0,0 +1,14 @@
+//! Outbound notifications
+//!
+//! Provides:
+//! - Telegram delivery for reports and alerts, plus remote commands
+//! - Periodic heartbeat summaries (`HEARTBEAT_INTERVAL_HOURS`)
+
+pub mod heartbeat;
+pub mod telegram;
+
+pub use heartbeat::{HeartbeatPosition, HeartbeatSchedule, HeartbeatSummary};
+pub use telegram::{
+    start_telegram_bot, telegram_commands_enabled, TelegramCommand, TelegramConfig,
+    TelegramNotifier,
+};
@@ -0,0 +1,489 @@
+//! Telegram Bot API notifier and remote control
+//!
+//! Sends plain-text messages to a single chat via `sendMessage`.
+//! Configured with `TELEGRAM_BOT_TOKEN` and `TELEGRAM_CHAT_ID`.
+//!
+//! `start_telegram_bot` subscribes to the `EventChannel` and pushes order
+//! fills and critical alerts (circuit-breaker trips, reconciliation
+//! mismatches, cancel-all) to the chat. With `TELEGRAM_COMMANDS_ENABLED` it
+//! also long-polls `getUpdates` and runs `/status`, `/pause`, `/resume` and
+//! `/close_all` sent from that chat (messages from other chats are ignored).
+
+use crate::error::{BotError, Result};
+use crate::modules::trading::{
+    AlertLevel, ConnectionState, EmergencyHandle, EventChannelHandle, EventFilter, EventType,
+    MarketEvent,
+};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Telegram API base URL
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Telegram messages are capped at 4096 characters
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+/// Long-poll timeout for `getUpdates` (seconds)
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Pause after a failed `getUpdates` before polling again
+const POLL_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Telegram credentials
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    /// Bot token from @BotFather
+    pub bot_token: String,
+    /// Target chat ID (user, group or channel)
+    pub chat_id: String,
+}
+
+impl TelegramConfig {
+    /// Load from environment; `None` when either variable is missing
+    pub fn from_env() -> Option<Self> {
+        let bot_token = env::var("TELEGRAM_BOT_TOKEN").ok().filter(|v| !v.is_empty())?;
+        let chat_id = env::var("TELEGRAM_CHAT_ID").ok().filter(|v| !v.is_empty())?;
+        Some(Self { bot_token, chat_id })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SendMessageRequest<'a> {
+    chat_id: &'a str,
+    text: &'a str,
+    disable_web_page_preview: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdatesResponse {
+    ok: bool,
+    #[serde(default)]
+    result: Vec<Update>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<IncomingMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncomingMessage {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+/// Commands accepted from the configured chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelegramCommand {
+    Status,
+    Pause,
+    Resume,
+    CloseAll,
+    Help,
+}
+
+impl TelegramCommand {
+    /// Parse `/status`, `/pause`, ... (also `/status@MyBot`); `None` for
+    /// plain text, unknown commands map to `Help`
+    pub fn parse(text: &str) -> Option<Self> {
+        let word = text.split_whitespace().next()?.strip_prefix('/')?;
+        let name = word.split('@').next().unwrap_or(word);
+        Some(match name.to_lowercase().as_str() {
+            "status" => TelegramCommand::Status,
+            "pause" => TelegramCommand::Pause,
+            "resume" => TelegramCommand::Resume,
+            "close_all" | "closeall" => TelegramCommand::CloseAll,
+            _ => TelegramCommand::Help,
+        })
+    }
+}
+
+const HELP_TEXT: &str = "Commands:\n\
+/status - positions, P&L, last signal\n\
+/pause - stop opening new positions\n\
+/resume - resume opening positions\n\
+/close_all - cancel all orders and close all positions";
+
+/// Telegram message sender
+#[derive(Clone)]
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    config: TelegramConfig,
+}
+
+impl TelegramNotifier {
+    /// Create a notifier for the given chat
+    pub fn new(config: TelegramConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!("Failed to build HTTP client: {}. Falling back to default client.", e);
+                reqwest::Client::new()
+            });
+
+        Self { client, config }
+    }
+
+    /// Create a notifier from environment variables, if configured
+    pub fn from_env() -> Option<Self> {
+        TelegramConfig::from_env().map(Self::new)
+    }
+
+    /// Send a text message, splitting it when it exceeds Telegram's limit
+    pub async fn send_message(&self, text: &str) -> Result<()> {
+        for chunk in split_message(text, MAX_MESSAGE_CHARS) {
+            self.send_chunk(&chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_chunk(&self, text: &str) -> Result<()> {
+        let url = format!("{}/bot{}/sendMessage", TELEGRAM_API_URL, self.config.bot_token);
+        let request = SendMessageRequest {
+            chat_id: &self.config.chat_id,
+            text,
+            disable_web_page_preview: true,
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(redact_token)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(BotError::Other(format!(
+                "Telegram sendMessage failed: {} - {}",
+                status, body
+            )));
+        }
+
+        debug!("Telegram message sent ({} chars)", text.chars().count());
+        Ok(())
+    }
+
+    /// Long-poll for updates after `offset`, waiting up to `timeout_secs`
+    async fn get_updates(&self, offset: i64, timeout_secs: u64) -> Result<Vec<Update>> {
+        let url = format!("{}/bot{}/getUpdates", TELEGRAM_API_URL, self.config.bot_token);
+        let response = self
+            .client
+            .get(&url)
+            .query(&[
+                ("offset", offset.to_string()),
+                ("timeout", timeout_secs.to_string()),
+            ])
+            // The request itself waits up to the poll timeout
+            .timeout(Duration::from_secs(timeout_secs + 10))
+            .send()
+            .await
+            .map_err(redact_token)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(BotError::Other(format!(
+                "Telegram getUpdates failed: {} - {}",
+                status, body
+            )));
+        }
+        let updates: UpdatesResponse = response.json().await.map_err(redact_token)?;
+        if !updates.ok {
+            return Err(BotError::Other("Telegram getUpdates returned ok=false".to_string()));
+        }
+        Ok(updates.result)
+    }
+
+    fn is_own_chat(&self, chat_id: i64) -> bool {
+        self.config.chat_id.trim() == chat_id.to_string()
+    }
+}
+
+/// Drop the request URL from a reqwest error: it carries the bot token
+fn redact_token(err: reqwest::Error) -> BotError {
+    BotError::Network(err.without_url())
+}
+
+/// Whether Telegram commands are accepted (`TELEGRAM_COMMANDS_ENABLED`)
+pub fn telegram_commands_enabled() -> bool {
+    matches!(
+        env::var("TELEGRAM_COMMANDS_ENABLED").as_deref(),
+        Ok("true") | Ok("1") | Ok("yes")
+    )
+}
+
+/// Telegram text for events worth pushing: fills, critical alerts, and a
+/// lost connection (first reconnect attempt, giving up, restored)
+pub fn event_message(event: &MarketEvent) -> Option<String> {
+    match event {
+        MarketEvent::ConnectionStatus {
+            state,
+            attempt,
+            duration_secs,
+            message,
+            ..
+        } => match (state, attempt) {
+            (ConnectionState::Reconnecting, Some(1)) | (ConnectionState::Failed, _) => {
+                Some(format!("🔌 {}", message))
+            }
+            (ConnectionState::Connected, _) if duration_secs.is_some() => {
+                Some(format!("🔌 {}", message))
+            }
+            _ => None,
+        },
+        MarketEvent::OrderFilled {
+            order_id,
+            side,
+            volume,
+            price,
+            ..
+        } => Some(format!(
+            "✅ Filled {:?} {} @ {:.2} (order {})",
+            side, volume, price, order_id
+        )),
+        MarketEvent::Alert {
+            level: AlertLevel::Critical,
+            message,
+            ..
+        } => Some(message.clone()),
+        _ => None,
+    }
+}
+
+/// Start the event forwarder and, when `control` is given, the command
+/// listener. Both run until the process exits.
+pub fn start_telegram_bot(
+    notifier: TelegramNotifier,
+    events: EventChannelHandle,
+    control: Option<EmergencyHandle>,
+) -> Vec<JoinHandle<()>> {
+    let mut tasks = Vec::new();
+
+    let forwarder = notifier.clone();
+    tasks.push(tokio::spawn(async move {
+        let (_id, mut rx) = events
+            .subscribe(EventFilter::event_types(vec![
+                EventType::OrderFilled,
+                EventType::Alert,
+                EventType::ConnectionStatus,
+            ]))
+            .await;
+        while let Some(event) = rx.recv().await {
+            let Some(text) = event_message(&event) else {
+                continue;
+            };
+            if let Err(err) = forwarder.send_message(&text).await {
+                warn!("Failed to forward event to Telegram: {}", err);
+            }
+        }
+    }));
+
+    if let Some(control) = control {
+        info!("Telegram commands enabled (/status, /pause, /resume, /close_all)");
+        tasks.push(tokio::spawn(poll_commands(notifier, control)));
+    }
+    tasks
+}
+
+async fn poll_commands(notifier: TelegramNotifier, control: EmergencyHandle) {
+    // Commands sent while the bot was down are stale (a /close_all from
+    // yesterday must not run now): skip everything already queued
+    let mut offset = match notifier.get_updates(0, 0).await {
+        Ok(updates) => updates.iter().map(|u| u.update_id + 1).max().unwrap_or(0),
+        Err(err) => {
+            warn!("Telegram polling failed: {}", err);
+            0
+        }
+    };
+    loop {
+        let updates = match notifier.get_updates(offset, POLL_TIMEOUT_SECS).await {
+            Ok(updates) => updates,
+            Err(err) => {
+                warn!("Telegram polling failed: {}", err);
+                tokio::time::sleep(POLL_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+            let Some(message) = update.message else {
+                continue;
+            };
+            if !notifier.is_own_chat(message.chat.id) {
+                warn!("Ignoring Telegram message from chat {}", message.chat.id);
+                continue;
+            }
+            let Some(command) = message.text.as_deref().and_then(TelegramCommand::parse) else {
+                continue;
+            };
+            info!("Telegram command: {:?}", command);
+            let reply = run_command(command, &control).await;
+            if let Err(err) = notifier.send_message(&reply).await {
+                warn!("Failed to answer Telegram command: {}", err);
+            }
+        }
+    }
+}
+
+async fn run_command(command: TelegramCommand, control: &EmergencyHandle) -> String {
+    match command {
+        TelegramCommand::Status => control
+            .status()
+            .await
+            .unwrap_or_else(|err| format!("⚠️ Status unavailable: {}", err)),
+        TelegramCommand::Pause => {
+            if control.request_pause(true) {
+                "⏸ Paused: no new positions (open positions are still managed)".to_string()
+            } else {
+                "⚠️ Pause not queued: trading loop busy or stopped".to_string()
+            }
+        }
+        TelegramCommand::Resume => {
+            if control.request_pause(false) {
+                "▶️ Resumed: new positions allowed".to_string()
+            } else {
+                "⚠️ Resume not queued: trading loop busy or stopped".to_string()
+            }
+        }
+        TelegramCommand::CloseAll => match control.cancel_all(true).await {
+            Ok(report) => format!(
+                "🛑 Close all: {} orders cancelled, {} positions closed, {} failures",
+                report.cancelled_orders.len(),
+                report.closed_positions.len(),
+                report.failures.len()
+            ),
+            Err(err) => format!("⚠️ Close all failed: {}", err),
+        },
+        TelegramCommand::Help => HELP_TEXT.to_string(),
+    }
+}
+
+/// Split text into chunks of at most `max_chars`, preferring line boundaries
+fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_len = line.chars().count();
+        if current_len + line_len > max_chars && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+
+        if line_len > max_chars {
+            // Single oversized line: hard-split on characters
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(max_chars) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+
+        current.push_str(line);
+        current_len += line_len;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_short_message() {
+        let chunks = split_message("hello\nworld", 4096);
+        assert_eq!(chunks, vec!["hello\nworld".to_string()]);
+    }
+
+    #[test]
+    fn test_split_on_line_boundaries() {
+        let chunks = split_message("aaaa\nbbbb\ncccc", 10);
+        assert_eq!(chunks, vec!["aaaa\nbbbb\n".to_string(), "cccc".to_string()]);
+    }
+
+    #[test]
+    fn test_split_oversized_line() {
+        let chunks = split_message("abcdefghij", 4);
+        assert_eq!(chunks, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(TelegramCommand::parse("/status"), Some(TelegramCommand::Status));
+        assert_eq!(
+            TelegramCommand::parse("/Pause@PalmOilBot now"),
+            Some(TelegramCommand::Pause)
+        );
+        assert_eq!(TelegramCommand::parse("/close_all"), Some(TelegramCommand::CloseAll));
+        assert_eq!(TelegramCommand::parse("/start"), Some(TelegramCommand::Help));
+        assert_eq!(TelegramCommand::parse("status"), None);
+        assert_eq!(TelegramCommand::parse("  "), None);
+    }
+
+    #[test]
+    fn test_event_messages() {
+        let fill = MarketEvent::OrderFilled {
+            order_id: 7,
+            symbol_id: 1,
+            side: crate::modules::trading::event_system::OrderSide::Buy,
+            volume: 1.0,
+            price: 4850.0,
+            timestamp: chrono::Utc::now(),
+        };
+        assert_eq!(
+            event_message(&fill).as_deref(),
+            Some("✅ Filled Buy 1 @ 4850.00 (order 7)")
+        );
+
+        let alert = |level| MarketEvent::Alert {
+            level,
+            message: "⛔ Circuit breaker tripped".to_string(),
+            timestamp: chrono::Utc::now(),
+        };
+        assert!(event_message(&alert(AlertLevel::Critical)).is_some());
+        assert!(event_message(&alert(AlertLevel::Warning)).is_none());
+
+        let status = |state, attempt| MarketEvent::ConnectionStatus {
+            connected: state == ConnectionState::Connected,
+            state,
+            attempt,
+            duration_secs: Some(12.0),
+            message: "cTrader reconnect attempt".to_string(),
+            timestamp: chrono::Utc::now(),
+        };
+        assert!(event_message(&status(ConnectionState::Reconnecting, Some(1))).is_some());
+        assert!(event_message(&status(ConnectionState::Reconnecting, Some(2))).is_none());
+        assert!(event_message(&status(ConnectionState::Connected, None)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_command_uses_control_channel() {
+        let (control, mut rx) = crate::modules::trading::emergency_channel();
+        let reply = run_command(TelegramCommand::Pause, &control).await;
+        assert!(reply.starts_with("⏸ Paused"));
+        assert!(matches!(
+            rx.recv().await,
+            Some(crate::modules::trading::EmergencyCommand::SetPaused(true))
+        ));
+
+        drop(rx);
+        let reply = run_command(TelegramCommand::Status, &control).await;
+        assert!(reply.contains("Status unavailable"));
+    }
+}
@@ -0,0 +1,456 @@
+//! Fundamental data inputs: weather, El Niño and production series
+//!
+//! Palm oil reacts to Malaysian/Indonesian weather (rainfall, the ONI El Niño
+//! index) and to MPOB production, export and stock figures. This module
+//! reads a configurable set of such series from plain JSON or CSV endpoints
+//! and turns each latest value into a bias from -100 (bearish) to +100
+//! (bullish) around a neutral level:
+//! `bias = (value - neutral) / scale * 100`, clamped. A negative scale
+//! inverts a series, e.g. stocks above normal are bearish. The series are
+//! averaged by weight into one fundamental bias score.
+//!
+//! `FUNDAMENTALS_SERIES` holds `;`-separated `name|url|field|neutral|scale|weight`
+//! entries (weight defaults to 1). `field` is a JSON pointer
+//! (`/data/0/value`) for JSON bodies or `csv:<column>` for CSV bodies, where
+//! the last row with a number in that column is read. Enabled with
+//! `FUNDAMENTALS_ENABLED`; the series are fetched again every
+//! `FUNDAMENTALS_REFRESH_HOURS` (default 6) and the strategy blends the bias
+//! into sentiment with `FUNDAMENTALS_WEIGHT` (default 0.3).
+
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tracing::{debug, info, warn};
+
+use crate::error::{BotError, Result};
+
+const DEFAULT_REFRESH_HOURS: i64 = 6;
+const DEFAULT_WEIGHT: f64 = 0.3;
+
+/// Where a series' value sits in the response body
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeriesField {
+    /// JSON pointer, e.g. `/data/0/value`
+    Json(String),
+    /// Column of a CSV body; the last row with a number is read
+    Csv(String),
+}
+
+impl SeriesField {
+    /// `csv:<column>` or a JSON pointer (`/...`)
+    pub fn parse(raw: &str) -> Option<Self> {
+        if let Some(column) = raw.strip_prefix("csv:") {
+            let column = column.trim();
+            return (!column.is_empty()).then(|| SeriesField::Csv(column.to_string()));
+        }
+        raw.starts_with('/')
+            .then(|| SeriesField::Json(raw.to_string()))
+    }
+
+    /// Latest value of the series in `body`
+    pub fn extract(&self, body: &str) -> Option<f64> {
+        match self {
+            SeriesField::Json(pointer) => {
+                let json: serde_json::Value = serde_json::from_str(body).ok()?;
+                let value = json.pointer(pointer)?;
+                value
+                    .as_f64()
+                    .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+            }
+            SeriesField::Csv(column) => last_csv_value(body, column),
+        }
+    }
+}
+
+/// One external data series and how it maps to a bias
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundamentalSeries {
+    /// Label used in logs and decisions
+    pub name: String,
+    pub url: String,
+    pub field: SeriesField,
+    /// Value that means no bias
+    pub neutral: f64,
+    /// Distance from `neutral` that counts as a full ±100 (negative = bearish
+    /// when above neutral)
+    pub scale: f64,
+    /// Share of the series in the combined score
+    pub weight: f64,
+}
+
+impl FundamentalSeries {
+    /// Parse a `name|url|field|neutral|scale|weight` entry
+    fn parse(entry: &str) -> Result<Self> {
+        let parts: Vec<&str> = entry.split('|').map(str::trim).collect();
+        let (name, url, field, neutral, scale, weight) = match parts.as_slice() {
+            [name, url, field, neutral, scale] => (*name, *url, *field, *neutral, *scale, None),
+            [name, url, field, neutral, scale, weight] => {
+                (*name, *url, *field, *neutral, *scale, Some(*weight))
+            }
+            _ => {
+                return Err(BotError::Config(format!(
+                    "invalid FUNDAMENTALS_SERIES entry {:?} (expected \
+                     name|url|field|neutral|scale|weight)",
+                    entry
+                )))
+            }
+        };
+        let number = |what: &str, raw: &str| {
+            raw.parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| {
+                    BotError::Config(format!(
+                        "invalid FUNDAMENTALS_SERIES {} for {}: {:?}",
+                        what, name, raw
+                    ))
+                })
+        };
+        if url::Url::parse(url).is_err() {
+            return Err(BotError::Config(format!(
+                "invalid FUNDAMENTALS_SERIES url for {}: {:?}",
+                name, url
+            )));
+        }
+        let field = SeriesField::parse(field).ok_or_else(|| {
+            BotError::Config(format!(
+                "invalid FUNDAMENTALS_SERIES field for {}: {:?} (expected a JSON pointer \
+                 such as /data/0/value or csv:<column>)",
+                name, field
+            ))
+        })?;
+        let scale = number("scale", scale)?;
+        if scale == 0.0 {
+            return Err(BotError::Config(format!(
+                "FUNDAMENTALS_SERIES scale for {} must not be 0",
+                name
+            )));
+        }
+        let weight = match weight {
+            Some(raw) => Some(number("weight", raw)?)
+                .filter(|w| *w > 0.0)
+                .ok_or_else(|| {
+                    BotError::Config(format!(
+                        "FUNDAMENTALS_SERIES weight for {} must be positive",
+                        name
+                    ))
+                })?,
+            None => 1.0,
+        };
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            field,
+            neutral: number("neutral level", neutral)?,
+            scale,
+            weight,
+        })
+    }
+
+    /// Bias of a value of this series (-100..100)
+    pub fn bias(&self, value: f64) -> f64 {
+        ((value - self.neutral) / self.scale * 100.0).clamp(-100.0, 100.0)
+    }
+}
+
+/// Which series are read and how much they count
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundamentalsConfig {
+    pub series: Vec<FundamentalSeries>,
+    /// How long a reading is used before the series are fetched again
+    pub refresh: Duration,
+    /// Share of the fundamental bias when blended with sentiment (0..1)
+    pub weight: f64,
+}
+
+impl FundamentalsConfig {
+    /// Load from `FUNDAMENTALS_*`; `None` unless `FUNDAMENTALS_ENABLED` is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let enabled = env::var("FUNDAMENTALS_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        let series = env::var("FUNDAMENTALS_SERIES")
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(FundamentalSeries::parse)
+            .collect::<Result<Vec<_>>>()?;
+        if series.is_empty() {
+            return Err(BotError::Config(
+                "FUNDAMENTALS_SERIES must list at least one series when \
+                 FUNDAMENTALS_ENABLED is set"
+                    .into(),
+            ));
+        }
+        let refresh_hours = match env::var("FUNDAMENTALS_REFRESH_HOURS") {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|hours| *hours > 0)
+                .ok_or_else(|| {
+                    BotError::Config(format!("invalid FUNDAMENTALS_REFRESH_HOURS: {:?}", raw))
+                })?,
+            _ => DEFAULT_REFRESH_HOURS,
+        };
+        let weight = match env::var("FUNDAMENTALS_WEIGHT") {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|w| (0.0..=1.0).contains(w))
+                .ok_or_else(|| {
+                    BotError::Config(format!(
+                        "invalid FUNDAMENTALS_WEIGHT: {:?} (expected 0 to 1)",
+                        raw
+                    ))
+                })?,
+            _ => DEFAULT_WEIGHT,
+        };
+        Ok(Some(Self {
+            series,
+            refresh: Duration::hours(refresh_hours),
+            weight,
+        }))
+    }
+}
+
+/// Latest value of one series and its bias
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FundamentalReading {
+    pub series: String,
+    pub value: f64,
+    pub bias: f64,
+    pub weight: f64,
+}
+
+/// Weighted fundamental bias over the series that could be read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FundamentalBias {
+    /// -100 (bearish) to +100 (bullish)
+    pub score: i32,
+    pub readings: Vec<FundamentalReading>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl FundamentalBias {
+    /// Weighted mean of the readings; None without any
+    pub fn from_readings(readings: Vec<FundamentalReading>, at: DateTime<Utc>) -> Option<Self> {
+        let total_weight: f64 = readings.iter().map(|r| r.weight).sum();
+        if readings.is_empty() || total_weight <= 0.0 {
+            return None;
+        }
+        let score = readings.iter().map(|r| r.bias * r.weight).sum::<f64>() / total_weight;
+        Some(Self {
+            score: score.round() as i32,
+            readings,
+            fetched_at: at,
+        })
+    }
+}
+
+/// Fetches the fundamental series and keeps the latest bias
+pub struct FundamentalsClient {
+    client: reqwest::Client,
+    config: FundamentalsConfig,
+    latest: Option<FundamentalBias>,
+    /// Last fetch attempt, successful or not
+    checked_at: Option<DateTime<Utc>>,
+}
+
+impl FundamentalsClient {
+    pub fn new(config: FundamentalsConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .user_agent(format!("palm-oil-bot/{}", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client: {}. Falling back to default client.",
+                    e
+                );
+                reqwest::Client::new()
+            });
+
+        Self {
+            client,
+            config,
+            latest: None,
+            checked_at: None,
+        }
+    }
+
+    pub fn config(&self) -> &FundamentalsConfig {
+        &self.config
+    }
+
+    /// Last bias read, however old
+    pub fn latest(&self) -> Option<&FundamentalBias> {
+        self.latest.as_ref()
+    }
+
+    /// Latest value of one series
+    async fn fetch_series(&self, series: &FundamentalSeries) -> Result<f64> {
+        debug!(
+            "Fetching fundamental series {}: {}",
+            series.name, series.url
+        );
+        let response = self
+            .client
+            .get(&series.url)
+            .send()
+            .await
+            .map_err(|e| BotError::Feed(format!("{}: request failed: {}", series.name, e)))?;
+        if !response.status().is_success() {
+            return Err(BotError::Feed(format!(
+                "{}: HTTP error: {}",
+                series.name,
+                response.status()
+            )));
+        }
+        let body = response.text().await.map_err(|e| {
+            BotError::Feed(format!("{}: failed to read response: {}", series.name, e))
+        })?;
+        series.field.extract(&body).ok_or_else(|| {
+            BotError::Feed(format!("{}: no value at {:?}", series.name, series.field))
+        })
+    }
+
+    /// Fetch every series; keeps the previous bias when none could be read
+    pub async fn refresh(&mut self, now: DateTime<Utc>) -> Option<&FundamentalBias> {
+        self.checked_at = Some(now);
+        let mut readings = Vec::new();
+        for series in &self.config.series {
+            match self.fetch_series(series).await {
+                Ok(value) => readings.push(FundamentalReading {
+                    series: series.name.clone(),
+                    value,
+                    bias: series.bias(value),
+                    weight: series.weight,
+                }),
+                Err(err) => warn!("Fundamental series failed: {}", err),
+            }
+        }
+        match FundamentalBias::from_readings(readings, now) {
+            Some(bias) => {
+                info!(
+                    "Fundamental bias {} from {}",
+                    bias.score,
+                    bias.readings
+                        .iter()
+                        .map(|r| format!("{}={} ({:+.0})", r.series, r.value, r.bias))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                self.latest = Some(bias);
+            }
+            None => warn!("No fundamental series could be read; keeping the last bias"),
+        }
+        self.latest.as_ref()
+    }
+
+    /// Current bias score, fetching the series again once the refresh
+    /// interval has passed since the last attempt
+    pub async fn bias(&mut self, now: DateTime<Utc>) -> Option<i32> {
+        let due = self
+            .checked_at
+            .map_or(true, |at| now - at >= self.config.refresh);
+        if due {
+            self.refresh(now).await;
+        }
+        self.latest.as_ref().map(|bias| bias.score)
+    }
+}
+
+/// Value of `column` in the last CSV row where it holds a number
+fn last_csv_value(body: &str, column: &str) -> Option<f64> {
+    let mut lines = body.lines().filter(|line| !line.trim().is_empty());
+    let header = lines.next()?;
+    let index = split_csv(header)
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(column))?;
+    lines
+        .filter_map(|line| split_csv(line).get(index)?.parse::<f64>().ok())
+        .last()
+}
+
+fn split_csv(line: &str) -> Vec<String> {
+    line.split(',')
+        .map(|cell| cell.trim().trim_matches('"').trim().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_parsing_and_bias() {
+        let oni = FundamentalSeries::parse("oni|https://data.example/oni.json|/latest/value|0|2")
+            .unwrap();
+        assert_eq!(oni.field, SeriesField::Json("/latest/value".into()));
+        assert_eq!(oni.weight, 1.0);
+        assert_eq!(oni.bias(1.0), 50.0);
+        assert_eq!(oni.bias(-5.0), -100.0);
+
+        let stocks = FundamentalSeries::parse(
+            "mpob_stocks|https://data.example/mpob.csv|csv:closing_stocks|1800000|-500000|2",
+        )
+        .unwrap();
+        assert_eq!(stocks.bias(2_050_000.0), -50.0);
+
+        assert!(FundamentalSeries::parse("oni|https://data.example/oni.json|value|0|2").is_err());
+        assert!(FundamentalSeries::parse("oni|https://data.example/oni.json|/v|0|0").is_err());
+
+        let bias = FundamentalBias::from_readings(
+            vec![
+                FundamentalReading {
+                    series: "oni".into(),
+                    value: 1.0,
+                    bias: 50.0,
+                    weight: oni.weight,
+                },
+                FundamentalReading {
+                    series: "mpob_stocks".into(),
+                    value: 2_050_000.0,
+                    bias: -50.0,
+                    weight: stocks.weight,
+                },
+            ],
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(bias.score, -17);
+        assert!(FundamentalBias::from_readings(Vec::new(), Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_value_extraction() {
+        let json = r#"{"latest": {"value": "1.25"}, "history": [{"value": 0.5}]}"#;
+        assert_eq!(
+            SeriesField::Json("/latest/value".into()).extract(json),
+            Some(1.25)
+        );
+        assert_eq!(
+            SeriesField::Json("/history/0/value".into()).extract(json),
+            Some(0.5)
+        );
+        assert_eq!(SeriesField::Json("/missing".into()).extract(json), None);
+
+        let csv = "month,\"Closing Stocks\",exports\n\
+                   2024-01,1910000,1200000\n\
+                   2024-02,1870000,\n\
+                   2024-03,n/a,1100000\n";
+        assert_eq!(
+            SeriesField::Csv("closing stocks".into()).extract(csv),
+            Some(1_870_000.0)
+        );
+        assert_eq!(SeriesField::Csv("rainfall".into()).extract(csv), None);
+    }
+}
@@ -0,0 +1,507 @@
+//! LLM backends for the sentiment prompts
+//!
+//! `PerplexityClient` builds the prompts and parses the answers; the model
+//! call itself goes through an `LlmClient`, chosen with `LLM_BACKEND`:
+//! - `perplexity` (default): Sonar, which searches the web and returns the
+//!   citations the source policy scores
+//! - `openai`: any OpenAI-compatible chat completions API (OpenAI, Groq,
+//!   vLLM, LM Studio, ...) at `LLM_ENDPOINT`
+//! - `anthropic`: the Anthropic Messages API
+//! - `ollama`: a local Ollama server, with no API key and no per-token cost
+//!
+//! Only Perplexity searches the web. The other models answer from what they
+//! were trained on, so their scores lag the news; give them a lower weight in
+//! `SENTIMENT_PROVIDER_WEIGHTS` when they run next to live sources.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, warn};
+
+use crate::config::{LlmBackend, PerplexityConfig};
+use crate::error::{BotError, PerplexityError, Result};
+
+/// Timeout of hosted APIs
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout of a local Ollama model, which may run on a CPU
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Anthropic API version header
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Future returned by `LlmClient::complete`
+pub type LlmFuture<'a> = Pin<Box<dyn Future<Output = Result<LlmAnswer>> + Send + 'a>>;
+
+/// One prompt and its model settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmRequest {
+    pub system: String,
+    pub prompt: String,
+    pub temperature: f32,
+    pub max_tokens: u32,
+}
+
+/// Answer text together with the URLs it cites (Perplexity only)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmAnswer {
+    pub content: String,
+    pub citations: Vec<String>,
+}
+
+/// A chat model the sentiment prompts can be sent to
+pub trait LlmClient: Send + Sync {
+    /// Backend name, used as the sentiment source and provider name
+    fn name(&self) -> &str;
+
+    /// Model the requests go to
+    fn model(&self) -> &str;
+
+    /// Send one prompt and return the answer
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_>;
+}
+
+/// Client for the configured backend
+pub fn llm_client(config: &PerplexityConfig) -> Arc<dyn LlmClient> {
+    match config.backend {
+        LlmBackend::Perplexity | LlmBackend::OpenAi => {
+            Arc::new(OpenAiCompatibleClient::new(config))
+        }
+        LlmBackend::Anthropic => Arc::new(AnthropicClient::new(config)),
+        LlmBackend::Ollama => Arc::new(OllamaClient::new(config)),
+    }
+}
+
+fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to build HTTP client: {}. Falling back to default client.",
+                e
+            );
+            reqwest::Client::new()
+        })
+}
+
+/// Send `request` and decode the JSON answer; HTTP 429 and 401 map to
+/// `RateLimited` and `InvalidApiKey`
+async fn send_json<T: DeserializeOwned>(
+    backend: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<T> {
+    let response = request.send().await.map_err(|e| {
+        error!("{} API request failed: {}", backend, e);
+        BotError::Perplexity(PerplexityError::RequestFailed(e.to_string()))
+    })?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        warn!("{} API rate limited (429)", backend);
+        return Err(BotError::Perplexity(PerplexityError::RateLimited));
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        error!("Invalid {} API key", backend);
+        return Err(BotError::Perplexity(PerplexityError::InvalidApiKey));
+    }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        error!("{} API error: {} - {}", backend, status, error_text);
+        return Err(BotError::Perplexity(PerplexityError::RequestFailed(
+            format!("{}: {}", status, error_text),
+        )));
+    }
+
+    response.json().await.map_err(|e| {
+        error!("Failed to parse {} response: {}", backend, e);
+        BotError::Perplexity(PerplexityError::ParseError(e.to_string()))
+    })
+}
+
+fn empty_answer() -> BotError {
+    BotError::Perplexity(PerplexityError::ParseError(
+        "No response content".to_string(),
+    ))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+/// System and user messages for chat-style APIs
+fn messages(request: &LlmRequest) -> Vec<Message> {
+    vec![
+        Message {
+            role: "system".to_string(),
+            content: request.system.clone(),
+        },
+        Message {
+            role: "user".to_string(),
+            content: request.prompt.clone(),
+        },
+    ]
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+    /// Source URLs backing the answer (Perplexity)
+    #[serde(default)]
+    citations: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+/// OpenAI-style `/chat/completions` API; also serves Perplexity, which adds
+/// `citations` to the response
+pub struct OpenAiCompatibleClient {
+    client: reqwest::Client,
+    name: &'static str,
+    endpoint: String,
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiCompatibleClient {
+    pub fn new(config: &PerplexityConfig) -> Self {
+        Self {
+            client: http_client(HTTP_TIMEOUT),
+            name: config.backend.name(),
+            endpoint: config.endpoint.clone(),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+        }
+    }
+
+    fn body(&self, request: &LlmRequest) -> ChatRequest {
+        ChatRequest {
+            model: self.model.clone(),
+            messages: messages(request),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+        }
+    }
+
+    async fn send(&self, request: LlmRequest) -> Result<LlmAnswer> {
+        let mut http = self.client.post(&self.endpoint).json(&self.body(&request));
+        if !self.api_key.is_empty() {
+            http = http.bearer_auth(&self.api_key);
+        }
+        let response: ChatResponse = send_json(self.name, http).await?;
+
+        if let Some(usage) = &response.usage {
+            debug!(
+                "{} tokens used: prompt {}, completion {}",
+                self.name, usage.prompt_tokens, usage.completion_tokens
+            );
+        }
+        let content = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(empty_answer)?;
+        Ok(LlmAnswer {
+            content,
+            citations: response.citations,
+        })
+    }
+}
+
+impl LlmClient for OpenAiCompatibleClient {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_> {
+        Box::pin(self.send(request))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    system: String,
+    messages: Vec<Message>,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicBlock>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+/// Anthropic Messages API
+pub struct AnthropicClient {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: String,
+    model: String,
+}
+
+impl AnthropicClient {
+    pub fn new(config: &PerplexityConfig) -> Self {
+        Self {
+            client: http_client(HTTP_TIMEOUT),
+            endpoint: config.endpoint.clone(),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+        }
+    }
+
+    fn body(&self, request: &LlmRequest) -> AnthropicRequest {
+        AnthropicRequest {
+            model: self.model.clone(),
+            system: request.system.clone(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: request.prompt.clone(),
+            }],
+            // The Messages API accepts 0.0-1.0
+            temperature: request.temperature.min(1.0),
+            max_tokens: request.max_tokens,
+        }
+    }
+
+    async fn send(&self, request: LlmRequest) -> Result<LlmAnswer> {
+        let http = self
+            .client
+            .post(&self.endpoint)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&self.body(&request));
+        let response: AnthropicResponse = send_json("anthropic", http).await?;
+
+        if let Some(usage) = &response.usage {
+            debug!(
+                "anthropic tokens used: prompt {}, completion {}",
+                usage.input_tokens, usage.output_tokens
+            );
+        }
+        let content: String = response
+            .content
+            .into_iter()
+            .filter(|block| block.kind == "text")
+            .map(|block| block.text)
+            .collect();
+        if content.is_empty() {
+            return Err(empty_answer());
+        }
+        Ok(LlmAnswer {
+            content,
+            citations: Vec::new(),
+        })
+    }
+}
+
+impl LlmClient for AnthropicClient {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_> {
+        Box::pin(self.send(request))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    temperature: f32,
+    num_predict: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaResponse {
+    message: Message,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
+}
+
+/// Local Ollama server (`/api/chat`, non-streaming)
+pub struct OllamaClient {
+    client: reqwest::Client,
+    endpoint: String,
+    model: String,
+}
+
+impl OllamaClient {
+    pub fn new(config: &PerplexityConfig) -> Self {
+        Self {
+            client: http_client(OLLAMA_TIMEOUT),
+            endpoint: config.endpoint.clone(),
+            model: config.model.clone(),
+        }
+    }
+
+    fn body(&self, request: &LlmRequest) -> OllamaRequest {
+        OllamaRequest {
+            model: self.model.clone(),
+            messages: messages(request),
+            stream: false,
+            options: OllamaOptions {
+                temperature: request.temperature,
+                num_predict: request.max_tokens,
+            },
+        }
+    }
+
+    async fn send(&self, request: LlmRequest) -> Result<LlmAnswer> {
+        let http = self.client.post(&self.endpoint).json(&self.body(&request));
+        let response: OllamaResponse = send_json("ollama", http).await?;
+
+        debug!(
+            "ollama tokens used: prompt {:?}, completion {:?}",
+            response.prompt_eval_count, response.eval_count
+        );
+        if response.message.content.trim().is_empty() {
+            return Err(empty_answer());
+        }
+        Ok(LlmAnswer {
+            content: response.message.content,
+            citations: Vec::new(),
+        })
+    }
+}
+
+impl LlmClient for OllamaClient {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_> {
+        Box::pin(self.send(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(backend: LlmBackend) -> PerplexityConfig {
+        PerplexityConfig {
+            backend,
+            api_key: "key".to_string(),
+            endpoint: backend.default_endpoint().to_string(),
+            model: backend.default_model().to_string(),
+            temperature: 1.5,
+            max_tokens: 400,
+            prompts: Default::default(),
+        }
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            system: "You trade FCPO.".to_string(),
+            prompt: "SENTIMENT_SCORE?".to_string(),
+            temperature: 1.5,
+            max_tokens: 400,
+        }
+    }
+
+    #[test]
+    fn test_request_bodies_per_backend() {
+        let openai = OpenAiCompatibleClient::new(&config(LlmBackend::OpenAi));
+        let body = serde_json::to_value(openai.body(&request())).unwrap();
+        assert_eq!(body["model"], "gpt-4o-mini");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["max_tokens"], 400);
+
+        let anthropic = AnthropicClient::new(&config(LlmBackend::Anthropic));
+        let body = serde_json::to_value(anthropic.body(&request())).unwrap();
+        assert_eq!(body["system"], "You trade FCPO.");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["temperature"], 1.0);
+
+        let ollama = OllamaClient::new(&config(LlmBackend::Ollama));
+        let body = serde_json::to_value(ollama.body(&request())).unwrap();
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["options"]["num_predict"], 400);
+
+        assert_eq!(llm_client(&config(LlmBackend::Ollama)).name(), "ollama");
+        assert_eq!(
+            llm_client(&config(LlmBackend::Perplexity)).name(),
+            "perplexity"
+        );
+    }
+
+    #[test]
+    fn test_responses_parse() {
+        let raw = r#"{"choices":[{"message":{"role":"assistant","content":"ok"},"finish_reason":"stop"}],"citations":["https://reuters.com/a"]}"#;
+        let parsed: ChatResponse = serde_json::from_str(raw).unwrap();
+        assert_eq!(parsed.citations, vec!["https://reuters.com/a".to_string()]);
+
+        let raw = r#"{"content":[{"type":"text","text":"SENTIMENT_SCORE: 20"}],"usage":{"input_tokens":10,"output_tokens":5}}"#;
+        let parsed: AnthropicResponse = serde_json::from_str(raw).unwrap();
+        assert_eq!(parsed.content[0].text, "SENTIMENT_SCORE: 20");
+
+        let raw =
+            r#"{"model":"llama3.1","message":{"role":"assistant","content":"hi"},"done":true}"#;
+        let parsed: OllamaResponse = serde_json::from_str(raw).unwrap();
+        assert_eq!(parsed.message.content, "hi");
+    }
+}
@@ -0,0 +1,192 @@
+//! Structured market brief (deep-dive sentiment report)
+//!
+//! A longer Perplexity answer covering supply/demand, MPOB expectations and
+//! technical levels, kept alongside the numeric sentiment score so the reasoning
+//! behind a reading can be reviewed later or pushed to a chat before the session.
+
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Section headers the brief prompt asks Perplexity to use
+pub const BRIEF_SECTIONS: [&str; 5] = [
+    "SUPPLY_DEMAND",
+    "MPOB_EXPECTATIONS",
+    "TECHNICAL_LEVELS",
+    "KEY_RISKS",
+    "SUMMARY",
+];
+
+/// Long-form market brief with its sentiment reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketBrief {
+    /// Symbol the brief was generated for
+    pub symbol: String,
+    /// Sentiment score extracted from the brief (-100 to +100)
+    pub score: i32,
+    /// Confidence of the score (0.0 to 1.0)
+    pub confidence: f64,
+    /// Full brief text as returned by the API
+    pub content: String,
+    /// When the brief was generated
+    pub generated_at: DateTime<Utc>,
+}
+
+impl MarketBrief {
+    /// Create a new brief stamped with the current time
+    pub fn new(symbol: &str, score: i32, confidence: f64, content: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            score: score.clamp(-100, 100),
+            confidence: confidence.clamp(0.0, 1.0),
+            content: content.into(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Extract a named section (e.g. `TECHNICAL_LEVELS`) from the brief.
+    ///
+    /// A section runs from its `NAME:` header up to the next known header.
+    pub fn section(&self, name: &str) -> Option<String> {
+        let header = format!("{}:", name);
+        let start = self.content.find(&header)? + header.len();
+        let rest = &self.content[start..];
+
+        let end = BRIEF_SECTIONS
+            .iter()
+            .chain(["SENTIMENT_SCORE", "CONFIDENCE"].iter())
+            .filter_map(|other| rest.find(&format!("{}:", other)))
+            .min()
+            .unwrap_or(rest.len());
+
+        let text = rest[..end].trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    }
+
+    /// Render the brief as a plain-text message for chat delivery
+    pub fn to_message(&self) -> String {
+        let mut message = format!(
+            "📰 {} market brief ({})\nSentiment: {:+} (confidence {:.0}%)\n",
+            self.symbol,
+            self.generated_at.format("%Y-%m-%d %H:%M UTC"),
+            self.score,
+            self.confidence * 100.0
+        );
+
+        let mut found_section = false;
+        for name in BRIEF_SECTIONS {
+            if let Some(text) = self.section(name) {
+                found_section = true;
+                message.push_str(&format!("\n{}\n{}\n", name.replace('_', " "), text));
+            }
+        }
+
+        // Unstructured answer: send it as-is rather than an empty brief
+        if !found_section {
+            message.push('\n');
+            message.push_str(self.content.trim());
+            message.push('\n');
+        }
+
+        message
+    }
+}
+
+/// Daily schedule for the morning brief
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketBriefSchedule {
+    /// UTC hour at which the brief is generated (0-23)
+    pub hour_utc: u32,
+}
+
+impl MarketBriefSchedule {
+    /// Create a schedule firing at the given UTC hour
+    pub fn new(hour_utc: u32) -> Self {
+        Self {
+            hour_utc: hour_utc.min(23),
+        }
+    }
+
+    /// Load schedule from `MARKET_BRIEF_HOUR_UTC` (disabled when unset or invalid)
+    pub fn from_env() -> Option<Self> {
+        env::var("MARKET_BRIEF_HOUR_UTC")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|h| *h < 24)
+            .map(Self::new)
+    }
+
+    /// Whether a brief should be produced now, given the date of the last one sent
+    pub fn is_due(&self, now: DateTime<Utc>, last_sent: Option<NaiveDate>) -> bool {
+        if now.hour() < self.hour_utc {
+            return false;
+        }
+        last_sent != Some(now.date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const SAMPLE: &str = "SUPPLY_DEMAND: Malaysian output seasonally rising, Indian imports firm.\n\
+        MPOB_EXPECTATIONS: Stocks seen up 4% m/m.\n\
+        TECHNICAL_LEVELS: Support 4150, resistance 4320.\n\
+        KEY_RISKS: Ringgit strength.\n\
+        SENTIMENT_SCORE: 25\n\
+        CONFIDENCE: medium\n\
+        SUMMARY: Mildly bullish into the session.";
+
+    #[test]
+    fn test_section_extraction() {
+        let brief = MarketBrief::new("FCPO", 25, 0.6, SAMPLE);
+
+        assert_eq!(
+            brief.section("TECHNICAL_LEVELS").as_deref(),
+            Some("Support 4150, resistance 4320.")
+        );
+        assert_eq!(
+            brief.section("KEY_RISKS").as_deref(),
+            Some("Ringgit strength.")
+        );
+        assert_eq!(
+            brief.section("SUMMARY").as_deref(),
+            Some("Mildly bullish into the session.")
+        );
+        assert!(brief.section("UNKNOWN").is_none());
+    }
+
+    #[test]
+    fn test_message_contains_sections() {
+        let brief = MarketBrief::new("FCPO", 25, 0.6, SAMPLE);
+        let message = brief.to_message();
+
+        assert!(message.contains("FCPO market brief"));
+        assert!(message.contains("+25"));
+        assert!(message.contains("MPOB EXPECTATIONS"));
+        assert!(message.contains("Support 4150"));
+    }
+
+    #[test]
+    fn test_message_falls_back_to_raw_content() {
+        let brief = MarketBrief::new("FCPO", 0, 0.5, "No structured answer available.");
+        assert!(brief.to_message().contains("No structured answer available."));
+    }
+
+    #[test]
+    fn test_schedule_is_due() {
+        let schedule = MarketBriefSchedule::new(6);
+        let before = Utc.with_ymd_and_hms(2024, 3, 4, 5, 59, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 4, 6, 1, 0).unwrap();
+
+        assert!(!schedule.is_due(before, None));
+        assert!(schedule.is_due(after, None));
+        assert!(!schedule.is_due(after, Some(after.date_naive())));
+        assert!(schedule.is_due(after, Some(before.date_naive().pred_opt().unwrap())));
+    }
+}
@@ -2,14 +2,48 @@
 //!
 //! This module provides sentiment analysis from multiple sources:
 //! - Perplexity API (primary): Real-time web search for market sentiment
-//! - Twitter scraping (backup): Direct KOL monitoring
+//! - Twitter scraping (backup): Direct KOL monitoring, or the official v2
+//!   recent-search API when a bearer token is configured
+//! - LLM backends: Perplexity, OpenAI-compatible, Anthropic or local Ollama
+//!   behind the `LlmClient` trait (`LLM_BACKEND`)
+//! - Reddit (backup): Recent posts from commodity subreddits
+//! - RSS/Atom news feeds: Palm oil headlines weighted per feed
+//! - Providers: every source behind the `SentimentProvider` trait, read in
+//!   priority order or aggregated with per-provider weights
+//! - Fundamentals: weather, El Niño and MPOB series turned into a bias score
+//! - Market brief: long-form Perplexity report stored next to the score
+//! - Sentiment series: historical readings replayed by the backtester
 
+pub mod fundamentals;
+pub mod llm;
+pub mod market_brief;
 pub mod perplexity;
+pub mod provider;
+pub mod reddit;
+pub mod rss;
 pub mod sentiment;
 pub mod sentiment_cache;
+pub mod sentiment_series;
+pub mod source_policy;
 pub mod twitter;
+pub mod twitter_api;
 
+pub use fundamentals::{
+    FundamentalBias, FundamentalReading, FundamentalSeries, FundamentalsClient, FundamentalsConfig,
+    SeriesField,
+};
+pub use llm::{llm_client, LlmAnswer, LlmClient, LlmFuture, LlmRequest};
+pub use market_brief::{MarketBrief, MarketBriefSchedule};
 pub use perplexity::PerplexityClient;
+pub use provider::{
+    ProviderReading, SentimentAggregator, SentimentBreakdown, SentimentProvider,
+    EMPTY_SCRAPE_CONFIDENCE,
+};
+pub use reddit::{RedditConfig, RedditPost, RedditScraper};
+pub use rss::{NewsFeed, NewsItem, RssConfig, RssNewsClient};
 pub use sentiment::{SentimentAnalyzer, SentimentResult, SentimentType};
 pub use sentiment_cache::SentimentCache;
+pub use sentiment_series::{SentimentPoint, SentimentSeries};
+pub use source_policy::SourcePolicy;
 pub use twitter::TwitterScraper;
+pub use twitter_api::{TwitterApiClient, TwitterApiConfig};
@@ -2,21 +2,38 @@
 //!
 //! Uses the Perplexity Sonar model to search the web for current market sentiment.
 //! Includes in-memory caching with TTL to avoid rate limits and reduce API costs.
+//!
+//! Model, temperature, token budget and the prompts come from
+//! `PerplexityConfig`; custom prompt templates use `{symbol}` for the traded
+//! symbol, so the same query can be pointed at soybean oil or a futures spread.
+//!
+//! The model call goes through an `LlmClient` (see `llm`), so `LLM_BACKEND`
+//! can send the same prompts to an OpenAI-compatible API, Anthropic or a
+//! local Ollama model instead.
 
 use crate::config::PerplexityConfig;
 use crate::error::{BotError, PerplexityError, Result};
+use crate::modules::scraper::llm::{llm_client, LlmAnswer, LlmClient, LlmRequest};
+use crate::modules::scraper::market_brief::MarketBrief;
 use crate::modules::scraper::sentiment::{SentimentAnalyzer, SentimentResult};
 use crate::modules::scraper::sentiment_cache::SentimentCache;
+use crate::modules::scraper::source_policy::SourcePolicy;
 use crate::modules::security::ApiRateLimiter;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info};
+
+/// Built-in system message
+const DEFAULT_SYSTEM_PROMPT: &str =
+    "You are an aggressive commodities trader specializing in {symbol}. \
+     You look for short-term momentum plays and take decisive positions. \
+     Explicitly incorporate social/trader sentiment from X/Twitter, StockTwits, Reddit, \
+     and trading forums. \
+     Provide concise, data-driven analysis. Always include a numerical sentiment score. \
+     Never sit on the fence - if there's any edge, take a strong directional view.";
 
-/// Build a market sentiment prompt dynamically based on the trading symbol
-fn build_sentiment_prompt(symbol: &str) -> String {
-    format!(
-        r#"Analyze the SHORT-TERM market sentiment for {symbol} RIGHT NOW (next 5-30 minutes).
+/// Built-in short-term sentiment prompt
+const DEFAULT_SENTIMENT_PROMPT: &str = r#"Analyze the SHORT-TERM market sentiment for {symbol} RIGHT NOW (next 5-30 minutes).
 
 Focus on INTRADAY factors only:
 1. Latest price action and intraday technical levels for {symbol} (support/resistance, recent candles)
@@ -37,55 +54,43 @@ Format your response as:
 SENTIMENT_SCORE: [number]
 CONFIDENCE: [low/medium/high]
 SOCIAL_SENTIMENT: [bullish/bearish/neutral + 1 sentence]
-SUMMARY: [1-2 sentences on immediate direction]"#
-    )
+SUMMARY: [1-2 sentences on immediate direction]"#;
+
+/// Built-in long-form market brief prompt (supply/demand, MPOB, technicals)
+const DEFAULT_MARKET_BRIEF_PROMPT: &str = r#"Write a pre-session market brief for {symbol} covering the next 1-3 trading days.
+
+Cover, with concrete numbers where available:
+1. Supply and demand: production, exports, import demand from key buyers, inventories
+2. MPOB expectations: consensus for the next MPOB report (stocks, output, exports) and any surprises in the last one
+3. Technical levels: key support/resistance, recent range, trend on daily and hourly charts
+4. Key risks: currency moves, competing oils, weather, policy changes
+
+Format your response EXACTLY as:
+SUPPLY_DEMAND: [3-5 sentences]
+MPOB_EXPECTATIONS: [2-4 sentences]
+TECHNICAL_LEVELS: [support/resistance levels and trend]
+KEY_RISKS: [2-3 bullet points]
+SENTIMENT_SCORE: [number from -100 to +100]
+CONFIDENCE: [low/medium/high]
+SUMMARY: [2 sentences on the expected direction]"#;
+
+/// Token budget for the long-form brief (sentiment queries use `max_tokens`)
+const MARKET_BRIEF_MAX_TOKENS: u32 = 1500;
+
+/// Fill the `{symbol}` placeholders of a prompt template
+fn render_prompt(template: &str, symbol: &str) -> String {
+    template.replace("{symbol}", symbol)
 }
 
-/// Perplexity API client with caching
+/// LLM sentiment client with caching (Perplexity unless configured otherwise)
 pub struct PerplexityClient {
-    client: reqwest::Client,
+    llm: Arc<dyn LlmClient>,
     config: PerplexityConfig,
     sentiment_analyzer: SentimentAnalyzer,
     cache: SentimentCache,
     rate_limiter: Arc<ApiRateLimiter>,
     symbol: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<Message>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    temperature: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    max_tokens: Option<u32>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Message {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatResponse {
-    choices: Vec<Choice>,
-    #[serde(default)]
-    usage: Option<Usage>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Choice {
-    message: Message,
-    #[allow(dead_code)]
-    finish_reason: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Usage {
-    prompt_tokens: u32,
-    completion_tokens: u32,
-    total_tokens: u32,
+    source_policy: SourcePolicy,
 }
 
 impl PerplexityClient {
@@ -103,35 +108,68 @@ impl PerplexityClient {
 
     /// Create a new Perplexity API client with custom cache
     pub fn with_cache(config: PerplexityConfig, cache: SentimentCache, rate_limiter: Arc<ApiRateLimiter>) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .unwrap_or_else(|e| {
-                warn!("Failed to build HTTP client: {}. Falling back to default client.", e);
-                reqwest::Client::new()
-            });
-
         Self {
-            client,
+            llm: llm_client(&config),
             config,
             sentiment_analyzer: SentimentAnalyzer::new(),
             cache,
             rate_limiter,
             symbol: "SUGARRAW".to_string(),
+            source_policy: SourcePolicy::from_env(),
         }
     }
 
+    /// Send the prompts to `llm` instead of the configured backend
+    pub fn with_llm(mut self, llm: Arc<dyn LlmClient>) -> Self {
+        self.llm = llm;
+        self
+    }
+
+    /// Name of the backend answering the prompts (`perplexity`, `ollama`, ...)
+    pub fn backend_name(&self) -> &str {
+        self.llm.name()
+    }
+
+    /// Override the citation domain policy
+    pub fn with_source_policy(mut self, policy: SourcePolicy) -> Self {
+        self.source_policy = policy;
+        self
+    }
+
+    /// Sentiment prompt for the current symbol
+    pub fn sentiment_prompt(&self) -> String {
+        let template = self.config.prompts.sentiment.as_deref();
+        render_prompt(template.unwrap_or(DEFAULT_SENTIMENT_PROMPT), &self.symbol)
+    }
+
+    /// Market brief prompt for the current symbol
+    pub fn market_brief_prompt(&self) -> String {
+        let template = self.config.prompts.market_brief.as_deref();
+        render_prompt(template.unwrap_or(DEFAULT_MARKET_BRIEF_PROMPT), &self.symbol)
+    }
+
+    /// System message for the current symbol
+    fn system_prompt(&self) -> String {
+        let template = self.config.prompts.system.as_deref();
+        render_prompt(template.unwrap_or(DEFAULT_SYSTEM_PROMPT), &self.symbol)
+    }
+
     /// Get cached sentiment or fetch from API if cache miss/expired
     pub async fn get_cached_sentiment(&self) -> Result<SentimentResult> {
-        let prompt = build_sentiment_prompt(&self.symbol);
-        if let Some(score) = self.cache.get(&prompt) {
-            info!("Using cached sentiment for {} (score: {})", self.symbol, score);
-            return Ok(SentimentResult::new(score, "perplexity_cache"));
+        let prompt = self.sentiment_prompt();
+        if let Some(result) = self.cache.get(&prompt) {
+            info!("Using cached sentiment for {} (score: {})", self.symbol, result.score);
+            return Ok(result);
         }
 
-        info!("Cache miss - fetching fresh sentiment for {} from Perplexity API", self.symbol);
+        info!(
+            "Cache miss - fetching fresh sentiment for {} from {} ({})",
+            self.symbol,
+            self.llm.name(),
+            self.llm.model()
+        );
         let result = self.get_market_sentiment_uncached().await?;
-        self.cache.set(&prompt, result.score);
+        self.cache.set(&prompt, result.clone());
         Ok(result)
     }
 
@@ -142,104 +180,91 @@ impl PerplexityClient {
 
     /// Direct API call without cache (for testing or force refresh)
     pub async fn get_market_sentiment_uncached(&self) -> Result<SentimentResult> {
-        let prompt = build_sentiment_prompt(&self.symbol);
-        let response = self.query(&prompt).await?;
-        self.parse_sentiment_response(&response)
+        let prompt = self.sentiment_prompt();
+        let answer = self.chat(&prompt, self.config.max_tokens).await?;
+        let result = self.parse_sentiment_response(&answer.content)?;
+        Ok(self.apply_source_policy(result, &answer.citations))
+    }
+
+    /// Ask Perplexity for a long-form market brief (never cached)
+    pub async fn get_market_brief(&self) -> Result<MarketBrief> {
+        let prompt = self.market_brief_prompt();
+        let max_tokens = MARKET_BRIEF_MAX_TOKENS.max(self.config.max_tokens);
+        let answer = self.chat(&prompt, max_tokens).await?;
+        let sentiment = self.parse_sentiment_response(&answer.content)?;
+        let sentiment = self.apply_source_policy(sentiment, &answer.citations);
+
+        Ok(MarketBrief::new(
+            &self.symbol,
+            sentiment.score,
+            sentiment.confidence,
+            answer.content,
+        ))
     }
 
     /// Query Perplexity with a custom prompt
     pub async fn query(&self, prompt: &str) -> Result<String> {
-        // Wait for rate limit before making request
-        self.rate_limiter.wait_for_rate_limit().await;
-
-        let system_prompt = format!(
-            "You are an aggressive commodities trader specializing in {}. \
-            You look for short-term momentum plays and take decisive positions. \
-            Explicitly incorporate social/trader sentiment from X/Twitter, StockTwits, Reddit, and trading forums. \
-            Provide concise, data-driven analysis. Always include a numerical sentiment score. \
-            Never sit on the fence - if there's any edge, take a strong directional view.",
-            self.symbol
-        );
-
-        let request = ChatRequest {
-            model: self.config.model.clone(),
-            messages: vec![
-                Message {
-                    role: "system".to_string(),
-                    content: system_prompt.clone(),
-                },
-                Message {
-                    role: "user".to_string(),
-                    content: prompt.to_string(),
-                },
-            ],
-            temperature: Some(0.3),
-            max_tokens: Some(500),
-        };
+        Ok(self.chat(prompt, self.config.max_tokens).await?.content)
+    }
 
-        debug!("Sending request to Perplexity API");
-
-        let response = self
-            .client
-            .post(&self.config.endpoint)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                error!("Perplexity API request failed: {}", e);
-                BotError::Perplexity(PerplexityError::RequestFailed(e.to_string()))
-            })?;
-
-        let status = response.status();
-        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-            warn!("Perplexity API rate limited (429)");
-            self.rate_limiter.record_failure().await;
+    /// Send a prompt to the LLM backend and return the answer with its citations
+    async fn chat(&self, prompt: &str, max_tokens: u32) -> Result<LlmAnswer> {
+        // Queue for a rate-limit slot; treat a full queue like an HTTP 429
+        if self.rate_limiter.acquire().await.is_err() {
             return Err(BotError::Perplexity(PerplexityError::RateLimited));
         }
 
-        if status == reqwest::StatusCode::UNAUTHORIZED {
-            error!("Invalid Perplexity API key");
-            return Err(BotError::Perplexity(PerplexityError::InvalidApiKey));
-        }
-
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            error!("Perplexity API error: {} - {}", status, error_text);
-            self.rate_limiter.record_failure().await;
-            return Err(BotError::Perplexity(PerplexityError::RequestFailed(
-                format!("{}: {}", status, error_text),
-            )));
-        }
-
-        // Record success for successful responses
+        debug!(
+            "Sending request to {} (model {}, temperature {})",
+            self.llm.name(),
+            self.llm.model(),
+            self.config.temperature
+        );
+        let answer = match self.llm.complete(self.llm_request(prompt, max_tokens)).await {
+            Ok(answer) => answer,
+            Err(err) => {
+                if !matches!(err, BotError::Perplexity(PerplexityError::InvalidApiKey)) {
+                    self.rate_limiter.record_failure().await;
+                }
+                return Err(err);
+            }
+        };
         self.rate_limiter.record_success().await;
 
-        let chat_response: ChatResponse = response.json().await.map_err(|e| {
-            error!("Failed to parse Perplexity response: {}", e);
-            BotError::Perplexity(PerplexityError::ParseError(e.to_string()))
-        })?;
+        info!(
+            "Received {} response ({} chars, {} citations)",
+            self.llm.name(),
+            answer.content.len(),
+            answer.citations.len()
+        );
+        Ok(answer)
+    }
 
-        if let Some(usage) = &chat_response.usage {
-            debug!(
-                "Perplexity API tokens used: {} (prompt: {}, completion: {})",
-                usage.total_tokens, usage.prompt_tokens, usage.completion_tokens
-            );
+    /// Request for `prompt` with the configured system message and model settings
+    fn llm_request(&self, prompt: &str, max_tokens: u32) -> LlmRequest {
+        LlmRequest {
+            system: self.system_prompt(),
+            prompt: prompt.to_string(),
+            temperature: self.config.temperature,
+            max_tokens,
         }
+    }
 
-        let content = chat_response
-            .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .ok_or_else(|| {
-                BotError::Perplexity(PerplexityError::ParseError(
-                    "No response content".to_string(),
-                ))
-            })?;
-
-        info!("Received Perplexity response ({} chars)", content.len());
-        Ok(content)
+    /// Scale confidence down when the answer leans on blocked or unknown domains
+    fn apply_source_policy(&self, result: SentimentResult, citations: &[String]) -> SentimentResult {
+        let factor = self.source_policy.confidence_factor(citations);
+        if factor >= 1.0 {
+            return result;
+        }
+
+        let confidence = result.confidence * factor;
+        info!(
+            "{} confidence reduced {:.2} -> {:.2} by citation policy",
+            self.llm.name(),
+            result.confidence,
+            confidence
+        );
+        result.with_confidence(confidence)
     }
 
     /// Parse the sentiment score from Perplexity's response
@@ -273,13 +298,16 @@ impl PerplexityClient {
             0.5
         };
 
-        let result = SentimentResult::new(score.clamp(-100, 100), "perplexity")
+        let result = SentimentResult::new(score.clamp(-100, 100), self.llm.name())
             .with_confidence(confidence)
             .with_raw_text(response.to_string());
 
         info!(
-            "Perplexity sentiment: {:?} (score: {}, confidence: {:.1})",
-            result.sentiment_type, result.score, result.confidence
+            "{} sentiment: {:?} (score: {}, confidence: {:.1})",
+            self.llm.name(),
+            result.sentiment_type,
+            result.score,
+            result.confidence
         );
 
         Ok(result)
@@ -302,9 +330,13 @@ mod tests {
 
     fn test_config() -> PerplexityConfig {
         PerplexityConfig {
+            backend: Default::default(),
             api_key: "test".to_string(),
             endpoint: "https://api.perplexity.ai".to_string(),
             model: "sonar".to_string(),
+            temperature: 0.3,
+            max_tokens: 500,
+            prompts: Default::default(),
         }
     }
 
@@ -337,6 +369,62 @@ mod tests {
         let rate_limiter = Arc::new(ApiRateLimiter::for_perplexity());
         let client = PerplexityClient::with_cache(test_config(), cache, rate_limiter);
 
-        assert_eq!(client.cache().get(&build_sentiment_prompt("SUGARRAW")), None);
+        assert!(client.cache().get(&client.sentiment_prompt()).is_none());
+    }
+
+    #[test]
+    fn test_market_brief_prompt_requests_all_sections() {
+        let prompt = render_prompt(DEFAULT_MARKET_BRIEF_PROMPT, "FCPO");
+        for section in crate::modules::scraper::market_brief::BRIEF_SECTIONS {
+            assert!(prompt.contains(&format!("{}:", section)), "missing {}", section);
+        }
+        assert!(prompt.contains("SENTIMENT_SCORE:"));
+    }
+
+    #[test]
+    fn test_prompt_templates_and_model_settings() {
+        let mut config = test_config();
+        config.model = "sonar-pro".to_string();
+        config.temperature = 0.1;
+        config.max_tokens = 300;
+        config.prompts.sentiment = Some(
+            "Soybean oil vs {symbol} spread outlook. SENTIMENT_SCORE for {symbol}?".to_string(),
+        );
+        let rate_limiter = Arc::new(ApiRateLimiter::for_perplexity());
+        let client = PerplexityClient::with_symbol(config, rate_limiter, "FCPO");
+
+        assert_eq!(
+            client.sentiment_prompt(),
+            "Soybean oil vs FCPO spread outlook. SENTIMENT_SCORE for FCPO?"
+        );
+        // Unset templates keep the built-in prompts
+        assert!(client.market_brief_prompt().contains("pre-session market brief for FCPO"));
+
+        let request = client.llm_request(&client.sentiment_prompt(), 300);
+        assert_eq!(client.llm.model(), "sonar-pro");
+        assert_eq!(request.temperature, 0.1);
+        assert_eq!(request.max_tokens, 300);
+        assert!(request.system.contains("specializing in FCPO"));
+        assert!(!request.system.contains("{symbol}"));
+    }
+
+    #[test]
+    fn test_source_policy_reduces_confidence() {
+        let rate_limiter = Arc::new(ApiRateLimiter::for_perplexity());
+        let client = PerplexityClient::new(test_config(), rate_limiter).with_source_policy(
+            SourcePolicy::new(vec!["reuters.com".to_string()], vec!["junk.example".to_string()]),
+        );
+
+        let result = client
+            .parse_sentiment_response("SENTIMENT_SCORE: 50\nCONFIDENCE: high")
+            .unwrap();
+        let trusted = client.apply_source_policy(
+            result.clone(),
+            &["https://www.reuters.com/markets".to_string()],
+        );
+        let blocked = client.apply_source_policy(result, &["https://junk.example/x".to_string()]);
+
+        assert!((trusted.confidence - 0.9).abs() < 1e-9);
+        assert!(blocked.confidence < 0.1);
     }
 }
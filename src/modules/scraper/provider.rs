@@ -0,0 +1,467 @@
+//! Pluggable sentiment providers and their weighted aggregate
+//!
+//! Every sentiment source (Perplexity, Twitter, Reddit, news feeds, ...)
+//! implements `SentimentProvider`. The `SentimentAggregator` owns the
+//! configured providers, in priority order, and reads them in one of two ways:
+//! - `aggregate`: all providers at once, each reading weighted by its
+//!   confidence times the provider's weight (`SENTIMENT_PROVIDER_WEIGHTS`,
+//!   default 1), with disagreement between providers lowering the confidence
+//!   of the result (`SENTIMENT_MULTI_SOURCE`)
+//! - `first_available`: one provider after the other until one answers with
+//!   more than the confidence of an empty scrape (the default)
+//!
+//! Both return a `SentimentBreakdown` with each provider's reading or error
+//! and its share of the result, exported to the metrics and the dashboards.
+//!
+//! `SENTIMENT_PROVIDER_WEIGHTS` is a comma-separated `name:weight` list, e.g.
+//! `perplexity:1,twitter:0.5,reddit:0.5,rss:0.8`. A weight of 0 disables the
+//! provider.
+
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::perplexity::PerplexityClient;
+use crate::modules::scraper::reddit::RedditScraper;
+use crate::modules::scraper::rss::RssNewsClient;
+use crate::modules::scraper::sentiment::{SentimentAnalyzer, SentimentResult};
+use crate::modules::scraper::twitter::TwitterScraper;
+use crate::modules::scraper::twitter_api::TwitterApiClient;
+
+/// Confidence of a scrape that found nothing (neutral placeholder reading)
+pub const EMPTY_SCRAPE_CONFIDENCE: f64 = 0.1;
+
+/// Longest wait for one provider before it counts as failed
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Future returned by `SentimentProvider::fetch`
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<SentimentResult>> + Send + 'a>>;
+
+/// A source of sentiment readings
+pub trait SentimentProvider: Send + Sync {
+    /// Short name used in logs, metrics and `SENTIMENT_PROVIDER_WEIGHTS`
+    fn name(&self) -> &str;
+
+    /// Fetch a fresh reading
+    fn fetch(&self) -> ProviderFuture<'_>;
+}
+
+impl SentimentProvider for PerplexityClient {
+    /// The LLM backend's name, so `ollama:0.5` weighs a local model
+    fn name(&self) -> &str {
+        self.backend_name()
+    }
+
+    fn fetch(&self) -> ProviderFuture<'_> {
+        Box::pin(self.get_market_sentiment())
+    }
+}
+
+impl SentimentProvider for TwitterScraper {
+    fn name(&self) -> &str {
+        "twitter"
+    }
+
+    fn fetch(&self) -> ProviderFuture<'_> {
+        Box::pin(self.get_sentiment())
+    }
+}
+
+/// Same provider name as the scraper: the two are alternatives
+impl SentimentProvider for TwitterApiClient {
+    fn name(&self) -> &str {
+        "twitter"
+    }
+
+    fn fetch(&self) -> ProviderFuture<'_> {
+        Box::pin(self.get_sentiment())
+    }
+}
+
+impl SentimentProvider for RedditScraper {
+    fn name(&self) -> &str {
+        "reddit"
+    }
+
+    fn fetch(&self) -> ProviderFuture<'_> {
+        Box::pin(self.get_sentiment())
+    }
+}
+
+impl SentimentProvider for RssNewsClient {
+    fn name(&self) -> &str {
+        "rss"
+    }
+
+    fn fetch(&self) -> ProviderFuture<'_> {
+        Box::pin(self.get_sentiment())
+    }
+}
+
+/// One provider's part in a sentiment result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderReading {
+    pub provider: String,
+    /// Configured provider weight
+    pub weight: f64,
+    /// Score (-100..100); `None` when the provider failed
+    pub score: Option<i32>,
+    /// Reported confidence (0.0..1.0)
+    pub confidence: f64,
+    /// Fraction of the result that came from this provider (0.0..1.0)
+    pub share: f64,
+    /// Why the provider gave no reading
+    pub error: Option<String>,
+}
+
+/// A sentiment result and the readings it was built from
+#[derive(Debug, Clone)]
+pub struct SentimentBreakdown {
+    pub result: SentimentResult,
+    /// Every provider queried, in priority order
+    pub providers: Vec<ProviderReading>,
+}
+
+/// Queries the configured providers and combines their readings
+pub struct SentimentAggregator {
+    /// Providers in priority order, with their weights
+    providers: Vec<(Arc<dyn SentimentProvider>, f64)>,
+    weights: HashMap<String, f64>,
+    analyzer: SentimentAnalyzer,
+}
+
+impl SentimentAggregator {
+    /// Aggregator with `weights` by provider name (missing = 1.0)
+    pub fn new(weights: HashMap<String, f64>) -> Self {
+        Self {
+            providers: Vec::new(),
+            weights,
+            analyzer: SentimentAnalyzer::new(),
+        }
+    }
+
+    /// Provider weights from `SENTIMENT_PROVIDER_WEIGHTS`, for [`Self::new`]
+    pub fn weights_from_env() -> Result<HashMap<String, f64>> {
+        parse_weights(&env::var("SENTIMENT_PROVIDER_WEIGHTS").unwrap_or_default())
+    }
+
+    /// Add a provider after the ones already added; skipped at weight 0
+    pub fn add(&mut self, provider: Arc<dyn SentimentProvider>) {
+        let weight = self.weight_of(provider.name());
+        if weight <= 0.0 {
+            info!("Sentiment provider {} disabled (weight 0)", provider.name());
+            return;
+        }
+        self.providers.push((provider, weight));
+    }
+
+    /// Builder form of `add`
+    pub fn with(mut self, provider: Arc<dyn SentimentProvider>) -> Self {
+        self.add(provider);
+        self
+    }
+
+    fn weight_of(&self, name: &str) -> f64 {
+        self.weights.get(name).copied().unwrap_or(1.0)
+    }
+
+    /// Names of the active providers, in priority order
+    pub fn provider_names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|(provider, _)| provider.name().to_string())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Query every provider concurrently and combine the readings; `None`
+    /// when none answered
+    pub async fn aggregate(&self) -> Option<SentimentBreakdown> {
+        let mut tasks = JoinSet::new();
+        for (idx, (provider, _)) in self.providers.iter().enumerate() {
+            let provider = Arc::clone(provider);
+            tasks.spawn(async move { (idx, fetch_with_timeout(provider.as_ref()).await) });
+        }
+
+        let mut results: Vec<Option<Result<SentimentResult>>> =
+            (0..self.providers.len()).map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((idx, result)) => results[idx] = Some(result),
+                Err(err) => warn!("Sentiment provider task failed: {}", err),
+            }
+        }
+
+        let readings = self
+            .providers
+            .iter()
+            .zip(results)
+            .map(|((provider, weight), result)| {
+                let result = result.unwrap_or_else(|| {
+                    Err(BotError::Other(format!("{} task aborted", provider.name())))
+                });
+                if let Err(err) = &result {
+                    warn!("{} sentiment failed: {}", provider.name(), err);
+                }
+                (provider.name().to_string(), *weight, result)
+            })
+            .collect();
+        self.combine(readings)
+    }
+
+    /// Query the providers in priority order until one gives a real reading;
+    /// an empty scrape is only used when nothing better answers
+    pub async fn first_available(&self) -> Option<SentimentBreakdown> {
+        let mut readings = Vec::new();
+        let mut chosen = None;
+        for (provider, weight) in &self.providers {
+            let result = fetch_with_timeout(provider.as_ref()).await;
+            if let Err(err) = &result {
+                warn!("{} sentiment failed: {}", provider.name(), err);
+            }
+            let answered = matches!(&result, Ok(r) if r.confidence > EMPTY_SCRAPE_CONFIDENCE);
+            readings.push((provider.name().to_string(), *weight, result));
+            if answered {
+                chosen = Some(readings.len() - 1);
+                break;
+            }
+        }
+        let chosen = chosen.or_else(|| readings.iter().position(|(_, _, r)| r.is_ok()))?;
+
+        let providers = readings
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, weight, result))| {
+                provider_reading(name, *weight, result, if idx == chosen { 1.0 } else { 0.0 })
+            })
+            .collect();
+        let result = match readings.swap_remove(chosen).2 {
+            Ok(result) => result,
+            Err(_) => return None,
+        };
+        Some(SentimentBreakdown { result, providers })
+    }
+
+    /// Weighted blend of `(provider, weight, reading)`s; `None` without a
+    /// single reading
+    pub fn combine(
+        &self,
+        readings: Vec<(String, f64, Result<SentimentResult>)>,
+    ) -> Option<SentimentBreakdown> {
+        let ok: Vec<(&SentimentResult, f64)> = readings
+            .iter()
+            .filter_map(|(_, weight, result)| result.as_ref().ok().map(|r| (r, *weight)))
+            .collect();
+        if ok.is_empty() {
+            return None;
+        }
+
+        // Each reading counts with its confidence times its provider's weight
+        let effective: Vec<f64> = ok.iter().map(|(r, w)| r.confidence * w).collect();
+        let total: f64 = effective.iter().sum();
+        let share = |r: &SentimentResult, weight: f64| {
+            if total > 0.0 {
+                r.confidence * weight / total
+            } else {
+                1.0 / ok.len() as f64
+            }
+        };
+
+        let result = if ok.len() == 1 {
+            ok[0].0.clone()
+        } else {
+            let score = ok
+                .iter()
+                .map(|(r, w)| r.score as f64 * share(r, *w))
+                .sum::<f64>();
+            // Dispersion only depends on the ratios between the weights
+            let max = effective.iter().cloned().fold(0.0, f64::max);
+            let scaled: Vec<SentimentResult> = ok
+                .iter()
+                .zip(&effective)
+                .map(|((r, _), e)| {
+                    r.clone()
+                        .with_confidence(if max > 0.0 { e / max } else { 0.0 })
+                })
+                .collect();
+            let dispersion = self.analyzer.dispersion(&scaled);
+            let weight_sum: f64 = ok.iter().map(|(_, w)| w).sum();
+            let confidence = ok.iter().map(|(r, w)| r.confidence * w).sum::<f64>() / weight_sum;
+            let sources: Vec<&str> = ok.iter().map(|(r, _)| r.source.as_str()).collect();
+            SentimentResult::new(
+                score.round() as i32,
+                &format!("blend({})", sources.join("+")),
+            )
+            .with_confidence(confidence * (1.0 - dispersion))
+            .with_dispersion(dispersion)
+        };
+
+        let providers = readings
+            .iter()
+            .map(|(name, weight, reading)| {
+                let part = reading.as_ref().map_or(0.0, |r| share(r, *weight));
+                provider_reading(name, *weight, reading, part)
+            })
+            .collect();
+        Some(SentimentBreakdown { result, providers })
+    }
+}
+
+async fn fetch_with_timeout(provider: &dyn SentimentProvider) -> Result<SentimentResult> {
+    match tokio::time::timeout(PROVIDER_TIMEOUT, provider.fetch()).await {
+        Ok(result) => result,
+        Err(_) => Err(BotError::Other(format!(
+            "{} sentiment timed out after {}s",
+            provider.name(),
+            PROVIDER_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+fn provider_reading(
+    name: &str,
+    weight: f64,
+    result: &Result<SentimentResult>,
+    share: f64,
+) -> ProviderReading {
+    match result {
+        Ok(reading) => ProviderReading {
+            provider: name.to_string(),
+            weight,
+            score: Some(reading.score),
+            confidence: reading.confidence,
+            share,
+            error: None,
+        },
+        Err(err) => ProviderReading {
+            provider: name.to_string(),
+            weight,
+            score: None,
+            confidence: 0.0,
+            share: 0.0,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+/// Parse `name:weight,name:weight`
+fn parse_weights(raw: &str) -> Result<HashMap<String, f64>> {
+    let mut weights = HashMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let weight = entry.split_once(':').and_then(|(name, weight)| {
+            let weight = weight.trim().parse::<f64>().ok()?;
+            (weight.is_finite() && weight >= 0.0).then(|| (name.trim().to_lowercase(), weight))
+        });
+        match weight {
+            Some((name, weight)) => weights.insert(name, weight),
+            None => {
+                return Err(BotError::Config(format!(
+                    "invalid SENTIMENT_PROVIDER_WEIGHTS entry {:?} (expected name:weight)",
+                    entry
+                )))
+            }
+        };
+    }
+    Ok(weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, Option<(i32, f64)>);
+
+    impl SentimentProvider for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn fetch(&self) -> ProviderFuture<'_> {
+            let result = match self.1 {
+                Some((score, confidence)) => {
+                    Ok(SentimentResult::new(score, self.0).with_confidence(confidence))
+                }
+                None => Err(BotError::Other("offline".into())),
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    fn aggregator(weights: &str, providers: Vec<Fixed>) -> SentimentAggregator {
+        let mut aggregator = SentimentAggregator::new(parse_weights(weights).unwrap());
+        for provider in providers {
+            aggregator.add(Arc::new(provider));
+        }
+        aggregator
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_weights_by_confidence_and_provider() {
+        let aggregator = aggregator(
+            "news:2, muted:0",
+            vec![
+                Fixed("news", Some((60, 0.5))),
+                Fixed("forum", Some((-20, 0.5))),
+                Fixed("down", None),
+                Fixed("muted", Some((-100, 1.0))),
+            ],
+        );
+        assert_eq!(aggregator.provider_names(), vec!["news", "forum", "down"]);
+
+        let breakdown = aggregator.aggregate().await.unwrap();
+        // news counts 1.0, forum 0.5: (60 - 10) / 1.5
+        assert_eq!(breakdown.result.score, 33);
+        assert!(breakdown.result.dispersion.unwrap() > 0.0);
+        assert!(breakdown.result.confidence < 0.5);
+
+        let shares: Vec<f64> = breakdown.providers.iter().map(|p| p.share).collect();
+        assert!((shares[0] - 2.0 / 3.0).abs() < 1e-9);
+        assert!((shares[1] - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(shares[2], 0.0);
+        assert_eq!(breakdown.providers[2].score, None);
+        assert!(breakdown.providers[2].error.is_some());
+
+        assert!(parse_weights("perplexity=1").is_err());
+        assert!(parse_weights("twitter:-1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_first_available_skips_failures_and_empty_scrapes() {
+        let aggregator = aggregator(
+            "",
+            vec![
+                Fixed("primary", None),
+                Fixed("empty", Some((0, EMPTY_SCRAPE_CONFIDENCE))),
+                Fixed("backup", Some((-40, 0.6))),
+                Fixed("never", Some((80, 0.9))),
+            ],
+        );
+        let breakdown = aggregator.first_available().await.unwrap();
+        assert_eq!(breakdown.result.score, -40);
+        assert_eq!(breakdown.providers.len(), 3);
+        assert_eq!(breakdown.providers[2].share, 1.0);
+
+        // Only empty scrapes: the first one is used
+        let empty = aggregator(
+            "",
+            vec![Fixed("empty", Some((0, 0.1))), Fixed("down", None)],
+        );
+        let breakdown = empty.first_available().await.unwrap();
+        assert_eq!(breakdown.result.source, "empty");
+        assert!(aggregator("", vec![Fixed("down", None)])
+            .first_available()
+            .await
+            .is_none());
+    }
+}
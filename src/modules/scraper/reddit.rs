@@ -0,0 +1,396 @@
+//! Reddit scraper for backup sentiment analysis
+//!
+//! Reads the newest posts of the configured subreddits through Reddit's public
+//! JSON listings (`/r/<name>/new.json`, no API key), keeps the recent ones that
+//! mention one of the keywords, and scores title and body with the same
+//! keyword `SentimentAnalyzer` as the Twitter backup. Upvoted posts weigh a
+//! little more in the aggregate.
+//!
+//! Enabled with `REDDIT_SENTIMENT_ENABLED`. When Perplexity fails, Reddit is
+//! tried after Twitter; with `SENTIMENT_MULTI_SOURCE` it is blended
+//! in with the other sources.
+
+use std::env;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::sentiment::{SentimentAnalyzer, SentimentResult};
+use crate::modules::security::ApiRateLimiter;
+
+/// Subreddits read when `REDDIT_SUBREDDITS` is unset
+const DEFAULT_SUBREDDITS: &str = "Commodities,PalmOil,agriculture";
+
+/// Keywords a post must contain when `REDDIT_KEYWORDS` is unset
+const DEFAULT_KEYWORDS: &str = "palm oil,palm-oil,cpo,fcpo";
+
+/// Which posts are read
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedditConfig {
+    /// Subreddit names, without `r/`
+    pub subreddits: Vec<String>,
+    /// Lowercase keywords; a post must contain one (empty = every post)
+    pub keywords: Vec<String>,
+    /// Newest posts requested per subreddit (Reddit caps this at 100)
+    pub post_limit: usize,
+    /// Posts older than this are ignored
+    pub max_age: Duration,
+}
+
+impl Default for RedditConfig {
+    fn default() -> Self {
+        Self {
+            subreddits: parse_list(DEFAULT_SUBREDDITS),
+            keywords: parse_list(DEFAULT_KEYWORDS),
+            post_limit: 25,
+            max_age: Duration::hours(48),
+        }
+    }
+}
+
+impl RedditConfig {
+    /// Load from `REDDIT_*`; `None` unless `REDDIT_SENTIMENT_ENABLED` is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let enabled = env::var("REDDIT_SENTIMENT_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        let defaults = Self::default();
+        let list = |key: &str, default: Vec<String>| match env::var(key) {
+            Ok(raw) if !raw.trim().is_empty() => parse_list(&raw),
+            _ => default,
+        };
+        let number = |key: &str, default: u64| -> Result<u64> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        };
+        let config = Self {
+            subreddits: list("REDDIT_SUBREDDITS", defaults.subreddits)
+                .into_iter()
+                .map(|name| name.trim_start_matches("r/").to_string())
+                .collect(),
+            keywords: list("REDDIT_KEYWORDS", defaults.keywords),
+            post_limit: number("REDDIT_POST_LIMIT", defaults.post_limit as u64)? as usize,
+            max_age: Duration::hours(number("REDDIT_MAX_AGE_HOURS", 48)? as i64),
+        };
+        if config.subreddits.is_empty() || !(1..=100).contains(&config.post_limit) {
+            return Err(BotError::Config(
+                "REDDIT_SUBREDDITS must not be empty and REDDIT_POST_LIMIT must be 1-100".into(),
+            ));
+        }
+        Ok(Some(config))
+    }
+}
+
+/// Reddit post data structure
+#[derive(Debug, Clone)]
+pub struct RedditPost {
+    pub subreddit: String,
+    pub title: String,
+    pub text: String,
+    /// Net upvotes
+    pub score: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RedditPost {
+    /// Title and body, as scored
+    pub fn full_text(&self) -> String {
+        if self.text.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{}\n{}", self.title, self.text)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Listing {
+    data: ListingData,
+}
+
+#[derive(Deserialize)]
+struct ListingData {
+    children: Vec<ListingChild>,
+}
+
+#[derive(Deserialize)]
+struct ListingChild {
+    data: PostData,
+}
+
+#[derive(Deserialize)]
+struct PostData {
+    subreddit: String,
+    title: String,
+    #[serde(default)]
+    selftext: String,
+    #[serde(default)]
+    score: i64,
+    created_utc: f64,
+}
+
+/// Reddit scraper for commodity subreddits
+pub struct RedditScraper {
+    client: reqwest::Client,
+    config: RedditConfig,
+    sentiment_analyzer: SentimentAnalyzer,
+    rate_limiter: Arc<ApiRateLimiter>,
+}
+
+impl RedditScraper {
+    /// Create a new Reddit scraper
+    pub fn new(config: RedditConfig, rate_limiter: Arc<ApiRateLimiter>) -> Self {
+        // Reddit throttles generic user agents hard
+        let user_agent = format!(
+            "palm-oil-bot/{} (sentiment monitor)",
+            env!("CARGO_PKG_VERSION")
+        );
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .user_agent(user_agent)
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client: {}. Falling back to default client.",
+                    e
+                );
+                reqwest::Client::new()
+            });
+
+        Self {
+            client,
+            config,
+            sentiment_analyzer: SentimentAnalyzer::new(),
+            rate_limiter,
+        }
+    }
+
+    pub fn config(&self) -> &RedditConfig {
+        &self.config
+    }
+
+    /// Recent, relevant posts from all configured subreddits
+    pub async fn scrape_all(&self) -> Result<Vec<RedditPost>> {
+        let mut all_posts = Vec::new();
+
+        for subreddit in &self.config.subreddits {
+            match self.scrape_subreddit(subreddit).await {
+                Ok(posts) => {
+                    info!(
+                        "Scraped {} relevant posts from r/{}",
+                        posts.len(),
+                        subreddit
+                    );
+                    all_posts.extend(posts);
+                }
+                Err(e) => {
+                    warn!("Failed to scrape r/{}: {}", subreddit, e);
+                }
+            }
+        }
+
+        Ok(all_posts)
+    }
+
+    /// Newest posts of one subreddit, filtered by age and keywords
+    async fn scrape_subreddit(&self, subreddit: &str) -> Result<Vec<RedditPost>> {
+        // Queue for a rate-limit slot
+        self.rate_limiter
+            .acquire()
+            .await
+            .map_err(|e| BotError::Reddit(e.to_string()))?;
+
+        let url = format!(
+            "https://www.reddit.com/r/{}/new.json?limit={}&raw_json=1",
+            subreddit, self.config.post_limit
+        );
+        debug!("Fetching posts from: {}", url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| BotError::Reddit(format!("Request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            warn!("Reddit request failed: {}", response.status());
+            self.rate_limiter.record_failure().await;
+            return Err(BotError::Reddit(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+
+        // Record success
+        self.rate_limiter.record_success().await;
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| BotError::Reddit(format!("Failed to read response: {}", e)))?;
+
+        self.parse_listing(&body, Utc::now())
+    }
+
+    /// Parse a listing (`/new.json`) into the posts worth scoring at `now`
+    fn parse_listing(&self, body: &str, now: DateTime<Utc>) -> Result<Vec<RedditPost>> {
+        let listing: Listing = serde_json::from_str(body)
+            .map_err(|e| BotError::Reddit(format!("Invalid listing: {}", e)))?;
+
+        let posts = listing
+            .data
+            .children
+            .into_iter()
+            .filter_map(|child| {
+                let post = child.data;
+                let created_at = Utc.timestamp_opt(post.created_utc as i64, 0).single()?;
+                Some(RedditPost {
+                    subreddit: post.subreddit,
+                    title: post.title.trim().to_string(),
+                    text: post.selftext.trim().to_string(),
+                    score: post.score,
+                    created_at,
+                })
+            })
+            .filter(|post| now - post.created_at <= self.config.max_age)
+            .filter(|post| self.is_relevant(post))
+            .collect();
+
+        Ok(posts)
+    }
+
+    /// Whether the post mentions one of the keywords
+    fn is_relevant(&self, post: &RedditPost) -> bool {
+        if self.config.keywords.is_empty() {
+            return true;
+        }
+        let text = post.full_text().to_lowercase();
+        self.config
+            .keywords
+            .iter()
+            .any(|keyword| text.contains(keyword.as_str()))
+    }
+
+    /// Get aggregated sentiment from all scraped posts
+    ///
+    /// Fails when no relevant post was found, so the caller can move on to
+    /// the next source.
+    pub async fn get_sentiment(&self) -> Result<SentimentResult> {
+        let posts = self.scrape_all().await?;
+        self.score_posts(&posts)
+    }
+
+    fn score_posts(&self, posts: &[RedditPost]) -> Result<SentimentResult> {
+        if posts.is_empty() {
+            return Err(BotError::Reddit(
+                "no recent posts matching the keywords".into(),
+            ));
+        }
+
+        // Upvoted posts count up to three times, downvoted ones once
+        let mut results = Vec::new();
+        for post in posts {
+            let weight = 1 + (post.score.max(0) as f64).log10().floor().clamp(0.0, 2.0) as usize;
+            let result = self.sentiment_analyzer.analyze(&post.full_text());
+            results.extend(std::iter::repeat(result).take(weight));
+        }
+
+        let aggregated = self.sentiment_analyzer.aggregate(&results);
+        // Forum posts: lower confidence than scraped KOLs
+        let result = SentimentResult::new(aggregated.score, "reddit")
+            .with_confidence(aggregated.confidence * 0.6);
+
+        info!(
+            "Reddit sentiment from {} posts: {:?} (score: {})",
+            posts.len(),
+            result.sentiment_type,
+            result.score
+        );
+
+        Ok(result)
+    }
+}
+
+fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|item| item.trim().to_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scraper() -> RedditScraper {
+        RedditScraper::new(
+            RedditConfig::default(),
+            Arc::new(ApiRateLimiter::for_reddit()),
+        )
+    }
+
+    fn listing(posts: &[(&str, &str, i64, i64)]) -> String {
+        let children: Vec<serde_json::Value> = posts
+            .iter()
+            .map(|(title, body, score, created)| {
+                serde_json::json!({
+                    "kind": "t3",
+                    "data": {
+                        "subreddit": "Commodities",
+                        "title": title,
+                        "selftext": body,
+                        "score": score,
+                        "created_utc": *created as f64,
+                    }
+                })
+            })
+            .collect();
+        serde_json::json!({ "kind": "Listing", "data": { "children": children } }).to_string()
+    }
+
+    #[test]
+    fn test_parse_listing_keeps_recent_relevant_posts() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap();
+        let hour = 3_600;
+        let body = listing(&[
+            ("Palm oil exports surge", "", 120, now.timestamp() - hour),
+            (
+                "Gold outlook",
+                "Nothing on vegetable oils",
+                40,
+                now.timestamp() - hour,
+            ),
+            (
+                "CPO futures",
+                "Bursa FCPO falls",
+                3,
+                now.timestamp() - 72 * hour,
+            ),
+        ]);
+
+        let posts = scraper().parse_listing(&body, now).unwrap();
+        assert_eq!(posts.len(), 1);
+        assert_eq!(posts[0].title, "Palm oil exports surge");
+        assert_eq!(posts[0].score, 120);
+
+        assert!(scraper().parse_listing("<html>", now).is_err());
+    }
+
+    #[test]
+    fn test_no_posts_is_an_error() {
+        assert!(scraper().score_posts(&[]).is_err());
+    }
+}
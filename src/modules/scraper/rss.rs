@@ -0,0 +1,497 @@
+//! RSS/Atom news feed sentiment
+//!
+//! Polls the configured news feeds (commodity desks, the MPOC news page, ...),
+//! keeps the recent headlines that mention one of the keywords and scores them
+//! with the keyword `SentimentAnalyzer`. Each feed has a weight: a headline's
+//! confidence is scaled by its feed's weight before the headlines are
+//! aggregated, so a wire service can count for more than a blog.
+//!
+//! Feeds are `RSS_FEEDS`, `;`-separated `name|url|weight` entries (weight
+//! defaults to 1). Both RSS 2.0 (`<item>`, `<pubDate>`) and Atom (`<entry>`,
+//! `<updated>`) are read with a small tag scanner; CDATA and the common
+//! entities are decoded, anything else in the document is ignored.
+//!
+//! Enabled with `RSS_SENTIMENT_ENABLED`. When Perplexity fails the feeds are
+//! tried after the scrapers; with `SENTIMENT_MULTI_SOURCE` they are
+//! blended in with the other sources.
+
+use std::collections::HashSet;
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+use tracing::{debug, info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::sentiment::{SentimentAnalyzer, SentimentResult};
+
+/// Keywords a headline must contain when `RSS_KEYWORDS` is unset
+const DEFAULT_KEYWORDS: &str = "palm oil,palm-oil,cpo,fcpo,mpob";
+
+/// One news feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsFeed {
+    /// Label used in logs
+    pub name: String,
+    pub url: String,
+    /// Multiplier on the confidence of the feed's headlines
+    pub weight: f64,
+}
+
+impl NewsFeed {
+    /// Parse a `name|url|weight` entry (`url` alone, or without weight, works)
+    fn parse(entry: &str) -> Result<Self> {
+        let parts: Vec<&str> = entry.split('|').map(str::trim).collect();
+        let (name, url, weight) = match parts.as_slice() {
+            [url] => (*url, *url, None),
+            [name, url] => (*name, *url, None),
+            [name, url, weight] => (*name, *url, Some(*weight)),
+            _ => {
+                return Err(BotError::Config(format!(
+                    "invalid RSS_FEEDS entry {:?} (expected name|url|weight)",
+                    entry
+                )))
+            }
+        };
+        let weight = match weight {
+            Some(raw) => raw
+                .parse::<f64>()
+                .ok()
+                .filter(|w| w.is_finite() && *w > 0.0)
+                .ok_or_else(|| {
+                    BotError::Config(format!("invalid RSS_FEEDS weight for {}: {:?}", name, raw))
+                })?,
+            None => 1.0,
+        };
+        if url::Url::parse(url).is_err() {
+            return Err(BotError::Config(format!(
+                "invalid RSS_FEEDS url for {}: {:?}",
+                name, url
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            weight,
+        })
+    }
+}
+
+/// Which feeds and headlines are read
+#[derive(Debug, Clone, PartialEq)]
+pub struct RssConfig {
+    pub feeds: Vec<NewsFeed>,
+    /// Lowercase keywords; a headline must contain one (empty = every headline)
+    pub keywords: Vec<String>,
+    /// Headlines older than this are ignored (undated ones are kept)
+    pub max_age: Duration,
+}
+
+impl RssConfig {
+    /// Load from `RSS_*`; `None` unless `RSS_SENTIMENT_ENABLED` is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let enabled = env::var("RSS_SENTIMENT_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        let feeds = env::var("RSS_FEEDS")
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(NewsFeed::parse)
+            .collect::<Result<Vec<_>>>()?;
+        if feeds.is_empty() {
+            return Err(BotError::Config(
+                "RSS_FEEDS must list at least one feed when RSS_SENTIMENT_ENABLED is set".into(),
+            ));
+        }
+        let keywords = env::var("RSS_KEYWORDS").unwrap_or_else(|_| DEFAULT_KEYWORDS.to_string());
+        let max_age_hours = match env::var("RSS_MAX_AGE_HOURS") {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| BotError::Config(format!("invalid RSS_MAX_AGE_HOURS: {:?}", raw)))?,
+            _ => 24,
+        };
+        Ok(Some(Self {
+            feeds,
+            keywords: keywords
+                .split(',')
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            max_age: Duration::hours(max_age_hours),
+        }))
+    }
+}
+
+/// One headline from a feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsItem {
+    pub title: String,
+    pub link: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+}
+
+/// Headline sentiment from RSS/Atom feeds
+pub struct RssNewsClient {
+    client: reqwest::Client,
+    config: RssConfig,
+    sentiment_analyzer: SentimentAnalyzer,
+}
+
+impl RssNewsClient {
+    pub fn new(config: RssConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .user_agent(format!("palm-oil-bot/{}", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client: {}. Falling back to default client.",
+                    e
+                );
+                reqwest::Client::new()
+            });
+
+        Self {
+            client,
+            config,
+            sentiment_analyzer: SentimentAnalyzer::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RssConfig {
+        &self.config
+    }
+
+    /// Fetch one feed's headlines
+    async fn fetch_feed(&self, feed: &NewsFeed) -> Result<Vec<NewsItem>> {
+        debug!("Fetching news feed {}: {}", feed.name, feed.url);
+        let response = self
+            .client
+            .get(&feed.url)
+            .send()
+            .await
+            .map_err(|e| BotError::Feed(format!("{}: request failed: {}", feed.name, e)))?;
+        if !response.status().is_success() {
+            return Err(BotError::Feed(format!(
+                "{}: HTTP error: {}",
+                feed.name,
+                response.status()
+            )));
+        }
+        let body = response.text().await.map_err(|e| {
+            BotError::Feed(format!("{}: failed to read response: {}", feed.name, e))
+        })?;
+        Ok(parse_feed(&body))
+    }
+
+    /// Recent headlines mentioning a keyword, with their feed's weight;
+    /// a headline carried by several feeds counts once
+    fn relevant_headlines(
+        &self,
+        feeds: Vec<(&NewsFeed, Vec<NewsItem>)>,
+        now: DateTime<Utc>,
+    ) -> Vec<(NewsItem, f64)> {
+        let mut seen = HashSet::new();
+        let mut headlines = Vec::new();
+        for (feed, items) in feeds {
+            for item in items {
+                let lower = item.title.to_lowercase();
+                let recent = item
+                    .published
+                    .map_or(true, |at| now - at <= self.config.max_age);
+                let relevant = self.config.keywords.is_empty()
+                    || self
+                        .config
+                        .keywords
+                        .iter()
+                        .any(|k| lower.contains(k.as_str()));
+                if recent && relevant && seen.insert(lower) {
+                    headlines.push((item, feed.weight));
+                }
+            }
+        }
+        headlines
+    }
+
+    /// Get aggregated sentiment from the headlines of all feeds
+    ///
+    /// Fails when no feed had a recent headline matching the keywords, so the
+    /// caller can move on to the next source.
+    pub async fn get_sentiment(&self) -> Result<SentimentResult> {
+        let mut feeds = Vec::new();
+        for feed in &self.config.feeds {
+            match self.fetch_feed(feed).await {
+                Ok(items) => feeds.push((feed, items)),
+                Err(err) => warn!("News feed failed: {}", err),
+            }
+        }
+        let headlines = self.relevant_headlines(feeds, Utc::now());
+        self.score_headlines(&headlines)
+    }
+
+    fn score_headlines(&self, headlines: &[(NewsItem, f64)]) -> Result<SentimentResult> {
+        if headlines.is_empty() {
+            return Err(BotError::Feed(
+                "no recent headlines matching the keywords".into(),
+            ));
+        }
+
+        let results: Vec<SentimentResult> = headlines
+            .iter()
+            .map(|(item, weight)| {
+                let result = self.sentiment_analyzer.analyze(&item.title);
+                let confidence = (result.confidence * weight).clamp(0.0, 1.0);
+                result.with_confidence(confidence)
+            })
+            .collect();
+        let aggregated = self.sentiment_analyzer.aggregate(&results);
+        let result = SentimentResult::new(aggregated.score, "rss")
+            .with_confidence(aggregated.confidence)
+            .with_raw_text(
+                headlines
+                    .iter()
+                    .map(|(item, _)| item.title.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+
+        info!(
+            "News feed sentiment from {} headlines: {:?} (score: {})",
+            headlines.len(),
+            result.sentiment_type,
+            result.score
+        );
+
+        Ok(result)
+    }
+}
+
+/// Items of an RSS 2.0 or Atom document; items without a title are skipped
+pub fn parse_feed(xml: &str) -> Vec<NewsItem> {
+    let (item_tag, date_tags): (&str, &[&str]) = if find_tag(xml, "entry").is_some() {
+        ("entry", &["published", "updated"])
+    } else {
+        ("item", &["pubDate", "dc:date"])
+    };
+
+    let mut items = Vec::new();
+    let mut rest = xml;
+    while let Some((_, body, after)) = element(rest, item_tag) {
+        rest = after;
+        let Some(title) = element(body, "title").map(|(_, text, _)| decode_text(text)) else {
+            continue;
+        };
+        if title.is_empty() {
+            continue;
+        }
+        let published = date_tags
+            .iter()
+            .find_map(|tag| element(body, tag))
+            .and_then(|(_, text, _)| parse_date(&decode_text(text)));
+        items.push(NewsItem {
+            title,
+            link: link(body),
+            published,
+        });
+    }
+    items
+}
+
+/// Position of the first `<tag>` or `<tag ...>` opening tag
+fn find_tag(xml: &str, tag: &str) -> Option<usize> {
+    let open = format!("<{}", tag);
+    let mut from = 0;
+    while let Some(pos) = xml[from..].find(&open) {
+        let start = from + pos;
+        match xml[start + open.len()..].chars().next() {
+            Some('>' | '/' | ' ' | '\t' | '\r' | '\n') => return Some(start),
+            _ => from = start + open.len(),
+        }
+    }
+    None
+}
+
+/// First `tag` element: its opening tag's attributes, its content and the
+/// text after it. Self-closing elements have no content.
+fn element<'a>(xml: &'a str, tag: &str) -> Option<(&'a str, &'a str, &'a str)> {
+    let start = find_tag(xml, tag)?;
+    let open_end = start + xml[start..].find('>')?;
+    let attributes = &xml[start + tag.len() + 1..open_end];
+    if attributes.ends_with('/') {
+        return Some((attributes, "", &xml[open_end + 1..]));
+    }
+    let close = format!("</{}>", tag);
+    let content_start = open_end + 1;
+    let content_end = content_start + xml[content_start..].find(&close)?;
+    Some((
+        attributes,
+        &xml[content_start..content_end],
+        &xml[content_end + close.len()..],
+    ))
+}
+
+/// RSS `<link>url</link>` or Atom `<link href="url"/>`
+fn link(item: &str) -> Option<String> {
+    let (attributes, text, _) = element(item, "link")?;
+    let text = decode_text(text);
+    if !text.is_empty() {
+        return Some(text);
+    }
+    let (_, value) = attributes.split_once("href=")?;
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    Some(decode_entities(&value[..value.find(quote)?]))
+}
+
+/// Element text: CDATA unwrapped, markup dropped, entities decoded
+fn decode_text(raw: &str) -> String {
+    let raw = raw.trim();
+    let raw = raw
+        .strip_prefix("<![CDATA[")
+        .and_then(|r| r.strip_suffix("]]>"))
+        .unwrap_or(raw);
+    // Atom titles may carry escaped or inline HTML
+    let decoded = decode_entities(raw);
+    let mut text = String::with_capacity(decoded.len());
+    let mut in_tag = false;
+    for c in decoded.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let after = &rest[amp..];
+        let Some(end) = after.find(';').filter(|end| *end <= 10) else {
+            out.push('&');
+            rest = &after[1..];
+            continue;
+        };
+        let entity = &after[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// RFC 2822 (RSS) or RFC 3339 (Atom) date
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Commodities</title>
+<item><title><![CDATA[Palm oil rallies as exports surge]]></title>
+<link>https://example.com/a</link><pubDate>Wed, 14 Oct 2026 08:00:00 +0000</pubDate></item>
+<item><title>Gold &amp; silver steady</title><link>https://example.com/b</link></item>
+<item><title>CPO futures fall on weak demand</title>
+<pubDate>Fri, 09 Oct 2026 08:00:00 +0000</pubDate></item>
+</channel></rss>"#;
+
+    const ATOM: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>MPOC</title>
+<entry><title type="html">MPOB stocks &lt;b&gt;drop&lt;/b&gt; to a low</title>
+<link rel="alternate" href="https://example.org/mpob"/>
+<updated>2026-10-14T06:30:00Z</updated></entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let items = parse_feed(RSS);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].title, "Palm oil rallies as exports surge");
+        assert_eq!(items[0].link.as_deref(), Some("https://example.com/a"));
+        assert_eq!(
+            items[0].published,
+            Some(Utc.with_ymd_and_hms(2026, 10, 14, 8, 0, 0).unwrap())
+        );
+        assert_eq!(items[1].title, "Gold & silver steady");
+        assert_eq!(items[1].published, None);
+
+        let entries = parse_feed(ATOM);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "MPOB stocks drop to a low");
+        assert_eq!(entries[0].link.as_deref(), Some("https://example.org/mpob"));
+        assert!(entries[0].published.is_some());
+    }
+
+    #[test]
+    fn test_relevant_headlines_are_weighted_by_feed() {
+        let client = RssNewsClient::new(RssConfig {
+            feeds: vec![
+                NewsFeed::parse("wire|https://example.com/rss|1.0").unwrap(),
+                NewsFeed::parse("blog|https://example.org/feed|0.5").unwrap(),
+            ],
+            keywords: vec!["palm oil".into(), "cpo".into(), "mpob".into()],
+            max_age: Duration::hours(24),
+        });
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap();
+        let (wire, blog) = (&client.config.feeds[0], &client.config.feeds[1]);
+        let mut blog_items = parse_feed(ATOM);
+        // The same story on both feeds counts once, at the first feed's weight
+        blog_items.extend(parse_feed(RSS).into_iter().take(1));
+
+        let headlines =
+            client.relevant_headlines(vec![(wire, parse_feed(RSS)), (blog, blog_items)], now);
+        // Gold is off-topic, the CPO headline is too old
+        let titles: Vec<(&str, f64)> = headlines
+            .iter()
+            .map(|(item, weight)| (item.title.as_str(), *weight))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Palm oil rallies as exports surge", 1.0),
+                ("MPOB stocks drop to a low", 0.5),
+            ]
+        );
+        assert!(client.score_headlines(&headlines).is_ok());
+        assert!(client.score_headlines(&[]).is_err());
+
+        assert!(NewsFeed::parse("wire|not a url").is_err());
+        assert!(NewsFeed::parse("wire|https://example.com|0").is_err());
+    }
+}
@@ -21,23 +21,21 @@ pub struct SentimentResult {
     pub source: String,
     pub raw_text: Option<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Disagreement between blended sources (0.0 = agree, 1.0 = opposite extremes)
+    #[serde(default)]
+    pub dispersion: Option<f64>,
 }
 
 impl SentimentResult {
     pub fn new(score: i32, source: &str) -> Self {
-        let sentiment_type = match score {
-            s if s > 20 => SentimentType::Bullish,
-            s if s < -20 => SentimentType::Bearish,
-            _ => SentimentType::Neutral,
-        };
-
         Self {
-            sentiment_type,
+            sentiment_type: classify(score),
             score: score.clamp(-100, 100),
             confidence: 0.5,
             source: source.to_string(),
             raw_text: None,
             timestamp: chrono::Utc::now(),
+            dispersion: None,
         }
     }
 
@@ -50,6 +48,26 @@ impl SentimentResult {
         self.raw_text = Some(text);
         self
     }
+
+    pub fn with_dispersion(mut self, dispersion: f64) -> Self {
+        self.dispersion = Some(dispersion.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Same reading with another score (and the matching type)
+    pub fn with_score(mut self, score: i32) -> Self {
+        self.score = score.clamp(-100, 100);
+        self.sentiment_type = classify(self.score);
+        self
+    }
+}
+
+fn classify(score: i32) -> SentimentType {
+    match score {
+        s if s > 20 => SentimentType::Bullish,
+        s if s < -20 => SentimentType::Bearish,
+        _ => SentimentType::Neutral,
+    }
 }
 
 /// Sentiment analyzer for keyword-based analysis
@@ -170,6 +188,45 @@ impl SentimentAnalyzer {
         SentimentResult::new(weighted_score.round() as i32, "aggregate")
             .with_confidence(avg_confidence)
     }
+
+    /// Confidence-weighted standard deviation of scores, scaled to 0.0-1.0
+    ///
+    /// 0.0 when all sources agree, 1.0 when they sit at opposite extremes
+    /// (+100 vs -100). Fewer than two weighted sources have no dispersion.
+    pub fn dispersion(&self, results: &[SentimentResult]) -> f64 {
+        let total_weight: f64 = results.iter().map(|r| r.confidence).sum();
+        if results.len() < 2 || total_weight == 0.0 {
+            return 0.0;
+        }
+
+        let mean = results
+            .iter()
+            .map(|r| r.score as f64 * r.confidence)
+            .sum::<f64>()
+            / total_weight;
+        let variance = results
+            .iter()
+            .map(|r| r.confidence * (r.score as f64 - mean).powi(2))
+            .sum::<f64>()
+            / total_weight;
+
+        (variance.sqrt() / 100.0).clamp(0.0, 1.0)
+    }
+
+    /// Aggregate sources and discount confidence by how much they disagree
+    ///
+    /// The blended confidence is scaled by `1 - dispersion`, and the dispersion
+    /// is attached to the result so the strategy can demand stronger RSI confirmation.
+    pub fn blend(&self, results: &[SentimentResult]) -> SentimentResult {
+        let dispersion = self.dispersion(results);
+        let aggregated = self.aggregate(results);
+        let confidence = aggregated.confidence * (1.0 - dispersion);
+
+        let sources: Vec<&str> = results.iter().map(|r| r.source.as_str()).collect();
+        SentimentResult::new(aggregated.score, &format!("blend({})", sources.join("+")))
+            .with_confidence(confidence)
+            .with_dispersion(dispersion)
+    }
 }
 
 fn extract_score_hint(text: &str) -> Option<i32> {
@@ -301,4 +358,42 @@ mod tests {
         assert!(aggregated.score > 20);
         assert!(aggregated.confidence > 0.0);
     }
+
+    #[test]
+    fn test_dispersion_agreeing_sources() {
+        let analyzer = SentimentAnalyzer::new();
+        let results = vec![
+            SentimentResult::new(50, "perplexity").with_confidence(0.8),
+            SentimentResult::new(50, "twitter").with_confidence(0.4),
+        ];
+        assert!(analyzer.dispersion(&results).abs() < 1e-9);
+        assert!(analyzer.dispersion(&results[..1]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dispersion_opposite_extremes() {
+        let analyzer = SentimentAnalyzer::new();
+        let results = vec![
+            SentimentResult::new(100, "perplexity").with_confidence(0.5),
+            SentimentResult::new(-100, "twitter").with_confidence(0.5),
+        ];
+        assert!((analyzer.dispersion(&results) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_blend_reduces_confidence_on_disagreement() {
+        let analyzer = SentimentAnalyzer::new();
+        let results = vec![
+            SentimentResult::new(60, "perplexity").with_confidence(0.8),
+            SentimentResult::new(-40, "twitter").with_confidence(0.8),
+        ];
+
+        let blended = analyzer.blend(&results);
+        let dispersion = blended.dispersion.unwrap();
+
+        assert!((dispersion - 0.5).abs() < 1e-9);
+        assert_eq!(blended.score, 10);
+        assert!((blended.confidence - 0.4).abs() < 1e-9);
+        assert_eq!(blended.source, "blend(perplexity+twitter)");
+    }
 }
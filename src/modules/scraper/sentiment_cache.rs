@@ -1,18 +1,21 @@
 //! Sentiment cache with TTL for Perplexity requests.
 //!
-//! Stores sentiment scores keyed by query strings to limit API calls.
+//! Stores sentiment results keyed by query strings to limit API calls. A hit
+//! returns the result as fetched, confidence and source included.
 
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use tracing::info;
 
+use super::sentiment::SentimentResult;
+
 const DEFAULT_TTL: Duration = Duration::from_secs(300);
 
 /// Thread-safe in-memory sentiment cache with TTL.
 #[derive(Debug, Clone)]
 pub struct SentimentCache {
-    state: Arc<RwLock<HashMap<String, (i32, Instant)>>>,
+    state: Arc<RwLock<HashMap<String, (SentimentResult, Instant)>>>,
     ttl: Duration,
 }
 
@@ -30,14 +33,14 @@ impl SentimentCache {
         }
     }
 
-    /// Get cached sentiment score for a query if present and not expired.
-    pub fn get(&self, query: &str) -> Option<i32> {
+    /// Get the cached sentiment for a query if present and not expired.
+    pub fn get(&self, query: &str) -> Option<SentimentResult> {
         let mut state = self.state.write().ok()?;
-        match state.get(query).copied() {
-            Some((score, inserted_at)) => {
+        match state.get(query).cloned() {
+            Some((result, inserted_at)) => {
                 if inserted_at.elapsed() <= self.ttl {
                     info!("Sentiment cache hit for query: {}", query);
-                    Some(score)
+                    Some(result)
                 } else {
                     state.remove(query);
                     info!("Sentiment cache miss (expired) for query: {}", query);
@@ -51,10 +54,10 @@ impl SentimentCache {
         }
     }
 
-    /// Store sentiment score in cache.
-    pub fn set(&self, query: &str, score: i32) {
+    /// Store a sentiment result in cache.
+    pub fn set(&self, query: &str, result: SentimentResult) {
         if let Ok(mut state) = self.state.write() {
-            state.insert(query.to_string(), (score, Instant::now()));
+            state.insert(query.to_string(), (result, Instant::now()));
         }
     }
 
@@ -81,27 +84,36 @@ impl Default for SentimentCache {
 mod tests {
     use super::*;
 
+    fn result(score: i32) -> SentimentResult {
+        let mut result = SentimentResult::new(score, "perplexity");
+        result.confidence = 0.42;
+        result
+    }
+
     #[test]
     fn test_cache_hit() {
         let cache = SentimentCache::with_ttl(Duration::from_secs(300));
-        cache.set("palm oil", 42);
-        assert_eq!(cache.get("palm oil"), Some(42));
+        cache.set("palm oil", result(42));
+        let hit = cache.get("palm oil").unwrap();
+        assert_eq!(hit.score, 42);
+        assert_eq!(hit.confidence, 0.42);
+        assert_eq!(hit.source, "perplexity");
         assert_eq!(cache.len(), 1);
     }
 
     #[test]
     fn test_cache_miss() {
         let cache = SentimentCache::with_ttl(Duration::from_secs(300));
-        assert_eq!(cache.get("missing"), None);
+        assert!(cache.get("missing").is_none());
     }
 
     #[test]
     fn test_cache_expiry() {
         let cache = SentimentCache::with_ttl(Duration::from_millis(50));
-        cache.set("short", 10);
-        assert_eq!(cache.get("short"), Some(10));
+        cache.set("short", result(10));
+        assert_eq!(cache.get("short").map(|r| r.score), Some(10));
         std::thread::sleep(Duration::from_millis(75));
-        assert_eq!(cache.get("short"), None);
+        assert!(cache.get("short").is_none());
         assert_eq!(cache.len(), 0);
     }
 }
@@ -0,0 +1,285 @@
+//! Historical sentiment series for backtesting
+//!
+//! Rebuilds a time-ordered series of sentiment readings from an external CSV,
+//! the bot's trade log, the recorded sentiment history or stored market
+//! briefs, and answers "what was the sentiment at time T" with an as-of
+//! lookup.
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::market_brief::MarketBrief;
+use crate::modules::trading::SentimentRecord;
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+use std::path::Path;
+
+/// A single historical sentiment reading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentimentPoint {
+    pub timestamp: DateTime<Utc>,
+    /// Score from -100 to +100
+    pub score: i32,
+    /// Confidence from 0.0 to 1.0
+    pub confidence: f64,
+}
+
+/// Time-ordered sentiment readings with as-of lookup
+#[derive(Debug, Clone, Default)]
+pub struct SentimentSeries {
+    points: Vec<SentimentPoint>,
+    /// Readings older than this are treated as missing (neutral)
+    max_age: Option<Duration>,
+}
+
+impl SentimentSeries {
+    /// Build a series from readings (sorted by timestamp)
+    pub fn new(mut points: Vec<SentimentPoint>) -> Self {
+        points.sort_by_key(|p| p.timestamp);
+        Self {
+            points,
+            max_age: None,
+        }
+    }
+
+    /// Ignore readings older than `max_age` at lookup time
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Load from a CSV with header `timestamp,score[,confidence]`
+    ///
+    /// Timestamps may be RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or unix seconds.
+    /// Missing confidence defaults to 0.5.
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Self> {
+        let content = read_file(path.as_ref())?;
+        let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+        let header = lines
+            .next()
+            .ok_or_else(|| BotError::Config("Sentiment CSV is empty".to_string()))?;
+        let columns: Vec<String> = header.split(',').map(|c| c.trim().to_ascii_lowercase()).collect();
+
+        let ts_idx = column_index(&columns, "timestamp")?;
+        let score_idx = column_index(&columns, "score")
+            .or_else(|_| column_index(&columns, "sentiment_score"))?;
+        let confidence_idx = column_index(&columns, "confidence")
+            .or_else(|_| column_index(&columns, "sentiment_confidence"))
+            .ok();
+
+        let mut points = Vec::new();
+        for (line_no, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let point = parse_point(&fields, ts_idx, score_idx, confidence_idx).ok_or_else(|| {
+                BotError::Config(format!("Invalid sentiment CSV row {}: {}", line_no + 2, line))
+            })?;
+            points.push(point);
+        }
+
+        Ok(Self::new(points))
+    }
+
+    /// Reconstruct readings from the bot's CSV trade log (`OPEN` rows carry the
+    /// sentiment that was in force when the trade was taken)
+    pub fn from_trade_log(path: impl AsRef<Path>) -> Result<Self> {
+        let content = read_file(path.as_ref())?;
+        let mut lines = content.lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| BotError::Config("Trade log is empty".to_string()))?;
+        let columns: Vec<String> = header.split(',').map(|c| c.trim().to_ascii_lowercase()).collect();
+
+        let ts_idx = column_index(&columns, "timestamp")?;
+        let event_idx = column_index(&columns, "event")?;
+        let score_idx = column_index(&columns, "sentiment_score")?;
+        let confidence_idx = column_index(&columns, "sentiment_confidence").ok();
+
+        let points = lines
+            .map(|line| line.split(',').map(str::trim).collect::<Vec<_>>())
+            .filter(|fields| fields.get(event_idx) == Some(&"OPEN"))
+            .filter_map(|fields| parse_point(&fields, ts_idx, score_idx, confidence_idx))
+            .collect();
+
+        Ok(Self::new(points))
+    }
+
+    /// Use stored market briefs as readings
+    pub fn from_market_briefs(briefs: &[MarketBrief]) -> Self {
+        Self::new(
+            briefs
+                .iter()
+                .map(|b| SentimentPoint {
+                    timestamp: b.generated_at,
+                    score: b.score,
+                    confidence: b.confidence,
+                })
+                .collect(),
+        )
+    }
+
+    /// Use readings recorded in the `sentiment_history` table
+    pub fn from_sentiment_history(records: &[SentimentRecord]) -> Self {
+        Self::new(
+            records
+                .iter()
+                .map(|r| SentimentPoint {
+                    timestamp: r.recorded_at,
+                    score: r.score,
+                    confidence: r.confidence,
+                })
+                .collect(),
+        )
+    }
+
+    /// Latest reading at or before `timestamp`, if any (and not too old)
+    pub fn point_at(&self, timestamp: DateTime<Utc>) -> Option<&SentimentPoint> {
+        let idx = self.points.partition_point(|p| p.timestamp <= timestamp);
+        let point = self.points.get(idx.checked_sub(1)?)?;
+        match self.max_age {
+            Some(max_age) if timestamp - point.timestamp > max_age => None,
+            _ => Some(point),
+        }
+    }
+
+    /// Score in force at `timestamp`, or neutral (0) when no reading applies
+    pub fn score_at(&self, timestamp: DateTime<Utc>) -> i32 {
+        self.point_at(timestamp).map(|p| p.score).unwrap_or(0)
+    }
+
+    /// All readings in time order
+    pub fn points(&self) -> &[SentimentPoint] {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| {
+        BotError::Config(format!("Failed to read sentiment file {}: {}", path.display(), e))
+    })
+}
+
+fn column_index(columns: &[String], name: &str) -> Result<usize> {
+    columns
+        .iter()
+        .position(|c| c == name)
+        .ok_or_else(|| BotError::Config(format!("Missing '{}' column", name)))
+}
+
+fn parse_point(
+    fields: &[&str],
+    ts_idx: usize,
+    score_idx: usize,
+    confidence_idx: Option<usize>,
+) -> Option<SentimentPoint> {
+    let timestamp = parse_timestamp(fields.get(ts_idx)?)?;
+    let score = fields.get(score_idx)?.parse::<f64>().ok()?.round() as i32;
+    let confidence = confidence_idx
+        .and_then(|idx| fields.get(idx))
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.5);
+
+    Some(SentimentPoint {
+        timestamp,
+        score: score.clamp(-100, 100),
+        confidence: confidence.clamp(0.0, 1.0),
+    })
+}
+
+/// Parse RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or unix seconds
+fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S") {
+        return Some(naive.and_utc());
+    }
+    raw.parse::<i64>()
+        .ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn write_temp(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_from_csv_mixed_timestamp_formats() {
+        let file = write_temp(
+            "timestamp,score,confidence\n\
+             2024-03-04T08:00:00Z,40,0.9\n\
+             2024-03-04 06:00:00,-20,0.6\n\
+             1709546400,10\n",
+        );
+
+        let series = SentimentSeries::from_csv(file.path()).unwrap();
+        assert_eq!(series.len(), 3);
+        // Sorted by time: 06:00, 08:00, then 10:00 (unix seconds)
+        assert_eq!(series.points()[0].score, -20);
+        assert_eq!(series.points()[1].score, 40);
+        assert_eq!(series.points()[2].score, 10);
+        assert!((series.points()[2].confidence - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_from_csv_rejects_bad_rows() {
+        let file = write_temp("timestamp,score\nnot-a-date,10\n");
+        assert!(SentimentSeries::from_csv(file.path()).is_err());
+
+        let file = write_temp("when,value\n2024-03-04T08:00:00Z,10\n");
+        assert!(SentimentSeries::from_csv(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_from_trade_log_uses_open_rows() {
+        let file = write_temp(
+            "timestamp,event,side,symbol,entry_price,sl,tp,volume,rsi,sentiment_score,sentiment_confidence,signal,position_id,close_price,pnl,close_reason\n\
+             2024-03-04T08:00:00+00:00,OPEN,Buy,FCPO,4200.0,4150.0,4280.0,1.0,25.0,45,0.90,Buy,1,,,\n\
+             2024-03-04T09:00:00+00:00,CLOSE,,,,,,,,,,,1,4280.0,80.0,TakeProfit\n",
+        );
+
+        let series = SentimentSeries::from_trade_log(file.path()).unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series.points()[0].score, 45);
+        assert!((series.points()[0].confidence - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_at_is_as_of_lookup() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+        let series = SentimentSeries::new(vec![
+            SentimentPoint { timestamp: t0, score: 30, confidence: 0.8 },
+            SentimentPoint { timestamp: t0 + Duration::hours(2), score: -50, confidence: 0.7 },
+        ]);
+
+        assert_eq!(series.score_at(t0 - Duration::minutes(1)), 0);
+        assert_eq!(series.score_at(t0), 30);
+        assert_eq!(series.score_at(t0 + Duration::minutes(90)), 30);
+        assert_eq!(series.score_at(t0 + Duration::hours(3)), -50);
+
+        let bounded = series.with_max_age(Duration::minutes(30));
+        assert_eq!(bounded.score_at(t0 + Duration::minutes(20)), 30);
+        assert_eq!(bounded.score_at(t0 + Duration::minutes(90)), 0);
+    }
+
+    #[test]
+    fn test_from_market_briefs() {
+        let brief = MarketBrief::new("FCPO", 35, 0.6, "SUMMARY: ok");
+        let series = SentimentSeries::from_market_briefs(std::slice::from_ref(&brief));
+        assert_eq!(series.score_at(brief.generated_at), 35);
+    }
+}
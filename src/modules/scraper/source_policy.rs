@@ -0,0 +1,212 @@
+//! Citation domain policy for Perplexity answers.
+//!
+//! Perplexity returns the URLs backing each answer. Readings that lean on
+//! blocked domains, or on domains outside the trusted list, get their
+//! confidence scaled down so junk web content carries less weight.
+
+use std::env;
+use tracing::debug;
+
+/// Lowest factor applied to confidence, even when every source is blocked
+const MIN_CONFIDENCE_FACTOR: f64 = 0.1;
+
+/// Trusted/blocked domain lists for sentiment sources
+#[derive(Debug, Clone)]
+pub struct SourcePolicy {
+    /// Domains considered reliable (subdomains match too)
+    pub trusted_domains: Vec<String>,
+    /// Domains whose content should be discounted entirely
+    pub blocked_domains: Vec<String>,
+    /// Confidence reduction when all citations are unknown (0.0 to 1.0).
+    /// Only applies when a trusted list is configured.
+    pub unknown_penalty: f64,
+}
+
+impl Default for SourcePolicy {
+    fn default() -> Self {
+        Self {
+            trusted_domains: Vec::new(),
+            blocked_domains: Vec::new(),
+            unknown_penalty: 0.5,
+        }
+    }
+}
+
+/// Breakdown of citations by classification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CitationBreakdown {
+    pub trusted: usize,
+    pub blocked: usize,
+    pub unknown: usize,
+}
+
+impl CitationBreakdown {
+    pub fn total(&self) -> usize {
+        self.trusted + self.blocked + self.unknown
+    }
+}
+
+impl SourcePolicy {
+    /// Create a policy from domain lists
+    pub fn new(trusted_domains: Vec<String>, blocked_domains: Vec<String>) -> Self {
+        Self {
+            trusted_domains: trusted_domains.iter().map(|d| normalize_domain(d)).collect(),
+            blocked_domains: blocked_domains.iter().map(|d| normalize_domain(d)).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Load from `PERPLEXITY_TRUSTED_DOMAINS` / `PERPLEXITY_BLOCKED_DOMAINS`
+    /// (comma-separated) and `PERPLEXITY_UNKNOWN_SOURCE_PENALTY`
+    pub fn from_env() -> Self {
+        let mut policy = Self::new(
+            parse_domain_list(&env::var("PERPLEXITY_TRUSTED_DOMAINS").unwrap_or_default()),
+            parse_domain_list(&env::var("PERPLEXITY_BLOCKED_DOMAINS").unwrap_or_default()),
+        );
+        if let Some(penalty) = env::var("PERPLEXITY_UNKNOWN_SOURCE_PENALTY")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+        {
+            policy.unknown_penalty = penalty.clamp(0.0, 1.0);
+        }
+        policy
+    }
+
+    /// Whether any list is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.trusted_domains.is_empty() || !self.blocked_domains.is_empty()
+    }
+
+    /// Classify citation URLs against the trusted/blocked lists
+    pub fn classify(&self, citations: &[String]) -> CitationBreakdown {
+        let mut breakdown = CitationBreakdown::default();
+        for citation in citations {
+            let Some(host) = citation_host(citation) else {
+                breakdown.unknown += 1;
+                continue;
+            };
+            if matches_any(&host, &self.blocked_domains) {
+                breakdown.blocked += 1;
+            } else if matches_any(&host, &self.trusted_domains) {
+                breakdown.trusted += 1;
+            } else {
+                breakdown.unknown += 1;
+            }
+        }
+        breakdown
+    }
+
+    /// Multiplier (0.1 to 1.0) to apply to a reading's confidence
+    pub fn confidence_factor(&self, citations: &[String]) -> f64 {
+        if !self.is_enabled() || citations.is_empty() {
+            return 1.0;
+        }
+
+        let breakdown = self.classify(citations);
+        let total = breakdown.total() as f64;
+        let blocked_share = breakdown.blocked as f64 / total;
+        let unknown_share = if self.trusted_domains.is_empty() {
+            0.0
+        } else {
+            breakdown.unknown as f64 / total
+        };
+
+        let factor = 1.0 - blocked_share - unknown_share * self.unknown_penalty;
+        debug!(
+            "Citation breakdown: {} trusted, {} blocked, {} unknown -> confidence x{:.2}",
+            breakdown.trusted, breakdown.blocked, breakdown.unknown, factor
+        );
+        factor.clamp(MIN_CONFIDENCE_FACTOR, 1.0)
+    }
+}
+
+fn parse_domain_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn normalize_domain(domain: &str) -> String {
+    let domain = domain.trim().to_ascii_lowercase();
+    domain.strip_prefix("www.").unwrap_or(&domain).to_string()
+}
+
+fn citation_host(citation: &str) -> Option<String> {
+    let parsed = url::Url::parse(citation.trim()).ok()?;
+    parsed.host_str().map(normalize_domain)
+}
+
+fn matches_any(host: &str, domains: &[String]) -> bool {
+    domains
+        .iter()
+        .any(|d| host == d || host.ends_with(&format!(".{}", d)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> SourcePolicy {
+        SourcePolicy::new(
+            vec!["reuters.com".to_string(), "mpob.gov.my".to_string()],
+            vec!["spam-news.io".to_string()],
+        )
+    }
+
+    fn urls(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_classify_matches_subdomains() {
+        let breakdown = policy().classify(&urls(&[
+            "https://www.reuters.com/markets/commodities/palm",
+            "https://bepi.mpob.gov.my/index.php",
+            "https://spam-news.io/palm-oil-moon",
+            "https://example.com/blog",
+            "not a url",
+        ]));
+
+        assert_eq!(breakdown.trusted, 2);
+        assert_eq!(breakdown.blocked, 1);
+        assert_eq!(breakdown.unknown, 2);
+    }
+
+    #[test]
+    fn test_trusted_sources_keep_confidence() {
+        let factor = policy().confidence_factor(&urls(&["https://www.reuters.com/a"]));
+        assert!((factor - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_blocked_sources_reduce_confidence() {
+        let factor = policy().confidence_factor(&urls(&[
+            "https://spam-news.io/a",
+            "https://spam-news.io/b",
+        ]));
+        assert!((factor - MIN_CONFIDENCE_FACTOR).abs() < f64::EPSILON);
+
+        let mixed = policy().confidence_factor(&urls(&[
+            "https://reuters.com/a",
+            "https://spam-news.io/b",
+        ]));
+        assert!((mixed - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unknown_sources_penalized_only_with_trusted_list() {
+        let citations = urls(&["https://example.com/a"]);
+        assert!((policy().confidence_factor(&citations) - 0.5).abs() < 1e-9);
+
+        let blocklist_only = SourcePolicy::new(Vec::new(), vec!["spam-news.io".to_string()]);
+        assert!((blocklist_only.confidence_factor(&citations) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_disabled_policy_is_neutral() {
+        let factor = SourcePolicy::default().confidence_factor(&urls(&["https://spam-news.io"]));
+        assert!((factor - 1.0).abs() < f64::EPSILON);
+    }
+}
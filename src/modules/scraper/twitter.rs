@@ -70,8 +70,11 @@ impl TwitterScraper {
         // Note: Twitter guest scraping is unreliable and may be blocked
         // This is a backup method when Perplexity is rate limited
 
-        // Wait for rate limit before making request
-        self.rate_limiter.wait_for_rate_limit().await;
+        // Queue for a rate-limit slot
+        self.rate_limiter
+            .acquire()
+            .await
+            .map_err(|e| BotError::Twitter(e.to_string()))?;
 
         let url = format!("https://nitter.net/{}", username);
         debug!("Scraping tweets from: {}", url);
@@ -0,0 +1,439 @@
+//! Twitter API v2 client for KOL sentiment
+//!
+//! The official alternative to `TwitterScraper`: one recent-search query per
+//! KOL (`from:<kol> -is:retweet`, plus `TWITTER_API_KEYWORDS` when set) on
+//! `GET /2/tweets/search/recent`, authenticated with the app's bearer token
+//! (`TWITTER_BEARER_TOKEN`). Results are paged with `next_token` up to
+//! `TWITTER_API_MAX_PAGES` (default 2) of `TWITTER_API_PAGE_SIZE` (default
+//! 25, 10-100) tweets from the last `TWITTER_API_MAX_AGE_HOURS` (default 24,
+//! the endpoint serves at most 7 days).
+//!
+//! Requests queue on the shared Twitter rate limiter. When the API answers
+//! 429 or reports no remaining requests, no call is made until the reset
+//! time it sent (`x-rate-limit-reset`), and the provider fails meanwhile so
+//! the next source is used.
+//!
+//! With a bearer token the bot uses this client for the `twitter` provider;
+//! `TWITTER_SOURCE=scrape` keeps the scraper.
+
+use std::env;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::sentiment::{SentimentAnalyzer, SentimentResult};
+use crate::modules::scraper::twitter::Tweet;
+use crate::modules::security::ApiRateLimiter;
+
+const SEARCH_URL: &str = "https://api.twitter.com/2/tweets/search/recent";
+
+const DEFAULT_MAX_PAGES: usize = 2;
+const DEFAULT_PAGE_SIZE: usize = 25;
+const DEFAULT_MAX_AGE_HOURS: i64 = 24;
+
+/// Pause used when a 429 carries no reset time
+const DEFAULT_RATE_LIMIT_PAUSE_SECS: i64 = 900;
+
+/// How the v2 search is queried
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwitterApiConfig {
+    pub bearer_token: String,
+    /// Terms added to every query as `(a OR b)`; empty = every tweet
+    pub keywords: Vec<String>,
+    /// Pages followed per KOL
+    pub max_pages: usize,
+    /// Tweets per page (10-100)
+    pub page_size: usize,
+    /// Oldest tweet searched
+    pub max_age: Duration,
+}
+
+impl TwitterApiConfig {
+    /// Load from `TWITTER_BEARER_TOKEN` and `TWITTER_API_*`; None without a
+    /// token or with `TWITTER_SOURCE=scrape`
+    pub fn from_env() -> Result<Option<Self>> {
+        let source = env::var("TWITTER_SOURCE").unwrap_or_default();
+        let bearer_token = env::var("TWITTER_BEARER_TOKEN").unwrap_or_default();
+        let bearer_token = bearer_token.trim();
+        match source.trim().to_ascii_lowercase().as_str() {
+            "" | "auto" if bearer_token.is_empty() => return Ok(None),
+            "" | "auto" | "api" => {}
+            "scrape" => return Ok(None),
+            other => {
+                return Err(BotError::Config(format!(
+                    "invalid TWITTER_SOURCE: {:?} (expected auto, api or scrape)",
+                    other
+                )))
+            }
+        }
+        if bearer_token.is_empty() {
+            return Err(BotError::Config(
+                "TWITTER_SOURCE=api requires TWITTER_BEARER_TOKEN".into(),
+            ));
+        }
+
+        let number = |key: &str, default: usize| -> Result<usize> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        };
+        let config = Self {
+            bearer_token: bearer_token.to_string(),
+            keywords: env::var("TWITTER_API_KEYWORDS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|keyword| keyword.trim().to_string())
+                .filter(|keyword| !keyword.is_empty())
+                .collect(),
+            max_pages: number("TWITTER_API_MAX_PAGES", DEFAULT_MAX_PAGES)?,
+            page_size: number("TWITTER_API_PAGE_SIZE", DEFAULT_PAGE_SIZE)?,
+            max_age: Duration::hours(number(
+                "TWITTER_API_MAX_AGE_HOURS",
+                DEFAULT_MAX_AGE_HOURS as usize,
+            )? as i64),
+        };
+        if config.max_pages == 0
+            || !(10..=100).contains(&config.page_size)
+            || !(1..=24 * 7).contains(&config.max_age.num_hours())
+        {
+            return Err(BotError::Config(
+                "TWITTER_API_MAX_PAGES must be at least 1, TWITTER_API_PAGE_SIZE 10-100 and \
+                 TWITTER_API_MAX_AGE_HOURS 1-168"
+                    .into(),
+            ));
+        }
+        Ok(Some(config))
+    }
+
+    /// Search query for one KOL
+    pub fn query(&self, kol: &str) -> String {
+        let mut query = format!("from:{} -is:retweet", kol.trim_start_matches('@'));
+        if !self.keywords.is_empty() {
+            let terms: Vec<String> = self
+                .keywords
+                .iter()
+                .map(|keyword| {
+                    if keyword.contains(' ') {
+                        format!("\"{}\"", keyword)
+                    } else {
+                        keyword.clone()
+                    }
+                })
+                .collect();
+            query.push_str(&format!(" ({})", terms.join(" OR ")));
+        }
+        query
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchPage {
+    #[serde(default)]
+    data: Vec<ApiTweet>,
+    #[serde(default)]
+    meta: SearchMeta,
+}
+
+#[derive(Deserialize)]
+struct ApiTweet {
+    text: String,
+    created_at: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct SearchMeta {
+    next_token: Option<String>,
+}
+
+/// KOL tweets through the official v2 recent-search endpoint
+pub struct TwitterApiClient {
+    client: reqwest::Client,
+    config: TwitterApiConfig,
+    kols: Vec<String>,
+    sentiment_analyzer: SentimentAnalyzer,
+    rate_limiter: Arc<ApiRateLimiter>,
+    /// No request before this, as told by the API's rate-limit headers
+    limited_until: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl TwitterApiClient {
+    pub fn new(
+        config: TwitterApiConfig,
+        kols: Vec<String>,
+        rate_limiter: Arc<ApiRateLimiter>,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .user_agent(format!("palm-oil-bot/{}", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client: {}. Falling back to default client.",
+                    e
+                );
+                reqwest::Client::new()
+            });
+
+        Self {
+            client,
+            config,
+            kols,
+            sentiment_analyzer: SentimentAnalyzer::new(),
+            rate_limiter,
+            limited_until: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &TwitterApiConfig {
+        &self.config
+    }
+
+    /// Recent tweets of every KOL
+    pub async fn search_all(&self) -> Result<Vec<Tweet>> {
+        let mut all_tweets = Vec::new();
+
+        for kol in &self.kols {
+            match self.search_user(kol).await {
+                Ok(tweets) => {
+                    info!("Fetched {} tweets from @{} (API)", tweets.len(), kol);
+                    all_tweets.extend(tweets);
+                }
+                Err(e) => {
+                    warn!("Failed to search tweets from @{}: {}", kol, e);
+                    if self.rate_limited_until(Utc::now()).is_some() {
+                        // Every other KOL would hit the same limit
+                        if all_tweets.is_empty() {
+                            return Err(e);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(all_tweets)
+    }
+
+    /// Reset time of an API rate limit still in force at `now`
+    fn rate_limited_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let until = *self.limited_until.lock().unwrap_or_else(|e| e.into_inner());
+        until.filter(|until| *until > now)
+    }
+
+    /// Remember the reset time when the API says the window is used up
+    fn note_rate_limit(&self, headers: &HeaderMap, exhausted: bool, now: DateTime<Utc>) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<i64>().ok())
+        };
+        let exhausted = exhausted || header("x-rate-limit-remaining") == Some(0);
+        if !exhausted {
+            return;
+        }
+        let until = header("x-rate-limit-reset")
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .filter(|until| *until > now)
+            .unwrap_or_else(|| now + Duration::seconds(DEFAULT_RATE_LIMIT_PAUSE_SECS));
+        warn!(
+            "Twitter API rate limit reached; pausing searches until {}",
+            until.format("%H:%M:%S UTC")
+        );
+        *self.limited_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(until);
+    }
+
+    /// Recent tweets of one KOL, following `next_token` up to the page limit
+    async fn search_user(&self, kol: &str) -> Result<Vec<Tweet>> {
+        let query = self.config.query(kol);
+        let start_time =
+            (Utc::now() - self.config.max_age).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let mut tweets = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        for page in 0..self.config.max_pages {
+            if let Some(until) = self.rate_limited_until(Utc::now()) {
+                return Err(BotError::Twitter(format!(
+                    "API rate limited until {}",
+                    until.format("%H:%M:%S UTC")
+                )));
+            }
+            // Queue for a rate-limit slot
+            self.rate_limiter
+                .acquire()
+                .await
+                .map_err(|e| BotError::Twitter(e.to_string()))?;
+
+            let mut params = vec![
+                ("query", query.clone()),
+                ("max_results", self.config.page_size.to_string()),
+                ("start_time", start_time.clone()),
+                ("tweet.fields", "created_at".to_string()),
+            ];
+            if let Some(token) = &next_token {
+                params.push(("next_token", token.clone()));
+            }
+            debug!("Searching tweets (page {}): {}", page + 1, query);
+
+            let response = self
+                .client
+                .get(SEARCH_URL)
+                .bearer_auth(&self.config.bearer_token)
+                .query(&params)
+                .send()
+                .await
+                .map_err(|e| BotError::Twitter(format!("Request failed: {}", e)))?;
+
+            let status = response.status();
+            self.note_rate_limit(
+                response.headers(),
+                status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+                Utc::now(),
+            );
+            if !status.is_success() {
+                warn!("Twitter API search failed: {}", status);
+                self.rate_limiter.record_failure().await;
+                return Err(BotError::Twitter(format!("HTTP error: {}", status)));
+            }
+            self.rate_limiter.record_success().await;
+
+            let body = response
+                .text()
+                .await
+                .map_err(|e| BotError::Twitter(format!("Failed to read response: {}", e)))?;
+            let (page_tweets, token) = parse_search_page(&body, kol)?;
+            tweets.extend(page_tweets);
+            next_token = token;
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(tweets)
+    }
+
+    /// Aggregated sentiment of the KOLs' recent tweets
+    pub async fn get_sentiment(&self) -> Result<SentimentResult> {
+        let tweets = self.search_all().await?;
+
+        if tweets.is_empty() {
+            warn!("No recent tweets from the API, returning neutral sentiment");
+            return Ok(SentimentResult::new(0, "twitter").with_confidence(0.1));
+        }
+
+        let results: Vec<SentimentResult> = tweets
+            .iter()
+            .map(|tweet| self.sentiment_analyzer.analyze(&tweet.text))
+            .collect();
+        let aggregated = self.sentiment_analyzer.aggregate(&results);
+        let result = SentimentResult::new(aggregated.score, "twitter")
+            .with_confidence(aggregated.confidence);
+
+        info!(
+            "Twitter API sentiment from {} tweets: {:?} (score: {})",
+            tweets.len(),
+            result.sentiment_type,
+            result.score
+        );
+
+        Ok(result)
+    }
+}
+
+/// One page of search results and the token of the next one
+fn parse_search_page(body: &str, kol: &str) -> Result<(Vec<Tweet>, Option<String>)> {
+    let page: SearchPage = serde_json::from_str(body)
+        .map_err(|e| BotError::Twitter(format!("Invalid search response: {}", e)))?;
+    let tweets = page
+        .data
+        .into_iter()
+        .map(|tweet| Tweet {
+            username: kol.trim_start_matches('@').to_string(),
+            text: tweet.text.trim().to_string(),
+            timestamp: tweet.created_at,
+        })
+        .filter(|tweet| !tweet.text.is_empty())
+        .collect();
+    Ok((tweets, page.meta.next_token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn config() -> TwitterApiConfig {
+        TwitterApiConfig {
+            bearer_token: "token".into(),
+            keywords: vec!["palm oil".into(), "cpo".into()],
+            max_pages: 2,
+            page_size: 25,
+            max_age: Duration::hours(24),
+        }
+    }
+
+    #[test]
+    fn test_query_and_page_parsing() {
+        assert_eq!(
+            config().query("@PalmOilTrader"),
+            "from:PalmOilTrader -is:retweet (\"palm oil\" OR cpo)"
+        );
+
+        let body = r#"{
+            "data": [
+                {"id": "1", "text": "CPO exports surge, bullish", "created_at": "2024-03-04T08:00:00.000Z"},
+                {"id": "2", "text": "  "}
+            ],
+            "meta": {"result_count": 2, "next_token": "b26v89c19zqg8o3f"}
+        }"#;
+        let (tweets, next) = parse_search_page(body, "@PalmOilTrader").unwrap();
+        assert_eq!(tweets.len(), 1);
+        assert_eq!(tweets[0].username, "PalmOilTrader");
+        assert_eq!(
+            tweets[0].timestamp.as_deref(),
+            Some("2024-03-04T08:00:00.000Z")
+        );
+        assert_eq!(next.as_deref(), Some("b26v89c19zqg8o3f"));
+
+        let (empty, next) = parse_search_page(r#"{"meta": {"result_count": 0}}"#, "x").unwrap();
+        assert!(empty.is_empty() && next.is_none());
+        assert!(parse_search_page("<html>", "x").is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_headers_pause_searches() {
+        let rate_limiter = Arc::new(ApiRateLimiter::for_twitter());
+        let client = TwitterApiClient::new(config(), vec!["a".into()], rate_limiter);
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+        let reset = now + Duration::minutes(10);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rate-limit-remaining", HeaderValue::from_static("3"));
+        client.note_rate_limit(&headers, false, now);
+        assert_eq!(client.rate_limited_until(now), None);
+
+        headers.insert("x-rate-limit-remaining", HeaderValue::from_static("0"));
+        headers.insert(
+            "x-rate-limit-reset",
+            HeaderValue::from_str(&reset.timestamp().to_string()).unwrap(),
+        );
+        client.note_rate_limit(&headers, false, now);
+        assert_eq!(client.rate_limited_until(now), Some(reset));
+        assert_eq!(client.rate_limited_until(reset), None);
+
+        // A 429 without headers pauses for the default window
+        client.note_rate_limit(&HeaderMap::new(), true, reset);
+        assert_eq!(
+            client.rate_limited_until(reset),
+            Some(reset + Duration::seconds(DEFAULT_RATE_LIMIT_PAUSE_SECS))
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Role-based access control for the control-plane APIs
+//!
+//! Tokens get a role in `ACCESS_TOKENS` (comma-separated `role:token` pairs,
+//! e.g. `viewer:dash123,operator:ops456,admin:root789`):
+//! - `viewer`: read state (status, positions, orders, web dashboard, event
+//!   stream, exports, Grafana)
+//! - `operator`: also pause / resume, close one position, cancel pending
+//!   orders, refresh sentiment and disarm live trading
+//! - `admin`: also flatten every position, arm live trading and switch
+//!   strategy features on or off
+//!
+//! The per-API tokens (`CONTROL_API_TOKEN`, `WEB_DASHBOARD_TOKEN`,
+//! `GRPC_API_TOKEN`, `EXPORT_API_TOKEN`, `GRAFANA_API_TOKEN`) keep working and
+//! carry the admin role. Denials and every
+//! allowed command are logged with an `[AUDIT]` prefix on the `audit` target.
+//! The HTTP routers check the bearer header with
+//! [`AccessPolicy::authorize_headers`] and answer a denial with its
+//! `IntoResponse` (`401` or `403`).
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use tracing::{debug, info, warn};
+
+use super::secrets_manager::SecretString;
+
+/// Access levels, each including the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role '{}'", other)),
+        }
+    }
+}
+
+/// What a caller is trying to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Status, positions, orders, dashboard state, event stream, exports, Grafana
+    ReadState,
+    Pause,
+    Resume,
+    ClosePosition,
+    /// Cancel pending orders, leaving positions open
+    CancelOrders,
+    /// Cancel pending orders and close every position
+    Flatten,
+    RefreshSentiment,
+    /// Allow live orders for a limited time
+    Arm,
+    /// Stop live orders until armed again
+    Disarm,
+    /// Switch a strategy component (trend filter, sentiment gate, ...) on or off
+    SetFeature,
+}
+
+impl Action {
+    /// Lowest role allowed to perform the action
+    pub fn required_role(self) -> Role {
+        match self {
+            Action::ReadState => Role::Viewer,
+            Action::Pause
+            | Action::Resume
+            | Action::ClosePosition
+            | Action::CancelOrders
+            | Action::RefreshSentiment
+            | Action::Disarm => Role::Operator,
+            Action::Flatten | Action::Arm | Action::SetFeature => Role::Admin,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::ReadState => "read_state",
+            Action::Pause => "pause",
+            Action::Resume => "resume",
+            Action::ClosePosition => "close_position",
+            Action::CancelOrders => "cancel_orders",
+            Action::Flatten => "flatten",
+            Action::RefreshSentiment => "refresh_sentiment",
+            Action::Arm => "arm",
+            Action::Disarm => "disarm",
+            Action::SetFeature => "set_feature",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessDenied {
+    /// No token, or one that is not configured
+    Unauthenticated,
+    /// Valid token whose role is too low for the action
+    Forbidden { role: Role, required: Role },
+}
+
+impl fmt::Display for AccessDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessDenied::Unauthenticated => write!(f, "Missing or invalid bearer token"),
+            AccessDenied::Forbidden { role, required } => {
+                write!(f, "Role {} may not do this (requires {})", role, required)
+            }
+        }
+    }
+}
+
+impl IntoResponse for AccessDenied {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AccessDenied::Unauthenticated => StatusCode::UNAUTHORIZED,
+            AccessDenied::Forbidden { .. } => StatusCode::FORBIDDEN,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Configured tokens and their roles
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    tokens: Vec<(SecretString, Role)>,
+}
+
+impl AccessPolicy {
+    /// Load `ACCESS_TOKENS`; malformed entries are skipped with a warning
+    pub fn from_env() -> Self {
+        Self::parse(&env::var("ACCESS_TOKENS").unwrap_or_default())
+    }
+
+    /// Parse comma-separated `role:token` pairs
+    pub fn parse(spec: &str) -> Self {
+        let mut policy = Self::default();
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let parsed = entry
+                .split_once(':')
+                .ok_or_else(|| "expected role:token".to_string())
+                .and_then(|(role, token)| Ok((role.parse::<Role>()?, token.trim())));
+            match parsed {
+                Ok((role, token)) if !token.is_empty() => {
+                    policy
+                        .tokens
+                        .push((SecretString::new(token.to_string()), role));
+                }
+                Ok(_) => warn!("ACCESS_TOKENS: empty token ignored"),
+                Err(err) => warn!("ACCESS_TOKENS: {}; entry ignored", err),
+            }
+        }
+        policy
+    }
+
+    /// Also accept `token` (typically a per-API token) with `role`
+    pub fn with_token(mut self, token: Option<String>, role: Role) -> Self {
+        if let Some(token) = token.filter(|t| !t.trim().is_empty()) {
+            self.tokens
+                .push((SecretString::new(token.trim().to_string()), role));
+        }
+        self
+    }
+
+    /// Whether no token is configured (callers keep their API disabled or open)
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Role of a presented token; the highest one if it is listed twice
+    pub fn role_of(&self, token: &str) -> Option<Role> {
+        let token = token.trim();
+        self.tokens
+            .iter()
+            .filter(|(secret, _)| constant_time_eq(secret.expose_secret(), token))
+            .map(|(_, role)| *role)
+            .max()
+    }
+
+    /// Authenticate `token` and check its role against `action`; the
+    /// decision is audited under `api`
+    pub fn authorize(
+        &self,
+        api: &str,
+        token: Option<&str>,
+        action: Action,
+    ) -> Result<Role, AccessDenied> {
+        let Some(role) = token.and_then(|t| self.role_of(t)) else {
+            warn!(target: "audit", "[AUDIT] {} {} denied: unauthenticated", api, action);
+            return Err(AccessDenied::Unauthenticated);
+        };
+        check_role(api, role, action)?;
+        Ok(role)
+    }
+
+    /// [`authorize`](Self::authorize) the `Authorization: Bearer` token of an
+    /// HTTP request
+    pub fn authorize_headers(
+        &self,
+        api: &str,
+        headers: &HeaderMap,
+        action: Action,
+    ) -> Result<Role, AccessDenied> {
+        let token = bearer_token(
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok()),
+        );
+        self.authorize(api, token, action)
+    }
+}
+
+/// Check an already authenticated role against `action`, auditing the decision
+pub fn check_role(api: &str, role: Role, action: Action) -> Result<(), AccessDenied> {
+    let required = action.required_role();
+    if role < required {
+        warn!(
+            target: "audit",
+            "[AUDIT] {} {} denied for role {} (requires {})",
+            api, action, role, required
+        );
+        return Err(AccessDenied::Forbidden { role, required });
+    }
+    if action == Action::ReadState {
+        // Dashboards poll every few seconds; reads are not worth an audit line
+        debug!(target: "audit", "[AUDIT] {} {} allowed for role {}", api, action, role);
+    } else {
+        info!(target: "audit", "[AUDIT] {} {} allowed for role {}", api, action, role);
+    }
+    Ok(())
+}
+
+/// Token from an `Authorization: Bearer <token>` header value
+pub fn bearer_token(header: Option<&str>) -> Option<&str> {
+    header.and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Compare without leaking the position of the first mismatch
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tokens_and_roles() {
+        let policy = AccessPolicy::parse("viewer:v1, operator:o1,admin:a1,root:x,nocolon,viewer:");
+        assert_eq!(policy.role_of("v1"), Some(Role::Viewer));
+        assert_eq!(policy.role_of("o1"), Some(Role::Operator));
+        assert_eq!(policy.role_of(" a1 "), Some(Role::Admin));
+        assert_eq!(policy.role_of("x"), None);
+        assert_eq!(policy.role_of(""), None);
+
+        let policy = policy.with_token(Some("v1".to_string()), Role::Admin);
+        assert_eq!(policy.role_of("v1"), Some(Role::Admin));
+        assert!(AccessPolicy::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_authorize_by_role() {
+        let policy = AccessPolicy::parse("viewer:v1,operator:o1,admin:a1");
+
+        assert_eq!(
+            policy.authorize("test", None, Action::ReadState),
+            Err(AccessDenied::Unauthenticated)
+        );
+        assert_eq!(
+            policy.authorize("test", Some("nope"), Action::ReadState),
+            Err(AccessDenied::Unauthenticated)
+        );
+        assert_eq!(
+            policy.authorize("test", Some("v1"), Action::ReadState),
+            Ok(Role::Viewer)
+        );
+        assert_eq!(
+            policy.authorize("test", Some("v1"), Action::Pause),
+            Err(AccessDenied::Forbidden {
+                role: Role::Viewer,
+                required: Role::Operator
+            })
+        );
+        assert!(policy
+            .authorize("test", Some("o1"), Action::ClosePosition)
+            .is_ok());
+        assert!(policy
+            .authorize("test", Some("o1"), Action::CancelOrders)
+            .is_ok());
+        assert!(policy
+            .authorize("test", Some("o1"), Action::Flatten)
+            .is_err());
+        assert!(policy
+            .authorize("test", Some("a1"), Action::Flatten)
+            .is_ok());
+        assert!(policy.authorize("test", Some("o1"), Action::Disarm).is_ok());
+        assert!(policy.authorize("test", Some("o1"), Action::Arm).is_err());
+        assert!(policy.authorize("test", Some("a1"), Action::Arm).is_ok());
+    }
+
+    #[test]
+    fn test_authorize_headers() {
+        let policy = AccessPolicy::parse("viewer:v1");
+        let mut headers = HeaderMap::new();
+        let denied = policy
+            .authorize_headers("test", &headers, Action::ReadState)
+            .unwrap_err();
+        assert_eq!(denied.into_response().status(), StatusCode::UNAUTHORIZED);
+
+        headers.insert(header::AUTHORIZATION, "Bearer v1".parse().unwrap());
+        assert_eq!(
+            policy.authorize_headers("test", &headers, Action::ReadState),
+            Ok(Role::Viewer)
+        );
+        let denied = policy
+            .authorize_headers("test", &headers, Action::Pause)
+            .unwrap_err();
+        assert_eq!(denied.into_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token(Some("Bearer abc")), Some("abc"));
+        assert_eq!(bearer_token(Some("Basic abc")), None);
+        assert_eq!(bearer_token(None), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Single-instance lock per trading account
+//!
+//! Two bots trading the same account would double every order. On startup the
+//! bot takes an exclusive advisory lock on `<INSTANCE_LOCK_DIR>/bot-<account>.lock`
+//! (default `data/`). The OS releases the lock when the process exits, even on a
+//! crash, so there is no stale-PID cleanup. The file records the owner's PID,
+//! host and start time so the second instance can say who holds the account.
+//!
+//! This only protects instances sharing a filesystem; it does not stop a second
+//! server from trading the same account.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{BotError, Result};
+
+/// What to do when another instance owns the account (`INSTANCE_LOCK_MODE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockConflictMode {
+    /// Exit with an error (default)
+    Refuse,
+    /// Keep running in dry-run without persistence or scheduled reports
+    Observe,
+}
+
+impl LockConflictMode {
+    /// Load from `INSTANCE_LOCK_MODE` (`refuse` or `observe`)
+    pub fn from_env() -> Self {
+        match env::var("INSTANCE_LOCK_MODE").as_deref().map(str::trim) {
+            Ok(mode) if mode.eq_ignore_ascii_case("observe") => Self::Observe,
+            _ => Self::Refuse,
+        }
+    }
+}
+
+/// Process holding the lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub host: String,
+    pub account_id: String,
+    pub started_at: DateTime<Utc>,
+}
+
+impl std::fmt::Display for LockOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pid {} on {} (since {})",
+            self.pid,
+            self.host,
+            self.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
+}
+
+/// Held lock; released when dropped or when the process exits
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+    path: PathBuf,
+}
+
+/// Result of trying to lock an account
+#[derive(Debug)]
+pub enum LockOutcome {
+    Acquired(InstanceLock),
+    /// Another process owns the account; owner details when they could be read
+    HeldBy(Option<LockOwner>),
+}
+
+impl InstanceLock {
+    /// Try to lock `account_id` in `INSTANCE_LOCK_DIR` (default `data`)
+    pub fn acquire(account_id: &str) -> Result<LockOutcome> {
+        let dir = env::var("INSTANCE_LOCK_DIR").unwrap_or_else(|_| "data".to_string());
+        Self::acquire_in(Path::new(&dir), account_id)
+    }
+
+    /// Try to lock `account_id` using a lock file in `dir`
+    pub fn acquire_in(dir: &Path, account_id: &str) -> Result<LockOutcome> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(lock_file_name(account_id));
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| {
+                BotError::Config(format!(
+                    "Failed to open lock file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut contents = String::new();
+                let _ = file.read_to_string(&mut contents);
+                return Ok(LockOutcome::HeldBy(serde_json::from_str(&contents).ok()));
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(BotError::Config(format!(
+                    "Failed to lock {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+
+        let owner = LockOwner {
+            pid: std::process::id(),
+            host: hostname(),
+            account_id: account_id.to_string(),
+            started_at: Utc::now(),
+        };
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(serde_json::to_string(&owner)?.as_bytes())?;
+        file.sync_all()?;
+
+        Ok(LockOutcome::Acquired(Self { _file: file, path }))
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// `bot-<account>.lock` with anything but `[A-Za-z0-9_-]` replaced
+fn lock_file_name(account_id: &str) -> String {
+    let safe: String = account_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "bot-{}.lock",
+        if safe.is_empty() { "default" } else { safe.as_str() }
+    )
+}
+
+fn hostname() -> String {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_second_lock_reports_owner() {
+        let dir = TempDir::new().unwrap();
+
+        let LockOutcome::Acquired(lock) = InstanceLock::acquire_in(dir.path(), "12345").unwrap()
+        else {
+            panic!("first lock should succeed");
+        };
+        assert!(lock.path().ends_with("bot-12345.lock"));
+
+        match InstanceLock::acquire_in(dir.path(), "12345").unwrap() {
+            LockOutcome::HeldBy(Some(owner)) => {
+                assert_eq!(owner.pid, std::process::id());
+                assert_eq!(owner.account_id, "12345");
+            }
+            other => panic!("expected lock to be held, got {:?}", other),
+        }
+
+        // Other accounts are independent
+        assert!(matches!(
+            InstanceLock::acquire_in(dir.path(), "67890").unwrap(),
+            LockOutcome::Acquired(_)
+        ));
+    }
+
+    #[test]
+    fn test_lock_released_on_drop() {
+        let dir = TempDir::new().unwrap();
+        let first = InstanceLock::acquire_in(dir.path(), "12345").unwrap();
+        drop(first);
+
+        assert!(matches!(
+            InstanceLock::acquire_in(dir.path(), "12345").unwrap(),
+            LockOutcome::Acquired(_)
+        ));
+    }
+
+    #[test]
+    fn test_lock_file_name_sanitized() {
+        assert_eq!(lock_file_name("12345"), "bot-12345.lock");
+        assert_eq!(lock_file_name("../live"), "bot-___live.lock");
+        assert_eq!(lock_file_name(""), "bot-default.lock");
+    }
+}
@@ -0,0 +1,260 @@
+//! Redact secrets from log output
+//!
+//! Error strings from the OAuth flow, the Perplexity / Telegram clients or a
+//! failed HTTP call can echo a token back (`invalid access_token=...`,
+//! `Authorization: Bearer ...`). `SecretRedactor` scans every outgoing log
+//! line and replaces:
+//! - the values of secret environment variables (see
+//!   [`SecretValidator::is_secret_var`], plus any names in `LOG_REDACT_VARS`)
+//! - anything that looks like a credential: bearer tokens and
+//!   `token=` / `client_secret=` / `api_key=` / `password=` style pairs,
+//!   including their JSON forms
+//!
+//! [`redacting_fmt_layer`] is the console layer used by the bot;
+//! the log shipper and crash reporter run their records through
+//! [`redact_secrets`] before they leave the process.
+
+use std::borrow::Cow;
+use std::env;
+use std::io;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use super::secrets_manager::{SecretString, SecretValidator};
+
+/// Replacement for every redacted value
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Secret values shorter than this are not matched (`1`, `true`, ports, ...)
+const MIN_SECRET_LEN: usize = 6;
+
+/// Credential-looking substrings; group 1 is kept, the rest is replaced
+const CREDENTIAL_PATTERNS: &[&str] = &[
+    r"(?i)(bearer\s+)[A-Za-z0-9._~+/=-]+",
+    r#"(?i)((?:access_token|refresh_token|client_secret|api_key|apikey|token|password|secret)["']?\s*[:=]\s*["']?)[^\s"'&,;}]+"#,
+];
+
+/// Known secret values and credential patterns to scrub from log lines
+#[derive(Debug, Clone, Default)]
+pub struct SecretRedactor {
+    secrets: Vec<SecretString>,
+    patterns: Vec<Regex>,
+}
+
+impl SecretRedactor {
+    /// Collect secret values from the environment and compile the patterns
+    pub fn from_env() -> Self {
+        let extra: Vec<String> = env::var("LOG_REDACT_VARS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        let mut redactor = Self::with_patterns();
+        for (name, value) in env::vars() {
+            if SecretValidator::is_secret_var(&name) || extra.contains(&name) {
+                redactor.add_secret(&value);
+            }
+        }
+        redactor
+    }
+
+    /// Redactor with the credential patterns and no known values
+    pub fn with_patterns() -> Self {
+        Self {
+            secrets: Vec::new(),
+            patterns: CREDENTIAL_PATTERNS
+                .iter()
+                .map(|p| Regex::new(p).expect("credential pattern compiles"))
+                .collect(),
+        }
+    }
+
+    /// Also redact `value`; lists like `ACCESS_TOKENS=role:token,...` are
+    /// split so each token is matched on its own
+    pub fn add_secret(&mut self, value: &str) {
+        let parts = std::iter::once(value).chain(
+            value
+                .split(',')
+                .map(|part| part.rsplit(':').next().unwrap_or(part)),
+        );
+        for part in parts.map(str::trim) {
+            if part.len() >= MIN_SECRET_LEN
+                && !self.secrets.iter().any(|s| s.expose_secret() == part)
+            {
+                self.secrets.push(SecretString::new(part.to_string()));
+            }
+        }
+        // Longest first so a token is not partly replaced by one of its parts
+        self.secrets
+            .sort_by_key(|s| std::cmp::Reverse(s.expose_secret().len()));
+    }
+
+    /// `text` with every known secret and credential-looking value replaced
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out = Cow::Borrowed(text);
+        for secret in &self.secrets {
+            if out.contains(secret.expose_secret()) {
+                out = Cow::Owned(out.replace(secret.expose_secret(), REDACTED));
+            }
+        }
+        for pattern in &self.patterns {
+            if pattern.is_match(&out) {
+                out = Cow::Owned(
+                    pattern
+                        .replace_all(&out, format!("${{1}}{}", REDACTED))
+                        .into_owned(),
+                );
+            }
+        }
+        out
+    }
+
+    /// Process-wide redactor, built from the environment on first use (load
+    /// `.env` before logging anything)
+    pub fn global() -> &'static SecretRedactor {
+        static GLOBAL: OnceLock<SecretRedactor> = OnceLock::new();
+        GLOBAL.get_or_init(SecretRedactor::from_env)
+    }
+}
+
+/// Redact `text` with the process-wide redactor
+pub fn redact_secrets(text: &str) -> Cow<'_, str> {
+    SecretRedactor::global().redact(text)
+}
+
+/// `MakeWriter` that redacts each formatted log line before writing it
+#[derive(Debug, Clone)]
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl RedactingMakeWriter<fn() -> io::Stdout> {
+    /// Redacting writer for standard output
+    pub fn stdout() -> Self {
+        Self::new(io::stdout)
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.inner.make_writer(),
+        }
+    }
+}
+
+/// Writer returned by [`RedactingMakeWriter`]; the fmt layer hands it one
+/// whole line per write
+pub struct RedactingWriter<W> {
+    inner: W,
+}
+
+impl<W: io::Write> io::Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.inner.write_all(redact_secrets(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Console `fmt` layer whose output goes through [`RedactingMakeWriter`]
+pub fn redacting_fmt_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_subscriber::fmt::layer().with_writer(RedactingMakeWriter::stdout())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_redacts_known_values() {
+        let mut redactor = SecretRedactor::default();
+        redactor.add_secret("cs-0123456789");
+        redactor.add_secret("viewer:dash-token-1,admin:root-token-2");
+        redactor.add_secret("1");
+
+        assert_eq!(
+            redactor.redact("OAuth refresh failed for client cs-0123456789"),
+            "OAuth refresh failed for client [REDACTED]"
+        );
+        assert_eq!(redactor.redact("denied root-token-2"), "denied [REDACTED]");
+        // Short values are ignored
+        assert_eq!(redactor.redact("retry 1 of 3"), "retry 1 of 3");
+        assert!(matches!(redactor.redact("clean line"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_redacts_credential_patterns() {
+        let redactor = SecretRedactor::with_patterns();
+
+        assert_eq!(
+            redactor.redact("request failed: Authorization: Bearer abc.def-123"),
+            "request failed: Authorization: Bearer [REDACTED]"
+        );
+        assert_eq!(
+            redactor.redact("GET /oauth?client_secret=s3cr3t&grant_type=refresh_token"),
+            "GET /oauth?client_secret=[REDACTED]&grant_type=refresh_token"
+        );
+        assert_eq!(
+            redactor.redact(r#"body: {"access_token": "xyz789", "expires_in": 3600}"#),
+            r#"body: {"access_token": "[REDACTED]", "expires_in": 3600}"#
+        );
+        assert_eq!(
+            redactor.redact("rsi=28.4 signal=buy"),
+            "rsi=28.4 signal=buy"
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_redacts_lines() {
+        let captured = Captured::default();
+        let sink = captured.clone();
+        let make_writer = RedactingMakeWriter::new(move || sink.clone());
+
+        let mut writer = make_writer.make_writer();
+        writer
+            .write_all(b"WARN api: 401 for Bearer tok_abcdef\n")
+            .unwrap();
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "WARN api: 401 for Bearer [REDACTED]\n");
+    }
+}
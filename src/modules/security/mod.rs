@@ -2,10 +2,24 @@
 //!
 //! Provides:
 //! - Secret validation and sanitized logging
-//! - Rate limiting for API calls (Perplexity, Twitter, cTrader)
+//! - Redaction of secrets and credential-looking values from every log line
+//! - Rate limiting for API calls (Perplexity, Twitter, cTrader) with queueing and metrics
+//! - Single-instance lock per trading account
+//! - Role-based access control (viewer / operator / admin) for the control-plane APIs
 
+pub mod access_control;
+pub mod instance_lock;
+pub mod log_redaction;
 pub mod rate_limiter;
 pub mod secrets_manager;
 
-pub use rate_limiter::{ApiRateLimiter, RateLimiterConfig};
+pub use access_control::{bearer_token, check_role, AccessDenied, AccessPolicy, Action, Role};
+pub use instance_lock::{InstanceLock, LockConflictMode, LockOutcome, LockOwner};
+pub use log_redaction::{
+    redact_secrets, redacting_fmt_layer, RedactingMakeWriter, SecretRedactor,
+};
+pub use rate_limiter::{
+    rate_limiter_snapshots, ApiRateLimiter, RateLimitExceeded, RateLimiterConfig,
+    RateLimiterSnapshot,
+};
 pub use secrets_manager::{SecretValidator, SecretString};
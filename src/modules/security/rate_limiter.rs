@@ -4,9 +4,17 @@
 //! - Perplexity API (sentiment analysis)
 //! - Twitter scraping
 //! - cTrader API (trading operations)
-
-use std::sync::Arc;
+//!
+//! Callers normally go through [`ApiRateLimiter::acquire`], which queues them
+//! (FIFO) until a slot frees up and only gives up after the limiter's
+//! `max_wait` (`RATE_LIMIT_<API>_MAX_WAIT_SECS`). Every limiter registers its
+//! counters so the Prometheus exporter can publish tokens remaining, queue
+//! depth, wait times and rejections per API.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
 use std::time::{Duration, Instant};
+use thiserror::Error;
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
@@ -23,6 +31,10 @@ pub struct RateLimiterConfig {
     pub max_backoff: f64,
     /// Jitter factor (0.0 to 1.0)
     pub jitter_factor: f64,
+    /// API name used in logs and metric labels
+    pub name: String,
+    /// Longest a caller queues in `acquire` before being rejected
+    pub max_wait: Duration,
 }
 
 impl Default for RateLimiterConfig {
@@ -33,10 +45,131 @@ impl Default for RateLimiterConfig {
             backoff_base: 2.0,
             max_backoff: 300.0, // 5 minutes
             jitter_factor: 0.1,
+            name: "default".to_string(),
+            max_wait: Duration::from_secs(30),
         }
     }
 }
 
+/// `RATE_LIMIT_<NAME>_MAX_WAIT_SECS`, falling back to `default`
+fn max_wait_from_env(name: &str, default: Duration) -> Duration {
+    std::env::var(format!("RATE_LIMIT_{}_MAX_WAIT_SECS", name.to_uppercase()))
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .map(Duration::from_secs_f64)
+        .unwrap_or(default)
+}
+
+/// A queued caller gave up after the limiter's `max_wait`
+#[derive(Debug, Clone, Error)]
+#[error("{api} rate limit: no slot after waiting {waited:?}")]
+pub struct RateLimitExceeded {
+    pub api: String,
+    pub waited: Duration,
+}
+
+/// Pressure counters for one limiter (monotonic except `queued`)
+#[derive(Debug, Default)]
+struct LimiterStats {
+    queued: AtomicUsize,
+    acquired: AtomicU64,
+    throttled: AtomicU64,
+    rejections: AtomicU64,
+    wait_micros_total: AtomicU64,
+    last_wait_micros: AtomicU64,
+    max_wait_micros: AtomicU64,
+    /// Slots left at the last check, used when the window is locked
+    last_remaining: AtomicUsize,
+}
+
+impl LimiterStats {
+    fn record_wait(&self, waited: Duration) {
+        let micros = waited.as_micros().min(u64::MAX as u128) as u64;
+        self.wait_micros_total.fetch_add(micros, Ordering::Relaxed);
+        self.last_wait_micros.store(micros, Ordering::Relaxed);
+        self.max_wait_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    fn snapshot(
+        &self,
+        api: &str,
+        max_requests: usize,
+        tokens_remaining: usize,
+    ) -> RateLimiterSnapshot {
+        let secs = |micros: &AtomicU64| micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        RateLimiterSnapshot {
+            api: api.to_string(),
+            max_requests,
+            tokens_remaining,
+            queued: self.queued.load(Ordering::Relaxed),
+            acquired_total: self.acquired.load(Ordering::Relaxed),
+            throttled_total: self.throttled.load(Ordering::Relaxed),
+            rejections_total: self.rejections.load(Ordering::Relaxed),
+            wait_seconds_total: secs(&self.wait_micros_total),
+            last_wait_seconds: secs(&self.last_wait_micros),
+            max_wait_seconds: secs(&self.max_wait_micros),
+        }
+    }
+}
+
+/// Point-in-time view of a limiter for metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimiterSnapshot {
+    pub api: String,
+    pub max_requests: usize,
+    pub tokens_remaining: usize,
+    /// Callers currently waiting in `acquire`
+    pub queued: usize,
+    pub acquired_total: u64,
+    /// Acquisitions that had to wait for a slot
+    pub throttled_total: u64,
+    /// Requests refused (queue timeout or a failed `check_rate_limit`)
+    pub rejections_total: u64,
+    pub wait_seconds_total: f64,
+    pub last_wait_seconds: f64,
+    pub max_wait_seconds: f64,
+}
+
+struct RegisteredLimiter {
+    api: String,
+    max_requests: usize,
+    window: Duration,
+    requests: Weak<Mutex<Vec<RequestRecord>>>,
+    stats: Weak<LimiterStats>,
+}
+
+fn registry() -> &'static std::sync::Mutex<Vec<RegisteredLimiter>> {
+    static REGISTRY: OnceLock<std::sync::Mutex<Vec<RegisteredLimiter>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| std::sync::Mutex::new(Vec::new()))
+}
+
+/// Snapshots of every live limiter, for the metrics exporter
+pub fn rate_limiter_snapshots() -> Vec<RateLimiterSnapshot> {
+    let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    registry.retain(|entry| entry.stats.strong_count() > 0);
+    registry
+        .iter()
+        .filter_map(|entry| {
+            let stats = entry.stats.upgrade()?;
+            let tokens_remaining = entry
+                .requests
+                .upgrade()
+                .and_then(|requests| {
+                    let requests = requests.try_lock().ok()?;
+                    let now = Instant::now();
+                    let in_window = requests
+                        .iter()
+                        .filter(|r| now.duration_since(r.timestamp) < entry.window)
+                        .count();
+                    Some(entry.max_requests.saturating_sub(in_window))
+                })
+                .unwrap_or_else(|| stats.last_remaining.load(Ordering::Relaxed));
+            Some(stats.snapshot(&entry.api, entry.max_requests, tokens_remaining))
+        })
+        .collect()
+}
+
 /// Request record for rate limiting
 #[derive(Debug, Clone)]
 struct RequestRecord {
@@ -48,6 +181,9 @@ pub struct ApiRateLimiter {
     pub(crate) config: RateLimiterConfig,
     requests: Arc<Mutex<Vec<RequestRecord>>>,
     pub(crate) consecutive_failures: Arc<Mutex<usize>>,
+    /// Held by the caller at the head of the queue so waiters are served in order
+    queue: Arc<Mutex<()>>,
+    stats: Arc<LimiterStats>,
 }
 
 impl ApiRateLimiter {
@@ -58,10 +194,27 @@ impl ApiRateLimiter {
 
     /// Create a rate limiter with custom config
     pub fn with_config(config: RateLimiterConfig) -> Self {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stats = Arc::new(LimiterStats::default());
+        stats
+            .last_remaining
+            .store(config.max_requests, Ordering::Relaxed);
+        registry()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(RegisteredLimiter {
+                api: config.name.clone(),
+                max_requests: config.max_requests,
+                window: config.window_duration,
+                requests: Arc::downgrade(&requests),
+                stats: Arc::downgrade(&stats),
+            });
         Self {
             config,
-            requests: Arc::new(Mutex::new(Vec::new())),
+            requests,
             consecutive_failures: Arc::new(Mutex::new(0)),
+            queue: Arc::new(Mutex::new(())),
+            stats,
         }
     }
 
@@ -73,6 +226,8 @@ impl ApiRateLimiter {
             backoff_base: 2.0,
             max_backoff: 300.0,
             jitter_factor: 0.1,
+            name: "perplexity".to_string(),
+            max_wait: max_wait_from_env("perplexity", Duration::from_secs(30)),
         })
     }
 
@@ -84,6 +239,22 @@ impl ApiRateLimiter {
             backoff_base: 3.0,
             max_backoff: 600.0,
             jitter_factor: 0.2,
+            name: "twitter".to_string(),
+            max_wait: max_wait_from_env("twitter", Duration::from_secs(60)),
+        })
+    }
+
+    /// Create a rate limiter for Reddit's public listings (10 requests/minute,
+    /// the unauthenticated allowance)
+    pub fn for_reddit() -> Self {
+        Self::with_config(RateLimiterConfig {
+            max_requests: 10,
+            window_duration: Duration::from_secs(60),
+            backoff_base: 3.0,
+            max_backoff: 600.0,
+            jitter_factor: 0.2,
+            name: "reddit".to_string(),
+            max_wait: max_wait_from_env("reddit", Duration::from_secs(60)),
         })
     }
 
@@ -95,12 +266,30 @@ impl ApiRateLimiter {
             backoff_base: 1.5,
             max_backoff: 60.0,
             jitter_factor: 0.05,
+            name: "ctrader".to_string(),
+            max_wait: max_wait_from_env("ctrader", Duration::from_secs(5)),
         })
     }
 
-    /// Check if a request is allowed and wait if necessary
+    /// Check if a request is allowed (non-blocking)
     /// Returns true if request can proceed, false if rate limited
     pub async fn check_rate_limit(&self) -> bool {
+        let allowed = self.try_take().await.is_ok();
+        if allowed {
+            self.stats.acquired.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.stats.rejections.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Rate limit EXCEEDED for {}: {} requests in {:?} window",
+                self.config.name, self.config.max_requests, self.config.window_duration
+            );
+        }
+        allowed
+    }
+
+    /// Take a slot if one is free; otherwise return how long until the oldest
+    /// request leaves the window
+    async fn try_take(&self) -> std::result::Result<(), Duration> {
         let mut requests = self.requests.lock().await;
         let now = Instant::now();
 
@@ -108,28 +297,89 @@ impl ApiRateLimiter {
         requests.retain(|record| now.duration_since(record.timestamp) < self.config.window_duration);
 
         // Check if we're under the limit
-        if requests.len() < self.config.max_requests {
+        let result = if requests.len() < self.config.max_requests {
             requests.push(RequestRecord { timestamp: now });
             debug!(
-                "Rate limit check PASS: {}/{} requests in {:?} window",
+                "Rate limit check PASS for {}: {}/{} requests in {:?} window",
+                self.config.name,
                 requests.len(),
                 self.config.max_requests,
                 self.config.window_duration
             );
-            true
+            Ok(())
         } else {
-            warn!(
-                "Rate limit EXCEEDED: {}/{} requests in {:?} window",
-                requests.len(),
-                self.config.max_requests,
-                self.config.window_duration
-            );
-            false
-        }
+            let next_free = requests
+                .iter()
+                .map(|r| {
+                    self.config
+                        .window_duration
+                        .saturating_sub(now.duration_since(r.timestamp))
+                })
+                .min()
+                .unwrap_or(self.config.window_duration);
+            Err(next_free)
+        };
+        self.stats.last_remaining.store(
+            self.config.max_requests.saturating_sub(requests.len()),
+            Ordering::Relaxed,
+        );
+        result
+    }
+
+    /// Wait in line for a slot, giving up after `max_wait`; returns the time spent
+    /// queued. Exponential backoff after failures counts towards the wait.
+    pub async fn acquire(&self) -> std::result::Result<Duration, RateLimitExceeded> {
+        self.acquire_within(Some(self.config.max_wait)).await
     }
 
     /// Wait for rate limit to be available, with exponential backoff on failures
+    /// (no deadline; prefer `acquire`)
     pub async fn wait_for_rate_limit(&self) {
+        let _ = self.acquire_within(None).await;
+    }
+
+    async fn acquire_within(
+        &self,
+        max_wait: Option<Duration>,
+    ) -> std::result::Result<Duration, RateLimitExceeded> {
+        let started = Instant::now();
+        let deadline = max_wait.map(|wait| started + wait);
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+        let result = self.wait_in_queue(started, deadline).await;
+        self.stats.queued.fetch_sub(1, Ordering::Relaxed);
+
+        let waited = started.elapsed();
+        self.stats.record_wait(waited);
+        match result {
+            Ok(()) => {
+                self.stats.acquired.fetch_add(1, Ordering::Relaxed);
+                if waited >= Duration::from_millis(1) {
+                    self.stats.throttled.fetch_add(1, Ordering::Relaxed);
+                    debug!("{} request queued for {:?}", self.config.name, waited);
+                }
+                Ok(waited)
+            }
+            Err(()) => {
+                self.stats.rejections.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "{} rate limit: gave up after queueing {:?} (max wait {:?})",
+                    self.config.name, waited, self.config.max_wait
+                );
+                Err(RateLimitExceeded {
+                    api: self.config.name.clone(),
+                    waited,
+                })
+            }
+        }
+    }
+
+    async fn wait_in_queue(
+        &self,
+        started: Instant,
+        deadline: Option<Instant>,
+    ) -> std::result::Result<(), ()> {
+        let time_left = |now: Instant| deadline.map(|d| d.saturating_duration_since(now));
+
         let failures = *self.consecutive_failures.lock().await;
 
         // Calculate backoff duration with exponential growth and jitter
@@ -139,29 +389,55 @@ impl ApiRateLimiter {
 
             // Add jitter to avoid thundering herd
             let jitter = backoff_secs * self.config.jitter_factor * rand::random::<f64>();
-            let total_wait = backoff_secs + jitter;
+            let total_wait = Duration::from_secs_f64(backoff_secs + jitter);
 
+            if time_left(started).is_some_and(|left| total_wait > left) {
+                return Err(());
+            }
             warn!(
-                "Rate limited after {} failures. Waiting {:.2}s (base: {:.2}s + jitter: {:.2}s)",
-                failures, total_wait, backoff_secs, jitter
+                "{} rate limited after {} failures. Waiting {:.2}s (base: {:.2}s + jitter: {:.2}s)",
+                self.config.name,
+                failures,
+                total_wait.as_secs_f64(),
+                backoff_secs,
+                jitter
             );
 
-            tokio::time::sleep(Duration::from_secs_f64(total_wait)).await;
+            tokio::time::sleep(total_wait).await;
         }
 
-        // Wait until we're under the limit
+        // Queue behind earlier callers, then wait until a slot frees up
+        let _turn = match time_left(Instant::now()) {
+            Some(left) => tokio::time::timeout(left, self.queue.lock())
+                .await
+                .map_err(|_| ())?,
+            None => self.queue.lock().await,
+        };
         loop {
-            if self.check_rate_limit().await {
-                break;
+            let next_free = match self.try_take().await {
+                Ok(()) => return Ok(()),
+                Err(next_free) => next_free.max(Duration::from_millis(1)),
+            };
+            match time_left(Instant::now()) {
+                Some(left) if next_free > left => return Err(()),
+                _ => {
+                    debug!("{} waiting {:?} for a free slot", self.config.name, next_free);
+                    tokio::time::sleep(next_free).await;
+                }
             }
-
-            // Wait for a portion of the window before retrying
-            let wait_duration = self.config.window_duration / (self.config.max_requests as u32);
-            debug!("Waiting {:?} before retry", wait_duration);
-            tokio::time::sleep(wait_duration).await;
         }
     }
 
+    /// Point-in-time metrics for this limiter
+    pub async fn snapshot(&self) -> RateLimiterSnapshot {
+        let tokens_remaining = self
+            .config
+            .max_requests
+            .saturating_sub(self.current_request_count().await);
+        self.stats
+            .snapshot(&self.config.name, self.config.max_requests, tokens_remaining)
+    }
+
     /// Record a successful API call (resets consecutive failures)
     pub async fn record_success(&self) {
         let mut failures = self.consecutive_failures.lock().await;
@@ -313,6 +589,62 @@ mod tests {
         assert!(limiter.check_rate_limit().await); // Now allowed
     }
 
+    #[tokio::test]
+    async fn test_acquire_queues_until_slot_frees() {
+        let limiter = ApiRateLimiter::with_config(RateLimiterConfig {
+            max_requests: 1,
+            window_duration: Duration::from_millis(100),
+            max_wait: Duration::from_secs(2),
+            ..Default::default()
+        });
+
+        assert!(limiter.acquire().await.unwrap() < Duration::from_millis(50));
+        let waited = limiter.acquire().await.unwrap();
+        assert!(waited >= Duration::from_millis(50), "waited {:?}", waited);
+
+        let snapshot = limiter.snapshot().await;
+        assert_eq!(snapshot.acquired_total, 2);
+        assert_eq!(snapshot.throttled_total, 1);
+        assert_eq!(snapshot.rejections_total, 0);
+        assert_eq!(snapshot.tokens_remaining, 0);
+        assert!(snapshot.max_wait_seconds >= 0.05);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_rejects_after_max_wait() {
+        let limiter = ApiRateLimiter::with_config(RateLimiterConfig {
+            name: "test_reject".to_string(),
+            max_requests: 1,
+            window_duration: Duration::from_secs(10),
+            max_wait: Duration::from_millis(50),
+            ..Default::default()
+        });
+
+        limiter.acquire().await.unwrap();
+        let err = limiter.acquire().await.unwrap_err();
+        assert_eq!(err.api, "test_reject");
+
+        let snapshot = rate_limiter_snapshots()
+            .into_iter()
+            .find(|s| s.api == "test_reject")
+            .unwrap();
+        assert_eq!(snapshot.rejections_total, 1);
+        assert_eq!(snapshot.queued, 0);
+        assert_eq!(snapshot.tokens_remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_limiter_leaves_registry() {
+        let limiter = ApiRateLimiter::with_config(RateLimiterConfig {
+            name: "test_dropped".to_string(),
+            ..Default::default()
+        });
+        assert!(rate_limiter_snapshots().iter().any(|s| s.api == "test_dropped"));
+
+        drop(limiter);
+        assert!(!rate_limiter_snapshots().iter().any(|s| s.api == "test_dropped"));
+    }
+
     #[tokio::test]
     async fn test_perplexity_config() {
         let limiter = ApiRateLimiter::for_perplexity();
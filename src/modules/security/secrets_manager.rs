@@ -1,6 +1,7 @@
 //! Secrets validation and sanitized logging
 //!
-//! Ensures critical secrets are present and never logged in plaintext.
+//! Ensures critical secrets are present and never logged in plaintext
+//! (see `log_redaction` for scrubbing them from log output).
 
 use std::fmt;
 
@@ -50,12 +51,19 @@ impl SecretValidator {
     /// Validate that all required secrets are present and non-empty
     /// Panics with clear error message if validation fails
     pub fn validate_required_secrets() {
-        let required_vars = vec![
+        let mut required_vars = vec![
             ("CTRADER_CLIENT_ID", "cTrader OAuth client ID"),
             ("CTRADER_CLIENT_SECRET", "cTrader OAuth client secret"),
             ("CTRADER_ACCOUNT_ID", "cTrader account ID"),
-            ("PERPLEXITY_API_KEY", "Perplexity API key for sentiment analysis"),
         ];
+        // The sentiment API key depends on LLM_BACKEND; a local Ollama needs none
+        let backend = std::env::var("LLM_BACKEND").unwrap_or_default();
+        match backend.trim().to_lowercase().as_str() {
+            "" | "perplexity" => required_vars
+                .push(("PERPLEXITY_API_KEY", "Perplexity API key for sentiment analysis")),
+            "ollama" => {}
+            _ => required_vars.push(("LLM_API_KEY", "API key of the LLM_BACKEND service")),
+        }
 
         let mut missing = Vec::new();
         let mut empty = Vec::new();
@@ -119,6 +127,22 @@ impl SecretValidator {
         }
     }
 
+    /// Whether an environment variable holds a credential (by name):
+    /// `*SECRET*`, `*TOKEN*`, `*PASSWORD*` or `*_KEY`, but not paths or URLs
+    pub fn is_secret_var(name: &str) -> bool {
+        let name = name.to_ascii_uppercase();
+        if ["_PATH", "_FILE", "_DIR", "_URL"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+        {
+            return false;
+        }
+        name.contains("SECRET")
+            || name.contains("TOKEN")
+            || name.contains("PASSWORD")
+            || name.ends_with("_KEY")
+    }
+
     /// Sanitize a string for safe logging (truncate and redact middle)
     pub fn sanitize_for_logging(secret: &str, prefix_len: usize, suffix_len: usize) -> String {
         if secret.len() <= prefix_len + suffix_len {
@@ -185,6 +209,16 @@ mod tests {
         assert_eq!(sanitized, "abc***(10 chars)***nop");
     }
 
+    #[test]
+    fn test_is_secret_var() {
+        assert!(SecretValidator::is_secret_var("CTRADER_CLIENT_SECRET"));
+        assert!(SecretValidator::is_secret_var("CTRADER_ACCESS_TOKEN"));
+        assert!(SecretValidator::is_secret_var("PERPLEXITY_API_KEY"));
+        assert!(SecretValidator::is_secret_var("LOG_SHIP_PASSWORD"));
+        assert!(!SecretValidator::is_secret_var("CTRADER_CLIENT_ID"));
+        assert!(!SecretValidator::is_secret_var("OAUTH_TOKEN_FILE"));
+    }
+
     #[test]
     fn test_validate_access_token_present() {
         let _lock = lock_env();
@@ -0,0 +1,985 @@
+//! Scheduled archive of bot data to remote storage.
+//!
+//! Every `ARCHIVE_INTERVAL_HOURS` a snapshot is uploaded under
+//! `<ARCHIVE_PREFIX>/<YYYYMMDDTHHMMSSZ>/`:
+//! - `positions.db` — consistent copy of the SQLite database
+//! - `trades.csv` / `daily_stats.csv` — closed trades and daily rollups
+//! - `trade_log.csv` — CSV trade log (`TRADE_LOG_PATH`)
+//! - any files listed in `ARCHIVE_EXTRA_PATHS` (e.g. backtest reports)
+//!
+//! Snapshots older than `ARCHIVE_RETENTION_DAYS` are deleted after each upload.
+//! The backend is selected with `ARCHIVE_BACKEND`:
+//! - `s3`: any S3-compatible store (AWS, R2, B2, MinIO), path-style, SigV4-signed
+//! - `sftp`: `sftp` batch mode against `ARCHIVE_SFTP_TARGET` (`user@host:/dir`)
+//! - `local`: a directory, typically a network mount (`ARCHIVE_LOCAL_DIR`)
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::trading::PositionDatabase;
+
+/// Snapshot directory names (UTC)
+const SNAPSHOT_ID_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// S3-compatible bucket credentials
+#[derive(Debug, Clone)]
+pub struct S3Target {
+    /// Base URL, e.g. `https://s3.eu-west-1.amazonaws.com` or a MinIO/R2 endpoint
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// SFTP destination used through the system `sftp` client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SftpTarget {
+    /// `user@host`
+    pub host: String,
+    /// Remote base directory
+    pub dir: String,
+    pub port: Option<u16>,
+    /// Private key file (`-i`), otherwise the SSH agent / default keys are used
+    pub identity_file: Option<PathBuf>,
+}
+
+/// Where snapshots are stored
+#[derive(Debug, Clone)]
+pub enum ArchiveBackend {
+    S3(S3Target),
+    Sftp(SftpTarget),
+    Local(PathBuf),
+}
+
+/// Archive schedule and contents
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    pub backend: ArchiveBackend,
+    /// Key / directory prefix all snapshots are stored under
+    pub prefix: String,
+    /// Time between snapshots
+    pub interval: Duration,
+    /// Snapshots older than this many days are deleted (0 keeps everything)
+    pub retention_days: u32,
+    /// CSV trade log written by the bot
+    pub trade_log_path: PathBuf,
+    /// Additional files or directories (top-level files only) to include
+    pub extra_paths: Vec<PathBuf>,
+}
+
+impl ArchiveConfig {
+    /// Load from environment; `None` when `ARCHIVE_BACKEND` is unset or incomplete
+    pub fn from_env() -> Option<Self> {
+        let backend = match env::var("ARCHIVE_BACKEND")
+            .ok()?
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "none" | "off" => return None,
+            "s3" => ArchiveBackend::S3(s3_target_from_env()?),
+            "sftp" => ArchiveBackend::Sftp(sftp_target_from_env()?),
+            "local" => match env_non_empty("ARCHIVE_LOCAL_DIR") {
+                Some(dir) => ArchiveBackend::Local(dir.into()),
+                None => {
+                    warn!("ARCHIVE_BACKEND=local requires ARCHIVE_LOCAL_DIR; archiving disabled");
+                    return None;
+                }
+            },
+            other => {
+                warn!("Unknown ARCHIVE_BACKEND '{}'; archiving disabled", other);
+                return None;
+            }
+        };
+
+        let interval_hours = env::var("ARCHIVE_INTERVAL_HOURS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|h| *h > 0)
+            .unwrap_or(24);
+
+        Some(Self {
+            backend,
+            prefix: env_non_empty("ARCHIVE_PREFIX")
+                .map(|p| p.trim_matches('/').to_string())
+                .unwrap_or_else(|| "palm-oil-bot".to_string()),
+            interval: Duration::from_secs(interval_hours * 3600),
+            retention_days: env::var("ARCHIVE_RETENTION_DAYS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(30),
+            trade_log_path: env::var("TRADE_LOG_PATH")
+                .unwrap_or_else(|_| "data/trade_log.csv".to_string())
+                .into(),
+            extra_paths: env::var("ARCHIVE_EXTRA_PATHS")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+fn env_non_empty(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn s3_target_from_env() -> Option<S3Target> {
+    let (Some(bucket), Some(access_key), Some(secret_key)) = (
+        env_non_empty("ARCHIVE_S3_BUCKET"),
+        env_non_empty("ARCHIVE_S3_ACCESS_KEY"),
+        env_non_empty("ARCHIVE_S3_SECRET_KEY"),
+    ) else {
+        warn!(
+            "ARCHIVE_BACKEND=s3 requires ARCHIVE_S3_BUCKET, ARCHIVE_S3_ACCESS_KEY and ARCHIVE_S3_SECRET_KEY; archiving disabled"
+        );
+        return None;
+    };
+    let region = env_non_empty("ARCHIVE_S3_REGION").unwrap_or_else(|| "us-east-1".to_string());
+    let endpoint = env_non_empty("ARCHIVE_S3_ENDPOINT")
+        .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+    Some(S3Target {
+        endpoint: endpoint.trim_end_matches('/').to_string(),
+        bucket,
+        region,
+        access_key,
+        secret_key,
+    })
+}
+
+fn sftp_target_from_env() -> Option<SftpTarget> {
+    let Some(mut target) = env_non_empty("ARCHIVE_SFTP_TARGET").and_then(|t| parse_sftp_target(&t))
+    else {
+        warn!(
+            "ARCHIVE_BACKEND=sftp requires ARCHIVE_SFTP_TARGET=user@host:/dir; archiving disabled"
+        );
+        return None;
+    };
+    target.port = env::var("ARCHIVE_SFTP_PORT")
+        .ok()
+        .and_then(|p| p.trim().parse().ok());
+    target.identity_file = env_non_empty("ARCHIVE_SFTP_KEY").map(PathBuf::from);
+    Some(target)
+}
+
+/// Parse `user@host:/remote/dir`
+pub fn parse_sftp_target(target: &str) -> Option<SftpTarget> {
+    let (host, dir) = target.trim().split_once(':')?;
+    if host.is_empty() || dir.is_empty() {
+        return None;
+    }
+    Some(SftpTarget {
+        host: host.to_string(),
+        dir: dir.trim_end_matches('/').to_string(),
+        port: None,
+        identity_file: None,
+    })
+}
+
+/// Snapshot directory name for a timestamp
+pub fn snapshot_id(at: DateTime<Utc>) -> String {
+    at.format(SNAPSHOT_ID_FORMAT).to_string()
+}
+
+/// Timestamp encoded in a snapshot directory name
+pub fn parse_snapshot_id(id: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(id, SNAPSHOT_ID_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+/// Snapshots older than the retention window (none when `retention_days` is 0)
+pub fn expired_snapshots(ids: &[String], now: DateTime<Utc>, retention_days: u32) -> Vec<String> {
+    if retention_days == 0 {
+        return Vec::new();
+    }
+    let cutoff = now - chrono::Duration::days(i64::from(retention_days));
+    ids.iter()
+        .filter(|id| parse_snapshot_id(id).map(|at| at < cutoff).unwrap_or(false))
+        .cloned()
+        .collect()
+}
+
+/// Time until the next snapshot is due, given the newest stored one
+pub fn next_due(latest: Option<DateTime<Utc>>, now: DateTime<Utc>, interval: Duration) -> Duration {
+    let Some(latest) = latest else {
+        return Duration::ZERO;
+    };
+    let elapsed = (now - latest).to_std().unwrap_or(Duration::ZERO);
+    interval.saturating_sub(elapsed)
+}
+
+/// Outcome of one archive run
+#[derive(Debug, Clone)]
+pub struct ArchiveReport {
+    pub snapshot_id: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Expired snapshots deleted afterwards
+    pub pruned: usize,
+}
+
+/// Uploads snapshots and enforces retention
+pub struct Archiver {
+    config: ArchiveConfig,
+    db: Option<PositionDatabase>,
+    client: reqwest::Client,
+}
+
+impl Archiver {
+    /// Create an archiver for the given database (trade log and extras are still archived without one)
+    pub fn new(config: ArchiveConfig, db: Option<PositionDatabase>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client: {}. Falling back to default client.",
+                    e
+                );
+                reqwest::Client::new()
+            });
+        Self { config, db, client }
+    }
+
+    /// Build, upload and prune in one go
+    pub async fn run_once(&self) -> Result<ArchiveReport> {
+        let now = Utc::now();
+        let id = snapshot_id(now);
+        let staging = env::temp_dir().join(format!("palm-oil-archive-{}", id));
+
+        let db = self.db.clone();
+        let config = self.config.clone();
+        let dir = staging.clone();
+        let files = tokio::task::spawn_blocking(move || build_snapshot(db.as_ref(), &config, &dir))
+            .await
+            .map_err(|e| BotError::Other(format!("Archive snapshot task failed: {}", e)))?;
+
+        let uploaded = match files {
+            Ok(files) => {
+                let bytes = files
+                    .iter()
+                    .filter_map(|(_, path)| fs::metadata(path).ok())
+                    .map(|m| m.len())
+                    .sum::<u64>();
+                self.upload_snapshot(&id, &files)
+                    .await
+                    .map(|_| (files.len(), bytes))
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = fs::remove_dir_all(&staging) {
+            debug!(
+                "Failed to remove archive staging dir {}: {}",
+                staging.display(),
+                err
+            );
+        }
+        let (files, bytes) = uploaded?;
+
+        let pruned = match self.prune(now).await {
+            Ok(pruned) => pruned,
+            Err(err) => {
+                warn!("Archive retention cleanup failed: {}", err);
+                0
+            }
+        };
+
+        Ok(ArchiveReport {
+            snapshot_id: id,
+            files,
+            bytes,
+            pruned,
+        })
+    }
+
+    /// Delete snapshots past the retention window; returns how many were removed
+    pub async fn prune(&self, now: DateTime<Utc>) -> Result<usize> {
+        let expired = expired_snapshots(
+            &self.list_snapshots().await?,
+            now,
+            self.config.retention_days,
+        );
+        for id in &expired {
+            self.delete_snapshot(id).await?;
+            info!("Archive snapshot {} expired and was deleted", id);
+        }
+        Ok(expired.len())
+    }
+
+    /// Snapshot IDs currently stored, oldest first
+    pub async fn list_snapshots(&self) -> Result<Vec<String>> {
+        let mut ids = match &self.config.backend {
+            ArchiveBackend::Local(root) => local_list(&root.join(&self.config.prefix))?,
+            ArchiveBackend::S3(target) => {
+                let keys = self
+                    .s3_list(target, &format!("{}/", self.config.prefix))
+                    .await?;
+                keys.iter()
+                    .filter_map(|k| k.strip_prefix(&format!("{}/", self.config.prefix)))
+                    .filter_map(|rest| rest.split('/').next())
+                    .map(str::to_string)
+                    .collect()
+            }
+            ArchiveBackend::Sftp(target) => {
+                // Leading '-' keeps a missing directory (no snapshots yet) from failing the batch
+                let listing = run_sftp(
+                    target,
+                    &format!("-ls -1 {}\n", sftp_quote(&self.remote_dir(target))),
+                )
+                .await?;
+                listing
+                    .lines()
+                    .filter(|l| !l.starts_with("sftp>"))
+                    .filter_map(|l| l.trim().rsplit('/').next())
+                    .map(str::to_string)
+                    .collect()
+            }
+        };
+        ids.retain(|id| parse_snapshot_id(id).is_some());
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    fn remote_dir(&self, target: &SftpTarget) -> String {
+        format!("{}/{}", target.dir, self.config.prefix)
+    }
+
+    async fn upload_snapshot(&self, id: &str, files: &[(String, PathBuf)]) -> Result<()> {
+        match &self.config.backend {
+            ArchiveBackend::Local(root) => {
+                let dir = root.join(&self.config.prefix).join(id);
+                fs::create_dir_all(&dir)?;
+                for (name, path) in files {
+                    fs::copy(path, dir.join(name))?;
+                }
+            }
+            ArchiveBackend::S3(target) => {
+                for (name, path) in files {
+                    let body = tokio::fs::read(path).await?;
+                    let key = format!("{}/{}/{}", self.config.prefix, id, name);
+                    self.s3_request(target, "PUT", &key, &[], body).await?;
+                }
+            }
+            ArchiveBackend::Sftp(target) => {
+                let dir = self.remote_dir(target);
+                let mut batch = String::new();
+                // `-mkdir` tolerates directories that already exist
+                let mut partial = String::new();
+                for part in dir.split('/').filter(|p| !p.is_empty()) {
+                    if !partial.is_empty() || dir.starts_with('/') {
+                        partial.push('/');
+                    }
+                    partial.push_str(part);
+                    batch.push_str(&format!("-mkdir {}\n", sftp_quote(&partial)));
+                }
+                let snapshot_dir = format!("{}/{}", dir, id);
+                batch.push_str(&format!("mkdir {}\n", sftp_quote(&snapshot_dir)));
+                for (name, path) in files {
+                    batch.push_str(&format!(
+                        "put {} {}\n",
+                        sftp_quote(&path.to_string_lossy()),
+                        sftp_quote(&format!("{}/{}", snapshot_dir, name))
+                    ));
+                }
+                run_sftp(target, &batch).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_snapshot(&self, id: &str) -> Result<()> {
+        match &self.config.backend {
+            ArchiveBackend::Local(root) => {
+                fs::remove_dir_all(root.join(&self.config.prefix).join(id))?;
+            }
+            ArchiveBackend::S3(target) => {
+                for key in self
+                    .s3_list(target, &format!("{}/{}/", self.config.prefix, id))
+                    .await?
+                {
+                    self.s3_request(target, "DELETE", &key, &[], Vec::new())
+                        .await?;
+                }
+            }
+            ArchiveBackend::Sftp(target) => {
+                let dir = sftp_quote(&format!("{}/{}", self.remote_dir(target), id));
+                // The glob stays outside the quotes so sftp still expands it
+                run_sftp(target, &format!("rm {}/*\nrmdir {}\n", dir, dir)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// All object keys under `prefix` (ListObjectsV2, following continuation tokens)
+    async fn s3_list(&self, target: &S3Target, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("list-type".to_string(), "2".to_string()),
+                ("prefix".to_string(), prefix.to_string()),
+            ];
+            if let Some(token) = &token {
+                query.push(("continuation-token".to_string(), token.clone()));
+            }
+            let body = self
+                .s3_request(target, "GET", "", &query, Vec::new())
+                .await?;
+            let page = parse_list_objects(&body);
+            keys.extend(page.keys);
+            match page.next_token {
+                Some(next) => token = Some(next),
+                None => return Ok(keys),
+            }
+        }
+    }
+
+    async fn s3_request(
+        &self,
+        target: &S3Target,
+        method: &str,
+        key: &str,
+        query: &[(String, String)],
+        body: Vec<u8>,
+    ) -> Result<String> {
+        let signed = sign_s3_request(target, method, key, query, &body, Utc::now())?;
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|e| BotError::Other(format!("Invalid HTTP method: {}", e)))?;
+        let mut request = self.client.request(method, &signed.url).body(body);
+        for (name, value) in &signed.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(BotError::Other(format!(
+                "S3 {} {} failed with {}: {}",
+                signed.method,
+                key,
+                status,
+                text.chars().take(300).collect::<String>()
+            )));
+        }
+        Ok(text)
+    }
+}
+
+/// Spawn the archive loop; the first run happens once the newest stored snapshot is due
+pub fn start_archive_scheduler(archiver: Archiver) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let latest = match archiver.list_snapshots().await {
+            Ok(ids) => ids.last().and_then(|id| parse_snapshot_id(id)),
+            Err(err) => {
+                warn!("Failed to list existing archive snapshots: {}", err);
+                None
+            }
+        };
+        let mut delay = next_due(latest, Utc::now(), archiver.config.interval);
+        info!(
+            "Archive scheduler started (every {}h, retention {} days, next in {}m)",
+            archiver.config.interval.as_secs() / 3600,
+            archiver.config.retention_days,
+            delay.as_secs() / 60
+        );
+
+        loop {
+            sleep(delay).await;
+            match archiver.run_once().await {
+                Ok(report) => info!(
+                    "Archived snapshot {} ({} files, {} bytes, {} expired removed)",
+                    report.snapshot_id, report.files, report.bytes, report.pruned
+                ),
+                Err(err) => warn!("Archive run failed: {}", err),
+            }
+            delay = archiver.config.interval;
+        }
+    })
+}
+
+/// Write the snapshot files into `dir`, returning `(archive name, local path)` pairs
+pub fn build_snapshot(
+    db: Option<&PositionDatabase>,
+    config: &ArchiveConfig,
+    dir: &Path,
+) -> Result<Vec<(String, PathBuf)>> {
+    fs::create_dir_all(dir)?;
+    let mut files = Vec::new();
+
+    if let Some(db) = db {
+        let db_path = dir.join("positions.db");
+        db.backup_to(&db_path)?;
+        files.push(("positions.db".to_string(), db_path));
+
+        let trades_path = dir.join("trades.csv");
+        db.export_closed_trades_csv(&trades_path)?;
+        files.push(("trades.csv".to_string(), trades_path));
+
+        let stats_path = dir.join("daily_stats.csv");
+        db.export_daily_stats_csv(&stats_path)?;
+        files.push(("daily_stats.csv".to_string(), stats_path));
+    }
+
+    if config.trade_log_path.is_file() {
+        files.push(("trade_log.csv".to_string(), config.trade_log_path.clone()));
+    }
+
+    for path in &config.extra_paths {
+        if path.is_dir() {
+            let prefix = file_name(path);
+            for entry in fs::read_dir(path)? {
+                let entry_path = entry?.path();
+                if entry_path.is_file() {
+                    files.push((format!("{}_{}", prefix, file_name(&entry_path)), entry_path));
+                }
+            }
+        } else if path.is_file() {
+            files.push((file_name(path), path.clone()));
+        } else {
+            warn!("Archive path {} does not exist; skipping", path.display());
+        }
+    }
+
+    Ok(files)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string())
+}
+
+fn local_list(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            ids.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(ids)
+}
+
+/// Quote a path for an sftp batch line so spaces and glob characters are taken literally
+fn sftp_quote(path: &str) -> String {
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Run an `sftp -b -` batch and return its stdout
+async fn run_sftp(target: &SftpTarget, batch: &str) -> Result<String> {
+    let mut command = Command::new("sftp");
+    command.arg("-b").arg("-").arg("-o").arg("BatchMode=yes");
+    if let Some(port) = target.port {
+        command.arg("-P").arg(port.to_string());
+    }
+    if let Some(identity) = &target.identity_file {
+        command.arg("-i").arg(identity);
+    }
+    command
+        .arg(&target.host)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(batch.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(BotError::Other(format!(
+            "sftp to {} failed ({}): {}",
+            target.host,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// One page of a ListObjectsV2 response
+#[derive(Debug, Default, PartialEq)]
+pub struct ListObjectsPage {
+    pub keys: Vec<String>,
+    pub next_token: Option<String>,
+}
+
+/// Extract keys and the continuation token from a ListObjectsV2 XML body
+pub fn parse_list_objects(xml: &str) -> ListObjectsPage {
+    let key_re = Regex::new(r"<Key>([^<]*)</Key>").expect("valid regex");
+    let token_re =
+        Regex::new(r"<NextContinuationToken>([^<]*)</NextContinuationToken>").expect("valid regex");
+    let truncated = xml.contains("<IsTruncated>true</IsTruncated>");
+
+    ListObjectsPage {
+        keys: key_re
+            .captures_iter(xml)
+            .map(|c| xml_unescape(&c[1]))
+            .collect(),
+        next_token: token_re
+            .captures(xml)
+            .filter(|_| truncated)
+            .map(|c| xml_unescape(&c[1])),
+    }
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// URL and headers of a SigV4-signed S3 request
+#[derive(Debug)]
+pub struct SignedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Sign a path-style S3 request (`<endpoint>/<bucket>/<key>`) with AWS Signature V4
+pub fn sign_s3_request(
+    target: &S3Target,
+    method: &str,
+    key: &str,
+    query: &[(String, String)],
+    payload: &[u8],
+    now: DateTime<Utc>,
+) -> Result<SignedRequest> {
+    let endpoint = url::Url::parse(&target.endpoint)
+        .map_err(|e| BotError::Config(format!("Invalid ARCHIVE_S3_ENDPOINT: {}", e)))?;
+    let host = match (endpoint.host_str(), endpoint.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => {
+            return Err(BotError::Config(
+                "ARCHIVE_S3_ENDPOINT has no host".to_string(),
+            ))
+        }
+    };
+
+    let mut path = format!(
+        "{}/{}",
+        endpoint.path().trim_end_matches('/'),
+        uri_encode(&target.bucket, false)
+    );
+    if !key.is_empty() {
+        path.push('/');
+        path.push_str(&uri_encode(key, true));
+    }
+    let mut sorted: Vec<(String, String)> = query
+        .iter()
+        .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+        .collect();
+    sorted.sort();
+    let canonical_query = sorted
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex::encode(Sha256::digest(payload));
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method, path, canonical_query, host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", date, target.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signature = hex::encode(hmac_sha256(
+        &signing_key(&target.secret_key, &date, &target.region, "s3"),
+        string_to_sign.as_bytes(),
+    ));
+
+    let mut url = format!("{}://{}{}", endpoint.scheme(), host, path);
+    if !canonical_query.is_empty() {
+        url.push('?');
+        url.push_str(&canonical_query);
+    }
+    Ok(SignedRequest {
+        method: method.to_string(),
+        url,
+        headers: vec![
+            ("x-amz-date".to_string(), amz_date),
+            ("x-amz-content-sha256".to_string(), payload_hash),
+            (
+                "authorization".to_string(),
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    target.access_key, scope, signed_headers, signature
+                ),
+            ),
+        ],
+    })
+}
+
+/// SigV4 signing key for a date / region / service
+pub fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// RFC 3986 encoding as required by SigV4 (optionally keeping `/` for object keys)
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::{CloseReason, OrderSide, Position};
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn local_config(root: &Path, trade_log_path: PathBuf) -> ArchiveConfig {
+        ArchiveConfig {
+            backend: ArchiveBackend::Local(root.to_path_buf()),
+            prefix: "bot".to_string(),
+            interval: Duration::from_secs(3600),
+            retention_days: 7,
+            trade_log_path,
+            extra_paths: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_id_roundtrip() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 4, 8, 5, 9).unwrap();
+        let id = snapshot_id(at);
+        assert_eq!(id, "20240304T080509Z");
+        assert_eq!(parse_snapshot_id(&id), Some(at));
+        assert_eq!(parse_snapshot_id("latest"), None);
+    }
+
+    #[test]
+    fn test_expired_snapshots() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 31, 0, 0, 0).unwrap();
+        let ids = vec![
+            "20240301T000000Z".to_string(),
+            "20240320T000000Z".to_string(),
+            "20240330T000000Z".to_string(),
+            "notes".to_string(),
+        ];
+        assert_eq!(
+            expired_snapshots(&ids, now, 10),
+            vec!["20240301T000000Z".to_string()]
+        );
+        assert!(expired_snapshots(&ids, now, 0).is_empty());
+    }
+
+    #[test]
+    fn test_next_due() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 12, 0, 0).unwrap();
+        let day = Duration::from_secs(24 * 3600);
+        assert_eq!(next_due(None, now, day), Duration::ZERO);
+        assert_eq!(
+            next_due(Some(now - chrono::Duration::hours(6)), now, day),
+            Duration::from_secs(18 * 3600)
+        );
+        assert_eq!(
+            next_due(Some(now - chrono::Duration::days(2)), now, day),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_parse_sftp_target() {
+        let target = parse_sftp_target("backup@storage.example.com:/home/backups/").unwrap();
+        assert_eq!(target.host, "backup@storage.example.com");
+        assert_eq!(target.dir, "/home/backups");
+        assert!(parse_sftp_target("storage.example.com").is_none());
+        assert!(parse_sftp_target(":/dir").is_none());
+
+        assert_eq!(sftp_quote("/srv/my backups"), "\"/srv/my backups\"");
+        assert_eq!(sftp_quote(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+
+    #[test]
+    fn test_signing_key_matches_aws_example() {
+        // Example from the AWS SigV4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+
+    #[test]
+    fn test_sign_s3_request_layout() {
+        let target = S3Target {
+            endpoint: "http://localhost:9000".to_string(),
+            bucket: "archive".to_string(),
+            region: "us-east-1".to_string(),
+            access_key: "AKID".to_string(),
+            secret_key: "secret".to_string(),
+        };
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+        let query = vec![
+            ("prefix".to_string(), "bot/".to_string()),
+            ("list-type".to_string(), "2".to_string()),
+        ];
+        let signed = sign_s3_request(&target, "GET", "", &query, b"", now).unwrap();
+
+        assert_eq!(
+            signed.url,
+            "http://localhost:9000/archive?list-type=2&prefix=bot%2F"
+        );
+        let auth = &signed
+            .headers
+            .iter()
+            .find(|(k, _)| k == "authorization")
+            .unwrap()
+            .1;
+        assert!(
+            auth.starts_with("AWS4-HMAC-SHA256 Credential=AKID/20240304/us-east-1/s3/aws4_request")
+        );
+
+        let put = sign_s3_request(
+            &target,
+            "PUT",
+            "bot/20240304T080000Z/trades.csv",
+            &[],
+            b"x",
+            now,
+        )
+        .unwrap();
+        assert_eq!(
+            put.url,
+            "http://localhost:9000/archive/bot/20240304T080000Z/trades.csv"
+        );
+    }
+
+    #[test]
+    fn test_parse_list_objects() {
+        let xml = "<ListBucketResult><IsTruncated>true</IsTruncated>\
+            <Contents><Key>bot/20240304T080000Z/trades.csv</Key></Contents>\
+            <Contents><Key>bot/20240304T080000Z/a&amp;b.csv</Key></Contents>\
+            <NextContinuationToken>abc</NextContinuationToken></ListBucketResult>";
+        let page = parse_list_objects(xml);
+        assert_eq!(page.keys.len(), 2);
+        assert_eq!(page.keys[1], "bot/20240304T080000Z/a&b.csv");
+        assert_eq!(page.next_token.as_deref(), Some("abc"));
+
+        let last = parse_list_objects("<IsTruncated>false</IsTruncated>");
+        assert_eq!(last, ListObjectsPage::default());
+    }
+
+    #[test]
+    fn test_build_snapshot_contents() {
+        let dir = TempDir::new().unwrap();
+        let db = PositionDatabase::new(dir.path().join("test.db")).unwrap();
+        db.upsert_position(&Position::new("42", "FCPO", OrderSide::Buy, 4850.0, 1.0))
+            .unwrap();
+        db.close_position("42", 4870.0, CloseReason::TakeProfit)
+            .unwrap();
+
+        let trade_log_path = dir.path().join("trade_log.csv");
+        fs::write(&trade_log_path, "timestamp,event\n").unwrap();
+        let reports = dir.path().join("reports");
+        fs::create_dir(&reports).unwrap();
+        fs::write(reports.join("weekly.md"), "# Week").unwrap();
+
+        let mut config = local_config(dir.path(), trade_log_path);
+        config.extra_paths = vec![reports, dir.path().join("missing.csv")];
+
+        let files = build_snapshot(Some(&db), &config, &dir.path().join("staging")).unwrap();
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "positions.db",
+                "trades.csv",
+                "daily_stats.csv",
+                "trade_log.csv",
+                "reports_weekly.md"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_archive_with_retention() {
+        let dir = TempDir::new().unwrap();
+        let remote = dir.path().join("remote");
+        let config = local_config(&remote, dir.path().join("trade_log.csv"));
+        fs::write(&config.trade_log_path, "timestamp,event\n").unwrap();
+
+        let expired = remote.join("bot").join("20000101T000000Z");
+        fs::create_dir_all(&expired).unwrap();
+        fs::write(expired.join("trade_log.csv"), "old").unwrap();
+
+        let archiver = Archiver::new(config, None);
+        let report = archiver.run_once().await.unwrap();
+
+        assert_eq!(report.files, 1);
+        assert_eq!(report.pruned, 1);
+        assert!(!expired.exists());
+        assert_eq!(
+            archiver.list_snapshots().await.unwrap(),
+            vec![report.snapshot_id.clone()]
+        );
+        assert!(remote
+            .join("bot")
+            .join(&report.snapshot_id)
+            .join("trade_log.csv")
+            .is_file());
+    }
+}
@@ -0,0 +1,461 @@
+//! Local, optionally encrypted backups of the persistence database.
+//!
+//! Each backup is an online SQLite snapshot, gzip-compressed and, when
+//! `BACKUP_PASSPHRASE` is set, sealed with ChaCha20-Poly1305 (key derived with
+//! PBKDF2-HMAC-SHA256). Files are written to `BACKUP_DIR` as
+//! `positions-<YYYYMMDDTHHMMSSZ>.db.gz[.enc]` with a `.sha256` sidecar, and
+//! every new backup is decoded again and checked with `PRAGMA integrity_check`
+//! before older ones are rotated out (`BACKUP_KEEP` newest are kept).
+//!
+//! The bot takes a backup every `BACKUP_INTERVAL_HOURS` when set; the
+//! `backup` binary creates, verifies, lists and restores them by hand.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::RngCore;
+use rusqlite::{Connection, OpenFlags};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use super::archive::{next_due, parse_snapshot_id, snapshot_id};
+use crate::error::{BotError, Result};
+use crate::modules::trading::PositionDatabase;
+
+/// Header of encrypted backups: magic, then salt and nonce
+const MAGIC: &[u8; 8] = b"PALMBK01";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ROUNDS: u32 = 100_000;
+
+const FILE_PREFIX: &str = "positions-";
+
+/// Backup location, rotation and schedule
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    /// Directory backups are written to
+    pub dir: PathBuf,
+    /// Number of newest backups to keep (0 keeps all)
+    pub keep: usize,
+    /// Encrypt with this passphrase when set
+    pub passphrase: Option<String>,
+    /// Scheduled backups in the bot; `None` means manual only
+    pub interval: Option<Duration>,
+}
+
+impl BackupConfig {
+    /// Load from `BACKUP_DIR`, `BACKUP_KEEP`, `BACKUP_PASSPHRASE` and `BACKUP_INTERVAL_HOURS`
+    pub fn from_env() -> Self {
+        Self {
+            dir: env::var("BACKUP_DIR")
+                .unwrap_or_else(|_| "data/backups".to_string())
+                .into(),
+            keep: env::var("BACKUP_KEEP")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(14),
+            passphrase: env::var("BACKUP_PASSPHRASE").ok().filter(|v| !v.is_empty()),
+            interval: env::var("BACKUP_INTERVAL_HOURS")
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|h| *h > 0)
+                .map(|h| Duration::from_secs(h * 3600)),
+        }
+    }
+}
+
+/// A verified backup written to disk
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub encrypted: bool,
+    /// Older backups removed by rotation
+    pub rotated: Vec<PathBuf>,
+}
+
+/// Snapshot, compress, optionally encrypt, verify and rotate
+pub fn create_backup(db: &PositionDatabase, config: &BackupConfig) -> Result<BackupInfo> {
+    create_backup_at(db, config, Utc::now())
+}
+
+fn create_backup_at(
+    db: &PositionDatabase,
+    config: &BackupConfig,
+    now: DateTime<Utc>,
+) -> Result<BackupInfo> {
+    fs::create_dir_all(&config.dir)?;
+    let encrypted = config.passphrase.is_some();
+    let name = format!(
+        "{}{}.db.gz{}",
+        FILE_PREFIX,
+        snapshot_id(now),
+        if encrypted { ".enc" } else { "" }
+    );
+    let path = config.dir.join(&name);
+
+    let scratch = scratch_file(&config.dir)?;
+    let snapshot = db
+        .backup_to(scratch.path())
+        .and_then(|_| check_integrity(scratch.path()))
+        .and_then(|_| fs::read(scratch.path()).map_err(BotError::from));
+    drop(scratch);
+    let payload = encode(&snapshot?, config.passphrase.as_deref())?;
+
+    // Write under a temporary name so a crash never leaves a truncated backup behind
+    let partial_path = config.dir.join(format!(".{}.partial", name));
+    fs::write(&partial_path, &payload)?;
+    fs::rename(&partial_path, &path)?;
+    fs::write(
+        checksum_path(&path),
+        format!("{}  {}\n", sha256_hex(&payload), name),
+    )?;
+
+    if let Err(err) = verify_backup(&path, config.passphrase.as_deref()) {
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(checksum_path(&path));
+        return Err(err);
+    }
+
+    let rotated = rotate_backups(&config.dir, config.keep)?;
+    Ok(BackupInfo {
+        path,
+        bytes: payload.len() as u64,
+        encrypted,
+        rotated,
+    })
+}
+
+/// Decode a backup and run `PRAGMA integrity_check` on the contained database
+pub fn verify_backup(path: &Path, passphrase: Option<&str>) -> Result<()> {
+    let database = decode_backup(path, passphrase)?;
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut scratch = scratch_file(dir)?;
+    scratch.write_all(&database)?;
+    scratch.flush()?;
+    check_integrity(scratch.path())
+}
+
+/// Decode a backup into a plain SQLite file at `output` (refuses to overwrite)
+pub fn restore_backup(path: &Path, passphrase: Option<&str>, output: &Path) -> Result<()> {
+    if output.exists() {
+        return Err(BotError::Config(format!(
+            "Refusing to overwrite existing file {}",
+            output.display()
+        )));
+    }
+    let database = decode_backup(path, passphrase)?;
+    fs::write(output, database)?;
+    check_integrity(output)
+}
+
+/// Read a backup, check its sidecar checksum and return the raw SQLite bytes
+pub fn decode_backup(path: &Path, passphrase: Option<&str>) -> Result<Vec<u8>> {
+    let payload = fs::read(path)?;
+
+    if let Ok(sidecar) = fs::read_to_string(checksum_path(path)) {
+        let expected = sidecar.split_whitespace().next().unwrap_or_default();
+        if expected != sha256_hex(&payload) {
+            return Err(BotError::Config(format!(
+                "Checksum mismatch for {}",
+                path.display()
+            )));
+        }
+    }
+
+    let encrypted = payload.starts_with(MAGIC);
+    let compressed = match (encrypted, passphrase) {
+        (true, Some(passphrase)) => decrypt(&payload, passphrase)?,
+        (true, None) => {
+            return Err(BotError::Config(format!(
+                "{} is encrypted; set BACKUP_PASSPHRASE",
+                path.display()
+            )))
+        }
+        (false, _) => payload,
+    };
+    decompress(&compressed)
+}
+
+/// Backups in `dir`, oldest first
+pub fn list_backups(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| backup_time(path).is_some())
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Delete all but the `keep` newest backups (and their checksums)
+pub fn rotate_backups(dir: &Path, keep: usize) -> Result<Vec<PathBuf>> {
+    if keep == 0 {
+        return Ok(Vec::new());
+    }
+    let backups = list_backups(dir)?;
+    let excess = backups.len().saturating_sub(keep);
+    let removed: Vec<PathBuf> = backups.into_iter().take(excess).collect();
+    for path in &removed {
+        fs::remove_file(path)?;
+        let _ = fs::remove_file(checksum_path(path));
+    }
+    Ok(removed)
+}
+
+/// Timestamp encoded in a backup file name
+pub fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    let name = path.file_name()?.to_str()?;
+    let stamp = name.strip_prefix(FILE_PREFIX)?;
+    let stamp = stamp
+        .strip_suffix(".db.gz.enc")
+        .or_else(|| stamp.strip_suffix(".db.gz"))?;
+    parse_snapshot_id(stamp)
+}
+
+/// Spawn the scheduled backup loop; `None` when no interval is configured
+pub fn start_backup_scheduler(
+    db: PositionDatabase,
+    config: BackupConfig,
+) -> Option<JoinHandle<()>> {
+    let interval = config.interval?;
+    Some(tokio::spawn(async move {
+        let latest = list_backups(&config.dir)
+            .ok()
+            .and_then(|backups| backups.last().and_then(|p| backup_time(p)));
+        let mut delay = next_due(latest, Utc::now(), interval);
+        info!(
+            "Backup scheduler started (every {}h to {}, keep {}, {})",
+            interval.as_secs() / 3600,
+            config.dir.display(),
+            config.keep,
+            if config.passphrase.is_some() {
+                "encrypted"
+            } else {
+                "unencrypted"
+            }
+        );
+
+        loop {
+            sleep(delay).await;
+            let (db, cfg) = (db.clone(), config.clone());
+            match tokio::task::spawn_blocking(move || create_backup(&db, &cfg)).await {
+                Ok(Ok(backup)) => info!(
+                    "Backup written to {} ({} bytes, {} rotated out)",
+                    backup.path.display(),
+                    backup.bytes,
+                    backup.rotated.len()
+                ),
+                Ok(Err(err)) => warn!("Database backup failed: {}", err),
+                Err(err) => warn!("Database backup task failed: {}", err),
+            }
+            delay = interval;
+        }
+    }))
+}
+
+/// Owner-only (0600) file in `dir` for a plaintext database, deleted on drop
+fn scratch_file(dir: &Path) -> Result<NamedTempFile> {
+    Ok(tempfile::Builder::new()
+        .prefix(".backup-scratch-")
+        .tempfile_in(dir)?)
+}
+
+fn checksum_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn check_integrity(path: &Path) -> Result<()> {
+    let conn =
+        Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|e| {
+            BotError::Config(format!("Failed to open backup {}: {}", path.display(), e))
+        })?;
+    let result: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| BotError::Config(format!("Integrity check failed to run: {}", e)))?;
+    if result != "ok" {
+        return Err(BotError::Config(format!(
+            "Integrity check failed for {}: {}",
+            path.display(),
+            result
+        )));
+    }
+    Ok(())
+}
+
+fn encode(database: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(database)?;
+    let compressed = encoder.finish()?;
+    match passphrase {
+        Some(passphrase) => encrypt(&compressed, passphrase),
+        None => Ok(compressed),
+    }
+}
+
+fn decompress(compressed: &[u8]) -> Result<Vec<u8>> {
+    let mut database = Vec::new();
+    GzDecoder::new(compressed)
+        .read_to_end(&mut database)
+        .map_err(|e| BotError::Config(format!("Backup is not valid gzip: {}", e)))?;
+    Ok(database)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| BotError::Other("Backup encryption failed".to_string()))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(payload: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let header = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if payload.len() < header || !payload.starts_with(MAGIC) {
+        return Err(BotError::Config("Not an encrypted backup".to_string()));
+    }
+    let salt = &payload[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = &payload[MAGIC.len() + SALT_LEN..header];
+
+    let key = derive_key(passphrase, salt);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(Nonce::from_slice(nonce), &payload[header..])
+        .map_err(|_| {
+            BotError::Config(
+                "Backup decryption failed (wrong passphrase or corrupted file)".to_string(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::{CloseReason, OrderSide, Position};
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn test_db(dir: &TempDir) -> PositionDatabase {
+        let db = PositionDatabase::new(dir.path().join("positions.db")).unwrap();
+        db.upsert_position(&Position::new("42", "FCPO", OrderSide::Buy, 4850.0, 1.0))
+            .unwrap();
+        db.close_position("42", 4870.0, CloseReason::TakeProfit)
+            .unwrap();
+        db
+    }
+
+    fn config(dir: &TempDir, passphrase: Option<&str>) -> BackupConfig {
+        BackupConfig {
+            dir: dir.path().join("backups"),
+            keep: 2,
+            passphrase: passphrase.map(str::to_string),
+            interval: None,
+        }
+    }
+
+    #[test]
+    fn test_encrypted_backup_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let db = test_db(&dir);
+        let config = config(&dir, Some("correct horse"));
+
+        let backup = create_backup(&db, &config).unwrap();
+        assert!(backup.encrypted);
+        assert!(backup.path.to_string_lossy().ends_with(".db.gz.enc"));
+        assert!(checksum_path(&backup.path).is_file());
+
+        assert!(verify_backup(&backup.path, None).is_err());
+        assert!(verify_backup(&backup.path, Some("wrong")).is_err());
+
+        let restored_path = dir.path().join("restored.db");
+        restore_backup(&backup.path, Some("correct horse"), &restored_path).unwrap();
+        let restored = PositionDatabase::new(&restored_path).unwrap();
+        assert_eq!(restored.get_closed_trades().unwrap().len(), 1);
+        assert!(restore_backup(&backup.path, Some("correct horse"), &restored_path).is_err());
+    }
+
+    #[test]
+    fn test_plain_backup_detects_corruption() {
+        let dir = TempDir::new().unwrap();
+        let db = test_db(&dir);
+        let backup = create_backup(&db, &config(&dir, None)).unwrap();
+        assert!(!backup.encrypted);
+        verify_backup(&backup.path, None).unwrap();
+        // Only the backup and its checksum: no plaintext scratch copy left over
+        assert_eq!(
+            fs::read_dir(backup.path.parent().unwrap()).unwrap().count(),
+            2
+        );
+
+        let mut bytes = fs::read(&backup.path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&backup.path, bytes).unwrap();
+        assert!(verify_backup(&backup.path, None).is_err());
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest() {
+        let dir = TempDir::new().unwrap();
+        let db = test_db(&dir);
+        let config = config(&dir, None);
+
+        for day in 1..=3 {
+            let at = Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap();
+            create_backup_at(&db, &config, at).unwrap();
+        }
+
+        let backups = list_backups(&config.dir).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(
+            backup_time(&backups[0]),
+            Some(Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap())
+        );
+        assert!(!checksum_path(&config.dir.join("positions-20240301T000000Z.db.gz")).exists());
+    }
+
+    #[test]
+    fn test_backup_time_parsing() {
+        assert!(backup_time(Path::new("positions-20240304T080000Z.db.gz.enc")).is_some());
+        assert!(backup_time(Path::new("positions-20240304T080000Z.db.gz.sha256")).is_none());
+        assert!(backup_time(Path::new("trades.csv")).is_none());
+    }
+}
@@ -0,0 +1,20 @@
+//! Off-host storage
+//!
+//! - `archive`: Scheduled snapshots of the database, trade CSVs and reports
+//!   to S3-compatible storage, SFTP or a mounted directory, with retention
+//! - `backup`: Local compressed, optionally encrypted database backups with
+//!   integrity checks and rotation
+//! - `tick_recorder`: Daily CSV/Parquet files of every live spot event, with
+//!   retention
+
+pub mod archive;
+pub mod backup;
+pub mod tick_recorder;
+
+pub use archive::{
+    start_archive_scheduler, ArchiveBackend, ArchiveConfig, ArchiveReport, Archiver,
+};
+pub use backup::{start_backup_scheduler, BackupConfig, BackupInfo};
+pub use tick_recorder::{
+    start_tick_recorder, TickRecordFormat, TickRecorder, TickRecorderConfig,
+};
@@ -0,0 +1,366 @@
+//! Recording of the live spot feed
+//!
+//! With `TICK_RECORD_ENABLED`, every spot event for the traded symbol is
+//! appended to the tick store layout under `TICK_RECORD_DIR` (default
+//! `data/ticks`), one file per symbol and UTC day:
+//!
+//! ```text
+//! <dir>/<SYMBOL>/<YYYY-MM-DD>.csv
+//! timestamp_ms,bid,ask
+//! ```
+//!
+//! so recorded days can be backtested with `backtest --ticks` or replayed
+//! with `--replay`. Spot events carry only the side that changed; the other
+//! is filled from the previous event, as `merge_quotes` does for downloads.
+//!
+//! Files rotate at midnight UTC (spot time). With `TICK_RECORD_FORMAT=parquet`
+//! each finished day is rewritten as `<YYYY-MM-DD>.parquet` and its CSV
+//! removed (needs the `parquet` cargo feature). On rotation, days older than
+//! `TICK_RECORD_RETENTION_DAYS` (default 30, 0 keeps everything) are deleted.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use tracing::{info, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::backtest::{read_tick_csv, write_tick_parquet, TickStore};
+use crate::modules::trading::Price;
+
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+const HEADER: &str = "timestamp_ms,bid,ask";
+
+/// How often buffered ticks are written out
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// File format of finished days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickRecordFormat {
+    Csv,
+    Parquet,
+}
+
+/// Where and how spot events are recorded
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickRecorderConfig {
+    pub dir: PathBuf,
+    pub format: TickRecordFormat,
+    /// Days of files kept; `None` keeps everything
+    pub retention_days: Option<u32>,
+}
+
+impl TickRecorderConfig {
+    /// Load from `TICK_RECORD_*`; `None` unless `TICK_RECORD_ENABLED` is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let enabled = env::var("TICK_RECORD_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        let format = match env::var("TICK_RECORD_FORMAT")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "csv" => TickRecordFormat::Csv,
+            "parquet" if cfg!(feature = "parquet") => TickRecordFormat::Parquet,
+            "parquet" => {
+                return Err(BotError::Config(
+                    "TICK_RECORD_FORMAT=parquet needs a build with `--features parquet`".into(),
+                ))
+            }
+            other => {
+                return Err(BotError::Config(format!(
+                    "TICK_RECORD_FORMAT must be csv or parquet, got {:?}",
+                    other
+                )))
+            }
+        };
+        let retention_days = match env::var("TICK_RECORD_RETENTION_DAYS") {
+            Ok(raw) if !raw.trim().is_empty() => raw.trim().parse::<u32>().map_err(|_| {
+                BotError::Config(format!("invalid TICK_RECORD_RETENTION_DAYS: {:?}", raw))
+            })?,
+            _ => DEFAULT_RETENTION_DAYS,
+        };
+
+        Ok(Some(Self {
+            dir: env::var("TICK_RECORD_DIR")
+                .unwrap_or_else(|_| "data/ticks".to_string())
+                .into(),
+            format,
+            retention_days: (retention_days > 0).then_some(retention_days),
+        }))
+    }
+}
+
+/// Appends one symbol's spot events to per-day files
+#[derive(Debug)]
+pub struct TickRecorder {
+    config: TickRecorderConfig,
+    symbol: String,
+    store: TickStore,
+    day: Option<(NaiveDate, BufWriter<File>)>,
+    bid: Option<f64>,
+    ask: Option<f64>,
+}
+
+impl TickRecorder {
+    pub fn new(config: TickRecorderConfig, symbol: impl Into<String>) -> Self {
+        Self {
+            store: TickStore::new(&config.dir),
+            config,
+            symbol: symbol.into(),
+            day: None,
+            bid: None,
+            ask: None,
+        }
+    }
+
+    /// Append one spot event, rotating to a new file when its day changes
+    pub fn record(&mut self, price: &Price) -> Result<()> {
+        // A side missing from the event (0) keeps its last known value
+        if price.bid > 0.0 {
+            self.bid = Some(price.bid);
+        }
+        if price.ask > 0.0 {
+            self.ask = Some(price.ask);
+        }
+        let (Some(bid), Some(ask)) = (self.bid, self.ask) else {
+            return Ok(());
+        };
+
+        let day = price.timestamp.date_naive();
+        if self.day.as_ref().map(|(current, _)| *current) != Some(day) {
+            self.rotate(day, price.timestamp)?;
+        }
+        if let Some((_, file)) = &mut self.day {
+            writeln!(
+                file,
+                "{},{},{}",
+                price.timestamp.timestamp_millis(),
+                bid,
+                ask
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered ticks to disk
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some((_, file)) = &mut self.day {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Close the current day, open `day` and apply retention
+    fn rotate(&mut self, day: NaiveDate, now: DateTime<Utc>) -> Result<()> {
+        if let Some((_, mut file)) = self.day.take() {
+            file.flush()?;
+        }
+        // Includes days left as CSV by a restart
+        self.finish_days_before(day)?;
+
+        let path = self.store.day_path(&self.symbol, day);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let is_new = fs::metadata(&path).map(|m| m.len() == 0).unwrap_or(true);
+        let mut file = BufWriter::new(OpenOptions::new().create(true).append(true).open(&path)?);
+        if is_new {
+            writeln!(file, "{}", HEADER)?;
+        }
+        info!("Recording {} ticks to {}", self.symbol, path.display());
+        self.day = Some((day, file));
+
+        let removed = self.prune(now)?;
+        if !removed.is_empty() {
+            info!("Tick retention removed {} old file(s)", removed.len());
+        }
+        Ok(())
+    }
+
+    /// Convert the CSV files of days before `day` to Parquet when configured
+    fn finish_days_before(&self, day: NaiveDate) -> Result<()> {
+        if self.config.format != TickRecordFormat::Parquet {
+            return Ok(());
+        }
+        for (finished, csv) in self.day_files()? {
+            if finished < day && csv.extension().is_some_and(|e| e == "csv") {
+                write_tick_parquet(&csv.with_extension("parquet"), &read_tick_csv(&csv)?)?;
+                fs::remove_file(&csv)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The symbol's day files and their days
+    fn day_files(&self) -> Result<Vec<(NaiveDate, PathBuf)>> {
+        let Ok(entries) = fs::read_dir(self.config.dir.join(&self.symbol)) else {
+            return Ok(Vec::new());
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let day = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok());
+            if let Some(day) = day {
+                files.push((day, path));
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Delete the symbol's day files older than the retention window
+    pub fn prune(&self, now: DateTime<Utc>) -> Result<Vec<PathBuf>> {
+        let Some(days) = self.config.retention_days else {
+            return Ok(Vec::new());
+        };
+        let cutoff = now.date_naive() - ChronoDuration::days(days as i64);
+        let mut removed = Vec::new();
+        for (day, path) in self.day_files()? {
+            if day < cutoff {
+                fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Record `symbol_id`'s spot events from `spots` until the feed closes
+pub fn start_tick_recorder(
+    mut recorder: TickRecorder,
+    symbol_id: i64,
+    mut spots: broadcast::Receiver<Price>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "Tick recorder started for {} in {}",
+            recorder.symbol,
+            recorder.config.dir.display()
+        );
+        let mut flush = interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                received = spots.recv() => match received {
+                    Ok(price) if price.symbol_id == symbol_id => {
+                        if let Err(err) = recorder.record(&price) {
+                            warn!("Tick recording failed: {}", err);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Tick recorder fell behind; {} spot event(s) not recorded", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = flush.tick() => {
+                    if let Err(err) = recorder.flush() {
+                        warn!("Tick recorder flush failed: {}", err);
+                    }
+                }
+            }
+        }
+        if let Err(err) = recorder.flush() {
+            warn!("Tick recorder flush failed: {}", err);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    fn spot(at: DateTime<Utc>, bid: f64, ask: f64) -> Price {
+        Price {
+            symbol_id: 1,
+            bid,
+            ask,
+            spread: ask - bid,
+            timestamp: at,
+            clock_skew_ms: None,
+            received_at: Instant::now(),
+        }
+    }
+
+    fn recorder(dir: &TempDir, retention_days: Option<u32>) -> TickRecorder {
+        TickRecorder::new(
+            TickRecorderConfig {
+                dir: dir.path().to_path_buf(),
+                format: TickRecordFormat::Csv,
+                retention_days,
+            },
+            "FCPO",
+        )
+    }
+
+    #[test]
+    fn test_spots_rotate_into_daily_files() {
+        let dir = TempDir::new().unwrap();
+        let mut recorder = recorder(&dir, None);
+        let at = |day, hour| Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+
+        // No ask yet: nothing to record
+        recorder.record(&spot(at(4, 9), 4800.0, 0.0)).unwrap();
+        recorder.record(&spot(at(4, 10), 0.0, 4802.0)).unwrap();
+        recorder.record(&spot(at(4, 23), 4801.0, 0.0)).unwrap();
+        recorder.record(&spot(at(5, 1), 4803.0, 4805.0)).unwrap();
+        recorder.flush().unwrap();
+
+        let store = TickStore::new(dir.path());
+        let first = read_tick_csv(&store.day_path("FCPO", at(4, 0).date_naive())).unwrap();
+        let quotes: Vec<(Option<f64>, Option<f64>)> =
+            first.iter().map(|t| (t.bid, t.ask)).collect();
+        assert_eq!(
+            quotes,
+            vec![(Some(4800.0), Some(4802.0)), (Some(4801.0), Some(4802.0))]
+        );
+        let second = read_tick_csv(&store.day_path("FCPO", at(5, 0).date_naive())).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].timestamp, at(5, 1));
+    }
+
+    #[test]
+    fn test_retention_removes_old_days() {
+        let dir = TempDir::new().unwrap();
+        let symbol_dir = dir.path().join("FCPO");
+        fs::create_dir_all(&symbol_dir).unwrap();
+        for name in [
+            "2024-02-01.csv",
+            "2024-02-27.parquet",
+            "2024-03-01.csv",
+            "notes.txt",
+        ] {
+            fs::write(symbol_dir.join(name), HEADER).unwrap();
+        }
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+
+        assert!(recorder(&dir, None).prune(now).unwrap().is_empty());
+        let removed = recorder(&dir, Some(5)).prune(now).unwrap();
+        assert_eq!(
+            removed,
+            vec![
+                symbol_dir.join("2024-02-01.csv"),
+                symbol_dir.join("2024-02-27.parquet")
+            ]
+        );
+        assert!(symbol_dir.join("2024-03-01.csv").exists());
+        assert!(symbol_dir.join("notes.txt").exists());
+    }
+}
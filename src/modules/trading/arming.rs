@@ -0,0 +1,193 @@
+//! Time-limited arming of live trading
+//!
+//! With `LIVE_ARMING_REQUIRED` (on by default against a LIVE account) the bot
+//! starts disarmed: it connects, manages open positions and logs its signals,
+//! but sends no new orders until an operator arms it (control API, admin
+//! console or `botctl arm`). Arming lasts `LIVE_ARMING_TTL_MINUTES` (default
+//! 60) or the TTL given with the command, capped at
+//! `LIVE_ARMING_MAX_TTL_MINUTES` (default 480); afterwards the bot disarms on
+//! its own. A test config left pointed at the live account therefore cannot
+//! trade for longer than one arming window.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::env;
+
+/// Arming settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArmingConfig {
+    /// New orders need an explicit arm
+    pub required: bool,
+    /// Arming window when the command gives none
+    pub default_ttl: Duration,
+    /// Longest window an operator may ask for
+    pub max_ttl: Duration,
+}
+
+impl ArmingConfig {
+    /// Read `LIVE_ARMING_REQUIRED` (default: `live`), `LIVE_ARMING_TTL_MINUTES`
+    /// and `LIVE_ARMING_MAX_TTL_MINUTES`
+    pub fn from_env(live: bool) -> Self {
+        let required = env::var("LIVE_ARMING_REQUIRED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(live);
+        let minutes = |key: &str, default: i64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        let max_ttl = Duration::minutes(minutes("LIVE_ARMING_MAX_TTL_MINUTES", 480));
+        Self {
+            required,
+            default_ttl: Duration::minutes(minutes("LIVE_ARMING_TTL_MINUTES", 60)).min(max_ttl),
+            max_ttl,
+        }
+    }
+}
+
+/// Arming state as reported to operators
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ArmingStatus {
+    pub required: bool,
+    /// Whether new orders may be sent now
+    pub armed: bool,
+    pub armed_until: Option<DateTime<Utc>>,
+}
+
+/// Gate for new live orders
+#[derive(Debug, Clone)]
+pub struct ArmingSwitch {
+    config: ArmingConfig,
+    armed_until: Option<DateTime<Utc>>,
+}
+
+impl ArmingSwitch {
+    /// Starts disarmed
+    pub fn new(config: ArmingConfig) -> Self {
+        Self {
+            config,
+            armed_until: None,
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.config.required
+    }
+
+    /// Whether new orders may be sent at `now`; always true when arming is
+    /// not required
+    pub fn allows_orders(&self, now: DateTime<Utc>) -> bool {
+        !self.config.required || self.armed_until.is_some_and(|until| now < until)
+    }
+
+    /// Arm for `ttl` (default window when `None`); returns the expiry
+    pub fn arm(
+        &mut self,
+        now: DateTime<Utc>,
+        ttl: Option<Duration>,
+    ) -> std::result::Result<DateTime<Utc>, String> {
+        let ttl = ttl.unwrap_or(self.config.default_ttl);
+        if ttl <= Duration::zero() {
+            return Err("arming TTL must be positive".to_string());
+        }
+        if ttl > self.config.max_ttl {
+            return Err(format!(
+                "arming TTL {}m exceeds the {}m maximum",
+                ttl.num_minutes(),
+                self.config.max_ttl.num_minutes()
+            ));
+        }
+        let until = now + ttl;
+        self.armed_until = Some(until);
+        Ok(until)
+    }
+
+    /// Disarm now; returns whether the switch was armed
+    pub fn disarm(&mut self) -> bool {
+        self.armed_until.take().is_some()
+    }
+
+    /// Clear an arming window that ended before `now`; returns its expiry so
+    /// the caller can report the automatic disarm once
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.armed_until {
+            Some(until) if now >= until => self.armed_until.take(),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> ArmingStatus {
+        ArmingStatus {
+            required: self.config.required,
+            armed: self.allows_orders(now),
+            armed_until: self.armed_until.filter(|until| now < *until),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ArmingConfig {
+        ArmingConfig {
+            required: true,
+            default_ttl: Duration::minutes(60),
+            max_ttl: Duration::minutes(480),
+        }
+    }
+
+    #[test]
+    fn test_arm_until_ttl_then_auto_disarm() {
+        let now = Utc::now();
+        let mut switch = ArmingSwitch::new(config());
+        assert!(!switch.allows_orders(now));
+
+        let until = switch.arm(now, None).unwrap();
+        assert_eq!(until, now + Duration::minutes(60));
+        assert!(switch.allows_orders(now + Duration::minutes(59)));
+        assert!(switch.expire(now + Duration::minutes(59)).is_none());
+
+        let later = now + Duration::minutes(60);
+        assert!(!switch.allows_orders(later));
+        assert_eq!(switch.expire(later), Some(until));
+        // Reported once
+        assert!(switch.expire(later).is_none());
+        assert_eq!(
+            switch.status(later),
+            ArmingStatus {
+                required: true,
+                armed: false,
+                armed_until: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_ttl_limits_and_manual_disarm() {
+        let now = Utc::now();
+        let mut switch = ArmingSwitch::new(config());
+
+        assert!(switch.arm(now, Some(Duration::minutes(481))).is_err());
+        assert!(switch.arm(now, Some(Duration::zero())).is_err());
+        assert!(!switch.allows_orders(now));
+
+        switch.arm(now, Some(Duration::minutes(5))).unwrap();
+        assert!(switch.allows_orders(now));
+        assert!(switch.disarm());
+        assert!(!switch.allows_orders(now));
+        assert!(!switch.disarm());
+    }
+
+    #[test]
+    fn test_not_required_always_allows() {
+        let switch = ArmingSwitch::new(ArmingConfig {
+            required: false,
+            ..config()
+        });
+        assert!(switch.allows_orders(Utc::now()));
+        assert!(switch.status(Utc::now()).armed);
+    }
+}
@@ -0,0 +1,205 @@
+//! Detection of balance changes not explained by trading
+//!
+//! Position sizing and the daily-loss circuit breaker read the account
+//! balance, so a deposit, a withdrawal or a broker adjustment changes their
+//! results without any trade being recorded. With `BALANCE_MONITOR_ENABLED`
+//! the bot re-reads the balance on every reconciliation pass and compares
+//! the change with the balance effect of the deals executed in between
+//! (realized P&L, swap and commission). A difference above
+//! `BALANCE_ANOMALY_TOLERANCE` (account currency, default 1.0) plus
+//! `BALANCE_ANOMALY_TOLERANCE_PERCENT` of the previous balance (default 0.1)
+//! raises a critical alert and, unless `BALANCE_ANOMALY_PAUSE=false`, pauses
+//! new entries until an operator resumes the bot.
+
+use std::env;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Anomaly thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceMonitorConfig {
+    /// Unexplained change always tolerated, in account currency
+    pub tolerance: f64,
+    /// Unexplained change tolerated on top, in percent of the previous balance
+    pub tolerance_percent: f64,
+    /// Pause new entries when an anomaly is found
+    pub pause_on_anomaly: bool,
+}
+
+impl Default for BalanceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: 1.0,
+            tolerance_percent: 0.1,
+            pause_on_anomaly: true,
+        }
+    }
+}
+
+impl BalanceMonitorConfig {
+    /// Read the thresholds when `BALANCE_MONITOR_ENABLED` is set; `None` otherwise
+    pub fn from_env() -> Option<Self> {
+        let flag = |key: &str, default: bool| {
+            env::var(key)
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(default)
+        };
+        if !flag("BALANCE_MONITOR_ENABLED", false) {
+            return None;
+        }
+
+        let defaults = Self::default();
+        let amount = |key: &str, default: f64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| *v >= 0.0)
+                .unwrap_or(default)
+        };
+        Some(Self {
+            tolerance: amount("BALANCE_ANOMALY_TOLERANCE", defaults.tolerance),
+            tolerance_percent: amount(
+                "BALANCE_ANOMALY_TOLERANCE_PERCENT",
+                defaults.tolerance_percent,
+            ),
+            pause_on_anomaly: flag("BALANCE_ANOMALY_PAUSE", defaults.pause_on_anomaly),
+        })
+    }
+}
+
+/// Balance read from the broker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceSnapshot {
+    pub balance: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// A balance change trading does not account for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceAnomaly {
+    pub previous: BalanceSnapshot,
+    pub current: BalanceSnapshot,
+    /// Balance effect of the deals executed in between
+    pub explained: f64,
+    /// Remaining change: deposit, withdrawal, adjustment, ...
+    pub unexplained: f64,
+}
+
+impl fmt::Display for BalanceAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "balance moved {:.2} → {:.2} since {} but trades explain only {:+.2} (unexplained {:+.2})",
+            self.previous.balance,
+            self.current.balance,
+            self.previous.at.format("%H:%M UTC"),
+            self.explained,
+            self.unexplained
+        )
+    }
+}
+
+/// Compares successive balance readings with the trading in between
+#[derive(Debug, Clone)]
+pub struct BalanceMonitor {
+    config: BalanceMonitorConfig,
+    last: Option<BalanceSnapshot>,
+}
+
+impl BalanceMonitor {
+    pub fn new(config: BalanceMonitorConfig) -> Self {
+        Self { config, last: None }
+    }
+
+    /// Monitor when `BALANCE_MONITOR_ENABLED` is set
+    pub fn from_env() -> Option<Self> {
+        BalanceMonitorConfig::from_env().map(Self::new)
+    }
+
+    pub fn config(&self) -> &BalanceMonitorConfig {
+        &self.config
+    }
+
+    /// Previous reading; deals since its time explain the next change
+    pub fn last(&self) -> Option<&BalanceSnapshot> {
+        self.last.as_ref()
+    }
+
+    /// Record `balance` read at `at`, given the balance effect of the deals
+    /// since the previous reading. The reading becomes the new baseline
+    /// either way, so one deposit is reported once.
+    pub fn observe(
+        &mut self,
+        balance: f64,
+        explained: f64,
+        at: DateTime<Utc>,
+    ) -> Option<BalanceAnomaly> {
+        let current = BalanceSnapshot { balance, at };
+        let previous = self.last.replace(current)?;
+
+        let unexplained = balance - previous.balance - explained;
+        let tolerance =
+            self.config.tolerance + previous.balance.abs() * self.config.tolerance_percent / 100.0;
+        (unexplained.abs() > tolerance).then_some(BalanceAnomaly {
+            previous,
+            current,
+            explained,
+            unexplained,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_trading_explains_balance_change() {
+        let mut monitor = BalanceMonitor::new(BalanceMonitorConfig::default());
+        let start = Utc::now();
+        assert!(monitor.observe(10_000.0, 0.0, start).is_none());
+
+        // A closed trade: -150 realized, -2.50 commission
+        let later = start + Duration::minutes(5);
+        assert!(monitor.observe(9_847.5, -152.5, later).is_none());
+        assert_eq!(monitor.last().unwrap().balance, 9_847.5);
+        // Rounding within the tolerance (1.0 + 0.1% of ~9.8k)
+        assert!(monitor
+            .observe(9_857.0, 0.0, later + Duration::minutes(5))
+            .is_none());
+    }
+
+    #[test]
+    fn test_deposit_is_an_anomaly_reported_once() {
+        let mut monitor = BalanceMonitor::new(BalanceMonitorConfig::default());
+        let start = Utc::now();
+        monitor.observe(10_000.0, 0.0, start);
+
+        let later = start + Duration::minutes(5);
+        let anomaly = monitor.observe(15_100.0, 100.0, later).unwrap();
+        assert_eq!(anomaly.unexplained, 5_000.0);
+        assert_eq!(anomaly.previous.balance, 10_000.0);
+
+        // The deposit is the new baseline
+        assert!(monitor
+            .observe(15_100.0, 0.0, later + Duration::minutes(5))
+            .is_none());
+    }
+
+    #[test]
+    fn test_withdrawal_is_an_anomaly() {
+        let mut monitor = BalanceMonitor::new(BalanceMonitorConfig {
+            tolerance: 0.0,
+            tolerance_percent: 0.0,
+            pause_on_anomaly: true,
+        });
+        let start = Utc::now();
+        monitor.observe(10_000.0, 0.0, start);
+        let anomaly = monitor
+            .observe(9_000.0, 0.0, start + Duration::minutes(5))
+            .unwrap();
+        assert_eq!(anomaly.unexplained, -1_000.0);
+    }
+}
@@ -0,0 +1,472 @@
+//! News blackout windows around scheduled report releases
+//!
+//! MPOB's monthly supply/demand data, cargo surveyor export estimates and
+//! USDA's WASDE move palm oil several percent within minutes, far beyond
+//! what the scalping stops are sized for. With `NEWS_BLACKOUT_ENABLED` the
+//! strategy takes no new entries from `NEWS_BLACKOUT_BEFORE_MINUTES` before
+//! a release until `NEWS_BLACKOUT_AFTER_MINUTES` after it, and with
+//! `NEWS_BLACKOUT_FLATTEN=true` the bot also closes open positions when a
+//! window starts.
+//!
+//! Releases come from either or both of:
+//! - `NEWS_BLACKOUT_EVENTS`: `;`-separated `YYYY-MM-DD HH:MM label` entries
+//! - `NEWS_BLACKOUT_ICS`: an iCalendar feed (URL or file path), re-read
+//!   every few hours; each `VEVENT` is one release, all-day events black out
+//!   the whole day
+//!
+//! Times without an explicit UTC marker are in `NEWS_BLACKOUT_UTC_OFFSET`
+//! (default `+00:00`; `+08:00` for MPOB's Malaysia-time schedule).
+
+use std::env;
+use std::fmt;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::error::{BotError, Result};
+use crate::modules::utils::market_calendar::parse_offset;
+
+/// How often the ICS feed is re-read
+pub const FEED_REFRESH_INTERVAL: Duration = Duration::hours(6);
+
+/// A period without new entries around one release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlackoutWindow {
+    pub start: DateTime<Utc>,
+    /// Exclusive
+    pub end: DateTime<Utc>,
+    pub label: String,
+}
+
+impl BlackoutWindow {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        at >= self.start && at < self.end
+    }
+}
+
+impl fmt::Display for BlackoutWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} - {})",
+            self.label,
+            self.start.format("%Y-%m-%d %H:%M"),
+            self.end.format("%H:%M UTC")
+        )
+    }
+}
+
+/// Blackout windows from the configured releases
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlackoutSchedule {
+    before: Duration,
+    after: Duration,
+    utc_offset: FixedOffset,
+    /// Close open positions when a window starts
+    pub flatten: bool,
+    /// Feed to re-read, URL or path
+    pub ics_source: Option<String>,
+    fixed: Vec<BlackoutWindow>,
+    feed: Vec<BlackoutWindow>,
+}
+
+impl BlackoutSchedule {
+    pub fn new(before: Duration, after: Duration, utc_offset: FixedOffset) -> Self {
+        Self {
+            before,
+            after,
+            utc_offset,
+            flatten: false,
+            ics_source: None,
+            fixed: Vec::new(),
+            feed: Vec::new(),
+        }
+    }
+
+    /// Schedule when `NEWS_BLACKOUT_ENABLED` is set; the ICS feed is loaded
+    /// separately with [`BlackoutSchedule::refresh_feed`]
+    pub fn from_env() -> Result<Option<Self>> {
+        let flag = |key: &str| {
+            env::var(key)
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false)
+        };
+        if !flag("NEWS_BLACKOUT_ENABLED") {
+            return Ok(None);
+        }
+
+        let var = |key: &str| env::var(key).ok().filter(|v| !v.trim().is_empty());
+        let minutes = |key: &str, default: i64| -> Result<Duration> {
+            match var(key) {
+                None => Ok(Duration::minutes(default)),
+                Some(raw) => raw
+                    .trim()
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|m| *m >= 0)
+                    .map(Duration::minutes)
+                    .ok_or_else(|| {
+                        BotError::Config(format!(
+                            "{} must be a whole number of minutes, got {:?}",
+                            key, raw
+                        ))
+                    }),
+            }
+        };
+        let offset = match var("NEWS_BLACKOUT_UTC_OFFSET") {
+            Some(raw) => parse_offset(&raw)?,
+            None => FixedOffset::east_opt(0).expect("UTC offset"),
+        };
+
+        let mut schedule = Self::new(
+            minutes("NEWS_BLACKOUT_BEFORE_MINUTES", 15)?,
+            minutes("NEWS_BLACKOUT_AFTER_MINUTES", 30)?,
+            offset,
+        );
+        schedule.flatten = flag("NEWS_BLACKOUT_FLATTEN");
+        schedule.ics_source = var("NEWS_BLACKOUT_ICS").map(|s| s.trim().to_string());
+        if let Some(raw) = var("NEWS_BLACKOUT_EVENTS") {
+            schedule.fixed = schedule.parse_events(&raw)?;
+        }
+        if schedule.fixed.is_empty() && schedule.ics_source.is_none() {
+            return Err(BotError::Config(
+                "NEWS_BLACKOUT_ENABLED needs NEWS_BLACKOUT_EVENTS or NEWS_BLACKOUT_ICS".into(),
+            ));
+        }
+        Ok(Some(schedule))
+    }
+
+    /// Window containing `at`, if any
+    pub fn active(&self, at: DateTime<Utc>) -> Option<&BlackoutWindow> {
+        self.windows().find(|w| w.contains(at))
+    }
+
+    /// Earliest window starting after `at`
+    pub fn next(&self, at: DateTime<Utc>) -> Option<&BlackoutWindow> {
+        self.windows()
+            .filter(|w| w.start > at)
+            .min_by_key(|w| w.start)
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &BlackoutWindow> {
+        self.fixed.iter().chain(&self.feed)
+    }
+
+    /// Settings and fixed releases as stable text, for the strategy
+    /// fingerprint; the ICS feed counts by its source, not by the events it
+    /// currently lists
+    pub fn canonical(&self) -> String {
+        let fixed: Vec<String> = self
+            .fixed
+            .iter()
+            .map(|w| format!("{}-{}", w.start.timestamp(), w.end.timestamp()))
+            .collect();
+        format!(
+            "{}/{}@{}{}|{}|{}",
+            self.before.num_minutes(),
+            self.after.num_minutes(),
+            self.utc_offset,
+            if self.flatten { "+flatten" } else { "" },
+            self.ics_source.as_deref().unwrap_or(""),
+            fixed.join(",")
+        )
+    }
+
+    /// Window around a release from `start` to `end`
+    pub fn window(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        label: impl Into<String>,
+    ) -> BlackoutWindow {
+        BlackoutWindow {
+            start: start - self.before,
+            end: end.max(start) + self.after,
+            label: label.into(),
+        }
+    }
+
+    /// Parse `;`-separated `YYYY-MM-DD HH:MM label` releases
+    pub fn parse_events(&self, raw: &str) -> Result<Vec<BlackoutWindow>> {
+        raw.split(';')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(|event| self.parse_event(event))
+            .collect()
+    }
+
+    fn parse_event(&self, event: &str) -> Result<BlackoutWindow> {
+        let invalid = || {
+            BotError::Config(format!(
+                "invalid blackout event {:?}, expected e.g. \"2026-11-10 12:30 MPOB\"",
+                event
+            ))
+        };
+        let mut parts = event.splitn(3, char::is_whitespace);
+        let date_time = format!(
+            "{} {}",
+            parts.next().unwrap_or(""),
+            parts.next().unwrap_or("")
+        );
+        let at = NaiveDateTime::parse_from_str(&date_time, "%Y-%m-%d %H:%M")
+            .ok()
+            .and_then(|local| self.to_utc(local))
+            .ok_or_else(invalid)?;
+        let label = match parts.next().map(str::trim) {
+            Some(label) if !label.is_empty() => label,
+            _ => "news",
+        };
+        Ok(self.window(at, at, label))
+    }
+
+    /// Replace the feed's windows with the events of an iCalendar document;
+    /// returns how many were read
+    pub fn set_feed(&mut self, ics: &str) -> usize {
+        self.feed = parse_ics(ics)
+            .into_iter()
+            .filter_map(|event| {
+                let start = self.ics_time(&event.start)?;
+                let end = match &event.end {
+                    Some(end) => self.ics_time(end)?,
+                    None if event.start.all_day => start + Duration::days(1),
+                    None => start,
+                };
+                Some(self.window(start, end, event.summary))
+            })
+            .collect();
+        self.feed.len()
+    }
+
+    /// Re-read `ics_source`; on failure the previous windows stay in place
+    pub async fn refresh_feed(&mut self) -> Result<usize> {
+        let Some(source) = self.ics_source.clone() else {
+            return Ok(0);
+        };
+        let ics = fetch_ics(&source).await?;
+        Ok(self.set_feed(&ics))
+    }
+
+    fn to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        self.utc_offset
+            .from_local_datetime(&local)
+            .single()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    fn ics_time(&self, time: &IcsTime) -> Option<DateTime<Utc>> {
+        if time.utc {
+            Some(Utc.from_utc_datetime(&time.value))
+        } else {
+            self.to_utc(time.value)
+        }
+    }
+}
+
+/// Read an iCalendar document from a URL or a file
+pub async fn fetch_ics(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()?;
+        Ok(client
+            .get(source)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    } else {
+        Ok(tokio::fs::read_to_string(source).await?)
+    }
+}
+
+/// `DTSTART`/`DTEND` value
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IcsTime {
+    value: NaiveDateTime,
+    /// Ends in `Z`
+    utc: bool,
+    /// `VALUE=DATE`
+    all_day: bool,
+}
+
+/// `VEVENT` being read
+#[derive(Debug, Default)]
+struct PartialEvent {
+    start: Option<IcsTime>,
+    end: Option<IcsTime>,
+    summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IcsEvent {
+    start: IcsTime,
+    end: Option<IcsTime>,
+    summary: String,
+}
+
+/// `VEVENT`s of an iCalendar document; events without a readable start are
+/// skipped
+fn parse_ics(text: &str) -> Vec<IcsEvent> {
+    // Long lines are folded: continuation lines start with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<PartialEvent> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or("").to_ascii_uppercase();
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(PartialEvent::default());
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                if let Some(PartialEvent {
+                    start: Some(start),
+                    end,
+                    summary,
+                }) = current.take()
+                {
+                    let summary = if summary.is_empty() {
+                        "news".to_string()
+                    } else {
+                        summary
+                    };
+                    events.push(IcsEvent {
+                        start,
+                        end,
+                        summary,
+                    });
+                }
+            }
+            field => {
+                let Some(event) = current.as_mut() else {
+                    continue;
+                };
+                match field {
+                    "DTSTART" => event.start = parse_ics_time(value),
+                    "DTEND" => event.end = parse_ics_time(value),
+                    "SUMMARY" => event.summary = unescape_ics(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+    events
+}
+
+fn parse_ics_time(value: &str) -> Option<IcsTime> {
+    let value = value.trim();
+    // `VALUE=DATE` values are bare dates
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime {
+            value: date.and_hms_opt(0, 0, 0)?,
+            utc: false,
+            all_day: true,
+        });
+    }
+    let (value, utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    Some(IcsTime {
+        value: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+        utc,
+        all_day: false,
+    })
+}
+
+fn unescape_ics(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn myt_schedule() -> BlackoutSchedule {
+        BlackoutSchedule::new(
+            Duration::minutes(15),
+            Duration::minutes(30),
+            FixedOffset::east_opt(8 * 3600).unwrap(),
+        )
+    }
+
+    fn utc(d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 11, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_events_become_padded_windows() {
+        let mut schedule = myt_schedule();
+        schedule.fixed = schedule
+            .parse_events("2026-11-10 12:30 MPOB supply & demand; 2026-11-12 01:00")
+            .unwrap();
+
+        // 12:30 MYT is 04:30 UTC
+        assert!(schedule.active(utc(10, 4, 14)).is_none());
+        let window = schedule.active(utc(10, 4, 15)).unwrap();
+        assert_eq!(window.label, "MPOB supply & demand");
+        assert_eq!(window.end, utc(10, 5, 0));
+        assert!(schedule.active(utc(10, 5, 0)).is_none());
+
+        let next = schedule.next(utc(10, 5, 0)).unwrap();
+        assert_eq!((next.start, next.label.as_str()), (utc(11, 16, 45), "news"));
+
+        assert!(schedule.parse_events("2026-11-10 MPOB").is_err());
+        assert!(schedule.parse_events("10/11/2026 12:30 MPOB").is_err());
+    }
+
+    #[test]
+    fn test_ics_feed() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20261110T043000Z\r\n\
+            DTEND:20261110T044500Z\r\n\
+            SUMMARY:MPOB Malaysian Palm Oil\r\n  Board report\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;TZID=Asia/Kuala_Lumpur:20261111T120000\r\n\
+            SUMMARY:Cargo surveyor\\, ITS\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20261113\r\n\
+            SUMMARY:USDA WASDE\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:not a date\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let mut schedule = myt_schedule();
+        assert_eq!(schedule.set_feed(ics), 3);
+
+        let windows: Vec<_> = schedule.windows().cloned().collect();
+        assert_eq!(windows[0].label, "MPOB Malaysian Palm Oil Board report");
+        assert_eq!(
+            (windows[0].start, windows[0].end),
+            (utc(10, 4, 15), utc(10, 5, 15))
+        );
+        // Local times use the configured offset
+        assert_eq!(windows[1].label, "Cargo surveyor, ITS");
+        assert_eq!(windows[1].start, utc(11, 3, 45));
+        // All day in Malaysia time
+        assert_eq!(windows[2].start, utc(12, 15, 45));
+        assert_eq!(windows[2].end, utc(13, 16, 30));
+    }
+}
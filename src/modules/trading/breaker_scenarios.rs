@@ -0,0 +1,225 @@
+//! Scripted circuit-breaker scenarios for the quick-test harness
+//!
+//! With `QUICK_TEST_SCENARIOS` set (a comma-separated list of the scenarios
+//! below, or `all`), `QUICK_TEST` also checks the breakers end to end once
+//! its trades are done. Each scenario trips a breaker with synthetic trade
+//! results, pushes a buy signal through the bot's normal entry path and
+//! expects it to be blocked by the circuit breakers; the risk state is put
+//! back afterwards. The bot runs the scenarios in probe mode: a signal that
+//! gets past every filter is withheld right before `execute_trade` and the
+//! scenario fails, so a broken breaker never sends a live order.
+//!
+//! - `consecutive_losses`: three losing trades in a row, the strategy's
+//!   consecutive-loss cool-down
+//! - `daily_loss`: one loss 10% past `MAX_DAILY_LOSS_PERCENT` of the balance
+//!
+//! A scenario blocked by another filter first (market closed, news
+//! blackout, ...) is inconclusive rather than passed.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{BotError, Result};
+
+use super::strategy::TradingStrategy;
+
+/// Blocker recorded on a decision when the risk checks refuse the entry
+pub const BREAKER_BLOCKER: &str = "circuit breakers active";
+
+/// Blocker recorded in probe mode when the signal got past every filter
+pub const PROBE_BLOCKER: &str = "breaker scenario: order withheld";
+
+/// Losing trades in a row that start the strategy's cool-down
+const CONSECUTIVE_LOSSES: u32 = 3;
+
+/// How far past the daily loss limit the synthetic loss goes
+const DAILY_LOSS_OVERSHOOT: f64 = 1.1;
+
+/// A breaker condition forced on the strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerScenario {
+    ConsecutiveLosses,
+    DailyLoss,
+}
+
+impl BreakerScenario {
+    pub const ALL: [Self; 2] = [Self::ConsecutiveLosses, Self::DailyLoss];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConsecutiveLosses => "consecutive_losses",
+            Self::DailyLoss => "daily_loss",
+        }
+    }
+
+    /// Parse a comma-separated list; `all` selects every scenario
+    pub fn parse_list(raw: &str) -> Result<Vec<Self>> {
+        let mut scenarios = Vec::new();
+        for name in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let selected = if name.eq_ignore_ascii_case("all") {
+                Self::ALL.to_vec()
+            } else {
+                vec![name.parse()?]
+            };
+            for scenario in selected {
+                if !scenarios.contains(&scenario) {
+                    scenarios.push(scenario);
+                }
+            }
+        }
+        Ok(scenarios)
+    }
+
+    /// Scenarios listed in `QUICK_TEST_SCENARIOS`; none when unset
+    pub fn from_env() -> Result<Vec<Self>> {
+        Self::parse_list(&env::var("QUICK_TEST_SCENARIOS").unwrap_or_default())
+    }
+
+    /// Record the synthetic trade results that trip this breaker
+    pub fn inject(&self, strategy: &mut TradingStrategy) {
+        match self {
+            Self::ConsecutiveLosses => {
+                let loss = strategy.account_balance() * 0.001;
+                for _ in 0..CONSECUTIVE_LOSSES {
+                    strategy.record_synthetic_trade(-loss);
+                }
+            }
+            Self::DailyLoss => {
+                let limit = strategy.trading_config().max_daily_loss_percent / 100.0;
+                let loss = strategy.account_balance() * limit * DAILY_LOSS_OVERSHOOT;
+                strategy.record_synthetic_trade(-loss);
+            }
+        }
+    }
+}
+
+impl fmt::Display for BreakerScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BreakerScenario {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scenario| scenario.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                BotError::Config(format!(
+                    "invalid QUICK_TEST_SCENARIOS entry: {:?} (expected consecutive_losses, \
+                     daily_loss or all)",
+                    s
+                ))
+            })
+    }
+}
+
+/// How a scenario ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScenarioVerdict {
+    /// Blocked by the circuit breakers, no order sent
+    Passed,
+    /// The signal got past the circuit breakers
+    Failed(String),
+    /// Blocked by another filter before the breakers were reached
+    Inconclusive(String),
+}
+
+/// Result of one scenario run
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioOutcome {
+    pub scenario: BreakerScenario,
+    pub verdict: ScenarioVerdict,
+}
+
+impl ScenarioOutcome {
+    /// Judge a scenario from the orders sent while it ran and the blockers
+    /// recorded on its decision
+    pub fn judge(scenario: BreakerScenario, orders_sent: u64, blockers: &[String]) -> Self {
+        let verdict = if orders_sent > 0 {
+            ScenarioVerdict::Failed(format!("{} order(s) sent", orders_sent))
+        } else if blockers.iter().any(|blocker| blocker == BREAKER_BLOCKER) {
+            ScenarioVerdict::Passed
+        } else if blockers.is_empty() || blockers.iter().any(|blocker| blocker == PROBE_BLOCKER) {
+            ScenarioVerdict::Failed("signal got past the circuit breakers".to_string())
+        } else {
+            ScenarioVerdict::Inconclusive(format!("blocked by {}", blockers.join(", ")))
+        };
+        Self { scenario, verdict }
+    }
+
+    pub fn failed(&self) -> bool {
+        matches!(self.verdict, ScenarioVerdict::Failed(_))
+    }
+}
+
+impl fmt::Display for ScenarioOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.verdict {
+            ScenarioVerdict::Passed => write!(f, "{}: PASS (blocked, no order)", self.scenario),
+            ScenarioVerdict::Failed(reason) => write!(f, "{}: FAIL ({})", self.scenario, reason),
+            ScenarioVerdict::Inconclusive(reason) => {
+                write!(f, "{}: INCONCLUSIVE ({})", self.scenario, reason)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_parse_and_judge() {
+        assert!(BreakerScenario::parse_list("").unwrap().is_empty());
+        assert_eq!(
+            BreakerScenario::parse_list("daily_loss, all").unwrap(),
+            vec![
+                BreakerScenario::DailyLoss,
+                BreakerScenario::ConsecutiveLosses
+            ]
+        );
+        assert!(BreakerScenario::parse_list("daily_loss,weekly_loss").is_err());
+
+        let scenario = BreakerScenario::DailyLoss;
+        let passed = ScenarioOutcome::judge(scenario, 0, &[BREAKER_BLOCKER.to_string()]);
+        assert_eq!(passed.to_string(), "daily_loss: PASS (blocked, no order)");
+        assert!(ScenarioOutcome::judge(scenario, 1, &[]).failed());
+        assert!(ScenarioOutcome::judge(scenario, 0, &[]).failed());
+        assert!(ScenarioOutcome::judge(scenario, 0, &[PROBE_BLOCKER.to_string()]).failed());
+        let closed = ScenarioOutcome::judge(scenario, 0, &["market closed".to_string()]);
+        assert!(matches!(closed.verdict, ScenarioVerdict::Inconclusive(_)));
+    }
+
+    #[test]
+    fn test_injected_breakers_block_until_restored() {
+        let config = Config::default();
+        let mut strategy = TradingStrategy::new(config.strategy, config.trading, 10000.0);
+        assert!(strategy.can_open_position().unwrap());
+
+        for scenario in BreakerScenario::ALL {
+            let saved = strategy.risk_state().clone();
+            scenario.inject(&mut strategy);
+            assert!(
+                !strategy.can_open_position().unwrap(),
+                "{} not blocked",
+                scenario
+            );
+            strategy.restore_risk_state(saved);
+            assert!(
+                strategy.can_open_position().unwrap(),
+                "{} not restored",
+                scenario
+            );
+        }
+        assert_eq!(strategy.risk_state().daily_trades, 0);
+    }
+}
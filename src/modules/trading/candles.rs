@@ -1,9 +1,25 @@
 //! Candle aggregation module
 //!
 //! Aggregates price ticks into OHLCV candles for different timeframes.
+//!
+//! OHLC is built from the mid price. When ticks carry bid/ask quotes, each
+//! candle also records its average and closing spread so entries can be
+//! modelled at the ask and exits at the bid.
+//!
+//! Boundary handling (live bot):
+//! - `CANDLE_CLOSE_GRACE_MS` (default 0): a candle stays open this long past
+//!   its end, and ticks stamped inside the window are added to it. Use roughly
+//!   the feed latency when spot events carry no server timestamp and ticks are
+//!   stamped on receipt; with server timestamps keep it at 0 to match broker
+//!   bars.
+//! - `LATE_TICK_POLICY` (`drop` | `current`, default `drop`): what to do with
+//!   a tick stamped before the candle being built, i.e. after its own candle
+//!   has already closed.
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::env;
+use tracing::warn;
 
 /// Timeframe for candle aggregation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -83,6 +99,12 @@ pub struct Candle {
     pub close: f64,
     /// Volume (number of ticks aggregated)
     pub volume: u64,
+    /// Average bid/ask spread over the candle (None for mid-only ticks)
+    #[serde(default)]
+    pub avg_spread: Option<f64>,
+    /// Spread of the last quote in the candle
+    #[serde(default)]
+    pub close_spread: Option<f64>,
 }
 
 impl Candle {
@@ -110,6 +132,16 @@ impl Candle {
     pub fn range(&self) -> f64 {
         self.high - self.low
     }
+
+    /// Ask at the close (mid + half the closing spread; mid when unknown)
+    pub fn ask_close(&self) -> f64 {
+        self.close + self.close_spread.unwrap_or(0.0) / 2.0
+    }
+
+    /// Bid at the close (mid - half the closing spread; mid when unknown)
+    pub fn bid_close(&self) -> f64 {
+        self.close - self.close_spread.unwrap_or(0.0) / 2.0
+    }
 }
 
 /// Price tick for aggregation
@@ -117,13 +149,59 @@ impl Candle {
 pub struct Tick {
     /// Timestamp of the tick
     pub timestamp: DateTime<Utc>,
-    /// Price
+    /// Price (mid when built from a quote)
     pub price: f64,
+    /// Bid quote, if known
+    pub bid: Option<f64>,
+    /// Ask quote, if known
+    pub ask: Option<f64>,
 }
 
 impl Tick {
     pub fn new(timestamp: DateTime<Utc>, price: f64) -> Self {
-        Self { timestamp, price }
+        Self {
+            timestamp,
+            price,
+            bid: None,
+            ask: None,
+        }
+    }
+
+    /// Tick from a bid/ask quote; `price` is the mid
+    pub fn from_quote(timestamp: DateTime<Utc>, bid: f64, ask: f64) -> Self {
+        Self {
+            timestamp,
+            price: (bid + ask) / 2.0,
+            bid: Some(bid),
+            ask: Some(ask),
+        }
+    }
+
+    /// Bid/ask spread, when both sides are known
+    pub fn spread(&self) -> Option<f64> {
+        Some((self.ask? - self.bid?).max(0.0))
+    }
+}
+
+/// Handling of ticks stamped before the candle currently being built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LateTickPolicy {
+    /// Discard the tick; closed candles are never changed
+    #[default]
+    Drop,
+    /// Fold the tick into the current candle as if it had just arrived
+    Current,
+}
+
+impl std::str::FromStr for LateTickPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "drop" => Ok(LateTickPolicy::Drop),
+            "current" => Ok(LateTickPolicy::Current),
+            other => Err(format!("unknown late tick policy {:?}", other)),
+        }
     }
 }
 
@@ -134,6 +212,12 @@ pub struct CandleBuilder {
     timeframe: TimeFrame,
     /// Current candle being built (if any)
     current_candle: Option<CandleInProgress>,
+    /// How long a candle stays open past its end
+    grace: Duration,
+    /// What to do with ticks older than the current candle
+    late_tick_policy: LateTickPolicy,
+    /// Ticks stamped before the current candle seen so far
+    late_ticks: u64,
 }
 
 /// Candle in progress (internal state)
@@ -145,25 +229,42 @@ struct CandleInProgress {
     low: f64,
     close: f64,
     volume: u64,
+    spread_sum: f64,
+    spread_count: u64,
+    close_spread: Option<f64>,
 }
 
 impl CandleInProgress {
-    fn new(timestamp: DateTime<Utc>, price: f64) -> Self {
-        Self {
+    fn new(timestamp: DateTime<Utc>, tick: &Tick) -> Self {
+        let mut candle = Self {
             timestamp,
-            open: price,
-            high: price,
-            low: price,
-            close: price,
+            open: tick.price,
+            high: tick.price,
+            low: tick.price,
+            close: tick.price,
             volume: 1,
-        }
+            spread_sum: 0.0,
+            spread_count: 0,
+            close_spread: None,
+        };
+        candle.record_spread(tick);
+        candle
     }
 
-    fn update(&mut self, price: f64) {
-        self.high = self.high.max(price);
-        self.low = self.low.min(price);
-        self.close = price;
+    fn update(&mut self, tick: &Tick) {
+        self.high = self.high.max(tick.price);
+        self.low = self.low.min(tick.price);
+        self.close = tick.price;
         self.volume += 1;
+        self.record_spread(tick);
+    }
+
+    fn record_spread(&mut self, tick: &Tick) {
+        if let Some(spread) = tick.spread() {
+            self.spread_sum += spread;
+            self.spread_count += 1;
+            self.close_spread = Some(spread);
+        }
     }
 
     fn into_candle(self, timeframe: TimeFrame) -> Candle {
@@ -175,6 +276,8 @@ impl CandleInProgress {
             low: self.low,
             close: self.close,
             volume: self.volume,
+            avg_spread: (self.spread_count > 0).then(|| self.spread_sum / self.spread_count as f64),
+            close_spread: self.close_spread,
         }
     }
 }
@@ -185,34 +288,78 @@ impl CandleBuilder {
         Self {
             timeframe,
             current_candle: None,
+            grace: Duration::zero(),
+            late_tick_policy: LateTickPolicy::default(),
+            late_ticks: 0,
         }
     }
 
+    /// Builder configured from `CANDLE_CLOSE_GRACE_MS` and `LATE_TICK_POLICY`
+    pub fn from_env(timeframe: TimeFrame) -> Self {
+        let grace_ms = env::var("CANDLE_CLOSE_GRACE_MS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(0);
+        let policy = match env::var("LATE_TICK_POLICY") {
+            Ok(value) => value.parse().unwrap_or_else(|err| {
+                warn!("LATE_TICK_POLICY: {}; using drop", err);
+                LateTickPolicy::Drop
+            }),
+            Err(_) => LateTickPolicy::Drop,
+        };
+        Self::new(timeframe)
+            .with_grace(Duration::milliseconds(grace_ms))
+            .with_late_tick_policy(policy)
+    }
+
+    /// Keep candles open for `grace` past their end. Negative values count
+    /// as zero; the window is capped below one timeframe.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        let max = self.timeframe.to_duration() - Duration::milliseconds(1);
+        self.grace = grace.max(Duration::zero()).min(max);
+        self
+    }
+
+    /// Set the handling of ticks older than the current candle
+    pub fn with_late_tick_policy(mut self, policy: LateTickPolicy) -> Self {
+        self.late_tick_policy = policy;
+        self
+    }
+
     /// Add a tick and potentially complete a candle
     ///
-    /// Returns `Some(Candle)` when a candle is completed, `None` otherwise
+    /// Returns `Some(Candle)` when a candle is completed, `None` otherwise.
+    /// A candle completes on the first tick stamped at or after its end plus
+    /// the grace window.
     pub fn add_tick(&mut self, tick: Tick) -> Option<Candle> {
-        let candle_start = self.timeframe.candle_start(tick.timestamp);
+        let Some(candle) = &mut self.current_candle else {
+            // Start new candle
+            let candle_start = self.timeframe.candle_start(tick.timestamp);
+            self.current_candle = Some(CandleInProgress::new(candle_start, &tick));
+            return None;
+        };
 
-        match &mut self.current_candle {
-            None => {
-                // Start new candle
-                self.current_candle = Some(CandleInProgress::new(candle_start, tick.price));
-                None
-            }
-            Some(candle) => {
-                if candle.timestamp == candle_start {
-                    // Same candle, update it
-                    candle.update(tick.price);
-                    None
-                } else {
-                    // New candle period started, complete the current one
-                    let completed = candle.clone().into_candle(self.timeframe);
-                    self.current_candle = Some(CandleInProgress::new(candle_start, tick.price));
-                    Some(completed)
-                }
+        if tick.timestamp < candle.timestamp {
+            // Its candle already closed
+            self.late_ticks += 1;
+            if self.late_tick_policy == LateTickPolicy::Current {
+                candle.update(&tick);
             }
+            return None;
+        }
+
+        let close_at = candle.timestamp + self.timeframe.to_duration() + self.grace;
+        if tick.timestamp < close_at {
+            // Same candle (or inside its grace window), update it
+            candle.update(&tick);
+            return None;
         }
+
+        // New candle period started, complete the current one
+        let completed = candle.clone().into_candle(self.timeframe);
+        let candle_start = self.timeframe.candle_start(tick.timestamp);
+        self.current_candle = Some(CandleInProgress::new(candle_start, &tick));
+        Some(completed)
     }
 
     /// Force complete the current candle (useful for finalization)
@@ -231,6 +378,11 @@ impl CandleBuilder {
     pub fn timeframe(&self) -> TimeFrame {
         self.timeframe
     }
+
+    /// Number of ticks received after their candle had closed
+    pub fn late_ticks(&self) -> u64 {
+        self.late_ticks
+    }
 }
 
 #[cfg(test)]
@@ -350,6 +502,8 @@ mod tests {
             low: 98.0,
             close: 103.0,
             volume: 10,
+            avg_spread: None,
+            close_spread: None,
         };
         assert!(bullish.is_bullish());
         assert!(!bullish.is_bearish());
@@ -362,6 +516,8 @@ mod tests {
             low: 95.0,
             close: 97.0,
             volume: 10,
+            avg_spread: None,
+            close_spread: None,
         };
         assert!(bearish.is_bearish());
         assert!(!bearish.is_bullish());
@@ -377,6 +533,8 @@ mod tests {
             low: 95.0,
             close: 105.0,
             volume: 10,
+            avg_spread: None,
+            close_spread: None,
         };
 
         assert_eq!(candle.body_size(), 5.0);
@@ -448,9 +606,137 @@ mod tests {
             low: 98.0,
             close: 102.0,
             volume: 10,
+            avg_spread: None,
+            close_spread: None,
         };
 
         let expected_end = ts + Duration::minutes(5);
         assert_eq!(candle.end_time(), expected_end);
     }
+
+    #[test]
+    fn test_candle_builder_tracks_spread() {
+        let mut builder = CandleBuilder::new(TimeFrame::M1);
+        let base_ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        builder.add_tick(Tick::from_quote(base_ts, 99.0, 101.0));
+        builder.add_tick(Tick::from_quote(base_ts + Duration::seconds(20), 101.5, 102.5));
+        let candle = builder.flush().unwrap();
+
+        assert_eq!(candle.open, 100.0);
+        assert_eq!(candle.close, 102.0);
+        assert!((candle.avg_spread.unwrap() - 1.5).abs() < 1e-9);
+        assert_eq!(candle.ask_close(), 102.5);
+        assert_eq!(candle.bid_close(), 101.5);
+    }
+
+    #[test]
+    fn test_mid_only_candle_has_no_spread() {
+        let mut builder = CandleBuilder::new(TimeFrame::M1);
+        let ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        builder.add_tick(Tick::new(ts, 100.0));
+        let candle = builder.flush().unwrap();
+
+        assert!(candle.avg_spread.is_none());
+        assert_eq!(candle.ask_close(), candle.close);
+        assert_eq!(candle.bid_close(), candle.close);
+    }
+
+    #[test]
+    fn test_tick_on_boundary_opens_next_candle() {
+        let mut builder = CandleBuilder::new(TimeFrame::M1);
+        let base_ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        builder.add_tick(Tick::new(base_ts, 100.0));
+        let last_ms = base_ts + Duration::seconds(60) - Duration::milliseconds(1);
+        assert!(builder.add_tick(Tick::new(last_ms, 101.0)).is_none());
+
+        let completed = builder
+            .add_tick(Tick::new(base_ts + Duration::seconds(60), 102.0))
+            .unwrap();
+        assert_eq!(completed.close, 101.0);
+        assert_eq!(builder.flush().unwrap().open, 102.0);
+    }
+
+    #[test]
+    fn test_grace_window_keeps_candle_open() {
+        let mut builder =
+            CandleBuilder::new(TimeFrame::M1).with_grace(Duration::milliseconds(500));
+        let base_ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let boundary = base_ts + Duration::seconds(60);
+
+        builder.add_tick(Tick::new(base_ts, 100.0));
+        // Inside the grace window: still the closing candle
+        assert!(builder.add_tick(Tick::new(boundary, 103.0)).is_none());
+        assert!(builder
+            .add_tick(Tick::new(boundary + Duration::milliseconds(499), 104.0))
+            .is_none());
+
+        // First tick past the window closes it
+        let completed = builder
+            .add_tick(Tick::new(boundary + Duration::milliseconds(500), 105.0))
+            .unwrap();
+        assert_eq!(completed.timestamp, base_ts);
+        assert_eq!(completed.close, 104.0);
+        assert_eq!(completed.volume, 3);
+
+        // The next candle keeps its aligned start
+        let next = builder.flush().unwrap();
+        assert_eq!(next.timestamp, boundary);
+        assert_eq!(next.open, 105.0);
+    }
+
+    #[test]
+    fn test_grace_is_capped_below_timeframe() {
+        let mut builder = CandleBuilder::new(TimeFrame::M1).with_grace(Duration::minutes(5));
+        let base_ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        builder.add_tick(Tick::new(base_ts, 100.0));
+        let completed = builder.add_tick(Tick::new(base_ts + Duration::seconds(120), 101.0));
+        assert!(completed.is_some());
+    }
+
+    #[test]
+    fn test_late_tick_dropped_by_default() {
+        let mut builder = CandleBuilder::new(TimeFrame::M1);
+        let base_ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        builder.add_tick(Tick::new(base_ts, 100.0));
+        builder
+            .add_tick(Tick::new(base_ts + Duration::seconds(60), 101.0))
+            .unwrap();
+
+        // Stamped in the closed candle: no new candle, current one untouched
+        assert!(builder
+            .add_tick(Tick::new(base_ts + Duration::seconds(59), 90.0))
+            .is_none());
+        assert_eq!(builder.late_ticks(), 1);
+        let current = builder.flush().unwrap();
+        assert_eq!(current.timestamp, base_ts + Duration::seconds(60));
+        assert_eq!(current.low, 101.0);
+        assert_eq!(current.volume, 1);
+    }
+
+    #[test]
+    fn test_late_tick_folded_into_current() {
+        let mut builder =
+            CandleBuilder::new(TimeFrame::M1).with_late_tick_policy(LateTickPolicy::Current);
+        let base_ts = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        builder.add_tick(Tick::new(base_ts, 100.0));
+        builder.add_tick(Tick::new(base_ts + Duration::seconds(60), 101.0));
+        builder.add_tick(Tick::new(base_ts + Duration::seconds(59), 90.0));
+
+        let current = builder.flush().unwrap();
+        assert_eq!(current.low, 90.0);
+        assert_eq!(current.volume, 2);
+        assert_eq!(builder.late_ticks(), 1);
+    }
+
+    #[test]
+    fn test_late_tick_policy_parse() {
+        assert_eq!("drop".parse(), Ok(LateTickPolicy::Drop));
+        assert_eq!(" Current ".parse(), Ok(LateTickPolicy::Current));
+        assert!("next".parse::<LateTickPolicy>().is_err());
+    }
 }
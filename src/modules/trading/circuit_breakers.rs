@@ -76,6 +76,11 @@ impl CircuitBreakers {
         }
     }
 
+    /// Seuil de volatilité (ratio ATR / moyenne) fourni par le détecteur de régime
+    pub fn set_volatility_threshold(&mut self, threshold: f64) {
+        self.volatility_threshold = threshold;
+    }
+
     /// Vérifie si la volatilité est anormalement élevée
     pub fn check_volatility(&self, atr: f64, avg_atr: f64) -> bool {
         if avg_atr == 0.0 {
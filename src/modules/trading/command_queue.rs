@@ -0,0 +1,188 @@
+//! Ordering guarantees for order-critical cTrader requests
+//!
+//! cTrader answers new orders, closes and SL/TP amendments asynchronously with
+//! `ProtoOAExecutionEvent`s, and a reconnect can drop a request or its answer.
+//! To keep a close from racing an amend (or a second close) on the same
+//! position, `CTraderClient` routes every order-critical request through a
+//! `PositionCommandQueue`:
+//!
+//! 1. Commands are keyed by position ID (new orders by symbol, since the
+//!    position does not exist yet; pending order cancels by order ID).
+//!    Commands with the same key run one at a time, in the order they were
+//!    issued; different keys run concurrently.
+//! 2. A command keeps its slot until the broker acknowledges it with an
+//!    execution event or error for that position, not just until it is sent.
+//! 3. When the acknowledgement never arrives (timeout, disconnect) the
+//!    outcome is unknown, so the key is marked unresolved and further
+//!    commands on it are refused until the next broker reconcile clears it.
+//!
+//! Rule 3 is what protects reconnects: after a dropped close, an amend cannot
+//! be sent for a position that may already be gone.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// What a command is serialized on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandKey {
+    /// Opening a new position on a symbol
+    NewOrder { symbol_id: i64 },
+    /// Any command on an existing position
+    Position(i64),
+    /// Any command on a pending (limit/stop) order
+    PendingOrder(i64),
+}
+
+/// Order-critical request kinds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderCommand {
+    NewOrder,
+    Close,
+    Amend,
+    Cancel,
+}
+
+impl fmt::Display for OrderCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderCommand::NewOrder => write!(f, "new order"),
+            OrderCommand::Close => write!(f, "close"),
+            OrderCommand::Amend => write!(f, "amend"),
+            OrderCommand::Cancel => write!(f, "cancel"),
+        }
+    }
+}
+
+/// Exclusive turn for one key; the next command runs when it is dropped
+pub struct CommandSlot {
+    key: CommandKey,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl CommandSlot {
+    pub fn key(&self) -> CommandKey {
+        self.key
+    }
+}
+
+/// Per-key FIFO serialization plus tracking of unresolved keys
+#[derive(Default)]
+pub struct PositionCommandQueue {
+    slots: StdMutex<HashMap<CommandKey, Arc<Mutex<()>>>>,
+    unresolved: StdMutex<HashMap<CommandKey, OrderCommand>>,
+}
+
+impl PositionCommandQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for this key's turn (FIFO among waiters on the same key)
+    pub async fn enter(&self, key: CommandKey) -> CommandSlot {
+        let lock = {
+            let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+            // Drop locks nobody holds or waits on so the map stays small
+            slots.retain(|k, lock| *k == key || Arc::strong_count(lock) > 1);
+            slots.entry(key).or_default().clone()
+        };
+        CommandSlot {
+            key,
+            _guard: lock.lock_owned().await,
+        }
+    }
+
+    /// The last command on `key` if its outcome is unknown
+    pub fn unresolved(&self, key: CommandKey) -> Option<OrderCommand> {
+        self.unresolved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+            .copied()
+    }
+
+    /// Record that `command` on `key` was never acknowledged
+    pub fn mark_unresolved(&self, key: CommandKey, command: OrderCommand) {
+        self.unresolved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, command);
+    }
+
+    /// Clear unresolved keys once broker state has been reloaded
+    pub fn resolve_all(&self) -> Vec<CommandKey> {
+        self.unresolved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain()
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_same_position_commands_run_in_order() {
+        let queue = Arc::new(PositionCommandQueue::new());
+        let log = Arc::new(StdMutex::new(Vec::new()));
+
+        let first = queue.enter(CommandKey::Position(7)).await;
+        let mut handles = Vec::new();
+        for label in ["close", "amend"] {
+            let (queue, log) = (queue.clone(), log.clone());
+            handles.push(tokio::spawn(async move {
+                let _slot = queue.enter(CommandKey::Position(7)).await;
+                log.lock().unwrap().push(label);
+            }));
+            // Let the task start waiting before the next one is spawned
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert!(log.lock().unwrap().is_empty());
+        drop(first);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*log.lock().unwrap(), vec!["close", "amend"]);
+    }
+
+    #[tokio::test]
+    async fn test_other_positions_are_not_blocked() {
+        let queue = PositionCommandQueue::new();
+        let _held = queue.enter(CommandKey::Position(1)).await;
+
+        let other = tokio::time::timeout(
+            Duration::from_millis(100),
+            queue.enter(CommandKey::Position(2)),
+        )
+        .await;
+        assert!(other.is_ok());
+
+        let new_order = tokio::time::timeout(
+            Duration::from_millis(100),
+            queue.enter(CommandKey::NewOrder { symbol_id: 1 }),
+        )
+        .await;
+        assert!(new_order.is_ok());
+    }
+
+    #[test]
+    fn test_unresolved_until_reconcile() {
+        let queue = PositionCommandQueue::new();
+        queue.mark_unresolved(CommandKey::Position(42), OrderCommand::Close);
+        assert_eq!(
+            queue.unresolved(CommandKey::Position(42)),
+            Some(OrderCommand::Close)
+        );
+        assert_eq!(queue.unresolved(CommandKey::Position(43)), None);
+        assert_eq!(queue.unresolved(CommandKey::PendingOrder(42)), None);
+
+        assert_eq!(queue.resolve_all(), vec![CommandKey::Position(42)]);
+        assert_eq!(queue.unresolved(CommandKey::Position(42)), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Single owner of the cTrader connection after startup
+//!
+//! The bot connects and authenticates once at startup. From then on the
+//! client's reader task is the only place that reconnects: after a dropped
+//! connection, a server that went silent, an auth error, or when asked to
+//! with `request_reconnect`. It re-authenticates and re-subscribes the
+//! symbols, behind the reconnect breaker. Everything else reads the
+//! connection state from here instead of reconnecting on its own, so two
+//! paths never race to replace the stream.
+//!
+//! Every state change, and every further reconnect attempt, is sent as a
+//! `ConnectionChange` (attempt number, time spent in the previous state);
+//! the bot publishes them as `MarketEvent::ConnectionStatus`.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{broadcast, watch, Notify};
+use tracing::{debug, info, warn};
+
+use super::position_reconciliation::ConnectionState;
+
+/// Changes buffered for a slow subscriber
+const CHANGE_CHANNEL_CAPACITY: usize = 64;
+
+/// A connection state change, or another attempt while reconnecting
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionChange {
+    pub from: ConnectionState,
+    pub to: ConnectionState,
+    /// Reconnect attempt (1 = first) while reconnecting
+    pub attempt: Option<u32>,
+    /// How long the connection had been in `from`: the outage so far while
+    /// reconnecting, its full length once connected again
+    pub duration: Duration,
+    /// What caused the change (the error that dropped the connection, ...)
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl fmt::Display for ConnectionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.to, self.attempt) {
+            (ConnectionState::Reconnecting, Some(attempt)) => write!(
+                f,
+                "cTrader reconnect attempt {} (down {}s)",
+                attempt,
+                self.duration.as_secs()
+            )?,
+            (ConnectionState::Connected, _) if self.from != ConnectionState::Disconnected => {
+                write!(
+                    f,
+                    "cTrader connection restored after {}s",
+                    self.duration.as_secs()
+                )?
+            }
+            (to, _) => write!(f, "cTrader connection {}", to)?,
+        }
+        if let Some(reason) = &self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Connection state and reconnect requests, shared by the client, its
+/// reader task and the bot
+#[derive(Debug, Clone)]
+pub struct ConnectionSupervisor {
+    state: Arc<watch::Sender<ConnectionState>>,
+    /// When the current state began
+    since: Arc<Mutex<Instant>>,
+    changes: broadcast::Sender<ConnectionChange>,
+    reconnect: Arc<Notify>,
+}
+
+impl Default for ConnectionSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionSupervisor {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(watch::channel(ConnectionState::Disconnected).0),
+            since: Arc::new(Mutex::new(Instant::now())),
+            changes: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+            reconnect: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        *self.state.borrow()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state() == ConnectionState::Connected
+    }
+
+    /// Follow state changes
+    pub fn watch(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Receive every state change and reconnect attempt
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectionChange> {
+        self.changes.subscribe()
+    }
+
+    /// Move to `state`; returns whether it changed
+    pub fn set(&self, state: ConnectionState) -> bool {
+        self.transition(state, None, None)
+    }
+
+    /// Move to `state` because of `reason`; returns whether it changed
+    pub fn set_because(&self, state: ConnectionState, reason: &str) -> bool {
+        self.transition(state, None, Some(reason))
+    }
+
+    /// A reconnect attempt is about to start (announced even when already
+    /// reconnecting)
+    pub fn reconnect_attempt(&self, attempt: u32, reason: &str) {
+        self.transition(ConnectionState::Reconnecting, Some(attempt), Some(reason));
+    }
+
+    fn transition(
+        &self,
+        state: ConnectionState,
+        attempt: Option<u32>,
+        reason: Option<&str>,
+    ) -> bool {
+        let mut from = state;
+        let changed = self.state.send_if_modified(|current| {
+            from = *current;
+            if *current == state {
+                return false;
+            }
+            *current = state;
+            true
+        });
+        if !changed && attempt.is_none() {
+            return false;
+        }
+
+        let now = Instant::now();
+        let duration = {
+            let mut since = self.since.lock().unwrap_or_else(|e| e.into_inner());
+            let duration = now.duration_since(*since);
+            if changed {
+                *since = now;
+            }
+            duration
+        };
+        if changed {
+            info!("cTrader connection: {} -> {}", from, state);
+        }
+        let _ = self.changes.send(ConnectionChange {
+            from,
+            to: state,
+            attempt,
+            duration,
+            reason: reason.map(str::to_string),
+            at: Utc::now(),
+        });
+        changed
+    }
+
+    /// Ask the reader task to drop and rebuild the connection. Ignored
+    /// unless connected: a reconnect already under way covers it.
+    pub fn request_reconnect(&self, reason: &str) -> bool {
+        if !self.is_connected() {
+            debug!(
+                "Reconnect request ignored ({}): connection is {}",
+                reason,
+                self.state()
+            );
+            return false;
+        }
+        warn!("Reconnect requested: {}", reason);
+        self.reconnect.notify_one();
+        true
+    }
+
+    /// Resolves when a reconnect was requested (for the reader task)
+    pub async fn reconnect_requested(&self) {
+        self.reconnect.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_changes_are_watched() {
+        let supervisor = ConnectionSupervisor::new();
+        let watcher = supervisor.watch();
+        assert_eq!(supervisor.state(), ConnectionState::Disconnected);
+
+        assert!(supervisor.set(ConnectionState::Connected));
+        assert!(!supervisor.set(ConnectionState::Connected));
+        assert!(supervisor.clone().is_connected());
+        assert_eq!(*watcher.borrow(), ConnectionState::Connected);
+        assert!(watcher.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_changes_carry_attempts_and_durations() {
+        let supervisor = ConnectionSupervisor::new();
+        let mut changes = supervisor.subscribe();
+        supervisor.set(ConnectionState::Connected);
+        supervisor.reconnect_attempt(1, "connection reset");
+        supervisor.reconnect_attempt(2, "refused");
+        supervisor.set(ConnectionState::Connected);
+
+        let connected = changes.try_recv().unwrap();
+        assert_eq!(connected.to_string(), "cTrader connection CONNECTED");
+        let first = changes.try_recv().unwrap();
+        assert_eq!(
+            (first.from, first.to, first.attempt),
+            (
+                ConnectionState::Connected,
+                ConnectionState::Reconnecting,
+                Some(1)
+            )
+        );
+        let second = changes.try_recv().unwrap();
+        assert_eq!(second.from, ConnectionState::Reconnecting);
+        assert!(second
+            .to_string()
+            .starts_with("cTrader reconnect attempt 2 (down 0s): refused"));
+        let restored = changes.try_recv().unwrap();
+        assert!(restored.duration >= second.duration);
+        assert!(restored
+            .to_string()
+            .starts_with("cTrader connection restored after"));
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_request_only_when_connected() {
+        let supervisor = ConnectionSupervisor::new();
+        assert!(!supervisor.request_reconnect("price error"));
+
+        supervisor.set(ConnectionState::Connected);
+        assert!(supervisor.request_reconnect("price error"));
+        tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            supervisor.reconnect_requested(),
+        )
+        .await
+        .expect("request wakes the reader");
+    }
+}
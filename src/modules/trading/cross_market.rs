@@ -0,0 +1,391 @@
+//! Cross-market filter on soybean oil and crude oil
+//!
+//! Palm oil rarely moves alone: soybean oil is its closest substitute and
+//! crude sets the value of palm-based biodiesel. With `CROSS_MARKET_SYMBOLS`
+//! set to broker symbol names (e.g. `SOYOIL,BRENT`), the bot subscribes to
+//! them on the same cTrader feed and samples each one's mid price with every
+//! closed candle. Over the last `CROSS_MARKET_WINDOW` samples (default 48) it
+//! computes, per market, the correlation of its returns with palm oil's and
+//! palm oil's relative strength (its change minus the market's).
+//!
+//! A market only counts once it is correlated at `CROSS_MARKET_MIN_CORRELATION`
+//! or more (default 0.5) and moved at least `CROSS_MARKET_MIN_MOVE_PERCENT`
+//! (default 0.5) over the window. Moving with the signal, it adds
+//! `CROSS_MARKET_BOOST` (default 0.2) to the decision score; moving against
+//! it, it vetoes the entry (`CROSS_MARKET_VETO=false` only lowers the score).
+//! Each market shows up as a `cross:<symbol>` factor on the decision, on the
+//! web dashboard and in the trade log.
+
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use super::decision::{DecisionFactor, SignalDecision};
+use super::strategy::Signal;
+use crate::error::{BotError, Result};
+
+const DEFAULT_WINDOW: usize = 48;
+const DEFAULT_MIN_CORRELATION: f64 = 0.5;
+const DEFAULT_MIN_MOVE_PERCENT: f64 = 0.5;
+const DEFAULT_BOOST: f64 = 0.2;
+
+/// Fewest samples before a correlation is reported
+const MIN_SAMPLES: usize = 10;
+
+/// Correlated markets and how much they weigh on signals
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossMarketConfig {
+    /// Broker symbol names to follow
+    pub symbols: Vec<String>,
+    /// Closed candles in the rolling window
+    pub window: usize,
+    /// Correlation a market needs before it counts (0..1)
+    pub min_correlation: f64,
+    /// Move over the window, in percent, before a market counts
+    pub min_move_percent: f64,
+    /// Score added (or taken) per market moving with (or against) the signal
+    pub boost: f64,
+    /// Block entries against a correlated market; false only lowers the score
+    pub veto: bool,
+}
+
+impl CrossMarketConfig {
+    /// Load from `CROSS_MARKET_SYMBOLS` and the `CROSS_MARKET_*` settings;
+    /// None when no symbol is listed
+    pub fn from_env() -> Result<Option<Self>> {
+        let symbols: Vec<String> = env::var("CROSS_MARKET_SYMBOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|symbol| symbol.trim().to_string())
+            .filter(|symbol| !symbol.is_empty())
+            .collect();
+        if symbols.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            symbols,
+            window: parse_var("CROSS_MARKET_WINDOW", DEFAULT_WINDOW, |w| *w >= MIN_SAMPLES)?,
+            min_correlation: parse_var(
+                "CROSS_MARKET_MIN_CORRELATION",
+                DEFAULT_MIN_CORRELATION,
+                |c| (0.0..=1.0).contains(c),
+            )?,
+            min_move_percent: parse_var(
+                "CROSS_MARKET_MIN_MOVE_PERCENT",
+                DEFAULT_MIN_MOVE_PERCENT,
+                |m| *m >= 0.0,
+            )?,
+            boost: parse_var("CROSS_MARKET_BOOST", DEFAULT_BOOST, |b| {
+                (0.0..=1.0).contains(b)
+            })?,
+            veto: env::var("CROSS_MARKET_VETO")
+                .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+                .unwrap_or(true),
+        }))
+    }
+}
+
+fn parse_var<T: FromStr>(key: &str, default: T, valid: impl Fn(&T) -> bool) -> Result<T> {
+    match env::var(key) {
+        Ok(raw) if !raw.trim().is_empty() => raw
+            .trim()
+            .parse::<T>()
+            .ok()
+            .filter(|value| valid(value))
+            .ok_or_else(|| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+        _ => Ok(default),
+    }
+}
+
+/// Where one correlated market stands against palm oil
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrossMarketReading {
+    pub symbol: String,
+    /// Correlation of candle-to-candle returns with palm oil (-1..1); None
+    /// until enough samples
+    pub correlation: Option<f64>,
+    /// The market's change over the window, in percent
+    pub change_percent: f64,
+    /// Palm oil's change minus the market's over the window, in percent
+    pub relative_strength: f64,
+}
+
+/// `SOYOIL corr 0.82 chg +1.20% rs -0.40%` (no commas: it goes in the CSV
+/// trade log)
+impl fmt::Display for CrossMarketReading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.correlation {
+            Some(correlation) => write!(f, "{} corr {:.2}", self.symbol, correlation)?,
+            None => write!(f, "{} corr n/a", self.symbol)?,
+        }
+        write!(
+            f,
+            " chg {:+.2}% rs {:+.2}%",
+            self.change_percent, self.relative_strength
+        )
+    }
+}
+
+/// How a market's recent move bears on a signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bearing {
+    With,
+    Against,
+    /// Not correlated enough, not moving enough, or no signal
+    Neutral,
+}
+
+/// Paired (palm oil, market) samples of one market
+#[derive(Debug, Clone)]
+struct Series {
+    symbol: String,
+    samples: VecDeque<(f64, f64)>,
+}
+
+/// Rolling correlation and relative strength against each correlated market
+#[derive(Debug, Clone)]
+pub struct CrossMarketFilter {
+    config: CrossMarketConfig,
+    markets: Vec<Series>,
+}
+
+impl CrossMarketFilter {
+    pub fn new(config: CrossMarketConfig) -> Self {
+        let markets = config
+            .symbols
+            .iter()
+            .map(|symbol| Series {
+                symbol: symbol.clone(),
+                samples: VecDeque::with_capacity(config.window),
+            })
+            .collect();
+        Self { config, markets }
+    }
+
+    pub fn config(&self) -> &CrossMarketConfig {
+        &self.config
+    }
+
+    /// Sample one market at a closed candle: palm oil's close and the
+    /// market's latest price (any scale: only returns are compared)
+    pub fn record(&mut self, symbol: &str, palm_close: f64, price: f64) {
+        if palm_close <= 0.0 || price <= 0.0 || !price.is_finite() {
+            return;
+        }
+        let window = self.config.window;
+        if let Some(series) = self.markets.iter_mut().find(|s| s.symbol == symbol) {
+            if series.samples.len() == window {
+                series.samples.pop_front();
+            }
+            series.samples.push_back((palm_close, price));
+        }
+    }
+
+    /// Current reading of every market with at least two samples
+    pub fn readings(&self) -> Vec<CrossMarketReading> {
+        self.markets.iter().filter_map(reading).collect()
+    }
+
+    /// Add a `cross:<symbol>` factor per market to the decision and move its
+    /// score by the boost for each market with (or against) the signal
+    pub fn annotate(&self, decision: &mut SignalDecision) {
+        let direction = direction(decision.signal);
+        for reading in self.readings() {
+            let bearing = self.bearing(&reading, decision.signal);
+            let mut factor = DecisionFactor::new(
+                format!("cross:{}", reading.symbol),
+                reading.correlation,
+                bearing != Bearing::Against,
+            )
+            .with_threshold(self.config.min_correlation)
+            .with_note(format!(
+                "chg {:+.2}%, rs {:+.2}%",
+                reading.change_percent, reading.relative_strength
+            ));
+            match bearing {
+                Bearing::With => decision.score += self.config.boost * direction,
+                Bearing::Against => {
+                    decision.score -= self.config.boost * direction;
+                    factor.note = factor.note.map(|note| format!("{}, against", note));
+                }
+                Bearing::Neutral => {}
+            }
+            decision.factors.push(factor);
+        }
+        decision.score = decision.score.clamp(-1.0, 1.0);
+    }
+
+    /// Why a buy or sell should not enter, when a correlated market moved
+    /// against it and vetoes are on
+    pub fn veto(&self, signal: Signal) -> Option<String> {
+        if !self.config.veto {
+            return None;
+        }
+        let against: Vec<String> = self
+            .readings()
+            .into_iter()
+            .filter(|reading| self.bearing(reading, signal) == Bearing::Against)
+            .map(|reading| {
+                format!(
+                    "{} {:+.2}% (corr {:.2})",
+                    reading.symbol,
+                    reading.change_percent,
+                    reading.correlation.unwrap_or_default()
+                )
+            })
+            .collect();
+        (!against.is_empty()).then(|| {
+            format!(
+                "correlated market(s) against the {} signal: {}",
+                signal,
+                against.join(", ")
+            )
+        })
+    }
+
+    fn bearing(&self, reading: &CrossMarketReading, signal: Signal) -> Bearing {
+        let direction = direction(signal);
+        let correlated = reading
+            .correlation
+            .is_some_and(|correlation| correlation >= self.config.min_correlation);
+        if direction == 0.0
+            || !correlated
+            || reading.change_percent.abs() < self.config.min_move_percent
+        {
+            Bearing::Neutral
+        } else if reading.change_percent * direction > 0.0 {
+            Bearing::With
+        } else {
+            Bearing::Against
+        }
+    }
+}
+
+fn direction(signal: Signal) -> f64 {
+    match signal {
+        Signal::Buy => 1.0,
+        Signal::Sell => -1.0,
+        Signal::Hold => 0.0,
+    }
+}
+
+fn reading(series: &Series) -> Option<CrossMarketReading> {
+    let (first_palm, first_market) = *series.samples.front()?;
+    let (last_palm, last_market) = *series.samples.back()?;
+    if series.samples.len() < 2 {
+        return None;
+    }
+    let change_percent = (last_market / first_market - 1.0) * 100.0;
+    let palm_change_percent = (last_palm / first_palm - 1.0) * 100.0;
+    Some(CrossMarketReading {
+        symbol: series.symbol.clone(),
+        correlation: correlation(&series.samples),
+        change_percent,
+        relative_strength: palm_change_percent - change_percent,
+    })
+}
+
+/// Pearson correlation of the two series' returns
+fn correlation(samples: &VecDeque<(f64, f64)>) -> Option<f64> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let returns: Vec<(f64, f64)> = samples
+        .iter()
+        .zip(samples.iter().skip(1))
+        .map(|((palm, market), (next_palm, next_market))| {
+            (next_palm / palm - 1.0, next_market / market - 1.0)
+        })
+        .collect();
+    let n = returns.len() as f64;
+    let mean_palm = returns.iter().map(|(p, _)| p).sum::<f64>() / n;
+    let mean_market = returns.iter().map(|(_, m)| m).sum::<f64>() / n;
+    let (mut covariance, mut var_palm, mut var_market) = (0.0, 0.0, 0.0);
+    for (palm, market) in &returns {
+        covariance += (palm - mean_palm) * (market - mean_market);
+        var_palm += (palm - mean_palm).powi(2);
+        var_market += (market - mean_market).powi(2);
+    }
+    if var_palm <= f64::EPSILON || var_market <= f64::EPSILON {
+        return None;
+    }
+    Some((covariance / (var_palm * var_market).sqrt()).clamp(-1.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(veto: bool) -> CrossMarketConfig {
+        CrossMarketConfig {
+            symbols: vec!["SOYOIL".into(), "BRENT".into()],
+            window: 20,
+            min_correlation: 0.5,
+            min_move_percent: 0.5,
+            boost: 0.2,
+            veto,
+        }
+    }
+
+    /// Palm oil rising in a zig-zag; soybean oil (twice the price) follows it
+    /// exactly, Brent mirrors its swings around a flat level
+    fn filled(veto: bool) -> CrossMarketFilter {
+        let mut filter = CrossMarketFilter::new(config(veto));
+        for i in 0..30 {
+            let swing = if i % 2 == 0 { 1.0 } else { -1.0 };
+            let palm = 4000.0 + i as f64 * 5.0 + swing * 10.0;
+            filter.record("SOYOIL", palm, palm * 2.0);
+            filter.record("BRENT", palm, 80.0 - swing);
+            filter.record("UNKNOWN", palm, 1.0);
+        }
+        filter
+    }
+
+    #[test]
+    fn test_correlation_and_relative_strength() {
+        let readings = filled(true).readings();
+        assert_eq!(readings.len(), 2);
+
+        let soy = &readings[0];
+        assert!((soy.correlation.unwrap() - 1.0).abs() < 1e-9);
+        assert!(soy.change_percent > 1.5);
+        assert!(soy.relative_strength.abs() < 1e-9);
+        assert!(soy.to_string().starts_with("SOYOIL corr 1.00 chg +"));
+
+        let brent = &readings[1];
+        assert!(brent.correlation.unwrap() < -0.9);
+        // Palm oil +1.85% against Brent +2.53% over the window
+        assert!((brent.relative_strength + 0.68).abs() < 0.01);
+
+        let mut sparse = CrossMarketFilter::new(config(true));
+        sparse.record("SOYOIL", 4000.0, 8000.0);
+        sparse.record("SOYOIL", 4010.0, 8020.0);
+        assert_eq!(sparse.readings()[0].correlation, None);
+    }
+
+    #[test]
+    fn test_boosts_with_and_vetoes_against_the_signal() {
+        let filter = filled(true);
+
+        let mut buy = SignalDecision::new(Signal::Buy).with_score(0.5);
+        filter.annotate(&mut buy);
+        assert!((buy.score - 0.7).abs() < 1e-9);
+        assert_eq!(buy.factors.len(), 2);
+        assert_eq!(buy.factors[0].name, "cross:SOYOIL");
+        assert!(buy.factors.iter().all(|factor| factor.passed));
+        assert_eq!(filter.veto(Signal::Buy), None);
+
+        let mut sell = SignalDecision::new(Signal::Sell).with_score(-0.5);
+        filter.annotate(&mut sell);
+        assert!((sell.score + 0.3).abs() < 1e-9);
+        assert!(!sell.factors[0].passed);
+        let veto = filter.veto(Signal::Sell).unwrap();
+        assert!(veto.contains("SOYOIL"), "{}", veto);
+        assert_eq!(filter.veto(Signal::Hold), None);
+
+        assert_eq!(filled(false).veto(Signal::Sell), None);
+    }
+}
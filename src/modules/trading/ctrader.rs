@@ -4,26 +4,43 @@
 //! Documentation: https://help.ctrader.com/open-api/
 
 use crate::config::CTraderConfig;
-use crate::error::{CTraderError, Result};
+use crate::error::{BotError, CTraderError, Result};
 use prost::Message as ProstMessage;
+use serde::Serialize;
 use rustls::ClientConfig;
 use rustls::RootCertStore;
 use rustls::pki_types::ServerName;
 use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::sync::{mpsc, Mutex, RwLock};
-use tokio::time::{interval, sleep, timeout};
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::time::{sleep, timeout};
 use tokio_rustls::TlsConnector;
 use tokio_rustls::client::TlsStream;
 use tracing::{debug, error, info, warn};
 
+use super::candles::{Candle, TimeFrame};
+use super::command_queue::{CommandKey, OrderCommand, PositionCommandQueue};
+use super::connection_supervisor::{ConnectionChange, ConnectionSupervisor};
+use super::frame::{self, CorruptFrameTracker, Inbound, MAX_CONSECUTIVE_CORRUPT};
+use super::heartbeat::{heartbeat_frame, HeartbeatConfig, HeartbeatMonitor};
+use super::orders::{OrderEvent, OrderStatus};
+use super::pacing::MessagePacer;
+use super::position_reconciliation::ConnectionState;
+use super::reconnect_breaker::{ReconnectTrip, SharedReconnectBreaker};
 use super::protobuf::*;
-use super::oauth::{OAuthManager, OAuthConfig, FileTokenStorage, Environment};
+use super::oauth::{
+    Environment, MultiAccountTokenStorage, OAuthConfig, OAuthManager, TokenStorage,
+    LEGACY_TOKEN_FILE,
+};
+
+/// Spot events buffered per `subscribe_spots` receiver
+const SPOT_CHANNEL_CAPACITY: usize = 256;
 
 /// cTrader environment (Demo or Live)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -81,7 +98,19 @@ pub struct Price {
     pub bid: f64,
     pub ask: f64,
     pub spread: f64,
+    /// Server spot time when the feed provides it, local receive time otherwise
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Local receive time minus server spot time, in milliseconds
+    pub clock_skew_ms: Option<i64>,
+    /// When the spot event arrived, for staleness checks
+    pub received_at: Instant,
+}
+
+impl Price {
+    /// Time since the spot event arrived
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
 }
 
 /// Position information
@@ -94,6 +123,153 @@ pub struct Position {
     pub entry_price: f64,
     pub current_price: f64,
     pub profit: f64,
+    /// Margin the broker holds for the position, in account currency
+    pub used_margin: f64,
+}
+
+/// Outcome of `CTraderClient::cancel_all`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CancelAllReport {
+    pub cancelled_orders: Vec<i64>,
+    pub closed_positions: Vec<i64>,
+    /// One line per order/position that could not be cancelled/closed
+    pub failures: Vec<String>,
+}
+
+impl CancelAllReport {
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Trading permissions of the access token on the configured account
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountScope {
+    pub account_id: i64,
+    pub trader_login: Option<i64>,
+    pub broker: Option<String>,
+    pub is_live: bool,
+}
+
+impl AccountScope {
+    /// Check the token's scope and account list, then the account's access
+    /// rights; every way trading could be refused gets its own error
+    pub fn verify(
+        account_id: i64,
+        environment: CTraderEnvironment,
+        accounts: &ProtoOaGetAccountListByAccessTokenRes,
+        access_rights: ProtoOaAccessRights,
+    ) -> Result<Self> {
+        if accounts.permission_scope() != ProtoOaClientPermissionScope::ScopeTrade {
+            return Err(CTraderError::InsufficientPermissions(
+                "access token has view-only scope; get one with scope=trading \
+                (cargo run --bin get-token)"
+                    .into(),
+            )
+            .into());
+        }
+
+        let account = accounts
+            .ctid_trader_account
+            .iter()
+            .find(|a| a.ctid_trader_account_id as i64 == account_id)
+            .ok_or_else(|| {
+                let available: Vec<String> = accounts
+                    .ctid_trader_account
+                    .iter()
+                    .map(|a| a.ctid_trader_account_id.to_string())
+                    .collect();
+                CTraderError::InsufficientPermissions(format!(
+                    "account {} is not linked to this access token (token accounts: [{}])",
+                    account_id,
+                    available.join(", ")
+                ))
+            })?;
+
+        let is_live = account.is_live.unwrap_or(false);
+        if is_live != environment.is_live() {
+            return Err(CTraderError::InsufficientPermissions(format!(
+                "account {} is a {} account but the bot is configured for {}",
+                account_id,
+                if is_live { "LIVE" } else { "DEMO" },
+                environment
+            ))
+            .into());
+        }
+
+        let refusal = match access_rights {
+            ProtoOaAccessRights::FullAccess => None,
+            ProtoOaAccessRights::CloseOnly => Some("CLOSE_ONLY: new orders are refused"),
+            ProtoOaAccessRights::NoTrading => Some("NO_TRADING: view-only access"),
+            ProtoOaAccessRights::NoLogin => Some("NO_LOGIN: no access"),
+        };
+        if let Some(reason) = refusal {
+            return Err(CTraderError::InsufficientPermissions(format!(
+                "account {} has access rights {} (ask the broker for FULL_ACCESS)",
+                account_id, reason
+            ))
+            .into());
+        }
+
+        Ok(Self {
+            account_id,
+            trader_login: account.trader_login,
+            broker: account.broker_title_short.clone(),
+            is_live,
+        })
+    }
+}
+
+/// Order as reported by the broker (pending or historical)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokerOrder {
+    pub order_id: i64,
+    pub symbol_id: i64,
+    pub side: String,
+    pub order_type: ProtoOaOrderType,
+    pub status: ProtoOaOrderStatus,
+    /// Volume in cents, like `Position::volume`
+    pub volume: i64,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub label: Option<String>,
+    /// Position opened (or targeted) by the order
+    pub position_id: Option<i64>,
+    pub updated_at_ms: Option<i64>,
+}
+
+impl BrokerOrder {
+    fn from_proto(order: &ProtoOaOrder) -> Self {
+        let side = match ProtoOaTradeSide::try_from(order.trade_data.trade_side) {
+            Ok(ProtoOaTradeSide::Buy) => "BUY",
+            Ok(ProtoOaTradeSide::Sell) => "SELL",
+            _ => "UNKNOWN",
+        };
+        Self {
+            order_id: order.order_id,
+            symbol_id: order.trade_data.symbol_id,
+            side: side.to_string(),
+            order_type: ProtoOaOrderType::try_from(order.order_type)
+                .unwrap_or(ProtoOaOrderType::Market),
+            status: ProtoOaOrderStatus::try_from(order.order_status)
+                .unwrap_or(ProtoOaOrderStatus::OrderStatusAccepted),
+            volume: order.trade_data.volume,
+            limit_price: order.limit_price,
+            stop_price: order.stop_price,
+            label: order.trade_data.label.clone(),
+            position_id: order.position_id,
+            updated_at_ms: order.utc_last_update_timestamp,
+        }
+    }
+
+    /// Working limit/stop order (protection SL/TP orders excluded)
+    pub fn is_pending(&self) -> bool {
+        self.status == ProtoOaOrderStatus::OrderStatusAccepted
+            && !matches!(
+                self.order_type,
+                ProtoOaOrderType::Market | ProtoOaOrderType::StopLossTakeProfit
+            )
+    }
 }
 
 /// Order ticket for placing orders
@@ -102,11 +278,88 @@ pub struct OrderTicket {
     pub symbol_id: i64,
     pub side: ProtoOaTradeSide,
     pub volume: i64, // in cents: 1 lot = 100 (volume in 0.01 units)
+    /// `Market`, `Limit` or `Stop`
+    pub order_type: ProtoOaOrderType,
+    /// Required for `Limit` orders
+    pub limit_price: Option<f64>,
+    /// Required for `Stop` orders
+    pub stop_price: Option<f64>,
+    /// Pending orders only: good till this time instead of until cancelled
+    pub expiration: Option<chrono::DateTime<chrono::Utc>>,
     pub stop_loss: Option<f64>,
     pub take_profit: Option<f64>,
     pub relative_stop_loss: Option<i64>,
     pub relative_take_profit: Option<i64>,
+    /// Let the broker trail the stop loss behind the price
+    pub trailing_stop_loss: bool,
     pub label: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl OrderTicket {
+    /// Whether the order rests at the broker instead of filling immediately
+    pub fn is_pending(&self) -> bool {
+        matches!(self.order_type, ProtoOaOrderType::Limit | ProtoOaOrderType::Stop)
+    }
+
+    /// Check that the prices and expiration match the order type
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |msg: &str| -> Result<()> {
+            Err(CTraderError::OrderRejected(format!("Invalid {:?} order: {}", self.order_type, msg)).into())
+        };
+        let positive = |price: Option<f64>| price.is_some_and(|p| p.is_finite() && p > 0.0);
+        match self.order_type {
+            ProtoOaOrderType::Market => {
+                if self.limit_price.is_some() || self.stop_price.is_some() || self.expiration.is_some() {
+                    return invalid("market orders take no limit/stop price or expiration");
+                }
+            }
+            ProtoOaOrderType::Limit => {
+                if !positive(self.limit_price) || self.stop_price.is_some() {
+                    return invalid("a positive limit price (and no stop price) is required");
+                }
+            }
+            ProtoOaOrderType::Stop => {
+                if !positive(self.stop_price) || self.limit_price.is_some() {
+                    return invalid("a positive stop price (and no limit price) is required");
+                }
+            }
+            _ => return invalid("only market, limit and stop orders are supported"),
+        }
+        if let Some(expiration) = self.expiration {
+            if expiration <= chrono::Utc::now() {
+                return invalid("expiration is in the past");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of `place_order`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderPlacement {
+    pub order_id: i64,
+    /// 0 until the order fills
+    pub position_id: i64,
+    /// Status the order reached when `place_order` returned
+    pub status: OrderStatus,
+    /// Every status the order went through, in order
+    pub lifecycle: Vec<OrderStatus>,
+    /// Average execution price, once (partly) filled
+    pub fill_price: Option<f64>,
+    /// Filled volume in protocol units (see `OrderTicket::volume`)
+    pub filled_volume: i64,
+}
+
+/// Client order id tying execution events to the request that caused them
+fn next_client_order_id() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "cb-{:x}-{}",
+        chrono::Utc::now().timestamp_millis(),
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    )
 }
 
 /// Symbol metadata used for order validation/normalization
@@ -118,6 +371,8 @@ pub struct SymbolMeta {
     pub min_volume: Option<i64>,
     pub max_volume: Option<i64>,
     pub step_volume: Option<i64>,
+    /// Volume units in one lot
+    pub lot_size: Option<i64>,
     pub sl_distance: Option<u32>,
     pub tp_distance: Option<u32>,
     pub distance_set_in: Option<ProtoOaSymbolDistanceType>,
@@ -140,6 +395,7 @@ impl SymbolMeta {
             min_volume: symbol.min_volume,
             max_volume: symbol.max_volume,
             step_volume: symbol.step_volume,
+            lot_size: symbol.lot_size,
             sl_distance: symbol.sl_distance,
             tp_distance: symbol.tp_distance,
             distance_set_in,
@@ -169,6 +425,42 @@ impl SymbolMeta {
     }
 }
 
+/// Symbol category and asset class, used to group symbols
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolClassification {
+    pub symbol_id: i64,
+    pub category_id: Option<i64>,
+    /// Category name (e.g. "Softs")
+    pub category: Option<String>,
+    pub asset_class_id: Option<i64>,
+    /// Asset class name (e.g. "Commodities")
+    pub asset_class: Option<String>,
+}
+
+impl SymbolClassification {
+    /// Resolve category and asset class names from the broker lists
+    fn from_lists(
+        symbol_id: i64,
+        category_id: Option<i64>,
+        categories: &[ProtoOaSymbolCategory],
+        asset_classes: &[ProtoOaAssetClass],
+    ) -> Self {
+        let category = category_id.and_then(|id| categories.iter().find(|c| c.id == id));
+        let asset_class_id = category.map(|c| c.asset_class_id);
+        let asset_class = asset_class_id
+            .and_then(|id| asset_classes.iter().find(|a| a.id == Some(id)))
+            .and_then(|a| a.name.clone());
+
+        Self {
+            symbol_id,
+            category_id,
+            category: category.map(|c| c.name.clone()),
+            asset_class_id,
+            asset_class,
+        }
+    }
+}
+
 /// cTrader API client
 pub struct CTraderClient {
     config: CTraderConfig,
@@ -185,6 +477,26 @@ pub struct CTraderClient {
     oauth_manager: Option<Arc<OAuthManager>>,
     subscribed_symbols: Arc<RwLock<Vec<i64>>>,
     symbol_meta_cache: Arc<RwLock<HashMap<i64, SymbolMeta>>>,
+    /// Symbol ID -> category ID, filled from the symbols list
+    symbol_category_ids: Arc<RwLock<HashMap<i64, i64>>>,
+    symbol_class_cache: Arc<RwLock<HashMap<i64, SymbolClassification>>>,
+    /// Outgoing message budgets (CTRADER_MAX_MSGS_PER_SEC / CTRADER_MAX_HISTORICAL_PER_SEC)
+    pacer: Arc<MessagePacer>,
+    /// Heartbeat interval and server silence limit (CTRADER_HEARTBEAT_*)
+    heartbeat: HeartbeatConfig,
+    /// Serializes new order / close / amend per position (see `command_queue`)
+    command_queue: Arc<PositionCommandQueue>,
+    /// Set when a command's acknowledgement was lost; the bot reconciles
+    /// (see `take_reconcile_request`)
+    reconcile_requested: Arc<AtomicBool>,
+    /// Pending orders from the last reconcile
+    pending_orders: Arc<RwLock<Vec<BrokerOrder>>>,
+    /// Every spot event as it arrives (see `subscribe_spots`)
+    spot_tx: broadcast::Sender<Price>,
+    /// Counts failed connection attempts from every path (CTRADER_RECONNECT_*)
+    reconnect_breaker: SharedReconnectBreaker,
+    /// Connection state; after startup only the reader task reconnects
+    connection: ConnectionSupervisor,
 }
 
 impl CTraderClient {
@@ -217,8 +529,8 @@ impl CTraderClient {
                 environment: oauth_env,
             };
 
-            let storage = FileTokenStorage::new("oauth_token.json");
-            let manager = OAuthManager::new(oauth_config).with_storage(Box::new(storage));
+            let storage = account_token_storage(oauth_env, config.active_account_id());
+            let manager = OAuthManager::new(oauth_config).with_storage(storage);
             
             Some(Arc::new(manager))
         } else {
@@ -240,6 +552,16 @@ impl CTraderClient {
             oauth_manager,
             subscribed_symbols: Arc::new(RwLock::new(Vec::new())),
             symbol_meta_cache: Arc::new(RwLock::new(HashMap::new())),
+            symbol_category_ids: Arc::new(RwLock::new(HashMap::new())),
+            symbol_class_cache: Arc::new(RwLock::new(HashMap::new())),
+            pacer: Arc::new(MessagePacer::from_env()),
+            heartbeat: HeartbeatConfig::from_env(),
+            command_queue: Arc::new(PositionCommandQueue::new()),
+            reconcile_requested: Arc::new(AtomicBool::new(false)),
+            pending_orders: Arc::new(RwLock::new(Vec::new())),
+            spot_tx: broadcast::channel(SPOT_CHANNEL_CAPACITY).0,
+            reconnect_breaker: SharedReconnectBreaker::from_env(),
+            connection: ConnectionSupervisor::new(),
         }
     }
 
@@ -297,8 +619,23 @@ impl CTraderClient {
         }
     }
 
-    /// Connect to cTrader server
+    /// Connect to cTrader server; refused while the reconnect breaker is open
     pub async fn connect(&self) -> Result<()> {
+        if let Some(wait) = self.reconnect_breaker.wait_before_attempt() {
+            return Err(CTraderError::ConnectionFailed(format!(
+                "reconnect breaker open; next attempt in {}s",
+                wait.as_secs()
+            ))
+            .into());
+        }
+        let result = self.open_stream().await;
+        if let Err(err) = &result {
+            self.reconnect_breaker.record_failure(err);
+        }
+        result
+    }
+
+    async fn open_stream(&self) -> Result<()> {
         let addr = format!("{}:{}", self.config.server, self.config.port);
         info!("Connecting to cTrader at {}", addr);
 
@@ -330,6 +667,18 @@ impl CTraderClient {
 
     /// Authenticate with cTrader API
     pub async fn authenticate(&self) -> Result<()> {
+        let result = self.authenticate_session().await;
+        match &result {
+            Ok(()) => {
+                self.reconnect_breaker.record_success();
+                self.connection.set(ConnectionState::Connected);
+            }
+            Err(err) => self.reconnect_breaker.record_failure(err),
+        }
+        result
+    }
+
+    async fn authenticate_session(&self) -> Result<()> {
         info!("Authenticating with cTrader API ({})", self.environment);
 
         // Step 1: Application authentication
@@ -340,11 +689,11 @@ impl CTraderClient {
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaApplicationAuthReq, app_auth_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
         // Wait for application auth response
         match self
-            .wait_for_message(ProtoOaPayloadType::ProtoOaApplicationAuthRes)
+            .wait_for_message(ProtoOaPayloadType::ProtoOaApplicationAuthRes, request_id.as_deref())
             .await
         {
             Ok(_) => {
@@ -417,10 +766,13 @@ impl CTraderClient {
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaAccountAuthReq, account_auth_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
         // Wait for account auth response - handle ALREADY_LOGGED_IN
-        match self.wait_for_message(ProtoOaPayloadType::ProtoOaAccountAuthRes).await {
+        match self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaAccountAuthRes, request_id.as_deref())
+            .await
+        {
             Ok(_) => {
                 info!("Account authenticated: {}", account_id);
             }
@@ -439,9 +791,6 @@ impl CTraderClient {
 
         *self.authenticated.write().await = true;
 
-        // Start heartbeat and message handling
-        self.start_background_tasks().await;
-
         Ok(())
     }
 
@@ -501,7 +850,61 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         Ok(())
     }
 
-    /// Get current price for a symbol
+    /// Receive every spot event as it arrives, for reacting between polls of
+    /// `get_price`. A slow receiver skips the oldest events (`Lagged`).
+    pub fn subscribe_spots(&self) -> broadcast::Receiver<Price> {
+        self.spot_tx.subscribe()
+    }
+
+    /// Receive every trip of the reconnect breaker, to alert on
+    pub fn subscribe_reconnect_trips(&self) -> broadcast::Receiver<ReconnectTrip> {
+        self.reconnect_breaker.subscribe()
+    }
+
+    /// Remaining cool-down of an open reconnect breaker; connection attempts
+    /// made before it ends are refused
+    pub fn reconnect_wait(&self) -> Option<Duration> {
+        self.reconnect_breaker.wait_before_attempt()
+    }
+
+    /// Current connection state, as kept by the reader task
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection.state()
+    }
+
+    /// Follow connection state changes
+    pub fn watch_connection(&self) -> tokio::sync::watch::Receiver<ConnectionState> {
+        self.connection.watch()
+    }
+
+    /// Receive every connection state change and reconnect attempt
+    pub fn subscribe_connection_changes(&self) -> broadcast::Receiver<ConnectionChange> {
+        self.connection.subscribe()
+    }
+
+    /// Whether a lost acknowledgement asked for a reconcile since the last
+    /// call; the commands it holds back stay refused until one runs
+    pub fn take_reconcile_request(&self) -> bool {
+        self.reconcile_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Whether commands on `position_id` are held until a reconcile because
+    /// an earlier one was never acknowledged
+    pub fn position_command_held(&self, position_id: i64) -> bool {
+        self.command_queue
+            .unresolved(CommandKey::Position(position_id))
+            .is_some()
+    }
+
+    /// Ask the reader task to rebuild the connection (reconnect,
+    /// re-authenticate, re-subscribe). Callers never reconnect themselves;
+    /// returns false when a reconnect is already under way.
+    pub fn request_reconnect(&self, reason: &str) -> bool {
+        self.connection.request_reconnect(reason)
+    }
+
+    /// Get the last price received for a symbol, however old; see
+    /// `Price::age`
     pub async fn get_price(&self, symbol_id: i64) -> Result<Price> {
         let prices = self.prices.read().await;
         prices
@@ -510,61 +913,145 @@ Possible causes: symbol invalid, market closed, or feed issue.",
             .ok_or_else(|| CTraderError::InvalidResponse(format!("No price data for symbol {}", symbol_id)).into())
     }
 
-    /// Place a market order and return (order_id, position_id)
-    pub async fn place_order(&self, ticket: OrderTicket) -> Result<(i64, i64)> {
+    /// Place an order and follow its execution events. Market orders
+    /// return once filled; limit and stop orders once the broker accepted
+    /// them, with a position id of 0 until they fill. A rejection, or a
+    /// cancellation or expiry before anything filled, is an error.
+    pub async fn place_order(&self, ticket: OrderTicket) -> Result<OrderPlacement> {
         if !*self.authenticated.read().await {
             return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
         }
 
+        ticket.validate()?;
+
         let account_id = self.config.active_account_id().parse::<i64>()
             .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
 
+        // One new order per symbol at a time, in call order
+        let _slot = self
+            .command_queue
+            .enter(CommandKey::NewOrder { symbol_id: ticket.symbol_id })
+            .await;
+
+        let client_order_id = next_client_order_id();
         let order_req = ProtoOaNewOrderReq {
             payload_type: None,
             ctid_trader_account_id: account_id,
             symbol_id: ticket.symbol_id,
-            order_type: ProtoOaOrderType::Market as i32,
+            order_type: ticket.order_type as i32,
             trade_side: ticket.side as i32,
             volume: ticket.volume,
-            limit_price: None,
-            stop_price: None,
-            time_in_force: None,
-            expiration_timestamp: None,
-            // For MARKET orders, cTrader requires relative SL/TP (absolute values rejected).
+            limit_price: ticket.limit_price,
+            stop_price: ticket.stop_price,
+            time_in_force: ticket.is_pending().then(|| {
+                if ticket.expiration.is_some() {
+                    ProtoOaTimeInForce::GoodTillDate as i32
+                } else {
+                    ProtoOaTimeInForce::GoodTillCancel as i32
+                }
+            }),
+            expiration_timestamp: ticket.expiration.map(|t| t.timestamp_millis()),
+            // For MARKET orders, cTrader requires relative SL/TP (absolute values rejected);
+            // for pending orders they are relative to the limit/stop price.
             stop_loss: None,
             take_profit: None,
-            comment: None,
+            comment: ticket.comment.clone(),
             base_slippage_price: None,
             slippage_in_points: None,
             label: ticket.label.clone(),
             position_id: None,
-            client_order_id: None,
+            client_order_id: Some(client_order_id.clone()),
             relative_stop_loss: ticket.relative_stop_loss,
             relative_take_profit: ticket.relative_take_profit,
             guaranteed_stop_loss: None,
-            trailing_stop_loss: None,
+            trailing_stop_loss: ticket.trailing_stop_loss.then_some(true),
             stop_trigger_method: None,
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaNewOrderReq, order_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
         info!("Order placed: {:?}", ticket);
 
-        // Wait for execution event
-        let response = self.wait_for_message(ProtoOaPayloadType::ProtoOaExecutionEvent).await?;
-
-        // Parse execution event to get order ID and position ID
-        if let Some(payload) = response.payload {
-            if let Ok(exec_event) = ProtoOaExecutionEvent::decode(payload.as_ref()) {
-                let order_id = exec_event.order.as_ref().map(|o| o.order_id).unwrap_or(0);
-                let position_id = exec_event.position.as_ref().map(|p| p.position_id).unwrap_or(0);
-                info!("Order executed: order_id={} position_id={}", order_id, position_id);
-                return Ok((order_id, position_id));
+        let mut placement = OrderPlacement {
+            order_id: 0,
+            position_id: 0,
+            status: OrderStatus::Pending,
+            lifecycle: Vec::new(),
+            fill_price: None,
+            filled_volume: 0,
+        };
+        loop {
+            let order_id = placement.order_id;
+            let exec = self
+                .wait_for_execution_matching(request_id.as_deref(), |ids| {
+                    ids.client_order_id == Some(client_order_id.as_str())
+                        || (order_id != 0 && ids.order_id == Some(order_id))
+                })
+                .await?;
+            if let Some(order) = &exec.order {
+                placement.order_id = order.order_id;
+                if let Some(volume) = order.executed_volume {
+                    placement.filled_volume = volume;
+                }
+            }
+            if let Some(position) = &exec.position {
+                placement.position_id = position.position_id;
+            }
+            let price = exec
+                .order
+                .as_ref()
+                .and_then(|o| o.execution_price)
+                .or_else(|| exec.deal.as_ref().and_then(|d| d.execution_price));
+
+            let Some(event) = OrderEvent::from_execution_type(exec.execution_type) else {
+                continue;
+            };
+            let Some(status) = placement.status.next(event) else {
+                debug!(
+                    "Ignoring {:?} for order {} in status {}",
+                    event, placement.order_id, placement.status
+                );
+                continue;
+            };
+            placement.status = status;
+            placement.lifecycle.push(status);
+            if matches!(status, OrderStatus::PartiallyFilled | OrderStatus::Filled) {
+                placement.fill_price = price.or(placement.fill_price);
+            }
+            info!(
+                "Order {} {}: position_id={} filled_volume={}",
+                placement.order_id, status, placement.position_id, placement.filled_volume
+            );
+
+            match status {
+                OrderStatus::Filled => return Ok(placement),
+                OrderStatus::Accepted if ticket.is_pending() => return Ok(placement),
+                OrderStatus::Rejected => {
+                    return Err(CTraderError::OrderRejected(format!(
+                        "order {} rejected: {}",
+                        placement.order_id,
+                        exec.error_code.unwrap_or_default()
+                    ))
+                    .into())
+                }
+                OrderStatus::Cancelled | OrderStatus::Expired if placement.filled_volume > 0 => {
+                    warn!(
+                        "Order {} {} after filling {} of {}",
+                        placement.order_id, status, placement.filled_volume, ticket.volume
+                    );
+                    return Ok(placement);
+                }
+                OrderStatus::Cancelled | OrderStatus::Expired => {
+                    return Err(CTraderError::OrderRejected(format!(
+                        "order {} {} before filling",
+                        placement.order_id, status
+                    ))
+                    .into())
+                }
+                _ => {}
             }
         }
-
-        Err(CTraderError::InvalidResponse("Failed to parse execution event".into()).into())
     }
 
     /// Get open positions
@@ -591,11 +1078,14 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaReconcileReq, reconcile_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
-        let response = self.wait_for_message(ProtoOaPayloadType::ProtoOaReconcileRes).await?;
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaReconcileRes, request_id.as_deref())
+            .await?;
 
         let mut positions = Vec::new();
+        let mut orders = Vec::new();
         if let Some(payload) = response.payload {
             if let Ok(reconcile_res) = ProtoOaReconcileRes::decode(payload.as_ref()) {
                 for pos in &reconcile_res.position {
@@ -613,8 +1103,15 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                         entry_price: pos.price.unwrap_or(0.0),
                         current_price: 0.0, // Updated via spot events
                         profit: 0.0,        // Calculated from price difference
+                        used_margin: position_used_margin(pos),
                     });
                 }
+                orders = reconcile_res
+                    .order
+                    .iter()
+                    .map(BrokerOrder::from_proto)
+                    .filter(BrokerOrder::is_pending)
+                    .collect();
             } else {
                 warn!("Failed to decode reconcile response payload");
             }
@@ -626,11 +1123,323 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         for position in &positions {
             cache.insert(position.position_id, position.clone());
         }
+        *self.pending_orders.write().await = orders;
+
+        // Broker state is authoritative again for commands whose outcome was lost
+        let resolved = self.command_queue.resolve_all();
+        if !resolved.is_empty() {
+            info!("Reconcile resolved commands without acknowledgement: {:?}", resolved);
+        }
 
         Ok(positions)
     }
 
-    /// Close a position
+    /// Pending (limit/stop) orders at the broker, refreshed by a reconcile
+    pub async fn pending_orders(&self) -> Result<Vec<BrokerOrder>> {
+        self.reconcile_positions().await?;
+        Ok(self.cached_pending_orders().await)
+    }
+
+    /// Pending orders as of the last reconcile
+    pub async fn cached_pending_orders(&self) -> Vec<BrokerOrder> {
+        self.pending_orders.read().await.clone()
+    }
+
+    /// Order history between two Unix timestamps in milliseconds, including
+    /// orders that were filled, cancelled or expired
+    pub async fn list_orders(&self, from_ms: i64, to_ms: i64) -> Result<Vec<BrokerOrder>> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self.config.active_account_id().parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let mut orders: Vec<BrokerOrder> = Vec::new();
+        let mut from = from_ms;
+        loop {
+            let list_req = ProtoOaOrderListReq {
+                payload_type: None,
+                ctid_trader_account_id: account_id,
+                from_timestamp: Some(from),
+                to_timestamp: Some(to_ms),
+            };
+            let msg = new_proto_message(ProtoOaPayloadType::ProtoOaOrderListReq, list_req);
+            let request_id = self.send_request(msg).await?;
+
+            let response = self
+                .wait_for_message(ProtoOaPayloadType::ProtoOaOrderListRes, request_id.as_deref())
+                .await?;
+            let list_res = response
+                .payload
+                .as_deref()
+                .and_then(|p| ProtoOaOrderListRes::decode(p).ok())
+                .ok_or_else(|| CTraderError::InvalidResponse("Failed to decode order list".into()))?;
+
+            let page: Vec<BrokerOrder> = list_res.order.iter().map(BrokerOrder::from_proto).collect();
+            // Page forward from the newest update seen; stop if the server does not advance
+            let next_from = page.iter().filter_map(|o| o.updated_at_ms).max().map(|t| t + 1);
+            orders.extend(page);
+            match next_from {
+                Some(next) if list_res.has_more && next > from => from = next,
+                _ => break,
+            }
+        }
+
+        // Pages can overlap on equal timestamps; keep the latest state per order
+        let mut by_id: HashMap<i64, BrokerOrder> = HashMap::new();
+        for order in orders {
+            by_id.insert(order.order_id, order);
+        }
+        Ok(by_id.into_values().collect())
+    }
+
+    /// The last `count` closed bid trendbars for a symbol, oldest first
+    pub async fn get_trendbars(
+        &self,
+        symbol_id: i64,
+        timeframe: TimeFrame,
+        count: u32,
+    ) -> Result<Vec<Candle>> {
+        let now = chrono::Utc::now();
+        // Look back well past `count` bars so weekends and session breaks still fill it
+        let lookback_secs = timeframe.duration_secs() * (count as i64 + 1) * 4;
+        let from_ms = (now.timestamp() - lookback_secs).max(0) * 1000;
+        // One extra for the bar still forming, which is dropped below
+        let (bars, _) = self
+            .request_trendbars(symbol_id, timeframe, from_ms, now.timestamp_millis(), Some(count + 1))
+            .await?;
+
+        let mut candles: Vec<Candle> = bars
+            .into_iter()
+            .filter(|candle| candle.end_time() <= now)
+            .collect();
+        if candles.len() > count as usize {
+            candles.drain(..candles.len() - count as usize);
+        }
+        debug!("Fetched {} {} trendbars for symbol {}", candles.len(), timeframe, symbol_id);
+        Ok(candles)
+    }
+
+    /// Closed bid trendbars starting between two Unix timestamps in
+    /// milliseconds, oldest first. Large ranges are fetched in several pages.
+    pub async fn get_trendbars_between(
+        &self,
+        symbol_id: i64,
+        timeframe: TimeFrame,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> Result<Vec<Candle>> {
+        let now = chrono::Utc::now();
+        let mut candles: Vec<Candle> = Vec::new();
+        let mut to = to_ms;
+        loop {
+            let (page, has_more) = self
+                .request_trendbars(symbol_id, timeframe, from_ms, to, None)
+                .await?;
+            // Pages are cut from the newest end; continue before the oldest bar
+            let next_to = page.first().map(|c| c.timestamp.timestamp_millis() - 1);
+            candles.extend(page);
+            match next_to {
+                Some(next) if has_more && next < to && next >= from_ms => to = next,
+                _ => break,
+            }
+        }
+
+        candles.retain(|c| c.end_time() <= now);
+        candles.sort_by_key(|c| c.timestamp);
+        candles.dedup_by_key(|c| c.timestamp);
+        Ok(candles)
+    }
+
+    /// One `ProtoOAGetTrendbarsReq`; returns the bars oldest first and whether
+    /// the server has more in the range
+    async fn request_trendbars(
+        &self,
+        symbol_id: i64,
+        timeframe: TimeFrame,
+        from_ms: i64,
+        to_ms: i64,
+        count: Option<u32>,
+    ) -> Result<(Vec<Candle>, bool)> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self.config.active_account_id().parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let trendbars_req = ProtoOaGetTrendbarsReq {
+            payload_type: None,
+            ctid_trader_account_id: account_id,
+            from_timestamp: Some(from_ms),
+            to_timestamp: Some(to_ms),
+            period: trendbar_period(timeframe) as i32,
+            symbol_id,
+            count,
+        };
+        let msg = new_proto_message(ProtoOaPayloadType::ProtoOaGetTrendbarsReq, trendbars_req);
+        let request_id = self.send_request(msg).await?;
+
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaGetTrendbarsRes, request_id.as_deref())
+            .await?;
+        let trendbars_res = response
+            .payload
+            .as_deref()
+            .and_then(|p| ProtoOaGetTrendbarsRes::decode(p).ok())
+            .ok_or_else(|| CTraderError::InvalidResponse("Failed to decode trendbars".into()))?;
+
+        let mut candles: Vec<Candle> = trendbars_res
+            .trendbar
+            .iter()
+            .filter_map(|bar| candle_from_trendbar(bar, timeframe))
+            .collect();
+        candles.sort_by_key(|c| c.timestamp);
+        Ok((candles, trendbars_res.has_more.unwrap_or(false)))
+    }
+
+    /// Historical ticks for one side of the quote between two Unix timestamps
+    /// in milliseconds, as `(timestamp_ms, price)` oldest first. The server
+    /// pages newest-first, so pages are requested backwards from `to_ms`.
+    pub async fn get_tick_data(
+        &self,
+        symbol_id: i64,
+        quote_type: ProtoOaQuoteType,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> Result<Vec<(i64, f64)>> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self.config.active_account_id().parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let mut ticks: Vec<(i64, f64)> = Vec::new();
+        let mut to = to_ms;
+        loop {
+            let tick_req = ProtoOaGetTickDataReq {
+                payload_type: None,
+                ctid_trader_account_id: account_id,
+                symbol_id,
+                r#type: quote_type as i32,
+                from_timestamp: Some(from_ms),
+                to_timestamp: Some(to),
+            };
+            let msg = new_proto_message(ProtoOaPayloadType::ProtoOaGetTickdataReq, tick_req);
+            let request_id = self.send_request(msg).await?;
+
+            let response = self
+                .wait_for_message(ProtoOaPayloadType::ProtoOaGetTickdataRes, request_id.as_deref())
+                .await?;
+            let tick_res = response
+                .payload
+                .as_deref()
+                .and_then(|p| ProtoOaGetTickDataRes::decode(p).ok())
+                .ok_or_else(|| CTraderError::InvalidResponse("Failed to decode tick data".into()))?;
+
+            let page = decode_tick_data(&tick_res.tick_data);
+            // Continue before the oldest tick seen; stop if the server does not move back
+            let next_to = page.first().map(|(t, _)| *t - 1);
+            ticks.extend(page);
+            match next_to {
+                Some(next) if tick_res.has_more && next < to && next >= from_ms => to = next,
+                _ => break,
+            }
+        }
+
+        ticks.sort_by_key(|(t, _)| *t);
+        ticks.dedup();
+        Ok(ticks)
+    }
+
+    /// Cancel a pending order; returns once the broker acknowledged it
+    pub async fn cancel_order(&self, order_id: i64) -> Result<()> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self.config.active_account_id().parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let key = CommandKey::PendingOrder(order_id);
+        let _slot = self.enter_command(key, OrderCommand::Cancel).await?;
+
+        let cancel_req = ProtoOaCancelOrderReq {
+            payload_type: None,
+            ctid_trader_account_id: account_id,
+            order_id,
+        };
+
+        let msg = new_proto_message(ProtoOaPayloadType::ProtoOaCancelOrderReq, cancel_req);
+        let request_id = self.send_request(msg).await?;
+        self.await_ack(key, OrderCommand::Cancel, request_id.as_deref()).await?;
+
+        info!("Order cancelled: {}", order_id);
+        Ok(())
+    }
+
+    /// Emergency stop: cancel every pending order at the broker and, with
+    /// `flatten`, close every open position at market.
+    ///
+    /// Failures on individual orders/positions are collected instead of
+    /// aborting, so one rejected cancel does not leave the rest working.
+    pub async fn cancel_all(&self, flatten: bool) -> Result<CancelAllReport> {
+        let positions = self.reconcile_positions().await?;
+        let orders = self.cached_pending_orders().await;
+        warn!(
+            "Cancel-all requested: {} pending orders, {} open positions (flatten={})",
+            orders.len(),
+            positions.len(),
+            flatten
+        );
+
+        let mut report = CancelAllReport::default();
+        for order in &orders {
+            match self.cancel_order(order.order_id).await {
+                Ok(()) => report.cancelled_orders.push(order.order_id),
+                Err(err) => {
+                    error!("Cancel-all: cancelling order {} failed: {}", order.order_id, err);
+                    report
+                        .failures
+                        .push(format!("order {}: {}", order.order_id, err));
+                }
+            }
+        }
+
+        if flatten {
+            for position in &positions {
+                match self.close_position(position.position_id, position.volume).await {
+                    Ok(()) => report.closed_positions.push(position.position_id),
+                    Err(err) => {
+                        error!(
+                            "Cancel-all: closing position {} failed: {}",
+                            position.position_id, err
+                        );
+                        report
+                            .failures
+                            .push(format!("position {}: {}", position.position_id, err));
+                    }
+                }
+            }
+        }
+
+        // Refresh caches so callers see what is left
+        if let Err(err) = self.reconcile_positions().await {
+            warn!("Cancel-all: post reconcile failed: {}", err);
+        }
+
+        info!(
+            "Cancel-all done: {} orders cancelled, {} positions closed, {} failures",
+            report.cancelled_orders.len(),
+            report.closed_positions.len(),
+            report.failures.len()
+        );
+        Ok(report)
+    }
+
+    /// Close a position; returns once the broker acknowledged the close
     pub async fn close_position(&self, position_id: i64, volume: i64) -> Result<()> {
         if !*self.authenticated.read().await {
             return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
@@ -639,6 +1448,9 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         let account_id = self.config.active_account_id().parse::<i64>()
             .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
 
+        let key = CommandKey::Position(position_id);
+        let _slot = self.enter_command(key, OrderCommand::Close).await?;
+
         let close_req = ProtoOaClosePositionReq {
             payload_type: None,
             ctid_trader_account_id: account_id,
@@ -647,63 +1459,331 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaClosePositionReq, close_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
+        self.await_ack(key, OrderCommand::Close, request_id.as_deref()).await?;
 
         info!("Position closed: {}", position_id);
         Ok(())
     }
 
-    /// Start continuous reader task to process incoming messages
-    pub async fn start_reader(&self) -> Result<()> {
-        if self.reader_task.read().await.is_some() {
-            return Ok(());
+    /// Move the absolute stop loss / take profit of an open position
+    /// (levels left as `None` are removed). With `trailing_stop_loss` the broker
+    /// trails the stop behind the price at its current distance.
+    pub async fn amend_position_sltp(
+        &self,
+        position_id: i64,
+        stop_loss: Option<f64>,
+        take_profit: Option<f64>,
+        trailing_stop_loss: bool,
+    ) -> Result<()> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
         }
 
-        let stream_arc = self.stream.clone();
-        let prices_arc = self.prices.clone();
-        let positions_arc = self.positions.clone();
-        let message_tx = self.message_tx.clone();
-        let pending = self.pending_messages.clone();
-        let authenticated_clone = self.authenticated.clone();
-        let mut config_clone = self.config.clone();
-        let environment = self.environment;
-        let subscribed_symbols_clone = self.subscribed_symbols.clone();
+        let account_id = self.config.active_account_id().parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
 
-        let task = tokio::spawn(async move {
-            info!("cTrader reader task started");
-            let mut reconnect_attempt = 0u32;
-            let mut auth_failure_count = 0u32;
+        let key = CommandKey::Position(position_id);
+        let _slot = self.enter_command(key, OrderCommand::Amend).await?;
 
-            loop {
-                let mut stream_guard = stream_arc.lock().await;
-                let stream = match stream_guard.as_mut() {
-                    Some(s) => s,
-                    None => {
-                        warn!("Reader: No active stream, waiting...");
-                        drop(stream_guard);
+        let amend_req = ProtoOaAmendPositionSltpReq {
+            payload_type: None,
+            ctid_trader_account_id: account_id,
+            position_id,
+            stop_loss,
+            take_profit,
+            guaranteed_stop_loss: None,
+            trailing_stop_loss: trailing_stop_loss.then_some(true),
+            stop_loss_trigger_method: None,
+        };
+
+        let msg = new_proto_message(ProtoOaPayloadType::ProtoOaAmendPositionSltpReq, amend_req);
+        let request_id = self.send_request(msg).await?;
+        self.await_ack(key, OrderCommand::Amend, request_id.as_deref()).await?;
+
+        info!(
+            "Position {} amended: SL={:?} TP={:?} trailing={}",
+            position_id, stop_loss, take_profit, trailing_stop_loss
+        );
+        Ok(())
+    }
+
+    /// Take the command slot for `key`, refusing while an earlier command's
+    /// outcome is unknown
+    async fn enter_command(
+        &self,
+        key: CommandKey,
+        command: OrderCommand,
+    ) -> Result<super::command_queue::CommandSlot> {
+        let slot = self.command_queue.enter(key).await;
+        if let Some(previous) = self.command_queue.unresolved(key) {
+            return Err(CTraderError::OrderRejected(format!(
+                "{} on {:?} refused: outcome of previous {} unknown until reconcile",
+                command, key, previous
+            ))
+            .into());
+        }
+        Ok(slot)
+    }
+
+    /// Wait for the broker's answer to request `request_id`, a command on
+    /// `key`. A lost answer leaves the key unresolved; after a timeout the
+    /// bot is asked to reconcile (`take_reconcile_request`) so the next
+    /// command is not held up until the periodic reconcile.
+    async fn await_ack(
+        &self,
+        key: CommandKey,
+        command: OrderCommand,
+        request_id: Option<&str>,
+    ) -> Result<()> {
+        match self.wait_for_execution(key, request_id).await {
+            Ok(_) => Ok(()),
+            Err(BotError::CTrader(err @ (CTraderError::Timeout | CTraderError::Disconnected))) => {
+                warn!(
+                    "No acknowledgement for {} on {:?} ({}); holding further commands until reconcile",
+                    command, key, err
+                );
+                self.command_queue.mark_unresolved(key, command);
+                // A dropped connection reconciles when it comes back
+                if matches!(err, CTraderError::Timeout) {
+                    self.reconcile_requested.store(true, Ordering::Relaxed);
+                }
+                Err(err.into())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Wait for an execution event (or order error) about the position or
+    /// pending order behind `key`, leaving other events queued
+    async fn wait_for_execution(
+        &self,
+        key: CommandKey,
+        request_id: Option<&str>,
+    ) -> Result<ProtoOaExecutionEvent> {
+        self.wait_for_execution_matching(request_id, |ids| match key {
+            CommandKey::Position(id) => ids.position_id == Some(id),
+            CommandKey::PendingOrder(id) => ids.order_id == Some(id),
+            CommandKey::NewOrder { .. } => false,
+        })
+        .await
+    }
+
+    /// Wait for the first execution event (or order error) `matches` accepts,
+    /// leaving other events queued. Order errors that name no position or
+    /// order are taken as the answer as well; an error response only when it
+    /// echoes `request_id`, the client message id of the request.
+    async fn wait_for_execution_matching(
+        &self,
+        request_id: Option<&str>,
+        matches: impl Fn(ExecutionIds<'_>) -> bool,
+    ) -> Result<ProtoOaExecutionEvent> {
+        let exec_type = ProtoOaPayloadType::ProtoOaExecutionEvent as i32 as u32;
+        let error_type = ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32;
+        let order_error_type = ProtoOaPayloadType::ProtoOaOrderErrorEvent as i32 as u32;
+        let for_key = |message: &ProtoMessage| -> Option<ProtoOaExecutionEvent> {
+            let exec = ProtoOaExecutionEvent::decode(message.payload.as_deref()?).ok()?;
+            matches(ExecutionIds::of(&exec)).then_some(exec)
+        };
+
+        // Take the reader first: whatever another waiter set aside for this
+        // request is queued by the time it lets go
+        let mut rx = self.message_rx.lock().await;
+        {
+            let mut pending = self.pending_messages.lock().await;
+            if let Some(queue) = pending.get_mut(&exec_type) {
+                if let Some(index) = queue.iter().position(|m| for_key(m).is_some()) {
+                    if let Some(exec) = queue.remove(index).as_ref().and_then(for_key) {
+                        return Ok(exec);
+                    }
+                }
+            }
+            if let Some(message) = take_error_response(&mut pending, request_id) {
+                return Err(error_response(&message).into());
+            }
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(30);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let message = match timeout(remaining, rx.recv()).await {
+                Ok(Some(message)) => message,
+                Ok(None) => return Err(CTraderError::Disconnected.into()),
+                Err(_) => return Err(CTraderError::Timeout.into()),
+            };
+
+            if message.payload_type == exec_type {
+                if let Some(exec) = for_key(&message) {
+                    return Ok(exec);
+                }
+            } else if message.payload_type == error_type {
+                if answers(&message, request_id) {
+                    return Err(error_response(&message).into());
+                }
+            } else if message.payload_type == order_error_type {
+                let event = message
+                    .payload
+                    .as_deref()
+                    .and_then(|p| ProtoOaOrderErrorEvent::decode(p).ok());
+                if let Some(event) = event {
+                    let unattributed = event.position_id.is_none() && event.order_id.is_none();
+                    let ids = ExecutionIds {
+                        position_id: event.position_id,
+                        order_id: event.order_id,
+                        client_order_id: None,
+                    };
+                    if unattributed || matches(ids) {
+                        return Err(CTraderError::OrderRejected(format!(
+                            "code={} desc={}",
+                            event.error_code,
+                            event.description.unwrap_or_default()
+                        ))
+                        .into());
+                    }
+                }
+            }
+
+            set_aside(&mut *self.pending_messages.lock().await, message);
+        }
+    }
+
+    /// Start continuous reader task to process incoming messages
+    pub async fn start_reader(&self) -> Result<()> {
+        if self.reader_task.read().await.is_some() {
+            return Ok(());
+        }
+
+        let stream_arc = self.stream.clone();
+        let prices_arc = self.prices.clone();
+        let spot_tx = self.spot_tx.clone();
+        let positions_arc = self.positions.clone();
+        let message_tx = self.message_tx.clone();
+        let pending = self.pending_messages.clone();
+        let authenticated_clone = self.authenticated.clone();
+        let mut config_clone = self.config.clone();
+        let environment = self.environment;
+        let subscribed_symbols_clone = self.subscribed_symbols.clone();
+        let heartbeat_config = self.heartbeat;
+        let breaker = self.reconnect_breaker.clone();
+        let connection = self.connection.clone();
+
+        let task = tokio::spawn(async move {
+            info!("cTrader reader task started");
+            let mut heartbeats = HeartbeatMonitor::new(heartbeat_config, Instant::now());
+            let mut reconnect_attempt = 0u32;
+            let mut auth_failure_count = 0u32;
+            let mut corrupt_frames = CorruptFrameTracker::default();
+            // A length prefix can arrive split around a heartbeat timeout
+            let mut len_buf = [0u8; 4];
+            let mut len_read = 0usize;
+
+            loop {
+                let mut stream_guard = stream_arc.lock().await;
+                let stream = match stream_guard.as_mut() {
+                    Some(s) => s,
+                    None => {
+                        warn!("Reader: No active stream, waiting...");
+                        drop(stream_guard);
                         tokio::time::sleep(Duration::from_secs(5)).await;
                         continue;
                     }
                 };
 
-                // Read length prefix (4 bytes, big-endian)
-                let mut len_buf = [0u8; 4];
-                match timeout(Duration::from_secs(30), stream.read_exact(&mut len_buf)).await {
-                    Ok(Ok(_)) => {
+                // Heartbeats go out between reads on this connection, and a
+                // server that stopped talking counts as a lost connection.
+                // The prefix is read with `read`, which keeps the bytes
+                // already received when the timeout cancels it, unlike
+                // `read_exact`, so a split prefix does not shift the stream.
+                let now = Instant::now();
+                let read = if let Some(silence) = heartbeats.server_silence(now) {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        format!("nothing received from the server for {}s", silence.as_secs()),
+                    ))
+                } else if let Err(e) =
+                    Self::send_heartbeat_if_due(stream, &mut heartbeats, now).await
+                {
+                    Err(e)
+                } else {
+                    let wait = heartbeats.read_timeout(now);
+                    // `read` is cancel safe, so a reconnect request can cut it short
+                    let result = tokio::select! {
+                        result = timeout(wait, stream.read(&mut len_buf[len_read..])) => result,
+                        _ = connection.reconnect_requested() => Ok(Err(std::io::Error::new(
+                            std::io::ErrorKind::Interrupted,
+                            "reconnect requested",
+                        ))),
+                    };
+                    match result {
+                        Ok(Ok(0)) => Err(std::io::ErrorKind::UnexpectedEof.into()),
+                        Ok(Ok(n)) => {
+                            heartbeats.received(Instant::now());
+                            len_read += n;
+                            if len_read < len_buf.len() {
+                                continue;
+                            }
+                            len_read = 0;
+                            match frame::frame_len(len_buf) {
+                                Ok(len) => Self::read_frame_body(stream, len).await,
+                                Err(e) => {
+                                    corrupt_frames.corrupt(&e);
+                                    Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+                                }
+                            }
+                        }
+                        Ok(Err(e)) => Err(e),
+                        // Time for a heartbeat or a silence check
+                        Err(_) => continue,
+                    }
+                };
+
+                let decoded = read.and_then(|buf| match frame::decode_frame(&buf) {
+                    Ok(message) => {
+                        corrupt_frames.frame_ok();
+                        Ok(Some(message))
+                    }
+                    Err(e) if corrupt_frames.corrupt(&e) => Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "{} undecodable frames in a row, last: {}",
+                            MAX_CONSECUTIVE_CORRUPT, e
+                        ),
+                    )),
+                    Err(e) => {
+                        warn!("Reader: Skipping undecodable frame: {}", e);
+                        Ok(None)
+                    }
+                });
+
+                let message = match decoded {
+                    Ok(Some(message)) => {
                         reconnect_attempt = 0; // Reset on successful read
+                        message
                     }
-                    Ok(Err(e)) => {
-                        error!("Connection lost: {}", e);
+                    Ok(None) => continue,
+                    // Corrupt or truncated frames leave the stream out of
+                    // sync, so they reconnect like a dropped connection
+                    Err(e) => {
+                        if e.kind() == std::io::ErrorKind::InvalidData {
+                            error!("Reader: Corrupt stream ({}), reconnecting to resynchronize", e);
+                        } else {
+                            error!("Connection lost: {}", e);
+                        }
                         drop(stream_guard);
+                        len_read = 0;
+                        corrupt_frames = CorruptFrameTracker::default();
+                        heartbeats.reset(Instant::now());
                         
                         *authenticated_clone.write().await = false;
                         
                         reconnect_attempt += 1;
-                        let backoff_secs = std::cmp::min(2u64.pow(reconnect_attempt - 1), 60);
+                        connection.reconnect_attempt(reconnect_attempt, &e.to_string());
+                        let backoff_secs = 2u64.saturating_pow(reconnect_attempt - 1).min(60);
                         warn!("Reconnection attempt {} in {}s...", reconnect_attempt, backoff_secs);
                         tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
                         
-                        match Self::reconnect_internal(
+                        match Self::reconnect_guarded(
+                            &breaker,
                             &mut config_clone,
                             environment,
                             &stream_arc,
@@ -712,6 +1792,7 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                         ).await {
                             Ok(_) => {
                                 info!("✅ Reconnected successfully");
+                                connection.set(ConnectionState::Connected);
                                 reconnect_attempt = 0;
                                 // Reset auth failure counter on successful reconnection
                                 auth_failure_count = 0;
@@ -719,166 +1800,145 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                             }
                             Err(reconnect_err) => {
                                 error!("Reconnection failed: {}", reconnect_err);
-                                if reconnect_attempt >= 10 {
-                                    error!("Max reconnection attempts reached, giving up");
-                                    break;
+                                // Keep trying: without the reader the bot
+                                // would run on stale prices
+                                if reconnect_attempt % 10 == 0 {
+                                    error!(
+                                        "Still disconnected after {} attempts; retrying every 60s",
+                                        reconnect_attempt
+                                    );
                                 }
                                 continue;
                             }
                         }
                     }
-                    Err(_) => {
-                        debug!("Reader: Heartbeat timeout - connection still alive");
-                        continue;
-                    }
-                }
-
-                let msg_len = u32::from_be_bytes(len_buf) as usize;
-                if msg_len > 1_000_000 {
-                    error!("Reader: Message too large: {}", msg_len);
-                    break;
-                }
-
-                let mut msg_buf = vec![0u8; msg_len];
-                if let Err(e) = stream.read_exact(&mut msg_buf).await {
-                    error!("Reader: Failed to read message: {}", e);
-                    break;
-                }
+                };
 
                 drop(stream_guard);
 
-                let message = match ProtoMessage::decode(msg_buf.as_ref()) {
-                    Ok(msg) => msg,
-                    Err(e) => {
-                        error!("Reader: Failed to decode message: {}", e);
-                        continue;
+                match frame::decode_inbound(message) {
+                    Inbound::Spot(Some(spot_event)) => {
+                        Self::handle_spot_event(spot_event, &prices_arc, &spot_tx).await;
                     }
-                };
-
-                let payload_type = message.payload_type;
-                if let Some(msg_type) = payload_type_from_u32(payload_type) {
-                    match msg_type {
-                        ProtoOaPayloadType::ProtoOaSpotEvent => {
-                            if let Some(payload) = &message.payload {
-                                if let Ok(spot_event) = ProtoOaSpotEvent::decode(payload.as_ref()) {
-                                    Self::handle_spot_event(spot_event, &prices_arc).await;
-                                }
-                            }
+                    Inbound::Spot(None) => {
+                        debug!("Reader: Undecodable spot event dropped");
+                    }
+                    Inbound::Execution(message, exec) => {
+                        debug!("Reader: Execution event received");
+                        if let Some(pos) = exec.and_then(|exec| exec.position) {
+                            let side = match ProtoOaTradeSide::try_from(pos.trade_data.trade_side) {
+                                Ok(ProtoOaTradeSide::Buy) => "BUY",
+                                Ok(ProtoOaTradeSide::Sell) => "SELL",
+                                _ => "UNKNOWN",
+                            };
+                            let mut positions = positions_arc.write().await;
+                            positions.insert(pos.position_id, Position {
+                                position_id: pos.position_id,
+                                symbol_id: pos.trade_data.symbol_id,
+                                volume: pos.trade_data.volume,
+                                side: side.to_string(),
+                                entry_price: pos.price.unwrap_or(0.0),
+                                current_price: 0.0,
+                                profit: 0.0,
+                                used_margin: position_used_margin(&pos),
+                            });
                         }
-                        ProtoOaPayloadType::ProtoOaExecutionEvent => {
-                            debug!("Reader: Execution event received");
-                            if let Some(payload) = &message.payload {
-                                if let Ok(exec) = ProtoOaExecutionEvent::decode(payload.as_ref()) {
-                                    let side = if let Some(ref pos) = exec.position {
-                                        match ProtoOaTradeSide::try_from(pos.trade_data.trade_side) {
-                                            Ok(ProtoOaTradeSide::Buy) => "BUY",
-                                            Ok(ProtoOaTradeSide::Sell) => "SELL",
-                                            _ => "UNKNOWN",
-                                        }
-                                    } else {
-                                        "UNKNOWN"
-                                    };
-                                    if let Some(ref pos) = exec.position {
-                                        let mut positions = positions_arc.write().await;
-                                        positions.insert(pos.position_id, Position {
-                                            position_id: pos.position_id,
-                                            symbol_id: pos.trade_data.symbol_id,
-                                            volume: pos.trade_data.volume,
-                                            side: side.to_string(),
-                                            entry_price: pos.price.unwrap_or(0.0),
-                                            current_price: 0.0,
-                                            profit: 0.0,
-                                        });
+                        let _ = message_tx.send(message);
+                    }
+                    Inbound::Error(message, err_res) => {
+                        if let Some(err_res) = err_res {
+                            let error_code = &err_res.error_code;
+                            let description = err_res.description.as_deref().unwrap_or("none");
+                            
+                            // Check for authentication failures
+                            if error_code.contains("CH_CLIENT_NOT_AUTHENTICATED") {
+                                auth_failure_count += 1;
+                                error!(
+                                    "⚠️ NOT AUTHENTICATED (attempt {}/3): code={} desc={}",
+                                    auth_failure_count, error_code, description
+                                );
+
+                                *authenticated_clone.write().await = false;
+                                connection.reconnect_attempt(
+                                    auth_failure_count,
+                                    &format!("not authenticated ({})", error_code),
+                                );
+
+                                match Self::reconnect_guarded(
+                                    &breaker,
+                                    &mut config_clone,
+                                    environment,
+                                    &stream_arc,
+                                    &authenticated_clone,
+                                    &subscribed_symbols_clone,
+                                ).await {
+                                    Ok(_) => {
+                                        info!("✅ Reconnected successfully after auth error");
+                                        connection.set(ConnectionState::Connected);
+                                        heartbeats.reset(Instant::now());
+                                        auth_failure_count = 0;
+                                        continue;
                                     }
-                                }
-                            }
-                            let _ = message_tx.send(message);
-                        }
-                        ProtoOaPayloadType::ProtoOaErrorRes => {
-                            if let Some(payload) = &message.payload {
-                                if let Ok(err_res) = ProtoOaErrorRes::decode(payload.as_ref()) {
-                                    let error_code = &err_res.error_code;
-                                    let description = err_res.description.as_deref().unwrap_or("none");
-                                    
-                                    // Check for authentication failures
-                                    if error_code.contains("CH_CLIENT_NOT_AUTHENTICATED") {
-                                        auth_failure_count += 1;
-                                        error!(
-                                            "⚠️ NOT AUTHENTICATED (attempt {}/3): code={} desc={}",
-                                            auth_failure_count, error_code, description
-                                        );
-
-                                        *authenticated_clone.write().await = false;
-
-                                        match Self::reconnect_internal(
-                                            &mut config_clone,
-                                            environment,
-                                            &stream_arc,
-                                            &authenticated_clone,
-                                            &subscribed_symbols_clone,
-                                        ).await {
-                                            Ok(_) => {
-                                                info!("✅ Reconnected successfully after auth error");
-                                                auth_failure_count = 0;
-                                                continue;
-                                            }
-                                            Err(reconnect_err) => {
-                                                error!("Reconnection after auth error failed: {}", reconnect_err);
-                                                if auth_failure_count >= 3 {
-                                                    error!("❌ CRITICAL: 3 consecutive auth errors detected!");
-                                                    break;
-                                                }
-                                                continue;
-                                            }
-                                        }
-                                    } else if error_code.contains("AUTH_FAILURE") || error_code.contains("CH_CLIENT_AUTH_FAILURE") {
-                                        auth_failure_count += 1;
-                                        error!(
-                                            "❌ AUTHENTICATION FAILED (attempt {}/3): code={} desc={}",
-                                            auth_failure_count, error_code, description
-                                        );
-                                        
+                                    Err(reconnect_err) => {
+                                        error!("Reconnection after auth error failed: {}", reconnect_err);
                                         if auth_failure_count >= 3 {
-                                            error!("❌ CRITICAL: 3 consecutive authentication failures detected!");
-                                            error!("❌ Invalid credentials - please verify CLIENT_ID, CLIENT_SECRET, and ACCOUNT_ID in your .env file");
-                                            error!("❌ Stopping reconnection attempts to prevent infinite loop");
-                                            break; // Exit reader task
+                                            error!("❌ CRITICAL: 3 consecutive auth errors detected!");
+                                            break;
                                         }
-                                    } else {
-                                        error!(
-                                            "Reader: cTrader error: code={} desc={}",
-                                            error_code, description
-                                        );
+                                        continue;
                                     }
                                 }
-                            }
-                            let _ = message_tx.send(message);
-                        }
-                        ProtoOaPayloadType::ProtoOaOrderErrorEvent => {
-                            if let Some(payload) = &message.payload {
-                                if let Ok(err_event) = ProtoOaOrderErrorEvent::decode(payload.as_ref()) {
-                                    error!("Reader: Order error: code={:?} desc={:?}", 
-                                        err_event.error_code, err_event.description);
+                            } else if error_code.contains("AUTH_FAILURE") || error_code.contains("CH_CLIENT_AUTH_FAILURE") {
+                                auth_failure_count += 1;
+                                error!(
+                                    "❌ AUTHENTICATION FAILED (attempt {}/3): code={} desc={}",
+                                    auth_failure_count, error_code, description
+                                );
+                                
+                                if auth_failure_count >= 3 {
+                                    error!("❌ CRITICAL: 3 consecutive authentication failures detected!");
+                                    error!("❌ Invalid credentials - please verify CLIENT_ID, CLIENT_SECRET, and ACCOUNT_ID in your .env file");
+                                    error!("❌ Stopping reconnection attempts to prevent infinite loop");
+                                    break; // Exit reader task
                                 }
+                            } else {
+                                error!(
+                                    "Reader: cTrader error: code={} desc={}",
+                                    error_code, description
+                                );
                             }
-                            let _ = message_tx.send(message);
                         }
-                        _ => {
-                            debug!("Reader: Message type {:?} queued", msg_type);
-                            let _ = message_tx.send(message);
+                        let _ = message_tx.send(message);
+                    }
+                    Inbound::OrderError(message, err_event) => {
+                        if let Some(err_event) = err_event {
+                            error!("Reader: Order error: code={:?} desc={:?}", 
+                                err_event.error_code, err_event.description);
                         }
+                        let _ = message_tx.send(message);
+                    }
+                    Inbound::Response(message) => {
+                        debug!("Reader: Message type {} queued", message.payload_type);
+                        let _ = message_tx.send(message);
+                    }
+                    Inbound::Heartbeat => {
+                        debug!("Reader: Heartbeat received");
+                    }
+                    Inbound::Unknown(message) => {
+                        // Unknown type - queue for troubleshooting
+                        let payload_type = message.payload_type;
+                        debug!("Reader: Unknown message type {} queued", payload_type);
+                        let mut pending_guard = pending.lock().await;
+                        pending_guard.entry(payload_type).or_default().push_back(message);
                     }
-                } else if payload_type == ProtoPayloadType::HeartbeatEvent as i32 as u32 {
-                    debug!("Reader: Heartbeat received");
-                } else {
-                    // Unknown type - queue for troubleshooting
-                    debug!("Reader: Unknown message type {} queued", payload_type);
-                    let mut pending_guard = pending.lock().await;
-                    pending_guard.entry(payload_type).or_default().push_back(message);
                 }
             }
 
             warn!("cTrader reader task stopped");
+            connection.set_because(
+                ConnectionState::Failed,
+                "reader stopped after repeated authentication failures",
+            );
         });
 
         *self.reader_task.write().await = Some(task);
@@ -886,23 +1946,60 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         Ok(())
     }
 
+    /// Send a heartbeat on `stream` if one is due
+    async fn send_heartbeat_if_due(
+        stream: &mut TlsStream<TcpStream>,
+        heartbeats: &mut HeartbeatMonitor,
+        now: Instant,
+    ) -> std::io::Result<()> {
+        if heartbeats.heartbeat_due(now) {
+            stream.write_all(&heartbeat_frame()).await?;
+            heartbeats.sent(now);
+            debug!("Heartbeat sent");
+        }
+        Ok(())
+    }
+
+    /// Read a frame body of `msg_len` bytes. A body that stalls is a
+    /// `TimedOut` error: part of it may be consumed, so the stream is out of
+    /// sync and the reader reconnects.
+    async fn read_frame_body(
+        stream: &mut TlsStream<TcpStream>,
+        msg_len: usize,
+    ) -> std::io::Result<Vec<u8>> {
+        let mut msg_buf = vec![0u8; msg_len];
+        timeout(Duration::from_secs(30), stream.read_exact(&mut msg_buf))
+            .await
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+        Ok(msg_buf)
+    }
+
     /// Handle spot event (price update)
-    async fn handle_spot_event(event: ProtoOaSpotEvent, prices: &Arc<RwLock<HashMap<i64, Price>>>) {
+    async fn handle_spot_event(
+        event: ProtoOaSpotEvent,
+        prices: &Arc<RwLock<HashMap<i64, Price>>>,
+        spot_tx: &broadcast::Sender<Price>,
+    ) {
         let symbol_id = event.symbol_id;
         // bid/ask are u64 in 1/100000 of unit (e.g. 123000 = 1.23)
         let bid = event.bid.unwrap_or(0) as f64 / 100000.0;
         let ask = event.ask.unwrap_or(0) as f64 / 100000.0;
         let spread = ask - bid;
+        let (timestamp, clock_skew_ms) = spot_time(event.timestamp, chrono::Utc::now());
 
         let price = Price {
             symbol_id,
             bid,
             ask,
             spread,
-            timestamp: chrono::Utc::now(),
+            timestamp,
+            clock_skew_ms,
+            received_at: Instant::now(),
         };
 
-        prices.write().await.insert(symbol_id, price);
+        prices.write().await.insert(symbol_id, price.clone());
+        // No receivers is fine: spots are only streamed while the bot listens
+        let _ = spot_tx.send(price);
         debug!("Price update: {} bid={} ask={} spread={}", symbol_id, bid, ask, spread);
     }
 
@@ -922,11 +2019,23 @@ Possible causes: symbol invalid, market closed, or feed issue.",
             info!("Disconnected from cTrader");
         }
         *self.authenticated.write().await = false;
+        self.connection.set(ConnectionState::Disconnected);
         Ok(())
     }
 
-    /// Send a protobuf message
+    /// Send a request and return its client message id, which the server
+    /// echoes on the response and on an error response
+    async fn send_request(&self, message: ProtoMessage) -> Result<Option<String>> {
+        let request_id = message.client_msg_id.clone();
+        self.send_message(message).await?;
+        Ok(request_id)
+    }
+
+    /// Send a protobuf message, paced to stay under the server's message limits
     async fn send_message(&self, message: ProtoMessage) -> Result<()> {
+        // Wait for a slot before taking the stream so heartbeats are not held up
+        self.pacer.pace(message.payload_type).await;
+
         let mut stream_guard = self.stream.lock().await;
         let stream = stream_guard.as_mut()
             .ok_or(CTraderError::Disconnected)?;
@@ -939,25 +2048,32 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         Ok(())
     }
 
-    /// Wait for a specific message type
-    async fn wait_for_message(&self, msg_type: ProtoOaPayloadType) -> Result<ProtoMessage> {
+    /// Wait for a specific message type, or for the error response to
+    /// request `request_id`. Error responses to other requests are left
+    /// queued for their own waiters.
+    async fn wait_for_message(
+        &self,
+        msg_type: ProtoOaPayloadType,
+        request_id: Option<&str>,
+    ) -> Result<ProtoMessage> {
         let type_u32 = msg_type as i32 as u32;
-        // Check pending queue first
+        let error_type = ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32;
+        let order_error_type = ProtoOaPayloadType::ProtoOaOrderErrorEvent as i32 as u32;
+
+        // Take the reader first: whatever another waiter set aside for this
+        // request is queued by the time it lets go
+        let mut rx = self.message_rx.lock().await;
         {
             let mut pending = self.pending_messages.lock().await;
-            if let Some(queue) = pending.get_mut(&type_u32) {
-                if let Some(message) = queue.pop_front() {
-                    return Ok(message);
-                }
+            if let Some(message) = pending.get_mut(&type_u32).and_then(VecDeque::pop_front) {
+                return Ok(message);
+            }
+            if let Some(message) = take_error_response(&mut pending, request_id) {
+                return Err(error_response(&message).into());
             }
         }
 
         let timeout_duration = Duration::from_secs(30);
-        let mut rx = self.message_rx.lock().await;
-
-        let error_type = ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32;
-        let order_error_type = ProtoOaPayloadType::ProtoOaOrderErrorEvent as i32 as u32;
-
         loop {
             match timeout(timeout_duration, rx.recv()).await {
                 Ok(Some(message)) => {
@@ -965,17 +2081,9 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                         return Ok(message);
                     }
 
-                    // Fail-fast on error responses instead of queuing them
-                    if message.payload_type == error_type {
-                        if let Some(payload) = &message.payload {
-                            if let Ok(err_res) = ProtoOaErrorRes::decode(payload.as_ref()) {
-                                let desc = err_res.description.as_deref().unwrap_or("none");
-                                return Err(CTraderError::ApiError(format!(
-                                    "code={} desc={}", err_res.error_code, desc
-                                )).into());
-                            }
-                        }
-                        return Err(CTraderError::ApiError("Unknown error response".into()).into());
+                    // Fail fast on this request's error response
+                    if message.payload_type == error_type && answers(&message, request_id) {
+                        return Err(error_response(&message).into());
                     }
 
                     if message.payload_type == order_error_type {
@@ -990,8 +2098,7 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                         return Err(CTraderError::ApiError("Unknown order error".into()).into());
                     }
 
-                    let mut pending = self.pending_messages.lock().await;
-                    pending.entry(message.payload_type).or_default().push_back(message);
+                    set_aside(&mut *self.pending_messages.lock().await, message);
                 }
                 Ok(None) => return Err(CTraderError::Disconnected.into()),
                 Err(_) => return Err(CTraderError::Timeout.into()),
@@ -999,53 +2106,37 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         }
     }
 
-    /// Start background tasks (heartbeat, message handler)
-    async fn start_background_tasks(&self) {
-        let stream_clone = self.stream.clone();
-        let authenticated_clone = self.authenticated.clone();
-
-        // Heartbeat task
-        tokio::spawn(async move {
-            let mut heartbeat_interval = interval(Duration::from_secs(25));
-
-            loop {
-                heartbeat_interval.tick().await;
-
-                if !*authenticated_clone.read().await {
-                    break;
-                }
-
-                let mut payload = Vec::new();
-                if let Err(err) = (ProtoHeartbeatEvent { payload_type: None }).encode(&mut payload) {
-                    error!("Failed to encode heartbeat payload: {}", err);
-                    break;
-                }
-                let heartbeat = ProtoMessage {
-                    payload_type: ProtoPayloadType::HeartbeatEvent as i32 as u32,
-                    payload: Some(payload),
-                    client_msg_id: None,
-                };
-
-                let mut stream_guard = stream_clone.lock().await;
-                if let Some(stream) = stream_guard.as_mut() {
-                    let encoded = encode_with_length(&heartbeat);
-                    if let Err(e) = stream.write_all(&encoded).await {
-                        error!("Failed to send heartbeat: {}", e);
-                        break;
-                    }
-                    debug!("Heartbeat sent");
-                }
-            }
-        });
-
-        info!("Background tasks started");
-    }
-
     /// Check if client is authenticated
     pub async fn is_authenticated(&self) -> bool {
         *self.authenticated.read().await
     }
 
+    /// `reconnect_internal` behind the reconnect breaker, for the reader task
+    /// (the only caller after startup):
+    /// an open breaker is waited out rather than hammered, and the outcome
+    /// is counted
+    async fn reconnect_guarded(
+        breaker: &SharedReconnectBreaker,
+        config: &mut CTraderConfig,
+        environment: CTraderEnvironment,
+        stream: &Arc<Mutex<Option<TlsStream<TcpStream>>>>,
+        authenticated: &Arc<RwLock<bool>>,
+        subscribed_symbols: &Arc<RwLock<Vec<i64>>>,
+    ) -> Result<()> {
+        if let Some(wait) = breaker.wait_before_attempt() {
+            warn!("Reconnect breaker open; next attempt in {}s", wait.as_secs());
+            sleep(wait).await;
+        }
+        let result =
+            Self::reconnect_internal(config, environment, stream, authenticated, subscribed_symbols)
+                .await;
+        match &result {
+            Ok(()) => breaker.record_success(),
+            Err(err) => breaker.record_failure(err),
+        }
+        result
+    }
+
     /// Internal reconnection logic (static to avoid self borrow issues)
     async fn reconnect_internal(
         config: &mut CTraderConfig,
@@ -1092,14 +2183,8 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                 .map_err(|_| CTraderError::Timeout)?
                 .map_err(|e| CTraderError::ConnectionFailed(e.to_string()))?;
 
-            let msg_len = u32::from_be_bytes(len_buf) as usize;
-            if msg_len > 1_000_000 {
-                return Err(CTraderError::InvalidResponse(format!(
-                    "Message too large: {}",
-                    msg_len
-                ))
-                .into());
-            }
+            let msg_len = frame::frame_len(len_buf)
+                .map_err(|e| CTraderError::InvalidResponse(e.to_string()))?;
 
             let mut msg_buf = vec![0u8; msg_len];
             timeout(Duration::from_secs(10), stream.read_exact(&mut msg_buf))
@@ -1107,8 +2192,8 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                 .map_err(|_| CTraderError::Timeout)?
                 .map_err(|e| CTraderError::ConnectionFailed(e.to_string()))?;
 
-            ProtoMessage::decode(msg_buf.as_ref())
-                .map_err(|e| CTraderError::InvalidResponse(format!("Decode error: {}", e)).into())
+            frame::decode_frame(&msg_buf)
+                .map_err(|e| CTraderError::InvalidResponse(e.to_string()).into())
         }
         
         // Application auth
@@ -1191,8 +2276,8 @@ Possible causes: symbol invalid, market closed, or feed issue.",
                 redirect_uri: oauth_redirect_uri(),
                 environment: Environment::Live,
             };
-            let storage = FileTokenStorage::new("oauth_token.json");
-            let manager = OAuthManager::new(oauth_config).with_storage(Box::new(storage));
+            let storage = account_token_storage(Environment::Live, config.active_account_id());
+            let manager = OAuthManager::new(oauth_config).with_storage(storage);
             if let Err(err) = manager.init().await {
                 warn!("OAuth init failed during reconnect, using existing token: {}", err);
             }
@@ -1308,17 +2393,6 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         Ok(())
     }
     
-    /// Public reconnection method
-    pub async fn reconnect(&mut self) -> Result<()> {
-        Self::reconnect_internal(
-            &mut self.config,
-            self.environment,
-            &self.stream,
-            &self.authenticated,
-            &self.subscribed_symbols,
-        ).await
-    }
-
     /// Resolve a symbol name to its numeric ID
     pub async fn get_symbol_id(&self, symbol_name: &str) -> Result<i64> {
         if !*self.authenticated.read().await {
@@ -1337,14 +2411,25 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaSymbolsListReq, symbols_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
-        let response = self.wait_for_message(ProtoOaPayloadType::ProtoOaSymbolsListRes).await?;
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaSymbolsListRes, request_id.as_deref())
+            .await?;
 
         if let Some(payload) = response.payload {
             let symbols_res = ProtoOaSymbolsListRes::decode(payload.as_ref())
                 .map_err(|e| CTraderError::InvalidResponse(format!("Failed to decode symbols list: {}", e)))?;
 
+        {
+            let mut category_ids = self.symbol_category_ids.write().await;
+            for light in &symbols_res.symbol {
+                if let Some(category_id) = light.symbol_category_id {
+                    category_ids.insert(light.symbol_id, category_id);
+                }
+            }
+        }
+
         let mut candidates = vec![
             symbol_name.to_string(),
         ];
@@ -1404,9 +2489,11 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaTraderReq, trader_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
-        let response = self.wait_for_message(ProtoOaPayloadType::ProtoOaTraderRes).await?;
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaTraderRes, request_id.as_deref())
+            .await?;
         if let Some(payload) = response.payload {
             let trader_res = ProtoOaTraderRes::decode(payload.as_ref()).map_err(|e| {
                 CTraderError::InvalidResponse(format!("Failed to decode trader info: {}", e))
@@ -1417,6 +2504,170 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         Err(CTraderError::InvalidResponse("Empty trader response".into()).into())
     }
 
+    /// Account balance in account currency
+    pub async fn get_balance(&self) -> Result<f64> {
+        let trader = self.get_trader().await?;
+        let money_digits = trader.money_digits.unwrap_or(0) as i32;
+        Ok(trader.balance as f64 / 10_f64.powi(money_digits))
+    }
+
+    /// Balance effect of the deals executed between two Unix timestamps in
+    /// milliseconds: realized P&L, swap, commission and conversion fees
+    pub async fn deal_balance_changes(&self, from_ms: i64, to_ms: i64) -> Result<f64> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self
+            .config
+            .active_account_id()
+            .parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let mut deals: HashMap<i64, f64> = HashMap::new();
+        let mut from = from_ms;
+        loop {
+            let list_req = ProtoOaDealListReq {
+                payload_type: None,
+                ctid_trader_account_id: account_id,
+                from_timestamp: Some(from),
+                to_timestamp: Some(to_ms),
+                max_rows: None,
+            };
+            let msg = new_proto_message(ProtoOaPayloadType::ProtoOaDealListReq, list_req);
+            let request_id = self.send_request(msg).await?;
+
+            let response = self
+                .wait_for_message(ProtoOaPayloadType::ProtoOaDealListRes, request_id.as_deref())
+                .await?;
+            let list_res = response
+                .payload
+                .as_deref()
+                .and_then(|p| ProtoOaDealListRes::decode(p).ok())
+                .ok_or_else(|| CTraderError::InvalidResponse("Failed to decode deal list".into()))?;
+
+            // Page forward from the newest execution seen; stop if the server does not advance
+            let next_from = list_res.deal.iter().map(|d| d.execution_timestamp).max().map(|t| t + 1);
+            for deal in &list_res.deal {
+                deals.insert(deal.deal_id, deal_balance_change(deal));
+            }
+            match next_from {
+                Some(next) if list_res.has_more && next > from => from = next,
+                _ => break,
+            }
+        }
+        Ok(deals.values().sum())
+    }
+
+    /// Verify that the access token may trade the configured account.
+    ///
+    /// Lists the token's accounts (a read that needs no trading rights) and
+    /// fetches the trader, so a view-only token, a foreign or wrong-environment
+    /// account, or a close-only account is reported at startup rather than on
+    /// the first order.
+    pub async fn verify_trading_scope(&self) -> Result<AccountScope> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self
+            .config
+            .active_account_id()
+            .parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+        let access_token = self
+            .access_token
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| CTraderError::AuthFailed("No access token".into()))?;
+
+        let accounts_req = ProtoOaGetAccountListByAccessTokenReq {
+            payload_type: None,
+            access_token,
+        };
+
+        let msg = new_proto_message(
+            ProtoOaPayloadType::ProtoOaGetAccountsByAccessTokenReq,
+            accounts_req,
+        );
+        let request_id = self.send_request(msg).await?;
+
+        let response = self
+            .wait_for_message(
+                ProtoOaPayloadType::ProtoOaGetAccountsByAccessTokenRes,
+                request_id.as_deref(),
+            )
+            .await?;
+        let payload = response.payload.ok_or_else(|| {
+            CTraderError::InvalidResponse("Empty account list response".into())
+        })?;
+        let accounts =
+            ProtoOaGetAccountListByAccessTokenRes::decode(payload.as_ref()).map_err(|e| {
+                CTraderError::InvalidResponse(format!("Failed to decode account list: {}", e))
+            })?;
+
+        let trader = self.get_trader().await?;
+        AccountScope::verify(
+            account_id,
+            self.environment,
+            &accounts,
+            trader.access_rights(),
+        )
+    }
+
+    /// Margin the broker would take for a new position of `volume` (in
+    /// cents of a unit, like `OrderTicket::volume`) on `side`
+    pub async fn expected_margin(
+        &self,
+        symbol_id: i64,
+        volume: i64,
+        side: ProtoOaTradeSide,
+    ) -> Result<f64> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        let account_id = self
+            .config
+            .active_account_id()
+            .parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let margin_req = ProtoOaExpectedMarginReq {
+            payload_type: None,
+            ctid_trader_account_id: account_id,
+            symbol_id,
+            volume: vec![volume],
+        };
+
+        let msg = new_proto_message(ProtoOaPayloadType::ProtoOaExpectedMarginReq, margin_req);
+        let request_id = self.send_request(msg).await?;
+
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaExpectedMarginRes, request_id.as_deref())
+            .await?;
+        let payload = response.payload.ok_or_else(|| {
+            CTraderError::InvalidResponse("Empty expected margin response".into())
+        })?;
+        let margin_res = ProtoOaExpectedMarginRes::decode(payload.as_ref()).map_err(|e| {
+            CTraderError::InvalidResponse(format!("Failed to decode expected margin: {}", e))
+        })?;
+
+        let margin = margin_res
+            .margin
+            .iter()
+            .find(|m| m.volume == volume)
+            .or_else(|| margin_res.margin.first())
+            .ok_or_else(|| CTraderError::InvalidResponse("No expected margin returned".into()))?;
+        let raw = match side {
+            ProtoOaTradeSide::Buy => margin.buy_margin,
+            ProtoOaTradeSide::Sell => margin.sell_margin,
+        };
+        let money_digits = margin_res.money_digits.unwrap_or(2) as i32;
+        Ok(raw as f64 / 10_f64.powi(money_digits))
+    }
+
     /// Fetch detailed symbol metadata (digits, volume steps, distances, etc.)
     pub async fn get_symbol_meta(&self, symbol_id: i64) -> Result<SymbolMeta> {
         if !*self.authenticated.read().await {
@@ -1437,9 +2688,11 @@ Possible causes: symbol invalid, market closed, or feed issue.",
         };
 
         let msg = new_proto_message(ProtoOaPayloadType::ProtoOaSymbolByIdReq, symbol_req);
-        self.send_message(msg).await?;
+        let request_id = self.send_request(msg).await?;
 
-        let response = self.wait_for_message(ProtoOaPayloadType::ProtoOaSymbolByIdRes).await?;
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaSymbolByIdRes, request_id.as_deref())
+            .await?;
 
         if let Some(payload) = response.payload {
             let symbols_res = ProtoOaSymbolByIdRes::decode(payload.as_ref())
@@ -1458,6 +2711,60 @@ Possible causes: symbol invalid, market closed, or feed issue.",
 
         Err(CTraderError::InvalidResponse("Empty symbol-by-id response".into()).into())
     }
+
+    /// Fetch the symbol's category and asset class.
+    ///
+    /// The category ID comes from the symbols list, so `get_symbol_id` must
+    /// have run first; otherwise the classification has no category.
+    pub async fn get_symbol_classification(&self, symbol_id: i64) -> Result<SymbolClassification> {
+        if !*self.authenticated.read().await {
+            return Err(CTraderError::AuthFailed("Not authenticated".into()).into());
+        }
+
+        if let Some(class) = self.symbol_class_cache.read().await.get(&symbol_id) {
+            return Ok(class.clone());
+        }
+
+        let account_id = self.config.active_account_id().parse::<i64>()
+            .map_err(|e| CTraderError::Protocol(format!("Invalid account ID: {}", e)))?;
+
+        let category_req = ProtoOaSymbolCategoryListReq {
+            payload_type: None,
+            ctid_trader_account_id: account_id,
+        };
+        let msg = new_proto_message(ProtoOaPayloadType::ProtoOaSymbolCategoryReq, category_req);
+        let request_id = self.send_request(msg).await?;
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaSymbolCategoryRes, request_id.as_deref())
+            .await?;
+        let categories = match response.payload {
+            Some(payload) => ProtoOaSymbolCategoryListRes::decode(payload.as_ref())
+                .map_err(|e| CTraderError::InvalidResponse(format!("Failed to decode symbol categories: {}", e)))?
+                .symbol_category,
+            None => return Err(CTraderError::InvalidResponse("Empty symbol category response".into()).into()),
+        };
+
+        let asset_class_req = ProtoOaAssetClassListReq {
+            payload_type: None,
+            ctid_trader_account_id: account_id,
+        };
+        let msg = new_proto_message(ProtoOaPayloadType::ProtoOaAssetClassListReq, asset_class_req);
+        let request_id = self.send_request(msg).await?;
+        let response = self
+            .wait_for_message(ProtoOaPayloadType::ProtoOaAssetClassListRes, request_id.as_deref())
+            .await?;
+        let asset_classes = match response.payload {
+            Some(payload) => ProtoOaAssetClassListRes::decode(payload.as_ref())
+                .map_err(|e| CTraderError::InvalidResponse(format!("Failed to decode asset classes: {}", e)))?
+                .asset_class,
+            None => return Err(CTraderError::InvalidResponse("Empty asset class response".into()).into()),
+        };
+
+        let category_id = self.symbol_category_ids.read().await.get(&symbol_id).copied();
+        let class = SymbolClassification::from_lists(symbol_id, category_id, &categories, &asset_classes);
+        self.symbol_class_cache.write().await.insert(symbol_id, class.clone());
+        Ok(class)
+    }
 }
 
 fn build_tls_config() -> std::result::Result<ClientConfig, CTraderError> {
@@ -1488,6 +2795,177 @@ fn build_tls_config() -> std::result::Result<ClientConfig, CTraderError> {
     Ok(config)
 }
 
+/// Resolve the timestamp for a spot event: the server's spot time (unix ms)
+/// when present, plus the local-vs-server skew. Falls back to the receive time.
+fn spot_time(
+    server_ms: Option<i64>,
+    received_at: chrono::DateTime<chrono::Utc>,
+) -> (chrono::DateTime<chrono::Utc>, Option<i64>) {
+    match server_ms.and_then(chrono::DateTime::from_timestamp_millis) {
+        Some(server_time) => {
+            let skew = (received_at - server_time).num_milliseconds();
+            (server_time, Some(skew))
+        }
+        None => (received_at, None),
+    }
+}
+
+fn trendbar_period(timeframe: TimeFrame) -> ProtoOaTrendbarPeriod {
+    match timeframe {
+        TimeFrame::M1 => ProtoOaTrendbarPeriod::M1,
+        TimeFrame::M5 => ProtoOaTrendbarPeriod::M5,
+        TimeFrame::M15 => ProtoOaTrendbarPeriod::M15,
+        TimeFrame::M30 => ProtoOaTrendbarPeriod::M30,
+        TimeFrame::H1 => ProtoOaTrendbarPeriod::H1,
+        TimeFrame::H4 => ProtoOaTrendbarPeriod::H4,
+        TimeFrame::D1 => ProtoOaTrendbarPeriod::D1,
+    }
+}
+
+/// Error responses kept for requests that have not collected them yet;
+/// beyond this the oldest, which nobody is waiting for, are dropped
+const MAX_PENDING_ERRORS: usize = 32;
+
+/// Whether `message` answers request `request_id`
+fn answers(message: &ProtoMessage, request_id: Option<&str>) -> bool {
+    request_id.is_some() && message.client_msg_id.as_deref() == request_id
+}
+
+/// Queue a message no current waiter wants for the one it belongs to
+fn set_aside(pending: &mut HashMap<u32, VecDeque<ProtoMessage>>, message: ProtoMessage) {
+    let error_type = ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32;
+    let payload_type = message.payload_type;
+    let queue = pending.entry(payload_type).or_default();
+    queue.push_back(message);
+    if payload_type == error_type && queue.len() > MAX_PENDING_ERRORS {
+        if let Some(dropped) = queue.pop_front() {
+            warn!(
+                "Dropping unclaimed error response {:?}: {}",
+                dropped.client_msg_id,
+                error_response(&dropped)
+            );
+        }
+    }
+}
+
+/// Remove and return the queued error response to `request_id`
+fn take_error_response(
+    pending: &mut HashMap<u32, VecDeque<ProtoMessage>>,
+    request_id: Option<&str>,
+) -> Option<ProtoMessage> {
+    let error_type = ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32;
+    let queue = pending.get_mut(&error_type)?;
+    let index = queue.iter().position(|m| answers(m, request_id))?;
+    queue.remove(index)
+}
+
+/// The error an error response carries
+fn error_response(message: &ProtoMessage) -> CTraderError {
+    let desc = message
+        .payload
+        .as_deref()
+        .and_then(|p| ProtoOaErrorRes::decode(p).ok())
+        .map(|e| format!("code={} desc={}", e.error_code, e.description.unwrap_or_default()))
+        .unwrap_or_else(|| "Unknown error response".to_string());
+    CTraderError::ApiError(desc)
+}
+
+/// Identifiers an execution event or order error carries
+#[derive(Debug, Clone, Copy)]
+struct ExecutionIds<'a> {
+    position_id: Option<i64>,
+    order_id: Option<i64>,
+    client_order_id: Option<&'a str>,
+}
+
+impl<'a> ExecutionIds<'a> {
+    fn of(exec: &'a ProtoOaExecutionEvent) -> Self {
+        Self {
+            position_id: exec.position.as_ref().map(|p| p.position_id),
+            order_id: exec.order.as_ref().map(|o| o.order_id),
+            client_order_id: exec.order.as_ref().and_then(|o| o.client_order_id.as_deref()),
+        }
+    }
+}
+
+/// Margin held for a broker position, scaled by its money digits
+fn position_used_margin(position: &ProtoOaPosition) -> f64 {
+    let money_digits = position.money_digits.unwrap_or(2) as i32;
+    position.used_margin.unwrap_or(0) as f64 / 10_f64.powi(money_digits)
+}
+
+/// Balance effect of one deal in account currency: realized P&L, swap,
+/// commission and conversion fee for a closing deal, commission otherwise.
+/// Rejected deals have none.
+fn deal_balance_change(deal: &ProtoOaDeal) -> f64 {
+    let filled = matches!(
+        ProtoOaDealStatus::try_from(deal.deal_status),
+        Ok(ProtoOaDealStatus::Filled | ProtoOaDealStatus::PartiallyFilled)
+    );
+    if !filled {
+        return 0.0;
+    }
+    match &deal.close_position_detail {
+        Some(detail) => {
+            let money_digits = detail.money_digits.or(deal.money_digits).unwrap_or(2) as i32;
+            (detail.gross_profit
+                + detail.swap
+                + detail.commission
+                + detail.pnl_conversion_fee.unwrap_or(0)) as f64
+                / 10_f64.powi(money_digits)
+        }
+        None => {
+            let money_digits = deal.money_digits.unwrap_or(2) as i32;
+            deal.commission.unwrap_or(0) as f64 / 10_f64.powi(money_digits)
+        }
+    }
+}
+
+/// Convert a trendbar (low plus deltas, in 1/100000 of a unit) into a candle
+fn candle_from_trendbar(bar: &ProtoOaTrendbar, timeframe: TimeFrame) -> Option<Candle> {
+    let low = bar.low? as f64;
+    let minutes = bar.utc_timestamp_in_minutes?;
+    let timestamp = chrono::DateTime::from_timestamp(minutes as i64 * 60, 0)?;
+    let price = |delta: Option<u64>| (low + delta.unwrap_or(0) as f64) / 100000.0;
+    Some(Candle {
+        timestamp,
+        timeframe,
+        open: price(bar.delta_open),
+        high: price(bar.delta_high),
+        low: low / 100000.0,
+        close: price(bar.delta_close),
+        volume: bar.volume.max(0) as u64,
+        avg_spread: None,
+        close_spread: None,
+    })
+}
+
+/// Decode a tick data page: newest first, the first entry absolute and the
+/// rest deltas from the previous one. Returns `(timestamp_ms, price)` oldest first.
+fn decode_tick_data(data: &[ProtoOaTickData]) -> Vec<(i64, f64)> {
+    let mut timestamp = 0i64;
+    let mut tick = 0i64;
+    let mut ticks: Vec<(i64, f64)> = data
+        .iter()
+        .map(|entry| {
+            timestamp += entry.timestamp;
+            tick += entry.tick;
+            (timestamp, tick as f64 / 100000.0)
+        })
+        .collect();
+    ticks.reverse();
+    ticks
+}
+
+/// This account's record in the shared token file, seeded from the legacy
+/// single-token file on first use
+fn account_token_storage(environment: Environment, account_id: &str) -> Box<dyn TokenStorage> {
+    let storage = Arc::new(MultiAccountTokenStorage::from_env())
+        .for_account(environment, account_id)
+        .with_legacy_file(LEGACY_TOKEN_FILE);
+    Box::new(storage)
+}
+
 fn oauth_redirect_uri() -> String {
     env::var("CTRADER_REDIRECT_URI")
         .unwrap_or_else(|_| "http://localhost:8899".to_string())
@@ -1518,6 +2996,153 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_symbol_classification_from_lists() {
+        let categories = vec![ProtoOaSymbolCategory {
+            id: 7,
+            asset_class_id: 3,
+            name: "Softs".to_string(),
+            sorting_number: None,
+        }];
+        let asset_classes = vec![ProtoOaAssetClass {
+            id: Some(3),
+            name: Some("Commodities".to_string()),
+            sorting_number: None,
+        }];
+
+        let class = SymbolClassification::from_lists(42, Some(7), &categories, &asset_classes);
+        assert_eq!(class.category.as_deref(), Some("Softs"));
+        assert_eq!(class.asset_class_id, Some(3));
+        assert_eq!(class.asset_class.as_deref(), Some("Commodities"));
+
+        let unknown = SymbolClassification::from_lists(42, None, &categories, &asset_classes);
+        assert!(unknown.category.is_none());
+        assert!(unknown.asset_class.is_none());
+    }
+
+    #[test]
+    fn test_spot_time_prefers_server_timestamp() {
+        let received = chrono::DateTime::from_timestamp_millis(1_709_546_400_250).unwrap();
+
+        let (timestamp, skew) = spot_time(Some(1_709_546_400_000), received);
+        assert_eq!(timestamp.timestamp_millis(), 1_709_546_400_000);
+        assert_eq!(skew, Some(250));
+
+        let (timestamp, skew) = spot_time(None, received);
+        assert_eq!(timestamp, received);
+        assert_eq!(skew, None);
+    }
+
+    fn ticket(order_type: ProtoOaOrderType) -> OrderTicket {
+        OrderTicket {
+            symbol_id: 1,
+            side: ProtoOaTradeSide::Buy,
+            volume: 100,
+            order_type,
+            limit_price: None,
+            stop_price: None,
+            expiration: None,
+            stop_loss: None,
+            take_profit: None,
+            relative_stop_loss: None,
+            relative_take_profit: None,
+            trailing_stop_loss: false,
+            label: None,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_order_ticket_validation() {
+        assert!(ticket(ProtoOaOrderType::Market).validate().is_ok());
+        assert!(!ticket(ProtoOaOrderType::Market).is_pending());
+
+        let mut market = ticket(ProtoOaOrderType::Market);
+        market.limit_price = Some(4800.0);
+        assert!(market.validate().is_err());
+
+        let mut limit = ticket(ProtoOaOrderType::Limit);
+        assert!(limit.validate().is_err());
+        limit.limit_price = Some(4800.0);
+        limit.expiration = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        assert!(limit.validate().is_ok());
+        assert!(limit.is_pending());
+        limit.expiration = Some(chrono::Utc::now() - chrono::Duration::hours(1));
+        assert!(limit.validate().is_err());
+
+        let mut stop = ticket(ProtoOaOrderType::Stop);
+        stop.limit_price = Some(4800.0);
+        assert!(stop.validate().is_err());
+        stop.limit_price = None;
+        stop.stop_price = Some(4850.0);
+        assert!(stop.validate().is_ok());
+
+        assert!(ticket(ProtoOaOrderType::StopLimit).validate().is_err());
+    }
+
+    #[test]
+    fn test_candle_from_trendbar() {
+        let bar = ProtoOaTrendbar {
+            volume: 42,
+            period: Some(ProtoOaTrendbarPeriod::M5 as i32),
+            low: Some(480_000_000),
+            delta_open: Some(50_000),
+            delta_close: Some(150_000),
+            delta_high: Some(200_000),
+            utc_timestamp_in_minutes: Some(28_500_000),
+        };
+        let candle = candle_from_trendbar(&bar, TimeFrame::M5).unwrap();
+        assert_eq!(candle.low, 4800.0);
+        assert_eq!(candle.open, 4800.5);
+        assert_eq!(candle.close, 4801.5);
+        assert_eq!(candle.high, 4802.0);
+        assert_eq!(candle.volume, 42);
+        assert_eq!(candle.timestamp.timestamp(), 28_500_000 * 60);
+
+        let missing_low = ProtoOaTrendbar { low: None, ..bar };
+        assert!(candle_from_trendbar(&missing_low, TimeFrame::M5).is_none());
+    }
+
+    #[test]
+    fn test_decode_tick_data_deltas() {
+        let data = [
+            ProtoOaTickData { timestamp: 1_700_000_003_000, tick: 480_100_000 },
+            ProtoOaTickData { timestamp: -1_000, tick: -50_000 },
+            ProtoOaTickData { timestamp: -2_000, tick: 20_000 },
+        ];
+        assert_eq!(
+            decode_tick_data(&data),
+            vec![
+                (1_700_000_000_000, 4800.7),
+                (1_700_000_002_000, 4800.5),
+                (1_700_000_003_000, 4801.0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spot_events_are_streamed() {
+        let client = CTraderClient::new(test_config());
+        let mut spots = client.subscribe_spots();
+        let event = ProtoOaSpotEvent {
+            symbol_id: 7,
+            bid: Some(480_000_000),
+            ask: Some(480_100_000),
+            timestamp: Some(1_709_546_400_000),
+            ..Default::default()
+        };
+
+        CTraderClient::handle_spot_event(event, &client.prices, &client.spot_tx).await;
+
+        let streamed = spots.try_recv().unwrap();
+        assert_eq!(streamed.symbol_id, 7);
+        assert_eq!(streamed.bid, 4800.0);
+        assert_eq!(streamed.ask, 4801.0);
+        let cached = client.get_price(7).await.unwrap();
+        assert_eq!(cached.ask, 4801.0);
+        assert!(cached.age() < Duration::from_secs(5));
+    }
+
     #[tokio::test]
     async fn test_client_creation() {
         let client = CTraderClient::new(test_config());
@@ -1610,4 +3235,164 @@ mod tests {
         let client = CTraderClient::from_config(config);
         assert_eq!(client.environment(), CTraderEnvironment::Live);
     }
+
+    #[test]
+    fn test_deal_balance_change() {
+        let mut deal = ProtoOaDeal {
+            deal_id: 1,
+            order_id: 2,
+            position_id: 3,
+            volume: 100,
+            filled_volume: 100,
+            symbol_id: 1,
+            create_timestamp: 0,
+            execution_timestamp: 0,
+            deal_status: ProtoOaDealStatus::Filled as i32,
+            commission: Some(-250),
+            money_digits: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(deal_balance_change(&deal), -2.5);
+
+        deal.close_position_detail = Some(ProtoOaClosePositionDetail {
+            gross_profit: 15_000,
+            swap: -100,
+            commission: -250,
+            money_digits: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(deal_balance_change(&deal), 146.5);
+
+        deal.deal_status = ProtoOaDealStatus::Rejected as i32;
+        assert_eq!(deal_balance_change(&deal), 0.0);
+    }
+
+    #[test]
+    fn test_account_scope_verify() {
+        let mut accounts = ProtoOaGetAccountListByAccessTokenRes {
+            permission_scope: Some(ProtoOaClientPermissionScope::ScopeTrade as i32),
+            ctid_trader_account: vec![ProtoOaCtidTraderAccount {
+                ctid_trader_account_id: 12345,
+                is_live: Some(false),
+                trader_login: Some(777),
+                broker_title_short: Some("Pepperstone".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let demo = CTraderEnvironment::Demo;
+        let full = ProtoOaAccessRights::FullAccess;
+
+        let scope = AccountScope::verify(12345, demo, &accounts, full).unwrap();
+        assert_eq!(scope.trader_login, Some(777));
+        assert_eq!(scope.broker.as_deref(), Some("Pepperstone"));
+        assert!(!scope.is_live);
+
+        // Configured account not linked to the token
+        let err = AccountScope::verify(999, demo, &accounts, full).unwrap_err();
+        assert!(err.to_string().contains("12345"));
+        // Demo account while the bot runs against LIVE
+        assert!(AccountScope::verify(12345, CTraderEnvironment::Live, &accounts, full).is_err());
+        // Account restricted by the broker
+        for rights in [
+            ProtoOaAccessRights::CloseOnly,
+            ProtoOaAccessRights::NoTrading,
+            ProtoOaAccessRights::NoLogin,
+        ] {
+            assert!(matches!(
+                AccountScope::verify(12345, demo, &accounts, rights),
+                Err(BotError::CTrader(CTraderError::InsufficientPermissions(_)))
+            ));
+        }
+
+        // View-only token
+        accounts.permission_scope = Some(ProtoOaClientPermissionScope::ScopeView as i32);
+        let err = AccountScope::verify(12345, demo, &accounts, full).unwrap_err();
+        assert!(err.to_string().contains("view-only"));
+    }
+
+    #[test]
+    fn test_client_order_ids_match_their_events() {
+        let first = next_client_order_id();
+        let second = next_client_order_id();
+        assert_ne!(first, second);
+        // Open API limit
+        assert!(first.len() <= 50);
+
+        let exec = ProtoOaExecutionEvent {
+            order: Some(ProtoOaOrder {
+                order_id: 9,
+                client_order_id: Some(first.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let ids = ExecutionIds::of(&exec);
+        assert_eq!(ids.client_order_id, Some(first.as_str()));
+        assert_eq!(ids.order_id, Some(9));
+        assert_eq!(ids.position_id, None);
+    }
+
+    #[test]
+    fn test_error_response_keeps_code_and_description() {
+        let error = ProtoOaErrorRes {
+            error_code: "POSITION_NOT_FOUND".to_string(),
+            description: Some("Position not found".to_string()),
+            ..Default::default()
+        };
+        let message = new_proto_message(ProtoOaPayloadType::ProtoOaErrorRes, error);
+        assert!(message.client_msg_id.is_some());
+        assert_eq!(
+            error_response(&message).to_string(),
+            CTraderError::ApiError("code=POSITION_NOT_FOUND desc=Position not found".into())
+                .to_string()
+        );
+    }
+
+    fn error_to(request_id: &str, error_code: &str) -> ProtoMessage {
+        let error = ProtoOaErrorRes {
+            error_code: error_code.to_string(),
+            ..Default::default()
+        };
+        let mut message = new_proto_message(ProtoOaPayloadType::ProtoOaErrorRes, error);
+        message.client_msg_id = Some(request_id.to_string());
+        message
+    }
+
+    #[tokio::test]
+    async fn test_error_response_goes_to_its_own_request() {
+        let client = CTraderClient::new(test_config());
+        client.message_tx.send(error_to("symbols", "INVALID_REQUEST")).unwrap();
+        let reconcile = ProtoOaReconcileRes::default();
+        client
+            .message_tx
+            .send(new_proto_message(ProtoOaPayloadType::ProtoOaReconcileRes, reconcile))
+            .unwrap();
+
+        // The reconcile skips the symbol lookup's error...
+        client
+            .wait_for_message(ProtoOaPayloadType::ProtoOaReconcileRes, Some("reconcile"))
+            .await
+            .unwrap();
+        // ...which then fails the lookup without waiting out the timeout
+        let err = client
+            .wait_for_message(ProtoOaPayloadType::ProtoOaSymbolsListRes, Some("symbols"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("INVALID_REQUEST"));
+        assert!(client.pending_messages.lock().await.values().all(VecDeque::is_empty));
+    }
+
+    #[test]
+    fn test_unclaimed_error_responses_are_bounded() {
+        let mut pending = HashMap::new();
+        for n in 0..=MAX_PENDING_ERRORS {
+            set_aside(&mut pending, error_to(&n.to_string(), "INVALID_REQUEST"));
+        }
+        assert!(take_error_response(&mut pending, Some("0")).is_none());
+        assert!(take_error_response(&mut pending, Some("1")).is_some());
+        assert!(take_error_response(&mut pending, None).is_none());
+        let error_type = ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32;
+        assert_eq!(pending[&error_type].len(), MAX_PENDING_ERRORS - 1);
+    }
 }
@@ -0,0 +1,194 @@
+//! Explainable signal decisions
+//!
+//! `Strategy::generate_signal` returns a `SignalDecision` rather than a bare
+//! `Signal`: besides the signal it carries a signed score, every condition
+//! the strategy checked (observed value, threshold, pass/fail) and the
+//! blockers that kept an otherwise valid signal from trading (circuit
+//! breakers, cooldowns, market hours, ...). The bot adds the blockers, logs
+//! the decision on every closed candle, publishes it as a `SignalDecision`
+//! event and stores it in the `signal_decisions` table, so any trade - or
+//! missing trade - can be traced back to its inputs.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::orders::OrderSide;
+use super::strategy::Signal;
+
+/// One condition checked for a decision
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecisionFactor {
+    /// Condition name, e.g. `rsi`, `sentiment`, `trend`
+    pub name: String,
+    /// Observed value (None when the input is not ready)
+    pub value: Option<f64>,
+    /// Level the value was compared with, if any
+    pub threshold: Option<f64>,
+    /// Whether the condition allowed the signal
+    pub passed: bool,
+    /// Extra context, e.g. the trend direction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl DecisionFactor {
+    pub fn new(name: impl Into<String>, value: Option<f64>, passed: bool) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            threshold: None,
+            passed,
+            note: None,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+impl fmt::Display for DecisionFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Some(value) => write!(f, "{}={:.2}", self.name, value)?,
+            None => write!(f, "{}=n/a", self.name)?,
+        }
+        if let Some(threshold) = self.threshold {
+            write!(f, " (vs {:.2})", threshold)?;
+        }
+        if let Some(note) = &self.note {
+            write!(f, " [{}]", note)?;
+        }
+        f.write_str(if self.passed { " ✓" } else { " ✗" })
+    }
+}
+
+/// A strategy's answer for one closed candle, with its reasons
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalDecision {
+    pub signal: Signal,
+    /// Signed conviction from -1.0 (strong sell) to +1.0 (strong buy); its
+    /// meaning is up to the strategy, 0.0 when it has none
+    pub score: f64,
+    /// Side the factors were checked for (None when nothing was checked)
+    pub side: Option<OrderSide>,
+    /// Conditions checked, in evaluation order
+    pub factors: Vec<DecisionFactor>,
+    /// Why the signal was not traded; empty when it was (or for a hold)
+    pub blockers: Vec<String>,
+}
+
+impl SignalDecision {
+    pub fn new(signal: Signal) -> Self {
+        Self {
+            signal,
+            score: 0.0,
+            side: None,
+            factors: Vec::new(),
+            blockers: Vec::new(),
+        }
+    }
+
+    pub fn hold() -> Self {
+        Self::new(Signal::Hold)
+    }
+
+    pub fn with_score(mut self, score: f64) -> Self {
+        self.score = score.clamp(-1.0, 1.0);
+        self
+    }
+
+    pub fn with_side(mut self, side: OrderSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    pub fn with_factor(mut self, factor: DecisionFactor) -> Self {
+        self.factors.push(factor);
+        self
+    }
+
+    pub fn with_factors(mut self, factors: impl IntoIterator<Item = DecisionFactor>) -> Self {
+        self.factors.extend(factors);
+        self
+    }
+
+    /// Record why the signal is not traded
+    pub fn block(&mut self, reason: impl Into<String>) {
+        self.blockers.push(reason.into());
+    }
+
+    /// A buy or sell that nothing blocked
+    pub fn is_actionable(&self) -> bool {
+        self.signal != Signal::Hold && self.blockers.is_empty()
+    }
+
+    /// Conditions that did not pass
+    pub fn failed_factors(&self) -> impl Iterator<Item = &DecisionFactor> {
+        self.factors.iter().filter(|factor| !factor.passed)
+    }
+}
+
+impl From<Signal> for SignalDecision {
+    fn from(signal: Signal) -> Self {
+        Self::new(signal)
+    }
+}
+
+/// One line for logs: `BUY score +0.42 | rsi=25.00 (vs 30.00) ✓, ... | blocked: paused`
+impl fmt::Display for SignalDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} score {:+.2}", self.signal, self.score)?;
+        if !self.factors.is_empty() {
+            let side = self.side.map(|s| format!("{} ", s)).unwrap_or_default();
+            let factors: Vec<String> = self.factors.iter().map(ToString::to_string).collect();
+            write!(f, " | {}checks: {}", side.to_lowercase(), factors.join(", "))?;
+        }
+        if !self.blockers.is_empty() {
+            write!(f, " | blocked: {}", self.blockers.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_summary_and_blockers() {
+        let mut decision = SignalDecision::new(Signal::Buy)
+            .with_score(1.7)
+            .with_side(OrderSide::Buy)
+            .with_factor(DecisionFactor::new("rsi", Some(25.0), true).with_threshold(30.0))
+            .with_factor(DecisionFactor::new("trend", None, true).with_note("Up"));
+        assert_eq!(decision.score, 1.0);
+        assert!(decision.is_actionable());
+
+        decision.block("paused by operator");
+        assert!(!decision.is_actionable());
+        let line = decision.to_string();
+        assert!(line.starts_with("BUY score +1.00"), "{}", line);
+        assert!(line.contains("rsi=25.00 (vs 30.00) ✓"), "{}", line);
+        assert!(line.contains("trend=n/a [Up] ✓"), "{}", line);
+        assert!(line.ends_with("blocked: paused by operator"), "{}", line);
+    }
+
+    #[test]
+    fn test_serializes_for_events() {
+        let decision = SignalDecision::from(Signal::Hold)
+            .with_factor(DecisionFactor::new("sentiment", Some(10.0), false).with_threshold(30.0));
+        let json = serde_json::to_value(&decision).unwrap();
+        assert_eq!(json["signal"], "Hold");
+        assert_eq!(json["factors"][0]["name"], "sentiment");
+        assert_eq!(json["factors"][0]["passed"], false);
+        assert_eq!(decision.failed_factors().count(), 1);
+    }
+}
@@ -0,0 +1,308 @@
+//! Emergency and operator commands for the running bot
+//!
+//! The trading loop owns the cTrader connection, so operator panic actions
+//! (dashboard keystroke, control API, Telegram) are queued on an
+//! `EmergencyHandle` and executed by the bot between ticks. Callers that need
+//! the outcome pass a reply channel; the dashboard fires and forgets.
+
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot};
+
+use super::arming::ArmingStatus;
+use super::ctrader::CancelAllReport;
+use super::features::{StrategyFeature, StrategyFeatures};
+use super::orders::Position;
+use super::pending_orders::TrackedOrder;
+use crate::modules::scraper::SentimentResult;
+
+/// Queued commands are few and urgent; a full queue means one is already pending
+const EMERGENCY_QUEUE_CAPACITY: usize = 8;
+
+/// Reply to a cancel-all request (`Err` carries the error message)
+pub type CancelAllReply = oneshot::Sender<std::result::Result<CancelAllReport, String>>;
+
+/// Operator panic actions
+#[derive(Debug)]
+pub enum EmergencyCommand {
+    /// Cancel every pending order, and close every position when `flatten`
+    CancelAll {
+        flatten: bool,
+        reply: Option<CancelAllReply>,
+    },
+    /// Stop (`true`) or resume (`false`) opening new positions; exits keep running
+    SetPaused(bool),
+    /// Reply with the bot's status
+    Status {
+        reply: oneshot::Sender<StatusReport>,
+    },
+    /// Reply with the open positions
+    Positions {
+        reply: oneshot::Sender<Vec<Position>>,
+    },
+    /// Reply with the pending limit/stop orders
+    Orders {
+        reply: oneshot::Sender<Vec<TrackedOrder>>,
+    },
+    /// Close one position at market (`Err` carries the error message)
+    ClosePosition {
+        position_id: String,
+        reply: oneshot::Sender<std::result::Result<Position, String>>,
+    },
+    /// Drop the cached sentiment and fetch a fresh reading
+    RefreshSentiment {
+        reply: oneshot::Sender<SentimentResult>,
+    },
+    /// Re-read `.env` and apply the settings that can change at runtime;
+    /// replies with what was reloaded
+    Reload { reply: oneshot::Sender<String> },
+    /// Allow live orders for `ttl_minutes` (the configured default when
+    /// `None`); `Err` carries the error message
+    Arm {
+        ttl_minutes: Option<i64>,
+        reply: oneshot::Sender<std::result::Result<ArmingStatus, String>>,
+    },
+    /// Stop sending new live orders until armed again
+    Disarm {
+        reply: oneshot::Sender<ArmingStatus>,
+    },
+    /// Reply with the strategy feature flags
+    Features {
+        reply: oneshot::Sender<StrategyFeatures>,
+    },
+    /// Switch a strategy feature on or off; replies with the new flags
+    SetFeature {
+        feature: StrategyFeature,
+        enabled: bool,
+        reply: oneshot::Sender<StrategyFeatures>,
+    },
+}
+
+/// Bot status as returned to operators
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub symbol: String,
+    pub dry_run: bool,
+    pub standby: bool,
+    /// New entries paused by the operator
+    pub paused: bool,
+    pub uptime_secs: i64,
+    pub open_positions: usize,
+    pub daily_pnl: f64,
+    pub daily_trades: u32,
+    pub last_signal: String,
+    pub last_price: Option<f64>,
+    /// Seconds since the last price tick, None before the first one
+    pub feed_age_secs: Option<i64>,
+    /// Live-trading arming switch
+    pub arming: ArmingStatus,
+    /// Human-readable summary (the heartbeat message)
+    pub summary: String,
+}
+
+/// Cloneable sender side, handed to the dashboard and control API
+#[derive(Debug, Clone)]
+pub struct EmergencyHandle {
+    tx: mpsc::Sender<EmergencyCommand>,
+}
+
+impl EmergencyHandle {
+    /// Queue a cancel-all without waiting for the result (usable from sync code)
+    pub fn request_cancel_all(&self, flatten: bool) -> bool {
+        self.tx
+            .try_send(EmergencyCommand::CancelAll {
+                flatten,
+                reply: None,
+            })
+            .is_ok()
+    }
+
+    /// Queue a cancel-all and wait for the bot's report
+    pub async fn cancel_all(&self, flatten: bool) -> std::result::Result<CancelAllReport, String> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(EmergencyCommand::CancelAll {
+                flatten,
+                reply: Some(reply),
+            })
+            .await
+            .map_err(|_| "trading loop is not running".to_string())?;
+        rx.await
+            .map_err(|_| "trading loop dropped the request".to_string())?
+    }
+
+    /// Queue a pause / resume of new entries
+    pub fn request_pause(&self, paused: bool) -> bool {
+        self.tx
+            .try_send(EmergencyCommand::SetPaused(paused))
+            .is_ok()
+    }
+
+    /// Ask the bot for its status summary
+    pub async fn status(&self) -> std::result::Result<String, String> {
+        self.status_report().await.map(|report| report.summary)
+    }
+
+    /// Ask the bot for its full status
+    pub async fn status_report(&self) -> std::result::Result<StatusReport, String> {
+        self.request(|reply| EmergencyCommand::Status { reply })
+            .await
+    }
+
+    /// Ask the bot for its open positions
+    pub async fn positions(&self) -> std::result::Result<Vec<Position>, String> {
+        self.request(|reply| EmergencyCommand::Positions { reply })
+            .await
+    }
+
+    /// Ask the bot for its pending orders
+    pub async fn orders(&self) -> std::result::Result<Vec<TrackedOrder>, String> {
+        self.request(|reply| EmergencyCommand::Orders { reply })
+            .await
+    }
+
+    /// Close one position and wait for the result
+    pub async fn close_position(&self, position_id: &str) -> std::result::Result<Position, String> {
+        let position_id = position_id.to_string();
+        self.request(|reply| EmergencyCommand::ClosePosition { position_id, reply })
+            .await?
+    }
+
+    /// Force a sentiment refresh and wait for the new reading
+    pub async fn refresh_sentiment(&self) -> std::result::Result<SentimentResult, String> {
+        self.request(|reply| EmergencyCommand::RefreshSentiment { reply })
+            .await
+    }
+
+    /// Reload runtime settings and wait for the summary
+    pub async fn reload(&self) -> std::result::Result<String, String> {
+        self.request(|reply| EmergencyCommand::Reload { reply })
+            .await
+    }
+
+    /// Arm live trading and wait for the new state
+    pub async fn arm(&self, ttl_minutes: Option<i64>) -> std::result::Result<ArmingStatus, String> {
+        self.request(|reply| EmergencyCommand::Arm { ttl_minutes, reply })
+            .await?
+    }
+
+    /// Disarm live trading and wait for the new state
+    pub async fn disarm(&self) -> std::result::Result<ArmingStatus, String> {
+        self.request(|reply| EmergencyCommand::Disarm { reply })
+            .await
+    }
+
+    /// Ask the bot for its strategy feature flags
+    pub async fn features(&self) -> std::result::Result<StrategyFeatures, String> {
+        self.request(|reply| EmergencyCommand::Features { reply })
+            .await
+    }
+
+    /// Switch a strategy feature and wait for the new flags
+    pub async fn set_feature(
+        &self,
+        feature: StrategyFeature,
+        enabled: bool,
+    ) -> std::result::Result<StrategyFeatures, String> {
+        self.request(|reply| EmergencyCommand::SetFeature {
+            feature,
+            enabled,
+            reply,
+        })
+        .await
+    }
+
+    /// Queue a command carrying a reply channel and wait for the answer
+    async fn request<T>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<T>) -> EmergencyCommand,
+    ) -> std::result::Result<T, String> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(command(reply))
+            .await
+            .map_err(|_| "trading loop is not running".to_string())?;
+        rx.await
+            .map_err(|_| "trading loop dropped the request".to_string())
+    }
+}
+
+/// Create the handle and the receiver polled by the trading loop
+pub fn emergency_channel() -> (EmergencyHandle, mpsc::Receiver<EmergencyCommand>) {
+    let (tx, rx) = mpsc::channel(EMERGENCY_QUEUE_CAPACITY);
+    (EmergencyHandle { tx }, rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_all_round_trip() {
+        let (handle, mut rx) = emergency_channel();
+
+        let worker = tokio::spawn(async move {
+            match rx.recv().await {
+                Some(EmergencyCommand::CancelAll { flatten, reply }) => {
+                    assert!(flatten);
+                    let report = CancelAllReport {
+                        cancelled_orders: vec![1, 2],
+                        ..Default::default()
+                    };
+                    reply.unwrap().send(Ok(report)).unwrap();
+                }
+                _ => panic!("expected a cancel-all"),
+            }
+        });
+
+        let report = handle.cancel_all(true).await.unwrap();
+        assert_eq!(report.cancelled_orders, vec![1, 2]);
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_without_reply_and_closed_loop() {
+        let (handle, mut rx) = emergency_channel();
+        assert!(handle.request_cancel_all(false));
+        assert!(matches!(
+            rx.recv().await,
+            Some(EmergencyCommand::CancelAll {
+                flatten: false,
+                reply: None
+            })
+        ));
+
+        assert!(handle.request_pause(true));
+        assert!(matches!(
+            rx.recv().await,
+            Some(EmergencyCommand::SetPaused(true))
+        ));
+
+        drop(rx);
+        assert!(!handle.request_cancel_all(false));
+        assert!(!handle.request_pause(false));
+        assert!(handle.status().await.is_err());
+        assert!(handle.cancel_all(false).await.is_err());
+        assert!(handle.positions().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_close_position_round_trip() {
+        let (handle, mut rx) = emergency_channel();
+
+        let worker = tokio::spawn(async move {
+            match rx.recv().await {
+                Some(EmergencyCommand::ClosePosition { position_id, reply }) => {
+                    assert_eq!(position_id, "42");
+                    reply
+                        .send(Err("Position 42 not found".to_string()))
+                        .unwrap();
+                }
+                _ => panic!("expected a close"),
+            }
+        });
+
+        let err = handle.close_position("42").await.unwrap_err();
+        assert_eq!(err, "Position 42 not found");
+        worker.await.unwrap();
+    }
+}
@@ -4,15 +4,25 @@
 //! Provides publish/subscribe pattern for price updates, order fills, and system events.
 
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::decision::SignalDecision;
+use super::orders::OrderStatus;
+use super::position_reconciliation::ConnectionState;
 
 /// Unique identifier for subscribers
 pub type SubscriberId = u64;
 
 /// Market event types for real-time data pipeline
-#[derive(Debug, Clone)]
+///
+/// Serializes as JSON tagged with the variant name, e.g.
+/// `{"type":"PriceTick","symbol_id":1,...}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
 pub enum MarketEvent {
     /// Price tick update
     PriceTick {
@@ -35,6 +45,13 @@ pub enum MarketEvent {
         volume: f64,
         timestamp: DateTime<Utc>,
     },
+    /// Strategy decision for a closed candle, with its factors and blockers
+    SignalDecision {
+        symbol_id: i64,
+        symbol: String,
+        decision: SignalDecision,
+        timestamp: DateTime<Utc>,
+    },
     /// Order filled
     OrderFilled {
         order_id: i64,
@@ -50,6 +67,15 @@ pub enum MarketEvent {
         reason: String,
         timestamp: DateTime<Utc>,
     },
+    /// Order moved through its lifecycle (accepted, partially filled, ...)
+    OrderStatusChanged {
+        order_id: i64,
+        /// 0 until the order fills
+        position_id: i64,
+        symbol_id: i64,
+        status: OrderStatus,
+        timestamp: DateTime<Utc>,
+    },
     /// Position updated
     PositionUpdate {
         position_id: i64,
@@ -65,9 +91,15 @@ pub enum MarketEvent {
         close_reason: String,
         timestamp: DateTime<Utc>,
     },
-    /// Connection status
+    /// Connection status: every state change and reconnect attempt
     ConnectionStatus {
         connected: bool,
+        state: ConnectionState,
+        /// Reconnect attempt (1 = first) while reconnecting
+        attempt: Option<u32>,
+        /// Seconds spent in the previous state: the outage so far while
+        /// reconnecting, its full length once connected again
+        duration_secs: Option<f64>,
         message: String,
         timestamp: DateTime<Utc>,
     },
@@ -84,14 +116,14 @@ pub enum MarketEvent {
 }
 
 /// Order side for events
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
 /// Alert severity levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum AlertLevel {
     Info,
     Warning,
@@ -113,8 +145,10 @@ pub struct EventFilter {
 pub enum EventType {
     PriceTick,
     BarClosed,
+    SignalDecision,
     OrderFilled,
     OrderRejected,
+    OrderStatusChanged,
     PositionUpdate,
     PositionClosed,
     ConnectionStatus,
@@ -122,14 +156,38 @@ pub enum EventType {
     Heartbeat,
 }
 
+impl FromStr for EventType {
+    type Err = String;
+
+    /// Parse a variant name, case-insensitively (`PriceTick`, `price_tick`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().replace('_', "").to_lowercase().as_str() {
+            "pricetick" => Ok(EventType::PriceTick),
+            "barclosed" => Ok(EventType::BarClosed),
+            "signaldecision" => Ok(EventType::SignalDecision),
+            "orderfilled" => Ok(EventType::OrderFilled),
+            "orderrejected" => Ok(EventType::OrderRejected),
+            "orderstatuschanged" => Ok(EventType::OrderStatusChanged),
+            "positionupdate" => Ok(EventType::PositionUpdate),
+            "positionclosed" => Ok(EventType::PositionClosed),
+            "connectionstatus" => Ok(EventType::ConnectionStatus),
+            "alert" => Ok(EventType::Alert),
+            "heartbeat" => Ok(EventType::Heartbeat),
+            _ => Err(format!("unknown event type '{}'", s.trim())),
+        }
+    }
+}
+
 impl MarketEvent {
     /// Get the event type
     pub fn event_type(&self) -> EventType {
         match self {
             MarketEvent::PriceTick { .. } => EventType::PriceTick,
             MarketEvent::BarClosed { .. } => EventType::BarClosed,
+            MarketEvent::SignalDecision { .. } => EventType::SignalDecision,
             MarketEvent::OrderFilled { .. } => EventType::OrderFilled,
             MarketEvent::OrderRejected { .. } => EventType::OrderRejected,
+            MarketEvent::OrderStatusChanged { .. } => EventType::OrderStatusChanged,
             MarketEvent::PositionUpdate { .. } => EventType::PositionUpdate,
             MarketEvent::PositionClosed { .. } => EventType::PositionClosed,
             MarketEvent::ConnectionStatus { .. } => EventType::ConnectionStatus,
@@ -143,7 +201,9 @@ impl MarketEvent {
         match self {
             MarketEvent::PriceTick { symbol_id, .. } => Some(*symbol_id),
             MarketEvent::BarClosed { symbol_id, .. } => Some(*symbol_id),
+            MarketEvent::SignalDecision { symbol_id, .. } => Some(*symbol_id),
             MarketEvent::OrderFilled { symbol_id, .. } => Some(*symbol_id),
+            MarketEvent::OrderStatusChanged { symbol_id, .. } => Some(*symbol_id),
             MarketEvent::PositionUpdate { symbol_id, .. } => Some(*symbol_id),
             MarketEvent::PositionClosed { symbol_id, .. } => Some(*symbol_id),
             _ => None,
@@ -155,8 +215,10 @@ impl MarketEvent {
         match self {
             MarketEvent::PriceTick { timestamp, .. } => *timestamp,
             MarketEvent::BarClosed { timestamp, .. } => *timestamp,
+            MarketEvent::SignalDecision { timestamp, .. } => *timestamp,
             MarketEvent::OrderFilled { timestamp, .. } => *timestamp,
             MarketEvent::OrderRejected { timestamp, .. } => *timestamp,
+            MarketEvent::OrderStatusChanged { timestamp, .. } => *timestamp,
             MarketEvent::PositionUpdate { timestamp, .. } => *timestamp,
             MarketEvent::PositionClosed { timestamp, .. } => *timestamp,
             MarketEvent::ConnectionStatus { timestamp, .. } => *timestamp,
@@ -597,4 +659,40 @@ mod tests {
         assert_eq!(event.event_type(), EventType::BarClosed);
         assert_eq!(event.symbol_id(), Some(42));
     }
+
+    #[test]
+    fn test_event_json_and_type_names() {
+        let event = MarketEvent::OrderFilled {
+            order_id: 7,
+            symbol_id: 1,
+            side: OrderSide::Buy,
+            volume: 1.0,
+            price: 4850.0,
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "OrderFilled");
+        assert_eq!(json["side"], "Buy");
+        assert_eq!(json["price"], 4850.0);
+
+        assert_eq!("PriceTick".parse::<EventType>(), Ok(EventType::PriceTick));
+        assert_eq!("bar_closed".parse::<EventType>(), Ok(EventType::BarClosed));
+
+        let event = MarketEvent::OrderStatusChanged {
+            order_id: 7,
+            position_id: 0,
+            symbol_id: 1,
+            status: OrderStatus::PartiallyFilled,
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "OrderStatusChanged");
+        assert_eq!(json["status"], "PartiallyFilled");
+        assert_eq!(event.event_type(), EventType::OrderStatusChanged);
+        assert_eq!(
+            "order_status_changed".parse::<EventType>(),
+            Ok(EventType::OrderStatusChanged)
+        );
+        assert!("Trade".parse::<EventType>().is_err());
+    }
 }
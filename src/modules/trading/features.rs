@@ -0,0 +1,203 @@
+//! Strategy feature flags and kill switches
+//!
+//! The entry filters of the core strategy can be switched off one by one,
+//! to run an experiment or to cut a component that misbehaves:
+//! - `trend_filter`: the EMA(50) trend must agree with the entry
+//! - `sentiment_gate`: sentiment must be beyond `SENTIMENT_THRESHOLD`
+//! - `macd_confirmation`: the MACD histogram must turn (with `MACD_CONFIRMATION`)
+//! - `higher_timeframe`: higher-timeframe RSI bounds (with `HIGHER_TIMEFRAME`)
+//! - `session_filter`: no entries while the market calendar says closed
+//! - `news_blackout`: no entries during report blackouts
+//!
+//! Everything is on by default; `STRATEGY_FEATURES_DISABLED` lists the
+//! features to start without. The admin console (`feature <name> on|off`) and
+//! the control API (`POST /control/features/<name>?enabled=false`) flip them
+//! while the bot runs. A flag only switches a component off: one that is not
+//! configured stays inactive whatever its flag says.
+//!
+//! Each position is stamped with the active set (`StrategyFeatures::tag`), so
+//! the trades of different experiments can be told apart afterwards.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::error::{BotError, Result};
+
+/// A strategy component that can be switched off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StrategyFeature {
+    TrendFilter,
+    SentimentGate,
+    MacdConfirmation,
+    HigherTimeframe,
+    SessionFilter,
+    NewsBlackout,
+}
+
+impl StrategyFeature {
+    pub const ALL: [StrategyFeature; 6] = [
+        StrategyFeature::TrendFilter,
+        StrategyFeature::SentimentGate,
+        StrategyFeature::MacdConfirmation,
+        StrategyFeature::HigherTimeframe,
+        StrategyFeature::SessionFilter,
+        StrategyFeature::NewsBlackout,
+    ];
+
+    /// Name used in config, commands and trade tags
+    pub fn name(self) -> &'static str {
+        match self {
+            StrategyFeature::TrendFilter => "trend_filter",
+            StrategyFeature::SentimentGate => "sentiment_gate",
+            StrategyFeature::MacdConfirmation => "macd_confirmation",
+            StrategyFeature::HigherTimeframe => "higher_timeframe",
+            StrategyFeature::SessionFilter => "session_filter",
+            StrategyFeature::NewsBlackout => "news_blackout",
+        }
+    }
+}
+
+impl fmt::Display for StrategyFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for StrategyFeature {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase().replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.name() == name)
+            .ok_or_else(|| {
+                BotError::Config(format!(
+                    "unknown strategy feature {:?} (expected one of: {})",
+                    s,
+                    Self::ALL.map(StrategyFeature::name).join(", ")
+                ))
+            })
+    }
+}
+
+/// Which strategy features are switched on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StrategyFeatures {
+    disabled: BTreeSet<StrategyFeature>,
+}
+
+impl StrategyFeatures {
+    /// All features on except those listed in `STRATEGY_FEATURES_DISABLED`
+    /// (comma-separated)
+    pub fn from_env() -> Result<Self> {
+        match env::var("STRATEGY_FEATURES_DISABLED") {
+            Ok(raw) => Self::with_disabled(&raw),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// All features on except those in the comma-separated `list`
+    pub fn with_disabled(list: &str) -> Result<Self> {
+        let disabled = list
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_>>()?;
+        Ok(Self { disabled })
+    }
+
+    pub fn is_enabled(&self, feature: StrategyFeature) -> bool {
+        !self.disabled.contains(&feature)
+    }
+
+    /// Switch a feature on or off; returns whether anything changed
+    pub fn set(&mut self, feature: StrategyFeature, enabled: bool) -> bool {
+        if enabled {
+            self.disabled.remove(&feature)
+        } else {
+            self.disabled.insert(feature)
+        }
+    }
+
+    /// Features currently switched off
+    pub fn disabled(&self) -> impl Iterator<Item = StrategyFeature> + '_ {
+        self.disabled.iter().copied()
+    }
+
+    /// Short label of the active set, recorded on each trade: `all`, or the
+    /// switched-off features such as `-trend_filter,-sentiment_gate`
+    pub fn tag(&self) -> String {
+        if self.disabled.is_empty() {
+            return "all".to_string();
+        }
+        self.disabled
+            .iter()
+            .map(|feature| format!("-{}", feature))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl fmt::Display for StrategyFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, feature) in StrategyFeature::ALL.into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            let state = if self.is_enabled(feature) {
+                "on"
+            } else {
+                "off"
+            };
+            write!(f, "{} {}", feature, state)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as `{"trend_filter": true, "sentiment_gate": false, ...}`
+impl Serialize for StrategyFeatures {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(StrategyFeature::ALL.len()))?;
+        for feature in StrategyFeature::ALL {
+            map.serialize_entry(feature.name(), &self.is_enabled(feature))?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_list_parsing_and_tag() {
+        let features = StrategyFeatures::with_disabled(" Sentiment-Gate, trend_filter,,").unwrap();
+        assert!(!features.is_enabled(StrategyFeature::TrendFilter));
+        assert!(!features.is_enabled(StrategyFeature::SentimentGate));
+        assert!(features.is_enabled(StrategyFeature::SessionFilter));
+        assert_eq!(features.tag(), "-trend_filter,-sentiment_gate");
+
+        assert_eq!(StrategyFeatures::with_disabled("").unwrap().tag(), "all");
+        assert!(StrategyFeatures::with_disabled("divergence").is_err());
+    }
+
+    #[test]
+    fn test_toggle_and_serialize() {
+        let mut features = StrategyFeatures::default();
+        assert!(features.set(StrategyFeature::NewsBlackout, false));
+        assert!(!features.set(StrategyFeature::NewsBlackout, false));
+
+        let json = serde_json::to_value(&features).unwrap();
+        assert_eq!(json["news_blackout"], false);
+        assert_eq!(json["trend_filter"], true);
+
+        assert!(features.set(StrategyFeature::NewsBlackout, true));
+        assert_eq!(features.tag(), "all");
+    }
+}
@@ -0,0 +1,250 @@
+//! Inbound cTrader frame and payload decoding
+//!
+//! Every message on the wire is a `ProtoMessage` preceded by its length as a
+//! 4-byte big-endian integer, and the envelope's payload is decoded again by
+//! payload type. The reader task feeds whatever the socket delivers through
+//! these functions, so none of them may panic on malformed input:
+//! - a length prefix above [`MAX_FRAME_LEN`] means the stream is out of sync;
+//!   the reader reconnects instead of allocating it
+//! - an envelope that does not decode is skipped, but after
+//!   [`MAX_CONSECUTIVE_CORRUPT`] in a row the framing itself is suspect and
+//!   the reader reconnects to resynchronize
+//! - a payload that does not match its declared type is still forwarded, so
+//!   a request waiting for it fails instead of timing out
+//!
+//! `fuzz/fuzz_targets/frame_decode.rs` runs arbitrary bytes through the same
+//! path (`cargo +nightly fuzz run frame_decode`).
+//!
+//! Corrupt frames and the reconnects they force are counted process-wide
+//! ([`frame_counters`]) and exported as `ctrader_corrupt_frames_total` and
+//! `ctrader_stream_resyncs_total`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use prost::Message;
+use thiserror::Error;
+
+use super::protobuf::*;
+
+/// Largest frame the reader accepts
+pub const MAX_FRAME_LEN: usize = 1_000_000;
+
+/// Undecodable frames in a row before the reader reconnects
+pub const MAX_CONSECUTIVE_CORRUPT: u32 = 3;
+
+static OVERSIZED_FRAMES: AtomicU64 = AtomicU64::new(0);
+static UNDECODABLE_FRAMES: AtomicU64 = AtomicU64::new(0);
+static STREAM_RESYNCS: AtomicU64 = AtomicU64::new(0);
+
+/// Why a frame was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FrameError {
+    #[error("frame of {0} bytes exceeds the {MAX_FRAME_LEN}-byte limit")]
+    TooLarge(usize),
+
+    #[error("undecodable message: {0}")]
+    Decode(#[from] prost::DecodeError),
+}
+
+/// Body length announced by a length prefix
+pub fn frame_len(prefix: [u8; 4]) -> Result<usize, FrameError> {
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(FrameError::TooLarge(len));
+    }
+    Ok(len)
+}
+
+/// Decode a frame body into its envelope
+pub fn decode_frame(body: &[u8]) -> Result<ProtoMessage, FrameError> {
+    Ok(ProtoMessage::decode(body)?)
+}
+
+/// Split the first complete frame off `buf`: the envelope and the bytes it
+/// used, or `Ok(None)` while the frame is incomplete
+pub fn split_frame(buf: &[u8]) -> Result<Option<(ProtoMessage, usize)>, FrameError> {
+    let Some(prefix) = buf.first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let len = frame_len(*prefix)?;
+    let Some(body) = buf.get(4..4 + len) else {
+        return Ok(None);
+    };
+    Ok(Some((decode_frame(body)?, 4 + len)))
+}
+
+/// Process-wide corrupt frame counts since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameCounters {
+    /// Length prefixes above `MAX_FRAME_LEN`
+    pub oversized: u64,
+    /// Frames whose envelope did not decode
+    pub undecodable: u64,
+    /// Reconnects forced to resynchronize the stream
+    pub resyncs: u64,
+}
+
+pub fn frame_counters() -> FrameCounters {
+    FrameCounters {
+        oversized: OVERSIZED_FRAMES.load(Ordering::Relaxed),
+        undecodable: UNDECODABLE_FRAMES.load(Ordering::Relaxed),
+        resyncs: STREAM_RESYNCS.load(Ordering::Relaxed),
+    }
+}
+
+/// Counts corrupt frames on one connection and decides when the framing can
+/// no longer be trusted
+#[derive(Debug, Default)]
+pub struct CorruptFrameTracker {
+    consecutive: u32,
+}
+
+impl CorruptFrameTracker {
+    /// A frame decoded; earlier corruption was isolated
+    pub fn frame_ok(&mut self) {
+        self.consecutive = 0;
+    }
+
+    /// Record a corrupt frame; `true` when the reader should reconnect to
+    /// resynchronize instead of skipping it
+    pub fn corrupt(&mut self, err: &FrameError) -> bool {
+        let resync = match err {
+            FrameError::TooLarge(_) => {
+                OVERSIZED_FRAMES.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            FrameError::Decode(_) => {
+                UNDECODABLE_FRAMES.fetch_add(1, Ordering::Relaxed);
+                self.consecutive += 1;
+                self.consecutive >= MAX_CONSECUTIVE_CORRUPT
+            }
+        };
+        if resync {
+            self.consecutive = 0;
+            STREAM_RESYNCS.fetch_add(1, Ordering::Relaxed);
+        }
+        resync
+    }
+}
+
+/// A decoded inbound message, sorted the way the reader task handles it.
+/// Typed payloads are `None` when the bytes do not match the declared type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inbound {
+    Spot(Option<ProtoOaSpotEvent>),
+    Execution(ProtoMessage, Option<ProtoOaExecutionEvent>),
+    Error(ProtoMessage, Option<ProtoOaErrorRes>),
+    OrderError(ProtoMessage, Option<ProtoOaOrderErrorEvent>),
+    Heartbeat,
+    /// Another Open API message, for a waiting request
+    Response(ProtoMessage),
+    /// Payload type this client does not know
+    Unknown(ProtoMessage),
+}
+
+/// Decode the payload of `message` according to its payload type
+pub fn decode_inbound(message: ProtoMessage) -> Inbound {
+    fn payload<T: Message + Default>(message: &ProtoMessage) -> Option<T> {
+        T::decode(message.payload.as_deref()?).ok()
+    }
+
+    let payload_type = message.payload_type;
+    match payload_type_from_u32(payload_type) {
+        Some(ProtoOaPayloadType::ProtoOaSpotEvent) => Inbound::Spot(payload(&message)),
+        Some(ProtoOaPayloadType::ProtoOaExecutionEvent) => {
+            let exec = payload(&message);
+            Inbound::Execution(message, exec)
+        }
+        Some(ProtoOaPayloadType::ProtoOaErrorRes) => {
+            let err = payload(&message);
+            Inbound::Error(message, err)
+        }
+        Some(ProtoOaPayloadType::ProtoOaOrderErrorEvent) => {
+            let err = payload(&message);
+            Inbound::OrderError(message, err)
+        }
+        Some(_) => Inbound::Response(message),
+        None if payload_type == ProtoPayloadType::HeartbeatEvent as i32 as u32 => {
+            Inbound::Heartbeat
+        }
+        None => Inbound::Unknown(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_frame_round_trip() {
+        let spot = ProtoOaSpotEvent {
+            ctid_trader_account_id: 1,
+            symbol_id: 42,
+            bid: Some(485_000_000),
+            ..Default::default()
+        };
+        let mut buf = encode_with_length(&new_proto_message(
+            ProtoOaPayloadType::ProtoOaSpotEvent,
+            spot.clone(),
+        ));
+        let frame_end = buf.len();
+        buf.extend_from_slice(&[0, 0]);
+
+        assert_eq!(split_frame(&buf[..frame_end - 1]).unwrap(), None);
+        let (message, used) = split_frame(&buf).unwrap().unwrap();
+        assert_eq!(used, frame_end);
+        assert_eq!(decode_inbound(message), Inbound::Spot(Some(spot)));
+    }
+
+    #[test]
+    fn test_malformed_frames_are_errors() {
+        assert_eq!(
+            frame_len([0xff, 0xff, 0xff, 0xff]),
+            Err(FrameError::TooLarge(u32::MAX as usize))
+        );
+        assert!(split_frame(&[0xff, 0xff, 0xff, 0xff, 0]).is_err());
+        // Announces 3 bytes of an unterminated varint
+        assert!(split_frame(&[0, 0, 0, 3, 0x08, 0xff, 0xff]).is_err());
+        assert_eq!(split_frame(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_repeated_corruption_forces_resync() {
+        let undecodable = decode_frame(&[0x08, 0xff]).unwrap_err();
+        let before = frame_counters();
+        let mut tracker = CorruptFrameTracker::default();
+
+        assert!(!tracker.corrupt(&undecodable));
+        assert!(!tracker.corrupt(&undecodable));
+        tracker.frame_ok();
+        assert!(!tracker.corrupt(&undecodable));
+        assert!(!tracker.corrupt(&undecodable));
+        assert!(tracker.corrupt(&undecodable));
+        // Counting starts over on the new connection
+        assert!(!tracker.corrupt(&undecodable));
+        assert!(tracker.corrupt(&FrameError::TooLarge(MAX_FRAME_LEN + 1)));
+
+        // Other tests may count concurrently
+        let after = frame_counters();
+        assert!(after.undecodable - before.undecodable >= 6);
+        assert!(after.oversized - before.oversized >= 1);
+        assert!(after.resyncs - before.resyncs >= 2);
+    }
+
+    #[test]
+    fn test_mismatched_payload_is_still_forwarded() {
+        let message = ProtoMessage {
+            payload_type: ProtoOaPayloadType::ProtoOaErrorRes as i32 as u32,
+            payload: Some(vec![0xff; 8]),
+            client_msg_id: None,
+        };
+        assert!(matches!(decode_inbound(message), Inbound::Error(_, None)));
+
+        let heartbeat = ProtoMessage {
+            payload_type: ProtoPayloadType::HeartbeatEvent as i32 as u32,
+            payload: None,
+            client_msg_id: None,
+        };
+        assert_eq!(decode_inbound(heartbeat), Inbound::Heartbeat);
+    }
+}
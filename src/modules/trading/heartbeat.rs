@@ -0,0 +1,204 @@
+//! cTrader connection heartbeats
+//!
+//! The Open API server drops a connection that stays quiet, so the client
+//! sends a heartbeat every `CTRADER_HEARTBEAT_SECS` (default 25). The reader
+//! task sends it between reads, on the connection it is reading: heartbeats
+//! stop with that connection and start again on the next one after a
+//! reconnect, instead of coming from a separate loop that outlives it.
+//!
+//! The server sends heartbeats as well. When nothing at all arrives for
+//! `CTRADER_HEARTBEAT_MISS_LIMIT` intervals (default 3), the connection is
+//! treated as dead and the reader reconnects instead of waiting for TCP to
+//! notice. A limit of 0 disables the check.
+
+use std::env;
+use std::time::{Duration, Instant};
+
+use prost::Message;
+
+use super::protobuf::{encode_with_length, ProtoHeartbeatEvent, ProtoMessage, ProtoPayloadType};
+
+const DEFAULT_INTERVAL_SECS: u64 = 25;
+const DEFAULT_MISS_LIMIT: u32 = 3;
+
+/// Shortest wait the reader is given, so an overdue heartbeat cannot make it spin
+const MIN_READ_WAIT: Duration = Duration::from_millis(50);
+
+/// Heartbeat interval and server silence limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// Time between client heartbeats
+    pub interval: Duration,
+    /// Intervals without any inbound message before the connection is
+    /// considered dead (0 = never)
+    pub miss_limit: u32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(DEFAULT_INTERVAL_SECS),
+            miss_limit: DEFAULT_MISS_LIMIT,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    /// Load from `CTRADER_HEARTBEAT_SECS` and `CTRADER_HEARTBEAT_MISS_LIMIT`
+    pub fn from_env() -> Self {
+        let interval = env::var("CTRADER_HEARTBEAT_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+        let miss_limit = env::var("CTRADER_HEARTBEAT_MISS_LIMIT")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .unwrap_or(DEFAULT_MISS_LIMIT);
+        Self {
+            interval: Duration::from_secs(interval),
+            miss_limit,
+        }
+    }
+
+    /// Longest the server may stay silent, if limited
+    pub fn silence_limit(&self) -> Option<Duration> {
+        (self.miss_limit > 0).then(|| self.interval * self.miss_limit)
+    }
+}
+
+/// Heartbeat timing for one connection
+#[derive(Debug, Clone)]
+pub struct HeartbeatMonitor {
+    config: HeartbeatConfig,
+    last_sent: Instant,
+    last_received: Instant,
+}
+
+impl HeartbeatMonitor {
+    pub fn new(config: HeartbeatConfig, now: Instant) -> Self {
+        Self {
+            config,
+            last_sent: now,
+            last_received: now,
+        }
+    }
+
+    pub fn config(&self) -> HeartbeatConfig {
+        self.config
+    }
+
+    /// Start over on a new connection
+    pub fn reset(&mut self, now: Instant) {
+        self.last_sent = now;
+        self.last_received = now;
+    }
+
+    /// Whether a heartbeat should be sent
+    pub fn heartbeat_due(&self, now: Instant) -> bool {
+        now.duration_since(self.last_sent) >= self.config.interval
+    }
+
+    pub fn sent(&mut self, now: Instant) {
+        self.last_sent = now;
+    }
+
+    /// Anything arrived from the server
+    pub fn received(&mut self, now: Instant) {
+        self.last_received = now;
+    }
+
+    /// How long the server has been silent, once that is past the limit
+    pub fn server_silence(&self, now: Instant) -> Option<Duration> {
+        let silence = now.duration_since(self.last_received);
+        let limit = self.config.silence_limit()?;
+        (silence >= limit).then_some(silence)
+    }
+
+    /// How long the next read may wait before a heartbeat is due or the
+    /// silence limit passes
+    pub fn read_timeout(&self, now: Instant) -> Duration {
+        let until_heartbeat = self
+            .config
+            .interval
+            .saturating_sub(now.duration_since(self.last_sent));
+        let wait = match self.config.silence_limit() {
+            Some(limit) => {
+                until_heartbeat.min(limit.saturating_sub(now.duration_since(self.last_received)))
+            }
+            None => until_heartbeat,
+        };
+        wait.max(MIN_READ_WAIT)
+    }
+}
+
+/// A length-prefixed heartbeat frame
+pub fn heartbeat_frame() -> Vec<u8> {
+    let heartbeat = ProtoMessage {
+        payload_type: ProtoPayloadType::HeartbeatEvent as i32 as u32,
+        payload: Some(ProtoHeartbeatEvent { payload_type: None }.encode_to_vec()),
+        client_msg_id: None,
+    };
+    encode_with_length(&heartbeat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::frame::{decode_inbound, split_frame, Inbound};
+
+    fn config(interval_secs: u64, miss_limit: u32) -> HeartbeatConfig {
+        HeartbeatConfig {
+            interval: Duration::from_secs(interval_secs),
+            miss_limit,
+        }
+    }
+
+    #[test]
+    fn test_heartbeat_schedule() {
+        let start = Instant::now();
+        let mut monitor = HeartbeatMonitor::new(config(25, 3), start);
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(!monitor.heartbeat_due(at(24)));
+        assert_eq!(monitor.read_timeout(at(10)), Duration::from_secs(15));
+        assert!(monitor.heartbeat_due(at(25)));
+        monitor.sent(at(25));
+        assert!(!monitor.heartbeat_due(at(49)));
+        assert_eq!(monitor.read_timeout(at(25)), Duration::from_secs(25));
+        // Overdue: wait briefly instead of not at all
+        assert_eq!(monitor.read_timeout(at(60)), MIN_READ_WAIT);
+    }
+
+    #[test]
+    fn test_missed_server_heartbeats_end_the_connection() {
+        let start = Instant::now();
+        let mut monitor = HeartbeatMonitor::new(config(25, 3), start);
+        let at = |secs| start + Duration::from_secs(secs);
+
+        monitor.sent(at(70));
+        assert_eq!(monitor.server_silence(at(74)), None);
+        // The silence deadline comes before the next heartbeat
+        assert_eq!(monitor.read_timeout(at(70)), Duration::from_secs(5));
+        assert_eq!(
+            monitor.server_silence(at(75)),
+            Some(Duration::from_secs(75))
+        );
+
+        monitor.received(at(75));
+        assert_eq!(monitor.server_silence(at(100)), None);
+        monitor.reset(at(200));
+        assert_eq!(monitor.server_silence(at(270)), None);
+
+        let unlimited = HeartbeatMonitor::new(config(25, 0), start);
+        assert_eq!(unlimited.server_silence(at(3_600)), None);
+    }
+
+    #[test]
+    fn test_heartbeat_frame_decodes() {
+        let frame = heartbeat_frame();
+        let (message, used) = split_frame(&frame).unwrap().unwrap();
+        assert_eq!(used, frame.len());
+        assert_eq!(decode_inbound(message), Inbound::Heartbeat);
+    }
+}
@@ -1,6 +1,6 @@
 //! Technical indicators module
 //!
-//! Provides technical indicators: RSI, EMA, MACD, Bollinger Bands, ATR
+//! Provides technical indicators: RSI, EMA, MACD, Bollinger Bands, ATR, ADX
 
 use std::collections::VecDeque;
 use tracing::debug;
@@ -287,7 +287,7 @@ impl Trend {
 }
 
 /// MACD values
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MacdValues {
     pub macd_line: f64,
     pub signal_line: f64,
@@ -295,21 +295,31 @@ pub struct MacdValues {
 }
 
 /// MACD (Moving Average Convergence Divergence) calculator
+///
+/// MACD line = EMA(fast) - EMA(slow), signal line = EMA(signal) of the MACD
+/// line, histogram = MACD line - signal line. Fed one close per candle.
+#[derive(Debug)]
 pub struct MacdCalculator {
     fast_ema: EmaCalculator,
     slow_ema: EmaCalculator,
     signal_ema: EmaCalculator,
+    current: Option<MacdValues>,
+    previous: Option<MacdValues>,
 }
 
 impl MacdCalculator {
+    /// Create a new MACD calculator with the given EMA periods
     pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
         Self {
             fast_ema: EmaCalculator::new(fast),
             slow_ema: EmaCalculator::new(slow),
             signal_ema: EmaCalculator::new(signal),
+            current: None,
+            previous: None,
         }
     }
 
+    /// Add a candle close and calculate MACD
     pub fn update(&mut self, price: f64) -> Option<MacdValues> {
         let fast = self.fast_ema.update(price);
         let slow = self.slow_ema.update(price);
@@ -321,11 +331,61 @@ impl MacdCalculator {
         let signal_line = self.signal_ema.update(macd_line)?;
         let histogram = macd_line - signal_line;
 
-        Some(MacdValues {
+        let values = MacdValues {
             macd_line,
             signal_line,
             histogram,
-        })
+        };
+        self.previous = self.current.replace(values);
+        debug!(
+            "MACD update: line={:.4}, signal={:.4}, histogram={:.4}",
+            macd_line, signal_line, histogram
+        );
+        Some(values)
+    }
+
+    /// Get the latest MACD values without adding a new price
+    pub fn current(&self) -> Option<MacdValues> {
+        self.current
+    }
+
+    /// Check if MACD, signal line and histogram are available
+    pub fn is_ready(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Histogram grew on the last candle (bearish momentum fading or bullish
+    /// momentum building). False until two histogram values exist.
+    pub fn histogram_rising(&self) -> bool {
+        match (self.previous, self.current) {
+            (Some(prev), Some(cur)) => cur.histogram > prev.histogram,
+            _ => false,
+        }
+    }
+
+    /// Histogram shrank on the last candle. False until two histogram values
+    /// exist.
+    pub fn histogram_falling(&self) -> bool {
+        match (self.previous, self.current) {
+            (Some(prev), Some(cur)) => cur.histogram < prev.histogram,
+            _ => false,
+        }
+    }
+
+    /// Reset the calculator
+    pub fn reset(&mut self) {
+        self.fast_ema.reset();
+        self.slow_ema.reset();
+        self.signal_ema.reset();
+        self.current = None;
+        self.previous = None;
+    }
+}
+
+impl Default for MacdCalculator {
+    /// Standard 12/26/9 MACD
+    fn default() -> Self {
+        Self::new(12, 26, 9)
     }
 }
 
@@ -387,12 +447,17 @@ impl BollingerBands {
 }
 
 /// ATR (Average True Range) calculator
+///
+/// True range = max(high - low, |high - prev close|, |low - prev close|),
+/// smoothed with an EMA. Fed one candle at a time.
+#[derive(Debug)]
 pub struct AtrCalculator {
     prev_close: Option<f64>,
     atr_ema: EmaCalculator,
 }
 
 impl AtrCalculator {
+    /// Create a new ATR calculator with the specified period
     pub fn new(period: usize) -> Self {
         Self {
             prev_close: None,
@@ -400,6 +465,7 @@ impl AtrCalculator {
         }
     }
 
+    /// Add a candle and calculate ATR
     pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<f64> {
         let true_range = if let Some(prev) = self.prev_close {
             (high - low)
@@ -413,9 +479,138 @@ impl AtrCalculator {
         self.atr_ema.update(true_range)
     }
 
+    /// Get current ATR value
     pub fn current(&self) -> Option<f64> {
         self.atr_ema.current()
     }
+
+    /// Check if ATR is ready
+    pub fn is_ready(&self) -> bool {
+        self.atr_ema.is_ready()
+    }
+
+    /// Reset the calculator
+    pub fn reset(&mut self) {
+        self.prev_close = None;
+        self.atr_ema.reset();
+    }
+}
+
+/// ADX output: trend strength and the two directional indicators (0-100)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxValues {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+/// ADX (Average Directional Index) calculator
+///
+/// Wilder's smoothing of true range and directional movement gives +DI and
+/// -DI; ADX is the smoothed DX = |+DI - -DI| / (+DI + -DI). Fed one candle at
+/// a time, ready after `2 * period` candles.
+#[derive(Debug)]
+pub struct AdxCalculator {
+    period: usize,
+    /// (high, low, close) of the previous candle
+    prev: Option<(f64, f64, f64)>,
+    /// Smoothed true range, +DM and -DM (plain sums during the first period)
+    smoothed: (f64, f64, f64),
+    movements: usize,
+    /// DX values summed to seed the first ADX
+    dx_sum: f64,
+    dx_count: usize,
+    current: Option<AdxValues>,
+}
+
+impl AdxCalculator {
+    /// Create a new ADX calculator with the specified period
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            prev: None,
+            smoothed: (0.0, 0.0, 0.0),
+            movements: 0,
+            dx_sum: 0.0,
+            dx_count: 0,
+            current: None,
+        }
+    }
+
+    /// Add a candle and calculate ADX
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<AdxValues> {
+        let (prev_high, prev_low, prev_close) = self.prev.replace((high, low, close))?;
+
+        let true_range = (high - low)
+            .max((high - prev_close).abs())
+            .max((low - prev_close).abs());
+        let up = high - prev_high;
+        let down = prev_low - low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+
+        let period = self.period as f64;
+        let (tr, plus, minus) = &mut self.smoothed;
+        self.movements += 1;
+        if self.movements <= self.period {
+            *tr += true_range;
+            *plus += plus_dm;
+            *minus += minus_dm;
+            if self.movements < self.period {
+                return None;
+            }
+        } else {
+            *tr += true_range - *tr / period;
+            *plus += plus_dm - *plus / period;
+            *minus += minus_dm - *minus / period;
+        }
+
+        let (tr, plus, minus) = self.smoothed;
+        let (plus_di, minus_di) = if tr > 0.0 {
+            (100.0 * plus / tr, 100.0 * minus / tr)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+
+        let adx = match self.current {
+            Some(values) => (values.adx * (period - 1.0) + dx) / period,
+            None => {
+                self.dx_sum += dx;
+                self.dx_count += 1;
+                if self.dx_count < self.period {
+                    return None;
+                }
+                self.dx_sum / period
+            }
+        };
+        self.current = Some(AdxValues {
+            adx,
+            plus_di,
+            minus_di,
+        });
+        self.current
+    }
+
+    /// Get current ADX values
+    pub fn current(&self) -> Option<AdxValues> {
+        self.current
+    }
+
+    /// Check if ADX is ready
+    pub fn is_ready(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Reset the calculator
+    pub fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
 }
 
 #[cfg(test)]
@@ -541,6 +736,65 @@ mod tests {
         assert!(values.histogram.abs() < 10.0);
     }
 
+    #[test]
+    fn test_macd_ready_after_slow_plus_signal_periods() {
+        let mut macd = MacdCalculator::new(3, 5, 2);
+        // Slow EMA needs 5 closes, then the signal EMA needs 2 MACD values
+        for i in 0..5 {
+            assert!(macd.update(100.0 + i as f64).is_none());
+        }
+        assert!(!macd.is_ready());
+        let values = macd.update(105.0).unwrap();
+        assert!(macd.is_ready());
+        assert_eq!(macd.current(), Some(values));
+        assert!((values.histogram - (values.macd_line - values.signal_line)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_macd_flat_prices_are_zero() {
+        let mut macd = MacdCalculator::default();
+        let mut last = None;
+        for _ in 0..40 {
+            last = macd.update(100.0);
+        }
+        let values = last.unwrap();
+        assert!(values.macd_line.abs() < 1e-9);
+        assert!(values.signal_line.abs() < 1e-9);
+        assert!(values.histogram.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_macd_trend_direction() {
+        let mut up = MacdCalculator::new(3, 6, 3);
+        let mut down = MacdCalculator::new(3, 6, 3);
+        for i in 0..30 {
+            up.update(100.0 + i as f64);
+            down.update(100.0 - i as f64);
+        }
+        assert!(up.current().unwrap().macd_line > 0.0);
+        assert!(down.current().unwrap().macd_line < 0.0);
+    }
+
+    #[test]
+    fn test_macd_histogram_turns_after_reversal() {
+        let mut macd = MacdCalculator::new(3, 6, 3);
+        for i in 0..20 {
+            macd.update(100.0 - i as f64);
+        }
+        // Accelerating decline keeps the histogram falling
+        macd.update(75.0);
+        assert!(macd.histogram_falling());
+        assert!(!macd.histogram_rising());
+
+        // Sharp rebound turns it up
+        macd.update(90.0);
+        assert!(macd.histogram_rising());
+
+        macd.reset();
+        assert!(!macd.is_ready());
+        assert!(!macd.histogram_rising());
+    }
+
     #[test]
     fn test_bollinger_bands() {
         let mut bb = BollingerBands::new(20, 2.0);
@@ -564,4 +818,45 @@ mod tests {
         let atr_value = result.unwrap();
         assert!(atr_value > 0.0 && atr_value < 10.0);
     }
+
+    #[test]
+    fn test_atr_true_range_includes_gaps() {
+        let mut atr = AtrCalculator::new(2);
+        assert!(atr.update(102.0, 98.0, 101.0).is_none());
+        assert!(!atr.is_ready());
+
+        // Gap up: range 2, but high is 9 above the previous close
+        let value = atr.update(110.0, 108.0, 109.0).unwrap();
+        assert!(atr.is_ready());
+        assert!((value - (4.0 + 9.0) / 2.0).abs() < 1e-9);
+        assert_eq!(atr.current(), Some(value));
+
+        atr.reset();
+        assert!(atr.current().is_none());
+    }
+
+    #[test]
+    fn test_adx_strong_in_trends_weak_in_chop() {
+        let mut adx = AdxCalculator::new(5);
+        let mut result = None;
+        for i in 0..10 {
+            let base = 100.0 + 2.0 * i as f64;
+            result = adx.update(base + 1.0, base - 1.0, base + 0.5);
+            assert_eq!(result.is_some(), i == 9);
+        }
+        // Every candle makes a higher high and a higher low
+        let trend = result.unwrap();
+        assert!(trend.adx > 90.0);
+        assert!(trend.plus_di > trend.minus_di);
+        assert!(trend.minus_di.abs() < 1e-9);
+
+        adx.reset();
+        assert!(!adx.is_ready());
+        let mut chop = None;
+        for i in 0..20 {
+            let base = if i % 2 == 0 { 100.0 } else { 102.0 };
+            chop = adx.update(base + 1.0, base - 1.0, base);
+        }
+        assert!(chop.unwrap().adx < 20.0);
+    }
 }
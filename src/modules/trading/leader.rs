@@ -0,0 +1,302 @@
+//! Leader election for hot-standby deployments
+//!
+//! Two bots pointed at the same account and the same SQLite database
+//! (`PERSISTENCE_DB_PATH` on shared storage) compete for a lease row. The
+//! holder renews it every `LEADER_HEARTBEAT_SECS`; when its heartbeat is older
+//! than `LEADER_LEASE_TTL_SECS` the standby takes the lease and starts trading.
+//! A renewal is trusted for the TTL minus one heartbeat: past that the leader
+//! refuses new orders and steps down, before the standby may take the lease.
+//! This shrinks the overlap but cannot rule it out: an order already in flight
+//! still reaches the broker, and clock skew between the nodes shifts the window.
+
+use chrono::{DateTime, Utc};
+use std::env;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::modules::trading::PositionDatabase;
+
+/// Role of this node in the pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderRole {
+    Leader,
+    Standby,
+}
+
+/// Role change produced by a heartbeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderTransition {
+    Unchanged(LeaderRole),
+    Promoted,
+    Demoted,
+}
+
+/// Lease settings
+#[derive(Debug, Clone)]
+pub struct LeaderElectionConfig {
+    /// Unique name of this node (`LEADER_NODE_ID`, default `<host>-<pid>`)
+    pub node_id: String,
+    /// Heartbeat age after which the lease may be taken over
+    pub lease_ttl: Duration,
+    /// How often the lease is renewed / contested
+    pub heartbeat_interval: Duration,
+}
+
+impl LeaderElectionConfig {
+    /// Load from environment; `None` unless `LEADER_ELECTION_ENABLED` is set
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("LEADER_ELECTION_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let secs = |key: &str, default: u64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        let heartbeat_secs = secs("LEADER_HEARTBEAT_SECS", 10);
+        // A lease shorter than a few heartbeats would flap on a single slow write
+        let ttl_secs = secs("LEADER_LEASE_TTL_SECS", 30).max(heartbeat_secs * 2);
+
+        Some(Self {
+            node_id: env::var("LEADER_NODE_ID")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(default_node_id),
+            lease_ttl: Duration::from_secs(ttl_secs),
+            heartbeat_interval: Duration::from_secs(heartbeat_secs),
+        })
+    }
+
+    /// Whether hot-standby mode is configured (`LEADER_ELECTION_ENABLED`)
+    pub fn enabled() -> bool {
+        Self::from_env().is_some()
+    }
+}
+
+fn default_node_id() -> String {
+    let host = env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "node".to_string());
+    format!("{}-{}", host, std::process::id())
+}
+
+/// Lease-based leader elector backed by the persistence database
+pub struct LeaderElector {
+    db: PositionDatabase,
+    scope: String,
+    config: LeaderElectionConfig,
+    role: LeaderRole,
+    /// Last successful renewal while leader
+    renewed_at: Option<DateTime<Utc>>,
+}
+
+impl LeaderElector {
+    /// Create an elector for one trading account; starts as standby
+    pub fn new(db: PositionDatabase, account_id: &str, config: LeaderElectionConfig) -> Self {
+        Self {
+            db,
+            scope: format!("account:{}", account_id),
+            config,
+            role: LeaderRole::Standby,
+            renewed_at: None,
+        }
+    }
+
+    pub fn role(&self) -> LeaderRole {
+        self.role
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role == LeaderRole::Leader
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.config.node_id
+    }
+
+    pub fn heartbeat_interval(&self) -> Duration {
+        self.config.heartbeat_interval
+    }
+
+    /// Renew or contest the lease; returns how the role changed
+    pub fn heartbeat(&mut self) -> Result<LeaderTransition> {
+        self.heartbeat_at(Utc::now())
+    }
+
+    fn heartbeat_at(&mut self, now: DateTime<Utc>) -> Result<LeaderTransition> {
+        let ttl_ms = self.config.lease_ttl.as_millis() as i64;
+        let acquired = match self.db.try_acquire_leader_lease(
+            &self.scope,
+            &self.config.node_id,
+            now.timestamp_millis(),
+            ttl_ms,
+        ) {
+            Ok(acquired) => acquired,
+            Err(err) => {
+                // Cannot reach the lease: keep leading only while our last renewal holds
+                if self.is_leader() && !self.holds_lease_at(now) {
+                    self.step_down();
+                    return Ok(LeaderTransition::Demoted);
+                }
+                return Err(err);
+            }
+        };
+
+        let transition = match (self.role, acquired) {
+            (LeaderRole::Standby, true) => LeaderTransition::Promoted,
+            (LeaderRole::Leader, false) => LeaderTransition::Demoted,
+            (role, _) => LeaderTransition::Unchanged(role),
+        };
+        if acquired {
+            self.role = LeaderRole::Leader;
+            self.renewed_at = Some(now);
+        } else {
+            self.step_down();
+        }
+        Ok(transition)
+    }
+
+    /// Leading on a renewal recent enough to trade on; checked before orders
+    pub fn holds_lease(&self) -> bool {
+        self.holds_lease_at(Utc::now())
+    }
+
+    fn holds_lease_at(&self, now: DateTime<Utc>) -> bool {
+        // One heartbeat short of the TTL, so we stop before the standby may start
+        let trusted = self
+            .config
+            .lease_ttl
+            .saturating_sub(self.config.heartbeat_interval)
+            .as_millis() as i64;
+        self.is_leader()
+            && self
+                .renewed_at
+                .map(|at| (now - at).num_milliseconds() < trusted)
+                .unwrap_or(false)
+    }
+
+    /// Current holder of the lease, if any
+    pub fn current_holder(&self) -> Result<Option<String>> {
+        Ok(self
+            .db
+            .get_leader_lease(&self.scope)?
+            .map(|(holder, _)| holder))
+    }
+
+    /// Hand the lease over immediately (graceful shutdown)
+    pub fn release(&mut self) -> Result<()> {
+        if self.is_leader() {
+            self.db
+                .release_leader_lease(&self.scope, &self.config.node_id)?;
+        }
+        self.step_down();
+        Ok(())
+    }
+
+    fn step_down(&mut self) {
+        self.role = LeaderRole::Standby;
+        self.renewed_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn elector(db: &PositionDatabase, node: &str) -> LeaderElector {
+        LeaderElector::new(
+            db.clone(),
+            "12345",
+            LeaderElectionConfig {
+                node_id: node.to_string(),
+                lease_ttl: Duration::from_secs(30),
+                heartbeat_interval: Duration::from_secs(10),
+            },
+        )
+    }
+
+    #[test]
+    fn test_standby_takes_over_after_missed_heartbeats() {
+        let dir = TempDir::new().unwrap();
+        let db = PositionDatabase::new(dir.path().join("test.db")).unwrap();
+        let mut primary = elector(&db, "primary");
+        let mut standby = elector(&db, "standby");
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+
+        assert_eq!(
+            primary.heartbeat_at(t0).unwrap(),
+            LeaderTransition::Promoted
+        );
+        assert_eq!(
+            standby
+                .heartbeat_at(t0 + chrono::Duration::seconds(5))
+                .unwrap(),
+            LeaderTransition::Unchanged(LeaderRole::Standby)
+        );
+        assert_eq!(
+            primary
+                .heartbeat_at(t0 + chrono::Duration::seconds(10))
+                .unwrap(),
+            LeaderTransition::Unchanged(LeaderRole::Leader)
+        );
+        // The renewal is trusted for the TTL minus one heartbeat
+        assert!(primary.holds_lease_at(t0 + chrono::Duration::seconds(29)));
+        assert!(!primary.holds_lease_at(t0 + chrono::Duration::seconds(30)));
+
+        // Primary goes silent; the standby wins once the lease is stale
+        assert_eq!(
+            standby
+                .heartbeat_at(t0 + chrono::Duration::seconds(35))
+                .unwrap(),
+            LeaderTransition::Unchanged(LeaderRole::Standby)
+        );
+        assert_eq!(
+            standby
+                .heartbeat_at(t0 + chrono::Duration::seconds(45))
+                .unwrap(),
+            LeaderTransition::Promoted
+        );
+        assert_eq!(
+            standby.current_holder().unwrap().as_deref(),
+            Some("standby")
+        );
+
+        // The old primary comes back and must not trade alongside it
+        assert_eq!(
+            primary
+                .heartbeat_at(t0 + chrono::Duration::seconds(50))
+                .unwrap(),
+            LeaderTransition::Demoted
+        );
+        assert!(!primary.is_leader());
+    }
+
+    #[test]
+    fn test_release_hands_over_immediately() {
+        let dir = TempDir::new().unwrap();
+        let db = PositionDatabase::new(dir.path().join("test.db")).unwrap();
+        let mut primary = elector(&db, "primary");
+        let mut standby = elector(&db, "standby");
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+
+        primary.heartbeat_at(t0).unwrap();
+        primary.release().unwrap();
+        assert_eq!(
+            standby
+                .heartbeat_at(t0 + chrono::Duration::seconds(1))
+                .unwrap(),
+            LeaderTransition::Promoted
+        );
+    }
+}
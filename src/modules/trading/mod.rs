@@ -3,37 +3,138 @@
 //! This module contains:
 //! - `ctrader`: cTrader Open API client (Protobuf/TCP)
 //! - `protobuf`: Protobuf message definitions for cTrader
-//! - `indicators`: Technical indicators (RSI)
-//! - `strategy`: Trading strategy logic
+//! - `frame`: Panic-free decoding of inbound frames and payloads
+//! - `heartbeat`: Client heartbeats and server silence detection
+//! - `indicators`: Technical indicators (RSI, EMA, MACD, ATR, ADX)
+//! - `strategy`: Trading strategy logic and the pluggable `Strategy` trait
 //! - `orders`: Order and position management
+//! - `normalize`: Price, SL/TP and volume normalization to broker symbol constraints
+//! - `command_queue`: Per-position ordering of new order / close / amend requests
+//! - `connection_supervisor`: Connection state; the reader task alone reconnects after startup
+//! - `cross_market`: Soybean/crude correlation and relative strength that boost or veto signals
+//! - `decision`: Explainable signal decisions (factors, thresholds, blockers)
+//! - `emergency`: Operator panic commands (cancel all / flatten) for the running bot
+//! - `arming`: Time-limited arming switch for live orders
+//! - `balance_monitor`: Alerts on balance changes not explained by trading
+//! - `blackout`: No-entry windows around scheduled report releases
+//! - `breaker_scenarios`: Scripted breaker trips that the quick test checks end to end
+//! - `features`: Runtime feature flags / kill switches for strategy components
+//! - `leader`: Lease-based leader election for hot-standby pairs
+//! - `order_label`: Templated order labels/comments for broker statements
+//! - `pending_orders`: Reconciliation of tracked limit/stop orders with the broker
+//! - `pacing`: Client-side cTrader message rate budgets
+//! - `reconnect_breaker`: Circuit breaker on cTrader reconnect storms
+//! - `risk_manager`: Portfolio exposure and margin checks before new orders
+//! - `secondary_feed`: Second price source for cross-checks and stale-feed monitoring
+//! - `simulated_broker`: Dry-run fills with spread, slippage, commission and swap
+//! - `size_guard`: Absolute per-order size caps against fat-finger configuration
+//! - `spread_guard`: Entry filter on abnormal bid/ask spreads
+//! - `stale_price`: Trading halt while the price feed has gone quiet
+//! - `structure_stop`: Swing and chandelier stops from the recent candle window
+//! - `trend_following`: EMA cross + ADX trend strategy with pyramiding and trailing stops
+//! - `volatility`: Realized-volatility regimes that scale down or halt entries
+//! - `vwap_reversion`: Session-VWAP reversion strategy with a sentiment gate
+//! - `oauth`: OAuth token flow and per-account token storage
 
+pub mod arming;
+pub mod balance_monitor;
+pub mod blackout;
+pub mod breaker_scenarios;
 pub mod candles;
 pub mod circuit_breakers;
+pub mod command_queue;
+pub mod connection_supervisor;
+pub mod cross_market;
 pub mod ctrader;
+pub mod decision;
+pub mod emergency;
 pub mod event_system;
+pub mod features;
+pub mod frame;
+pub mod heartbeat;
 pub mod indicators;
+pub mod leader;
+pub mod normalize;
 pub mod oauth;
+pub mod order_label;
 pub mod orders;
+pub mod pacing;
+pub mod pending_orders;
 pub mod persistence;
 pub mod position_manager;
 pub mod position_reconciliation;
 pub mod protobuf;
 pub mod reconciliation;
+pub mod reconnect_breaker;
+pub mod risk_manager;
+pub mod secondary_feed;
+pub mod simulated_broker;
+pub mod size_guard;
+pub mod spread_guard;
+pub mod stale_price;
 pub mod strategy;
+pub mod structure_stop;
+pub mod trend_following;
+pub mod volatility;
+pub mod vwap_reversion;
 
-pub use candles::{Candle, CandleBuilder, TimeFrame, Tick};
+pub use arming::{ArmingConfig, ArmingStatus, ArmingSwitch};
+pub use balance_monitor::{
+    BalanceAnomaly, BalanceMonitor, BalanceMonitorConfig, BalanceSnapshot,
+};
+pub use blackout::{BlackoutSchedule, BlackoutWindow};
+pub use breaker_scenarios::{BreakerScenario, ScenarioOutcome, ScenarioVerdict};
+pub use candles::{Candle, CandleBuilder, LateTickPolicy, TimeFrame, Tick};
 pub use circuit_breakers::CircuitBreakers;
-pub use ctrader::{CTraderClient, CTraderEnvironment, Price, OrderTicket, SymbolMeta};
+pub use command_queue::{CommandKey, OrderCommand, PositionCommandQueue};
+pub use connection_supervisor::{ConnectionChange, ConnectionSupervisor};
+pub use cross_market::{CrossMarketConfig, CrossMarketFilter, CrossMarketReading};
+pub use ctrader::{AccountScope, BrokerOrder, CancelAllReport, CTraderClient, CTraderEnvironment, Price, OrderPlacement, OrderTicket, SymbolClassification, SymbolMeta};
+pub use decision::{DecisionFactor, SignalDecision};
+pub use emergency::{emergency_channel, EmergencyCommand, EmergencyHandle, StatusReport};
+pub use features::{StrategyFeature, StrategyFeatures};
+pub use heartbeat::{HeartbeatConfig, HeartbeatMonitor};
 pub use event_system::{MarketEvent, EventChannel, EventChannelHandle, EventFilter, EventType, AlertLevel, SubscriberId};
-pub use indicators::{RsiCalculator, PricePoint};
-pub use oauth::OAuthClient;
-pub use orders::{Order, OrderSide, OrderStatus, Position, PositionManager, ClosedPosition, CloseReason};
-pub use persistence::{PositionDatabase, DailyStats, ClosedTradeRecord};
+pub use indicators::{
+    AdxCalculator, AdxValues, MacdCalculator, MacdValues, RsiCalculator, PricePoint,
+};
+pub use leader::{LeaderElectionConfig, LeaderElector, LeaderRole, LeaderTransition};
+pub use oauth::{AccountTokenStorage, MultiAccountTokenStorage, OAuthClient};
+pub use order_label::{LabelContext, OrderLabeler};
+pub use orders::{Order, OrderEvent, OrderSide, OrderStatus, Position, PositionManager, ClosedPosition, CloseReason};
+pub use pacing::{MessageBudget, MessagePacer};
+pub use pending_orders::{
+    reconcile_orders, OrderReconciliation, PendingOrderBook, TrackedOrder, UnknownOrderPolicy,
+};
+pub use persistence::{
+    ClosedTradeRecord, DailyStats, IndicatorSample, PositionDatabase, SentimentRecord,
+    SignalDecisionRecord, StrategyVersionStats,
+};
 pub use position_manager::{PersistentPositionManager, BrokerPosition, ReconciliationResult};
+pub use risk_manager::{
+    Exposure, ExposureReport, OrderExposure, RiskLimits, RiskManager, RiskRejection,
+};
+pub use secondary_feed::{
+    CrossCheck, FeedDivergence, SecondaryFeed, SecondaryFeedConfig, SecondaryQuote,
+};
+pub use simulated_broker::{SimulatedBroker, SimulatedClose, SimulatedFill};
+pub use size_guard::{OrderSizeGuard, SizeViolation};
+pub use spread_guard::{SpreadGuard, SpreadViolation};
+pub use stale_price::{PriceFreshness, StalePriceGuard};
 pub use position_reconciliation::{
     PositionReconciliationSystem, ConnectionState, ReconciliationConfig,
     ReconciliationReport, ReconciliationMismatch, AuditEntry, AuditEventType,
     BrokerPositionData, CachedPosition, ReconciliationState,
 };
 pub use reconciliation::ReconciliationEngine;
-pub use strategy::{TradingStrategy, Signal, RiskState};
+pub use reconnect_breaker::{
+    ReconnectBreaker, ReconnectBreakerConfig, ReconnectTrip, SharedReconnectBreaker,
+};
+pub use strategy::{
+    TradingStrategy, Signal, SignalContext, RiskState, EntryMark, Strategy, EntryKind,
+    KellyEstimate, OppositeSignalPolicy, StrategyKind,
+};
+pub use structure_stop::{StructureStop, StructureStopConfig, StructureStopMode};
+pub use trend_following::{TrendFollowing, TrendFollowingConfig};
+pub use volatility::{VolatilityConfig, VolatilityDetector, VolatilityReading, VolatilityRegime};
+pub use vwap_reversion::{SessionVwap, VwapReversion, VwapReversionConfig};
@@ -0,0 +1,387 @@
+//! Price and volume normalization to the broker's symbol constraints
+//!
+//! cTrader rejects an order whose prices carry more decimals than the
+//! symbol's `digits`, whose volume is off the `step_volume` grid or outside
+//! `min_volume`..`max_volume`, or whose stop-loss / take-profit sit closer to
+//! the entry than `sl_distance` / `tp_distance`. These functions bring raw
+//! strategy values onto those constraints; without symbol metadata they fall
+//! back to 5 price digits and a volume safety cap.
+//!
+//! The property tests below generate random symbol metadata and inputs and
+//! check that the results are always acceptable to the broker.
+
+use tracing::{debug, warn};
+
+use super::ctrader::SymbolMeta;
+use super::orders::OrderSide;
+
+/// Price digits when symbol metadata is unavailable (safe for most commodities/forex)
+const DEFAULT_DIGITS: i32 = 5;
+
+/// Volume cap without symbol metadata, in cTrader units (≈0.5 lots forex)
+const DEFAULT_MAX_VOLUME: i64 = 5_000_000;
+
+fn digits(meta: Option<&SymbolMeta>) -> i32 {
+    meta.map(|m| m.digits)
+        .filter(|&d| d >= 0)
+        .unwrap_or(DEFAULT_DIGITS)
+}
+
+/// `10^digits`: price units per point
+pub fn price_factor(meta: Option<&SymbolMeta>) -> f64 {
+    10_f64.powi(digits(meta))
+}
+
+/// Round `price` to the symbol's digits
+pub fn normalize_price(meta: Option<&SymbolMeta>, price: f64) -> f64 {
+    if meta.is_none() {
+        debug!(
+            "Using default precision ({} digits) - symbol_meta unavailable",
+            DEFAULT_DIGITS
+        );
+    }
+    let prec = digits(meta) as usize;
+    let formatted = format!("{:.prec$}", price, prec = prec);
+    formatted.parse::<f64>().unwrap_or(price)
+}
+
+/// Round `price` up to the symbol's digits
+pub fn round_price_up(meta: Option<&SymbolMeta>, price: f64) -> f64 {
+    let factor = price_factor(meta);
+    normalize_price(meta, (price * factor).ceil() / factor)
+}
+
+/// Round `price` down to the symbol's digits
+pub fn round_price_down(meta: Option<&SymbolMeta>, price: f64) -> f64 {
+    let factor = price_factor(meta);
+    normalize_price(meta, (price * factor).floor() / factor)
+}
+
+/// Move take-profit and stop-loss onto the price grid, on the profitable /
+/// losing side of `entry` respectively, and at least the broker's minimum
+/// distances away from it. Rounding always widens the distance.
+pub fn normalize_tp_sl(
+    meta: Option<&SymbolMeta>,
+    side: OrderSide,
+    entry: f64,
+    take_profit: f64,
+    stop_loss: f64,
+) -> (f64, f64) {
+    let mut tp = take_profit;
+    let mut sl = stop_loss;
+
+    if let Some(meta) = meta {
+        if let Some(min_tp) = meta.min_distance_price(entry, meta.tp_distance) {
+            match side {
+                OrderSide::Buy => tp = tp.max(entry + min_tp),
+                OrderSide::Sell => tp = tp.min(entry - min_tp),
+            }
+        }
+        if let Some(min_sl) = meta.min_distance_price(entry, meta.sl_distance) {
+            match side {
+                OrderSide::Buy => sl = sl.min(entry - min_sl),
+                OrderSide::Sell => sl = sl.max(entry + min_sl),
+            }
+        }
+    }
+
+    match side {
+        OrderSide::Buy => {
+            tp = round_price_up(meta, tp);
+            sl = round_price_down(meta, sl);
+        }
+        OrderSide::Sell => {
+            tp = round_price_down(meta, tp);
+            sl = round_price_up(meta, sl);
+        }
+    }
+
+    let Some(meta_ref) = meta else {
+        return (tp, sl);
+    };
+    let Some(point) = meta_ref.point_size() else {
+        return (tp, sl);
+    };
+
+    // Rounding may have landed on the entry itself
+    match side {
+        OrderSide::Buy => {
+            if tp <= entry {
+                tp = round_price_up(meta, entry + point);
+            }
+            if sl >= entry {
+                sl = round_price_down(meta, entry - point);
+            }
+        }
+        OrderSide::Sell => {
+            if tp >= entry {
+                tp = round_price_down(meta, entry - point);
+            }
+            if sl <= entry {
+                sl = round_price_up(meta, entry + point);
+            }
+        }
+    }
+
+    // ...or just inside the minimum distance
+    if let Some(min_tp) = meta_ref.min_distance_price(entry, meta_ref.tp_distance) {
+        match side {
+            OrderSide::Buy => {
+                let min_target = entry + min_tp;
+                if tp < min_target {
+                    tp = round_price_up(meta, min_target + point);
+                }
+            }
+            OrderSide::Sell => {
+                let min_target = entry - min_tp;
+                if tp > min_target {
+                    tp = round_price_down(meta, min_target - point);
+                }
+            }
+        }
+    }
+    if let Some(min_sl) = meta_ref.min_distance_price(entry, meta_ref.sl_distance) {
+        match side {
+            OrderSide::Buy => {
+                let min_target = entry - min_sl;
+                if sl > min_target {
+                    sl = round_price_down(meta, min_target - point);
+                }
+            }
+            OrderSide::Sell => {
+                let min_target = entry + min_sl;
+                if sl < min_target {
+                    sl = round_price_up(meta, min_target + point);
+                }
+            }
+        }
+    }
+
+    (tp, sl)
+}
+
+/// Convert base currency units to cTrader volume units, aligned to broker constraints.
+///
+/// Input: base_currency_units (e.g. 33,898 EUR for a 2% risk trade on EURUSD)
+/// Output: (base_units_display, ctrader_volume_units)
+/// cTrader volume = base_currency_units × 100 (centigranular convention)
+pub fn normalize_volume(meta: Option<&SymbolMeta>, base_units: f64) -> Option<(f64, i64)> {
+    // cTrader uses centigranular volume: 1 base unit = 100 volume units
+    let mut units = (base_units * 100.0).round() as i64;
+    if units <= 0 {
+        return None;
+    }
+
+    if let Some(meta) = meta {
+        if let Some(step) = meta.step_volume.filter(|step| *step > 0) {
+            units = (units / step) * step;
+        }
+        if let Some(min) = meta.min_volume {
+            units = units.max(min);
+        }
+        if let Some(max) = meta.max_volume {
+            units = units.min(max);
+        }
+    } else if units > DEFAULT_MAX_VOLUME {
+        warn!(
+            "No symbol meta; capping volume {} → {} (safety limit)",
+            units, DEFAULT_MAX_VOLUME
+        );
+        units = DEFAULT_MAX_VOLUME;
+    }
+
+    if units <= 0 {
+        return None;
+    }
+
+    Some((units as f64 / 100.0, units))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::protobuf::ProtoOaSymbolDistanceType;
+    use proptest::prelude::*;
+
+    fn meta(digits: i32) -> SymbolMeta {
+        SymbolMeta {
+            symbol_id: 1,
+            digits,
+            pip_position: (digits - 1).max(0),
+            min_volume: None,
+            max_volume: None,
+            step_volume: None,
+            lot_size: None,
+            sl_distance: None,
+            tp_distance: None,
+            distance_set_in: None,
+            trading_mode: None,
+        }
+    }
+
+    /// Decimals in the shortest representation of `price`
+    fn decimals(price: f64) -> usize {
+        price.to_string().split('.').nth(1).map_or(0, |d| d.len())
+    }
+
+    /// Half a point of slack for float comparisons of prices
+    fn slack(digits: i32) -> f64 {
+        0.5 / 10_f64.powi(digits)
+    }
+
+    fn side() -> impl Strategy<Value = OrderSide> {
+        prop_oneof![Just(OrderSide::Buy), Just(OrderSide::Sell)]
+    }
+
+    /// Symbol with random digits and broker minimum distances, either in
+    /// points or in percent of the entry
+    fn distance_meta() -> impl Strategy<Value = SymbolMeta> {
+        (
+            0i32..=6,
+            proptest::option::of(0u32..=1_000),
+            proptest::option::of(0u32..=1_000),
+            any::<bool>(),
+        )
+            .prop_map(|(digits, sl_distance, tp_distance, percent)| {
+                let mut meta = meta(digits);
+                if percent {
+                    // Percentage distances stay small: 0-5%
+                    meta.sl_distance = sl_distance.map(|d| d % 6);
+                    meta.tp_distance = tp_distance.map(|d| d % 6);
+                    meta.distance_set_in =
+                        Some(ProtoOaSymbolDistanceType::SymbolDistanceInPercentage);
+                } else {
+                    meta.sl_distance = sl_distance;
+                    meta.tp_distance = tp_distance;
+                }
+                meta
+            })
+    }
+
+    /// Symbol with a consistent volume grid: min and max are multiples of the step
+    fn volume_meta() -> impl Strategy<Value = SymbolMeta> {
+        (
+            proptest::option::of(1i64..=100_000),
+            proptest::option::of(1i64..=100),
+            proptest::option::of(0i64..=10_000),
+        )
+            .prop_map(|(step, min_steps, extra_steps)| {
+                let unit = step.unwrap_or(1);
+                let mut meta = meta(2);
+                meta.step_volume = step;
+                meta.min_volume = min_steps.map(|n| n * unit);
+                meta.max_volume = extra_steps.map(|n| meta.min_volume.unwrap_or(unit) + n * unit);
+                meta
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_normalized_price_fits_symbol_digits(
+            digits in 0i32..=6,
+            price in 0.0001f64..100_000.0,
+        ) {
+            let meta = meta(digits);
+            let normalized = normalize_price(Some(&meta), price);
+            prop_assert!(decimals(normalized) <= digits as usize, "{} → {}", price, normalized);
+            prop_assert!((normalized - price).abs() <= slack(digits) + price * 1e-15);
+
+            let up = round_price_up(Some(&meta), price);
+            let down = round_price_down(Some(&meta), price);
+            prop_assert!(decimals(up) <= digits as usize && decimals(down) <= digits as usize);
+            prop_assert!(up >= price - price * 1e-12);
+            prop_assert!(down <= price + price * 1e-12);
+        }
+
+        #[test]
+        fn prop_price_without_meta_uses_default_digits(price in 0.0001f64..100_000.0) {
+            let normalized = normalize_price(None, price);
+            prop_assert!(decimals(normalized) <= DEFAULT_DIGITS as usize);
+        }
+
+        #[test]
+        fn prop_tp_sl_respect_side_grid_and_min_distance(
+            meta in distance_meta(),
+            side in side(),
+            entry_points in 10_000i64..100_000_000,
+            tp_offset in -0.2f64..0.2,
+            sl_offset in -0.2f64..0.2,
+        ) {
+            let factor = price_factor(Some(&meta));
+            // The bot normalizes the entry before the brackets
+            let entry = normalize_price(Some(&meta), entry_points as f64 / factor);
+            let (tp, sl) = normalize_tp_sl(
+                Some(&meta),
+                side,
+                entry,
+                entry * (1.0 + tp_offset),
+                entry * (1.0 + sl_offset),
+            );
+
+            let digits = meta.digits as usize;
+            prop_assert!(decimals(tp) <= digits, "TP {} off the grid", tp);
+            prop_assert!(decimals(sl) <= digits, "SL {} off the grid", sl);
+            prop_assert!(tp > 0.0 && sl > 0.0);
+
+            let (tp_distance, sl_distance) = match side {
+                OrderSide::Buy => (tp - entry, entry - sl),
+                OrderSide::Sell => (entry - tp, sl - entry),
+            };
+            prop_assert!(tp_distance > 0.0, "{:?} TP {} on the wrong side of {}", side, tp, entry);
+            prop_assert!(sl_distance > 0.0, "{:?} SL {} on the wrong side of {}", side, sl, entry);
+
+            let tolerance = slack(meta.digits);
+            if let Some(min_tp) = meta.min_distance_price(entry, meta.tp_distance) {
+                prop_assert!(
+                    tp_distance >= min_tp - tolerance,
+                    "TP {} within {} of {}", tp, min_tp, entry
+                );
+            }
+            if let Some(min_sl) = meta.min_distance_price(entry, meta.sl_distance) {
+                prop_assert!(
+                    sl_distance >= min_sl - tolerance,
+                    "SL {} within {} of {}", sl, min_sl, entry
+                );
+            }
+        }
+
+        #[test]
+        fn prop_volume_stays_on_broker_grid(
+            meta in volume_meta(),
+            base_units in 0.0f64..1_000_000_000.0,
+        ) {
+            let Some((volume, units)) = normalize_volume(Some(&meta), base_units) else {
+                // Dropped only when it rounds to nothing, or below one step
+                // without a broker minimum to lift it
+                let requested = (base_units * 100.0).round() as i64;
+                prop_assert!(requested <= 0 || meta.min_volume.is_none());
+                return Ok(());
+            };
+            prop_assert!(units > 0);
+            prop_assert_eq!((volume * 100.0).round() as i64, units);
+            if let Some(step) = meta.step_volume {
+                prop_assert_eq!(units % step, 0, "{} off the {} step", units, step);
+            }
+            if let Some(min) = meta.min_volume {
+                prop_assert!(units >= min);
+            }
+            if let Some(max) = meta.max_volume {
+                prop_assert!(units <= max);
+            }
+        }
+
+        #[test]
+        fn prop_volume_without_meta_is_capped(base_units in 0.0f64..1_000_000_000.0) {
+            if let Some((_, units)) = normalize_volume(None, base_units) {
+                prop_assert!(units > 0 && units <= DEFAULT_MAX_VOLUME);
+            }
+        }
+    }
+
+    #[test]
+    fn test_volume_rounding_to_zero_is_dropped() {
+        assert_eq!(normalize_volume(None, 0.004), None);
+        assert_eq!(normalize_volume(None, -1.0), None);
+        assert_eq!(normalize_volume(None, 12.5), Some((12.5, 1250)));
+    }
+}
@@ -8,10 +8,17 @@
 //! 2. Redirect with authorization code to callback URL
 //! 3. Exchange code for access_token and refresh_token via POST to token endpoint
 //! 4. Use refresh_token to get new access_token before expiration
+//!
+//! Tokens are persisted per account in `MultiAccountTokenStorage`
+//! (`OAUTH_TOKEN_FILE`, default `oauth_tokens.json`), keyed by environment and
+//! ctid trader account id, so demo and live bots can run side by side and
+//! each record is refreshed on its own expiry.
 
 use chrono::{DateTime, Duration, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
@@ -56,6 +63,11 @@ impl OAuthToken {
     pub fn time_remaining(&self) -> Duration {
         self.expires_at - Utc::now()
     }
+
+    /// When the token should be refreshed (expiry minus the refresh buffer)
+    pub fn refresh_at(&self) -> DateTime<Utc> {
+        self.expires_at - Duration::seconds(REFRESH_BUFFER_SECS)
+    }
 }
 
 /// OAuth token response from cTrader API
@@ -97,6 +109,14 @@ impl Environment {
     pub fn port(&self) -> u16 {
         5035
     }
+
+    /// Lowercase name used in token record keys
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Demo => "demo",
+            Environment::Live => "live",
+        }
+    }
 }
 
 /// OAuth configuration for cTrader
@@ -387,6 +407,181 @@ impl TokenStorage for FileTokenStorage {
     }
 }
 
+/// Default file for per-account token records
+pub const DEFAULT_TOKEN_FILE: &str = "oauth_tokens.json";
+
+/// Single-token file written by older versions, read as a fallback
+pub const LEGACY_TOKEN_FILE: &str = "oauth_token.json";
+
+/// Serializes read-modify-write cycles on token files within the process
+static TOKEN_FILE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Key of an account's token record, e.g. `live:10092792`
+pub fn token_key(environment: Environment, account_id: &str) -> String {
+    format!("{}:{}", environment.as_str(), account_id.trim())
+}
+
+/// File of token records, one per environment and account id
+///
+/// Every save re-reads the file and replaces it atomically, so bots for
+/// different accounts sharing the file only ever touch their own record.
+pub struct MultiAccountTokenStorage {
+    path: PathBuf,
+}
+
+impl MultiAccountTokenStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Storage at `OAUTH_TOKEN_FILE` (default `oauth_tokens.json`)
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("OAUTH_TOKEN_FILE").unwrap_or_else(|_| DEFAULT_TOKEN_FILE.to_string()),
+        )
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every stored record by key
+    pub fn load_all(&self) -> Result<BTreeMap<String, OAuthToken>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let json = std::fs::read_to_string(&self.path)?;
+        if json.trim().is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Token of one account, if stored
+    pub fn load_account(
+        &self,
+        environment: Environment,
+        account_id: &str,
+    ) -> Result<Option<OAuthToken>> {
+        Ok(self.load_all()?.remove(&token_key(environment, account_id)))
+    }
+
+    /// Store or replace one account's token, leaving the other records alone
+    pub fn save_account(
+        &self,
+        environment: Environment,
+        account_id: &str,
+        token: &OAuthToken,
+    ) -> Result<()> {
+        self.update(|records| {
+            records.insert(token_key(environment, account_id), token.clone());
+        })
+    }
+
+    /// Drop one account's token; returns whether it was stored
+    pub fn remove_account(&self, environment: Environment, account_id: &str) -> Result<bool> {
+        let mut removed = false;
+        self.update(|records| {
+            removed = records.remove(&token_key(environment, account_id)).is_some();
+        })?;
+        Ok(removed)
+    }
+
+    /// When each record is due for refresh, soonest first
+    pub fn refresh_schedule(&self) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let mut schedule: Vec<(String, DateTime<Utc>)> = self
+            .load_all()?
+            .into_iter()
+            .map(|(key, token)| (key, token.refresh_at()))
+            .collect();
+        schedule.sort_by_key(|(_, at)| *at);
+        Ok(schedule)
+    }
+
+    /// `TokenStorage` view of one account's record
+    pub fn for_account(
+        self: &Arc<Self>,
+        environment: Environment,
+        account_id: &str,
+    ) -> AccountTokenStorage {
+        AccountTokenStorage {
+            store: Arc::clone(self),
+            environment,
+            account_id: account_id.trim().to_string(),
+            legacy: None,
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut BTreeMap<String, OAuthToken>)) -> Result<()> {
+        let _guard = TOKEN_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut records = self.load_all()?;
+        apply(&mut records);
+
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&records)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// One account's record in a `MultiAccountTokenStorage`
+pub struct AccountTokenStorage {
+    store: Arc<MultiAccountTokenStorage>,
+    environment: Environment,
+    account_id: String,
+    legacy: Option<FileTokenStorage>,
+}
+
+impl AccountTokenStorage {
+    /// Fall back to a single-token file when the account has no record yet;
+    /// the token is copied into the store on first load
+    pub fn with_legacy_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.legacy = Some(FileTokenStorage::new(path));
+        self
+    }
+
+    pub fn key(&self) -> String {
+        token_key(self.environment, &self.account_id)
+    }
+}
+
+impl TokenStorage for AccountTokenStorage {
+    fn save(&self, token: &OAuthToken) -> Result<()> {
+        self.store
+            .save_account(self.environment, &self.account_id, token)?;
+        info!("Token for {} saved to {:?}", self.key(), self.store.path());
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<OAuthToken>> {
+        if let Some(token) = self
+            .store
+            .load_account(self.environment, &self.account_id)?
+        {
+            if token.is_expired() {
+                warn!("Stored token for {} is expired, will need refresh", self.key());
+            }
+            debug!("Token for {} loaded from {:?}", self.key(), self.store.path());
+            return Ok(Some(token));
+        }
+
+        let Some(legacy) = &self.legacy else {
+            return Ok(None);
+        };
+        let token = legacy.load()?;
+        if let Some(token) = &token {
+            info!(
+                "Migrating token from {:?} to {} in {:?}",
+                legacy.path,
+                self.key(),
+                self.store.path()
+            );
+            self.save(token)?;
+        }
+        Ok(token)
+    }
+}
+
 /// OAuth manager that combines client with persistent storage
 pub struct OAuthManager {
     client: OAuthClient,
@@ -562,4 +757,71 @@ mod tests {
         assert_eq!(loaded.access_token, "access_test");
         assert_eq!(loaded.refresh_token, "refresh_test");
     }
+
+    #[test]
+    fn test_multi_account_storage() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(MultiAccountTokenStorage::new(dir.path().join("tokens.json")));
+
+        let demo = store.for_account(Environment::Demo, "12345");
+        let live = store.for_account(Environment::Live, "12345");
+        let other = store.for_account(Environment::Live, "67890");
+        assert!(demo.load().unwrap().is_none());
+
+        demo.save(&OAuthToken::new("demo_a".into(), "demo_r".into(), 7200))
+            .unwrap();
+        live.save(&OAuthToken::new("live_a".into(), "live_r".into(), 600))
+            .unwrap();
+        other
+            .save(&OAuthToken::new("other_a".into(), "other_r".into(), 3600))
+            .unwrap();
+
+        // Same account id on demo and live keeps separate records
+        assert_eq!(demo.load().unwrap().unwrap().access_token, "demo_a");
+        assert_eq!(live.load().unwrap().unwrap().access_token, "live_a");
+
+        // Refreshing one account leaves the others untouched
+        live.save(&OAuthToken::new("live_b".into(), "live_r2".into(), 7200))
+            .unwrap();
+        assert_eq!(live.load().unwrap().unwrap().access_token, "live_b");
+        assert_eq!(other.load().unwrap().unwrap().access_token, "other_a");
+
+        let schedule = store.refresh_schedule().unwrap();
+        let keys: Vec<&str> = schedule.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["live:67890", "demo:12345", "live:12345"]);
+
+        assert!(store.remove_account(Environment::Demo, "12345").unwrap());
+        assert!(demo.load().unwrap().is_none());
+        assert_eq!(store.load_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_legacy_token_migration() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let legacy_path = dir.path().join("oauth_token.json");
+        FileTokenStorage::new(&legacy_path)
+            .save(&OAuthToken::new("old_a".into(), "old_r".into(), 7200))
+            .unwrap();
+
+        let store = Arc::new(MultiAccountTokenStorage::new(dir.path().join("tokens.json")));
+        let account = store
+            .for_account(Environment::Live, "12345")
+            .with_legacy_file(&legacy_path);
+
+        assert_eq!(account.load().unwrap().unwrap().access_token, "old_a");
+        assert_eq!(
+            store
+                .load_account(Environment::Live, "12345")
+                .unwrap()
+                .unwrap()
+                .access_token,
+            "old_a"
+        );
+        // Only the account given the fallback is seeded from it
+        assert!(store
+            .for_account(Environment::Demo, "12345")
+            .load()
+            .unwrap()
+            .is_none());
+    }
 }
@@ -0,0 +1,193 @@
+//! Order label / comment templates
+//!
+//! Labels and comments sent with new orders are rendered from templates so
+//! broker statements show which strategy, signal and bot build placed each
+//! order:
+//! - `ORDER_LABEL_TEMPLATE` (default `PalmOilBot`), cut to 100 characters
+//! - `ORDER_COMMENT_TEMPLATE` (default: no comment), cut to 512 characters
+//!
+//! Placeholders: `{strategy}`, `{version}` (strategy fingerprint),
+//! `{bot_version}`, `{signal}`, `{signal_id}`, `{session}` and `{symbol}`.
+//! Unknown placeholders are left as written.
+//!
+//! The label also identifies the bot's own orders during pending-order
+//! reconciliation: any label starting with the template's literal prefix (the
+//! text before the first placeholder) is treated as the bot's, so the label
+//! template must start with fixed text.
+
+use chrono::{DateTime, Utc};
+use std::env;
+use tracing::warn;
+
+/// cTrader `ProtoOANewOrderReq.label` limit
+pub const MAX_LABEL_LEN: usize = 100;
+/// cTrader `ProtoOANewOrderReq.comment` limit
+pub const MAX_COMMENT_LEN: usize = 512;
+
+const DEFAULT_LABEL_TEMPLATE: &str = "PalmOilBot";
+
+/// Values substituted into the templates for one order
+#[derive(Debug, Clone)]
+pub struct LabelContext<'a> {
+    pub strategy: &'a str,
+    pub strategy_version: &'a str,
+    /// `BUY` / `SELL`
+    pub signal: &'a str,
+    /// Sequence number of the order within the session
+    pub signal_id: u64,
+    pub symbol: &'a str,
+}
+
+/// Label and comment templates plus the session they are rendered for
+#[derive(Debug, Clone)]
+pub struct OrderLabeler {
+    label_template: String,
+    comment_template: Option<String>,
+    session: String,
+}
+
+impl OrderLabeler {
+    /// Templates for a session started at `session_start`. A label template
+    /// without a literal prefix falls back to the default.
+    pub fn new(
+        label_template: &str,
+        comment_template: Option<&str>,
+        session_start: DateTime<Utc>,
+    ) -> Self {
+        let label_template = if literal_prefix(label_template).trim().is_empty() {
+            warn!(
+                "ORDER_LABEL_TEMPLATE {:?} must start with fixed text; using {:?}",
+                label_template, DEFAULT_LABEL_TEMPLATE
+            );
+            DEFAULT_LABEL_TEMPLATE.to_string()
+        } else {
+            label_template.to_string()
+        };
+
+        Self {
+            label_template,
+            comment_template: comment_template
+                .map(str::to_string)
+                .filter(|t| !t.trim().is_empty()),
+            session: session_start.format("%Y%m%d%H%M").to_string(),
+        }
+    }
+
+    /// Load `ORDER_LABEL_TEMPLATE` and `ORDER_COMMENT_TEMPLATE`; the session
+    /// starts now
+    pub fn from_env() -> Self {
+        let label =
+            env::var("ORDER_LABEL_TEMPLATE").unwrap_or_else(|_| DEFAULT_LABEL_TEMPLATE.to_string());
+        let comment = env::var("ORDER_COMMENT_TEMPLATE").ok();
+        Self::new(&label, comment.as_deref(), Utc::now())
+    }
+
+    /// Label for an order, within cTrader's length limit
+    pub fn label(&self, ctx: &LabelContext<'_>) -> String {
+        truncate_chars(&self.render(&self.label_template, ctx), MAX_LABEL_LEN)
+    }
+
+    /// Comment for an order, if a comment template is configured
+    pub fn comment(&self, ctx: &LabelContext<'_>) -> Option<String> {
+        self.comment_template
+            .as_ref()
+            .map(|t| truncate_chars(&self.render(t, ctx), MAX_COMMENT_LEN))
+    }
+
+    /// Fixed start shared by every label this labeler produces
+    pub fn owner_prefix(&self) -> &str {
+        literal_prefix(&self.label_template)
+    }
+
+    /// Whether a broker order label was produced by this template
+    pub fn is_own_label(&self, label: &str) -> bool {
+        label.starts_with(self.owner_prefix())
+    }
+
+    fn render(&self, template: &str, ctx: &LabelContext<'_>) -> String {
+        template
+            .replace("{strategy}", ctx.strategy)
+            .replace("{version}", ctx.strategy_version)
+            .replace("{bot_version}", env!("CARGO_PKG_VERSION"))
+            .replace("{signal}", ctx.signal)
+            .replace("{signal_id}", &ctx.signal_id.to_string())
+            .replace("{session}", &self.session)
+            .replace("{symbol}", ctx.symbol)
+    }
+}
+
+impl Default for OrderLabeler {
+    fn default() -> Self {
+        Self::new(DEFAULT_LABEL_TEMPLATE, None, Utc::now())
+    }
+}
+
+fn literal_prefix(template: &str) -> &str {
+    template.split('{').next().unwrap_or_default()
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ctx() -> LabelContext<'static> {
+        LabelContext {
+            strategy: "rsi_sentiment",
+            strategy_version: "0.1.0-abcd1234",
+            signal: "BUY",
+            signal_id: 7,
+            symbol: "FCPO",
+        }
+    }
+
+    fn session_start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 4, 8, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_default_label_is_unchanged() {
+        let labeler = OrderLabeler::new(DEFAULT_LABEL_TEMPLATE, None, session_start());
+        assert_eq!(labeler.label(&ctx()), "PalmOilBot");
+        assert_eq!(labeler.comment(&ctx()), None);
+        assert!(labeler.is_own_label("PalmOilBot"));
+        assert!(!labeler.is_own_label("manual"));
+    }
+
+    #[test]
+    fn test_templates_render_placeholders() {
+        let labeler = OrderLabeler::new(
+            "PalmOilBot-{session}-{signal_id}",
+            Some("{strategy} {signal} {symbol} v{version} {unknown}"),
+            session_start(),
+        );
+        assert_eq!(labeler.label(&ctx()), "PalmOilBot-202403040830-7");
+        assert_eq!(
+            labeler.comment(&ctx()).unwrap(),
+            "rsi_sentiment BUY FCPO v0.1.0-abcd1234 {unknown}"
+        );
+        assert_eq!(labeler.owner_prefix(), "PalmOilBot-");
+        assert!(labeler.is_own_label("PalmOilBot-202403010900-3"));
+    }
+
+    #[test]
+    fn test_lengths_are_capped() {
+        let long = "x".repeat(600);
+        let labeler = OrderLabeler::new(&format!("Bot{}", long), Some(&long), session_start());
+        assert_eq!(labeler.label(&ctx()).chars().count(), MAX_LABEL_LEN);
+        assert_eq!(
+            labeler.comment(&ctx()).unwrap().chars().count(),
+            MAX_COMMENT_LEN
+        );
+    }
+
+    #[test]
+    fn test_label_without_prefix_falls_back() {
+        let labeler = OrderLabeler::new("{strategy}", None, session_start());
+        assert_eq!(labeler.label(&ctx()), "PalmOilBot");
+    }
+}
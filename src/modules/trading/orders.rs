@@ -1,11 +1,27 @@
 //! Order and Position management module
 //!
 //! Provides structures for managing trading orders and positions.
+//!
+//! An order's status follows the broker's execution events:
+//!
+//! ```text
+//! Pending -> Accepted -> PartiallyFilled -> Filled
+//!    |          |              |
+//!    |          |              +-> Cancelled / Expired (remainder)
+//!    |          +-> Filled / Cancelled / Expired / Rejected
+//!    +-> Rejected
+//! ```
+//!
+//! `OrderStatus::next` is the transition table; events that do not apply to
+//! the current status (duplicates, anything after a final status) are
+//! ignored rather than overwriting it.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use super::protobuf::ProtoOaExecutionType;
+
 /// Order side (direction)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderSide {
@@ -37,6 +53,8 @@ impl OrderSide {
 pub enum OrderStatus {
     /// Order is pending execution
     Pending,
+    /// Order passed the broker's validation and is working
+    Accepted,
     /// Order has been filled
     Filled,
     /// Order was cancelled
@@ -45,16 +63,80 @@ pub enum OrderStatus {
     Rejected,
     /// Order partially filled
     PartiallyFilled,
+    /// Good-till-date order reached its expiration
+    Expired,
 }
 
 impl fmt::Display for OrderStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             OrderStatus::Pending => write!(f, "PENDING"),
+            OrderStatus::Accepted => write!(f, "ACCEPTED"),
             OrderStatus::Filled => write!(f, "FILLED"),
             OrderStatus::Cancelled => write!(f, "CANCELLED"),
             OrderStatus::Rejected => write!(f, "REJECTED"),
             OrderStatus::PartiallyFilled => write!(f, "PARTIALLY_FILLED"),
+            OrderStatus::Expired => write!(f, "EXPIRED"),
+        }
+    }
+}
+
+impl OrderStatus {
+    /// Status after `event`, or `None` when the event does not apply
+    pub fn next(self, event: OrderEvent) -> Option<OrderStatus> {
+        use OrderStatus::*;
+        match (self, event) {
+            (Pending, OrderEvent::Accepted) => Some(Accepted),
+            (Pending | Accepted, OrderEvent::Rejected) => Some(Rejected),
+            (Pending | Accepted | PartiallyFilled, OrderEvent::PartiallyFilled) => {
+                Some(PartiallyFilled)
+            }
+            (Pending | Accepted | PartiallyFilled, OrderEvent::Filled) => Some(Filled),
+            (Accepted | PartiallyFilled, OrderEvent::Cancelled) => Some(Cancelled),
+            (Accepted | PartiallyFilled, OrderEvent::Expired) => Some(Expired),
+            // An amended pending order keeps working
+            (Accepted | PartiallyFilled, OrderEvent::Replaced) => Some(self),
+            _ => None,
+        }
+    }
+
+    /// Whether no further execution event can change the order
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Cancelled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+        )
+    }
+}
+
+/// Order lifecycle step reported by an execution event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderEvent {
+    Accepted,
+    PartiallyFilled,
+    Filled,
+    Replaced,
+    Cancelled,
+    Expired,
+    Rejected,
+}
+
+impl OrderEvent {
+    /// Map a `ProtoOAExecutionType`; account events (swaps, deposits) and
+    /// rejected cancel requests do not move the order and map to `None`
+    pub fn from_execution_type(execution_type: i32) -> Option<Self> {
+        match ProtoOaExecutionType::try_from(execution_type).ok()? {
+            ProtoOaExecutionType::OrderAccepted => Some(OrderEvent::Accepted),
+            ProtoOaExecutionType::OrderPartialFill => Some(OrderEvent::PartiallyFilled),
+            ProtoOaExecutionType::OrderFilled => Some(OrderEvent::Filled),
+            ProtoOaExecutionType::OrderReplaced => Some(OrderEvent::Replaced),
+            ProtoOaExecutionType::OrderCancelled => Some(OrderEvent::Cancelled),
+            ProtoOaExecutionType::OrderExpired => Some(OrderEvent::Expired),
+            ProtoOaExecutionType::OrderRejected => Some(OrderEvent::Rejected),
+            _ => None,
         }
     }
 }
@@ -166,17 +248,31 @@ impl Order {
         self.rejection_reason = Some(reason.into());
     }
 
+    /// Apply an execution event; returns the new status, or `None` when the
+    /// event was ignored
+    pub fn apply(&mut self, event: OrderEvent, fill_price: Option<f64>) -> Option<OrderStatus> {
+        let next = self.status.next(event)?;
+        self.status = next;
+        if matches!(next, OrderStatus::PartiallyFilled | OrderStatus::Filled) {
+            self.filled_at = Some(Utc::now());
+            if fill_price.is_some() {
+                self.fill_price = fill_price;
+            }
+        }
+        Some(next)
+    }
+
     /// Check if order is active (can be cancelled)
     pub fn is_active(&self) -> bool {
-        matches!(self.status, OrderStatus::Pending | OrderStatus::PartiallyFilled)
+        matches!(
+            self.status,
+            OrderStatus::Pending | OrderStatus::Accepted | OrderStatus::PartiallyFilled
+        )
     }
 
     /// Check if order is terminal (final state)
     pub fn is_terminal(&self) -> bool {
-        matches!(
-            self.status,
-            OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected
-        )
+        self.status.is_terminal()
     }
 }
 
@@ -223,6 +319,15 @@ pub struct Position {
     pub opened_at: DateTime<Utc>,
     /// Associated order ID
     pub order_id: String,
+    /// Strategy version/config fingerprint the position was opened under
+    #[serde(default)]
+    pub strategy_version: Option<String>,
+    /// Strategy features active at entry (`StrategyFeatures::tag`)
+    #[serde(default)]
+    pub features: Option<String>,
+    /// Asset class of the symbol (e.g. "Commodities"), when known
+    #[serde(default)]
+    pub asset_class: Option<String>,
     /// Trailing stop configuration
     #[serde(skip)]
     trailing_config: Option<TrailingStopConfig>,
@@ -251,6 +356,9 @@ impl Position {
             stop_loss: order.stop_loss,
             opened_at: Utc::now(),
             order_id: order.id.clone(),
+            strategy_version: None,
+            features: None,
+            asset_class: None,
             trailing_config: None,
             highest_price: fill_price,
             lowest_price: fill_price,
@@ -279,6 +387,9 @@ impl Position {
             stop_loss: None,
             opened_at: Utc::now(),
             order_id: String::new(),
+            strategy_version: None,
+            features: None,
+            asset_class: None,
             trailing_config: None,
             highest_price: entry_price,
             lowest_price: entry_price,
@@ -299,6 +410,24 @@ impl Position {
         self
     }
 
+    /// Stamp the strategy version/config fingerprint
+    pub fn with_strategy_version(mut self, version: impl Into<String>) -> Self {
+        self.strategy_version = Some(version.into());
+        self
+    }
+
+    /// Record the strategy features the position was opened with
+    pub fn with_features(mut self, features: impl Into<String>) -> Self {
+        self.features = Some(features.into());
+        self
+    }
+
+    /// Tag the position with its symbol's asset class
+    pub fn with_asset_class(mut self, asset_class: impl Into<String>) -> Self {
+        self.asset_class = Some(asset_class.into());
+        self
+    }
+
     /// Enable trailing stop for this position
     pub fn with_trailing_stop(mut self, config: TrailingStopConfig) -> Self {
         self.trailing_config = Some(config);
@@ -463,6 +592,8 @@ pub enum CloseReason {
     TrailingStop,
     Manual,
     Signal,
+    /// A signal formed against the position
+    SignalReversal,
     RiskLimit,
 }
 
@@ -474,6 +605,7 @@ impl fmt::Display for CloseReason {
             CloseReason::TrailingStop => write!(f, "Trailing Stop"),
             CloseReason::Manual => write!(f, "Manual Close"),
             CloseReason::Signal => write!(f, "Exit Signal"),
+            CloseReason::SignalReversal => write!(f, "Signal Reversal"),
             CloseReason::RiskLimit => write!(f, "Risk Limit"),
         }
     }
@@ -503,6 +635,25 @@ impl PositionManager {
             .collect()
     }
 
+    /// Open positions in `symbol` on `side`: the legs of one scaled-in
+    /// position, oldest first
+    pub fn legs(&self, symbol: &str, side: OrderSide) -> Vec<&Position> {
+        self.positions
+            .iter()
+            .filter(|p| p.symbol == symbol && p.side == side)
+            .collect()
+    }
+
+    /// Volume-weighted average entry of the legs in `symbol` on `side`
+    pub fn average_entry(&self, symbol: &str, side: OrderSide) -> Option<f64> {
+        let legs = self.legs(symbol, side);
+        let volume: f64 = legs.iter().map(|p| p.volume).sum();
+        if volume <= 0.0 {
+            return None;
+        }
+        Some(legs.iter().map(|p| p.entry_price * p.volume).sum::<f64>() / volume)
+    }
+
     /// Get total number of open positions
     pub fn count(&self) -> usize {
         self.positions.len()
@@ -524,12 +675,24 @@ impl PositionManager {
 
     /// Close a position
     pub fn close(&mut self, position_id: &str, close_price: f64, reason: CloseReason) -> Option<ClosedPosition> {
+        self.close_with_costs(position_id, close_price, reason, 0.0)
+    }
+
+    /// Close a position, taking trading costs (commission, swap) off its
+    /// realized P&L
+    pub fn close_with_costs(
+        &mut self,
+        position_id: &str,
+        close_price: f64,
+        reason: CloseReason,
+        costs: f64,
+    ) -> Option<ClosedPosition> {
         if let Some(idx) = self.positions.iter().position(|p| p.id == position_id) {
             let mut position = self.positions.remove(idx);
             position.update_price(close_price);
 
             let closed = ClosedPosition {
-                realized_pnl: position.current_pnl,
+                realized_pnl: position.current_pnl - costs,
                 close_price,
                 closed_at: Utc::now(),
                 close_reason: reason,
@@ -558,6 +721,17 @@ impl PositionManager {
         &self.closed_positions
     }
 
+    /// Move the stop loss of an open position; returns whether it was found
+    pub fn set_stop_loss(&mut self, position_id: &str, stop_loss: f64) -> bool {
+        match self.positions.iter_mut().find(|p| p.id == position_id) {
+            Some(position) => {
+                position.stop_loss = Some(stop_loss);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Replace all open positions (used for reconciliation)
     pub fn replace_positions(&mut self, positions: Vec<Position>) {
         self.positions = positions;
@@ -624,6 +798,62 @@ mod tests {
         assert_eq!(order.rejection_reason, Some("Insufficient margin".to_string()));
     }
 
+    #[test]
+    fn test_order_lifecycle_from_execution_events() {
+        let event = |t: ProtoOaExecutionType| OrderEvent::from_execution_type(t as i32).unwrap();
+
+        let mut order = Order::limit("1", "FCPO", OrderSide::Buy, 2.0, 4800.0);
+        assert_eq!(
+            order.apply(event(ProtoOaExecutionType::OrderAccepted), None),
+            Some(OrderStatus::Accepted)
+        );
+        assert!(order.is_active());
+        assert_eq!(
+            order.apply(event(ProtoOaExecutionType::OrderReplaced), None),
+            Some(OrderStatus::Accepted)
+        );
+        assert_eq!(
+            order.apply(event(ProtoOaExecutionType::OrderPartialFill), Some(4799.0)),
+            Some(OrderStatus::PartiallyFilled)
+        );
+        assert_eq!(order.fill_price, Some(4799.0));
+        assert_eq!(
+            order.apply(event(ProtoOaExecutionType::OrderFilled), Some(4799.5)),
+            Some(OrderStatus::Filled)
+        );
+        assert!(order.is_terminal());
+
+        // Nothing moves a final status
+        assert_eq!(order.apply(OrderEvent::Cancelled, None), None);
+        assert_eq!(order.apply(OrderEvent::Accepted, None), None);
+        assert_eq!(order.status, OrderStatus::Filled);
+
+        // Account events are not order transitions
+        assert_eq!(
+            OrderEvent::from_execution_type(ProtoOaExecutionType::Swap as i32),
+            None
+        );
+        assert_eq!(
+            OrderEvent::from_execution_type(ProtoOaExecutionType::OrderCancelRejected as i32),
+            None
+        );
+    }
+
+    #[test]
+    fn test_order_lifecycle_final_states() {
+        use OrderStatus::*;
+        assert_eq!(Pending.next(OrderEvent::Rejected), Some(Rejected));
+        assert_eq!(Pending.next(OrderEvent::Filled), Some(Filled));
+        // Only a working order can be cancelled or expire
+        assert_eq!(Pending.next(OrderEvent::Cancelled), None);
+        assert_eq!(Accepted.next(OrderEvent::Expired), Some(Expired));
+        assert_eq!(PartiallyFilled.next(OrderEvent::Cancelled), Some(Cancelled));
+        assert_eq!(PartiallyFilled.next(OrderEvent::Rejected), None);
+        assert_eq!(Accepted.next(OrderEvent::Accepted), None);
+        assert!(Expired.is_terminal());
+        assert!(!PartiallyFilled.is_terminal());
+    }
+
     #[test]
     fn test_position_pnl_buy() {
         let position = Position::new("pos_1", "FCPO", OrderSide::Buy, 4850.0, 1.0);
@@ -728,6 +958,19 @@ mod tests {
         assert_eq!(manager.closed_positions().len(), 1);
     }
 
+    #[test]
+    fn test_legs_average_entry() {
+        let mut manager = PositionManager::new();
+        manager.add(Position::new("leg_1", "FCPO", OrderSide::Buy, 4800.0, 1.0));
+        manager.add(Position::new("leg_2", "FCPO", OrderSide::Buy, 4900.0, 0.5));
+        manager.add(Position::new("short", "FCPO", OrderSide::Sell, 5000.0, 1.0));
+
+        assert_eq!(manager.legs("FCPO", OrderSide::Buy).len(), 2);
+        let average = manager.average_entry("FCPO", OrderSide::Buy).unwrap();
+        assert!((average - 4833.333).abs() < 0.001);
+        assert_eq!(manager.average_entry("GOLD", OrderSide::Buy), None);
+    }
+
     #[test]
     fn test_order_side_opposite() {
         assert_eq!(OrderSide::Buy.opposite(), OrderSide::Sell);
@@ -0,0 +1,150 @@
+//! Client-side pacing of outgoing cTrader messages
+//!
+//! cTrader Open API allows 50 messages per second per connection, and only 5
+//! per second for historical data requests (trendbars, tick data). Exceeding
+//! either gets requests rejected or the connection dropped, which is most
+//! likely during startup bursts (symbol list, metadata, reconcile). Every
+//! message sent through `CTraderClient::send_message` first takes a slot from
+//! the matching budget, waiting in line when the budget is spent:
+//!
+//! - `CTRADER_MAX_MSGS_PER_SEC` (default 45) for everything
+//! - `CTRADER_MAX_HISTORICAL_PER_SEC` (default 4) additionally for historical requests
+//!
+//! Defaults stay a little under the server limits. Both budgets show up in the
+//! `rate_limiter_*` metrics as `ctrader_messages` / `ctrader_historical`.
+
+use std::env;
+use std::time::Duration;
+
+use super::protobuf::{payload_type_from_u32, ProtoOaPayloadType};
+use crate::modules::security::{ApiRateLimiter, RateLimiterConfig};
+
+/// Which budget a message draws from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBudget {
+    /// Trendbar / tick data requests (also count against `Trading`)
+    Historical,
+    /// Every other request, including orders and metadata
+    Trading,
+}
+
+impl MessageBudget {
+    /// Budget for a raw payload type
+    pub fn for_payload(payload_type: u32) -> Self {
+        match payload_type_from_u32(payload_type) {
+            Some(ProtoOaPayloadType::ProtoOaGetTrendbarsReq)
+            | Some(ProtoOaPayloadType::ProtoOaGetTickdataReq) => Self::Historical,
+            _ => Self::Trading,
+        }
+    }
+}
+
+/// Per-connection message budgets
+pub struct MessagePacer {
+    trading: ApiRateLimiter,
+    historical: ApiRateLimiter,
+}
+
+impl MessagePacer {
+    /// Budgets of `per_sec` messages and `historical_per_sec` historical requests
+    pub fn new(per_sec: usize, historical_per_sec: usize) -> Self {
+        let budget = |name: &str, max_requests: usize| {
+            ApiRateLimiter::with_config(RateLimiterConfig {
+                name: name.to_string(),
+                max_requests: max_requests.max(1),
+                window_duration: Duration::from_secs(1),
+                ..Default::default()
+            })
+        };
+        Self {
+            trading: budget("ctrader_messages", per_sec),
+            historical: budget("ctrader_historical", historical_per_sec),
+        }
+    }
+
+    /// Load from `CTRADER_MAX_MSGS_PER_SEC` and `CTRADER_MAX_HISTORICAL_PER_SEC`
+    pub fn from_env() -> Self {
+        let per_sec = |key: &str, default: usize| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        Self::new(
+            per_sec("CTRADER_MAX_MSGS_PER_SEC", 45),
+            per_sec("CTRADER_MAX_HISTORICAL_PER_SEC", 4),
+        )
+    }
+
+    /// Wait until a message of `payload_type` may be sent. Messages are delayed,
+    /// never dropped.
+    pub async fn pace(&self, payload_type: u32) {
+        if MessageBudget::for_payload(payload_type) == MessageBudget::Historical {
+            self.historical.wait_for_rate_limit().await;
+        }
+        self.trading.wait_for_rate_limit().await;
+    }
+}
+
+impl Default for MessagePacer {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn payload(pt: ProtoOaPayloadType) -> u32 {
+        pt as i32 as u32
+    }
+
+    #[test]
+    fn test_budget_classification() {
+        assert_eq!(
+            MessageBudget::for_payload(payload(ProtoOaPayloadType::ProtoOaGetTrendbarsReq)),
+            MessageBudget::Historical
+        );
+        assert_eq!(
+            MessageBudget::for_payload(payload(ProtoOaPayloadType::ProtoOaGetTickdataReq)),
+            MessageBudget::Historical
+        );
+        assert_eq!(
+            MessageBudget::for_payload(payload(ProtoOaPayloadType::ProtoOaNewOrderReq)),
+            MessageBudget::Trading
+        );
+        assert_eq!(MessageBudget::for_payload(51), MessageBudget::Trading);
+    }
+
+    #[tokio::test]
+    async fn test_historical_burst_is_spread_out() {
+        let pacer = MessagePacer::new(100, 2);
+        let historical = payload(ProtoOaPayloadType::ProtoOaGetTrendbarsReq);
+        let started = Instant::now();
+
+        pacer.pace(historical).await;
+        pacer.pace(historical).await;
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        // Third historical request in the same second waits for the window
+        pacer.pace(historical).await;
+        assert!(started.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_trading_messages_ignore_historical_budget() {
+        let pacer = MessagePacer::new(100, 1);
+        let historical = payload(ProtoOaPayloadType::ProtoOaGetTrendbarsReq);
+        let order = payload(ProtoOaPayloadType::ProtoOaNewOrderReq);
+        let started = Instant::now();
+
+        pacer.pace(historical).await;
+        for _ in 0..10 {
+            pacer.pace(order).await;
+        }
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,365 @@
+//! Pending order reconciliation
+//!
+//! Limit/stop orders placed by the bot are tracked locally in a
+//! `PendingOrderBook`. After a reconnect (and on every periodic reconcile) the
+//! book is matched against the broker's working orders (`ProtoOAReconcileReq`)
+//! and, for orders that are no longer working, the order history
+//! (`ProtoOAOrderListReq`) to learn whether they filled, were cancelled or
+//! expired while the bot was away.
+//!
+//! Working orders whose label starts with the bot's label prefix (see
+//! `order_label`) that the book does not know about are handled according to `PENDING_ORDER_POLICY`:
+//! - `adopt` (default): start tracking them
+//! - `cancel`: cancel them at the broker
+//! - `ignore`: leave them alone and untracked
+//!
+//! Orders with another label (placed by hand or by another tool) are never
+//! touched.
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use std::env;
+
+use super::ctrader::{BrokerOrder, OrderTicket};
+use super::protobuf::ProtoOaOrderStatus;
+
+/// What to do with bot-labelled broker orders the book does not know
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownOrderPolicy {
+    Adopt,
+    Cancel,
+    Ignore,
+}
+
+impl UnknownOrderPolicy {
+    /// Load from `PENDING_ORDER_POLICY` (`adopt`, `cancel` or `ignore`)
+    pub fn from_env() -> Self {
+        match env::var("PENDING_ORDER_POLICY")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "cancel" => Self::Cancel,
+            "ignore" => Self::Ignore,
+            _ => Self::Adopt,
+        }
+    }
+}
+
+/// A limit/stop order the bot believes is working
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub order_id: i64,
+    pub symbol_id: i64,
+    pub side: String,
+    pub volume: i64,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub placed_at: DateTime<Utc>,
+}
+
+impl TrackedOrder {
+    /// Track a limit/stop order the bot just placed
+    pub fn from_ticket(order_id: i64, ticket: &OrderTicket) -> Self {
+        Self {
+            order_id,
+            symbol_id: ticket.symbol_id,
+            side: ticket.side.as_str_name().to_string(),
+            volume: ticket.volume,
+            limit_price: ticket.limit_price,
+            stop_price: ticket.stop_price,
+            placed_at: Utc::now(),
+        }
+    }
+
+    /// Track an order as reported by the broker
+    pub fn from_broker(order: &BrokerOrder) -> Self {
+        Self {
+            order_id: order.order_id,
+            symbol_id: order.symbol_id,
+            side: order.side.clone(),
+            volume: order.volume,
+            limit_price: order.limit_price,
+            stop_price: order.stop_price,
+            placed_at: order
+                .updated_at_ms
+                .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+                .unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Locally tracked pending orders
+#[derive(Debug, Default)]
+pub struct PendingOrderBook {
+    orders: HashMap<i64, TrackedOrder>,
+}
+
+impl PendingOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&mut self, order: TrackedOrder) {
+        self.orders.insert(order.order_id, order);
+    }
+
+    pub fn remove(&mut self, order_id: i64) -> Option<TrackedOrder> {
+        self.orders.remove(&order_id)
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Placement time of the oldest tracked order (start of the history window)
+    pub fn oldest_placed_at(&self) -> Option<DateTime<Utc>> {
+        self.orders.values().map(|o| o.placed_at).min()
+    }
+
+    /// Apply a reconciliation: drop finished orders, adopt unknown ones
+    pub fn apply(&mut self, outcome: &OrderReconciliation) {
+        for (order, _) in &outcome.filled {
+            self.orders.remove(&order.order_id);
+        }
+        for (order, _) in &outcome.finished {
+            self.orders.remove(&order.order_id);
+        }
+        for order in &outcome.missing {
+            self.orders.remove(&order.order_id);
+        }
+        for order in &outcome.adopt {
+            self.track(TrackedOrder::from_broker(order));
+        }
+    }
+}
+
+/// Result of matching the book against the broker
+#[derive(Debug, Default)]
+pub struct OrderReconciliation {
+    /// Tracked and still working
+    pub still_pending: Vec<TrackedOrder>,
+    /// Tracked orders that filled while away, with the resulting position
+    pub filled: Vec<(TrackedOrder, Option<i64>)>,
+    /// Tracked orders that were cancelled, rejected or expired
+    pub finished: Vec<(TrackedOrder, ProtoOaOrderStatus)>,
+    /// Tracked orders found neither working nor in the history window
+    pub missing: Vec<TrackedOrder>,
+    /// Unknown bot orders to start tracking (`adopt`)
+    pub adopt: Vec<BrokerOrder>,
+    /// Unknown bot orders to cancel at the broker (`cancel`)
+    pub cancel: Vec<BrokerOrder>,
+}
+
+impl OrderReconciliation {
+    pub fn has_changes(&self) -> bool {
+        !(self.filled.is_empty()
+            && self.finished.is_empty()
+            && self.missing.is_empty()
+            && self.adopt.is_empty()
+            && self.cancel.is_empty())
+    }
+}
+
+/// Match tracked orders against the broker's working orders and order history.
+/// Unknown working orders count as the bot's when their label starts with
+/// `bot_label_prefix`.
+pub fn reconcile_orders(
+    book: &PendingOrderBook,
+    working: &[BrokerOrder],
+    history: &[BrokerOrder],
+    policy: UnknownOrderPolicy,
+    bot_label_prefix: &str,
+) -> OrderReconciliation {
+    let working_by_id: HashMap<i64, &BrokerOrder> =
+        working.iter().map(|o| (o.order_id, o)).collect();
+    let history_by_id: HashMap<i64, &BrokerOrder> =
+        history.iter().map(|o| (o.order_id, o)).collect();
+    let mut outcome = OrderReconciliation::default();
+
+    for tracked in book.orders() {
+        if working_by_id.contains_key(&tracked.order_id) {
+            outcome.still_pending.push(tracked.clone());
+            continue;
+        }
+        match history_by_id.get(&tracked.order_id) {
+            Some(order) if order.status == ProtoOaOrderStatus::OrderStatusFilled => {
+                outcome.filled.push((tracked.clone(), order.position_id));
+            }
+            Some(order) if order.status != ProtoOaOrderStatus::OrderStatusAccepted => {
+                outcome.finished.push((tracked.clone(), order.status));
+            }
+            // Still "accepted" in history but not working, or outside the window
+            _ => outcome.missing.push(tracked.clone()),
+        }
+    }
+
+    for order in working {
+        let ours = order
+            .label
+            .as_deref()
+            .is_some_and(|label| label.starts_with(bot_label_prefix));
+        let known = book.orders.contains_key(&order.order_id);
+        if !ours || known {
+            continue;
+        }
+        match policy {
+            UnknownOrderPolicy::Adopt => outcome.adopt.push(order.clone()),
+            UnknownOrderPolicy::Cancel => outcome.cancel.push(order.clone()),
+            UnknownOrderPolicy::Ignore => {}
+        }
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::protobuf::ProtoOaOrderType;
+
+    fn broker_order(id: i64, status: ProtoOaOrderStatus, label: &str) -> BrokerOrder {
+        BrokerOrder {
+            order_id: id,
+            symbol_id: 1,
+            side: "BUY".to_string(),
+            order_type: ProtoOaOrderType::Limit,
+            status,
+            volume: 100,
+            limit_price: Some(4800.0),
+            stop_price: None,
+            label: Some(label.to_string()),
+            position_id: None,
+            updated_at_ms: Some(1_700_000_000_000),
+        }
+    }
+
+    fn book_with(ids: &[i64]) -> PendingOrderBook {
+        let mut book = PendingOrderBook::new();
+        for id in ids {
+            book.track(TrackedOrder::from_broker(&broker_order(
+                *id,
+                ProtoOaOrderStatus::OrderStatusAccepted,
+                "PalmOilBot",
+            )));
+        }
+        book
+    }
+
+    #[test]
+    fn test_tracked_orders_classified_from_history() {
+        let book = book_with(&[1, 2, 3, 4]);
+        let working = vec![broker_order(
+            1,
+            ProtoOaOrderStatus::OrderStatusAccepted,
+            "PalmOilBot",
+        )];
+        let mut filled = broker_order(2, ProtoOaOrderStatus::OrderStatusFilled, "PalmOilBot");
+        filled.position_id = Some(77);
+        let history = vec![
+            filled,
+            broker_order(3, ProtoOaOrderStatus::OrderStatusCancelled, "PalmOilBot"),
+        ];
+
+        let outcome = reconcile_orders(
+            &book,
+            &working,
+            &history,
+            UnknownOrderPolicy::Adopt,
+            "PalmOilBot",
+        );
+
+        assert_eq!(outcome.still_pending.len(), 1);
+        assert_eq!(outcome.filled.len(), 1);
+        assert_eq!(outcome.filled[0].1, Some(77));
+        assert_eq!(
+            outcome.finished[0].1,
+            ProtoOaOrderStatus::OrderStatusCancelled
+        );
+        assert_eq!(outcome.missing[0].order_id, 4);
+
+        let mut book = book;
+        book.apply(&outcome);
+        assert_eq!(
+            book.orders().map(|o| o.order_id).collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_unknown_bot_orders_follow_policy() {
+        let book = book_with(&[]);
+        let working = vec![
+            broker_order(10, ProtoOaOrderStatus::OrderStatusAccepted, "PalmOilBot"),
+            broker_order(11, ProtoOaOrderStatus::OrderStatusAccepted, "manual"),
+        ];
+
+        let adopt = reconcile_orders(
+            &book,
+            &working,
+            &[],
+            UnknownOrderPolicy::Adopt,
+            "PalmOilBot",
+        );
+        assert_eq!(adopt.adopt.len(), 1);
+        assert_eq!(adopt.adopt[0].order_id, 10);
+        assert!(adopt.cancel.is_empty());
+
+        let cancel = reconcile_orders(
+            &book,
+            &working,
+            &[],
+            UnknownOrderPolicy::Cancel,
+            "PalmOilBot",
+        );
+        assert_eq!(cancel.cancel.len(), 1);
+        assert_eq!(cancel.cancel[0].order_id, 10);
+
+        let ignore = reconcile_orders(
+            &book,
+            &working,
+            &[],
+            UnknownOrderPolicy::Ignore,
+            "PalmOilBot",
+        );
+        assert!(!ignore.has_changes());
+    }
+
+    #[test]
+    fn test_track_from_ticket_matches_broker_view() {
+        let ticket = OrderTicket {
+            symbol_id: 1,
+            side: crate::modules::trading::protobuf::ProtoOaTradeSide::Buy,
+            volume: 100,
+            order_type: ProtoOaOrderType::Limit,
+            limit_price: Some(4800.0),
+            stop_price: None,
+            expiration: None,
+            stop_loss: None,
+            take_profit: None,
+            relative_stop_loss: None,
+            relative_take_profit: None,
+            trailing_stop_loss: false,
+            label: Some("PalmOilBot".to_string()),
+            comment: None,
+        };
+        let tracked = TrackedOrder::from_ticket(7, &ticket);
+        let from_broker = TrackedOrder::from_broker(&broker_order(
+            7,
+            ProtoOaOrderStatus::OrderStatusAccepted,
+            "PalmOilBot",
+        ));
+        assert_eq!(
+            tracked,
+            TrackedOrder {
+                placed_at: tracked.placed_at,
+                ..from_broker
+            }
+        );
+    }
+}
@@ -4,24 +4,44 @@
 //! - Open positions
 //! - Closed trades (audit trail)
 //! - Daily statistics
+//! - Market briefs (long-form sentiment reports)
+//! - Sentiment history (every fresh sentiment reading, for analysis and
+//!   backtest replay)
+//! - Indicator samples (close / RSI / sentiment per candle, for charting)
+//! - Signal decisions (the strategy's factors and blockers per candle)
+//! - Closed candles, reloaded on startup to warm up indicators
 //!
 //! Complements JSON persistence with stronger consistency.
+//!
+//! Money columns stay `REAL` for compatibility with existing databases, but
+//! every amount is rounded to the account currency's decimals before it is
+//! stored and running totals are summed as [`Money`], so `daily_stats` does
+//! not pick up binary rounding drift trade after trade.
 
 use crate::error::{BotError, Result};
-use crate::modules::trading::{CloseReason, OrderSide, Position};
+use crate::modules::scraper::MarketBrief;
+use crate::modules::trading::{
+    Candle, CloseReason, OrderSide, Position, SignalDecision, TimeFrame,
+};
+use crate::modules::utils::money::{round_money, Money};
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::backup::Backup;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
 use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, info};
 
-/// SQLite database for positions
+/// SQLite database for positions (clones share the same connection)
+#[derive(Clone)]
 pub struct PositionDatabase {
     conn: Arc<Mutex<Connection>>,
+    /// Database file, for side connections that must not hold `conn`
+    path: Arc<PathBuf>,
 }
 
 impl PositionDatabase {
@@ -33,6 +53,7 @@ impl PositionDatabase {
 
         let db = Self {
             conn: Arc::new(Mutex::new(conn)),
+            path: Arc::new(db_path.as_ref().to_path_buf()),
         };
 
         db.init_schema()?;
@@ -56,7 +77,9 @@ impl PositionDatabase {
                 stop_loss REAL,
                 opened_at TEXT NOT NULL,
                 last_updated TEXT NOT NULL,
-                status TEXT NOT NULL DEFAULT 'open'
+                status TEXT NOT NULL DEFAULT 'open',
+                strategy_version TEXT,
+                features TEXT
             )",
             [],
         )
@@ -76,7 +99,9 @@ impl PositionDatabase {
                 realized_pnl REAL NOT NULL,
                 opened_at TEXT NOT NULL,
                 closed_at TEXT NOT NULL,
-                close_reason TEXT NOT NULL
+                close_reason TEXT NOT NULL,
+                strategy_version TEXT,
+                features TEXT
             )",
             [],
         )
@@ -97,6 +122,108 @@ impl PositionDatabase {
         )
         .map_err(|e| BotError::Config(format!("Failed to create daily_stats table: {}", e)))?;
 
+        // Market briefs table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS market_briefs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                confidence REAL NOT NULL,
+                content TEXT NOT NULL,
+                generated_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to create market_briefs table: {}", e)))?;
+
+        // Every sentiment reading the bot fetched
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sentiment_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                source TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                confidence REAL NOT NULL,
+                summary TEXT
+            )",
+            [],
+        )
+        .map_err(|e| {
+            BotError::Config(format!("Failed to create sentiment_history table: {}", e))
+        })?;
+
+        // Strategy decision per closed candle, factors and blockers as JSON
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS signal_decisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                decided_at TEXT NOT NULL,
+                signal TEXT NOT NULL,
+                score REAL NOT NULL,
+                side TEXT,
+                factors TEXT NOT NULL,
+                blockers TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| {
+            BotError::Config(format!("Failed to create signal_decisions table: {}", e))
+        })?;
+
+        // Leader lease for hot-standby pairs (one row per trading account)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS leader_lease (
+                scope TEXT PRIMARY KEY,
+                holder TEXT NOT NULL,
+                acquired_at_ms INTEGER NOT NULL,
+                heartbeat_at_ms INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to create leader_lease table: {}", e)))?;
+
+        // Per-candle indicator values for dashboards
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS indicator_samples (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                close REAL NOT NULL,
+                rsi REAL NOT NULL,
+                sentiment INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| {
+            BotError::Config(format!("Failed to create indicator_samples table: {}", e))
+        })?;
+
+        // Closed candles per symbol and timeframe, for indicator warm-up
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS candles (
+                symbol TEXT NOT NULL,
+                timeframe TEXT NOT NULL,
+                opened_at TEXT NOT NULL,
+                open REAL NOT NULL,
+                high REAL NOT NULL,
+                low REAL NOT NULL,
+                close REAL NOT NULL,
+                volume INTEGER NOT NULL,
+                avg_spread REAL,
+                close_spread REAL,
+                PRIMARY KEY (symbol, timeframe, opened_at)
+            )",
+            [],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to create candles table: {}", e)))?;
+
+        // Databases created before strategy versioning lack these columns
+        add_column_if_missing(&conn, "positions", "strategy_version", "TEXT")?;
+        add_column_if_missing(&conn, "closed_trades", "strategy_version", "TEXT")?;
+        add_column_if_missing(&conn, "positions", "features", "TEXT")?;
+        add_column_if_missing(&conn, "closed_trades", "features", "TEXT")?;
+
         // Indexes for performance
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_positions_status ON positions(status)",
@@ -108,6 +235,26 @@ impl PositionDatabase {
             [],
         )
         .ok();
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_market_briefs_symbol ON market_briefs(symbol, generated_at)",
+            [],
+        )
+        .ok();
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_indicator_samples_symbol ON indicator_samples(symbol, recorded_at)",
+            [],
+        )
+        .ok();
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sentiment_history_symbol ON sentiment_history(symbol, recorded_at)",
+            [],
+        )
+        .ok();
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_signal_decisions_symbol ON signal_decisions(symbol, decided_at)",
+            [],
+        )
+        .ok();
 
         info!("SQLite database schema initialized");
         Ok(())
@@ -124,8 +271,8 @@ impl PositionDatabase {
 
         conn.execute(
             "INSERT OR REPLACE INTO positions 
-             (id, broker_id, symbol, side, entry_price, volume, take_profit, stop_loss, opened_at, last_updated, status)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'open')",
+             (id, broker_id, symbol, side, entry_price, volume, take_profit, stop_loss, opened_at, last_updated, status, strategy_version, features)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'open', ?11, ?12)",
             params![
                 &position.id,
                 broker_id,
@@ -137,6 +284,8 @@ impl PositionDatabase {
                 position.stop_loss,
                 opened_at,
                 updated_at,
+                &position.strategy_version,
+                &position.features,
             ],
         )
         .map_err(|e| BotError::Config(format!("Failed to upsert position: {}", e)))?;
@@ -151,35 +300,11 @@ impl PositionDatabase {
 
         let result = conn
             .query_row(
-                "SELECT id, symbol, side, entry_price, volume, take_profit, stop_loss, opened_at
+                "SELECT id, symbol, side, entry_price, volume, take_profit, stop_loss, opened_at, strategy_version, features
                  FROM positions
                  WHERE id = ?1 AND status = 'open'",
                 params![id],
-                |row| {
-                    let id: String = row.get(0)?;
-                    let symbol: String = row.get(1)?;
-                    let side_str: String = row.get(2)?;
-                    let entry_price: f64 = row.get(3)?;
-                    let volume: f64 = row.get(4)?;
-                    let take_profit: Option<f64> = row.get(5)?;
-                    let stop_loss: Option<f64> = row.get(6)?;
-
-                    let side = match side_str.as_str() {
-                        "Buy" => OrderSide::Buy,
-                        "Sell" => OrderSide::Sell,
-                        _ => OrderSide::Buy,
-                    };
-
-                    let mut pos = Position::new(id, symbol, side, entry_price, volume);
-                    if let Some(tp) = take_profit {
-                        pos = pos.with_take_profit(tp);
-                    }
-                    if let Some(sl) = stop_loss {
-                        pos = pos.with_stop_loss(sl);
-                    }
-
-                    Ok(pos)
-                },
+                position_from_row,
             )
             .optional()
             .map_err(|e| BotError::Config(format!("Failed to get position: {}", e)))?;
@@ -193,7 +318,7 @@ impl PositionDatabase {
 
         let mut stmt = conn
             .prepare(
-                "SELECT id, symbol, side, entry_price, volume, take_profit, stop_loss, opened_at
+                "SELECT id, symbol, side, entry_price, volume, take_profit, stop_loss, opened_at, strategy_version, features
                  FROM positions
                  WHERE status = 'open'
                  ORDER BY opened_at DESC",
@@ -201,31 +326,7 @@ impl PositionDatabase {
             .map_err(|e| BotError::Config(format!("Failed to prepare statement: {}", e)))?;
 
         let positions = stmt
-            .query_map([], |row| {
-                let id: String = row.get(0)?;
-                let symbol: String = row.get(1)?;
-                let side_str: String = row.get(2)?;
-                let entry_price: f64 = row.get(3)?;
-                let volume: f64 = row.get(4)?;
-                let take_profit: Option<f64> = row.get(5)?;
-                let stop_loss: Option<f64> = row.get(6)?;
-
-                let side = match side_str.as_str() {
-                    "Buy" => OrderSide::Buy,
-                    "Sell" => OrderSide::Sell,
-                    _ => OrderSide::Buy,
-                };
-
-                let mut pos = Position::new(id, symbol, side, entry_price, volume);
-                if let Some(tp) = take_profit {
-                    pos = pos.with_take_profit(tp);
-                }
-                if let Some(sl) = stop_loss {
-                    pos = pos.with_stop_loss(sl);
-                }
-
-                Ok(pos)
-            })
+            .query_map([], position_from_row)
             .map_err(|e| BotError::Config(format!("Failed to query positions: {}", e)))?
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| BotError::Config(format!("Failed to collect positions: {}", e)))?;
@@ -243,20 +344,33 @@ impl PositionDatabase {
         let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
 
         // Get position data
-        let (symbol, side_str, entry_price, volume, opened_at, broker_id): (
+        let (symbol, side_str, entry_price, volume, opened_at, broker_id, strategy_version, features): (
             String,
             String,
             f64,
             f64,
             String,
             Option<i64>,
+            Option<String>,
+            Option<String>,
         ) = conn
             .query_row(
-                "SELECT symbol, side, entry_price, volume, opened_at, broker_id
+                "SELECT symbol, side, entry_price, volume, opened_at, broker_id, strategy_version, features
                  FROM positions
                  WHERE id = ?1 AND status = 'open'",
                 params![position_id],
-                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                    ))
+                },
             )
             .map_err(|e| BotError::Config(format!("Failed to get position for closing: {}", e)))?;
 
@@ -267,10 +381,10 @@ impl PositionDatabase {
             _ => OrderSide::Buy,
         };
 
-        let pnl = match side {
+        let pnl = round_money(match side {
             OrderSide::Buy => (exit_price - entry_price) * volume,
             OrderSide::Sell => (entry_price - exit_price) * volume,
-        };
+        });
 
         // Mark position as closed
         conn.execute(
@@ -283,8 +397,8 @@ impl PositionDatabase {
         // Insert into closed_trades
         conn.execute(
             "INSERT INTO closed_trades 
-             (position_id, broker_id, symbol, side, entry_price, exit_price, volume, realized_pnl, opened_at, closed_at, close_reason)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+             (position_id, broker_id, symbol, side, entry_price, exit_price, volume, realized_pnl, opened_at, closed_at, close_reason, strategy_version, features)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 position_id,
                 broker_id,
@@ -297,6 +411,8 @@ impl PositionDatabase {
                 opened_at,
                 Utc::now().to_rfc3339(),
                 format!("{:?}", close_reason),
+                strategy_version,
+                features,
             ],
         )
         .map_err(|e| BotError::Config(format!("Failed to insert closed trade: {}", e)))?;
@@ -322,6 +438,23 @@ impl PositionDatabase {
         Ok(())
     }
 
+    /// Mark an open position as gone from the broker without a recorded close
+    /// (e.g. stopped out while the bot was offline). No P&L is booked since the
+    /// exit price is unknown.
+    pub fn mark_position_missing(&self, position_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        conn.execute(
+            "UPDATE positions SET status = 'missing', last_updated = ?1
+             WHERE id = ?2 AND status = 'open'",
+            params![Utc::now().to_rfc3339(), position_id],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to update position status: {}", e)))?;
+
+        debug!("Position {} marked missing in SQLite", position_id);
+        Ok(())
+    }
+
     /// Get count of open positions
     pub fn count_open_positions(&self) -> Result<usize> {
         let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
@@ -370,6 +503,7 @@ impl PositionDatabase {
 
     /// Update daily statistics
     pub fn update_daily_stats(&self, date: &str, pnl: f64, is_win: bool) -> Result<()> {
+        let pnl = Money::from_f64(pnl);
         let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
 
         // Get existing stats or create new
@@ -395,7 +529,8 @@ impl PositionDatabase {
 
         let (total_pnl, total_trades, winning, losing, largest_win, largest_loss) = match existing {
             Some((tp, tt, w, l, lw, ll)) => {
-                let new_pnl = tp + pnl;
+                let (lw, ll) = (Money::from_f64(lw), Money::from_f64(ll));
+                let new_pnl = Money::from_f64(tp) + pnl;
                 let new_total = tt + 1;
                 let (new_win, new_lose) = if is_win { (w + 1, l) } else { (w, l + 1) };
                 let new_largest_win = if is_win { lw.max(pnl) } else { lw };
@@ -411,8 +546,8 @@ impl PositionDatabase {
             }
             None => {
                 let (win, lose) = if is_win { (1, 0) } else { (0, 1) };
-                let largest_win = if is_win { pnl } else { 0.0 };
-                let largest_loss = if !is_win { pnl } else { 0.0 };
+                let largest_win = if is_win { pnl } else { Money::ZERO };
+                let largest_loss = if !is_win { pnl } else { Money::ZERO };
                 (pnl, 1, win, lose, largest_win, largest_loss)
             }
         };
@@ -423,12 +558,12 @@ impl PositionDatabase {
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 date,
-                total_pnl,
+                total_pnl.to_f64(),
                 total_trades,
                 winning,
                 losing,
-                largest_win,
-                largest_loss
+                largest_win.to_f64(),
+                largest_loss.to_f64()
             ],
         )
         .map_err(|e| BotError::Config(format!("Failed to update daily stats: {}", e)))?;
@@ -436,6 +571,94 @@ impl PositionDatabase {
         Ok(())
     }
 
+    /// Recompute a day's `daily_stats` row from `closed_trades`.
+    ///
+    /// Idempotent: the row is rebuilt from scratch, so it can be re-run after
+    /// late closes. Returns `None` (and writes nothing) for days without trades.
+    pub fn rollup_daily_stats(&self, date: &str) -> Result<Option<DailyStats>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let stats = conn
+            .query_row(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(realized_pnl), 0.0),
+                        COALESCE(SUM(CASE WHEN realized_pnl > 0 THEN 1 ELSE 0 END), 0),
+                        COALESCE(MAX(CASE WHEN realized_pnl > 0 THEN realized_pnl ELSE 0.0 END), 0.0),
+                        COALESCE(MIN(CASE WHEN realized_pnl <= 0 THEN realized_pnl ELSE 0.0 END), 0.0)
+                 FROM closed_trades
+                 WHERE DATE(closed_at) = DATE(?1)",
+                params![date],
+                |row| {
+                    let total_trades: i64 = row.get(0)?;
+                    let winning_trades: i64 = row.get(2)?;
+                    // The stored amounts are already rounded, so rounding the
+                    // float SUM recovers the exact total
+                    Ok(DailyStats {
+                        date: date.to_string(),
+                        total_pnl: round_money(row.get(1)?),
+                        total_trades,
+                        winning_trades,
+                        losing_trades: total_trades - winning_trades,
+                        largest_win: round_money(row.get(3)?),
+                        largest_loss: round_money(row.get(4)?),
+                    })
+                },
+            )
+            .map_err(|e| BotError::Config(format!("Failed to aggregate daily stats: {}", e)))?;
+
+        if stats.total_trades == 0 {
+            return Ok(None);
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO daily_stats
+             (date, total_pnl, total_trades, winning_trades, losing_trades, largest_win, largest_loss)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                stats.date,
+                stats.total_pnl,
+                stats.total_trades,
+                stats.winning_trades,
+                stats.losing_trades,
+                stats.largest_win,
+                stats.largest_loss
+            ],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to update daily stats: {}", e)))?;
+
+        Ok(Some(stats))
+    }
+
+    /// Roll up every day before `before` (YYYY-MM-DD) that has closed trades
+    /// but no `daily_stats` row. Returns the dates that were filled in.
+    pub fn backfill_daily_stats(&self, before: &str) -> Result<Vec<String>> {
+        let missing: Vec<String> = {
+            let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+            let mut stmt = conn
+                .prepare(
+                    "SELECT DISTINCT DATE(closed_at) AS day
+                     FROM closed_trades
+                     WHERE DATE(closed_at) < DATE(?1)
+                       AND DATE(closed_at) NOT IN (SELECT date FROM daily_stats)
+                     ORDER BY day",
+                )
+                .map_err(|e| BotError::Config(format!("Failed to prepare backfill query: {}", e)))?;
+
+            let days = stmt
+                .query_map(params![before], |row| row.get::<_, String>(0))
+                .map_err(|e| BotError::Config(format!("Failed to query missing days: {}", e)))?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| BotError::Config(format!("Failed to collect missing days: {}", e)))?;
+            days
+        };
+
+        for day in &missing {
+            self.rollup_daily_stats(day)?;
+        }
+
+        Ok(missing)
+    }
+
     /// Get statistics for a date
     pub fn get_daily_stats(&self, date: &str) -> Result<Option<DailyStats>> {
         let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
@@ -470,7 +693,7 @@ impl PositionDatabase {
 
         let mut stmt = conn
             .prepare(
-                "SELECT position_id, broker_id, symbol, side, entry_price, exit_price, volume, realized_pnl, opened_at, closed_at, close_reason
+                "SELECT position_id, broker_id, symbol, side, entry_price, exit_price, volume, realized_pnl, opened_at, closed_at, close_reason, strategy_version, features
                  FROM closed_trades
                  ORDER BY closed_at",
             )
@@ -490,6 +713,8 @@ impl PositionDatabase {
                     opened_at: row.get(8)?,
                     closed_at: row.get(9)?,
                     close_reason: row.get(10)?,
+                    strategy_version: row.get(11)?,
+                    features: row.get(12)?,
                 })
             })
             .map_err(|e| BotError::Config(format!("Failed to query closed trades: {}", e)))?
@@ -499,22 +724,543 @@ impl PositionDatabase {
         Ok(records)
     }
 
+    /// Closed-trade performance grouped by strategy version, oldest version first
+    pub fn get_strategy_version_stats(&self) -> Result<Vec<StrategyVersionStats>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT strategy_version,
+                        COUNT(*),
+                        SUM(CASE WHEN realized_pnl > 0 THEN 1 ELSE 0 END),
+                        SUM(realized_pnl),
+                        MIN(closed_at),
+                        MAX(closed_at)
+                 FROM closed_trades
+                 GROUP BY strategy_version
+                 ORDER BY MIN(closed_at)",
+            )
+            .map_err(|e| BotError::Config(format!("Failed to prepare strategy stats: {}", e)))?;
+
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(StrategyVersionStats {
+                    strategy_version: row.get(0)?,
+                    total_trades: row.get(1)?,
+                    winning_trades: row.get(2)?,
+                    total_pnl: round_money(row.get(3)?),
+                    first_closed_at: row.get(4)?,
+                    last_closed_at: row.get(5)?,
+                })
+            })
+            .map_err(|e| BotError::Config(format!("Failed to query strategy stats: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BotError::Config(format!("Failed to collect strategy stats: {}", e)))?;
+
+        Ok(stats)
+    }
+
+    /// Store a market brief
+    pub fn save_market_brief(&self, brief: &MarketBrief) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        conn.execute(
+            "INSERT INTO market_briefs (symbol, score, confidence, content, generated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                &brief.symbol,
+                brief.score,
+                brief.confidence,
+                &brief.content,
+                brief.generated_at.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to save market brief: {}", e)))?;
+
+        debug!("Market brief for {} saved to SQLite", brief.symbol);
+        Ok(())
+    }
+
+    /// Get the most recent market brief for a symbol
+    pub fn get_latest_market_brief(&self, symbol: &str) -> Result<Option<MarketBrief>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let result = conn
+            .query_row(
+                "SELECT symbol, score, confidence, content, generated_at
+                 FROM market_briefs
+                 WHERE symbol = ?1
+                 ORDER BY generated_at DESC, id DESC
+                 LIMIT 1",
+                params![symbol],
+                |row| {
+                    let generated_at: String = row.get(4)?;
+                    Ok(MarketBrief {
+                        symbol: row.get(0)?,
+                        score: row.get(1)?,
+                        confidence: row.get(2)?,
+                        content: row.get(3)?,
+                        generated_at: DateTime::parse_from_rfc3339(&generated_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| BotError::Config(format!("Failed to get market brief: {}", e)))?;
+
+        Ok(result)
+    }
+
+    /// Get all market briefs for a symbol, oldest first
+    pub fn get_market_briefs(&self, symbol: &str) -> Result<Vec<MarketBrief>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT symbol, score, confidence, content, generated_at
+                 FROM market_briefs
+                 WHERE symbol = ?1
+                 ORDER BY generated_at, id",
+            )
+            .map_err(|e| BotError::Config(format!("Failed to prepare market briefs: {}", e)))?;
+
+        let briefs = stmt
+            .query_map(params![symbol], |row| {
+                let generated_at: String = row.get(4)?;
+                Ok(MarketBrief {
+                    symbol: row.get(0)?,
+                    score: row.get(1)?,
+                    confidence: row.get(2)?,
+                    content: row.get(3)?,
+                    generated_at: DateTime::parse_from_rfc3339(&generated_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .map_err(|e| BotError::Config(format!("Failed to query market briefs: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BotError::Config(format!("Failed to collect market briefs: {}", e)))?;
+
+        Ok(briefs)
+    }
+
+    /// Store one sentiment reading
+    pub fn save_sentiment(&self, record: &SentimentRecord) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        conn.execute(
+            "INSERT INTO sentiment_history (symbol, recorded_at, source, score, confidence, summary)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                &record.symbol,
+                record.recorded_at.to_rfc3339(),
+                &record.source,
+                record.score,
+                record.confidence,
+                record.summary.as_deref(),
+            ],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to save sentiment: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Sentiment readings for a symbol, oldest first, optionally limited to
+    /// `from <= recorded_at <= to`
+    pub fn get_sentiment_history(
+        &self,
+        symbol: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SentimentRecord>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT symbol, recorded_at, source, score, confidence, summary
+                 FROM sentiment_history
+                 WHERE symbol = ?1
+                   AND (?2 IS NULL OR recorded_at >= ?2)
+                   AND (?3 IS NULL OR recorded_at <= ?3)
+                 ORDER BY recorded_at, id",
+            )
+            .map_err(|e| BotError::Config(format!("Failed to prepare sentiment history: {}", e)))?;
+
+        let records = stmt
+            .query_map(
+                params![
+                    symbol,
+                    from.map(|t| t.to_rfc3339()),
+                    to.map(|t| t.to_rfc3339())
+                ],
+                |row| {
+                    let recorded_at: String = row.get(1)?;
+                    Ok(SentimentRecord {
+                        symbol: row.get(0)?,
+                        recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        source: row.get(2)?,
+                        score: row.get(3)?,
+                        confidence: row.get(4)?,
+                        summary: row.get(5)?,
+                    })
+                },
+            )
+            .map_err(|e| BotError::Config(format!("Failed to query sentiment history: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BotError::Config(format!("Failed to collect sentiment history: {}", e)))?;
+
+        Ok(records)
+    }
+
+    /// Store the strategy's decision for the candle closed at `decided_at`
+    pub fn save_signal_decision(
+        &self,
+        symbol: &str,
+        decided_at: DateTime<Utc>,
+        decision: &SignalDecision,
+    ) -> Result<()> {
+        let factors = serde_json::to_string(&decision.factors)?;
+        let blockers = serde_json::to_string(&decision.blockers)?;
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        conn.execute(
+            "INSERT INTO signal_decisions (symbol, decided_at, signal, score, side, factors, blockers)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                symbol,
+                decided_at.to_rfc3339(),
+                decision.signal.to_string(),
+                decision.score,
+                decision.side.map(|side| side.to_string()),
+                factors,
+                blockers,
+            ],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to save signal decision: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Signal decisions for a symbol, oldest first, optionally limited to
+    /// `from <= decided_at <= to`
+    pub fn get_signal_decisions(
+        &self,
+        symbol: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SignalDecisionRecord>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT symbol, decided_at, signal, score, side, factors, blockers
+                 FROM signal_decisions
+                 WHERE symbol = ?1
+                   AND (?2 IS NULL OR decided_at >= ?2)
+                   AND (?3 IS NULL OR decided_at <= ?3)
+                 ORDER BY decided_at, id",
+            )
+            .map_err(|e| BotError::Config(format!("Failed to prepare signal decisions: {}", e)))?;
+
+        let records = stmt
+            .query_map(
+                params![
+                    symbol,
+                    from.map(|t| t.to_rfc3339()),
+                    to.map(|t| t.to_rfc3339())
+                ],
+                |row| {
+                    let decided_at: String = row.get(1)?;
+                    let factors: String = row.get(5)?;
+                    let blockers: String = row.get(6)?;
+                    Ok(SignalDecisionRecord {
+                        symbol: row.get(0)?,
+                        decided_at: DateTime::parse_from_rfc3339(&decided_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        signal: row.get(2)?,
+                        score: row.get(3)?,
+                        side: row.get(4)?,
+                        factors: serde_json::from_str(&factors)
+                            .unwrap_or(serde_json::Value::Null),
+                        blockers: serde_json::from_str(&blockers).unwrap_or_default(),
+                    })
+                },
+            )
+            .map_err(|e| BotError::Config(format!("Failed to query signal decisions: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BotError::Config(format!("Failed to collect signal decisions: {}", e)))?;
+
+        Ok(records)
+    }
+
+    /// Export a symbol's sentiment history to CSV file
+    pub fn export_sentiment_history_csv(&self, symbol: &str, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = File::create(path.as_ref())
+            .map_err(|e| BotError::Config(format!("Failed to create export file: {}", e)))?;
+        self.write_sentiment_history_csv(symbol, &mut file)
+    }
+
+    /// Write a symbol's sentiment history as CSV (`timestamp,source,score,
+    /// confidence,summary`, readable by the backtest's `--sentiment-csv`)
+    pub fn write_sentiment_history_csv(&self, symbol: &str, out: &mut impl Write) -> Result<()> {
+        let records = self.get_sentiment_history(symbol, None, None)?;
+        writeln!(out, "timestamp,source,score,confidence,summary")
+            .map_err(|e| BotError::Config(format!("Failed to write CSV header: {}", e)))?;
+
+        for record in records {
+            // One line per reading; the free-text summary goes last, quoted
+            let summary = record
+                .summary
+                .as_deref()
+                .map(|s| format!("\"{}\"", s.replace(['\n', '\r'], " ").replace('"', "\"\"")))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "{},{},{},{:.4},{}",
+                record.recorded_at.to_rfc3339(),
+                record.source,
+                record.score,
+                record.confidence,
+                summary
+            )
+            .map_err(|e| BotError::Config(format!("Failed to write CSV row: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Store one candle's indicator values
+    pub fn save_indicator_sample(&self, sample: &IndicatorSample) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        conn.execute(
+            "INSERT INTO indicator_samples (symbol, recorded_at, close, rsi, sentiment)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                &sample.symbol,
+                sample.recorded_at.to_rfc3339(),
+                sample.close,
+                sample.rsi,
+                sample.sentiment,
+            ],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to save indicator sample: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Indicator samples for a symbol with `from <= recorded_at <= to`, oldest first
+    pub fn get_indicator_samples(
+        &self,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<IndicatorSample>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT symbol, recorded_at, close, rsi, sentiment
+                 FROM indicator_samples
+                 WHERE symbol = ?1 AND recorded_at >= ?2 AND recorded_at <= ?3
+                 ORDER BY recorded_at, id",
+            )
+            .map_err(|e| BotError::Config(format!("Failed to prepare indicator samples: {}", e)))?;
+
+        let samples = stmt
+            .query_map(
+                params![symbol, from.to_rfc3339(), to.to_rfc3339()],
+                |row| {
+                    let recorded_at: String = row.get(1)?;
+                    Ok(IndicatorSample {
+                        symbol: row.get(0)?,
+                        recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        close: row.get(2)?,
+                        rsi: row.get(3)?,
+                        sentiment: row.get(4)?,
+                    })
+                },
+            )
+            .map_err(|e| BotError::Config(format!("Failed to query indicator samples: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BotError::Config(format!("Failed to collect indicator samples: {}", e)))?;
+
+        Ok(samples)
+    }
+
+    /// Store a closed candle, replacing any stored candle with the same start
+    pub fn save_candle(&self, symbol: &str, candle: &Candle) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        conn.execute(
+            "INSERT OR REPLACE INTO candles (symbol, timeframe, opened_at,
+                 open, high, low, close, volume, avg_spread, close_spread)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                symbol,
+                candle.timeframe.to_string(),
+                candle.timestamp.to_rfc3339(),
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                candle.volume as i64,
+                candle.avg_spread,
+                candle.close_spread,
+            ],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to save candle: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// The `limit` most recent stored `timeframe` candles for a symbol, oldest first
+    pub fn get_recent_candles(
+        &self,
+        symbol: &str,
+        timeframe: TimeFrame,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT opened_at, open, high, low, close, volume, avg_spread, close_spread
+                 FROM candles
+                 WHERE symbol = ?1 AND timeframe = ?2
+                 ORDER BY opened_at DESC
+                 LIMIT ?3",
+            )
+            .map_err(|e| BotError::Config(format!("Failed to prepare candles query: {}", e)))?;
+
+        let mut candles = stmt
+            .query_map(
+                params![symbol, timeframe.to_string(), limit as i64],
+                |row| {
+                    let opened_at: String = row.get(0)?;
+                    let volume: i64 = row.get(5)?;
+                    Ok(Candle {
+                        timestamp: DateTime::parse_from_rfc3339(&opened_at)
+                            .map_err(|e| {
+                                rusqlite::Error::FromSqlConversionFailure(
+                                    0,
+                                    rusqlite::types::Type::Text,
+                                    Box::new(e),
+                                )
+                            })?
+                            .with_timezone(&Utc),
+                        timeframe,
+                        open: row.get(1)?,
+                        high: row.get(2)?,
+                        low: row.get(3)?,
+                        close: row.get(4)?,
+                        volume: volume.max(0) as u64,
+                        avg_spread: row.get(6)?,
+                        close_spread: row.get(7)?,
+                    })
+                },
+            )
+            .map_err(|e| BotError::Config(format!("Failed to query candles: {}", e)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BotError::Config(format!("Failed to collect candles: {}", e)))?;
+        candles.reverse();
+
+        Ok(candles)
+    }
+
+    /// Take or renew the leader lease for `scope`. Succeeds when `holder`
+    /// already owns it or the current holder's heartbeat is older than `ttl_ms`.
+    pub fn try_acquire_leader_lease(
+        &self,
+        scope: &str,
+        holder: &str,
+        now_ms: i64,
+        ttl_ms: i64,
+    ) -> Result<bool> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let changed = conn
+            .execute(
+                "INSERT INTO leader_lease (scope, holder, acquired_at_ms, heartbeat_at_ms)
+                 VALUES (?1, ?2, ?3, ?3)
+                 ON CONFLICT(scope) DO UPDATE SET
+                    acquired_at_ms = CASE WHEN leader_lease.holder = excluded.holder
+                        THEN leader_lease.acquired_at_ms ELSE excluded.acquired_at_ms END,
+                    holder = excluded.holder,
+                    heartbeat_at_ms = excluded.heartbeat_at_ms
+                 WHERE leader_lease.holder = excluded.holder
+                    OR leader_lease.heartbeat_at_ms < ?4",
+                params![scope, holder, now_ms, now_ms - ttl_ms],
+            )
+            .map_err(|e| BotError::Config(format!("Failed to update leader lease: {}", e)))?;
+        Ok(changed == 1)
+    }
+
+    /// Give up the lease so a standby can take over without waiting for expiry
+    pub fn release_leader_lease(&self, scope: &str, holder: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute(
+            "DELETE FROM leader_lease WHERE scope = ?1 AND holder = ?2",
+            params![scope, holder],
+        )
+        .map_err(|e| BotError::Config(format!("Failed to release leader lease: {}", e)))?;
+        Ok(())
+    }
+
+    /// Current lease holder and last heartbeat (ms since epoch) for `scope`
+    pub fn get_leader_lease(&self, scope: &str) -> Result<Option<(String, i64)>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row(
+            "SELECT holder, heartbeat_at_ms FROM leader_lease WHERE scope = ?1",
+            params![scope],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| BotError::Config(format!("Failed to read leader lease: {}", e)))
+    }
+
+    /// Write a consistent copy of the whole database to `path` using the SQLite
+    /// online backup API (safe while the bot keeps writing)
+    pub fn backup_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let backup_err =
+            |e: rusqlite::Error| BotError::Config(format!("Failed to back up database: {}", e));
+        // A read connection of its own, so the bot's writes never wait on the
+        // shared one while the copy runs
+        let source =
+            Connection::open_with_flags(self.path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(backup_err)?;
+        let mut target = Connection::open(path.as_ref()).map_err(backup_err)?;
+        // All pages in one step: a write between steps would restart the copy
+        Backup::new(&source, &mut target)
+            .and_then(|backup| backup.run_to_completion(-1, Duration::ZERO, None))
+            .map_err(backup_err)
+    }
+
     /// Export closed trades to CSV file
     pub fn export_closed_trades_csv(&self, path: impl AsRef<Path>) -> Result<()> {
-        let records = self.get_closed_trades()?;
         let mut file = File::create(path.as_ref())
             .map_err(|e| BotError::Config(format!("Failed to create export file: {}", e)))?;
+        self.write_closed_trades_csv(&mut file)
+    }
 
+    /// Write closed trades as CSV to any writer (file, HTTP body buffer)
+    pub fn write_closed_trades_csv(&self, out: &mut impl Write) -> Result<()> {
+        let records = self.get_closed_trades()?;
         writeln!(
-            file,
-            "position_id,broker_id,symbol,side,entry_price,exit_price,volume,realized_pnl,opened_at,closed_at,close_reason"
+            out,
+            "position_id,broker_id,symbol,side,entry_price,exit_price,volume,realized_pnl,opened_at,closed_at,close_reason,strategy_version,features"
         )
         .map_err(|e| BotError::Config(format!("Failed to write CSV header: {}", e)))?;
 
         for record in records {
             writeln!(
-                file,
-                "{},{},{},{},{:.5},{:.5},{:.4},{:.4},{},{},{}",
+                out,
+                "{},{},{},{},{:.5},{:.5},{:.4},{:.4},{},{},{},{},\"{}\"",
                 record.position_id,
                 record
                     .broker_id
@@ -528,7 +1274,9 @@ impl PositionDatabase {
                 record.realized_pnl,
                 record.opened_at,
                 record.closed_at,
-                record.close_reason
+                record.close_reason,
+                record.strategy_version.as_deref().unwrap_or(""),
+                record.features.as_deref().unwrap_or("")
             )
             .map_err(|e| BotError::Config(format!("Failed to write CSV row: {}", e)))?;
         }
@@ -538,16 +1286,21 @@ impl PositionDatabase {
 
     /// Export closed trades to JSON file
     pub fn export_closed_trades_json(&self, path: impl AsRef<Path>) -> Result<()> {
-        let records = self.get_closed_trades()?;
-        let payload = serde_json::to_string_pretty(&records)
-            .map_err(|e| BotError::Config(format!("Failed to serialize JSON: {}", e)))?;
+        let payload = self.closed_trades_json()?;
         std::fs::write(path.as_ref(), payload)
             .map_err(|e| BotError::Config(format!("Failed to write JSON file: {}", e)))?;
         Ok(())
     }
 
-    /// Export daily stats to CSV file
-    pub fn export_daily_stats_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+    /// Closed trades as a pretty-printed JSON array
+    pub fn closed_trades_json(&self) -> Result<String> {
+        let records = self.get_closed_trades()?;
+        serde_json::to_string_pretty(&records)
+            .map_err(|e| BotError::Config(format!("Failed to serialize JSON: {}", e)))
+    }
+
+    /// All daily statistics rows, oldest first
+    pub fn get_all_daily_stats(&self) -> Result<Vec<DailyStats>> {
         let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
         let mut stmt = conn
             .prepare(
@@ -573,17 +1326,28 @@ impl PositionDatabase {
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| BotError::Config(format!("Failed to collect daily stats: {}", e)))?;
 
+        Ok(rows)
+    }
+
+    /// Export daily stats to CSV file
+    pub fn export_daily_stats_csv(&self, path: impl AsRef<Path>) -> Result<()> {
         let mut file = File::create(path.as_ref())
             .map_err(|e| BotError::Config(format!("Failed to create export file: {}", e)))?;
+        self.write_daily_stats_csv(&mut file)
+    }
+
+    /// Write daily stats as CSV to any writer
+    pub fn write_daily_stats_csv(&self, out: &mut impl Write) -> Result<()> {
+        let rows = self.get_all_daily_stats()?;
         writeln!(
-            file,
+            out,
             "date,total_pnl,total_trades,winning_trades,losing_trades,largest_win,largest_loss"
         )
         .map_err(|e| BotError::Config(format!("Failed to write CSV header: {}", e)))?;
 
         for row in rows {
             writeln!(
-                file,
+                out,
                 "{},{:.4},{},{},{},{:.4},{:.4}",
                 row.date,
                 row.total_pnl,
@@ -601,7 +1365,7 @@ impl PositionDatabase {
 }
 
 /// Daily statistics record
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DailyStats {
     pub date: String,
     pub total_pnl: f64,
@@ -636,6 +1400,128 @@ pub struct ClosedTradeRecord {
     pub opened_at: String,
     pub closed_at: String,
     pub close_reason: String,
+    /// Strategy version/config fingerprint the trade was opened under
+    pub strategy_version: Option<String>,
+    /// Strategy features active at entry (`StrategyFeatures::tag`)
+    pub features: Option<String>,
+}
+
+/// Indicator values recorded at one candle close
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndicatorSample {
+    pub symbol: String,
+    pub recorded_at: DateTime<Utc>,
+    pub close: f64,
+    pub rsi: f64,
+    pub sentiment: i32,
+}
+
+/// One sentiment reading as the bot resolved it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SentimentRecord {
+    pub symbol: String,
+    pub recorded_at: DateTime<Utc>,
+    /// Provider the reading came from (`blend(...)` when several were combined)
+    pub source: String,
+    /// Score from -100 to +100
+    pub score: i32,
+    /// Confidence from 0.0 to 1.0
+    pub confidence: f64,
+    /// Raw provider text or per-provider breakdown
+    pub summary: Option<String>,
+}
+
+/// One stored signal decision
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalDecisionRecord {
+    pub symbol: String,
+    /// Close time of the candle the decision was made on
+    pub decided_at: DateTime<Utc>,
+    /// `BUY`, `SELL` or `HOLD`
+    pub signal: String,
+    pub score: f64,
+    pub side: Option<String>,
+    /// The decision's factors, as stored
+    pub factors: serde_json::Value,
+    /// Why the signal was not traded
+    pub blockers: Vec<String>,
+}
+
+/// Aggregated closed-trade performance for one strategy version
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyVersionStats {
+    /// `None` for trades recorded before versioning was introduced
+    pub strategy_version: Option<String>,
+    pub total_trades: i64,
+    pub winning_trades: i64,
+    pub total_pnl: f64,
+    pub first_closed_at: String,
+    pub last_closed_at: String,
+}
+
+impl StrategyVersionStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.total_trades == 0 {
+            0.0
+        } else {
+            (self.winning_trades as f64 / self.total_trades as f64) * 100.0
+        }
+    }
+}
+
+/// Add a column to an existing table if an older schema lacks it
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|e| BotError::Config(format!("Failed to inspect {} table: {}", table, e)))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| BotError::Config(format!("Failed to inspect {} table: {}", table, e)))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl), [])
+            .map_err(|e| BotError::Config(format!("Failed to add {}.{}: {}", table, column, e)))?;
+        info!("Migrated {} table: added {} column", table, column);
+    }
+    Ok(())
+}
+
+/// Map a `positions` row (id, symbol, side, entry_price, volume, take_profit,
+/// stop_loss, opened_at, strategy_version, features) back into a `Position`
+fn position_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Position> {
+    let id: String = row.get(0)?;
+    let symbol: String = row.get(1)?;
+    let side_str: String = row.get(2)?;
+    let entry_price: f64 = row.get(3)?;
+    let volume: f64 = row.get(4)?;
+    let take_profit: Option<f64> = row.get(5)?;
+    let stop_loss: Option<f64> = row.get(6)?;
+    let opened_at: String = row.get(7)?;
+    let strategy_version: Option<String> = row.get(8)?;
+    let features: Option<String> = row.get(9)?;
+
+    let side = match side_str.as_str() {
+        "Buy" => OrderSide::Buy,
+        "Sell" => OrderSide::Sell,
+        _ => OrderSide::Buy,
+    };
+
+    let mut pos = Position::new(id, symbol, side, entry_price, volume);
+    if let Some(tp) = take_profit {
+        pos = pos.with_take_profit(tp);
+    }
+    if let Some(sl) = stop_loss {
+        pos = pos.with_stop_loss(sl);
+    }
+    if let Ok(opened_at) = DateTime::parse_from_rfc3339(&opened_at) {
+        pos.opened_at = opened_at.with_timezone(&Utc);
+    }
+    pos.strategy_version = strategy_version;
+    pos.features = features;
+
+    Ok(pos)
 }
 
 #[cfg(test)]
@@ -713,6 +1599,36 @@ mod tests {
         assert_eq!(db.count_open_positions().unwrap(), 0);
     }
 
+    #[test]
+    fn test_open_position_keeps_entry_metadata() {
+        let (db, _dir) = create_test_db();
+
+        let mut pos = create_test_position("77", "FCPO", OrderSide::Sell, 4900.0)
+            .with_take_profit(4800.0)
+            .with_stop_loss(4950.0)
+            .with_strategy_version("1.0.0-deadbeef");
+        pos.opened_at = Utc::now() - chrono::Duration::hours(6);
+        db.upsert_position(&pos).unwrap();
+
+        let restored = db.get_open_positions().unwrap().remove(0);
+        assert_eq!(restored.take_profit, Some(4800.0));
+        assert_eq!(restored.stop_loss, Some(4950.0));
+        assert_eq!(restored.strategy_version.as_deref(), Some("1.0.0-deadbeef"));
+        assert_eq!(restored.opened_at.timestamp(), pos.opened_at.timestamp());
+    }
+
+    #[test]
+    fn test_mark_position_missing() {
+        let (db, _dir) = create_test_db();
+
+        db.upsert_position(&create_test_position("88", "FCPO", OrderSide::Buy, 4850.0))
+            .unwrap();
+        db.mark_position_missing("88").unwrap();
+
+        assert_eq!(db.count_open_positions().unwrap(), 0);
+        assert!(db.get_closed_trades().unwrap().is_empty());
+    }
+
     #[test]
     fn test_daily_stats() {
         let (db, _dir) = create_test_db();
@@ -730,6 +1646,74 @@ mod tests {
         assert!((stats.win_rate() - 50.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_daily_stats_total_does_not_drift() {
+        let (db, _dir) = create_test_db();
+        let today = Utc::now().date_naive().to_string();
+
+        for _ in 0..100 {
+            db.update_daily_stats(&today, 0.1, true).unwrap();
+            db.update_daily_stats(&today, 0.2, true).unwrap();
+            db.update_daily_stats(&today, -0.3, false).unwrap();
+        }
+
+        let stats = db.get_daily_stats(&today).unwrap().unwrap();
+        assert_eq!(stats.total_pnl, 0.0);
+        assert_eq!(stats.largest_win, 0.2);
+        assert_eq!(stats.largest_loss, -0.3);
+    }
+
+    fn insert_closed_trade(db: &PositionDatabase, id: &str, pnl: f64, closed_at: &str) {
+        let conn = db.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO closed_trades
+             (position_id, symbol, side, entry_price, exit_price, volume, realized_pnl, opened_at, closed_at, close_reason)
+             VALUES (?1, 'FCPO', 'Buy', 4850.0, 4850.0, 1.0, ?2, ?3, ?3, 'Manual')",
+            params![id, pnl, closed_at],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_rollup_daily_stats_is_idempotent() {
+        let (db, _dir) = create_test_db();
+        insert_closed_trade(&db, "a", 40.0, "2024-03-04T08:00:00.123456+00:00");
+        insert_closed_trade(&db, "b", -15.0, "2024-03-04T11:30:00+00:00");
+        insert_closed_trade(&db, "c", 25.0, "2024-03-05T09:00:00+00:00");
+
+        db.rollup_daily_stats("2024-03-04").unwrap();
+        let stats = db.rollup_daily_stats("2024-03-04").unwrap().unwrap();
+        assert_eq!(stats.total_trades, 2);
+        assert_eq!(stats.winning_trades, 1);
+        assert_eq!(stats.losing_trades, 1);
+        assert!((stats.total_pnl - 25.0).abs() < 1e-9);
+        assert!((stats.largest_win - 40.0).abs() < 1e-9);
+        assert!((stats.largest_loss + 15.0).abs() < 1e-9);
+
+        let stored = db.get_daily_stats("2024-03-04").unwrap().unwrap();
+        assert_eq!(stored.total_trades, 2);
+
+        assert!(db.rollup_daily_stats("2024-03-06").unwrap().is_none());
+        assert!(db.get_daily_stats("2024-03-06").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_backfill_daily_stats_fills_missing_days_only() {
+        let (db, _dir) = create_test_db();
+        insert_closed_trade(&db, "a", 10.0, "2024-03-04T08:00:00+00:00");
+        insert_closed_trade(&db, "b", -5.0, "2024-03-05T08:00:00+00:00");
+        insert_closed_trade(&db, "c", 7.0, "2024-03-06T08:00:00+00:00");
+        db.rollup_daily_stats("2024-03-05").unwrap();
+
+        let filled = db.backfill_daily_stats("2024-03-06").unwrap();
+        assert_eq!(filled, vec!["2024-03-04".to_string()]);
+        assert!(db.get_daily_stats("2024-03-04").unwrap().is_some());
+        // The day in progress is left for the end-of-day roll-up
+        assert!(db.get_daily_stats("2024-03-06").unwrap().is_none());
+
+        assert!(db.backfill_daily_stats("2024-03-06").unwrap().is_empty());
+    }
+
     #[test]
     fn test_export_closed_trades_csv() {
         let (db, _dir) = create_test_db();
@@ -778,4 +1762,259 @@ mod tests {
         assert!(content.contains("date,total_pnl"));
         assert!(content.contains(&today));
     }
+
+    #[test]
+    fn test_candles_reload_most_recent() {
+        let (db, _temp) = create_test_db();
+        let start = DateTime::parse_from_rfc3339("2024-03-04T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let candle = |i: i32, timeframe: TimeFrame| Candle {
+            timestamp: start + timeframe.to_duration() * i,
+            timeframe,
+            open: 4850.0,
+            high: 4855.0,
+            low: 4845.0,
+            close: 4850.0 + i as f64,
+            volume: 12,
+            avg_spread: Some(1.5),
+            close_spread: None,
+        };
+        for i in 0..5 {
+            db.save_candle("FCPO", &candle(i, TimeFrame::M5)).unwrap();
+        }
+        db.save_candle("FCPO", &candle(0, TimeFrame::H1)).unwrap();
+        // Saving a candle again replaces it
+        let mut revised = candle(4, TimeFrame::M5);
+        revised.close = 4860.0;
+        db.save_candle("FCPO", &revised).unwrap();
+
+        let candles = db.get_recent_candles("FCPO", TimeFrame::M5, 3).unwrap();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        assert_eq!(closes, vec![4852.0, 4853.0, 4860.0]);
+        assert_eq!(candles[0].timestamp, start + chrono::Duration::minutes(10));
+        assert_eq!(candles[0].volume, 12);
+        assert_eq!(candles[0].avg_spread, Some(1.5));
+        assert_eq!(db.get_recent_candles("FCPO", TimeFrame::H1, 10).unwrap().len(), 1);
+        assert!(db.get_recent_candles("OTHER", TimeFrame::M5, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_indicator_samples_range() {
+        let (db, _temp) = create_test_db();
+        let start = DateTime::parse_from_rfc3339("2024-03-04T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for i in 0..4 {
+            db.save_indicator_sample(&IndicatorSample {
+                symbol: "FCPO".to_string(),
+                recorded_at: start + chrono::Duration::minutes(5 * i),
+                close: 4850.0 + i as f64,
+                rsi: 40.0 + i as f64,
+                sentiment: 10,
+            })
+            .unwrap();
+        }
+
+        let samples = db
+            .get_indicator_samples(
+                "FCPO",
+                start + chrono::Duration::minutes(5),
+                start + chrono::Duration::minutes(10),
+            )
+            .unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].close, 4851.0);
+        assert_eq!(samples[1].rsi, 42.0);
+
+        let other = db
+            .get_indicator_samples("OTHER", start, start + chrono::Duration::hours(1))
+            .unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn test_sentiment_history_range_and_export() {
+        let (db, _temp) = create_test_db();
+        let start = DateTime::parse_from_rfc3339("2024-03-04T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for (i, (source, score)) in [("perplexity", 35), ("fallback", 0), ("twitter", -20)]
+            .into_iter()
+            .enumerate()
+        {
+            db.save_sentiment(&SentimentRecord {
+                symbol: "FCPO".to_string(),
+                recorded_at: start + chrono::Duration::minutes(15 * i as i64),
+                source: source.to_string(),
+                score,
+                confidence: 0.6,
+                summary: (i == 0).then(|| "Exports up, \"strong\"\ndemand".to_string()),
+            })
+            .unwrap();
+        }
+
+        let all = db.get_sentiment_history("FCPO", None, None).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].summary.as_deref(), Some("Exports up, \"strong\"\ndemand"));
+        let later = db
+            .get_sentiment_history("FCPO", Some(start + chrono::Duration::minutes(15)), None)
+            .unwrap();
+        assert_eq!(later.len(), 2);
+        assert_eq!(later[1].score, -20);
+        assert!(db.get_sentiment_history("OTHER", None, None).unwrap().is_empty());
+
+        let mut csv = Vec::new();
+        db.write_sentiment_history_csv("FCPO", &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "timestamp,source,score,confidence,summary");
+        assert!(lines[1].ends_with(r#",perplexity,35,0.6000,"Exports up, ""strong"" demand""#));
+    }
+
+    #[test]
+    fn test_signal_decisions_round_trip() {
+        use crate::modules::trading::{DecisionFactor, Signal};
+
+        let (db, _temp) = create_test_db();
+        let at = DateTime::parse_from_rfc3339("2024-03-04T08:05:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut decision = SignalDecision::new(Signal::Buy)
+            .with_score(0.4)
+            .with_side(OrderSide::Buy)
+            .with_factor(DecisionFactor::new("rsi", Some(25.0), true).with_threshold(30.0));
+        decision.block("paused by operator");
+        db.save_signal_decision("FCPO", at, &decision).unwrap();
+        db.save_signal_decision("FCPO", at + chrono::Duration::minutes(5), &SignalDecision::hold())
+            .unwrap();
+
+        let all = db.get_signal_decisions("FCPO", None, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].signal, "BUY");
+        assert_eq!(all[0].side.as_deref(), Some("BUY"));
+        assert_eq!(all[0].factors[0]["threshold"], 30.0);
+        assert_eq!(all[0].blockers, vec!["paused by operator".to_string()]);
+        assert_eq!(all[1].signal, "HOLD");
+        let later = db.get_signal_decisions("FCPO", Some(at + chrono::Duration::minutes(1)), None);
+        assert_eq!(later.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_leader_lease_expiry_and_takeover() {
+        let (db, _dir) = create_test_db();
+        let ttl = 30_000;
+
+        assert!(db.try_acquire_leader_lease("acct", "node-a", 1_000, ttl).unwrap());
+        assert!(!db.try_acquire_leader_lease("acct", "node-b", 20_000, ttl).unwrap());
+        // Renewal keeps the lease alive past the original expiry
+        assert!(db.try_acquire_leader_lease("acct", "node-a", 25_000, ttl).unwrap());
+        assert!(!db.try_acquire_leader_lease("acct", "node-b", 50_000, ttl).unwrap());
+
+        // Missed heartbeats let the standby take over
+        assert!(db.try_acquire_leader_lease("acct", "node-b", 60_000, ttl).unwrap());
+        assert_eq!(
+            db.get_leader_lease("acct").unwrap(),
+            Some(("node-b".to_string(), 60_000))
+        );
+        assert!(!db.try_acquire_leader_lease("acct", "node-a", 61_000, ttl).unwrap());
+
+        db.release_leader_lease("acct", "node-b").unwrap();
+        assert!(db.try_acquire_leader_lease("acct", "node-a", 62_000, ttl).unwrap());
+    }
+
+    #[test]
+    fn test_backup_to_copies_trades() {
+        let (db, dir) = create_test_db();
+
+        db.upsert_position(&create_test_position("1", "FCPO", OrderSide::Buy, 4000.0))
+            .unwrap();
+        db.close_position("1", 4100.0, CloseReason::TakeProfit).unwrap();
+
+        let backup_path = dir.path().join("backup.db");
+        db.backup_to(&backup_path).unwrap();
+
+        let restored = PositionDatabase::new(&backup_path).unwrap();
+        assert_eq!(restored.get_closed_trades().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_strategy_version_survives_close() {
+        let (db, _dir) = create_test_db();
+
+        let old = create_test_position("1", "FCPO", OrderSide::Buy, 4000.0)
+            .with_strategy_version("0.1.0-aaaa0000")
+            .with_features("-trend_filter");
+        let new = create_test_position("2", "FCPO", OrderSide::Buy, 4000.0).with_strategy_version("0.1.0-bbbb1111");
+        db.upsert_position(&old).unwrap();
+        db.upsert_position(&new).unwrap();
+
+        let loaded = db.get_position("1").unwrap().unwrap();
+        assert_eq!(loaded.strategy_version.as_deref(), Some("0.1.0-aaaa0000"));
+        assert_eq!(loaded.features.as_deref(), Some("-trend_filter"));
+
+        db.close_position("1", 4100.0, CloseReason::TakeProfit).unwrap();
+        db.close_position("2", 3950.0, CloseReason::StopLoss).unwrap();
+
+        let trades = db.get_closed_trades().unwrap();
+        assert_eq!(trades[0].strategy_version.as_deref(), Some("0.1.0-aaaa0000"));
+        assert_eq!(trades[0].features.as_deref(), Some("-trend_filter"));
+        assert_eq!(trades[1].features, None);
+
+        let stats = db.get_strategy_version_stats().unwrap();
+        assert_eq!(stats.len(), 2);
+        let old_stats = stats
+            .iter()
+            .find(|s| s.strategy_version.as_deref() == Some("0.1.0-aaaa0000"))
+            .unwrap();
+        assert_eq!(old_stats.total_trades, 1);
+        assert!((old_stats.win_rate() - 100.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_migrates_legacy_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("legacy.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute(
+                "CREATE TABLE positions (
+                    id TEXT PRIMARY KEY, broker_id INTEGER, symbol TEXT NOT NULL, side TEXT NOT NULL,
+                    entry_price REAL NOT NULL, volume REAL NOT NULL, take_profit REAL, stop_loss REAL,
+                    opened_at TEXT NOT NULL, last_updated TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'open'
+                )",
+                [],
+            )
+            .unwrap();
+        }
+
+        let db = PositionDatabase::new(&db_path).unwrap();
+        let position = create_test_position("1", "FCPO", OrderSide::Sell, 4000.0).with_strategy_version("v2-00000000");
+        db.upsert_position(&position).unwrap();
+        let loaded = db.get_position("1").unwrap().unwrap();
+        assert_eq!(loaded.strategy_version.as_deref(), Some("v2-00000000"));
+    }
+
+    #[test]
+    fn test_market_brief_roundtrip() {
+        let (db, _dir) = create_test_db();
+        assert!(db.get_latest_market_brief("FCPO").unwrap().is_none());
+
+        let mut older = MarketBrief::new("FCPO", -10, 0.3, "SUMMARY: older");
+        older.generated_at = Utc::now() - chrono::Duration::days(1);
+        db.save_market_brief(&older).unwrap();
+        db.save_market_brief(&MarketBrief::new("FCPO", 40, 0.9, "SUMMARY: latest"))
+            .unwrap();
+        db.save_market_brief(&MarketBrief::new("SUGARRAW", 5, 0.6, "SUMMARY: other"))
+            .unwrap();
+
+        let latest = db.get_latest_market_brief("FCPO").unwrap().unwrap();
+        assert_eq!(latest.score, 40);
+        assert_eq!(latest.content, "SUMMARY: latest");
+
+        let all = db.get_market_briefs("FCPO").unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].score, -10);
+    }
 }
@@ -199,6 +199,23 @@ impl ReconciliationEngine {
             .collect()
     }
 
+    /// Re-adopt a persisted position that is still open on the broker.
+    ///
+    /// The broker is authoritative for side, fill price and volume; SL/TP and
+    /// entry metadata (open time, order, strategy version) come from the stored
+    /// record so the bot keeps managing the position as it was opened.
+    pub fn adopt_stored_position(&self, stored: &Position, broker: &BrokerPosition) -> Position {
+        let mut pos = stored.clone();
+        if let Some(mismatch) = self.check_mismatch(stored, broker) {
+            warn!("Re-adopting position {} with broker values: {}", stored.id, mismatch);
+        }
+        pos.side = broker.side;
+        pos.entry_price = broker.entry_price;
+        pos.volume = broker.volume;
+        pos.current_pnl = broker.current_pnl;
+        pos
+    }
+
     /// Auto-heal: Apply reconciliation actions
     ///
     /// Returns tuple: (positions_to_add, position_ids_to_remove)
@@ -515,6 +532,29 @@ mod tests {
         assert_eq!(result.mismatched.len(), 0);
     }
 
+    #[test]
+    fn test_adopt_stored_position_keeps_sl_tp() {
+        let engine = ReconciliationEngine::new();
+
+        let opened_at = chrono::Utc::now() - chrono::Duration::hours(3);
+        let mut stored = create_test_position("123", "FCPO", OrderSide::Buy, 4850.0)
+            .with_take_profit(4950.0)
+            .with_stop_loss(4800.0)
+            .with_strategy_version("1.0.0-abc");
+        stored.opened_at = opened_at;
+
+        let mut broker = create_broker_position(123, "FCPO", OrderSide::Buy, 4852.0);
+        broker.volume = 2.0;
+
+        let adopted = engine.adopt_stored_position(&stored, &broker);
+        assert_eq!(adopted.take_profit, Some(4950.0));
+        assert_eq!(adopted.stop_loss, Some(4800.0));
+        assert_eq!(adopted.opened_at, opened_at);
+        assert_eq!(adopted.strategy_version.as_deref(), Some("1.0.0-abc"));
+        assert_eq!(adopted.entry_price, 4852.0);
+        assert_eq!(adopted.volume, 2.0);
+    }
+
     #[test]
     fn test_generate_missing_positions() {
         let engine = ReconciliationEngine::new();
@@ -0,0 +1,276 @@
+//! Circuit breaker on cTrader connection attempts
+//!
+//! Reconnects come from the reader task (after a dropped connection or an
+//! auth error) and from the bot's retry helpers. When the broker is down,
+//! those would each retry every few seconds, indefinitely. The breaker counts
+//! failed attempts from all of them: after `CTRADER_RECONNECT_MAX_FAILURES`
+//! (default 5) within `CTRADER_RECONNECT_WINDOW_SECS` (default 300), it opens
+//! and every caller waits out `CTRADER_RECONNECT_COOLDOWN_SECS` (default 900)
+//! before the next attempt. Each trip is announced on a broadcast channel so
+//! the bot can alert the operator. A successful connection closes it again.
+//! `CTRADER_RECONNECT_MAX_FAILURES=0` disables the breaker.
+
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tracing::{error, info};
+
+const DEFAULT_MAX_FAILURES: u32 = 5;
+const DEFAULT_WINDOW_SECS: u64 = 300;
+const DEFAULT_COOLDOWN_SECS: u64 = 900;
+
+/// Trips buffered for a slow subscriber
+const TRIP_CHANNEL_CAPACITY: usize = 16;
+
+/// Failure budget and cool-down of the reconnect breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectBreakerConfig {
+    /// Failed attempts within `window` that open the breaker (0 = never)
+    pub max_failures: u32,
+    pub window: Duration,
+    /// How long an open breaker holds off new attempts
+    pub cooldown: Duration,
+}
+
+impl Default for ReconnectBreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_failures: DEFAULT_MAX_FAILURES,
+            window: Duration::from_secs(DEFAULT_WINDOW_SECS),
+            cooldown: Duration::from_secs(DEFAULT_COOLDOWN_SECS),
+        }
+    }
+}
+
+impl ReconnectBreakerConfig {
+    /// Load from `CTRADER_RECONNECT_MAX_FAILURES`, `CTRADER_RECONNECT_WINDOW_SECS`
+    /// and `CTRADER_RECONNECT_COOLDOWN_SECS`
+    pub fn from_env() -> Self {
+        let var = |key: &str| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        Self {
+            max_failures: var("CTRADER_RECONNECT_MAX_FAILURES")
+                .map(|n| n as u32)
+                .unwrap_or(DEFAULT_MAX_FAILURES),
+            window: Duration::from_secs(
+                var("CTRADER_RECONNECT_WINDOW_SECS")
+                    .filter(|secs| *secs > 0)
+                    .unwrap_or(DEFAULT_WINDOW_SECS),
+            ),
+            cooldown: Duration::from_secs(
+                var("CTRADER_RECONNECT_COOLDOWN_SECS")
+                    .filter(|secs| *secs > 0)
+                    .unwrap_or(DEFAULT_COOLDOWN_SECS),
+            ),
+        }
+    }
+}
+
+/// The breaker opening after too many failed attempts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectTrip {
+    /// Failed attempts within the window
+    pub failures: u32,
+    pub window: Duration,
+    pub cooldown: Duration,
+    /// Error of the attempt that tripped the breaker
+    pub last_error: String,
+}
+
+impl fmt::Display for ReconnectTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failed cTrader connection attempts in {} min (last: {}); \
+             pausing reconnects for {} min",
+            self.failures,
+            self.window.as_secs() / 60,
+            self.last_error,
+            self.cooldown.as_secs() / 60
+        )
+    }
+}
+
+/// Failed connection attempts in the current window, and the cool-down
+#[derive(Debug, Clone)]
+pub struct ReconnectBreaker {
+    config: ReconnectBreakerConfig,
+    failures: VecDeque<Instant>,
+    open_until: Option<Instant>,
+}
+
+impl ReconnectBreaker {
+    pub fn new(config: ReconnectBreakerConfig) -> Self {
+        Self {
+            config,
+            failures: VecDeque::new(),
+            open_until: None,
+        }
+    }
+
+    pub fn config(&self) -> ReconnectBreakerConfig {
+        self.config
+    }
+
+    /// Remaining cool-down before the next attempt, if the breaker is open
+    pub fn wait_before_attempt(&self, now: Instant) -> Option<Duration> {
+        self.open_until
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    pub fn is_open(&self, now: Instant) -> bool {
+        self.wait_before_attempt(now).is_some()
+    }
+
+    /// Count a failed attempt; returns the trip when it opens the breaker
+    pub fn record_failure(&mut self, now: Instant, error: &str) -> Option<ReconnectTrip> {
+        if self.config.max_failures == 0 {
+            return None;
+        }
+        while self
+            .failures
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > self.config.window)
+        {
+            self.failures.pop_front();
+        }
+        self.failures.push_back(now);
+        if self.failures.len() < self.config.max_failures as usize {
+            return None;
+        }
+
+        let failures = self.failures.len() as u32;
+        self.failures.clear();
+        self.open_until = Some(now + self.config.cooldown);
+        Some(ReconnectTrip {
+            failures,
+            window: self.config.window,
+            cooldown: self.config.cooldown,
+            last_error: error.to_string(),
+        })
+    }
+
+    /// A connection went through: forget earlier failures
+    pub fn record_success(&mut self) {
+        self.failures.clear();
+        self.open_until = None;
+    }
+}
+
+/// One breaker shared by every caller that connects, with its trip channel
+#[derive(Debug, Clone)]
+pub struct SharedReconnectBreaker {
+    breaker: Arc<Mutex<ReconnectBreaker>>,
+    trips: broadcast::Sender<ReconnectTrip>,
+}
+
+impl SharedReconnectBreaker {
+    pub fn new(config: ReconnectBreakerConfig) -> Self {
+        Self {
+            breaker: Arc::new(Mutex::new(ReconnectBreaker::new(config))),
+            trips: broadcast::channel(TRIP_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(ReconnectBreakerConfig::from_env())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ReconnectBreaker> {
+        self.breaker.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Remaining cool-down before the next attempt, if the breaker is open
+    pub fn wait_before_attempt(&self) -> Option<Duration> {
+        self.lock().wait_before_attempt(Instant::now())
+    }
+
+    /// Count a failed attempt; a trip is logged and sent to subscribers
+    pub fn record_failure(&self, error: &dyn fmt::Display) {
+        let trip = self
+            .lock()
+            .record_failure(Instant::now(), &error.to_string());
+        if let Some(trip) = trip {
+            error!("⛔ Reconnect breaker open: {}", trip);
+            let _ = self.trips.send(trip);
+        }
+    }
+
+    /// A connection went through
+    pub fn record_success(&self) {
+        let mut breaker = self.lock();
+        if !breaker.failures.is_empty() || breaker.open_until.is_some() {
+            info!("Reconnect breaker reset after a successful connection");
+        }
+        breaker.record_success();
+    }
+
+    /// Receive every trip of the breaker
+    pub fn subscribe(&self) -> broadcast::Receiver<ReconnectTrip> {
+        self.trips.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> ReconnectBreaker {
+        ReconnectBreaker::new(ReconnectBreakerConfig {
+            max_failures: 3,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(600),
+        })
+    }
+
+    #[test]
+    fn test_trips_after_failures_in_window() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+
+        assert!(breaker.record_failure(start, "refused").is_none());
+        // Outside the window: the first failure no longer counts
+        let later = start + Duration::from_secs(90);
+        assert!(breaker.record_failure(later, "refused").is_none());
+        assert!(breaker.record_failure(later, "refused").is_none());
+        let trip = breaker.record_failure(later, "timeout").unwrap();
+        assert_eq!(trip.failures, 3);
+        assert_eq!(trip.last_error, "timeout");
+
+        assert!(breaker.is_open(later));
+        assert_eq!(
+            breaker.wait_before_attempt(later + Duration::from_secs(100)),
+            Some(Duration::from_secs(500))
+        );
+        assert!(!breaker.is_open(later + Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_success_closes_and_zero_disables() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure(now, "refused");
+        }
+        assert!(breaker.is_open(now));
+        breaker.record_success();
+        assert!(!breaker.is_open(now));
+        assert!(breaker.record_failure(now, "refused").is_none());
+
+        let mut disabled = ReconnectBreaker::new(ReconnectBreakerConfig {
+            max_failures: 0,
+            ..ReconnectBreakerConfig::default()
+        });
+        for _ in 0..10 {
+            assert!(disabled.record_failure(now, "refused").is_none());
+        }
+    }
+}
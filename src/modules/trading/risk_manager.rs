@@ -0,0 +1,328 @@
+//! Portfolio-level exposure and margin checks before an order is sent
+//!
+//! With `RISK_MANAGER_ENABLED` set, every new order is checked against the
+//! whole account, not just the strategy's own positions:
+//! - total notional of all open positions plus the order, at most
+//!   `RISK_MAX_EXPOSURE_RATIO` × balance (default 5)
+//! - notional in the order's symbol, at most `RISK_MAX_SYMBOL_EXPOSURE_RATIO`
+//!   × balance (default 2.5), so one symbol cannot take the whole account
+//! - margin in use plus the broker's expected margin for the order
+//!   (`ProtoOAExpectedMarginReq`), at most `RISK_MAX_MARGIN_UTILIZATION` of
+//!   the balance (default 0.5)
+//!
+//! An order that would break a limit is skipped, not resized.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+
+use tracing::debug;
+
+/// Limits applied to every new order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskLimits {
+    /// Total notional as a multiple of the balance
+    pub max_exposure_ratio: f64,
+    /// Notional in one symbol as a multiple of the balance
+    pub max_symbol_exposure_ratio: f64,
+    /// Margin used after the order, as a fraction of the balance
+    pub max_margin_utilization: f64,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_exposure_ratio: 5.0,
+            max_symbol_exposure_ratio: 2.5,
+            max_margin_utilization: 0.5,
+        }
+    }
+}
+
+impl RiskLimits {
+    /// Read the limits when `RISK_MANAGER_ENABLED` is set; `None` otherwise
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("RISK_MANAGER_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let defaults = Self::default();
+        let limit = |key: &str, default: f64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+                .unwrap_or(default)
+        };
+        Some(Self {
+            max_exposure_ratio: limit("RISK_MAX_EXPOSURE_RATIO", defaults.max_exposure_ratio),
+            max_symbol_exposure_ratio: limit(
+                "RISK_MAX_SYMBOL_EXPOSURE_RATIO",
+                defaults.max_symbol_exposure_ratio,
+            ),
+            max_margin_utilization: limit(
+                "RISK_MAX_MARGIN_UTILIZATION",
+                defaults.max_margin_utilization,
+            ),
+        })
+    }
+}
+
+/// One open position as the risk manager sees it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exposure {
+    pub symbol_id: i64,
+    /// Volume × price, in account currency
+    pub notional: f64,
+    /// Margin the broker holds for the position
+    pub used_margin: f64,
+}
+
+/// Order about to be sent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderExposure {
+    pub symbol_id: i64,
+    pub notional: f64,
+    /// Broker's expected margin; `None` skips the margin check
+    pub expected_margin: Option<f64>,
+}
+
+/// Account exposure after a proposed order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExposureReport {
+    pub balance: f64,
+    pub total_exposure: f64,
+    pub symbol_exposure: f64,
+    /// Margin in use after the order over the balance, when known
+    pub margin_utilization: Option<f64>,
+}
+
+impl fmt::Display for ExposureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exposure {:.2} (symbol {:.2}) on balance {:.2}",
+            self.total_exposure, self.symbol_exposure, self.balance
+        )?;
+        if let Some(utilization) = self.margin_utilization {
+            write!(f, ", margin {:.1}%", utilization * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why an order was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RiskRejection {
+    /// No positive balance to measure exposure against
+    NoBalance,
+    TotalExposure {
+        exposure: f64,
+        limit: f64,
+    },
+    SymbolExposure {
+        symbol_id: i64,
+        exposure: f64,
+        limit: f64,
+    },
+    MarginUtilization {
+        utilization: f64,
+        limit: f64,
+    },
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRejection::NoBalance => write!(f, "account balance unknown or not positive"),
+            RiskRejection::TotalExposure { exposure, limit } => write!(
+                f,
+                "total exposure {:.2} would exceed the {:.2} limit",
+                exposure, limit
+            ),
+            RiskRejection::SymbolExposure {
+                symbol_id,
+                exposure,
+                limit,
+            } => write!(
+                f,
+                "symbol {} exposure {:.2} would exceed the {:.2} limit",
+                symbol_id, exposure, limit
+            ),
+            RiskRejection::MarginUtilization { utilization, limit } => write!(
+                f,
+                "margin utilization {:.1}% would exceed {:.1}%",
+                utilization * 100.0,
+                limit * 100.0
+            ),
+        }
+    }
+}
+
+/// Checks new orders against portfolio-wide limits
+#[derive(Debug, Clone)]
+pub struct RiskManager {
+    limits: RiskLimits,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self { limits }
+    }
+
+    /// Risk manager when `RISK_MANAGER_ENABLED` is set
+    pub fn from_env() -> Option<Self> {
+        RiskLimits::from_env().map(Self::new)
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Notional per symbol across `positions`
+    pub fn exposure_by_symbol(positions: &[Exposure]) -> HashMap<i64, f64> {
+        let mut by_symbol = HashMap::new();
+        for position in positions {
+            *by_symbol.entry(position.symbol_id).or_insert(0.0) += position.notional.abs();
+        }
+        by_symbol
+    }
+
+    /// Check `order` against the open `positions` and `balance`
+    pub fn check(
+        &self,
+        balance: f64,
+        positions: &[Exposure],
+        order: &OrderExposure,
+    ) -> Result<ExposureReport, RiskRejection> {
+        if balance <= 0.0 || !balance.is_finite() {
+            return Err(RiskRejection::NoBalance);
+        }
+
+        let order_notional = order.notional.abs();
+        let total_exposure =
+            positions.iter().map(|p| p.notional.abs()).sum::<f64>() + order_notional;
+        let symbol_exposure = Self::exposure_by_symbol(positions)
+            .get(&order.symbol_id)
+            .copied()
+            .unwrap_or(0.0)
+            + order_notional;
+        let margin_utilization = order.expected_margin.map(|margin| {
+            (positions.iter().map(|p| p.used_margin).sum::<f64>() + margin) / balance
+        });
+        let report = ExposureReport {
+            balance,
+            total_exposure,
+            symbol_exposure,
+            margin_utilization,
+        };
+        debug!("Risk check: {}", report);
+
+        let total_limit = self.limits.max_exposure_ratio * balance;
+        if total_exposure > total_limit {
+            return Err(RiskRejection::TotalExposure {
+                exposure: total_exposure,
+                limit: total_limit,
+            });
+        }
+        let symbol_limit = self.limits.max_symbol_exposure_ratio * balance;
+        if symbol_exposure > symbol_limit {
+            return Err(RiskRejection::SymbolExposure {
+                symbol_id: order.symbol_id,
+                exposure: symbol_exposure,
+                limit: symbol_limit,
+            });
+        }
+        if let Some(utilization) = margin_utilization {
+            if utilization > self.limits.max_margin_utilization {
+                return Err(RiskRejection::MarginUtilization {
+                    utilization,
+                    limit: self.limits.max_margin_utilization,
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(symbol_id: i64, notional: f64, used_margin: f64) -> Exposure {
+        Exposure {
+            symbol_id,
+            notional,
+            used_margin,
+        }
+    }
+
+    fn order(symbol_id: i64, notional: f64, expected_margin: Option<f64>) -> OrderExposure {
+        OrderExposure {
+            symbol_id,
+            notional,
+            expected_margin,
+        }
+    }
+
+    #[test]
+    fn test_order_within_limits() {
+        let manager = RiskManager::new(RiskLimits::default());
+        let positions = [position(1, 10_000.0, 1_000.0), position(2, -8_000.0, 800.0)];
+
+        let report = manager
+            .check(10_000.0, &positions, &order(1, 5_000.0, Some(500.0)))
+            .unwrap();
+        assert_eq!(report.total_exposure, 23_000.0);
+        assert_eq!(report.symbol_exposure, 15_000.0);
+        assert_eq!(report.margin_utilization, Some(0.23));
+    }
+
+    #[test]
+    fn test_single_symbol_cannot_take_the_account() {
+        let manager = RiskManager::new(RiskLimits::default());
+        let positions = [position(1, 20_000.0, 0.0)];
+
+        // Another 10k in symbol 1 breaks its 25k limit, the same order in symbol 2 does not
+        assert_eq!(
+            manager.check(10_000.0, &positions, &order(1, 10_000.0, None)),
+            Err(RiskRejection::SymbolExposure {
+                symbol_id: 1,
+                exposure: 30_000.0,
+                limit: 25_000.0
+            })
+        );
+        assert!(manager
+            .check(10_000.0, &positions, &order(2, 10_000.0, None))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_total_exposure_and_margin_limits() {
+        let manager = RiskManager::new(RiskLimits::default());
+        let positions = [
+            position(1, 20_000.0, 2_000.0),
+            position(2, 20_000.0, 2_000.0),
+        ];
+
+        assert!(matches!(
+            manager.check(10_000.0, &positions, &order(3, 15_000.0, None)),
+            Err(RiskRejection::TotalExposure { .. })
+        ));
+        assert!(matches!(
+            manager.check(10_000.0, &positions, &order(3, 5_000.0, Some(1_500.0))),
+            Err(RiskRejection::MarginUtilization { .. })
+        ));
+        // Without a margin estimate only exposure is checked
+        assert!(manager
+            .check(10_000.0, &positions, &order(3, 5_000.0, None))
+            .is_ok());
+        assert_eq!(
+            manager.check(0.0, &[], &order(3, 1.0, None)),
+            Err(RiskRejection::NoBalance)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Secondary price source for cross-checking the cTrader feed
+//!
+//! With `SECONDARY_FEED_URL` set, the bot polls a second quote source every
+//! `SECONDARY_FEED_POLL_SECS` (default 30): a public quotes API returning
+//! JSON or CSV, read at `SECONDARY_FEED_FIELD` (a JSON pointer, default
+//! `/price`, or `csv:<column>`) and multiplied by `SECONDARY_FEED_SCALE`
+//! (default 1) to match the broker's units. Two uses:
+//! - cross-check: when the two prices are more than
+//!   `SECONDARY_FEED_MAX_DIVERGENCE_PERCENT` (default 1.0) apart, a warning
+//!   alert goes out once, and an info alert when they agree again
+//! - fallback: while the cTrader feed is stale, the dashboard price and the
+//!   heartbeat's unrealized P&L use the secondary quote (when it is younger
+//!   than `SECONDARY_FEED_MAX_AGE_SECS`, default 300)
+//!
+//! The secondary price is for monitoring only: candles, signals, exits and
+//! orders always use the cTrader feed.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::error::{BotError, Result};
+use crate::modules::scraper::SeriesField;
+
+const DEFAULT_FIELD: &str = "/price";
+const DEFAULT_POLL_SECS: u64 = 30;
+const DEFAULT_MAX_DIVERGENCE_PERCENT: f64 = 1.0;
+const DEFAULT_MAX_AGE_SECS: u64 = 300;
+
+/// Where the secondary quote comes from and how far it may drift
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecondaryFeedConfig {
+    pub url: String,
+    pub field: SeriesField,
+    /// Multiplier to the broker's price units
+    pub scale: f64,
+    pub poll_interval: Duration,
+    /// Gap between the two prices, in percent, that raises an alert
+    pub max_divergence_percent: f64,
+    /// Oldest secondary quote still used as a fallback
+    pub max_age: Duration,
+}
+
+impl SecondaryFeedConfig {
+    /// Load from `SECONDARY_FEED_URL` and the `SECONDARY_FEED_*` settings;
+    /// None when no URL is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let url = env::var("SECONDARY_FEED_URL").unwrap_or_default();
+        let url = url.trim();
+        if url.is_empty() {
+            return Ok(None);
+        }
+        let raw_field = env::var("SECONDARY_FEED_FIELD").unwrap_or_default();
+        let raw_field = match raw_field.trim() {
+            "" => DEFAULT_FIELD,
+            field => field,
+        };
+        let field = SeriesField::parse(raw_field).ok_or_else(|| {
+            BotError::Config(format!(
+                "invalid SECONDARY_FEED_FIELD: {:?} (expected a JSON pointer or csv:<column>)",
+                raw_field
+            ))
+        })?;
+        Ok(Some(Self {
+            url: url.to_string(),
+            field,
+            scale: parse_var("SECONDARY_FEED_SCALE", 1.0, |s: &f64| *s > 0.0)?,
+            poll_interval: Duration::from_secs(parse_var(
+                "SECONDARY_FEED_POLL_SECS",
+                DEFAULT_POLL_SECS,
+                |secs| *secs > 0,
+            )?),
+            max_divergence_percent: parse_var(
+                "SECONDARY_FEED_MAX_DIVERGENCE_PERCENT",
+                DEFAULT_MAX_DIVERGENCE_PERCENT,
+                |p| *p > 0.0,
+            )?,
+            max_age: Duration::from_secs(parse_var(
+                "SECONDARY_FEED_MAX_AGE_SECS",
+                DEFAULT_MAX_AGE_SECS,
+                |secs| *secs > 0,
+            )?),
+        }))
+    }
+}
+
+fn parse_var<T: FromStr>(key: &str, default: T, valid: impl Fn(&T) -> bool) -> Result<T> {
+    match env::var(key) {
+        Ok(raw) if !raw.trim().is_empty() => raw
+            .trim()
+            .parse::<T>()
+            .ok()
+            .filter(|value| valid(value))
+            .ok_or_else(|| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+        _ => Ok(default),
+    }
+}
+
+/// One price read from the secondary source, in broker units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecondaryQuote {
+    pub price: f64,
+    pub received_at: DateTime<Utc>,
+}
+
+/// The two feeds apart by more than the limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedDivergence {
+    pub primary: f64,
+    pub secondary: f64,
+    /// Gap relative to the primary price, in percent
+    pub percent: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for FeedDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cTrader price {:.2} and secondary feed {:.2} differ by {:.2}% (limit {:.2}%)",
+            self.primary, self.secondary, self.percent, self.limit
+        )
+    }
+}
+
+/// Result of cross-checking the cTrader price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossCheck {
+    Agree,
+    /// Back within the limit after diverging
+    Converged {
+        percent: f64,
+    },
+    /// Apart by more than the limit; `first` on the check that found it
+    Diverged {
+        divergence: FeedDivergence,
+        first: bool,
+    },
+}
+
+/// Polls the secondary source and compares it with the cTrader feed
+pub struct SecondaryFeed {
+    client: reqwest::Client,
+    config: SecondaryFeedConfig,
+    latest: Option<SecondaryQuote>,
+    diverged: bool,
+}
+
+impl SecondaryFeed {
+    pub fn new(config: SecondaryFeedConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent(format!("palm-oil-bot/{}", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client: {}. Falling back to default client.",
+                    e
+                );
+                reqwest::Client::new()
+            });
+
+        Self {
+            client,
+            config,
+            latest: None,
+            diverged: false,
+        }
+    }
+
+    pub fn config(&self) -> &SecondaryFeedConfig {
+        &self.config
+    }
+
+    /// Last quote read, however old
+    pub fn latest(&self) -> Option<SecondaryQuote> {
+        self.latest
+    }
+
+    /// Fetch the current price; the previous quote is kept on failure
+    pub async fn poll(&mut self) -> Result<SecondaryQuote> {
+        debug!("Polling secondary price feed: {}", self.config.url);
+        let response = self
+            .client
+            .get(&self.config.url)
+            .send()
+            .await
+            .map_err(|e| BotError::Feed(format!("secondary feed: request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(BotError::Feed(format!(
+                "secondary feed: HTTP error: {}",
+                response.status()
+            )));
+        }
+        let body = response.text().await.map_err(|e| {
+            BotError::Feed(format!("secondary feed: failed to read response: {}", e))
+        })?;
+        let price = self
+            .config
+            .field
+            .extract(&body)
+            .map(|value| value * self.config.scale)
+            .filter(|price| price.is_finite() && *price > 0.0)
+            .ok_or_else(|| {
+                BotError::Feed(format!(
+                    "secondary feed: no price at {:?}",
+                    self.config.field
+                ))
+            })?;
+        let quote = SecondaryQuote {
+            price,
+            received_at: Utc::now(),
+        };
+        self.latest = Some(quote);
+        Ok(quote)
+    }
+
+    /// Latest quote if it is recent enough to stand in for the cTrader feed
+    pub fn fallback_price(&self, now: DateTime<Utc>) -> Option<f64> {
+        let max_age = chrono::Duration::from_std(self.config.max_age).ok()?;
+        self.latest
+            .filter(|quote| now - quote.received_at <= max_age)
+            .map(|quote| quote.price)
+    }
+
+    /// Compare the cTrader mid price with the latest secondary quote; None
+    /// without a recent one
+    pub fn cross_check(&mut self, primary: f64, now: DateTime<Utc>) -> Option<CrossCheck> {
+        let secondary = self.fallback_price(now)?;
+        if primary <= 0.0 {
+            return None;
+        }
+        let percent = (secondary - primary).abs() / primary * 100.0;
+        let within = percent <= self.config.max_divergence_percent;
+        let check = match (within, self.diverged) {
+            (true, false) => CrossCheck::Agree,
+            (true, true) => CrossCheck::Converged { percent },
+            (false, was_diverged) => CrossCheck::Diverged {
+                divergence: FeedDivergence {
+                    primary,
+                    secondary,
+                    percent,
+                    limit: self.config.max_divergence_percent,
+                },
+                first: !was_diverged,
+            },
+        };
+        self.diverged = !within;
+        Some(check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed() -> SecondaryFeed {
+        SecondaryFeed::new(SecondaryFeedConfig {
+            url: "http://quotes.invalid/fcpo".into(),
+            field: SeriesField::parse("/data/last").unwrap(),
+            scale: 1.0,
+            poll_interval: Duration::from_secs(30),
+            max_divergence_percent: 1.0,
+            max_age: Duration::from_secs(300),
+        })
+    }
+
+    #[test]
+    fn test_cross_check_alerts_once_per_divergence() {
+        let mut feed = feed();
+        let now = Utc::now();
+        assert_eq!(feed.cross_check(4000.0, now), None);
+
+        feed.latest = Some(SecondaryQuote {
+            price: 4020.0,
+            received_at: now,
+        });
+        assert_eq!(feed.cross_check(4000.0, now), Some(CrossCheck::Agree));
+
+        let Some(CrossCheck::Diverged { divergence, first }) = feed.cross_check(3960.0, now) else {
+            panic!("expected a divergence");
+        };
+        assert!(first);
+        assert!((divergence.percent - 1.515).abs() < 0.01);
+        assert!(divergence.to_string().contains("differ by 1.52%"));
+        assert!(matches!(
+            feed.cross_check(3950.0, now),
+            Some(CrossCheck::Diverged { first: false, .. })
+        ));
+        assert!(matches!(
+            feed.cross_check(4010.0, now),
+            Some(CrossCheck::Converged { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fallback_only_while_recent() {
+        let mut feed = feed();
+        let now = Utc::now();
+        feed.latest = Some(SecondaryQuote {
+            price: 4020.0,
+            received_at: now - chrono::Duration::seconds(60),
+        });
+        assert_eq!(feed.fallback_price(now), Some(4020.0));
+        assert_eq!(
+            feed.fallback_price(now + chrono::Duration::seconds(300)),
+            None
+        );
+        assert_eq!(
+            feed.cross_check(4000.0, now + chrono::Duration::seconds(300)),
+            None
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Simulated execution for dry-run
+//!
+//! Dry-run orders never reach the broker, so their fills are modelled on what
+//! a live fill would cost:
+//! - entries buy at the ask and sell at the bid, exits trade the other side;
+//!   the latest quote's spread is used, `DRY_RUN_SPREAD` (price units) when
+//!   the feed has no bid/ask
+//! - `DRY_RUN_SLIPPAGE_PERCENT` of the price is added against every fill
+//! - `DRY_RUN_COMMISSION_PER_LOT` is charged on entry and on exit
+//! - `DRY_RUN_SWAP_LONG_PER_LOT` / `DRY_RUN_SWAP_SHORT_PER_LOT` are charged
+//!   for every rollover (`DRY_RUN_ROLLOVER_HOUR_UTC`, default 22) a position
+//!   is held through; negative values are credits
+//!
+//! Fills use the backtest's `FillModel`, so dry-run and backtest results are
+//! priced the same way. Commission and swap are booked when the position
+//! closes and come off its realized P&L. All values default to 0.
+
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::orders::{OrderSide, Position};
+use crate::error::{BotError, Result};
+use crate::modules::backtest::FillModel;
+
+const DEFAULT_ROLLOVER_HOUR_UTC: u32 = 22;
+
+/// Dry-run execution cost model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedBroker {
+    /// Spread used without a quote, slippage and commission per lot
+    pub fill: FillModel,
+    /// Charged per lot for each rollover a long position is held through
+    pub swap_long_per_lot: f64,
+    /// Charged per lot for each rollover a short position is held through
+    pub swap_short_per_lot: f64,
+    /// UTC hour at which swaps are charged
+    pub rollover_hour_utc: u32,
+}
+
+impl Default for SimulatedBroker {
+    fn default() -> Self {
+        Self {
+            fill: FillModel::default(),
+            swap_long_per_lot: 0.0,
+            swap_short_per_lot: 0.0,
+            rollover_hour_utc: DEFAULT_ROLLOVER_HOUR_UTC,
+        }
+    }
+}
+
+/// A simulated entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedFill {
+    pub price: f64,
+    /// Distance from the mid price paid for spread and slippage
+    pub execution_cost: f64,
+}
+
+/// A simulated exit and what the position cost to hold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedClose {
+    pub price: f64,
+    /// Entry and exit commission
+    pub commission: f64,
+    /// Swap over the rollovers held through
+    pub swap: f64,
+}
+
+impl SimulatedClose {
+    /// Amount to take off the position's price P&L
+    pub fn costs(&self) -> f64 {
+        self.commission + self.swap
+    }
+}
+
+impl SimulatedBroker {
+    /// Load from the `DRY_RUN_*` cost variables
+    pub fn from_env() -> Result<Self> {
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> Result<T> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        }
+        let broker = Self {
+            fill: FillModel {
+                spread: var("DRY_RUN_SPREAD", 0.0)?,
+                slippage_percent: var("DRY_RUN_SLIPPAGE_PERCENT", 0.0)?,
+                commission_per_unit: var("DRY_RUN_COMMISSION_PER_LOT", 0.0)?,
+            },
+            swap_long_per_lot: var("DRY_RUN_SWAP_LONG_PER_LOT", 0.0)?,
+            swap_short_per_lot: var("DRY_RUN_SWAP_SHORT_PER_LOT", 0.0)?,
+            rollover_hour_utc: var("DRY_RUN_ROLLOVER_HOUR_UTC", DEFAULT_ROLLOVER_HOUR_UTC)?,
+        };
+        broker.validate()?;
+        Ok(broker)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let fill = &self.fill;
+        let costs = [fill.spread, fill.slippage_percent, fill.commission_per_unit];
+        if !costs.iter().all(|cost| *cost >= 0.0) {
+            return Err(BotError::Config(
+                "DRY_RUN_SPREAD, DRY_RUN_SLIPPAGE_PERCENT and DRY_RUN_COMMISSION_PER_LOT \
+                 must not be negative"
+                    .into(),
+            ));
+        }
+        if !self.swap_long_per_lot.is_finite() || !self.swap_short_per_lot.is_finite() {
+            return Err(BotError::Config(
+                "DRY_RUN_SWAP_*_PER_LOT must be numbers".into(),
+            ));
+        }
+        if self.rollover_hour_utc > 23 {
+            return Err(BotError::Config(
+                "DRY_RUN_ROLLOVER_HOUR_UTC must be between 0 and 23".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Price an exit check sees without a quote: the side the position
+    /// would close against
+    pub fn exit_quote(&self, side: OrderSide, mid: f64) -> f64 {
+        self.fill.quote_at(mid, None, side.opposite())
+    }
+
+    /// Fill a new `side` order around `mid`; `spread` is the quote's, if any
+    pub fn open(&self, side: OrderSide, mid: f64, spread: Option<f64>) -> SimulatedFill {
+        let price = self.fill.fill_price_at(mid, spread, side);
+        SimulatedFill {
+            price,
+            execution_cost: (price - mid).abs(),
+        }
+    }
+
+    /// Close `position` around `mid` at `now`
+    pub fn close(
+        &self,
+        position: &Position,
+        mid: f64,
+        spread: Option<f64>,
+        now: DateTime<Utc>,
+    ) -> SimulatedClose {
+        let swap_per_lot = match position.side {
+            OrderSide::Buy => self.swap_long_per_lot,
+            OrderSide::Sell => self.swap_short_per_lot,
+        };
+        SimulatedClose {
+            price: self
+                .fill
+                .fill_price_at(mid, spread, position.side.opposite()),
+            commission: 2.0 * self.fill.commission(position.volume),
+            swap: swap_per_lot * position.volume * self.rollovers(position.opened_at, now) as f64,
+        }
+    }
+
+    /// Rollovers between `from` and `to`
+    pub fn rollovers(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+        // Shifted so each rollover falls on midnight
+        let shift = Duration::hours(self.rollover_hour_utc as i64);
+        let days = ((to - shift).date_naive() - (from - shift).date_naive()).num_days();
+        days.max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn broker() -> SimulatedBroker {
+        SimulatedBroker {
+            fill: FillModel {
+                spread: 2.0,
+                slippage_percent: 0.1,
+                commission_per_unit: 1.5,
+            },
+            swap_long_per_lot: 4.0,
+            swap_short_per_lot: -1.0,
+            rollover_hour_utc: 22,
+        }
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_fills_cross_the_spread_and_slip() {
+        let broker = broker();
+        // Quote spread of 4 instead of the model's 2, plus 1.0 slippage
+        let buy = broker.open(OrderSide::Buy, 1000.0, Some(4.0));
+        assert_eq!(buy.price, 1003.0);
+        assert_eq!(buy.execution_cost, 3.0);
+        assert_eq!(broker.open(OrderSide::Sell, 1000.0, None).price, 998.0);
+        assert_eq!(broker.exit_quote(OrderSide::Buy, 1000.0), 999.0);
+    }
+
+    #[test]
+    fn test_close_charges_commission_and_swap() {
+        let broker = broker();
+        let mut long = Position::new("dry_run_1", "FCPO", OrderSide::Buy, 1003.0, 2.0);
+        long.opened_at = at(4, 10);
+
+        let same_day = broker.close(&long, 1010.0, Some(2.0), at(4, 21));
+        // Sells at the 1009 bid less 1.01 slippage
+        assert!((same_day.price - 1007.99).abs() < 1e-9);
+        assert_eq!(same_day.commission, 6.0);
+        assert_eq!(same_day.swap, 0.0);
+
+        // Held through the rollovers of the 4th and 5th
+        let two_nights = broker.close(&long, 1010.0, Some(2.0), at(6, 9));
+        assert_eq!(two_nights.swap, 16.0);
+        assert_eq!(two_nights.costs(), 22.0);
+
+        let mut short = long.clone();
+        short.side = OrderSide::Sell;
+        assert_eq!(broker.close(&short, 1010.0, None, at(6, 9)).swap, -4.0);
+    }
+
+    #[test]
+    fn test_rollovers_counted_at_the_rollover_hour() {
+        let broker = broker();
+        assert_eq!(broker.rollovers(at(4, 21), at(4, 22)), 1);
+        assert_eq!(broker.rollovers(at(4, 22), at(5, 21)), 0);
+        assert_eq!(broker.rollovers(at(4, 23), at(7, 23)), 3);
+        assert_eq!(broker.rollovers(at(5, 0), at(4, 0)), 0);
+    }
+
+    #[test]
+    fn test_negative_costs_are_rejected() {
+        let mut broker = broker();
+        assert!(broker.validate().is_ok());
+        broker.fill.slippage_percent = -0.1;
+        assert!(broker.validate().is_err());
+        broker.fill.slippage_percent = 0.0;
+        broker.rollover_hour_utc = 24;
+        assert!(broker.validate().is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Absolute order size caps against fat-finger configuration
+//!
+//! Position size follows from `RISK_PER_TRADE`, the account balance and the
+//! stop distance, so a mistyped risk value, a balance glitch or a stop one
+//! tick from the entry can size an enormous order. These caps do not depend
+//! on any of that:
+//! - `MAX_ORDER_NOTIONAL`: order value (volume × price) in account currency
+//! - `MAX_ORDER_LOTS`: order volume in lots, using the symbol's lot size
+//!   from the broker
+//!
+//! Unparseable or non-positive values stop the bot at startup instead of
+//! silently turning a cap off. An order above either cap is refused, not
+//! shrunk: a size that large means something upstream is wrong.
+
+use std::env;
+use std::fmt;
+
+use crate::error::{BotError, Result};
+
+/// Hard caps for a single order; `None` leaves that cap off
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OrderSizeGuard {
+    pub max_notional: Option<f64>,
+    pub max_lots: Option<f64>,
+}
+
+/// Why an order was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeViolation {
+    Notional { notional: f64, limit: f64 },
+    Lots { lots: f64, limit: f64 },
+}
+
+impl fmt::Display for SizeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeViolation::Notional { notional, limit } => write!(
+                f,
+                "order value {:.2} exceeds MAX_ORDER_NOTIONAL {:.2}",
+                notional, limit
+            ),
+            SizeViolation::Lots { lots, limit } => write!(
+                f,
+                "order size {:.2} lots exceeds MAX_ORDER_LOTS {:.2}",
+                lots, limit
+            ),
+        }
+    }
+}
+
+impl OrderSizeGuard {
+    /// Read `MAX_ORDER_NOTIONAL` and `MAX_ORDER_LOTS`; a value that is set
+    /// but not a positive number is a configuration error
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            max_notional: cap_from_env("MAX_ORDER_NOTIONAL")?,
+            max_lots: cap_from_env("MAX_ORDER_LOTS")?,
+        })
+    }
+
+    /// Whether any cap is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_notional.is_some() || self.max_lots.is_some()
+    }
+
+    /// Check an order of `volume` units at `price`; `lots` is `None` when the
+    /// broker gave no lot size, which skips the lot cap
+    pub fn check(
+        &self,
+        volume: f64,
+        price: f64,
+        lots: Option<f64>,
+    ) -> std::result::Result<(), SizeViolation> {
+        let notional = (volume * price).abs();
+        if let Some(limit) = self.max_notional {
+            // NaN from a balance or price glitch fails the check too
+            if notional.is_nan() || notional > limit {
+                return Err(SizeViolation::Notional { notional, limit });
+            }
+        }
+        if let (Some(limit), Some(lots)) = (self.max_lots, lots) {
+            if lots.is_nan() || lots > limit {
+                return Err(SizeViolation::Lots { lots, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn cap_from_env(key: &str) -> Result<Option<f64>> {
+    let Ok(raw) = env::var(key) else {
+        return Ok(None);
+    };
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    match raw.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(Some(value)),
+        _ => Err(BotError::Config(format!(
+            "{} must be a positive number, got {:?}",
+            key, raw
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_refuse_oversized_orders() {
+        let guard = OrderSizeGuard {
+            max_notional: Some(50_000.0),
+            max_lots: Some(2.0),
+        };
+        assert!(guard.check(10.0, 4_000.0, Some(0.4)).is_ok());
+        assert_eq!(
+            guard.check(20.0, 4_000.0, Some(0.8)),
+            Err(SizeViolation::Notional {
+                notional: 80_000.0,
+                limit: 50_000.0
+            })
+        );
+        assert_eq!(
+            guard.check(10.0, 4_000.0, Some(3.0)),
+            Err(SizeViolation::Lots {
+                lots: 3.0,
+                limit: 2.0
+            })
+        );
+        // Unknown lot size only skips the lot cap
+        assert!(guard.check(10.0, 4_000.0, None).is_ok());
+        assert!(guard.check(f64::NAN, 4_000.0, None).is_err());
+    }
+
+    #[test]
+    fn test_no_caps_allows_everything() {
+        let guard = OrderSizeGuard::default();
+        assert!(!guard.is_enabled());
+        assert!(guard.check(1e9, 1e9, Some(1e6)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_cap_is_a_config_error() {
+        env::set_var("MAX_ORDER_LOTS_TEST_BAD", "1O");
+        env::set_var("MAX_ORDER_LOTS_TEST_NEG", "-5");
+        env::set_var("MAX_ORDER_LOTS_TEST_OK", " 2.5 ");
+        assert!(cap_from_env("MAX_ORDER_LOTS_TEST_BAD").is_err());
+        assert!(cap_from_env("MAX_ORDER_LOTS_TEST_NEG").is_err());
+        assert_eq!(cap_from_env("MAX_ORDER_LOTS_TEST_OK").unwrap(), Some(2.5));
+        assert_eq!(cap_from_env("MAX_ORDER_LOTS_TEST_UNSET").unwrap(), None);
+    }
+}
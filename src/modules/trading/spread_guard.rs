@@ -0,0 +1,126 @@
+//! Entry filter on the bid/ask spread
+//!
+//! Spreads widen around report releases, at session opens and when liquidity
+//! dries up; entering then pays the whole spread up front and puts the stop
+//! closer to the market than intended. Before a new order the bot checks the
+//! latest quote against:
+//! - `MAX_SPREAD`: ask minus bid, in price units
+//! - `MAX_SPREAD_PERCENT`: the spread as a percentage of the mid price
+//!
+//! A crossed quote (ask below bid) is refused whenever a limit is set. Orders
+//! are skipped, not delayed: the next signal is checked again. Values that are
+//! set but not positive numbers stop the bot at startup.
+
+use std::fmt;
+
+use super::size_guard::cap_from_env;
+use crate::error::Result;
+
+/// Spread limits for new entries; `None` leaves that limit off
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpreadGuard {
+    pub max_spread: Option<f64>,
+    pub max_spread_percent: Option<f64>,
+}
+
+/// Why an entry was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpreadViolation {
+    Absolute { spread: f64, limit: f64 },
+    Percent { percent: f64, limit: f64 },
+    Crossed { bid: f64, ask: f64 },
+}
+
+impl fmt::Display for SpreadViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpreadViolation::Absolute { spread, limit } => {
+                write!(f, "spread {} exceeds MAX_SPREAD {}", spread, limit)
+            }
+            SpreadViolation::Percent { percent, limit } => write!(
+                f,
+                "spread {:.3}% exceeds MAX_SPREAD_PERCENT {:.3}%",
+                percent, limit
+            ),
+            SpreadViolation::Crossed { bid, ask } => {
+                write!(f, "crossed quote (bid {} above ask {})", bid, ask)
+            }
+        }
+    }
+}
+
+impl SpreadGuard {
+    /// Read `MAX_SPREAD` and `MAX_SPREAD_PERCENT`
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            max_spread: cap_from_env("MAX_SPREAD")?,
+            max_spread_percent: cap_from_env("MAX_SPREAD_PERCENT")?,
+        })
+    }
+
+    /// Whether any limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_spread.is_some() || self.max_spread_percent.is_some()
+    }
+
+    /// Check a quote before entering
+    pub fn check(&self, bid: f64, ask: f64) -> std::result::Result<(), SpreadViolation> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let spread = ask - bid;
+        if spread < 0.0 {
+            return Err(SpreadViolation::Crossed { bid, ask });
+        }
+        if let Some(limit) = self.max_spread {
+            if spread.is_nan() || spread > limit {
+                return Err(SpreadViolation::Absolute { spread, limit });
+            }
+        }
+        if let Some(limit) = self.max_spread_percent {
+            let percent = spread / ((bid + ask) / 2.0) * 100.0;
+            if percent.is_nan() || percent > limit {
+                return Err(SpreadViolation::Percent { percent, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_spreads_block_entries() {
+        let guard = SpreadGuard {
+            max_spread: Some(3.0),
+            max_spread_percent: Some(0.05),
+        };
+        assert!(guard.check(4_000.0, 4_002.0).is_ok());
+        assert_eq!(
+            guard.check(4_000.0, 4_004.0),
+            Err(SpreadViolation::Absolute {
+                spread: 4.0,
+                limit: 3.0
+            })
+        );
+        // 2.5 points on a 2000 price is 0.125%
+        assert!(matches!(
+            guard.check(2_000.0, 2_002.5),
+            Err(SpreadViolation::Percent { .. })
+        ));
+        assert!(matches!(
+            guard.check(4_002.0, 4_000.0),
+            Err(SpreadViolation::Crossed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_disabled_guard_allows_everything() {
+        let guard = SpreadGuard::default();
+        assert!(!guard.is_enabled());
+        assert!(guard.check(4_000.0, 4_100.0).is_ok());
+        assert!(guard.check(4_002.0, 4_000.0).is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+//! Stale price detection
+//!
+//! The cached price for a symbol stays in place when spot events stop, so the
+//! trading cycle would keep acting on the last quote for as long as the feed
+//! is down. Each spot event records when it arrived, and the cycle refuses
+//! prices older than `MAX_PRICE_AGE_SECS` (default 120, 0 disables): no
+//! candles, signals or exits are computed until ticks resume. Broker-side
+//! stops keep protecting open positions meanwhile.
+//!
+//! Spot events only arrive when the price changes, so the limit should stay
+//! well above the quietest normal gap between ticks.
+
+use std::env;
+use std::time::Duration;
+
+use crate::error::{BotError, Result};
+
+const DEFAULT_MAX_AGE_SECS: u64 = 120;
+
+/// Result of checking one price's age
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceFreshness {
+    Fresh,
+    /// Fresh again after being stale
+    Recovered,
+    /// Older than the limit; `first` on the check that found it stale
+    Stale {
+        age: Duration,
+        first: bool,
+    },
+}
+
+/// Refuses prices older than a limit, remembering whether the feed is stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalePriceGuard {
+    max_age: Option<Duration>,
+    stale: bool,
+}
+
+impl Default for StalePriceGuard {
+    fn default() -> Self {
+        Self::new(Some(Duration::from_secs(DEFAULT_MAX_AGE_SECS)))
+    }
+}
+
+impl StalePriceGuard {
+    /// `None` accepts prices of any age
+    pub fn new(max_age: Option<Duration>) -> Self {
+        Self {
+            max_age,
+            stale: false,
+        }
+    }
+
+    /// Load from `MAX_PRICE_AGE_SECS`
+    pub fn from_env() -> Result<Self> {
+        let secs = match env::var("MAX_PRICE_AGE_SECS") {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| BotError::Config(format!("invalid MAX_PRICE_AGE_SECS: {:?}", raw)))?,
+            _ => DEFAULT_MAX_AGE_SECS,
+        };
+        Ok(Self::new((secs > 0).then(|| Duration::from_secs(secs))))
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Whether the last checked price was stale
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Check a price received `age` ago
+    pub fn check(&mut self, age: Duration) -> PriceFreshness {
+        let stale = self.max_age.is_some_and(|limit| age > limit);
+        let was_stale = std::mem::replace(&mut self.stale, stale);
+        match (stale, was_stale) {
+            (true, _) => PriceFreshness::Stale {
+                age,
+                first: !was_stale,
+            },
+            (false, true) => PriceFreshness::Recovered,
+            (false, false) => PriceFreshness::Fresh,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_prices_are_refused_until_ticks_resume() {
+        let mut guard = StalePriceGuard::new(Some(Duration::from_secs(60)));
+        let secs = Duration::from_secs;
+
+        assert_eq!(guard.check(secs(5)), PriceFreshness::Fresh);
+        assert_eq!(guard.check(secs(60)), PriceFreshness::Fresh);
+        assert_eq!(
+            guard.check(secs(61)),
+            PriceFreshness::Stale {
+                age: secs(61),
+                first: true
+            }
+        );
+        assert!(guard.is_stale());
+        assert_eq!(
+            guard.check(secs(120)),
+            PriceFreshness::Stale {
+                age: secs(120),
+                first: false
+            }
+        );
+        assert_eq!(guard.check(secs(1)), PriceFreshness::Recovered);
+        assert_eq!(guard.check(secs(2)), PriceFreshness::Fresh);
+        assert!(!guard.is_stale());
+    }
+
+    #[test]
+    fn test_disabled_guard_accepts_any_age() {
+        let mut guard = StalePriceGuard::new(None);
+        assert_eq!(
+            guard.check(Duration::from_secs(86_400)),
+            PriceFreshness::Fresh
+        );
+        assert_eq!(
+            StalePriceGuard::default().max_age(),
+            Some(Duration::from_secs(120))
+        );
+    }
+}
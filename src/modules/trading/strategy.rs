@@ -3,17 +3,32 @@
 //! Implements the trading logic combining RSI and sentiment analysis.
 //! Includes risk management with position limits and daily loss circuit breaker.
 
-use crate::config::{StrategyConfig, TradingConfig};
-use crate::error::Result;
+use crate::config::{AtrExitConfig, RsiExitMode, SizingPolicy, StrategyConfig, TradingConfig};
+use crate::error::{BotError, Result};
+use crate::modules::utils::MarketCalendar;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use tracing::{debug, info, warn};
 
+use super::blackout::{BlackoutSchedule, BlackoutWindow};
+use super::candles::Candle;
 use super::circuit_breakers::{CircuitBreakers, CircuitBreakerConfig};
-use super::indicators::{EmaCalculator, Trend};
+use super::decision::{DecisionFactor, SignalDecision};
+use super::features::{StrategyFeature, StrategyFeatures};
+use super::indicators::{AtrCalculator, EmaCalculator, MacdCalculator, MacdValues, Trend};
 use super::orders::{CloseReason, OrderSide, Position, PositionManager};
+use super::structure_stop::{StructureStop, StructureStopConfig};
+use super::volatility::{VolatilityConfig, VolatilityDetector, VolatilityRegime};
+
+/// RSI points added to the entry thresholds when sentiment sources fully disagree
+const DEFAULT_DISAGREEMENT_RSI_MARGIN: f64 = 10.0;
+
+/// ATR period when ATR exits are not configured (ATR is still tracked)
+const DEFAULT_ATR_PERIOD: usize = 14;
 
 /// Trading signal
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum Signal {
     /// Buy signal
     Buy,
@@ -23,6 +38,139 @@ pub enum Signal {
     Hold,
 }
 
+/// How an allowed signal enters the market
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    /// New position at full size
+    New,
+    /// Another leg on a winning position (`leg` 1 is the first add), sized
+    /// at `size_factor` of a full position
+    ScaleIn { leg: usize, size_factor: f64 },
+}
+
+impl EntryKind {
+    /// Multiplier applied to the risk-based position size
+    pub fn size_factor(&self) -> f64 {
+        match self {
+            EntryKind::New => 1.0,
+            EntryKind::ScaleIn { size_factor, .. } => *size_factor,
+        }
+    }
+}
+
+/// What a signal against open positions does (`OPPOSITE_SIGNAL_POLICY`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OppositeSignalPolicy {
+    /// Positions are left to their exits; the signal only enters if max
+    /// positions allows it
+    #[default]
+    Ignore,
+    /// Positions are closed; no entry on the signal
+    Close,
+    /// Positions are closed and the signal enters the other way
+    Reverse,
+}
+
+impl std::str::FromStr for OppositeSignalPolicy {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "ignore" => Ok(Self::Ignore),
+            "close" => Ok(Self::Close),
+            "reverse" | "close_and_reverse" => Ok(Self::Reverse),
+            other => Err(BotError::Config(format!(
+                "OPPOSITE_SIGNAL_POLICY must be ignore, close or reverse, got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+impl OppositeSignalPolicy {
+    /// Load from `OPPOSITE_SIGNAL_POLICY` (default `ignore`)
+    pub fn from_env() -> Result<Self> {
+        std::env::var("OPPOSITE_SIGNAL_POLICY")
+            .unwrap_or_default()
+            .parse()
+    }
+}
+
+impl std::fmt::Display for OppositeSignalPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OppositeSignalPolicy::Ignore => write!(f, "ignore"),
+            OppositeSignalPolicy::Close => write!(f, "close"),
+            OppositeSignalPolicy::Reverse => write!(f, "reverse"),
+        }
+    }
+}
+
+/// Built-in strategy the bot trades (`STRATEGY`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrategyKind {
+    /// RSI extremes confirmed by sentiment (`TradingStrategy`)
+    #[default]
+    RsiSentiment,
+    /// Session-VWAP reversion with a sentiment gate (`VwapReversion`)
+    VwapReversion,
+    /// EMA cross + ADX trend following with pyramiding (`TrendFollowing`)
+    TrendFollowing,
+}
+
+impl std::str::FromStr for StrategyKind {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "rsi" | "rsi_sentiment" => Ok(Self::RsiSentiment),
+            "vwap" | "vwap_reversion" => Ok(Self::VwapReversion),
+            "trend" | "trend_following" => Ok(Self::TrendFollowing),
+            other => Err(BotError::Config(format!(
+                "STRATEGY must be rsi_sentiment, vwap_reversion or trend_following, got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+impl StrategyKind {
+    /// Load from `STRATEGY` (default `rsi_sentiment`)
+    pub fn from_env() -> Result<Self> {
+        std::env::var("STRATEGY").unwrap_or_default().parse()
+    }
+
+    /// Candle timeframe used when `RSI_TIMEFRAME` is unset
+    pub fn default_timeframe(&self) -> &'static str {
+        match self {
+            StrategyKind::TrendFollowing => "1h",
+            StrategyKind::RsiSentiment | StrategyKind::VwapReversion => "5m",
+        }
+    }
+}
+
+impl std::fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrategyKind::RsiSentiment => write!(f, "rsi_sentiment"),
+            StrategyKind::VwapReversion => write!(f, "vwap_reversion"),
+            StrategyKind::TrendFollowing => write!(f, "trend_following"),
+        }
+    }
+}
+
+impl Signal {
+    /// Order side the signal points to (`None` for `Hold`)
+    pub fn side(&self) -> Option<OrderSide> {
+        match self {
+            Signal::Buy => Some(OrderSide::Buy),
+            Signal::Sell => Some(OrderSide::Sell),
+            Signal::Hold => None,
+        }
+    }
+}
+
 impl std::fmt::Display for Signal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -33,6 +181,59 @@ impl std::fmt::Display for Signal {
     }
 }
 
+/// Most recent entry, for the same-direction entry cooldown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntryMark {
+    pub side: OrderSide,
+    pub at: DateTime<Utc>,
+    /// Closed candles the strategy had seen when the entry was made
+    pub candle: u64,
+}
+
+/// Kelly criterion estimated from recent closed trades
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KellyEstimate {
+    /// Closed trades in the estimate
+    pub trades: usize,
+    /// Share of winning trades (0.0-1.0)
+    pub win_rate: f64,
+    /// Average win divided by average loss
+    pub payoff: f64,
+    /// Full Kelly fraction `W - (1 - W) / R`; at or below zero means the
+    /// recent record has no edge
+    pub fraction: f64,
+}
+
+impl KellyEstimate {
+    /// Estimate from realized P&L; `None` without at least one win and one
+    /// loss
+    pub fn from_results<'a>(results: impl IntoIterator<Item = &'a f64>) -> Option<Self> {
+        let (mut wins, mut win_total, mut losses, mut loss_total) = (0usize, 0.0, 0usize, 0.0);
+        for &pnl in results {
+            if pnl > 0.0 {
+                wins += 1;
+                win_total += pnl;
+            } else if pnl < 0.0 {
+                losses += 1;
+                loss_total -= pnl;
+            }
+        }
+        if wins == 0 || losses == 0 {
+            return None;
+        }
+
+        let trades = wins + losses;
+        let win_rate = wins as f64 / trades as f64;
+        let payoff = (win_total / wins as f64) / (loss_total / losses as f64);
+        Some(Self {
+            trades,
+            win_rate,
+            payoff,
+            fraction: win_rate - (1.0 - win_rate) / payoff,
+        })
+    }
+}
+
 /// Risk management state
 #[derive(Debug, Clone)]
 pub struct RiskState {
@@ -46,6 +247,8 @@ pub struct RiskState {
     pub consecutive_losses: u32,
     /// Today's trade count
     pub daily_trades: u32,
+    /// Last entry made (kept across days, like the cooldown it drives)
+    pub last_entry: Option<EntryMark>,
 }
 
 impl Default for RiskState {
@@ -58,6 +261,7 @@ impl Default for RiskState {
             circuit_breaker: false,
             consecutive_losses: 0,
             daily_trades: 0,
+            last_entry: None,
         }
     }
 }
@@ -65,9 +269,12 @@ impl Default for RiskState {
 impl RiskState {
     /// Check if a new trading day has started and reset if needed
     pub fn check_new_day(&mut self) {
-        let today = Utc::now().date_naive().and_hms_opt(0, 0, 0)
-            .map(|dt| dt.and_utc())
-            .unwrap_or_else(Utc::now);
+        self.check_new_day_at(Utc::now());
+    }
+
+    /// Same as `check_new_day`, with the current time supplied (backtests)
+    pub fn check_new_day_at(&mut self, now: DateTime<Utc>) {
+        let today = start_of_day(now);
 
         if today > self.day_start {
             info!("New trading day started. Resetting risk state.");
@@ -112,6 +319,13 @@ impl RiskState {
     }
 }
 
+fn start_of_day(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|dt| dt.and_utc())
+        .unwrap_or(at)
+}
+
 /// Trading strategy combining RSI and sentiment analysis
 #[derive(Debug)]
 pub struct TradingStrategy {
@@ -129,10 +343,46 @@ pub struct TradingStrategy {
     ema: EmaCalculator,
     /// Current trend based on EMA
     current_trend: Trend,
-    /// Enable/disable trend filter
-    use_trend_filter: bool,
+    /// Components switched on or off (trend filter, sentiment gate, ...)
+    features: StrategyFeatures,
+    /// MACD (12/26/9) on candle closes, for entry confirmation
+    macd: MacdCalculator,
+    /// Require MACD confirmation of RSI extremes
+    use_macd_confirmation: bool,
+    /// Higher-timeframe RSI bounds for entries (buy below, sell above)
+    higher_rsi_bounds: Option<(f64, f64)>,
+    /// Latest RSI on the higher timeframe (None until ready)
+    higher_rsi: Option<f64>,
+    /// ATR on candles, for ATR-based SL/TP (`TradingConfig::atr_exits`)
+    atr: AtrCalculator,
     /// Circuit breakers for risk management
     circuit_breakers: CircuitBreakers,
+    /// Disagreement between sentiment sources for the current reading (0.0-1.0)
+    sentiment_dispersion: f64,
+    /// Share of the fundamental bias blended into sentiment (0 = ignored)
+    fundamentals_weight: f64,
+    /// Extra RSI distance required at full disagreement
+    disagreement_rsi_margin: f64,
+    /// Maximum open positions sharing one asset class (None = no limit)
+    max_positions_per_asset_class: Option<usize>,
+    /// Replayed market time driving daily resets (None = wall clock)
+    simulated_now: Option<DateTime<Utc>>,
+    /// Closed candles seen, the clock for `entry_cooldown_candles`
+    candles_seen: u64,
+    /// Exchange sessions; no entries while closed (None = always open)
+    market_calendar: Option<MarketCalendar>,
+    /// Report releases; no entries during their windows
+    blackout: Option<BlackoutSchedule>,
+    /// Realized P&L of the latest closed trades, for Kelly sizing
+    recent_results: VecDeque<f64>,
+    /// Base units in one broker lot, for fixed-lot sizing (None = unknown)
+    units_per_lot: Option<f64>,
+    /// Realized-volatility regime; scales sizes down or stands aside
+    volatility: Option<VolatilityDetector>,
+    /// Handling of signals against open positions
+    opposite_signal_policy: OppositeSignalPolicy,
+    /// Swing / chandelier stops from the recent candles (None = off)
+    structure_stop: Option<StructureStop>,
 }
 
 impl TradingStrategy {
@@ -148,6 +398,11 @@ impl TradingStrategy {
             volatility_threshold: 2.0,
         };
 
+        let atr_period = trading_config
+            .atr_exits
+            .map(|atr| atr.period)
+            .unwrap_or(DEFAULT_ATR_PERIOD);
+
         Self {
             strategy_config,
             trading_config,
@@ -156,8 +411,26 @@ impl TradingStrategy {
             account_balance,
             ema: EmaCalculator::new(50), // 50-period EMA for trend
             current_trend: Trend::Neutral,
-            use_trend_filter: true,
+            features: StrategyFeatures::default(),
+            macd: MacdCalculator::default(),
+            use_macd_confirmation: false,
+            higher_rsi_bounds: None,
+            higher_rsi: None,
+            atr: AtrCalculator::new(atr_period),
             circuit_breakers: CircuitBreakers::new(circuit_breaker_config),
+            sentiment_dispersion: 0.0,
+            fundamentals_weight: 0.0,
+            disagreement_rsi_margin: DEFAULT_DISAGREEMENT_RSI_MARGIN,
+            max_positions_per_asset_class: None,
+            simulated_now: None,
+            candles_seen: 0,
+            market_calendar: None,
+            blackout: None,
+            recent_results: VecDeque::new(),
+            units_per_lot: None,
+            volatility: None,
+            opposite_signal_policy: OppositeSignalPolicy::default(),
+            structure_stop: None,
         }
     }
 
@@ -172,67 +445,366 @@ impl TradingStrategy {
         }
     }
 
+    /// Update candle-based indicators (MACD, ATR) with a closed candle
+    pub fn update_candle(&mut self, candle: &Candle) {
+        self.macd.update(candle.close);
+        self.atr.update(candle.high, candle.low, candle.close);
+        self.candles_seen += 1;
+        if let Some(structure_stop) = &mut self.structure_stop {
+            structure_stop.update(candle);
+        }
+        if let Some(volatility) = &mut self.volatility {
+            let before = volatility.regime();
+            volatility.update(candle.close);
+            let after = volatility.regime();
+            if after != before {
+                let ratio = volatility.reading().map(|r| r.ratio()).unwrap_or(1.0);
+                info!(
+                    "Volatility regime {} -> {} (realized/baseline {:.2})",
+                    before, after, ratio
+                );
+            }
+        }
+    }
+
+    /// Record the higher-timeframe RSI for the next signals
+    pub fn set_higher_rsi(&mut self, rsi: Option<f64>) {
+        self.higher_rsi = rsi;
+    }
+
+    /// Record how much sentiment sources disagree for the next signals
+    pub fn set_sentiment_dispersion(&mut self, dispersion: f64) {
+        self.sentiment_dispersion = dispersion.clamp(0.0, 1.0);
+    }
+
+    /// Blend the fundamental bias into sentiment with this share (0..1,
+    /// `FUNDAMENTALS_WEIGHT`)
+    pub fn set_fundamentals_weight(&mut self, weight: f64) {
+        self.fundamentals_weight = weight.clamp(0.0, 1.0);
+    }
+
+    /// Sentiment with the fundamental bias blended in, if there is one
+    pub fn blend_fundamentals(&self, sentiment: i32, fundamental_bias: Option<i32>) -> i32 {
+        match fundamental_bias {
+            Some(bias) => {
+                let w = self.fundamentals_weight;
+                (sentiment as f64 * (1.0 - w) + bias as f64 * w).round() as i32
+            }
+            None => sentiment,
+        }
+    }
+
+    /// Set the extra RSI distance required when sources fully disagree
+    pub fn set_disagreement_rsi_margin(&mut self, margin: f64) {
+        self.disagreement_rsi_margin = margin.max(0.0);
+    }
+
+    /// Limit how many open positions may share an asset class
+    pub fn set_max_positions_per_asset_class(&mut self, limit: Option<usize>) {
+        self.max_positions_per_asset_class = limit;
+    }
+
+    /// Drive daily risk resets from replayed market time instead of the wall
+    /// clock (backtests). The first call also starts the trading day there.
+    pub fn set_simulated_time(&mut self, now: DateTime<Utc>) {
+        if self.simulated_now.is_none() {
+            self.risk_state.day_start = start_of_day(now);
+        }
+        self.simulated_now = Some(now);
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.simulated_now.unwrap_or_else(Utc::now)
+    }
+
+    /// Only allow entries during the calendar's sessions
+    pub fn set_market_calendar(&mut self, calendar: Option<MarketCalendar>) {
+        self.market_calendar = calendar;
+    }
+
+    pub fn market_calendar(&self) -> Option<&MarketCalendar> {
+        self.market_calendar.as_ref()
+    }
+
+    /// Refuse entries during the schedule's news blackout windows
+    pub fn set_blackout(&mut self, schedule: Option<BlackoutSchedule>) {
+        self.blackout = schedule;
+    }
+
+    pub fn blackout(&self) -> Option<&BlackoutSchedule> {
+        self.blackout.as_ref()
+    }
+
+    /// For refreshing the schedule's feed in place
+    pub fn blackout_mut(&mut self) -> Option<&mut BlackoutSchedule> {
+        self.blackout.as_mut()
+    }
+
+    /// What a signal against open positions does
+    pub fn set_opposite_signal_policy(&mut self, policy: OppositeSignalPolicy) {
+        self.opposite_signal_policy = policy;
+    }
+
+    pub fn opposite_signal_policy(&self) -> OppositeSignalPolicy {
+        self.opposite_signal_policy
+    }
+
+    /// Exit reason for `position` when `signal` forms: `SignalReversal` if
+    /// the signal points the other way and the opposite-signal policy is not
+    /// `Ignore`. Applies alongside the position's TP/SL, not instead of them.
+    pub fn check_signal_exit(&self, position: &Position, signal: Signal) -> Option<CloseReason> {
+        let side = signal.side()?;
+        (self.opposite_signal_policy != OppositeSignalPolicy::Ignore
+            && position.symbol == self.trading_config.symbol
+            && position.side == side.opposite())
+        .then_some(CloseReason::SignalReversal)
+    }
+
+    /// Exit reason for `position` when the RSI moves from `previous_rsi` to
+    /// `rsi` on a closed candle: in mean-reversion mode a long closes once
+    /// RSI crosses back above the long exit level and a short once it
+    /// crosses back below the short exit level. Needs a previous reading, so
+    /// a position opened above the level does not close on the next candle.
+    pub fn check_rsi_exit(
+        &self,
+        position: &Position,
+        previous_rsi: Option<f64>,
+        rsi: f64,
+    ) -> Option<CloseReason> {
+        let RsiExitMode::MeanReversion {
+            long_exit,
+            short_exit,
+        } = self.strategy_config.rsi_exit
+        else {
+            return None;
+        };
+        let previous = previous_rsi?;
+        if position.symbol != self.trading_config.symbol {
+            return None;
+        }
+        let crossed = match position.side {
+            OrderSide::Buy => previous < long_exit && rsi >= long_exit,
+            OrderSide::Sell => previous > short_exit && rsi <= short_exit,
+        };
+        crossed.then_some(CloseReason::Signal)
+    }
+
+    /// Track realized volatility: sizes are scaled down in an elevated
+    /// regime and entries refused in an extreme one
+    pub fn set_volatility(&mut self, config: Option<VolatilityConfig>) {
+        if let Some(config) = &config {
+            self.circuit_breakers.set_volatility_threshold(config.halt_ratio);
+        }
+        self.volatility = config.map(VolatilityDetector::new);
+    }
+
+    /// Place and tighten stops behind recent price structure instead of a
+    /// fixed distance from the entry
+    pub fn set_structure_stop(&mut self, config: Option<StructureStopConfig>) {
+        self.structure_stop = config.map(StructureStop::new);
+    }
+
+    pub fn structure_stop(&self) -> Option<&StructureStop> {
+        self.structure_stop.as_ref()
+    }
+
+    /// Structure stop level for a new or open `side` position, once the
+    /// candle window (and ATR, when the mode needs it) is ready
+    fn structure_stop_level(&self, side: OrderSide) -> Option<f64> {
+        self.structure_stop.as_ref()?.level(side, self.atr.current())
+    }
+
+    /// Tighter stop for `position` from the current structure level: `None`
+    /// when structure stops are off, the level would loosen the stop, or it is
+    /// already through `current_price` (the position exits on its own stop)
+    pub fn structure_stop_update(&self, position: &Position, current_price: f64) -> Option<f64> {
+        let level = self.structure_stop_level(position.side)?;
+        let valid = match (position.side, position.stop_loss) {
+            (OrderSide::Buy, sl) => level < current_price && sl.map_or(true, |sl| level > sl),
+            (OrderSide::Sell, sl) => level > current_price && sl.map_or(true, |sl| level < sl),
+        };
+        valid.then_some(level)
+    }
+
+    pub fn volatility(&self) -> Option<&VolatilityDetector> {
+        self.volatility.as_ref()
+    }
+
+    /// Current volatility regime (normal without a detector)
+    pub fn volatility_regime(&self) -> VolatilityRegime {
+        self.volatility
+            .as_ref()
+            .map_or(VolatilityRegime::Normal, VolatilityDetector::regime)
+    }
+
+    /// News blackout window in force now
+    pub fn active_blackout(&self) -> Option<&BlackoutWindow> {
+        if !self.features.is_enabled(StrategyFeature::NewsBlackout) {
+            return None;
+        }
+        self.blackout.as_ref()?.active(self.now())
+    }
+
+    /// Whether the symbol's market is in session now (always true without a
+    /// calendar)
+    pub fn market_open(&self) -> bool {
+        !self.features.is_enabled(StrategyFeature::SessionFilter)
+            || self
+                .market_calendar
+                .as_ref()
+                .is_none_or(|calendar| calendar.is_open(self.now()))
+    }
+
+    /// Number of open positions tagged with the given asset class
+    pub fn open_positions_in_asset_class(&self, asset_class: &str) -> usize {
+        self.position_manager
+            .open_positions()
+            .iter()
+            .filter(|p| p.asset_class.as_deref() == Some(asset_class))
+            .count()
+    }
+
+    /// Whether opening another position in `asset_class` would exceed the limit.
+    /// Unclassified symbols are never limited.
+    pub fn asset_class_limit_reached(&self, asset_class: Option<&str>) -> bool {
+        match (self.max_positions_per_asset_class, asset_class) {
+            (Some(limit), Some(class)) => self.open_positions_in_asset_class(class) >= limit,
+            _ => false,
+        }
+    }
+
+    /// RSI oversold threshold, tightened by source disagreement
+    pub fn effective_rsi_oversold(&self) -> f64 {
+        self.strategy_config.rsi_oversold - self.disagreement_rsi_margin * self.sentiment_dispersion
+    }
+
+    /// RSI overbought threshold, tightened by source disagreement
+    pub fn effective_rsi_overbought(&self) -> f64 {
+        self.strategy_config.rsi_overbought + self.disagreement_rsi_margin * self.sentiment_dispersion
+    }
+
     /// Check if conditions indicate a BUY signal
     ///
     /// Buy when:
     /// - RSI < 30 (oversold)
-    /// - Sentiment > 30 (bullish)
+    /// - Sentiment > 30 (bullish, unless the sentiment gate is switched off)
     /// - Trend is UP or Neutral (if trend filter enabled)
+    /// - MACD histogram rising (if MACD confirmation enabled)
+    /// - Higher-timeframe RSI below its buy bound (if configured)
     pub fn should_buy(&self, rsi: f64, sentiment: i32) -> bool {
-        let oversold = rsi < self.strategy_config.rsi_oversold;
-        let bullish = sentiment > self.strategy_config.sentiment_threshold;
-        let trend_ok = !self.use_trend_filter || self.current_trend.allows_buy();
-
-        debug!(
-            "Buy check: RSI={:.2} (<{:.2}? {}), Sentiment={} (>{}? {}), Trend={:?} (ok={})",
-            rsi,
-            self.strategy_config.rsi_oversold,
-            oversold,
-            sentiment,
-            self.strategy_config.sentiment_threshold,
-            bullish,
-            self.current_trend,
-            trend_ok
-        );
-
-        oversold && bullish && trend_ok
+        let factors = self.entry_factors(OrderSide::Buy, rsi, sentiment);
+        debug!("Buy check: {}", format_factors(&factors));
+        factors.iter().all(|factor| factor.passed)
     }
 
     /// Check if conditions indicate a SELL signal
     ///
     /// Sell when:
     /// - RSI > 70 (overbought)
-    /// - Sentiment < -30 (bearish)
+    /// - Sentiment < -30 (bearish, unless the sentiment gate is switched off)
     /// - Trend is DOWN or Neutral (if trend filter enabled)
+    /// - MACD histogram falling (if MACD confirmation enabled)
+    /// - Higher-timeframe RSI above its sell bound (if configured)
     pub fn should_sell(&self, rsi: f64, sentiment: i32) -> bool {
-        let overbought = rsi > self.strategy_config.rsi_overbought;
-        let bearish = sentiment < -self.strategy_config.sentiment_threshold;
-        let trend_ok = !self.use_trend_filter || self.current_trend.allows_sell();
+        let factors = self.entry_factors(OrderSide::Sell, rsi, sentiment);
+        debug!("Sell check: {}", format_factors(&factors));
+        factors.iter().all(|factor| factor.passed)
+    }
 
-        debug!(
-            "Sell check: RSI={:.2} (>{:.2}? {}), Sentiment={} (<-{}? {}), Trend={:?} (ok={})",
-            rsi,
-            self.strategy_config.rsi_overbought,
-            overbought,
-            sentiment,
-            self.strategy_config.sentiment_threshold,
-            bearish,
-            self.current_trend,
-            trend_ok
-        );
+    /// Entry conditions for one side, as checked by `should_buy` /
+    /// `should_sell`; components that are off or unconfigured are left out
+    fn entry_factors(&self, side: OrderSide, rsi: f64, sentiment: i32) -> Vec<DecisionFactor> {
+        let buy = side == OrderSide::Buy;
+        let mut factors = Vec::new();
+
+        let rsi_threshold = if buy {
+            self.effective_rsi_oversold()
+        } else {
+            self.effective_rsi_overbought()
+        };
+        let rsi_ok = if buy { rsi < rsi_threshold } else { rsi > rsi_threshold };
+        factors.push(DecisionFactor::new("rsi", Some(rsi), rsi_ok).with_threshold(rsi_threshold));
+
+        if self.features.is_enabled(StrategyFeature::SentimentGate) {
+            let threshold = self.strategy_config.sentiment_threshold;
+            let (threshold, ok) = if buy {
+                (threshold, sentiment > threshold)
+            } else {
+                (-threshold, sentiment < -threshold)
+            };
+            factors.push(
+                DecisionFactor::new("sentiment", Some(sentiment as f64), ok)
+                    .with_threshold(threshold as f64),
+            );
+        }
+
+        if self.features.is_enabled(StrategyFeature::TrendFilter) {
+            let ok = if buy {
+                self.current_trend.allows_buy()
+            } else {
+                self.current_trend.allows_sell()
+            };
+            factors.push(
+                DecisionFactor::new("trend", self.ema.current(), ok)
+                    .with_note(format!("{:?}", self.current_trend)),
+            );
+        }
+
+        if self.macd_confirmation_active() {
+            let ok = if buy {
+                self.macd.histogram_rising()
+            } else {
+                self.macd.histogram_falling()
+            };
+            let histogram = self.macd.current().map(|macd| macd.histogram);
+            factors.push(DecisionFactor::new("macd_histogram", histogram, ok));
+        }
+
+        if let (true, Some((buy_below, sell_above))) = (
+            self.features.is_enabled(StrategyFeature::HigherTimeframe),
+            self.higher_rsi_bounds,
+        ) {
+            let (bound, ok) = if buy {
+                (buy_below, self.higher_rsi.is_some_and(|r| r < buy_below))
+            } else {
+                (sell_above, self.higher_rsi.is_some_and(|r| r > sell_above))
+            };
+            factors.push(
+                DecisionFactor::new("higher_rsi", self.higher_rsi, ok).with_threshold(bound),
+            );
+        }
 
-        overbought && bearish && trend_ok
+        factors
     }
 
-    /// Generate trading signal based on RSI and sentiment
-    pub fn generate_signal(&self, rsi: f64, sentiment: i32) -> Signal {
-        if self.should_buy(rsi, sentiment) {
-            Signal::Buy
-        } else if self.should_sell(rsi, sentiment) {
-            Signal::Sell
+    /// Signal for the given RSI and sentiment, with the conditions behind it
+    ///
+    /// The factors are those of the side that fired or, for a hold, of the
+    /// side RSI leans to. The score averages RSI distance from 50 and
+    /// sentiment, each scaled to ±1 (positive = bullish).
+    pub fn evaluate_signal(&self, rsi: f64, sentiment: i32) -> SignalDecision {
+        let score = ((50.0 - rsi) / 50.0 + sentiment as f64 / 100.0) / 2.0;
+        let buy = self.entry_factors(OrderSide::Buy, rsi, sentiment);
+        let sell = self.entry_factors(OrderSide::Sell, rsi, sentiment);
+        let (signal, side, factors) = if buy.iter().all(|f| f.passed) {
+            (Signal::Buy, OrderSide::Buy, buy)
+        } else if sell.iter().all(|f| f.passed) {
+            (Signal::Sell, OrderSide::Sell, sell)
+        } else if rsi < 50.0 {
+            (Signal::Hold, OrderSide::Buy, buy)
         } else {
-            Signal::Hold
-        }
+            (Signal::Hold, OrderSide::Sell, sell)
+        };
+        SignalDecision::new(signal)
+            .with_score(score)
+            .with_side(side)
+            .with_factors(factors)
+    }
+
+    /// Generate trading signal based on RSI and sentiment (the signal of
+    /// `evaluate_signal`)
+    pub fn generate_signal(&self, rsi: f64, sentiment: i32) -> Signal {
+        self.evaluate_signal(rsi, sentiment).signal
     }
 
     /// Check if take profit is hit for a position
@@ -255,7 +827,8 @@ impl TradingStrategy {
 
     /// Check if stop loss is hit for a position
     ///
-    /// Stop loss at -1.5% (configurable)
+    /// Stop loss at -1.5% (configurable), or at the position's own stop with
+    /// structure stops
     pub fn check_stop_loss(&self, position: &Position, current_price: f64) -> bool {
         let pnl_percent = position.calculate_pnl_percent(current_price);
         let sl_threshold = -self.trading_config.stop_loss_percent;
@@ -268,7 +841,23 @@ impl TradingStrategy {
             pnl_percent <= sl_threshold
         );
 
+        // Structure stops replace the fixed percent
+        if self.structure_stop.is_some() && position.stop_loss.is_some() {
+            return position.is_stop_loss_hit(current_price);
+        }
+
+        // A stop already moved to entry or beyond (break-even) is honoured too
         pnl_percent <= sl_threshold
+            || (Self::stop_locks_in_entry(position) && position.is_stop_loss_hit(current_price))
+    }
+
+    /// Whether the position's stop sits at or beyond its entry price
+    fn stop_locks_in_entry(position: &Position) -> bool {
+        match (position.side, position.stop_loss) {
+            (_, None) => false,
+            (OrderSide::Buy, Some(sl)) => sl >= position.entry_price,
+            (OrderSide::Sell, Some(sl)) => sl <= position.entry_price,
+        }
     }
 
     /// Check position for exit conditions
@@ -282,13 +871,130 @@ impl TradingStrategy {
         }
     }
 
+    /// Whether `position` is far enough in profit to switch its stop to a
+    /// trailing stop (`trailing_activation_percent`)
+    pub fn trailing_activation_reached(&self, position: &Position, current_price: f64) -> bool {
+        self.trading_config
+            .trailing_activation_percent
+            .is_some_and(|activation| position.calculate_pnl_percent(current_price) >= activation)
+    }
+
+    /// Break-even stop for `position` once it is `break_even.activation_percent`
+    /// in profit: the entry moved `offset_percent` in the position's favour.
+    /// `None` before activation or when the stop is already there or better
+    pub fn break_even_stop(&self, position: &Position, current_price: f64) -> Option<f64> {
+        let config = self.trading_config.break_even?;
+        if position.calculate_pnl_percent(current_price) < config.activation_percent {
+            return None;
+        }
+        let offset = position.entry_price * config.offset_percent / 100.0;
+        let stop = match position.side {
+            OrderSide::Buy => position.entry_price + offset,
+            OrderSide::Sell => position.entry_price - offset,
+        };
+        let improves = match (position.side, position.stop_loss) {
+            (_, None) => true,
+            (OrderSide::Buy, Some(sl)) => stop > sl,
+            (OrderSide::Sell, Some(sl)) => stop < sl,
+        };
+        improves.then_some(stop)
+    }
+
     /// Check if we can open a new position (risk management)
     pub fn can_open_position(&mut self) -> Result<bool> {
+        if !self.trading_allowed()? {
+            return Ok(false);
+        }
+
+        // Check max positions limit
+        let current_positions = self.position_manager.count();
+        if current_positions >= self.trading_config.max_positions {
+            debug!(
+                "Max positions reached: {}/{}",
+                current_positions, self.trading_config.max_positions
+            );
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// How a `side` signal at `price` may enter, if at all. With scale-in
+    /// configured, a signal in the direction of open legs adds a smaller leg
+    /// while the position is winning and adds are left (max positions does
+    /// not apply to it); anything else needs `can_open_position`. Either
+    /// way, no entry is made while the same-direction cooldown runs.
+    pub fn entry_for(&mut self, side: OrderSide, price: f64) -> Result<Option<EntryKind>> {
+        let has_legs = !self
+            .position_manager
+            .legs(&self.trading_config.symbol, side)
+            .is_empty();
+        let entry = if self.trading_config.scale_in.is_some() && has_legs {
+            match self.scale_in_entry(side, price) {
+                Some(entry) => self.trading_allowed()?.then_some(entry),
+                None => None,
+            }
+        } else {
+            self.can_open_position()?.then_some(EntryKind::New)
+        };
+        if let Some(remaining) = self.entry_cooldown_remaining(side) {
+            debug!(
+                "Entry cooldown: {} more candle(s) before another {} entry",
+                remaining, side
+            );
+            return Ok(None);
+        }
+        Ok(entry)
+    }
+
+    /// Candles left before another `side` entry is allowed, while the
+    /// `entry_cooldown_candles` window after the last `side` entry runs
+    pub fn entry_cooldown_remaining(&self, side: OrderSide) -> Option<u64> {
+        let cooldown = self.trading_config.entry_cooldown_candles as u64;
+        let last = self.risk_state.last_entry.filter(|entry| entry.side == side)?;
+        let elapsed = self.candles_seen.saturating_sub(last.candle);
+        (elapsed < cooldown).then_some(cooldown - elapsed)
+    }
+
+    /// Next scale-in leg for the open `side` legs at `price`, when adds are
+    /// left and the price is `min_profit_percent` past their average entry
+    pub fn scale_in_entry(&self, side: OrderSide, price: f64) -> Option<EntryKind> {
+        let config = self.trading_config.scale_in?;
+        let symbol = &self.trading_config.symbol;
+        if !self.position_manager.legs(symbol, side.opposite()).is_empty() {
+            return None;
+        }
+        let legs = self.position_manager.legs(symbol, side).len();
+        if legs == 0 || legs > config.max_adds {
+            return None;
+        }
+        let average = self.position_manager.average_entry(symbol, side)?;
+        let profit_percent = match side {
+            OrderSide::Buy => (price - average) / average * 100.0,
+            OrderSide::Sell => (average - price) / average * 100.0,
+        };
+        if profit_percent < config.min_profit_percent {
+            debug!(
+                "Scale-in skipped: {:.2}% past average entry {:.5} (needs {:.2}%)",
+                profit_percent, average, config.min_profit_percent
+            );
+            return None;
+        }
+        Some(EntryKind::ScaleIn {
+            leg: legs,
+            size_factor: config.size_factor.powi(legs as i32),
+        })
+    }
+
+    /// Risk checks that apply to every entry: circuit breakers, daily loss
+    /// limit, the consecutive-loss cooldown, market hours and news blackouts
+    pub fn trading_allowed(&mut self) -> Result<bool> {
         // Check for new trading day and reset circuit breakers if needed
-        self.risk_state.check_new_day();
+        let now = self.now();
+        self.risk_state.check_new_day_at(now);
         
         // Sync circuit breakers daily reset
-        if self.risk_state.day_start > chrono::Utc::now() - chrono::Duration::hours(24) {
+        if self.risk_state.day_start > now - chrono::Duration::hours(24) {
             self.circuit_breakers.reset_daily();
         }
 
@@ -315,16 +1021,6 @@ impl TradingStrategy {
             return Ok(false);
         }
 
-        // Check max positions limit
-        let current_positions = self.position_manager.count();
-        if current_positions >= self.trading_config.max_positions {
-            debug!(
-                "Max positions reached: {}/{}",
-                current_positions, self.trading_config.max_positions
-            );
-            return Ok(false);
-        }
-
         // Check consecutive losses (cool down after 3 consecutive losses)
         if self.risk_state.consecutive_losses >= 3 {
             warn!(
@@ -334,34 +1030,137 @@ impl TradingStrategy {
             return Ok(false);
         }
 
+        // Exits of open positions do not come through here
+        if !self.market_open() {
+            debug!("Market closed - no new positions");
+            return Ok(false);
+        }
+
+        if let Some(window) = self.active_blackout() {
+            debug!("News blackout {} - no new positions", window);
+            return Ok(false);
+        }
+
+        // Realized volatility against its baseline feeds the volatility breaker
+        if let Some(reading) = self.volatility.as_ref().and_then(|v| v.reading()) {
+            if self.circuit_breakers.check_volatility(reading.realized, reading.baseline) {
+                return Ok(false);
+            }
+        }
+
         Ok(true)
     }
 
     /// Calculate take profit price for a given entry price and side
+    ///
+    /// `take_profit_multiplier × ATR` away in ATR mode once ATR is ready,
+    /// `take_profit_percent` otherwise
     pub fn calculate_take_profit(&self, entry_price: f64, side: OrderSide) -> f64 {
-        let tp_percent = self.trading_config.take_profit_percent / 100.0;
+        let distance = match self.atr_distance(|atr| atr.take_profit_multiplier) {
+            Some(distance) => distance,
+            None => entry_price * self.trading_config.take_profit_percent / 100.0,
+        };
         match side {
-            OrderSide::Buy => entry_price * (1.0 + tp_percent),
-            OrderSide::Sell => entry_price * (1.0 - tp_percent),
+            OrderSide::Buy => entry_price + distance,
+            OrderSide::Sell => entry_price - distance,
         }
     }
 
     /// Calculate stop loss price for a given entry price and side
+    ///
+    /// The structure level when structure stops are on, ready and on the
+    /// losing side of the entry; otherwise `stop_loss_multiplier × ATR` away
+    /// in ATR mode once ATR is ready, `stop_loss_percent` otherwise
     pub fn calculate_stop_loss(&self, entry_price: f64, side: OrderSide) -> f64 {
-        let sl_percent = self.trading_config.stop_loss_percent / 100.0;
+        let structure = self.structure_stop_level(side).filter(|level| match side {
+            OrderSide::Buy => *level < entry_price,
+            OrderSide::Sell => *level > entry_price,
+        });
+        if let Some(level) = structure {
+            return level;
+        }
+        let distance = match self.atr_distance(|atr| atr.stop_loss_multiplier) {
+            Some(distance) => distance,
+            None => entry_price * self.trading_config.stop_loss_percent / 100.0,
+        };
         match side {
-            OrderSide::Buy => entry_price * (1.0 - sl_percent),
-            OrderSide::Sell => entry_price * (1.0 + sl_percent),
+            OrderSide::Buy => entry_price - distance,
+            OrderSide::Sell => entry_price + distance,
         }
     }
 
-    /// Calculate position size based on risk (returns base currency units)
+    /// `multiplier × ATR` when ATR exits are configured and ATR is ready
+    fn atr_distance(&self, multiplier: impl Fn(&AtrExitConfig) -> f64) -> Option<f64> {
+        let config = self.trading_config.atr_exits.as_ref()?;
+        match self.atr.current() {
+            Some(atr) if atr > 0.0 => Some(multiplier(config) * atr),
+            _ => {
+                debug!("ATR not ready; using percentage SL/TP");
+                None
+            }
+        }
+    }
+
+    /// Get current ATR value
+    pub fn current_atr(&self) -> Option<f64> {
+        self.atr.current()
+    }
+
+    /// Calculate position size under the configured `SizingPolicy` (returns
+    /// base currency units)
     ///
-    /// Formula: volume = risk_amount / risk_per_unit
+    /// Fixed risk: volume = risk_amount / risk_per_unit. Kelly sizing changes
+    /// the risked percentage, fixed lots and volatility targeting ignore the
+    /// stop distance. An elevated volatility regime scales the result down.
     /// The result is in base currency units. normalize_volume() in bot.rs
     /// handles conversion to cTrader volume units and broker min/max/step.
     pub fn calculate_position_size(&self, entry_price: f64, stop_loss: f64) -> f64 {
-        let risk_amount = self.account_balance * (self.trading_config.risk_per_trade / 100.0);
+        let factor = self.volatility.as_ref().map_or(1.0, |v| v.size_factor());
+        self.policy_position_size(entry_price, stop_loss) * factor
+    }
+
+    fn policy_position_size(&self, entry_price: f64, stop_loss: f64) -> f64 {
+        let fixed_risk = self.trading_config.risk_per_trade;
+        let risk_percent = match self.trading_config.sizing {
+            SizingPolicy::FixedRisk => fixed_risk,
+            SizingPolicy::FixedLots { lots } => {
+                return match self.units_per_lot {
+                    Some(units) => lots * units,
+                    None => {
+                        warn!("Broker lot size unknown; cannot size a fixed-lot entry");
+                        0.0
+                    }
+                };
+            }
+            SizingPolicy::KellyFraction {
+                fraction,
+                max_risk_percent,
+                min_trades,
+                ..
+            } => match self.kelly_estimate() {
+                Some(kelly) if kelly.trades >= min_trades => {
+                    if kelly.fraction <= 0.0 {
+                        debug!(
+                            "Kelly fraction {:.3} over {} trades: no edge, no entry",
+                            kelly.fraction, kelly.trades
+                        );
+                        return 0.0;
+                    }
+                    (kelly.fraction * fraction * 100.0).min(max_risk_percent)
+                }
+                // Too little history to estimate from yet
+                _ => fixed_risk,
+            },
+            SizingPolicy::VolatilityTarget { target_percent } => match self.atr.current() {
+                Some(atr) if atr > 0.0 => {
+                    return self.account_balance * (target_percent / 100.0) / atr;
+                }
+                // ATR still warming up
+                _ => fixed_risk,
+            },
+        };
+
+        let risk_amount = self.account_balance * (risk_percent / 100.0);
         let risk_per_unit = (entry_price - stop_loss).abs();
 
         if risk_per_unit > 0.0 {
@@ -371,8 +1170,24 @@ impl TradingStrategy {
         }
     }
 
-    /// Add a position to the manager
+    /// Kelly criterion over the trades kept for `SizingPolicy::KellyFraction`
+    pub fn kelly_estimate(&self) -> Option<KellyEstimate> {
+        KellyEstimate::from_results(&self.recent_results)
+    }
+
+    /// Base units in one broker lot, from the symbol's lot size
+    pub fn set_units_per_lot(&mut self, units: Option<f64>) {
+        self.units_per_lot = units.filter(|u| *u > 0.0);
+    }
+
+    /// Add a newly opened position to the manager; it starts the entry
+    /// cooldown for its direction
     pub fn add_position(&mut self, position: Position) {
+        self.risk_state.last_entry = Some(EntryMark {
+            side: position.side,
+            at: position.opened_at,
+            candle: self.candles_seen,
+        });
         self.position_manager.add(position);
     }
 
@@ -383,9 +1198,30 @@ impl TradingStrategy {
         close_price: f64,
         reason: CloseReason,
     ) -> Option<f64> {
-        if let Some(closed) = self.position_manager.close(position_id, close_price, reason) {
+        self.close_position_with_costs(position_id, close_price, reason, 0.0)
+    }
+
+    /// Close a position and record the trade net of `costs` (commission,
+    /// swap); returns the net P&L
+    pub fn close_position_with_costs(
+        &mut self,
+        position_id: &str,
+        close_price: f64,
+        reason: CloseReason,
+        costs: f64,
+    ) -> Option<f64> {
+        if let Some(closed) = self
+            .position_manager
+            .close_with_costs(position_id, close_price, reason, costs)
+        {
             // Record trade in risk state
             self.risk_state.record_trade(closed.realized_pnl);
+            if let SizingPolicy::KellyFraction { lookback, .. } = self.trading_config.sizing {
+                self.recent_results.push_back(closed.realized_pnl);
+                while self.recent_results.len() > lookback {
+                    self.recent_results.pop_front();
+                }
+            }
             
             // Also record in circuit breakers
             let won = closed.realized_pnl > 0.0;
@@ -406,6 +1242,11 @@ impl TradingStrategy {
         self.account_balance = balance;
     }
 
+    /// Account balance used for sizing and risk limits
+    pub fn account_balance(&self) -> f64 {
+        self.account_balance
+    }
+
     /// Get position manager reference
     pub fn position_manager(&self) -> &PositionManager {
         &self.position_manager
@@ -452,10 +1293,23 @@ impl TradingStrategy {
         self.risk_state.circuit_breaker = false;
     }
 
+    /// Record a trade result in the risk state and circuit breakers without a
+    /// position behind it (quick-test breaker scenarios)
+    pub fn record_synthetic_trade(&mut self, pnl: f64) {
+        self.risk_state.record_trade(pnl);
+        self.circuit_breakers.record_trade_result(pnl > 0.0);
+    }
+
+    /// Put back a risk state saved from `risk_state()` and clear the circuit
+    /// breakers
+    pub fn restore_risk_state(&mut self, state: RiskState) {
+        self.risk_state = state;
+        self.circuit_breakers.force_reset();
+    }
+
     /// Enable or disable trend filter
     pub fn set_trend_filter(&mut self, enabled: bool) {
-        self.use_trend_filter = enabled;
-        info!("Trend filter {}", if enabled { "enabled" } else { "disabled" });
+        self.set_feature(StrategyFeature::TrendFilter, enabled);
     }
 
     /// Get current trend
@@ -470,13 +1324,208 @@ impl TradingStrategy {
 
     /// Check if trend filter is enabled
     pub fn is_trend_filter_enabled(&self) -> bool {
-        self.use_trend_filter
+        self.features.is_enabled(StrategyFeature::TrendFilter)
+    }
+
+    /// Switch a strategy component on or off; returns whether anything changed
+    pub fn set_feature(&mut self, feature: StrategyFeature, enabled: bool) -> bool {
+        let changed = self.features.set(feature, enabled);
+        if changed {
+            info!(
+                "Strategy feature {} {}",
+                feature,
+                if enabled { "enabled" } else { "disabled" }
+            );
+        }
+        changed
+    }
+
+    /// Replace the whole feature set (`STRATEGY_FEATURES_DISABLED` at startup)
+    pub fn set_features(&mut self, features: StrategyFeatures) {
+        if features != self.features {
+            info!("Strategy features: {}", features);
+        }
+        self.features = features;
+    }
+
+    pub fn features(&self) -> &StrategyFeatures {
+        &self.features
+    }
+
+    /// Require the MACD histogram to turn in the trade's direction before
+    /// acting on RSI extremes. Entries are blocked until MACD has two values.
+    pub fn set_macd_confirmation(&mut self, enabled: bool) {
+        self.use_macd_confirmation = enabled;
+        info!(
+            "MACD confirmation {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    /// Get current MACD values
+    pub fn current_macd(&self) -> Option<MacdValues> {
+        self.macd.current()
+    }
+
+    /// Check if MACD confirmation is enabled
+    pub fn is_macd_confirmation_enabled(&self) -> bool {
+        self.use_macd_confirmation
+    }
+
+    /// MACD confirmation configured and not switched off
+    fn macd_confirmation_active(&self) -> bool {
+        self.use_macd_confirmation && self.features.is_enabled(StrategyFeature::MacdConfirmation)
+    }
+
+    /// Only buy while the higher-timeframe RSI is below `buy_below` and only
+    /// sell while it is above `sell_above`. Entries are blocked until the
+    /// higher-timeframe RSI is ready.
+    pub fn set_higher_rsi_filter(&mut self, buy_below: f64, sell_above: f64) {
+        self.higher_rsi_bounds = Some((buy_below, sell_above));
+        info!(
+            "Higher-timeframe RSI filter enabled (buy below {:.1}, sell above {:.1})",
+            buy_below, sell_above
+        );
+    }
+
+    /// Higher-timeframe RSI bounds (buy below, sell above), if the filter is on
+    pub fn higher_rsi_filter(&self) -> Option<(f64, f64)> {
+        self.higher_rsi_bounds
+    }
+}
+
+/// Factors joined for debug logs
+fn format_factors(factors: &[DecisionFactor]) -> String {
+    factors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Inputs available to a strategy when a candle closes
+#[derive(Debug, Clone, Copy)]
+pub struct SignalContext<'a> {
+    /// The candle that just closed
+    pub candle: &'a Candle,
+    /// RSI on the strategy timeframe, including this candle
+    pub rsi: f64,
+    /// Blended sentiment score (-100..100)
+    pub sentiment: i32,
+    /// Sentiment confidence (0.0..1.0)
+    pub sentiment_confidence: f64,
+    /// Disagreement between sentiment sources (0.0 = agree, 1.0 = opposite)
+    pub sentiment_dispersion: f64,
+    /// RSI on the higher confirmation timeframe (`HIGHER_TIMEFRAME`); None
+    /// when not configured or not ready yet
+    pub higher_rsi: Option<f64>,
+    /// Weather/production bias (-100..100, `FUNDAMENTALS_ENABLED`); None when
+    /// off or not read yet
+    pub fundamental_bias: Option<i32>,
+}
+
+/// Pluggable entry/exit logic driven by `TradingBot`
+///
+/// Implementations decide when to enter and exit and how large/where the
+/// brackets go. Bookkeeping shared by every strategy (open positions, balance,
+/// daily P&L and circuit breakers) stays in the wrapped `TradingStrategy`
+/// returned by `core()`, so risk limits apply no matter which logic trades.
+/// Only `generate_signal` is required; the other hooks default to the
+/// RSI + sentiment behaviour.
+pub trait Strategy: Send {
+    /// Short identifier used in logs
+    fn name(&self) -> &str {
+        "rsi_sentiment"
+    }
+
+    /// Position and risk bookkeeping
+    fn core(&self) -> &TradingStrategy;
+
+    /// Mutable position and risk bookkeeping
+    fn core_mut(&mut self) -> &mut TradingStrategy;
+
+    /// Called on every tick before exits are checked
+    fn on_price(&mut self, price: f64) {
+        self.core_mut().update_price(price);
+    }
+
+    /// Called once per closed candle, before `generate_signal`
+    fn on_candle(&mut self, candle: &Candle) {
+        self.core_mut().update_candle(candle);
+    }
+
+    /// Entry signal for a closed candle, with the reasons behind it (a bare
+    /// `Signal` converts with `.into()`)
+    fn generate_signal(&mut self, ctx: &SignalContext<'_>) -> SignalDecision;
+
+    /// Exit reason for an open position at `current_price`, if it should close
+    fn check_exit(&self, position: &Position, current_price: f64) -> Option<CloseReason> {
+        self.core().check_position_exit(position, current_price)
+    }
+
+    /// Exit reason for an open position when a closed candle gives `signal`
+    fn check_signal_exit(&self, position: &Position, signal: Signal) -> Option<CloseReason> {
+        self.core().check_signal_exit(position, signal)
+    }
+
+    /// Exit reason for an open position when a closed candle moves the RSI
+    fn check_rsi_exit(
+        &self,
+        position: &Position,
+        previous_rsi: Option<f64>,
+        rsi: f64,
+    ) -> Option<CloseReason> {
+        self.core().check_rsi_exit(position, previous_rsi, rsi)
+    }
+
+    /// Take-profit price for a new position
+    fn take_profit(&self, entry_price: f64, side: OrderSide) -> f64 {
+        self.core().calculate_take_profit(entry_price, side)
+    }
+
+    /// Stop-loss price for a new position
+    fn stop_loss(&self, entry_price: f64, side: OrderSide) -> f64 {
+        self.core().calculate_stop_loss(entry_price, side)
+    }
+
+    /// Position size in base currency units (normalized to broker units by the bot)
+    fn position_size(&self, entry_price: f64, stop_loss: f64) -> f64 {
+        self.core().calculate_position_size(entry_price, stop_loss)
+    }
+}
+
+impl Strategy for TradingStrategy {
+    fn core(&self) -> &TradingStrategy {
+        self
+    }
+
+    fn core_mut(&mut self) -> &mut TradingStrategy {
+        self
+    }
+
+    fn generate_signal(&mut self, ctx: &SignalContext<'_>) -> SignalDecision {
+        self.set_sentiment_dispersion(ctx.sentiment_dispersion);
+        self.set_higher_rsi(ctx.higher_rsi);
+        let sentiment = self.blend_fundamentals(ctx.sentiment, ctx.fundamental_bias);
+        let decision = self.evaluate_signal(ctx.rsi, sentiment);
+        match ctx.fundamental_bias {
+            Some(bias) if self.fundamentals_weight > 0.0 => decision.with_factor(
+                DecisionFactor::new("fundamentals", Some(bias as f64), true).with_note(format!(
+                    "{:.0}% of sentiment {}",
+                    self.fundamentals_weight * 100.0,
+                    ctx.sentiment
+                )),
+            ),
+            _ => decision,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{BreakEvenConfig, ScaleInConfig, SizingPolicy};
+    use crate::modules::trading::{TrendFollowingConfig, VwapReversionConfig};
 
     fn create_test_strategy() -> TradingStrategy {
         let strategy_config = StrategyConfig {
@@ -485,6 +1534,13 @@ mod tests {
             rsi_overbought: 70.0,
             rsi_timeframe: "5m".to_string(),
             sentiment_threshold: 30,
+            rsi_exit: RsiExitMode::Off,
+            macd_confirmation: false,
+            higher_timeframe: None,
+            opposite_signal_policy: OppositeSignalPolicy::Ignore,
+            kind: StrategyKind::RsiSentiment,
+            vwap: VwapReversionConfig::default(),
+            trend: TrendFollowingConfig::default(),
         };
 
         let trading_config = TradingConfig {
@@ -495,11 +1551,56 @@ mod tests {
             max_positions: 1,
             max_daily_loss_percent: 5.0,
             initial_balance: 10000.0,
+            trailing_activation_percent: None,
+            atr_exits: None,
+            break_even: None,
+            scale_in: None,
+            entry_cooldown_candles: 0,
+            sizing: SizingPolicy::FixedRisk,
         };
 
         TradingStrategy::new(strategy_config, trading_config, 10000.0)
     }
 
+    #[test]
+    fn test_trailing_activation() {
+        let mut strategy = create_test_strategy();
+        let position = Position::new("1".into(), "FCPO".into(), OrderSide::Buy, 1000.0, 1.0);
+        assert!(!strategy.trailing_activation_reached(&position, 1050.0));
+
+        strategy.trading_config.trailing_activation_percent = Some(1.0);
+        assert!(!strategy.trailing_activation_reached(&position, 1005.0));
+        assert!(strategy.trailing_activation_reached(&position, 1010.0));
+
+        let short = Position::new("2".into(), "FCPO".into(), OrderSide::Sell, 1000.0, 1.0);
+        assert!(strategy.trailing_activation_reached(&short, 990.0));
+    }
+
+    #[test]
+    fn test_break_even_stop() {
+        let mut strategy = create_test_strategy();
+        let position = Position::new("1".into(), "FCPO".into(), OrderSide::Buy, 1000.0, 1.0)
+            .with_stop_loss(985.0);
+        assert_eq!(strategy.break_even_stop(&position, 1020.0), None);
+
+        strategy.trading_config.break_even = Some(BreakEvenConfig {
+            activation_percent: 1.0,
+            offset_percent: 0.1,
+        });
+        assert_eq!(strategy.break_even_stop(&position, 1005.0), None);
+        assert_eq!(strategy.break_even_stop(&position, 1010.0), Some(1001.0));
+
+        // Already at break-even: nothing to do, and the stop closes the position
+        let moved = position.clone().with_stop_loss(1001.0);
+        assert_eq!(strategy.break_even_stop(&moved, 1015.0), None);
+        assert!(!strategy.check_stop_loss(&moved, 1002.0));
+        assert!(strategy.check_stop_loss(&moved, 1000.5));
+        assert!(!strategy.check_stop_loss(&position, 1000.5));
+
+        let short = Position::new("2".into(), "FCPO".into(), OrderSide::Sell, 1000.0, 1.0);
+        assert_eq!(strategy.break_even_stop(&short, 990.0), Some(999.0));
+    }
+
     #[test]
     fn test_should_buy() {
         let strategy = create_test_strategy();
@@ -545,6 +1646,26 @@ mod tests {
         assert_eq!(strategy.generate_signal(75.0, 50), Signal::Hold); // Overbought but bullish
     }
 
+    #[test]
+    fn test_evaluate_signal_explains_hold() {
+        let mut strategy = create_test_strategy();
+        strategy.set_trend_filter(false);
+
+        // Oversold but sentiment too weak: the buy side is explained
+        let decision = strategy.evaluate_signal(25.0, 10);
+        assert_eq!(decision.signal, Signal::Hold);
+        assert_eq!(decision.side, Some(OrderSide::Buy));
+        let failed: Vec<&str> = decision.failed_factors().map(|f| f.name.as_str()).collect();
+        assert_eq!(failed, vec!["sentiment"]);
+        assert!(decision.score > 0.0);
+        assert!(!decision.factors.iter().any(|f| f.name == "trend"));
+
+        let decision = strategy.evaluate_signal(75.0, -50);
+        assert_eq!(decision.signal, Signal::Sell);
+        assert!(decision.failed_factors().next().is_none());
+        assert!(decision.score < 0.0);
+    }
+
     #[test]
     fn test_get_open_positions() {
         let mut strategy = create_test_strategy();
@@ -629,6 +1750,31 @@ mod tests {
         assert!((sl - 4922.75).abs() < 0.01);  // +1.5% (loss for short)
     }
 
+    #[test]
+    fn test_atr_tp_sl() {
+        let mut strategy = create_test_strategy();
+        strategy.trading_config.atr_exits = Some(AtrExitConfig {
+            period: 14,
+            stop_loss_multiplier: 1.5,
+            take_profit_multiplier: 2.0,
+        });
+        let entry = 4850.0;
+
+        // ATR not ready: fixed percentages
+        assert!((strategy.calculate_stop_loss(entry, OrderSide::Buy) - 4777.25).abs() < 0.01);
+
+        // Constant 20-point candles -> ATR 20
+        for _ in 0..14 {
+            strategy.update_candle(&candle(4850.0, 4860.0, 4840.0, 4850.0));
+        }
+        assert!((strategy.current_atr().unwrap() - 20.0).abs() < 1e-9);
+
+        assert!((strategy.calculate_stop_loss(entry, OrderSide::Buy) - 4820.0).abs() < 1e-9);
+        assert!((strategy.calculate_take_profit(entry, OrderSide::Buy) - 4890.0).abs() < 1e-9);
+        assert!((strategy.calculate_stop_loss(entry, OrderSide::Sell) - 4880.0).abs() < 1e-9);
+        assert!((strategy.calculate_take_profit(entry, OrderSide::Sell) - 4810.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_can_open_position() {
         let mut strategy = create_test_strategy();
@@ -644,6 +1790,259 @@ mod tests {
         assert!(!strategy.can_open_position().unwrap());
     }
 
+    #[test]
+    fn test_scale_in_adds_smaller_legs_to_winners() {
+        let mut strategy = create_test_strategy();
+        strategy.add_position(Position::new("leg_1", "FCPO", OrderSide::Buy, 1000.0, 1.0));
+
+        // Without scale-in the open position blocks the signal
+        assert_eq!(strategy.entry_for(OrderSide::Buy, 1020.0).unwrap(), None);
+
+        strategy.trading_config.scale_in = Some(ScaleInConfig {
+            max_adds: 2,
+            size_factor: 0.5,
+            min_profit_percent: 1.0,
+        });
+        // Not winning enough yet
+        assert_eq!(strategy.entry_for(OrderSide::Buy, 1005.0).unwrap(), None);
+        assert_eq!(
+            strategy.entry_for(OrderSide::Buy, 1020.0).unwrap(),
+            Some(EntryKind::ScaleIn {
+                leg: 1,
+                size_factor: 0.5
+            })
+        );
+
+        strategy.add_position(Position::new("leg_2", "FCPO", OrderSide::Buy, 1020.0, 0.5));
+        // Average entry 1006.67: 1020 is only 1.3% past it
+        assert_eq!(
+            strategy.entry_for(OrderSide::Buy, 1020.0).unwrap(),
+            Some(EntryKind::ScaleIn {
+                leg: 2,
+                size_factor: 0.25
+            })
+        );
+
+        strategy.add_position(Position::new("leg_3", "FCPO", OrderSide::Buy, 1030.0, 0.25));
+        // Adds used up; the opposite side still needs a free slot
+        assert_eq!(strategy.entry_for(OrderSide::Buy, 1100.0).unwrap(), None);
+        assert_eq!(strategy.entry_for(OrderSide::Sell, 1100.0).unwrap(), None);
+
+        // Risk checks still apply to adds
+        strategy.close_position("leg_3", 1030.0, CloseReason::Manual);
+        strategy.risk_state.consecutive_losses = 3;
+        assert_eq!(strategy.entry_for(OrderSide::Buy, 1100.0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_entry_cooldown_after_same_direction_entry() {
+        let mut strategy = create_test_strategy();
+        strategy.trading_config.entry_cooldown_candles = 3;
+        let bar = candle(4850.0, 4860.0, 4840.0, 4850.0);
+
+        strategy.update_candle(&bar);
+        strategy.add_position(Position::new("pos_1", "FCPO", OrderSide::Buy, 4850.0, 1.0));
+        let mark = strategy.risk_state().last_entry.unwrap();
+        assert_eq!((mark.side, mark.candle), (OrderSide::Buy, 1));
+        strategy.close_position("pos_1", 4780.0, CloseReason::StopLoss);
+
+        // Stopped out on the entry candle: no immediate re-entry
+        assert_eq!(strategy.entry_cooldown_remaining(OrderSide::Buy), Some(3));
+        assert_eq!(strategy.entry_for(OrderSide::Buy, 4780.0).unwrap(), None);
+        // The other direction is not affected
+        assert_eq!(strategy.entry_cooldown_remaining(OrderSide::Sell), None);
+        assert_eq!(
+            strategy.entry_for(OrderSide::Sell, 4780.0).unwrap(),
+            Some(EntryKind::New)
+        );
+
+        strategy.update_candle(&bar);
+        strategy.update_candle(&bar);
+        assert_eq!(strategy.entry_cooldown_remaining(OrderSide::Buy), Some(1));
+        strategy.update_candle(&bar);
+        assert_eq!(
+            strategy.entry_for(OrderSide::Buy, 4780.0).unwrap(),
+            Some(EntryKind::New)
+        );
+    }
+
+    #[test]
+    fn test_opposite_signal_policy() {
+        let mut strategy = create_test_strategy();
+        let long = Position::new("long", "FCPO", OrderSide::Buy, 4850.0, 1.0);
+        strategy.add_position(long.clone());
+
+        // Default: the open long just blocks the sell
+        assert_eq!(strategy.check_signal_exit(&long, Signal::Sell), None);
+        assert_eq!(strategy.entry_for(OrderSide::Sell, 4800.0).unwrap(), None);
+
+        strategy.set_opposite_signal_policy("reverse".parse().unwrap());
+        assert_eq!(
+            strategy.check_signal_exit(&long, Signal::Sell),
+            Some(CloseReason::SignalReversal)
+        );
+        // Signals in the position's own direction, or none, close nothing
+        assert_eq!(strategy.check_signal_exit(&long, Signal::Buy), None);
+        assert_eq!(strategy.check_signal_exit(&long, Signal::Hold), None);
+
+        assert_eq!(
+            "Close".parse::<OppositeSignalPolicy>().unwrap(),
+            OppositeSignalPolicy::Close
+        );
+        assert_eq!(
+            "".parse::<OppositeSignalPolicy>().unwrap(),
+            OppositeSignalPolicy::Ignore
+        );
+        assert!("flip".parse::<OppositeSignalPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_structure_stop_placement_and_tightening() {
+        use crate::modules::trading::{StructureStopConfig, StructureStopMode};
+
+        let mut strategy = create_test_strategy();
+        strategy.set_structure_stop(Some(StructureStopConfig {
+            mode: StructureStopMode::Chandelier,
+            lookback: 3,
+            atr_multiplier: 2.0,
+        }));
+        // Window not full: percent stop
+        assert_eq!(strategy.calculate_stop_loss(1000.0, OrderSide::Buy), 985.0);
+
+        // 20-point candles -> ATR 20 once ready
+        for i in 0..15 {
+            let base = 1000.0 + i as f64;
+            strategy.update_candle(&candle(base, base + 10.0, base - 10.0, base));
+        }
+        // Highest high 1024 - 2 x 20
+        assert!((strategy.calculate_stop_loss(1014.0, OrderSide::Buy) - 984.0).abs() < 1e-9);
+        // Not below the entry: percent stop instead
+        assert!((strategy.calculate_stop_loss(980.0, OrderSide::Buy) - 965.3).abs() < 1e-9);
+
+        let position = Position::new("pos_1", "FCPO", OrderSide::Buy, 1014.0, 1.0)
+            .with_stop_loss(984.0);
+        assert_eq!(strategy.structure_stop_update(&position, 1014.0), None);
+        strategy.update_candle(&candle(1020.0, 1040.0, 1020.0, 1035.0));
+        let tightened = strategy.structure_stop_update(&position, 1035.0).unwrap();
+        assert!(tightened > 984.0 && tightened < 1035.0);
+        // A level the price is already through is left to the stop
+        assert_eq!(strategy.structure_stop_update(&position, tightened - 1.0), None);
+
+        // The position's own stop replaces the fixed percent
+        assert!(!strategy.check_stop_loss(&position, 990.0));
+        assert!(strategy.check_stop_loss(&position, 984.0));
+    }
+
+    #[test]
+    fn test_rsi_mean_reversion_exit() {
+        let mut strategy = create_test_strategy();
+        let long = Position::new("long", "FCPO", OrderSide::Buy, 4850.0, 1.0);
+        let short = Position::new("short", "FCPO", OrderSide::Sell, 4850.0, 1.0);
+
+        // Off by default: positions wait for TP/SL
+        assert_eq!(strategy.check_rsi_exit(&long, Some(45.0), 55.0), None);
+
+        strategy.strategy_config.rsi_exit = RsiExitMode::MeanReversion {
+            long_exit: 50.0,
+            short_exit: 50.0,
+        };
+        assert_eq!(
+            strategy.check_rsi_exit(&long, Some(45.0), 55.0),
+            Some(CloseReason::Signal)
+        );
+        assert_eq!(
+            strategy.check_rsi_exit(&short, Some(55.0), 45.0),
+            Some(CloseReason::Signal)
+        );
+        // Only the crossing closes: no previous reading, already above the
+        // level, or moving the wrong way
+        assert_eq!(strategy.check_rsi_exit(&long, None, 55.0), None);
+        assert_eq!(strategy.check_rsi_exit(&long, Some(52.0), 58.0), None);
+        assert_eq!(strategy.check_rsi_exit(&short, Some(45.0), 55.0), None);
+    }
+
+    #[test]
+    fn test_no_entries_outside_market_sessions() {
+        use chrono::TimeZone;
+
+        let mut strategy = create_test_strategy();
+        strategy.set_market_calendar(Some(MarketCalendar::bursa_fcpo()));
+        // Wednesday 2026-10-14, 11:00 Malaysia time
+        strategy.set_simulated_time(Utc.with_ymd_and_hms(2026, 10, 14, 3, 0, 0).unwrap());
+        assert!(strategy.market_open());
+        assert!(strategy.can_open_position().unwrap());
+        strategy.add_position(Position::new("pos_1", "FCPO", OrderSide::Buy, 4850.0, 1.0));
+
+        // Lunch break: no entries, but the open position still exits
+        strategy.set_simulated_time(Utc.with_ymd_and_hms(2026, 10, 14, 5, 0, 0).unwrap());
+        assert!(!strategy.market_open());
+        assert!(!strategy.can_open_position().unwrap());
+        assert_eq!(strategy.entry_for(OrderSide::Sell, 4850.0).unwrap(), None);
+        assert!(strategy
+            .close_position("pos_1", 4900.0, CloseReason::TakeProfit)
+            .is_some());
+
+        strategy.set_market_calendar(None);
+        assert!(strategy.can_open_position().unwrap());
+    }
+
+    #[test]
+    fn test_volatility_regime_scales_and_halts_entries() {
+        let mut strategy = create_test_strategy();
+        strategy.set_volatility(Some(VolatilityConfig {
+            window: 4,
+            baseline: 10,
+            ..Default::default()
+        }));
+        // Closes alternating `step` up and down
+        let feed = |strategy: &mut TradingStrategy, step: f64, candles: usize| {
+            let mut close = 100.0;
+            for i in 0..candles {
+                close *= if i % 2 == 0 { 1.0 + step } else { 1.0 / (1.0 + step) };
+                strategy.update_candle(&candle(close, close, close, close));
+            }
+        };
+
+        feed(&mut strategy, 0.01, 30);
+        assert_eq!(strategy.volatility_regime(), VolatilityRegime::Normal);
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 10.0).abs() < 1e-9);
+
+        // Swings twice as wide: half size
+        feed(&mut strategy, 0.02, 4);
+        assert_eq!(strategy.volatility_regime(), VolatilityRegime::Elevated);
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 5.0).abs() < 1e-9);
+        assert!(strategy.can_open_position().unwrap());
+
+        // Five times as wide: past the volatility breaker
+        feed(&mut strategy, 0.05, 2);
+        assert_eq!(strategy.volatility_regime(), VolatilityRegime::Extreme);
+        assert!(!strategy.can_open_position().unwrap());
+    }
+
+    #[test]
+    fn test_no_entries_during_news_blackout() {
+        use chrono::{Duration, FixedOffset, TimeZone};
+
+        let mut schedule = BlackoutSchedule::new(
+            Duration::minutes(15),
+            Duration::minutes(30),
+            FixedOffset::east_opt(0).unwrap(),
+        );
+        schedule.set_feed(
+            "BEGIN:VEVENT\nDTSTART:20261110T043000Z\nSUMMARY:MPOB\nEND:VEVENT\n",
+        );
+        let mut strategy = create_test_strategy();
+        strategy.set_blackout(Some(schedule));
+
+        strategy.set_simulated_time(Utc.with_ymd_and_hms(2026, 11, 10, 4, 0, 0).unwrap());
+        assert!(strategy.can_open_position().unwrap());
+        strategy.set_simulated_time(Utc.with_ymd_and_hms(2026, 11, 10, 4, 40, 0).unwrap());
+        assert_eq!(strategy.active_blackout().unwrap().label, "MPOB");
+        assert!(!strategy.can_open_position().unwrap());
+        strategy.set_simulated_time(Utc.with_ymd_and_hms(2026, 11, 10, 5, 0, 0).unwrap());
+        assert!(strategy.can_open_position().unwrap());
+    }
+
     #[test]
     fn test_consecutive_losses_cooldown() {
         let mut strategy = create_test_strategy();
@@ -722,6 +2121,64 @@ mod tests {
         assert!(size > 0.0);
     }
 
+    #[test]
+    fn test_kelly_estimate() {
+        // 60% winners paying twice the average loss: f = 0.6 - 0.4 / 2
+        let results = [200.0, 200.0, 200.0, -100.0, -100.0];
+        let kelly = KellyEstimate::from_results(&results).unwrap();
+        assert_eq!(kelly.trades, 5);
+        assert!((kelly.win_rate - 0.6).abs() < 1e-9);
+        assert!((kelly.payoff - 2.0).abs() < 1e-9);
+        assert!((kelly.fraction - 0.4).abs() < 1e-9);
+
+        assert!(KellyEstimate::from_results(&[100.0, 50.0]).is_none());
+    }
+
+    #[test]
+    fn test_kelly_sizing() {
+        let mut strategy = create_test_strategy();
+        strategy.trading_config.sizing = SizingPolicy::KellyFraction {
+            fraction: 0.5,
+            max_risk_percent: 2.0,
+            lookback: 4,
+            min_trades: 4,
+        };
+
+        // Fixed 1% risk until enough trades are recorded
+        strategy.recent_results.extend([200.0, -100.0]);
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 10.0).abs() < 1e-9);
+
+        // Half Kelly of 0.25 = 12.5%, capped at 2% of 10000
+        strategy.recent_results.extend([200.0, -100.0]);
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 20.0).abs() < 1e-9);
+
+        // Closing a losing trade drops the oldest result; no edge left
+        let position = Position::new("k", "FCPO", OrderSide::Buy, 100.0, 1.0);
+        strategy.add_position(position);
+        strategy.close_position("k", 0.0, CloseReason::StopLoss);
+        assert_eq!(strategy.recent_results.len(), 4);
+        assert!(strategy.kelly_estimate().unwrap().fraction < 0.0);
+        assert_eq!(strategy.calculate_position_size(100.0, 90.0), 0.0);
+    }
+
+    #[test]
+    fn test_fixed_lots_and_volatility_target_sizing() {
+        let mut strategy = create_test_strategy();
+        strategy.trading_config.sizing = SizingPolicy::FixedLots { lots: 0.5 };
+        assert_eq!(strategy.calculate_position_size(100.0, 90.0), 0.0);
+        strategy.set_units_per_lot(Some(100.0));
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 50.0).abs() < 1e-9);
+
+        strategy.trading_config.sizing = SizingPolicy::VolatilityTarget { target_percent: 1.0 };
+        // No ATR yet: fixed risk
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 10.0).abs() < 1e-9);
+        for _ in 0..20 {
+            strategy.update_candle(&candle(100.0, 110.0, 90.0, 100.0));
+        }
+        // 1% of 10000 per 20-point ATR
+        assert!((strategy.calculate_position_size(100.0, 90.0) - 5.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_check_position_exit() {
         let strategy = create_test_strategy();
@@ -875,4 +2332,218 @@ mod tests {
         // Now EMA should be ready
         assert!(strategy.current_ema().is_some());
     }
+
+    #[test]
+    fn test_sentiment_disagreement_requires_stronger_rsi() {
+        let mut strategy = create_test_strategy();
+        strategy.set_trend_filter(false);
+
+        assert!(strategy.should_buy(25.0, 50));
+        assert!(strategy.should_sell(75.0, -50));
+
+        // Half disagreement -> thresholds move 5 points (default margin 10)
+        strategy.set_sentiment_dispersion(0.5);
+        assert!((strategy.effective_rsi_oversold() - 25.0).abs() < 1e-9);
+        assert!((strategy.effective_rsi_overbought() - 75.0).abs() < 1e-9);
+        assert!(!strategy.should_buy(25.0, 50));
+        assert!(strategy.should_buy(24.0, 50));
+        assert!(!strategy.should_sell(75.0, -50));
+        assert!(strategy.should_sell(76.0, -50));
+
+        strategy.set_sentiment_dispersion(0.0);
+        assert!(strategy.should_buy(25.0, 50));
+    }
+
+    #[test]
+    fn test_macd_confirmation() {
+        let mut strategy = create_test_strategy();
+        strategy.set_trend_filter(false);
+        strategy.set_macd_confirmation(true);
+
+        // MACD not ready: RSI extremes alone are not enough
+        assert!(!strategy.should_buy(25.0, 50));
+        assert!(!strategy.should_sell(75.0, -50));
+
+        for i in 0..40 {
+            let close = 5000.0 - i as f64 * 5.0;
+            strategy.update_candle(&candle(close, close, close, close));
+        }
+        // Accelerating sell-off: bearish momentum confirms shorts only
+        strategy.update_candle(&candle(4700.0, 4700.0, 4650.0, 4650.0));
+        assert!(strategy.current_macd().unwrap().macd_line < 0.0);
+        assert!(!strategy.should_buy(25.0, 50));
+        assert!(strategy.should_sell(75.0, -50));
+
+        // Rebound turns the histogram up: oversold buy is confirmed
+        strategy.update_candle(&candle(4650.0, 4760.0, 4650.0, 4760.0));
+        assert!(strategy.should_buy(25.0, 50));
+        assert!(!strategy.should_sell(75.0, -50));
+
+        strategy.set_macd_confirmation(false);
+        assert!(strategy.should_sell(75.0, -50));
+    }
+
+    #[test]
+    fn test_higher_timeframe_rsi_filter() {
+        let mut strategy = create_test_strategy();
+        strategy.set_trend_filter(false);
+        strategy.set_higher_rsi_filter(50.0, 50.0);
+
+        // Higher timeframe not ready: no entries
+        assert!(!strategy.should_buy(25.0, 50));
+        assert!(!strategy.should_sell(75.0, -50));
+
+        strategy.set_higher_rsi(Some(45.0));
+        assert!(strategy.should_buy(25.0, 50));
+        assert!(!strategy.should_sell(75.0, -50));
+
+        strategy.set_higher_rsi(Some(62.0));
+        assert!(!strategy.should_buy(25.0, 50));
+        assert!(strategy.should_sell(75.0, -50));
+    }
+
+    #[test]
+    fn test_asset_class_limit() {
+        let mut strategy = create_test_strategy();
+        strategy.add_position(
+            Position::new("pos_1", "FCPO", OrderSide::Buy, 4850.0, 1.0).with_asset_class("Commodities"),
+        );
+
+        // No limit configured
+        assert!(!strategy.asset_class_limit_reached(Some("Commodities")));
+
+        strategy.set_max_positions_per_asset_class(Some(1));
+        assert_eq!(strategy.open_positions_in_asset_class("Commodities"), 1);
+        assert!(strategy.asset_class_limit_reached(Some("Commodities")));
+        assert!(!strategy.asset_class_limit_reached(Some("Forex")));
+        assert!(!strategy.asset_class_limit_reached(None));
+    }
+
+    fn candle(open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle {
+            timestamp: Utc::now(),
+            timeframe: crate::modules::trading::TimeFrame::M5,
+            open,
+            high,
+            low,
+            close,
+            volume: 10,
+            avg_spread: None,
+            close_spread: None,
+        }
+    }
+
+    /// Breakout of the previous candle's range, with risk handled by the core
+    struct Breakout {
+        core: TradingStrategy,
+        prev_high: Option<f64>,
+    }
+
+    impl Strategy for Breakout {
+        fn name(&self) -> &str {
+            "breakout"
+        }
+
+        fn core(&self) -> &TradingStrategy {
+            &self.core
+        }
+
+        fn core_mut(&mut self) -> &mut TradingStrategy {
+            &mut self.core
+        }
+
+        fn generate_signal(&mut self, ctx: &SignalContext<'_>) -> SignalDecision {
+            let signal = match self.prev_high {
+                Some(high) if ctx.candle.close > high => Signal::Buy,
+                _ => Signal::Hold,
+            };
+            self.prev_high = Some(ctx.candle.high);
+            signal.into()
+        }
+
+        fn stop_loss(&self, entry_price: f64, _side: OrderSide) -> f64 {
+            entry_price - 10.0
+        }
+    }
+
+    #[test]
+    fn test_trait_defaults_delegate_to_core() {
+        let mut strategy = create_test_strategy();
+        strategy.set_trend_filter(false);
+        let bar = candle(4850.0, 4860.0, 4840.0, 4850.0);
+        let ctx = SignalContext {
+            candle: &bar,
+            rsi: 25.0,
+            sentiment: 50,
+            sentiment_confidence: 0.8,
+            sentiment_dispersion: 1.0,
+            higher_rsi: None,
+            fundamental_bias: None,
+        };
+
+        // Full disagreement widens the threshold to 20, so RSI 25 is no longer a buy
+        let decision = Strategy::generate_signal(&mut strategy, &ctx);
+        assert_eq!(decision.signal, Signal::Hold);
+        let rsi = &decision.factors[0];
+        assert_eq!((rsi.name.as_str(), rsi.threshold, rsi.passed), ("rsi", Some(20.0), false));
+        assert_eq!(
+            Strategy::take_profit(&strategy, 100.0, OrderSide::Buy),
+            strategy.calculate_take_profit(100.0, OrderSide::Buy)
+        );
+        assert_eq!(strategy.name(), "rsi_sentiment");
+    }
+
+    #[test]
+    fn test_fundamental_bias_blends_into_sentiment() {
+        let mut strategy = create_test_strategy();
+        strategy.set_trend_filter(false);
+        let bar = candle(4850.0, 4860.0, 4840.0, 4850.0);
+        let ctx = SignalContext {
+            candle: &bar,
+            rsi: 25.0,
+            sentiment: 20,
+            sentiment_confidence: 0.8,
+            sentiment_dispersion: 0.0,
+            higher_rsi: None,
+            fundamental_bias: Some(80),
+        };
+        // Ignored until a weight is set
+        assert_eq!(Strategy::generate_signal(&mut strategy, &ctx).signal, Signal::Hold);
+
+        strategy.set_fundamentals_weight(0.5);
+        assert_eq!(strategy.blend_fundamentals(20, Some(80)), 50);
+        let decision = Strategy::generate_signal(&mut strategy, &ctx);
+        assert_eq!(decision.signal, Signal::Buy);
+        let fundamentals = decision.factors.last().unwrap();
+        assert_eq!(fundamentals.name, "fundamentals");
+        assert_eq!(fundamentals.value, Some(80.0));
+    }
+
+    #[test]
+    fn test_custom_strategy_overrides_hooks() {
+        let mut breakout = Breakout {
+            core: create_test_strategy(),
+            prev_high: None,
+        };
+        let signal = |s: &mut Breakout, bar: &Candle| {
+            s.generate_signal(&SignalContext {
+                candle: bar,
+                rsi: 50.0,
+                sentiment: 0,
+                sentiment_confidence: 0.0,
+                sentiment_dispersion: 0.0,
+                higher_rsi: None,
+                fundamental_bias: None,
+            })
+            .signal
+        };
+
+        assert_eq!(signal(&mut breakout, &candle(100.0, 105.0, 95.0, 102.0)), Signal::Hold);
+        assert_eq!(signal(&mut breakout, &candle(102.0, 108.0, 101.0, 107.0)), Signal::Buy);
+
+        assert_eq!(breakout.stop_loss(100.0, OrderSide::Buy), 90.0);
+        // Risk 1% of 10000 over 10 points of stop
+        assert!((breakout.position_size(100.0, 90.0) - 10.0).abs() < 1e-9);
+        assert!(breakout.core_mut().can_open_position().unwrap());
+    }
 }
@@ -0,0 +1,259 @@
+//! Structure-based stop losses
+//!
+//! Instead of a fixed percent (or ATR distance) from the entry, the stop sits
+//! behind recent price structure, read from the last
+//! `STRUCTURE_STOP_LOOKBACK` closed strategy-timeframe candles (the window is
+//! warmed up from the stored candles on restart):
+//! - `swing`: below the lowest low of the window for longs, above the highest
+//!   high for shorts, with a buffer of `STRUCTURE_STOP_ATR_MULTIPLIER` ATRs
+//!   (default 10 candles, 0.5 ATR)
+//! - `chandelier`: the highest high of the window minus
+//!   `STRUCTURE_STOP_ATR_MULTIPLIER` ATRs for longs, the lowest low plus that
+//!   distance for shorts (default 22 candles, 3 ATR)
+//!
+//! New positions start at the structure level when it is on the right side
+//! of the entry. On every closed candle the level is recomputed and the stop
+//! of an open position moves there when that tightens it, through the amend
+//! API in the bot and in place in the backtest engine. Stops never loosen.
+//!
+//! Selected with `STRUCTURE_STOP_MODE` (`off`, `swing` or `chandelier`). ATR
+//! comes from the strategy's ATR (`ATR_PERIOD`).
+
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+
+use super::candles::Candle;
+use super::orders::OrderSide;
+use crate::error::{BotError, Result};
+
+/// Where the stop is placed relative to the candle window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureStopMode {
+    /// Beyond the window's extreme against the position
+    Swing,
+    /// An ATR multiple from the window's extreme in the position's favour
+    Chandelier,
+}
+
+impl fmt::Display for StructureStopMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureStopMode::Swing => write!(f, "swing"),
+            StructureStopMode::Chandelier => write!(f, "chandelier"),
+        }
+    }
+}
+
+/// Structure stop parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StructureStopConfig {
+    pub mode: StructureStopMode,
+    /// Closed candles in the window
+    pub lookback: usize,
+    /// Swing buffer or chandelier distance, in ATRs
+    pub atr_multiplier: f64,
+}
+
+impl StructureStopConfig {
+    /// Defaults for `mode`
+    pub fn new(mode: StructureStopMode) -> Self {
+        match mode {
+            StructureStopMode::Swing => Self {
+                mode,
+                lookback: 10,
+                atr_multiplier: 0.5,
+            },
+            StructureStopMode::Chandelier => Self {
+                mode,
+                lookback: 22,
+                atr_multiplier: 3.0,
+            },
+        }
+    }
+
+    /// Load from `STRUCTURE_STOP_*`; `None` while `STRUCTURE_STOP_MODE` is
+    /// unset or `off`
+    pub fn from_env() -> Result<Option<Self>> {
+        let mode = env::var("STRUCTURE_STOP_MODE").unwrap_or_default();
+        let mode = match mode.trim().to_ascii_lowercase().as_str() {
+            "" | "off" | "none" => return Ok(None),
+            "swing" => StructureStopMode::Swing,
+            "chandelier" => StructureStopMode::Chandelier,
+            other => {
+                return Err(BotError::Config(format!(
+                    "STRUCTURE_STOP_MODE must be off, swing or chandelier, got {:?}",
+                    other
+                )))
+            }
+        };
+
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> Result<T> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        }
+        let defaults = Self::new(mode);
+        let config = Self {
+            mode,
+            lookback: var("STRUCTURE_STOP_LOOKBACK", defaults.lookback)?,
+            atr_multiplier: var("STRUCTURE_STOP_ATR_MULTIPLIER", defaults.atr_multiplier)?,
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.lookback < 2 {
+            return Err(BotError::Config(
+                "STRUCTURE_STOP_LOOKBACK must be at least 2".into(),
+            ));
+        }
+        let distance_ok = match self.mode {
+            StructureStopMode::Swing => self.atr_multiplier >= 0.0,
+            StructureStopMode::Chandelier => self.atr_multiplier > 0.0,
+        };
+        if !distance_ok {
+            return Err(BotError::Config(format!(
+                "STRUCTURE_STOP_ATR_MULTIPLIER must be {} with STRUCTURE_STOP_MODE={}",
+                match self.mode {
+                    StructureStopMode::Swing => "at least 0",
+                    StructureStopMode::Chandelier => "positive",
+                },
+                self.mode
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Rolling high/low window of closed candles
+#[derive(Debug, Clone)]
+pub struct StructureStop {
+    config: StructureStopConfig,
+    /// (high, low) of the latest candles, oldest first
+    window: VecDeque<(f64, f64)>,
+}
+
+impl StructureStop {
+    pub fn new(config: StructureStopConfig) -> Self {
+        Self {
+            config,
+            window: VecDeque::with_capacity(config.lookback),
+        }
+    }
+
+    pub fn config(&self) -> &StructureStopConfig {
+        &self.config
+    }
+
+    /// Add a closed candle
+    pub fn update(&mut self, candle: &Candle) {
+        if self.window.len() == self.config.lookback {
+            self.window.pop_front();
+        }
+        self.window.push_back((candle.high, candle.low));
+    }
+
+    /// Whether the window holds `lookback` candles
+    pub fn is_ready(&self) -> bool {
+        self.window.len() >= self.config.lookback
+    }
+
+    /// Stop level for a `side` position; `None` until the window is full, or
+    /// while ATR is not ready and the mode needs it
+    pub fn level(&self, side: OrderSide, atr: Option<f64>) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let highest = self
+            .window
+            .iter()
+            .map(|(high, _)| *high)
+            .fold(f64::MIN, f64::max);
+        let lowest = self
+            .window
+            .iter()
+            .map(|(_, low)| *low)
+            .fold(f64::MAX, f64::min);
+        let distance = match atr {
+            _ if self.config.atr_multiplier == 0.0 => 0.0,
+            Some(atr) if atr > 0.0 => self.config.atr_multiplier * atr,
+            _ => return None,
+        };
+        let level = match (self.config.mode, side) {
+            (StructureStopMode::Swing, OrderSide::Buy) => lowest - distance,
+            (StructureStopMode::Swing, OrderSide::Sell) => highest + distance,
+            (StructureStopMode::Chandelier, OrderSide::Buy) => highest - distance,
+            (StructureStopMode::Chandelier, OrderSide::Sell) => lowest + distance,
+        };
+        Some(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::TimeFrame;
+    use chrono::Utc;
+
+    fn candle(high: f64, low: f64) -> Candle {
+        let mid = (high + low) / 2.0;
+        Candle {
+            timestamp: Utc::now(),
+            timeframe: TimeFrame::M5,
+            open: mid,
+            high,
+            low,
+            close: mid,
+            volume: 10,
+            avg_spread: None,
+            close_spread: None,
+        }
+    }
+
+    #[test]
+    fn test_swing_stop_sits_beyond_window_extremes() {
+        let mut stop = StructureStop::new(StructureStopConfig {
+            mode: StructureStopMode::Swing,
+            lookback: 3,
+            atr_multiplier: 0.5,
+        });
+        stop.update(&candle(1010.0, 990.0));
+        stop.update(&candle(1020.0, 1000.0));
+        assert_eq!(stop.level(OrderSide::Buy, Some(10.0)), None);
+
+        stop.update(&candle(1015.0, 995.0));
+        assert_eq!(stop.level(OrderSide::Buy, Some(10.0)), Some(985.0));
+        assert_eq!(stop.level(OrderSide::Sell, Some(10.0)), Some(1025.0));
+        // The oldest candle drops out of the window
+        stop.update(&candle(1030.0, 1005.0));
+        assert_eq!(stop.level(OrderSide::Buy, Some(10.0)), Some(990.0));
+        // The buffer needs ATR
+        assert_eq!(stop.level(OrderSide::Buy, None), None);
+    }
+
+    #[test]
+    fn test_chandelier_stop_hangs_from_extremes() {
+        let mut stop = StructureStop::new(StructureStopConfig::new(StructureStopMode::Chandelier));
+        for i in 0..22 {
+            let base = 1000.0 + i as f64;
+            stop.update(&candle(base + 10.0, base - 10.0));
+        }
+        // Highest high 1031 - 3 x 20, lowest low 990 + 3 x 20
+        assert_eq!(stop.level(OrderSide::Buy, Some(20.0)), Some(971.0));
+        assert_eq!(stop.level(OrderSide::Sell, Some(20.0)), Some(1050.0));
+        assert_eq!(stop.level(OrderSide::Buy, None), None);
+
+        assert!(StructureStopConfig {
+            lookback: 1,
+            ..StructureStopConfig::new(StructureStopMode::Swing)
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! EMA cross + ADX trend-following strategy
+//!
+//! A slower built-in `Strategy`, selected with `STRATEGY=trend_following`,
+//! meant for hourly candles: the bot switches `RSI_TIMEFRAME` to `1h` for it
+//! unless the variable is set.
+//!
+//! - Buy when the `TREND_FAST_EMA` (default 20) closes above the
+//!   `TREND_SLOW_EMA` (default 50) while ADX (`TREND_ADX_PERIOD`, default 14)
+//!   is at least `TREND_ADX_THRESHOLD` (default 25) and +DI leads -DI
+//! - Sell on the mirror cross with -DI leading
+//! - While the trend stays strong, the signal repeats for the side already
+//!   held, so the position is pyramided through the core's scale-in
+//!   (`SCALE_IN_*`, defaulting to 2 adds of half size, each 1% in profit)
+//!
+//! Stops trail as a chandelier structure stop, `TREND_TRAILING_ATR_MULTIPLIER`
+//! ATRs (default 3) from the extreme of the last `TREND_TRAILING_LOOKBACK`
+//! candles (default 22), tightened on every close; `STRUCTURE_STOP_MODE`
+//! overrides it. The broker-side target sits `TREND_TAKE_PROFIT_ATR` ATRs
+//! away (default 8) so the trailing stop does most of the exiting. Sentiment
+//! is not used.
+
+use std::env;
+
+use tracing::debug;
+
+use super::candles::Candle;
+use super::decision::{DecisionFactor, SignalDecision};
+use super::indicators::{AdxCalculator, EmaCalculator};
+use super::orders::{CloseReason, OrderSide, Position};
+use super::strategy::{Signal, SignalContext, Strategy, TradingStrategy};
+use super::structure_stop::{StructureStopConfig, StructureStopMode};
+use crate::config::{Config, ScaleInConfig};
+use crate::error::{BotError, Result};
+
+/// Pyramiding used when `SCALE_IN_MAX_ADDS` is unset
+const DEFAULT_SCALE_IN: ScaleInConfig = ScaleInConfig {
+    max_adds: 2,
+    size_factor: 0.5,
+    min_profit_percent: 1.0,
+};
+
+/// Trend-following parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendFollowingConfig {
+    /// Fast EMA period, in candles
+    pub fast_period: usize,
+    /// Slow EMA period, in candles
+    pub slow_period: usize,
+    /// ADX period, in candles
+    pub adx_period: usize,
+    /// Minimum ADX for entries and adds (0-100)
+    pub adx_threshold: f64,
+    /// Trailing stop distance from the window extreme, in ATRs
+    pub trailing_atr_multiplier: f64,
+    /// Candles in the trailing stop window
+    pub trailing_lookback: usize,
+    /// Take-profit distance from the entry, in ATRs
+    pub take_profit_atr: f64,
+}
+
+impl Default for TrendFollowingConfig {
+    fn default() -> Self {
+        Self {
+            fast_period: 20,
+            slow_period: 50,
+            adx_period: 14,
+            adx_threshold: 25.0,
+            trailing_atr_multiplier: 3.0,
+            trailing_lookback: 22,
+            take_profit_atr: 8.0,
+        }
+    }
+}
+
+impl TrendFollowingConfig {
+    /// Load from `TREND_*`, defaulting unset values
+    pub fn from_env() -> Result<Self> {
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> Result<T> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        }
+        let defaults = Self::default();
+        let config = Self {
+            fast_period: var("TREND_FAST_EMA", defaults.fast_period)?,
+            slow_period: var("TREND_SLOW_EMA", defaults.slow_period)?,
+            adx_period: var("TREND_ADX_PERIOD", defaults.adx_period)?,
+            adx_threshold: var("TREND_ADX_THRESHOLD", defaults.adx_threshold)?,
+            trailing_atr_multiplier: var(
+                "TREND_TRAILING_ATR_MULTIPLIER",
+                defaults.trailing_atr_multiplier,
+            )?,
+            trailing_lookback: var("TREND_TRAILING_LOOKBACK", defaults.trailing_lookback)?,
+            take_profit_atr: var("TREND_TAKE_PROFIT_ATR", defaults.take_profit_atr)?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.fast_period < 2 || self.slow_period <= self.fast_period {
+            return Err(BotError::Config(
+                "TREND_FAST_EMA must be at least 2 and below TREND_SLOW_EMA".into(),
+            ));
+        }
+        if self.adx_period < 2 {
+            return Err(BotError::Config(
+                "TREND_ADX_PERIOD must be at least 2".into(),
+            ));
+        }
+        if !(0.0..=100.0).contains(&self.adx_threshold) {
+            return Err(BotError::Config(
+                "TREND_ADX_THRESHOLD must be between 0 and 100".into(),
+            ));
+        }
+        if !(self.take_profit_atr.is_finite() && self.take_profit_atr > 0.0) {
+            return Err(BotError::Config(
+                "TREND_TAKE_PROFIT_ATR must be positive".into(),
+            ));
+        }
+        self.trailing_stop().validate().map_err(|_| {
+            BotError::Config(
+                "TREND_TRAILING_LOOKBACK must be at least 2 and \
+                 TREND_TRAILING_ATR_MULTIPLIER positive"
+                    .into(),
+            )
+        })
+    }
+
+    /// The chandelier stop that trails positions
+    pub fn trailing_stop(&self) -> StructureStopConfig {
+        StructureStopConfig {
+            mode: StructureStopMode::Chandelier,
+            lookback: self.trailing_lookback,
+            atr_multiplier: self.trailing_atr_multiplier,
+        }
+    }
+}
+
+/// Rides EMA crosses confirmed by ADX, adding to winners
+pub struct TrendFollowing {
+    core: TradingStrategy,
+    config: TrendFollowingConfig,
+    fast: EmaCalculator,
+    slow: EmaCalculator,
+    adx: AdxCalculator,
+    /// Fast EMA above the slow one at the last close
+    bullish: Option<bool>,
+    /// The EMAs crossed on the last close
+    crossed: bool,
+}
+
+impl TrendFollowing {
+    /// Wrap `core`, trailing its stops with the config's chandelier stop
+    pub fn new(mut core: TradingStrategy, config: TrendFollowingConfig) -> Self {
+        core.set_structure_stop(Some(config.trailing_stop()));
+        Self {
+            core,
+            config,
+            fast: EmaCalculator::new(config.fast_period),
+            slow: EmaCalculator::new(config.slow_period),
+            adx: AdxCalculator::new(config.adx_period),
+            bullish: None,
+            crossed: false,
+        }
+    }
+
+    /// Strategy for `config`'s risk settings and trend parameters, with
+    /// pyramiding on
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut trading = config.trading.clone();
+        trading.scale_in.get_or_insert(DEFAULT_SCALE_IN);
+        let core = TradingStrategy::new(
+            config.strategy.clone(),
+            trading,
+            config.trading.initial_balance,
+        );
+        config.strategy.trend.validate()?;
+        Ok(Self::new(core, config.strategy.trend))
+    }
+
+    pub fn config(&self) -> &TrendFollowingConfig {
+        &self.config
+    }
+
+    /// Side of a strong trend: EMAs, ADX and the leading DI agree
+    fn holds(&self, side: OrderSide) -> bool {
+        let symbol = &self.core.trading_config().symbol;
+        !self.core.position_manager().legs(symbol, side).is_empty()
+    }
+}
+
+impl Strategy for TrendFollowing {
+    fn name(&self) -> &str {
+        "trend_following"
+    }
+
+    fn core(&self) -> &TradingStrategy {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut TradingStrategy {
+        &mut self.core
+    }
+
+    fn on_candle(&mut self, candle: &Candle) {
+        self.core.update_candle(candle);
+        self.adx.update(candle.high, candle.low, candle.close);
+        let fast = self.fast.update(candle.close);
+        let slow = self.slow.update(candle.close);
+        let bullish = match (fast, slow) {
+            (Some(fast), Some(slow)) => Some(fast > slow),
+            _ => None,
+        };
+        self.crossed = matches!((self.bullish, bullish), (Some(prev), Some(now)) if prev != now);
+        self.bullish = bullish;
+    }
+
+    fn generate_signal(&mut self, _ctx: &SignalContext<'_>) -> SignalDecision {
+        let (Some(adx), Some(bullish)) = (self.adx.current(), self.bullish) else {
+            return SignalDecision::hold()
+                .with_factor(DecisionFactor::new("adx", None, false).with_note("warming up"));
+        };
+        let side = if bullish {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        };
+        let strong = adx.adx >= self.config.adx_threshold;
+        let di_agrees = if bullish {
+            adx.plus_di > adx.minus_di
+        } else {
+            adx.minus_di > adx.plus_di
+        };
+        // Enter on a fresh cross, then keep signalling to pyramid
+        let holds = self.holds(side);
+        let triggered = self.crossed || holds;
+        let signal = match (strong && di_agrees && triggered, side) {
+            (true, OrderSide::Buy) => Signal::Buy,
+            (true, OrderSide::Sell) => Signal::Sell,
+            (false, _) => Signal::Hold,
+        };
+        debug!(
+            "Trend {:?}: ADX {:.1}, crossed {} -> {}",
+            side, adx.adx, self.crossed, signal
+        );
+
+        let direction = if bullish { 1.0 } else { -1.0 };
+        let trigger = if self.crossed {
+            "fresh cross"
+        } else if holds {
+            "pyramiding"
+        } else {
+            "no fresh cross"
+        };
+        SignalDecision::new(signal)
+            // ADX 50 and above counts as a full-strength trend
+            .with_score(direction * adx.adx / 50.0)
+            .with_side(side)
+            .with_factor(
+                DecisionFactor::new("adx", Some(adx.adx), strong)
+                    .with_threshold(self.config.adx_threshold),
+            )
+            .with_factor(
+                DecisionFactor::new(
+                    "directional_index",
+                    Some(adx.plus_di - adx.minus_di),
+                    di_agrees,
+                )
+                .with_note(format!("+DI {:.1} / -DI {:.1}", adx.plus_di, adx.minus_di)),
+            )
+            .with_factor(DecisionFactor::new("ema_cross", None, triggered).with_note(trigger))
+    }
+
+    fn check_exit(&self, position: &Position, current_price: f64) -> Option<CloseReason> {
+        // No fixed-percent target: only the ATR target and the trailing stop
+        if position.is_take_profit_hit(current_price) {
+            return Some(CloseReason::TakeProfit);
+        }
+        self.core
+            .check_stop_loss(position, current_price)
+            .then_some(CloseReason::StopLoss)
+    }
+
+    fn take_profit(&self, entry_price: f64, side: OrderSide) -> f64 {
+        let Some(atr) = self.core.current_atr().filter(|atr| *atr > 0.0) else {
+            return self.core.calculate_take_profit(entry_price, side);
+        };
+        let distance = self.config.take_profit_atr * atr;
+        match side {
+            OrderSide::Buy => entry_price + distance,
+            OrderSide::Sell => entry_price - distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::TimeFrame;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn config() -> TrendFollowingConfig {
+        TrendFollowingConfig {
+            fast_period: 3,
+            slow_period: 6,
+            adx_period: 3,
+            trailing_lookback: 4,
+            ..TrendFollowingConfig::default()
+        }
+    }
+
+    fn strategy() -> TrendFollowing {
+        let mut config = Config::default();
+        config.trading.scale_in = Some(DEFAULT_SCALE_IN);
+        let core = TradingStrategy::new(config.strategy, config.trading, 10000.0);
+        TrendFollowing::new(core, self::config())
+    }
+
+    fn feed(strategy: &mut TrendFollowing, closes: &[f64]) -> Vec<Signal> {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let bar = Candle {
+                    timestamp: start + Duration::hours(i as i64),
+                    timeframe: TimeFrame::H1,
+                    open: *close,
+                    high: close + 2.0,
+                    low: close - 2.0,
+                    close: *close,
+                    volume: 0,
+                    avg_spread: None,
+                    close_spread: None,
+                };
+                strategy.on_candle(&bar);
+                strategy
+                    .generate_signal(&SignalContext {
+                        candle: &bar,
+                        rsi: 50.0,
+                        sentiment: 0,
+                        sentiment_confidence: 0.8,
+                        sentiment_dispersion: 0.0,
+                        higher_rsi: None,
+                        fundamental_bias: None,
+                    })
+                    .signal
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enters_on_strong_cross_and_repeats_while_held() {
+        let mut strategy = strategy();
+        // A slow decline, then a steady rally that crosses the EMAs
+        let mut closes: Vec<f64> = (0..10).map(|i| 4800.0 - 5.0 * i as f64).collect();
+        closes.extend((1..=8).map(|i| 4755.0 + 15.0 * i as f64));
+        let signals = feed(&mut strategy, &closes);
+
+        let first_buy = signals.iter().position(|s| *s == Signal::Buy).unwrap();
+        assert!(first_buy >= 10);
+        assert!(!signals.contains(&Signal::Sell));
+        // Without a position, later candles of the trend do not re-enter
+        assert_eq!(signals.last(), Some(&Signal::Hold));
+
+        // Holding the trend side, the signal repeats so the core can add
+        let long = Position::new("long", "FCPO", OrderSide::Buy, 4800.0, 1.0);
+        strategy.core_mut().position_manager_mut().add(long);
+        let more = feed(&mut strategy, &[4900.0]);
+        assert_eq!(more, vec![Signal::Buy]);
+        assert_eq!(strategy.name(), "trend_following");
+    }
+
+    #[test]
+    fn test_trails_with_chandelier_and_targets_in_atrs() {
+        let strategy = strategy();
+        let stop = strategy.core().structure_stop().unwrap().config();
+        assert_eq!(stop.mode, StructureStopMode::Chandelier);
+        assert_eq!(stop.lookback, 4);
+        assert_eq!(stop.atr_multiplier, 3.0);
+
+        let long = Position::new("long", "FCPO", OrderSide::Buy, 4800.0, 1.0)
+            .with_take_profit(4900.0)
+            .with_stop_loss(4750.0);
+        // A 2% move is no exit on its own
+        assert_eq!(strategy.check_exit(&long, 4896.0), None);
+        assert_eq!(
+            strategy.check_exit(&long, 4900.0),
+            Some(CloseReason::TakeProfit)
+        );
+        assert_eq!(
+            strategy.check_exit(&long, 4749.0),
+            Some(CloseReason::StopLoss)
+        );
+
+        assert!(TrendFollowingConfig {
+            slow_period: 3,
+            ..config()
+        }
+        .validate()
+        .is_err());
+    }
+}
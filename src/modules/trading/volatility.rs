@@ -0,0 +1,298 @@
+//! Realized-volatility regime detection
+//!
+//! The detector keeps the standard deviation of log returns over the last
+//! `VOL_REGIME_WINDOW` closed candles (default 20) and compares it with the
+//! average of that reading over the last `VOL_REGIME_BASELINE` candles
+//! (default 100). The ratio sorts the market into a regime:
+//! - below `VOL_REGIME_REDUCE_RATIO` (default 1.5): normal sizing
+//! - from there: positions are sized at `VOL_REGIME_REDUCE_FACTOR` (default 0.5)
+//! - from `VOL_REGIME_HALT_RATIO` (default 2.0): no new entries; the ratio is
+//!   checked through the circuit breakers' volatility threshold
+//!
+//! Enabled with `VOL_REGIME_ENABLED`. Until the baseline is full the regime is
+//! normal.
+
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+
+use crate::error::{BotError, Result};
+
+/// Detector parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolatilityConfig {
+    /// Returns in one realized-volatility reading
+    pub window: usize,
+    /// Readings averaged into the baseline
+    pub baseline: usize,
+    /// Ratio from which positions are scaled down
+    pub reduce_ratio: f64,
+    /// Size multiplier while scaled down
+    pub reduce_factor: f64,
+    /// Ratio from which no new entries are made
+    pub halt_ratio: f64,
+}
+
+impl Default for VolatilityConfig {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            baseline: 100,
+            reduce_ratio: 1.5,
+            reduce_factor: 0.5,
+            halt_ratio: 2.0,
+        }
+    }
+}
+
+impl VolatilityConfig {
+    /// Load from `VOL_REGIME_*`; `None` unless `VOL_REGIME_ENABLED` is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let enabled = env::var("VOL_REGIME_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> Result<T> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        }
+        let defaults = Self::default();
+        let config = Self {
+            window: var("VOL_REGIME_WINDOW", defaults.window)?,
+            baseline: var("VOL_REGIME_BASELINE", defaults.baseline)?,
+            reduce_ratio: var("VOL_REGIME_REDUCE_RATIO", defaults.reduce_ratio)?,
+            reduce_factor: var("VOL_REGIME_REDUCE_FACTOR", defaults.reduce_factor)?,
+            halt_ratio: var("VOL_REGIME_HALT_RATIO", defaults.halt_ratio)?,
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.window < 2 || self.baseline == 0 {
+            return Err(BotError::Config(
+                "VOL_REGIME_WINDOW must be at least 2 and VOL_REGIME_BASELINE positive".into(),
+            ));
+        }
+        if !(self.reduce_factor > 0.0 && self.reduce_factor <= 1.0) {
+            return Err(BotError::Config(
+                "VOL_REGIME_REDUCE_FACTOR must be above 0 and at most 1".into(),
+            ));
+        }
+        if !(self.reduce_ratio > 1.0 && self.halt_ratio >= self.reduce_ratio) {
+            return Err(BotError::Config(
+                "VOL_REGIME_REDUCE_RATIO must be above 1 and at most VOL_REGIME_HALT_RATIO".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How the current volatility compares with its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolatilityRegime {
+    Normal,
+    /// Entries sized down
+    Elevated,
+    /// No new entries
+    Extreme,
+}
+
+impl fmt::Display for VolatilityRegime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VolatilityRegime::Normal => write!(f, "normal"),
+            VolatilityRegime::Elevated => write!(f, "elevated"),
+            VolatilityRegime::Extreme => write!(f, "extreme"),
+        }
+    }
+}
+
+/// Latest realized volatility against its baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolatilityReading {
+    /// Standard deviation of log returns over the window
+    pub realized: f64,
+    /// Average realized volatility over the baseline
+    pub baseline: f64,
+}
+
+impl VolatilityReading {
+    pub fn ratio(&self) -> f64 {
+        if self.baseline > 0.0 {
+            self.realized / self.baseline
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Rolling realized-volatility estimator
+#[derive(Debug, Clone)]
+pub struct VolatilityDetector {
+    config: VolatilityConfig,
+    last_close: Option<f64>,
+    returns: VecDeque<f64>,
+    history: VecDeque<f64>,
+}
+
+impl VolatilityDetector {
+    pub fn new(config: VolatilityConfig) -> Self {
+        Self {
+            config,
+            last_close: None,
+            returns: VecDeque::with_capacity(config.window),
+            history: VecDeque::with_capacity(config.baseline),
+        }
+    }
+
+    pub fn config(&self) -> &VolatilityConfig {
+        &self.config
+    }
+
+    /// Add a candle close
+    pub fn update(&mut self, close: f64) {
+        if !(close > 0.0) {
+            return;
+        }
+        if let Some(last) = self.last_close.replace(close) {
+            self.returns.push_back((close / last).ln());
+            if self.returns.len() > self.config.window {
+                self.returns.pop_front();
+            }
+        }
+        if self.returns.len() < self.config.window {
+            return;
+        }
+
+        self.history.push_back(std_dev(&self.returns));
+        if self.history.len() > self.config.baseline {
+            self.history.pop_front();
+        }
+    }
+
+    /// Current reading, once the baseline is full
+    pub fn reading(&self) -> Option<VolatilityReading> {
+        if self.history.len() < self.config.baseline {
+            return None;
+        }
+        Some(VolatilityReading {
+            realized: *self.history.back()?,
+            baseline: self.history.iter().sum::<f64>() / self.history.len() as f64,
+        })
+    }
+
+    pub fn regime(&self) -> VolatilityRegime {
+        match self.reading().map(|r| r.ratio()) {
+            Some(ratio) if ratio >= self.config.halt_ratio => VolatilityRegime::Extreme,
+            Some(ratio) if ratio >= self.config.reduce_ratio => VolatilityRegime::Elevated,
+            _ => VolatilityRegime::Normal,
+        }
+    }
+
+    /// Multiplier for position sizes in the current regime
+    pub fn size_factor(&self) -> f64 {
+        match self.regime() {
+            VolatilityRegime::Normal => 1.0,
+            VolatilityRegime::Elevated => self.config.reduce_factor,
+            VolatilityRegime::Extreme => 0.0,
+        }
+    }
+}
+
+/// Sample standard deviation
+fn std_dev(values: &VecDeque<f64>) -> f64 {
+    let n = values.len() as f64;
+    if n < 2.0 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> VolatilityDetector {
+        VolatilityDetector::new(VolatilityConfig {
+            window: 4,
+            baseline: 10,
+            ..Default::default()
+        })
+    }
+
+    /// Closes alternating `step` percent up and down
+    fn feed(detector: &mut VolatilityDetector, step: f64, candles: usize) {
+        let mut close = 100.0;
+        for i in 0..candles {
+            close *= if i % 2 == 0 {
+                1.0 + step
+            } else {
+                1.0 / (1.0 + step)
+            };
+            detector.update(close);
+        }
+    }
+
+    #[test]
+    fn test_regime_follows_volatility_spikes() {
+        let mut detector = detector();
+        feed(&mut detector, 0.01, 10);
+        // Baseline not full yet
+        assert_eq!(detector.reading(), None);
+        assert_eq!(detector.regime(), VolatilityRegime::Normal);
+
+        feed(&mut detector, 0.01, 20);
+        let reading = detector.reading().unwrap();
+        assert!(
+            (reading.ratio() - 1.0).abs() < 0.05,
+            "ratio {}",
+            reading.ratio()
+        );
+        assert_eq!(detector.size_factor(), 1.0);
+
+        // Swings four times as wide: realized volatility jumps before the
+        // baseline catches up
+        feed(&mut detector, 0.04, 4);
+        assert!(detector.reading().unwrap().ratio() >= 2.0);
+        assert_eq!(detector.regime(), VolatilityRegime::Extreme);
+        assert_eq!(detector.size_factor(), 0.0);
+
+        // The baseline absorbs the new level
+        feed(&mut detector, 0.04, 30);
+        assert_eq!(detector.regime(), VolatilityRegime::Normal);
+    }
+
+    #[test]
+    fn test_elevated_regime_scales_size() {
+        let mut detector = detector();
+        feed(&mut detector, 0.01, 30);
+        feed(&mut detector, 0.02, 4);
+        assert_eq!(detector.regime(), VolatilityRegime::Elevated);
+        assert_eq!(detector.size_factor(), 0.5);
+    }
+
+    #[test]
+    fn test_invalid_configuration() {
+        let config = |reduce_ratio, halt_ratio, reduce_factor| VolatilityConfig {
+            reduce_ratio,
+            halt_ratio,
+            reduce_factor,
+            ..Default::default()
+        };
+        assert!(config(1.5, 2.0, 0.5).validate().is_ok());
+        assert!(config(2.5, 2.0, 0.5).validate().is_err());
+        assert!(config(0.8, 2.0, 0.5).validate().is_err());
+        assert!(config(1.5, 2.0, 0.0).validate().is_err());
+    }
+}
@@ -0,0 +1,407 @@
+//! Session-VWAP reversion strategy
+//!
+//! A second built-in `Strategy`, selected with `STRATEGY=vwap_reversion`, and
+//! a baseline to compare the RSI + sentiment strategy against in backtests.
+//!
+//! The volume-weighted average of the typical price `(high + low + close) / 3`
+//! is accumulated over the current session, together with the weighted
+//! standard deviation around it. Sessions start at `VWAP_SESSION_START_HOUR`
+//! (UTC, default 0). Candles without volume (CFD feeds without tick volume)
+//! count with weight 1.
+//!
+//! Once the session has `VWAP_MIN_SESSION_CANDLES` candles (default 6):
+//! - Buy when a candle closes more than `VWAP_BAND_WIDTH` deviations (default
+//!   2) below the VWAP, unless sentiment is below `-VWAP_SENTIMENT_GATE`
+//! - Sell when it closes that far above, unless sentiment is above
+//!   `VWAP_SENTIMENT_GATE` (default 30)
+//!
+//! Positions take profit when price gets back to the session VWAP; stops,
+//! sizing and risk limits come from the wrapped `TradingStrategy`.
+
+use std::env;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use tracing::debug;
+
+use super::candles::Candle;
+use super::decision::{DecisionFactor, SignalDecision};
+use super::orders::{CloseReason, OrderSide, Position};
+use super::strategy::{Signal, SignalContext, Strategy, TradingStrategy};
+use crate::config::Config;
+use crate::error::{BotError, Result};
+
+/// VWAP reversion parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VwapReversionConfig {
+    /// Entry distance from the VWAP, in standard deviations
+    pub band_width: f64,
+    /// Sentiment beyond this against the trade blocks the entry (0-100)
+    pub sentiment_gate: i32,
+    /// Candles the session needs before the VWAP is traded
+    pub min_session_candles: usize,
+    /// UTC hour (0-23) at which a new session starts
+    pub session_start_hour: u32,
+}
+
+impl Default for VwapReversionConfig {
+    fn default() -> Self {
+        Self {
+            band_width: 2.0,
+            sentiment_gate: 30,
+            min_session_candles: 6,
+            session_start_hour: 0,
+        }
+    }
+}
+
+impl VwapReversionConfig {
+    /// Load from `VWAP_*`, defaulting unset values
+    pub fn from_env() -> Result<Self> {
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> Result<T> {
+            match env::var(key) {
+                Ok(raw) if !raw.trim().is_empty() => raw
+                    .trim()
+                    .parse()
+                    .map_err(|_| BotError::Config(format!("invalid {}: {:?}", key, raw))),
+                _ => Ok(default),
+            }
+        }
+        let defaults = Self::default();
+        let config = Self {
+            band_width: var("VWAP_BAND_WIDTH", defaults.band_width)?,
+            sentiment_gate: var("VWAP_SENTIMENT_GATE", defaults.sentiment_gate)?,
+            min_session_candles: var("VWAP_MIN_SESSION_CANDLES", defaults.min_session_candles)?,
+            session_start_hour: var("VWAP_SESSION_START_HOUR", defaults.session_start_hour)?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(self.band_width.is_finite() && self.band_width > 0.0) {
+            return Err(BotError::Config("VWAP_BAND_WIDTH must be positive".into()));
+        }
+        if !(0..=100).contains(&self.sentiment_gate) {
+            return Err(BotError::Config(
+                "VWAP_SENTIMENT_GATE must be between 0 and 100".into(),
+            ));
+        }
+        if self.min_session_candles < 2 {
+            return Err(BotError::Config(
+                "VWAP_MIN_SESSION_CANDLES must be at least 2".into(),
+            ));
+        }
+        if self.session_start_hour > 23 {
+            return Err(BotError::Config(
+                "VWAP_SESSION_START_HOUR must be between 0 and 23".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Running VWAP of one session
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionVwap {
+    weight: f64,
+    weighted_price: f64,
+    weighted_square: f64,
+    candles: usize,
+}
+
+impl SessionVwap {
+    /// Add a closed candle
+    pub fn update(&mut self, candle: &Candle) {
+        let typical = (candle.high + candle.low + candle.close) / 3.0;
+        let weight = candle.volume.max(1) as f64;
+        self.weight += weight;
+        self.weighted_price += weight * typical;
+        self.weighted_square += weight * typical * typical;
+        self.candles += 1;
+    }
+
+    /// Candles in the session so far
+    pub fn candles(&self) -> usize {
+        self.candles
+    }
+
+    /// Volume-weighted average price; `None` before the first candle
+    pub fn vwap(&self) -> Option<f64> {
+        (self.weight > 0.0).then(|| self.weighted_price / self.weight)
+    }
+
+    /// Volume-weighted standard deviation of the typical price around the VWAP
+    pub fn deviation(&self) -> Option<f64> {
+        let vwap = self.vwap()?;
+        let variance = self.weighted_square / self.weight - vwap * vwap;
+        Some(variance.max(0.0).sqrt())
+    }
+}
+
+/// Fades stretches away from the session VWAP, gated by sentiment
+pub struct VwapReversion {
+    core: TradingStrategy,
+    config: VwapReversionConfig,
+    session: SessionVwap,
+    /// Day the current session started on, shifted by the session start hour
+    session_day: Option<NaiveDate>,
+}
+
+impl VwapReversion {
+    pub fn new(core: TradingStrategy, config: VwapReversionConfig) -> Self {
+        Self {
+            core,
+            config,
+            session: SessionVwap::default(),
+            session_day: None,
+        }
+    }
+
+    /// Strategy for `config`'s risk settings and VWAP parameters
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let core = TradingStrategy::new(
+            config.strategy.clone(),
+            config.trading.clone(),
+            config.trading.initial_balance,
+        );
+        config.strategy.vwap.validate()?;
+        Ok(Self::new(core, config.strategy.vwap))
+    }
+
+    pub fn config(&self) -> &VwapReversionConfig {
+        &self.config
+    }
+
+    /// Current session VWAP
+    pub fn session(&self) -> &SessionVwap {
+        &self.session
+    }
+
+    fn session_day_of(&self, at: DateTime<Utc>) -> NaiveDate {
+        (at - Duration::hours(self.config.session_start_hour as i64)).date_naive()
+    }
+
+    /// The VWAP once the session has enough candles to trade it
+    fn tradable_vwap(&self) -> Option<f64> {
+        if self.session.candles() < self.config.min_session_candles {
+            return None;
+        }
+        self.session.vwap()
+    }
+}
+
+impl Strategy for VwapReversion {
+    fn name(&self) -> &str {
+        "vwap_reversion"
+    }
+
+    fn core(&self) -> &TradingStrategy {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut TradingStrategy {
+        &mut self.core
+    }
+
+    fn on_candle(&mut self, candle: &Candle) {
+        self.core.update_candle(candle);
+        let day = self.session_day_of(candle.timestamp);
+        if self.session_day != Some(day) {
+            debug!("New VWAP session for {}", day);
+            self.session = SessionVwap::default();
+            self.session_day = Some(day);
+        }
+        self.session.update(candle);
+    }
+
+    fn generate_signal(&mut self, ctx: &SignalContext<'_>) -> SignalDecision {
+        let (Some(vwap), Some(deviation)) = (self.tradable_vwap(), self.session.deviation()) else {
+            let note = format!(
+                "{}/{} session candles",
+                self.session.candles(),
+                self.config.min_session_candles
+            );
+            return SignalDecision::hold()
+                .with_factor(DecisionFactor::new("session_vwap", None, false).with_note(note));
+        };
+        if deviation <= 0.0 {
+            return SignalDecision::hold().with_factor(
+                DecisionFactor::new("vwap_deviation", Some(deviation), false).with_threshold(0.0),
+            );
+        }
+        let distance = (ctx.candle.close - vwap) / deviation;
+        let gate = self.config.sentiment_gate;
+        let band_width = self.config.band_width;
+        // Below the VWAP only a buy can fire, above it only a sell
+        let (side, band_threshold, in_band, gate_level, sentiment_ok) = if distance < 0.0 {
+            let in_band = distance <= -band_width;
+            (
+                OrderSide::Buy,
+                -band_width,
+                in_band,
+                -gate,
+                ctx.sentiment >= -gate,
+            )
+        } else {
+            let in_band = distance >= band_width;
+            (
+                OrderSide::Sell,
+                band_width,
+                in_band,
+                gate,
+                ctx.sentiment <= gate,
+            )
+        };
+        let signal = match (in_band && sentiment_ok, side) {
+            (true, OrderSide::Buy) => Signal::Buy,
+            (true, OrderSide::Sell) => Signal::Sell,
+            (false, _) => Signal::Hold,
+        };
+        debug!(
+            "VWAP {:.2} ± {:.2}: close {:.2} is {:.2} deviations away, sentiment {} -> {}",
+            vwap, deviation, ctx.candle.close, distance, ctx.sentiment, signal
+        );
+        SignalDecision::new(signal)
+            // One band width away scores ±0.5
+            .with_score(-distance / (2.0 * band_width))
+            .with_side(side)
+            .with_factor(
+                DecisionFactor::new("vwap_distance", Some(distance), in_band)
+                    .with_threshold(band_threshold)
+                    .with_note(format!("VWAP {:.2}", vwap)),
+            )
+            .with_factor(
+                DecisionFactor::new("sentiment", Some(ctx.sentiment as f64), sentiment_ok)
+                    .with_threshold(gate_level as f64),
+            )
+    }
+
+    fn check_exit(&self, position: &Position, current_price: f64) -> Option<CloseReason> {
+        let back_at_vwap = self
+            .tradable_vwap()
+            .is_some_and(|vwap| match position.side {
+                OrderSide::Buy => current_price >= vwap && vwap > position.entry_price,
+                OrderSide::Sell => current_price <= vwap && vwap < position.entry_price,
+            });
+        if back_at_vwap {
+            return Some(CloseReason::TakeProfit);
+        }
+        self.core.check_position_exit(position, current_price)
+    }
+
+    fn take_profit(&self, entry_price: f64, side: OrderSide) -> f64 {
+        // The broker-side target is the VWAP when it is on the profitable side
+        let target = self.tradable_vwap().filter(|vwap| match side {
+            OrderSide::Buy => *vwap > entry_price,
+            OrderSide::Sell => *vwap < entry_price,
+        });
+        target.unwrap_or_else(|| self.core.calculate_take_profit(entry_price, side))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::trading::TimeFrame;
+    use chrono::TimeZone;
+
+    fn candle(at: DateTime<Utc>, close: f64) -> Candle {
+        Candle {
+            timestamp: at,
+            timeframe: TimeFrame::H1,
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 0,
+            avg_spread: None,
+            close_spread: None,
+        }
+    }
+
+    fn strategy() -> VwapReversion {
+        let config = Config::default();
+        let core = TradingStrategy::new(config.strategy, config.trading, 10000.0);
+        VwapReversion::new(
+            core,
+            VwapReversionConfig {
+                min_session_candles: 4,
+                ..VwapReversionConfig::default()
+            },
+        )
+    }
+
+    fn signal(strategy: &mut VwapReversion, bar: &Candle, sentiment: i32) -> Signal {
+        strategy.on_candle(bar);
+        strategy
+            .generate_signal(&SignalContext {
+                candle: bar,
+                rsi: 50.0,
+                sentiment,
+                sentiment_confidence: 0.8,
+                sentiment_dispersion: 0.0,
+                higher_rsi: None,
+                fundamental_bias: None,
+            })
+            .signal
+    }
+
+    #[test]
+    fn test_session_vwap_resets_at_session_start() {
+        let mut strategy = strategy();
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 20, 0, 0).unwrap();
+        for (i, close) in [4800.0, 4810.0, 4820.0].into_iter().enumerate() {
+            strategy.on_candle(&candle(start + Duration::hours(i as i64), close));
+        }
+        assert_eq!(strategy.session().candles(), 3);
+        assert!((strategy.session().vwap().unwrap() - 4810.0).abs() < 1e-9);
+        assert!(strategy.session().deviation().unwrap() > 0.0);
+
+        // Midnight UTC starts a new session
+        strategy.on_candle(&candle(start + Duration::hours(4), 4900.0));
+        assert_eq!(strategy.session().candles(), 1);
+        assert_eq!(strategy.session().vwap(), Some(4900.0));
+
+        assert!(VwapReversionConfig {
+            session_start_hour: 24,
+            ..VwapReversionConfig::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_fades_band_breaks_unless_sentiment_disagrees() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 1, 0, 0).unwrap();
+        let at = |i: usize| start + Duration::hours(i as i64);
+        let quiet = [
+            4800.0, 4802.0, 4798.0, 4801.0, 4799.0, 4800.0, 4802.0, 4798.0,
+        ];
+        let mut strategy = strategy();
+        let mut bearish = strategy();
+        for (i, close) in quiet.into_iter().enumerate() {
+            assert_eq!(
+                signal(&mut strategy, &candle(at(i), close), 0),
+                Signal::Hold
+            );
+            bearish.on_candle(&candle(at(i), close));
+        }
+
+        // A flush well below the VWAP is bought, unless sentiment is bearish
+        let flush = candle(at(quiet.len()), 4770.0);
+        assert_eq!(signal(&mut bearish, &flush, -60), Signal::Hold);
+        assert_eq!(signal(&mut strategy, &flush, 10), Signal::Buy);
+
+        // Targets and exits sit at the VWAP, above the entry
+        let vwap = strategy.session().vwap().unwrap();
+        assert!(vwap > 4770.0);
+        assert_eq!(strategy.take_profit(4770.0, OrderSide::Buy), vwap);
+        let long = Position::new("long", "FCPO", OrderSide::Buy, 4770.0, 1.0);
+        assert_eq!(strategy.check_exit(&long, vwap - 5.0), None);
+        assert_eq!(
+            strategy.check_exit(&long, vwap),
+            Some(CloseReason::TakeProfit)
+        );
+        assert_eq!(strategy.name(), "vwap_reversion");
+    }
+}
@@ -0,0 +1,355 @@
+//! Exchange trading sessions and holidays
+//!
+//! FCPO trades on Bursa Malaysia Derivatives in fixed sessions (Malaysia
+//! time, UTC+8): 10:30-12:30 and 14:30-18:00 on weekdays, plus a night
+//! session 21:00-23:30 Monday to Thursday. Outside them the broker quotes a
+//! stale or very wide price, so signals there are not worth taking.
+//!
+//! With `MARKET_HOURS_ENABLED` the strategy refuses new entries while the
+//! symbol's market is closed; open positions are still managed. Per symbol
+//! (upper-cased, e.g. `FCPO`):
+//! - `MARKET_SESSIONS_<SYMBOL>`: sessions separated by `;`, each a day range
+//!   and a time range in exchange time, e.g. `Mon-Fri 10:30-12:30`. A session
+//!   ending before it starts runs past midnight into the next day.
+//! - `MARKET_UTC_OFFSET_<SYMBOL>`: exchange time zone, e.g. `+08:00`
+//! - `MARKET_HOLIDAYS_<SYMBOL>`: closed dates, `YYYY-MM-DD` separated by `,`
+//!
+//! FCPO has built-in Bursa sessions; other symbols need
+//! `MARKET_SESSIONS_<SYMBOL>`.
+
+use std::collections::HashSet;
+use std::env;
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday};
+use tracing::warn;
+
+use crate::error::{BotError, Result};
+
+/// Bursa Malaysia FCPO sessions, Malaysia time
+const BURSA_FCPO_SESSIONS: &str = "Mon-Fri 10:30-12:30; Mon-Fri 14:30-18:00; Mon-Thu 21:00-23:30";
+const BURSA_UTC_OFFSET: &str = "+08:00";
+
+/// One trading session, repeated on `days`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// Days the session starts on
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    /// End, exclusive; at or before `start` means the next day
+    pub end: NaiveTime,
+}
+
+impl Session {
+    fn wraps_midnight(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// Whether the session started on `day` covers `time` that day (or, for
+    /// a session past midnight, the next morning when `next_day`)
+    fn covers(&self, time: NaiveTime, next_day: bool) -> bool {
+        match (self.wraps_midnight(), next_day) {
+            (false, false) => time >= self.start && time < self.end,
+            (false, true) => false,
+            (true, false) => time >= self.start,
+            (true, true) => time < self.end,
+        }
+    }
+}
+
+/// Trading sessions and holidays of one exchange
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketCalendar {
+    utc_offset: FixedOffset,
+    sessions: Vec<Session>,
+    holidays: HashSet<NaiveDate>,
+}
+
+impl MarketCalendar {
+    pub fn new(utc_offset: FixedOffset, sessions: Vec<Session>) -> Self {
+        Self {
+            utc_offset,
+            sessions,
+            holidays: HashSet::new(),
+        }
+    }
+
+    /// Bursa Malaysia Derivatives FCPO sessions, without holidays
+    pub fn bursa_fcpo() -> Self {
+        let offset = parse_offset(BURSA_UTC_OFFSET).expect("valid Bursa offset");
+        let sessions = parse_sessions(BURSA_FCPO_SESSIONS).expect("valid Bursa sessions");
+        Self::new(offset, sessions)
+    }
+
+    /// Calendar for `symbol` when `MARKET_HOURS_ENABLED` is set; `None` when
+    /// disabled, or when the symbol has no built-in or configured sessions
+    pub fn from_env(symbol: &str) -> Result<Option<Self>> {
+        let enabled = env::var("MARKET_HOURS_ENABLED")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        let key = symbol.to_uppercase();
+        let var = |name: &str| {
+            env::var(format!("{}_{}", name, key))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        };
+        let builtin = key == "FCPO";
+
+        let sessions = match var("MARKET_SESSIONS") {
+            Some(raw) => parse_sessions(&raw)?,
+            None if builtin => parse_sessions(BURSA_FCPO_SESSIONS)?,
+            None => {
+                warn!(
+                    "MARKET_HOURS_ENABLED but no MARKET_SESSIONS_{} set; trading {} at all hours",
+                    key, symbol
+                );
+                return Ok(None);
+            }
+        };
+        let offset = match var("MARKET_UTC_OFFSET") {
+            Some(raw) => parse_offset(&raw)?,
+            None if builtin => parse_offset(BURSA_UTC_OFFSET)?,
+            None => FixedOffset::east_opt(0).expect("UTC offset"),
+        };
+        let mut calendar = Self::new(offset, sessions);
+        if let Some(raw) = var("MARKET_HOLIDAYS") {
+            calendar = calendar.with_holidays(parse_holidays(&raw)?);
+        }
+        Ok(Some(calendar))
+    }
+
+    /// Add dates (exchange time) on which no session opens
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+
+    /// Whether a session is open at `at`
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.utc_offset).naive_local();
+        let (today, time) = (local.date(), local.time());
+        let yesterday = today - Duration::days(1);
+
+        self.sessions.iter().any(|session| {
+            let open_on =
+                |day: NaiveDate| session.days.contains(&day.weekday()) && !self.is_holiday(day);
+            (open_on(today) && session.covers(time, false))
+                || (open_on(yesterday) && session.covers(time, true))
+        })
+    }
+
+    /// Start of the next session after `at`, looking up to two weeks ahead
+    pub fn next_open(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local_date = at.with_timezone(&self.utc_offset).date_naive();
+        (0..14)
+            .map(|days| local_date + Duration::days(days))
+            .filter(|day| !self.is_holiday(*day))
+            .flat_map(|day| {
+                self.sessions
+                    .iter()
+                    .filter(move |s| s.days.contains(&day.weekday()))
+                    .filter_map(move |s| {
+                        day.and_time(s.start)
+                            .and_local_timezone(self.utc_offset)
+                            .single()
+                    })
+            })
+            .map(|start| start.with_timezone(&Utc))
+            .filter(|start| *start > at)
+            .min()
+    }
+
+    /// Sessions, offset and holidays as stable text, for the strategy
+    /// fingerprint (holidays sorted, the set has no order)
+    pub fn canonical(&self) -> String {
+        let sessions: Vec<String> = self
+            .sessions
+            .iter()
+            .map(|session| {
+                let days: Vec<String> = session
+                    .days
+                    .iter()
+                    .map(|day| format!("{:?}", day))
+                    .collect();
+                format!(
+                    "{} {}-{}",
+                    days.join(","),
+                    session.start.format("%H:%M"),
+                    session.end.format("%H:%M")
+                )
+            })
+            .collect();
+        let mut holidays: Vec<NaiveDate> = self.holidays.iter().copied().collect();
+        holidays.sort();
+        let holidays: Vec<String> = holidays.iter().map(|day| day.to_string()).collect();
+        format!(
+            "{} {};{}",
+            self.utc_offset,
+            sessions.join(";"),
+            holidays.join(",")
+        )
+    }
+}
+
+/// Parse `;`-separated sessions such as `Mon-Fri 10:30-12:30`
+pub fn parse_sessions(raw: &str) -> Result<Vec<Session>> {
+    let sessions = raw
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(parse_session)
+        .collect::<Result<Vec<_>>>()?;
+    if sessions.is_empty() {
+        return Err(BotError::Config("market sessions are empty".into()));
+    }
+    Ok(sessions)
+}
+
+fn parse_session(raw: &str) -> Result<Session> {
+    let invalid = || {
+        BotError::Config(format!(
+            "invalid market session {:?}, expected e.g. \"Mon-Fri 10:30-12:30\"",
+            raw
+        ))
+    };
+    let (days, times) = raw.split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let (start, end) = times.trim().split_once('-').ok_or_else(invalid)?;
+    let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").map_err(|_| invalid());
+    let weekday = |d: &str| d.trim().parse::<Weekday>().map_err(|_| invalid());
+
+    let days = match days.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (weekday(first)?, weekday(last)?);
+            let mut days = vec![first];
+            let mut day = first;
+            while day != last {
+                day = day.succ();
+                days.push(day);
+            }
+            days
+        }
+        None => vec![weekday(days)?],
+    };
+    Ok(Session {
+        days,
+        start: time(start)?,
+        end: time(end)?,
+    })
+}
+
+/// Parse a UTC offset such as `+08:00`
+pub fn parse_offset(raw: &str) -> Result<FixedOffset> {
+    raw.trim().parse::<FixedOffset>().map_err(|_| {
+        BotError::Config(format!(
+            "invalid UTC offset {:?}, expected e.g. +08:00",
+            raw
+        ))
+    })
+}
+
+/// Parse `,`-separated `YYYY-MM-DD` dates
+pub fn parse_holidays(raw: &str) -> Result<Vec<NaiveDate>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .map_err(|_| BotError::Config(format!("invalid market holiday {:?}", d)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Malaysia time (UTC+8) as UTC
+    fn myt(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(8 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_bursa_fcpo_sessions() {
+        let calendar = MarketCalendar::bursa_fcpo();
+        // 2026-10-14 is a Wednesday
+        assert!(!calendar.is_open(myt(2026, 10, 14, 10, 29)));
+        assert!(calendar.is_open(myt(2026, 10, 14, 10, 30)));
+        assert!(!calendar.is_open(myt(2026, 10, 14, 12, 30)));
+        assert!(calendar.is_open(myt(2026, 10, 14, 15, 0)));
+        assert!(calendar.is_open(myt(2026, 10, 14, 22, 0)));
+        // No night session on Friday, nothing at the weekend
+        assert!(calendar.is_open(myt(2026, 10, 16, 17, 59)));
+        assert!(!calendar.is_open(myt(2026, 10, 16, 22, 0)));
+        assert!(!calendar.is_open(myt(2026, 10, 17, 11, 0)));
+
+        assert_eq!(
+            calendar.next_open(myt(2026, 10, 16, 19, 0)),
+            Some(myt(2026, 10, 19, 10, 30))
+        );
+    }
+
+    #[test]
+    fn test_holidays_close_the_market() {
+        let holiday = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let calendar = MarketCalendar::bursa_fcpo().with_holidays([holiday]);
+        assert!(!calendar.is_open(myt(2026, 10, 14, 11, 0)));
+        assert!(calendar.is_open(myt(2026, 10, 15, 11, 0)));
+        assert_eq!(
+            calendar.next_open(myt(2026, 10, 13, 23, 45)),
+            Some(myt(2026, 10, 15, 10, 30))
+        );
+    }
+
+    #[test]
+    fn test_session_past_midnight() {
+        let sessions = parse_sessions("Sun-Thu 22:00-02:00").unwrap();
+        assert_eq!(sessions[0].days.len(), 5);
+        let calendar = MarketCalendar::new(FixedOffset::east_opt(0).unwrap(), sessions);
+        // 2026-10-15 is a Thursday
+        let utc = |d, h| Utc.with_ymd_and_hms(2026, 10, d, h, 0, 0).unwrap();
+        assert!(calendar.is_open(utc(15, 23)));
+        assert!(calendar.is_open(utc(16, 1)));
+        assert!(!calendar.is_open(utc(16, 2)));
+        assert!(!calendar.is_open(utc(16, 23)));
+    }
+
+    #[test]
+    fn test_invalid_configuration() {
+        assert!(parse_sessions("").is_err());
+        assert!(parse_sessions("Mon-Fri").is_err());
+        assert!(parse_sessions("Mon-Fri 25:00-26:00").is_err());
+        assert!(parse_sessions("Someday 10:00-11:00").is_err());
+        assert!(parse_offset("MYT").is_err());
+        assert!(parse_holidays("2026-02-30").is_err());
+        assert_eq!(parse_holidays(" 2026-01-01, ").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_canonical_is_stable() {
+        let first = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let second = NaiveDate::from_ymd_opt(2026, 2, 17).unwrap();
+        let forward = MarketCalendar::bursa_fcpo().with_holidays([first, second]);
+        let backward = MarketCalendar::bursa_fcpo().with_holidays([second, first]);
+        assert_eq!(forward.canonical(), backward.canonical());
+        assert_ne!(
+            forward.canonical(),
+            MarketCalendar::bursa_fcpo().canonical()
+        );
+    }
+}
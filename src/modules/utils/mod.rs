@@ -4,10 +4,16 @@
 //! - Retry logic with exponential backoff
 //! - Price and percentage formatting
 //! - Time utilities
+//! - Account-currency money math in integer minor units
+//! - Exchange trading sessions and holidays
 
 pub mod helpers;
+pub mod market_calendar;
+pub mod money;
 
 pub use helpers::{
     format_currency, format_percentage, format_price, format_timestamp, retry_with_backoff,
     RetryConfig,
 };
+pub use market_calendar::{MarketCalendar, Session};
+pub use money::{account_digits, round_money, Money};
@@ -0,0 +1,174 @@
+//! Account-currency amounts in integer minor units
+//!
+//! Balances and P&L used to be plain `f64` sums, so every closed trade added
+//! a little binary rounding error to the running balance and to the daily
+//! P&L the circuit breaker reads. [`Money`] stores an amount as whole minor
+//! units (cents for a 2-digit currency): an amount is rounded once, when it
+//! enters from broker or price arithmetic, and all sums after that are exact.
+//!
+//! The number of decimals comes from `ACCOUNT_CURRENCY_DIGITS` (default 2,
+//! at most 8); set it to 0 for currencies such as JPY. It is read once per
+//! process, so every amount shares the same scale.
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::sync::OnceLock;
+
+const DEFAULT_DIGITS: u32 = 2;
+const MAX_DIGITS: u32 = 8;
+
+/// Decimals of the account currency (`ACCOUNT_CURRENCY_DIGITS`, default 2)
+pub fn account_digits() -> u32 {
+    static DIGITS: OnceLock<u32> = OnceLock::new();
+    *DIGITS.get_or_init(|| {
+        std::env::var("ACCOUNT_CURRENCY_DIGITS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|d| *d <= MAX_DIGITS)
+            .unwrap_or(DEFAULT_DIGITS)
+    })
+}
+
+/// Round `amount` to the account currency's decimals
+pub fn round_money(amount: f64) -> f64 {
+    Money::from_f64(amount).to_f64()
+}
+
+/// An amount of account currency, in minor units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_minor(minor: i64) -> Self {
+        Self(minor)
+    }
+
+    /// Round `amount` half away from zero to the account currency's
+    /// decimals; NaN becomes zero
+    pub fn from_f64(amount: f64) -> Self {
+        Self(to_minor(amount, account_digits()))
+    }
+
+    pub fn minor(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / scale(account_digits())
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+}
+
+fn scale(digits: u32) -> f64 {
+    10_f64.powi(digits as i32)
+}
+
+fn to_minor(amount: f64, digits: u32) -> i64 {
+    if amount.is_nan() {
+        return 0;
+    }
+    // `as` saturates at the i64 bounds
+    (amount * scale(digits)).round() as i64
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.saturating_neg())
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.*}", account_digits() as usize, self.to_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_half_away_from_zero() {
+        assert_eq!(to_minor(0.125, 2), 13);
+        assert_eq!(to_minor(-0.125, 2), -13);
+        assert_eq!(to_minor(12.344, 2), 1234);
+        assert_eq!(to_minor(1_234.5, 0), 1235);
+        assert_eq!(to_minor(f64::NAN, 2), 0);
+        assert_eq!(to_minor(f64::INFINITY, 2), i64::MAX);
+    }
+
+    #[test]
+    fn test_sums_do_not_drift() {
+        // 0.1 has no exact binary form: ten thousand f64 additions drift
+        let float: f64 = (0..10_000).map(|_| 0.1).sum();
+        assert_ne!(float, 1_000.0);
+
+        let money: Money = (0..10_000).map(|_| Money::from_minor(10)).sum();
+        assert_eq!(money, Money::from_minor(100_000));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let balance = Money::from_minor(1_000_000);
+        let loss = Money::from_minor(-15_250);
+        assert_eq!(balance + loss, Money::from_minor(984_750));
+        assert_eq!(balance - loss, Money::from_minor(1_015_250));
+        assert_eq!(-loss, Money::from_minor(15_250));
+        assert!(loss.is_negative());
+        assert_eq!(loss.abs(), Money::from_minor(15_250));
+        assert_eq!(
+            Money::from_minor(i64::MAX) + Money::from_minor(1),
+            Money::from_minor(i64::MAX)
+        );
+    }
+}
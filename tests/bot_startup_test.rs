@@ -2,8 +2,11 @@
 
 use palm_oil_bot::bot::TradingBot;
 use palm_oil_bot::config::{
-    BotConfig, CTraderConfig, Config, PerplexityConfig, StrategyConfig, TradingConfig,
-    TradingEnvironment,
+    BotConfig, CTraderConfig, Config, DecisionConfig, LlmBackend, PerplexityConfig,
+    PromptTemplates, RsiExitMode, SizingPolicy, StrategyConfig, TradingConfig, TradingEnvironment,
+};
+use palm_oil_bot::modules::trading::{
+    OppositeSignalPolicy, StrategyKind, TrendFollowingConfig, VwapReversionConfig,
 };
 
 fn test_config_without_token() -> Config {
@@ -21,9 +24,13 @@ fn test_config_without_token() -> Config {
             account_id_live: None,
         },
         perplexity: PerplexityConfig {
+            backend: LlmBackend::Perplexity,
             api_key: "test_key".to_string(),
             endpoint: "https://api.perplexity.ai/chat/completions".to_string(),
             model: "sonar".to_string(),
+            temperature: 0.3,
+            max_tokens: 500,
+            prompts: PromptTemplates::default(),
         },
         trading: TradingConfig {
             symbol: "FCPO".to_string(),
@@ -33,6 +40,12 @@ fn test_config_without_token() -> Config {
             max_positions: 1,
             max_daily_loss_percent: 5.0,
                 initial_balance: 10000.0,
+                trailing_activation_percent: None,
+                atr_exits: None,
+                break_even: None,
+                scale_in: None,
+                entry_cooldown_candles: 0,
+                sizing: SizingPolicy::FixedRisk,
         },
         strategy: StrategyConfig {
             rsi_period: 14,
@@ -40,6 +53,13 @@ fn test_config_without_token() -> Config {
             rsi_overbought: 70.0,
             rsi_timeframe: "5m".to_string(),
             sentiment_threshold: 30,
+            rsi_exit: RsiExitMode::Off,
+            macd_confirmation: false,
+            higher_timeframe: None,
+            opposite_signal_policy: OppositeSignalPolicy::Ignore,
+            kind: StrategyKind::RsiSentiment,
+            vwap: VwapReversionConfig::default(),
+            trend: TrendFollowingConfig::default(),
         },
         kols: vec!["PalmOilTrader".to_string()],
         bot: BotConfig {
@@ -47,6 +67,7 @@ fn test_config_without_token() -> Config {
             dry_run: true,
             log_level: "info".to_string(),
         },
+        decision: DecisionConfig::default(),
     }
 }
 
@@ -1,5 +1,8 @@
-use palm_oil_bot::config::{StrategyConfig, TradingConfig};
-use palm_oil_bot::modules::trading::{CircuitBreakers, TradingStrategy, OrderSide, CloseReason, Position};
+use palm_oil_bot::config::{RsiExitMode, SizingPolicy, StrategyConfig, TradingConfig};
+use palm_oil_bot::modules::trading::{
+    CircuitBreakers, CloseReason, OppositeSignalPolicy, OrderSide, Position, StrategyKind,
+    TradingStrategy, TrendFollowingConfig, VwapReversionConfig,
+};
 use palm_oil_bot::modules::trading::circuit_breakers::CircuitBreakerConfig;
 
 #[test]
@@ -179,6 +182,13 @@ fn create_test_strategy() -> TradingStrategy {
         rsi_overbought: 70.0,
         rsi_timeframe: "1H".to_string(),
         sentiment_threshold: 30,
+        rsi_exit: RsiExitMode::Off,
+        macd_confirmation: false,
+        higher_timeframe: None,
+        opposite_signal_policy: OppositeSignalPolicy::Ignore,
+        kind: StrategyKind::RsiSentiment,
+        vwap: VwapReversionConfig::default(),
+        trend: TrendFollowingConfig::default(),
     };
 
     let trading_config = TradingConfig {
@@ -189,6 +199,12 @@ fn create_test_strategy() -> TradingStrategy {
         stop_loss_percent: 1.5,
         max_daily_loss_percent: 5.0,
         initial_balance: 10000.0,
+        trailing_activation_percent: None,
+        atr_exits: None,
+        break_even: None,
+        scale_in: None,
+        entry_cooldown_candles: 0,
+        sizing: SizingPolicy::FixedRisk,
     };
 
     TradingStrategy::new(strategy_config, trading_config, 10000.0)
@@ -4,10 +4,13 @@
 //! Generates synthetic candles, calculates RSI, generates signals, simulates positions,
 //! and validates P&L and statistics.
 
-use palm_oil_bot::config::{StrategyConfig, TradingConfig};
+use palm_oil_bot::config::{RsiExitMode, SizingPolicy, StrategyConfig, TradingConfig};
 use palm_oil_bot::modules::trading::indicators::RsiCalculator;
 use palm_oil_bot::modules::trading::orders::{OrderSide, Position};
-use palm_oil_bot::modules::trading::strategy::{Signal, TradingStrategy};
+use palm_oil_bot::modules::trading::strategy::{
+    OppositeSignalPolicy, Signal, StrategyKind, TradingStrategy,
+};
+use palm_oil_bot::modules::trading::{TrendFollowingConfig, VwapReversionConfig};
 use rand::Rng;
 
 /// Synthetic candle data (OHLC)
@@ -169,6 +172,13 @@ fn test_dry_run_full_trading_loop() {
         rsi_overbought: 70.0,
         rsi_timeframe: "M5".to_string(),
         sentiment_threshold: 30,
+        rsi_exit: RsiExitMode::Off,
+        macd_confirmation: false,
+        higher_timeframe: None,
+        opposite_signal_policy: OppositeSignalPolicy::Ignore,
+        kind: StrategyKind::RsiSentiment,
+        vwap: VwapReversionConfig::default(),
+        trend: TrendFollowingConfig::default(),
     };
 
     let trading_config = TradingConfig {
@@ -179,6 +189,12 @@ fn test_dry_run_full_trading_loop() {
         stop_loss_percent: 1.5,
         max_daily_loss_percent: 5.0,
         initial_balance: 10000.0,
+        trailing_activation_percent: None,
+        atr_exits: None,
+        break_even: None,
+        scale_in: None,
+        entry_cooldown_candles: 0,
+        sizing: SizingPolicy::FixedRisk,
     };
 
     let starting_balance = 10000.0;
@@ -327,6 +343,13 @@ fn test_dry_run_with_circuit_breaker() {
         rsi_overbought: 70.0,
         rsi_timeframe: "M5".to_string(),
         sentiment_threshold: 30,
+        rsi_exit: RsiExitMode::Off,
+        macd_confirmation: false,
+        higher_timeframe: None,
+        opposite_signal_policy: OppositeSignalPolicy::Ignore,
+        kind: StrategyKind::RsiSentiment,
+        vwap: VwapReversionConfig::default(),
+        trend: TrendFollowingConfig::default(),
     };
 
     let trading_config = TradingConfig {
@@ -337,6 +360,12 @@ fn test_dry_run_with_circuit_breaker() {
         stop_loss_percent: 1.5,
         max_daily_loss_percent: 5.0,
         initial_balance: 10000.0,
+        trailing_activation_percent: None,
+        atr_exits: None,
+        break_even: None,
+        scale_in: None,
+        entry_cooldown_candles: 0,
+        sizing: SizingPolicy::FixedRisk,
     };
 
     let starting_balance = 10000.0;
@@ -372,6 +401,13 @@ fn test_dry_run_consecutive_losses() {
         rsi_overbought: 70.0,
         rsi_timeframe: "M5".to_string(),
         sentiment_threshold: 30,
+        rsi_exit: RsiExitMode::Off,
+        macd_confirmation: false,
+        higher_timeframe: None,
+        opposite_signal_policy: OppositeSignalPolicy::Ignore,
+        kind: StrategyKind::RsiSentiment,
+        vwap: VwapReversionConfig::default(),
+        trend: TrendFollowingConfig::default(),
     };
 
     let trading_config = TradingConfig {
@@ -382,6 +418,12 @@ fn test_dry_run_consecutive_losses() {
         stop_loss_percent: 1.5,
         max_daily_loss_percent: 5.0,
         initial_balance: 10000.0,
+        trailing_activation_percent: None,
+        atr_exits: None,
+        break_even: None,
+        scale_in: None,
+        entry_cooldown_candles: 0,
+        sizing: SizingPolicy::FixedRisk,
     };
 
     let starting_balance = 10000.0;
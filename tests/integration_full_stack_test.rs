@@ -10,10 +10,11 @@
 //! 7. Close position on take profit
 
 use palm_oil_bot::config::{
-    BotConfig, CTraderConfig, Config, PerplexityConfig, StrategyConfig, TradingConfig, TradingEnvironment,
+    BotConfig, CTraderConfig, Config, DecisionConfig, LlmBackend, PerplexityConfig, PromptTemplates, RsiExitMode, SizingPolicy, StrategyConfig, TradingConfig, TradingEnvironment,
 };
 use palm_oil_bot::modules::trading::{
-    CircuitBreakers, CloseReason, OrderSide, Position, RsiCalculator, Signal, TradingStrategy,
+    CircuitBreakers, CloseReason, OppositeSignalPolicy, OrderSide, Position, RsiCalculator, Signal,
+    StrategyKind, TradingStrategy, TrendFollowingConfig, VwapReversionConfig,
 };
 use palm_oil_bot::modules::trading::circuit_breakers::CircuitBreakerConfig;
 use palm_oil_bot::modules::trading::position_manager::{
@@ -38,9 +39,13 @@ fn create_test_config() -> Config {
             account_id_live: None,
         },
         perplexity: PerplexityConfig {
+            backend: LlmBackend::Perplexity,
             api_key: "test_perplexity_key".to_string(),
             endpoint: "https://api.perplexity.ai/chat/completions".to_string(),
             model: "sonar".to_string(),
+            temperature: 0.3,
+            max_tokens: 500,
+            prompts: PromptTemplates::default(),
         },
         trading: TradingConfig {
             symbol: "FCPO".to_string(),
@@ -50,6 +55,12 @@ fn create_test_config() -> Config {
             max_positions: 1,
             max_daily_loss_percent: 5.0,
                 initial_balance: 10000.0,
+                trailing_activation_percent: None,
+                atr_exits: None,
+                break_even: None,
+                scale_in: None,
+                entry_cooldown_candles: 0,
+                sizing: SizingPolicy::FixedRisk,
         },
         strategy: StrategyConfig {
             rsi_period: 14,
@@ -57,6 +68,13 @@ fn create_test_config() -> Config {
             rsi_overbought: 70.0,
             rsi_timeframe: "5m".to_string(),
             sentiment_threshold: 30,
+            rsi_exit: RsiExitMode::Off,
+            macd_confirmation: false,
+            higher_timeframe: None,
+            opposite_signal_policy: OppositeSignalPolicy::Ignore,
+            kind: StrategyKind::RsiSentiment,
+            vwap: VwapReversionConfig::default(),
+            trend: TrendFollowingConfig::default(),
         },
         kols: vec![
             "PalmOilTrader".to_string(),
@@ -67,6 +85,7 @@ fn create_test_config() -> Config {
             dry_run: true,
             log_level: "debug".to_string(),
         },
+        decision: DecisionConfig::default(),
     }
 }
 